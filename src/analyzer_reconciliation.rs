@@ -0,0 +1,392 @@
+use anyhow::{Context, Result};
+use polars::prelude::*;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+use crate::error::require_column;
+
+/// Default spread between analyzers before a resource-year category is
+/// flagged, in percent of the largest value
+pub const DEFAULT_TOLERANCE_PCT: f64 = 10.0;
+
+/// Differences smaller than this many dollars are never flagged
+const MIN_DIFFERENCE: f64 = 1.0;
+
+/// Discrepancies listed in the console summary
+const DISCREPANCIES_SHOWN: usize = 20;
+
+/// Revenue categories every analyzer output is mapped onto
+pub const CATEGORIES: [&str; 4] = ["DAM_Energy", "RT_Energy", "Ancillary", "Total"];
+
+/// How an output's rows are dated
+#[derive(Debug, Clone, Copy)]
+pub enum Period {
+    /// `YYYY-MM-DD` date column, summed to years
+    Daily(&'static str),
+    /// Integer year column
+    Annual(&'static str),
+}
+
+/// One analyzer's saved output and how its columns map onto `CATEGORIES`
+#[derive(Debug, Clone, Copy)]
+pub struct AnalyzerOutput {
+    pub analyzer: &'static str,
+    /// Relative to the directory the analyzers ran in
+    pub path: &'static str,
+    pub resource_column: &'static str,
+    pub period: Period,
+    pub dam_energy: &'static [&'static str],
+    pub rt_energy: &'static [&'static str],
+    pub ancillary: &'static [&'static str],
+    pub total: &'static str,
+    /// Where the analyzer's AS revenue comes from
+    pub ancillary_basis: &'static str,
+}
+
+pub const ANALYZER_OUTPUTS: [AnalyzerOutput; 3] = [
+    AnalyzerOutput {
+        analyzer: "revenue_calculator",
+        path: "bess_analysis/bess_daily_revenues.parquet",
+        resource_column: "Resource_Name",
+        period: Period::Daily("Date"),
+        dam_energy: &["DAM_Energy_Revenue"],
+        rt_energy: &["RT_Energy_Revenue"],
+        ancillary: &["RegUp_Revenue", "RegDown_Revenue", "RRS_Revenue", "ECRS_Revenue", "NonSpin_Revenue"],
+        total: "Total_Revenue",
+        ancillary_basis: "60-day DAM disclosure awards × MCPC",
+    },
+    AnalyzerOutput {
+        analyzer: "parquet_calculator",
+        path: "bess_analysis/bess_daily_revenues_parquet.parquet",
+        resource_column: "Resource_Name",
+        period: Period::Daily("Date"),
+        dam_energy: &["DAM_Energy_Revenue"],
+        rt_energy: &["RT_Energy_Revenue"],
+        ancillary: &["RegUp_Revenue", "RegDown_Revenue"],
+        total: "Total_Revenue",
+        ancillary_basis: "placeholder: 10% of capacity at fixed $5/$3 per MW",
+    },
+    AnalyzerOutput {
+        analyzer: "complete_analyzer",
+        path: "bess_complete_analysis/bess_annual_revenues_complete.parquet",
+        resource_column: "BESS_Asset_Name",
+        period: Period::Annual("Year"),
+        dam_energy: &["DA_Revenue"],
+        rt_energy: &["RT_Revenue"],
+        ancillary: &["RegUp_Revenue", "RegDown_Revenue", "Spin_Revenue", "NonSpin_Revenue", "ECRS_Revenue"],
+        total: "Total_Revenue",
+        ancillary_basis: "60-day DAM disclosure awards × MCPC",
+    },
+];
+
+/// Revenue per `CATEGORIES` entry for each (resource, year)
+pub type ResourceYearTotals = BTreeMap<(String, i32), [f64; 4]>;
+
+/// One resource-year category on which analyzers disagree
+#[derive(Debug, Clone)]
+pub struct Discrepancy {
+    pub resource_name: String,
+    pub year: i32,
+    pub category: &'static str,
+    /// Analyzer and its value, for every analyzer covering the resource-year
+    pub values: Vec<(&'static str, f64)>,
+    /// Largest minus smallest value
+    pub spread: f64,
+    pub spread_pct: f64,
+}
+
+/// Whether two values are more than `tolerance_pct` of the larger magnitude
+/// (and `MIN_DIFFERENCE` dollars) apart
+fn differs(a: f64, b: f64, tolerance_pct: f64) -> bool {
+    let difference = (a - b).abs();
+    difference > MIN_DIFFERENCE && difference > a.abs().max(b.abs()) * tolerance_pct / 100.0
+}
+
+fn summed(df: &DataFrame, columns: &[&str]) -> Result<Vec<f64>> {
+    let mut sums = vec![0.0; df.height()];
+    for column in columns {
+        let values = require_column(df, column)?.cast(&DataType::Float64)?;
+        for (sum, value) in sums.iter_mut().zip(values.f64()?.into_iter()) {
+            *sum += value.unwrap_or(0.0);
+        }
+    }
+    Ok(sums)
+}
+
+fn years(df: &DataFrame, period: Period) -> Result<Vec<Option<i32>>> {
+    Ok(match period {
+        Period::Daily(column) => require_column(df, column)?
+            .cast(&DataType::Utf8)?
+            .utf8()?
+            .into_iter()
+            .map(|date| date.and_then(|d| d.get(..4)).and_then(|y| y.parse().ok()))
+            .collect(),
+        Period::Annual(column) => require_column(df, column)?
+            .cast(&DataType::Int32)?
+            .i32()?
+            .into_iter()
+            .collect(),
+    })
+}
+
+/// Sum one analyzer's output into per resource-year category totals
+pub fn resource_year_totals(df: &DataFrame, output: &AnalyzerOutput) -> Result<ResourceYearTotals> {
+    let names = require_column(df, output.resource_column)?.cast(&DataType::Utf8)?;
+    let years = years(df, output.period)?;
+    let columns = [
+        summed(df, output.dam_energy)?,
+        summed(df, output.rt_energy)?,
+        summed(df, output.ancillary)?,
+        summed(df, &[output.total])?,
+    ];
+
+    let mut totals = ResourceYearTotals::new();
+    for (i, (name, year)) in names.utf8()?.into_iter().zip(years).enumerate() {
+        let (Some(name), Some(year)) = (name, year) else {
+            continue;
+        };
+        let entry = totals.entry((name.to_string(), year)).or_insert([0.0; 4]);
+        for (total, column) in entry.iter_mut().zip(columns.iter()) {
+            *total += column[i];
+        }
+    }
+    Ok(totals)
+}
+
+/// Resource-year categories covered by two or more analyzers whose values
+/// spread by more than `tolerance_pct` of the largest magnitude, largest
+/// spread first
+pub fn find_discrepancies(totals: &[(&'static str, ResourceYearTotals)], tolerance_pct: f64) -> Vec<Discrepancy> {
+    let keys: BTreeSet<&(String, i32)> = totals.iter().flat_map(|(_, t)| t.keys()).collect();
+
+    let mut discrepancies = Vec::new();
+    for key in keys {
+        for (c, category) in CATEGORIES.iter().enumerate() {
+            let values: Vec<(&'static str, f64)> = totals
+                .iter()
+                .filter_map(|(analyzer, t)| t.get(key).map(|v| (*analyzer, v[c])))
+                .collect();
+            if values.len() < 2 {
+                continue;
+            }
+
+            let max = values.iter().map(|(_, v)| *v).fold(f64::NEG_INFINITY, f64::max);
+            let min = values.iter().map(|(_, v)| *v).fold(f64::INFINITY, f64::min);
+            if differs(max, min, tolerance_pct) {
+                discrepancies.push(Discrepancy {
+                    resource_name: key.0.clone(),
+                    year: key.1,
+                    category,
+                    values,
+                    spread: max - min,
+                    spread_pct: (max - min) / max.abs().max(min.abs()) * 100.0,
+                });
+            }
+        }
+    }
+
+    discrepancies.sort_by(|a, b| b.spread.partial_cmp(&a.spread).unwrap_or(std::cmp::Ordering::Equal));
+    discrepancies
+}
+
+fn save_discrepancies(discrepancies: &[Discrepancy], analyzers: &[&str], output_path: &Path) -> Result<()> {
+    let mut columns = vec![
+        Series::new("Resource_Name", discrepancies.iter().map(|d| d.resource_name.clone()).collect::<Vec<_>>()),
+        Series::new("Year", discrepancies.iter().map(|d| d.year).collect::<Vec<_>>()),
+        Series::new("Category", discrepancies.iter().map(|d| d.category).collect::<Vec<_>>()),
+    ];
+    for analyzer in analyzers {
+        columns.push(Series::new(analyzer, discrepancies.iter()
+            .map(|d| d.values.iter().find(|(a, _)| a == analyzer).map(|(_, v)| *v))
+            .collect::<Vec<_>>()));
+    }
+    columns.push(Series::new("Spread", discrepancies.iter().map(|d| d.spread).collect::<Vec<_>>()));
+    columns.push(Series::new("Spread_Pct", discrepancies.iter().map(|d| d.spread_pct).collect::<Vec<_>>()));
+
+    let mut df = DataFrame::new(columns)?;
+    CsvWriter::new(std::fs::File::create(output_path)?)
+        .finish(&mut df)?;
+    Ok(())
+}
+
+/// Load each analyzer's output found under `base_dir`, compare their revenue
+/// per resource-year and category, and write the disagreements to
+/// `bess_analysis/analyzer_reconciliation.csv`
+pub fn reconcile_analyzers(base_dir: &Path, tolerance_pct: f64) -> Result<Vec<Discrepancy>> {
+    println!("🔍 Analyzer Reconciliation");
+    println!("{}", "=".repeat(80));
+
+    let mut loaded = Vec::new();
+    let mut totals = Vec::new();
+    for output in &ANALYZER_OUTPUTS {
+        let path = base_dir.join(output.path);
+        if !path.exists() {
+            println!("  ⏭️  {}: {} not found", output.analyzer, output.path);
+            continue;
+        }
+        let df = ParquetReader::new(std::fs::File::open(&path)
+            .with_context(|| format!("Failed to open {}", path.display()))?)
+            .finish()?;
+        let output_totals = resource_year_totals(&df, output)
+            .with_context(|| format!("Failed to read {} output {}", output.analyzer, path.display()))?;
+        println!("  ✅ {}: {} resource-years (AS: {})", output.analyzer, output_totals.len(), output.ancillary_basis);
+        loaded.push(output);
+        totals.push((output.analyzer, output_totals));
+    }
+
+    if totals.len() < 2 {
+        println!("\nNeed outputs from at least two analyzers to reconcile");
+        return Ok(Vec::new());
+    }
+
+    let discrepancies = find_discrepancies(&totals, tolerance_pct);
+
+    // Disagreement counts per category and analyzer pair
+    println!("\n📊 Resource-years differing by more than {}%:", tolerance_pct);
+    println!("{:<12} {:<42} {:>10} {:>10}", "Category", "Analyzers", "Differ", "Shared");
+    println!("{}", "-".repeat(77));
+    for category in CATEGORIES {
+        for (i, (a, totals_a)) in totals.iter().enumerate() {
+            for (b, totals_b) in totals.iter().skip(i + 1) {
+                let shared = totals_a.keys().filter(|k| totals_b.contains_key(*k)).count();
+                let differ = discrepancies.iter()
+                    .filter(|d| d.category == category)
+                    .filter(|d| {
+                        let value = |analyzer: &str| d.values.iter().find(|(x, _)| *x == analyzer).map(|(_, v)| *v);
+                        matches!((value(a), value(b)), (Some(va), Some(vb)) if differs(va, vb, tolerance_pct))
+                    })
+                    .count();
+                println!("{:<12} {:<42} {:>10} {:>10}{}", category, format!("{} vs {}", a, b), differ, shared,
+                         if differ > 0 { "  ⚠️" } else { "" });
+            }
+        }
+    }
+
+    let placeholder: Vec<&AnalyzerOutput> = loaded.iter()
+        .copied()
+        .filter(|o| o.ancillary_basis.starts_with("placeholder"))
+        .collect();
+    for output in placeholder {
+        let differ = discrepancies.iter()
+            .filter(|d| d.category == "Ancillary" && d.values.iter().any(|(a, _)| *a == output.analyzer))
+            .count();
+        if differ > 0 {
+            println!("\n⚠️  {} AS revenue is a {}; it disagrees with disclosure-based AS on {} resource-years",
+                     output.analyzer, output.ancillary_basis, differ);
+        }
+    }
+
+    if !discrepancies.is_empty() {
+        println!("\n🔝 Largest discrepancies:");
+        for d in discrepancies.iter().take(DISCREPANCIES_SHOWN) {
+            let values: Vec<String> = d.values.iter().map(|(a, v)| format!("{}=${:.0}", a, v)).collect();
+            println!("  {} {} {}: {} (spread ${:.0}, {:.0}%)",
+                     d.resource_name, d.year, d.category, values.join(", "), d.spread, d.spread_pct);
+        }
+    }
+
+    let output_dir = base_dir.join("bess_analysis");
+    std::fs::create_dir_all(&output_dir)?;
+    let output_path = output_dir.join("analyzer_reconciliation.csv");
+    let analyzers: Vec<&str> = totals.iter().map(|(a, _)| *a).collect();
+    save_discrepancies(&discrepancies, &analyzers, &output_path)?;
+
+    if discrepancies.is_empty() {
+        println!("\n✅ Analyzers agree within {}%", tolerance_pct);
+    } else {
+        println!("\n⚠️  {} discrepancies saved to: {}", discrepancies.len(), output_path.display());
+    }
+
+    Ok(discrepancies)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_parquet(base: &Path, relative: &str, mut df: DataFrame) {
+        let path = base.join(relative);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        ParquetWriter::new(std::fs::File::create(&path).unwrap()).finish(&mut df).unwrap();
+    }
+
+    #[test]
+    fn test_placeholder_as_is_flagged_against_disclosure_as() {
+        let dir = tempfile::tempdir().unwrap();
+
+        // Same energy revenue everywhere; disclosure-based AS of $2,000/day
+        // against the parquet calculator's fixed $80/day placeholder
+        write_parquet(dir.path(), ANALYZER_OUTPUTS[0].path, df! {
+            "Resource_Name" => ["BATCAVE_BES1", "BATCAVE_BES1"],
+            "Date" => ["2024-01-01", "2024-01-02"],
+            "Energy_Revenue" => [1000.0, 1000.0],
+            "DAM_Energy_Revenue" => [600.0, 600.0],
+            "RT_Energy_Revenue" => [400.0, 400.0],
+            "RegUp_Revenue" => [1500.0, 1500.0],
+            "RegDown_Revenue" => [500.0, 500.0],
+            "RRS_Revenue" => [0.0, 0.0],
+            "ECRS_Revenue" => [0.0, 0.0],
+            "NonSpin_Revenue" => [0.0, 0.0],
+            "Total_Revenue" => [3000.0, 3000.0],
+        }.unwrap());
+        write_parquet(dir.path(), ANALYZER_OUTPUTS[1].path, df! {
+            "Resource_Name" => ["BATCAVE_BES1", "BATCAVE_BES1"],
+            "Date" => ["2024-01-01", "2024-01-02"],
+            "Energy_Revenue" => [1000.0, 1000.0],
+            "DAM_Energy_Revenue" => [600.0, 600.0],
+            "RT_Energy_Revenue" => [400.0, 400.0],
+            "RegUp_Revenue" => [50.0, 50.0],
+            "RegDown_Revenue" => [30.0, 30.0],
+            "Total_Revenue" => [1080.0, 1080.0],
+        }.unwrap());
+
+        let discrepancies = reconcile_analyzers(dir.path(), DEFAULT_TOLERANCE_PCT).unwrap();
+        let mut categories: Vec<&str> = discrepancies.iter().map(|d| d.category).collect();
+        categories.sort();
+        assert_eq!(categories, vec!["Ancillary", "Total"]);
+
+        let ancillary = discrepancies.iter().find(|d| d.category == "Ancillary").unwrap();
+        assert_eq!((ancillary.resource_name.as_str(), ancillary.year), ("BATCAVE_BES1", 2024));
+        assert_eq!(ancillary.values, vec![("revenue_calculator", 4000.0), ("parquet_calculator", 160.0)]);
+        assert!((ancillary.spread_pct - 96.0).abs() < 1e-9);
+
+        assert!(dir.path().join("bess_analysis/analyzer_reconciliation.csv").exists());
+    }
+
+    #[test]
+    fn test_annual_output_compares_with_daily_sums() {
+        let daily = df! {
+            "Resource_Name" => ["BESS_A", "BESS_A", "BESS_A"],
+            "Date" => ["2023-12-31", "2024-01-01", "2024-06-30"],
+            "DAM_Energy_Revenue" => [100.0, 200.0, 300.0],
+            "RT_Energy_Revenue" => [0.0, 0.0, 0.0],
+            "RegUp_Revenue" => [0.0, 0.0, 0.0],
+            "RegDown_Revenue" => [0.0, 0.0, 0.0],
+            "Total_Revenue" => [100.0, 200.0, 300.0],
+        }.unwrap();
+        let annual = df! {
+            "BESS_Asset_Name" => ["BESS_A", "BESS_A"],
+            "Year" => [2023i32, 2024],
+            "DA_Revenue" => [100.0, 520.0],
+            "RT_Revenue" => [0.0, 0.0],
+            "RegUp_Revenue" => [0.0, 0.0],
+            "RegDown_Revenue" => [0.0, 0.0],
+            "Spin_Revenue" => [0.0, 0.0],
+            "NonSpin_Revenue" => [0.0, 0.0],
+            "ECRS_Revenue" => [0.0, 0.0],
+            "Total_Revenue" => [100.0, 520.0],
+        }.unwrap();
+
+        let totals = vec![
+            ("parquet_calculator", resource_year_totals(&daily, &ANALYZER_OUTPUTS[1]).unwrap()),
+            ("complete_analyzer", resource_year_totals(&annual, &ANALYZER_OUTPUTS[2]).unwrap()),
+        ];
+        assert_eq!(totals[0].1[&("BESS_A".to_string(), 2024)][0], 500.0);
+
+        // 2023 agrees; 2024 is 4% apart, flagged only under a tighter tolerance
+        assert!(find_discrepancies(&totals, DEFAULT_TOLERANCE_PCT).is_empty());
+        let tight = find_discrepancies(&totals, 2.0);
+        assert_eq!(tight.len(), 2);
+        assert!(tight.iter().all(|d| d.year == 2024));
+    }
+}
@@ -1,4 +1,5 @@
 use anyhow::Result;
+use log::{debug, info, warn};
 use glob::glob;
 use indicatif::{ProgressBar, ProgressStyle};
 use polars::prelude::*;
@@ -6,9 +7,15 @@ use rayon::prelude::*;
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
+use crate::output_formats::OutputFormats;
+use crate::csv_headers::read_csv;
+use crate::output_prefix::OutputPrefix;
+use crate::run_metadata;
+
 pub struct AncillaryProcessor {
     base_dir: PathBuf,
     output_dir: PathBuf,
+    formats: OutputFormats,
 }
 
 impl AncillaryProcessor {
@@ -18,12 +25,18 @@ impl AncillaryProcessor {
         Self {
             base_dir,
             output_dir,
+            formats: OutputFormats::default(),
         }
     }
 
+    /// Which of CSV, Parquet and Arrow to write for each year
+    pub fn with_formats(mut self, formats: OutputFormats) -> Self {
+        self.formats = formats;
+        self
+    }
+
     pub fn process_all_ancillary_services(&self) -> Result<()> {
-        println!("\n⚡ Processing Ancillary Services Data");
-        println!("{}", "=".repeat(60));
+        info!("Processing Ancillary Services Data");
         
         // List of ancillary services directories to process
         let ancillary_dirs = vec![
@@ -39,10 +52,10 @@ impl AncillaryProcessor {
         for dir_name in ancillary_dirs {
             let dir_path = self.base_dir.join(dir_name);
             if dir_path.exists() {
-                println!("\n📁 Processing {}", dir_name);
+                info!("Processing {}", dir_name);
                 self.process_ancillary_directory(&dir_path, dir_name)?;
             } else {
-                println!("⚠️  Directory not found: {}", dir_name);
+                warn!("Directory not found: {}", dir_name);
             }
         }
         
@@ -64,17 +77,18 @@ impl AncillaryProcessor {
                 .collect();
             
             if !zip_files.is_empty() {
-                println!("  🗜️  Found {} ZIP files, extracting...", zip_files.len());
+                info!("Found {} ZIP files, extracting...", zip_files.len());
                 csv_files = self.extract_zips_to_csv(&zip_files, dir_name)?;
             }
         }
         
         if csv_files.is_empty() {
-            println!("  ❌ No data files found");
+            warn!("No data files found");
             return Ok(());
         }
         
-        println!("  📊 Found {} files to process", csv_files.len());
+        info!("Found {} files to process", csv_files.len());
+        run_metadata::record_inputs(dir_name, csv_files.len());
         
         // Group files by year
         let mut files_by_year: HashMap<u16, Vec<PathBuf>> = HashMap::new();
@@ -153,7 +167,7 @@ impl AncillaryProcessor {
     }
 
     fn process_year_ancillary_files(&self, year: u16, files: &[PathBuf], service_type: &str) -> Result<()> {
-        println!("\n  📅 Processing {} year {}: {} files", service_type, year, files.len());
+        info!("Processing {} year {}: {} files", service_type, year, files.len());
         
         let pb = ProgressBar::new(files.len() as u64);
         pb.set_style(ProgressStyle::default_bar()
@@ -166,9 +180,7 @@ impl AncillaryProcessor {
             .filter_map(|file| {
                 pb.inc(1);
                 
-                CsvReader::new(std::fs::File::open(file).ok()?)
-                    .has_header(true)
-                    .finish()
+                read_csv(file)
                     .ok()
             })
             .collect();
@@ -176,11 +188,11 @@ impl AncillaryProcessor {
         pb.finish();
         
         if all_dfs.is_empty() {
-            println!("    ❌ No valid data");
+            warn!("No valid data");
             return Ok(());
         }
         
-        println!("    📊 Combining {} dataframes...", all_dfs.len());
+        debug!("Combining {} dataframes...", all_dfs.len());
         
         // Concatenate
         let combined = concat(
@@ -191,31 +203,18 @@ impl AncillaryProcessor {
         // Save files
         let base_name = format!("{}_{}", service_type, year);
         
-        // CSV
-        let csv_path = self.output_dir.join(format!("{}.csv", base_name));
-        CsvWriter::new(std::fs::File::create(&csv_path)?)
-            .finish(&mut combined.clone())?;
-        
-        // Parquet
-        let parquet_path = self.output_dir.join(format!("{}.parquet", base_name));
-        ParquetWriter::new(std::fs::File::create(&parquet_path)?)
-            .finish(&mut combined.clone())?;
-        
-        // Arrow
-        let arrow_path = self.output_dir.join(format!("{}.arrow", base_name));
-        IpcWriter::new(std::fs::File::create(&arrow_path)?)
-            .finish(&mut combined.clone())?;
+        self.formats.write(&combined, &self.output_dir, &base_name)?;
         
-        println!("    ✅ Saved: {} records", combined.height());
+        debug!("Saved: {} records", combined.height());
         Ok(())
     }
 }
 
-pub fn process_all_ancillary_data() -> Result<()> {
+pub fn process_all_ancillary_data(formats: OutputFormats, output_prefix: &OutputPrefix) -> Result<()> {
     let base_dir = PathBuf::from("/Users/enrico/data/ERCOT_data");
-    let output_dir = PathBuf::from("ancillary_annual_data");
+    let output_dir = output_prefix.output_dir("ancillary_annual_data");
     
-    let processor = AncillaryProcessor::new(base_dir, output_dir);
+    let processor = AncillaryProcessor::new(base_dir, output_dir).with_formats(formats);
     processor.process_all_ancillary_services()?;
     
     Ok(())
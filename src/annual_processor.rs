@@ -1,4 +1,5 @@
 use anyhow::Result;
+use log::{debug, error, info, warn};
 use polars::prelude::*;
 use rayon::prelude::*;
 use std::collections::{HashMap, HashSet};
@@ -6,21 +7,55 @@ use std::fs;
 use std::path::{Path, PathBuf};
 use std::io::BufRead;
 use std::sync::Arc;
+use crate::output_formats::OutputFormats;
+use crate::output_prefix::OutputPrefix;
+use crate::run_metadata;
+
+/// Columns forced to Float64 when reading, whatever the first rows look like
+const PRICE_COLUMNS: [&str; 17] = [
+    "SettlementPointPrice", "LMP", "Price", "ShadowPrice",
+    "MCPCValue", "MCPC", "EnergyPrice", "CongestionPrice", 
+    "LossPrice", "EnergyComponent", "CongestionComponent", 
+    "LossComponent", "RegUp MCPC", "RegDown MCPC", 
+    "RRS MCPC", "NonSpin MCPC", "ECRS MCPC"
+];
+
+fn price_schema_overrides() -> Schema {
+    let mut schema_overrides = Schema::new();
+    for col in PRICE_COLUMNS {
+        schema_overrides.with_column(col.to_string().into(), DataType::Float64);
+    }
+    schema_overrides
+}
 
 pub struct AnnualProcessor {
     base_dir: PathBuf,
     output_dir: PathBuf,
+    streaming: bool,
+    formats: Option<OutputFormats>,
 }
 
 impl AnnualProcessor {
     pub fn new(base_dir: PathBuf, output_dir: PathBuf) -> Self {
-        Self { base_dir, output_dir }
+        Self { base_dir, output_dir, streaming: false, formats: None }
+    }
+    
+    /// Convert each year with `stream_year_files` instead of in memory
+    pub fn with_streaming(mut self, streaming: bool) -> Self {
+        self.streaming = streaming;
+        self
+    }
+    
+    /// Write exactly these formats; `None` keeps the default of Parquet,
+    /// CSV unless the year is large or SKIP_CSV=1, and Arrow if SAVE_ARROW=1
+    pub fn with_formats(mut self, formats: Option<OutputFormats>) -> Self {
+        self.formats = formats;
+        self
     }
     
     pub fn process_all_extracted_data(&self) -> Result<()> {
-        println!("🚀 Annual Data Processor for Extracted CSV Files");
-        println!("Using {} CPU cores", rayon::current_num_threads());
-        println!("{}", "=".repeat(80));
+        info!("Annual Data Processor for Extracted CSV Files");
+        info!("Using {} CPU cores", rayon::current_num_threads());
         
         // Read directories from CSV file
         let csv_file = "ercot_directories.csv";
@@ -44,7 +79,7 @@ impl AnnualProcessor {
             directories.push(line.to_string());
         }
 
-        println!("Found {} directories to process", directories.len());
+        info!("Found {} directories to process", directories.len());
         
         // Create output directory
         fs::create_dir_all(&self.output_dir)?;
@@ -54,15 +89,15 @@ impl AnnualProcessor {
             let csv_dir = self.base_dir.join(dir_name).join("csv");
             
             if !csv_dir.exists() {
-                println!("⚠️  CSV directory not found: {}", csv_dir.display());
+                warn!("CSV directory not found: {}", csv_dir.display());
                 continue;
             }
             
-            println!("\n🔄 [{}/{}] Processing: {}", idx + 1, directories.len(), dir_name);
+            info!("[{}/{}] Processing: {}", idx + 1, directories.len(), dir_name);
             self.process_directory(&csv_dir, dir_name)?;
         }
         
-        println!("\n✅ Annual processing complete!");
+        info!("Annual processing complete!");
         Ok(())
     }
     
@@ -81,15 +116,16 @@ impl AnnualProcessor {
             .collect();
         
         if csv_files.is_empty() {
-            println!("  ⚠️  No CSV files found in {}", csv_dir.display());
+            warn!("No CSV files found in {}", csv_dir.display());
             return Ok(());
         }
         
-        println!("  📊 Found {} CSV files", csv_files.len());
+        info!("Found {} CSV files", csv_files.len());
+        run_metadata::record_inputs(dir_name, csv_files.len());
         
         // Special handling for DAM_Hourly_LMPs which contains two different file types
         if dir_name == "DAM_Hourly_LMPs" {
-            println!("  📝 Special handling for DAM_Hourly_LMPs - separating file types");
+            info!("Special handling for DAM_Hourly_LMPs - separating file types");
             
             // Separate DAMHRLMPNP4183 (LMP) and DAMSPNP4190 (Settlement Point Price) files
             let lmp_files: Vec<PathBuf> = csv_files.iter()
@@ -102,18 +138,18 @@ impl AnnualProcessor {
                 .cloned()
                 .collect();
             
-            println!("  📁 Found {} DAMHRLMPNP4183 (LMP) files", lmp_files.len());
-            println!("  📁 Found {} DAMSPNP4190 (Settlement Point Price) files", spp_files.len());
+            info!("Found {} DAMHRLMPNP4183 (LMP) files", lmp_files.len());
+            info!("Found {} DAMSPNP4190 (Settlement Point Price) files", spp_files.len());
             
             // Process LMP files
             if !lmp_files.is_empty() {
-                println!("\n  Processing DAMHRLMPNP4183 (LMP) files...");
+                info!("Processing DAMHRLMPNP4183 (LMP) files...");
                 self.process_file_group(&lmp_files, "DAM_Hourly_LMPs_BusLevel")?;
             }
             
             // Process Settlement Point Price files
             if !spp_files.is_empty() {
-                println!("\n  Processing DAMSPNP4190 (Settlement Point Price) files...");
+                info!("Processing DAMSPNP4190 (Settlement Point Price) files...");
                 self.process_file_group(&spp_files, "DAM_Settlement_Point_Prices_Hourly")?;
             }
         } else {
@@ -135,23 +171,28 @@ impl AnnualProcessor {
         }
         
         if files_by_year.is_empty() {
-            println!("  ⚠️  No files with recognizable year patterns");
+            warn!("No files with recognizable year patterns");
             return Ok(());
         }
         
         let mut years: Vec<i32> = files_by_year.keys().cloned().collect();
         years.sort();
         
-        println!("  📅 Years found: {:?}", years);
+        info!("Years found: {:?}", years);
         
         // Process each year
         for year in years {
             let year_files = &files_by_year[&year];
-            println!("  🔄 Processing year {}: {} files", year, year_files.len());
+            info!("Processing year {}: {} files", year, year_files.len());
             
-            match self.process_year_files(year, year_files, output_name) {
-                Ok(()) => println!("  ✅ Completed year {}", year),
-                Err(e) => println!("  ❌ Failed year {}: {}", year, e),
+            let result = if self.streaming {
+                self.stream_year_files(year, year_files, output_name)
+            } else {
+                self.process_year_files(year, year_files, output_name)
+            };
+            match result {
+                Ok(()) => info!("Completed year {}", year),
+                Err(e) => error!("Failed year {}: {}", year, e),
             }
         }
         
@@ -218,24 +259,7 @@ impl AnnualProcessor {
     
     fn process_year_files(&self, year: i32, files: &[PathBuf], dir_name: &str) -> Result<()> {
         // First pass: determine all columns across all files
-        println!("    🔍 Analyzing schema across all files...");
-        let mut all_columns = HashSet::new();
-        let mut sample_count = 0;
-        
-        for (i, file) in files.iter().enumerate() {
-            if i % 50 == 0 {  // Sample every 50th file
-                if let Ok(df) = CsvReader::new(std::fs::File::open(file)?)
-                    .has_header(true)
-                    .finish() {
-                    for col in df.get_column_names() {
-                        all_columns.insert(col.to_string());
-                    }
-                    sample_count += 1;
-                }
-            }
-        }
-        
-        println!("    📋 Found {} unique columns across {} sampled files", all_columns.len(), sample_count);
+        let all_columns = self.sample_columns(files);
         
         // Read and combine all CSV files for the year
         let mut all_dataframes = Vec::new();
@@ -246,69 +270,24 @@ impl AnnualProcessor {
         for (batch_idx, batch) in files.chunks(batch_size).enumerate() {
             // Only log every 10th batch or first/last batch
             if batch_idx == 0 || batch_idx == total_batches - 1 || batch_idx % 10 == 0 {
-                println!("    🔄 Processing batch {}/{}", batch_idx + 1, total_batches);
+                debug!("Processing batch {}/{}", batch_idx + 1, total_batches);
             }
             
             let batch_dfs: Vec<LazyFrame> = batch
                 .par_iter()
                 .filter_map(|file| {
-                    // Define schema overrides for price columns
-                    let mut schema_overrides = Schema::new();
-                    
-                    // Force all price-related columns to Float64
-                    let price_columns = vec![
-                        "SettlementPointPrice", "LMP", "Price", "ShadowPrice",
-                        "MCPCValue", "MCPC", "EnergyPrice", "CongestionPrice", 
-                        "LossPrice", "EnergyComponent", "CongestionComponent", 
-                        "LossComponent", "RegUp MCPC", "RegDown MCPC", 
-                        "RRS MCPC", "NonSpin MCPC", "ECRS MCPC"
-                    ];
-                    
-                    for col in price_columns {
-                        schema_overrides.with_column(col.to_string().into(), DataType::Float64);
-                    }
-                    
-                    // Read CSV with schema overrides
+                    // Read CSV with schema overrides for price columns
                     match CsvReader::new(std::fs::File::open(file).ok()?)
                         .has_header(true)
                         .infer_schema(Some(50000))  // Much larger schema inference
-                        .with_dtypes(Some(Arc::new(schema_overrides)))
+                        .with_dtypes(Some(Arc::new(price_schema_overrides())))
                         .finish() {
                         Ok(df) => {
                             let cols: Vec<String> = df.get_column_names().iter().map(|s| s.to_string()).collect();
-                            let mut lazy_df = df.lazy();
-                            
-                            // Standardize column names: BusName -> SettlementPoint
-                            if cols.contains(&"BusName".to_string()) && !cols.contains(&"SettlementPoint".to_string()) {
-                                lazy_df = lazy_df.with_column(
-                                    col("BusName").alias("SettlementPoint")
-                                );
-                            }
-                            
-                            // Double-check and force cast any column that might be a price
-                            // This catches any columns that weren't in our predefined list
-                            for col_name in &cols {
-                                if col_name.to_lowercase().contains("price") || 
-                                   col_name.to_lowercase().contains("mcpc") || 
-                                   col_name.to_lowercase().contains("lmp") || 
-                                   col_name.to_lowercase().contains("component") ||
-                                   col_name.to_lowercase().contains("shadow") ||
-                                   col_name.to_lowercase().contains("energy") ||
-                                   col_name.to_lowercase().contains("congestion") ||
-                                   col_name.to_lowercase().contains("loss") {
-                                    lazy_df = lazy_df.with_column(
-                                        col(col_name).cast(DataType::Float64)
-                                    );
-                                }
-                            }
-                            
-                            // Normalize the dataframe to have all expected columns
-                            lazy_df = self.normalize_dataframe(lazy_df, &all_columns);
-                            
-                            Some(lazy_df)
+                            Some(self.standardize_columns(df.lazy(), &cols, &all_columns))
                         },
                         Err(e) => {
-                            eprintln!("    ⚠️  Failed to read {}: {}", file.display(), e);
+                            warn!("Failed to read {}: {}", file.display(), e);
                             None
                         }
                     }
@@ -321,11 +300,11 @@ impl AnnualProcessor {
         }
         
         if all_dataframes.is_empty() {
-            println!("    ⚠️  No valid dataframes for year {}", year);
+            warn!("No valid dataframes for year {}", year);
             return Ok(());
         }
         
-        println!("    📊 Combining {} dataframes", all_dataframes.len());
+        debug!("Combining {} dataframes", all_dataframes.len());
         
         // Concatenate all dataframes with relaxed column matching
         let combined = concat(
@@ -349,15 +328,14 @@ impl AnnualProcessor {
             .sort(&sort_column, Default::default())
             .collect()?;
         
-        println!("    📊 Final record count: {}", final_df.height());
+        debug!("Final record count: {}", final_df.height());
         
         // Save in multiple formats
-        let safe_dir_name = dir_name.replace(",", "_").replace(" ", "_");
-        let base_filename = format!("{}_{}", safe_dir_name, year);
+        let (dataset_output_dir, base_filename) = self.output_location(year, dir_name)?;
         
-        // Create output directory for this dataset
-        let dataset_output_dir = self.output_dir.join(&safe_dir_name);
-        fs::create_dir_all(&dataset_output_dir)?;
+        if let Some(formats) = self.formats {
+            return formats.write(&final_df, &dataset_output_dir, &base_filename);
+        }
         
         // Skip CSV for large datasets to save disk space
         // CSV files can be 20-50x larger than Parquet
@@ -367,23 +345,23 @@ impl AnnualProcessor {
         if !skip_csv {
             // CSV
             let csv_path = dataset_output_dir.join(format!("{}.csv", base_filename));
-            println!("    💾 Saving CSV: {}", csv_path.display());
+            debug!("Saving CSV: {}", csv_path.display());
             CsvWriter::new(fs::File::create(&csv_path)?)
                 .finish(&mut final_df.clone())?;
         } else {
-            println!("    ⏭️  Skipping CSV output (dataset too large: {} rows)", final_df.height());
+            warn!("Skipping CSV output (dataset too large: {} rows)", final_df.height());
         }
         
         // Parquet - ALWAYS save this as it's highly compressed
         let parquet_path = dataset_output_dir.join(format!("{}.parquet", base_filename));
-        println!("    📦 Saving Parquet: {}", parquet_path.display());
+        debug!("Saving Parquet: {}", parquet_path.display());
         ParquetWriter::new(fs::File::create(&parquet_path)?)
             .finish(&mut final_df.clone())?;
         
         // Arrow IPC - Optional, controlled by environment variable
         if std::env::var("SAVE_ARROW").unwrap_or_default() == "1" {
             let arrow_path = dataset_output_dir.join(format!("{}.arrow", base_filename));
-            println!("    🏹 Saving Arrow: {}", arrow_path.display());
+            debug!("Saving Arrow: {}", arrow_path.display());
             IpcWriter::new(fs::File::create(&arrow_path)?)
                 .finish(&mut final_df.clone())?;
         }
@@ -391,6 +369,131 @@ impl AnnualProcessor {
         Ok(())
     }
     
+    /// Convert a year of CSVs with Polars' streaming engine: each file is
+    /// scanned lazily and the de-duplicated, sorted concatenation is sunk
+    /// straight to disk, so the year is never held in memory. Writes the same
+    /// rows as `process_year_files` as Parquet and/or Arrow per `--formats`
+    /// (each one re-reads the CSVs), or only Parquet without the flag. CSV
+    /// output is not streamed: Polars 0.33's CSV sink stalls on this plan.
+    fn stream_year_files(&self, year: i32, files: &[PathBuf], dir_name: &str) -> Result<()> {
+        let all_columns = self.sample_columns(files);
+        let schema_overrides = price_schema_overrides();
+        
+        let mut frames = Vec::with_capacity(files.len());
+        for file in files {
+            let scanned = LazyCsvReader::new(file)
+                .has_header(true)
+                .with_infer_schema_length(Some(50000))
+                .with_dtype_overwrite(Some(&schema_overrides))
+                .finish()
+                .and_then(|lazy_df| Ok((lazy_df.schema()?, lazy_df)));
+            match scanned {
+                Ok((schema, lazy_df)) => {
+                    let cols: Vec<String> = schema.iter_names().map(|s| s.to_string()).collect();
+                    frames.push(self.standardize_columns(lazy_df, &cols, &all_columns));
+                }
+                Err(e) => warn!("Failed to scan {}: {}", file.display(), e),
+            }
+        }
+        
+        if frames.is_empty() {
+            warn!("No valid dataframes for year {}", year);
+            return Ok(());
+        }
+        
+        debug!("Streaming {} files", frames.len());
+        let combined = concat(
+            frames,
+            UnionArgs {
+                parallel: true,
+                rechunk: false,
+                to_supertypes: true,
+                ..Default::default()
+            },
+        )?;
+        let processed = self.process_datetime_columns(combined)?;
+        let sort_column = self.find_sort_column(&processed)?;
+        
+        let output = processed
+            .unique(None, UniqueKeepStrategy::First)
+            .sort(&sort_column, Default::default());
+        
+        let (dataset_output_dir, base_filename) = self.output_location(year, dir_name)?;
+        let formats = self.formats.unwrap_or(OutputFormats { csv: false, parquet: true, arrow: false });
+        if formats.parquet {
+            let parquet_path = dataset_output_dir.join(format!("{}.parquet", base_filename));
+            debug!("Streaming Parquet: {}", parquet_path.display());
+            output.clone().sink_parquet(parquet_path, ParquetWriteOptions::default())?;
+        }
+        if formats.arrow {
+            let arrow_path = dataset_output_dir.join(format!("{}.arrow", base_filename));
+            debug!("Streaming Arrow IPC: {}", arrow_path.display());
+            output.sink_ipc(arrow_path, IpcWriterOptions::default())?;
+        }
+        
+        Ok(())
+    }
+    
+    /// Union of the header columns of every 50th file, read without parsing
+    /// the rows
+    fn sample_columns(&self, files: &[PathBuf]) -> HashSet<String> {
+        debug!("Analyzing schema across all files...");
+        let mut all_columns = HashSet::new();
+        let mut sample_count = 0;
+        
+        for file in files.iter().step_by(50) {
+            if let Ok(headers) = csv::Reader::from_path(file).and_then(|mut reader| reader.headers().cloned()) {
+                all_columns.extend(headers.iter().map(str::to_string));
+                sample_count += 1;
+            }
+        }
+        
+        debug!("Found {} unique columns across {} sampled files", all_columns.len(), sample_count);
+        all_columns
+    }
+    
+    /// Rename BusName to SettlementPoint, cast price-like columns to Float64
+    /// and conform a file to the year's columns
+    fn standardize_columns(&self, mut lazy_df: LazyFrame, cols: &[String], all_columns: &HashSet<String>) -> LazyFrame {
+        // Standardize column names: BusName -> SettlementPoint
+        if cols.contains(&"BusName".to_string()) && !cols.contains(&"SettlementPoint".to_string()) {
+            lazy_df = lazy_df.with_column(
+                col("BusName").alias("SettlementPoint")
+            );
+        }
+        
+        // Double-check and force cast any column that might be a price
+        // This catches any columns that weren't in our predefined list
+        for col_name in cols {
+            if col_name.to_lowercase().contains("price") || 
+               col_name.to_lowercase().contains("mcpc") || 
+               col_name.to_lowercase().contains("lmp") || 
+               col_name.to_lowercase().contains("component") ||
+               col_name.to_lowercase().contains("shadow") ||
+               col_name.to_lowercase().contains("energy") ||
+               col_name.to_lowercase().contains("congestion") ||
+               col_name.to_lowercase().contains("loss") {
+                lazy_df = lazy_df.with_column(
+                    col(col_name).cast(DataType::Float64)
+                );
+            }
+        }
+        
+        // Normalize the dataframe to have all expected columns
+        self.normalize_dataframe(lazy_df, all_columns)
+    }
+    
+    /// Output directory for a dataset (created if needed) and the year's file stem
+    fn output_location(&self, year: i32, dir_name: &str) -> Result<(PathBuf, String)> {
+        let safe_dir_name = dir_name.replace(",", "_").replace(" ", "_");
+        let base_filename = format!("{}_{}", safe_dir_name, year);
+        
+        // Create output directory for this dataset
+        let dataset_output_dir = self.output_dir.join(&safe_dir_name);
+        fs::create_dir_all(&dataset_output_dir)?;
+        Ok((dataset_output_dir, base_filename))
+    }
+    
     fn find_sort_column(&self, df: &LazyFrame) -> Result<String> {
         let df_collected = df.clone().limit(1).collect()?;
         let columns: Vec<String> = df_collected.get_column_names().iter().map(|s| s.to_string()).collect();
@@ -435,10 +538,80 @@ impl AnnualProcessor {
     }
 }
 
-pub fn process_all_annual_data() -> Result<()> {
+pub fn process_all_annual_data(streaming: bool, formats: Option<OutputFormats>, output_prefix: &OutputPrefix) -> Result<()> {
     let base_dir = PathBuf::from("/Users/enrico/data/ERCOT_data");
-    let output_dir = PathBuf::from("annual_output");
+    let output_dir = output_prefix.output_dir("annual_output");
     
-    let processor = AnnualProcessor::new(base_dir, output_dir);
+    if streaming && formats.is_some_and(|formats| formats.csv) {
+        warn!("--streaming does not write CSV; only the Parquet/Arrow formats in --formats are written \
+               (run without --streaming for CSV output)");
+    }
+    
+    let processor = AnnualProcessor::new(base_dir, output_dir)
+        .with_streaming(streaming)
+        .with_formats(formats);
     processor.process_all_extracted_data()
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sorted(df: DataFrame) -> DataFrame {
+        let mut columns: Vec<String> = df.get_column_names().iter().map(|s| s.to_string()).collect();
+        columns.sort();
+        df.select(&columns).unwrap().sort(&columns, false, false).unwrap()
+    }
+
+    #[test]
+    fn test_streaming_output_matches_eager() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut files = Vec::new();
+        for day in 1..=3 {
+            let path = dir.path().join(format!("cdr.00012301.0000000000000000.202401{:02}.csv", day));
+            let mut csv = String::from("DeliveryDate,DeliveryHour,DeliveryInterval,SettlementPointName,SettlementPointPrice\n");
+            for hour in 1..=2 {
+                for point in ["HB_NORTH", "BATCAVE_RN"] {
+                    csv.push_str(&format!("01/{:02}/2024,{},1,{},{}\n", day, hour, point, day * 10 + hour));
+                }
+            }
+            // Day 2's file repeats a row of day 1
+            if day == 2 {
+                csv.push_str("01/01/2024,1,1,HB_NORTH,11\n");
+            }
+            std::fs::write(&path, csv).unwrap();
+            files.push(path);
+        }
+
+        let eager_dir = dir.path().join("eager");
+        AnnualProcessor::new(dir.path().to_path_buf(), eager_dir.clone())
+            .process_year_files(2024, &files, "RT_Prices").unwrap();
+        let streaming_dir = dir.path().join("streaming");
+        AnnualProcessor::new(dir.path().to_path_buf(), streaming_dir.clone())
+            .with_streaming(true)
+            .stream_year_files(2024, &files, "RT_Prices").unwrap();
+
+        let read = |base: &Path| {
+            ParquetReader::new(fs::File::open(base.join("RT_Prices/RT_Prices_2024.parquet")).unwrap())
+                .finish()
+                .unwrap()
+        };
+        let eager = read(&eager_dir);
+        let streamed = read(&streaming_dir);
+        assert_eq!(eager.height(), 12);
+        assert!(!streaming_dir.join("RT_Prices/RT_Prices_2024.csv").exists());
+        assert!(sorted(eager.clone()).frame_equal_missing(&sorted(streamed)));
+
+        // --formats arrow streams Arrow instead of Parquet
+        let formats_dir = dir.path().join("formats");
+        AnnualProcessor::new(dir.path().to_path_buf(), formats_dir.clone())
+            .with_streaming(true)
+            .with_formats(Some(OutputFormats::parse("arrow").unwrap()))
+            .stream_year_files(2024, &files, "RT_Prices").unwrap();
+        assert!(!formats_dir.join("RT_Prices/RT_Prices_2024.parquet").exists());
+        let arrow = IpcReader::new(fs::File::open(formats_dir.join("RT_Prices/RT_Prices_2024.arrow")).unwrap())
+            .finish()
+            .unwrap();
+        assert!(sorted(eager).frame_equal_missing(&sorted(arrow)));
+    }
+}
@@ -0,0 +1,111 @@
+use anyhow::{Context, Result};
+use flate2::read::GzDecoder;
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+/// Common interface over the archive formats ERCOT data arrives in
+pub trait ArchiveReader: Send + Sync {
+    /// Extract every file in the archive under `extract_to`, returning the extracted file paths
+    fn extract(&self, archive_path: &Path, extract_to: &Path) -> Result<Vec<PathBuf>>;
+}
+
+pub struct ZipReader;
+
+impl ArchiveReader for ZipReader {
+    fn extract(&self, archive_path: &Path, extract_to: &Path) -> Result<Vec<PathBuf>> {
+        let file = fs::File::open(archive_path)
+            .with_context(|| format!("Failed to open ZIP file: {:?}", archive_path))?;
+
+        let mut archive = ::zip::ZipArchive::new(file)
+            .with_context(|| format!("Failed to read ZIP archive: {:?}", archive_path))?;
+
+        let mut extracted = Vec::new();
+        for i in 0..archive.len() {
+            let mut file = archive.by_index(i)?;
+            let outpath = extract_to.join(file.name());
+
+            if file.name().ends_with('/') {
+                fs::create_dir_all(&outpath)?;
+            } else {
+                if let Some(p) = outpath.parent() {
+                    fs::create_dir_all(p)?;
+                }
+
+                let mut outfile = fs::File::create(&outpath)?;
+                io::copy(&mut file, &mut outfile)?;
+                extracted.push(outpath);
+            }
+        }
+
+        Ok(extracted)
+    }
+}
+
+pub struct TarReader {
+    gzipped: bool,
+}
+
+impl TarReader {
+    pub fn new(gzipped: bool) -> Self {
+        Self { gzipped }
+    }
+
+    fn unpack<R: Read>(&self, reader: R, extract_to: &Path) -> Result<Vec<PathBuf>> {
+        let mut archive = tar::Archive::new(reader);
+        let mut extracted = Vec::new();
+
+        for entry in archive.entries()? {
+            let mut entry = entry?;
+            if !entry.header().entry_type().is_file() {
+                continue;
+            }
+
+            let relative_path = entry.path()?.into_owned();
+            if entry.unpack_in(extract_to)? {
+                extracted.push(extract_to.join(relative_path));
+            }
+        }
+
+        Ok(extracted)
+    }
+}
+
+impl ArchiveReader for TarReader {
+    fn extract(&self, archive_path: &Path, extract_to: &Path) -> Result<Vec<PathBuf>> {
+        let file = fs::File::open(archive_path)
+            .with_context(|| format!("Failed to open TAR file: {:?}", archive_path))?;
+
+        let extracted = if self.gzipped {
+            self.unpack(GzDecoder::new(file), extract_to)
+        } else {
+            self.unpack(file, extract_to)
+        };
+
+        extracted.with_context(|| format!("Failed to read TAR archive: {:?}", archive_path))
+    }
+}
+
+/// Pick a reader from the file extension (`.zip`, `.tar`, `.tar.gz`, `.tgz`).
+/// XML-only ZIPs are skipped since only CSV data is used.
+pub fn archive_reader_for(path: &Path) -> Option<Box<dyn ArchiveReader>> {
+    let filename = path.file_name().and_then(|s| s.to_str()).unwrap_or("");
+    let lower = filename.to_lowercase();
+
+    if lower.ends_with(".zip") {
+        if lower.ends_with("_xml.zip") {
+            return None;
+        }
+        Some(Box::new(ZipReader))
+    } else if lower.ends_with(".tar.gz") || lower.ends_with(".tgz") {
+        Some(Box::new(TarReader::new(true)))
+    } else if lower.ends_with(".tar") {
+        Some(Box::new(TarReader::new(false)))
+    } else {
+        None
+    }
+}
+
+pub fn is_archive(path: &Path) -> bool {
+    archive_reader_for(path).is_some()
+}
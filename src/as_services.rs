@@ -0,0 +1,236 @@
+use anyhow::Result;
+use chrono::NaiveDate;
+use polars::prelude::*;
+use std::collections::{HashMap, HashSet};
+
+/// Ancillary services under the keys revenue maps use. ERCOT has renamed
+/// and split products over the years: RRS was a single "RRS Awarded" column
+/// until it split into PFR/FFR/UFR subtypes, and ECRS has appeared as both
+/// "ECRS" and its "ECRSSD"/"ECRSM"/"ECRSS" variants. Every variant maps to
+/// one service here so old and new files land in the same bucket.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum AsService {
+    RegUp,
+    RegDown,
+    Rrs,
+    Ecrs,
+    NonSpin,
+}
+
+impl AsService {
+    pub const ALL: [AsService; 5] = [
+        AsService::RegUp,
+        AsService::RegDown,
+        AsService::Rrs,
+        AsService::Ecrs,
+        AsService::NonSpin,
+    ];
+
+    /// Key used in per-service revenue and price maps
+    pub fn key(&self) -> &'static str {
+        match self {
+            AsService::RegUp => "RegUp",
+            AsService::RegDown => "RegDown",
+            AsService::Rrs => "RRS",
+            AsService::Ecrs => "ECRS",
+            AsService::NonSpin => "NonSpin",
+        }
+    }
+
+    /// Whether the service needs room to discharge further; only RegDown
+    /// needs room to charge
+    pub fn is_up(&self) -> bool {
+        !matches!(self, AsService::RegDown)
+    }
+
+    /// Service for a bare product name such as `REGDN`, `RRSPFR` or `ECRSSD`,
+    /// ignoring case, spaces and underscores
+    pub fn from_product(name: &str) -> Option<Self> {
+        let name: String = name
+            .chars()
+            .filter(|c| !c.is_whitespace() && *c != '_' && *c != '-')
+            .collect::<String>()
+            .to_ascii_uppercase();
+        match name.as_str() {
+            "REGUP" => Some(AsService::RegUp),
+            "REGDN" | "REGDOWN" => Some(AsService::RegDown),
+            "NSPIN" | "NONSPIN" | "NSRS" => Some(AsService::NonSpin),
+            n if n.starts_with("RRS") => Some(AsService::Rrs),
+            n if n.starts_with("ECRS") => Some(AsService::Ecrs),
+            _ => None,
+        }
+    }
+
+    /// Service for a DAM Gen Resource Data award column, e.g. `RRS Awarded`
+    /// or `RRSFFR Awarded`
+    pub fn from_award_column(column: &str) -> Option<Self> {
+        column.trim().strip_suffix("Awarded").and_then(Self::from_product)
+    }
+
+    /// Service for a DAM Gen Resource Data clearing price column, e.g. `RRS MCPC`
+    pub fn from_mcpc_column(column: &str) -> Option<Self> {
+        column.trim().strip_suffix("MCPC").and_then(Self::from_product)
+    }
+}
+
+/// Column as f64 with blanks counted as zero
+fn numeric_values(column: &Series) -> Result<Vec<f64>> {
+    let values = column.cast(&DataType::Float64)?;
+    Ok(values.f64()?.into_iter().map(|v| v.filter(|v| !v.is_nan()).unwrap_or(0.0)).collect())
+}
+
+/// Row-wise award MW per service, summing every variant column present, so a
+/// file with split RRS subtypes yields the same RRS total as one with a
+/// combined `RRS Awarded` column
+pub fn service_awards(df: &DataFrame) -> Result<HashMap<AsService, Vec<f64>>> {
+    let mut awards: HashMap<AsService, Vec<f64>> = HashMap::new();
+    for column in df.get_columns() {
+        if let Some(service) = AsService::from_award_column(column.name()) {
+            let totals = awards.entry(service).or_insert_with(|| vec![0.0; df.height()]);
+            for (total, value) in totals.iter_mut().zip(numeric_values(column)?) {
+                *total += value;
+            }
+        }
+    }
+    Ok(awards)
+}
+
+/// Row-wise clearing price per service. Variants of one service share a
+/// price, so the first present column wins.
+pub fn service_mcpcs(df: &DataFrame) -> Result<HashMap<AsService, Vec<f64>>> {
+    let mut prices = HashMap::new();
+    for column in df.get_columns() {
+        if let Some(service) = AsService::from_mcpc_column(column.name()) {
+            if !prices.contains_key(&service) {
+                prices.insert(service, numeric_values(column)?);
+            }
+        }
+    }
+    Ok(prices)
+}
+
+/// Row-wise award × clearing price per service, for services with both an
+/// award and a price column
+pub fn service_revenues(df: &DataFrame) -> Result<HashMap<AsService, Vec<f64>>> {
+    let awards = service_awards(df)?;
+    let prices = service_mcpcs(df)?;
+    Ok(awards
+        .into_iter()
+        .filter_map(|(service, awards)| {
+            let prices = prices.get(&service)?;
+            Some((service, awards.iter().zip(prices).map(|(a, p)| a * p).collect::<Vec<f64>>()))
+        })
+        .collect())
+}
+
+/// Resource-hours whose DAM awards (AS or energy) have been settled. DAM Gen
+/// Resource Data has one row per resource per hour, so a repeated row,
+/// within a file or across overlapping files, would add that hour's award ×
+/// price twice.
+#[derive(Debug, Default)]
+pub struct SettledAwardHours {
+    seen: HashSet<(String, NaiveDate, i64, bool)>,
+    duplicates: usize,
+}
+
+impl SettledAwardHours {
+    /// Whether this resource-hour is new; a repeat is counted and should be
+    /// skipped. `repeated_hour` keeps the fall-back day's second hour apart.
+    pub fn insert(&mut self, resource: &str, date: NaiveDate, hour_ending: i64, repeated_hour: bool) -> bool {
+        let new = self.seen.insert((resource.to_string(), date, hour_ending, repeated_hour));
+        if !new {
+            self.duplicates += 1;
+        }
+        new
+    }
+
+    /// Rows skipped as repeats of an hour already settled
+    pub fn duplicates(&self) -> usize {
+        self.duplicates
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_combined_and_split_rrs_give_same_revenue() {
+        // 2020 files carry one RRS award column; 2023 files split it by subtype
+        let combined = df! {
+            "Resource Name" => &["BATCAVE_BES1", "BATCAVE_BES1"],
+            "RegUp Awarded" => &["2", ""],
+            "RegUp MCPC" => &["5.0", "6.0"],
+            "RRS Awarded" => &["10", "4"],
+            "RRS MCPC" => &["12.5", "8.0"],
+        }
+        .unwrap();
+        let split = df! {
+            "Resource Name" => &["BATCAVE_BES1", "BATCAVE_BES1"],
+            "RegUp Awarded" => &["2", ""],
+            "RegUp MCPC" => &["5.0", "6.0"],
+            "RRSPFR Awarded" => &["6", "0"],
+            "RRSFFR Awarded" => &["", "4"],
+            "RRSUFR Awarded" => &["4", ""],
+            "RRS MCPC" => &["12.5", "8.0"],
+            "ECRSSD Awarded" => &["1", "0"],
+            "ECRS MCPC" => &["3.0", "3.0"],
+        }
+        .unwrap();
+
+        let combined = service_revenues(&combined).unwrap();
+        let split = service_revenues(&split).unwrap();
+        assert_eq!(combined[&AsService::Rrs], vec![125.0, 32.0]);
+        assert_eq!(split[&AsService::Rrs], combined[&AsService::Rrs]);
+        assert_eq!(split[&AsService::RegUp], vec![10.0, 0.0]);
+        assert_eq!(split[&AsService::Ecrs], vec![3.0, 0.0]);
+        assert!(!combined.contains_key(&AsService::Ecrs));
+    }
+
+    #[test]
+    fn test_duplicated_hourly_rows_are_settled_once() {
+        // HE1 and HE2, then HE1 repeated by an overlapping file
+        let df = df! {
+            "Resource Name" => &["BATCAVE_BES1", "BATCAVE_BES1", "BATCAVE_BES1"],
+            "Hour Ending" => &[1i64, 2, 1],
+            "RegUp Awarded" => &["10", "10", "10"],
+            "RegUp MCPC" => &["5.0", "7.0", "5.0"],
+        }
+        .unwrap();
+        let revenues = service_revenues(&df).unwrap();
+        let hours: Vec<i64> = df.column("Hour Ending").unwrap().i64().unwrap().into_no_null_iter().collect();
+
+        let date = NaiveDate::from_ymd_opt(2024, 8, 1).unwrap();
+        let mut settled = SettledAwardHours::default();
+        let total: f64 = (0..df.height())
+            .filter(|&i| settled.insert("BATCAVE_BES1", date, hours[i], false))
+            .map(|i| revenues[&AsService::RegUp][i])
+            .sum();
+
+        assert_eq!(total, 120.0);
+        assert_eq!(settled.duplicates(), 1);
+        // The fall-back day's repeated HE2 is its own hour
+        assert!(settled.insert("BATCAVE_BES1", date, 2, true));
+    }
+
+    #[test]
+    fn test_product_names_map_to_canonical_services() {
+        for (name, service) in [
+            ("REGUP", AsService::RegUp),
+            ("REGDN", AsService::RegDown),
+            ("RRS", AsService::Rrs),
+            ("RRSPFR", AsService::Rrs),
+            ("RRSUFR", AsService::Rrs),
+            ("NSPIN", AsService::NonSpin),
+            ("ECRS", AsService::Ecrs),
+            ("ECRSM", AsService::Ecrs),
+            ("ECRSS", AsService::Ecrs),
+        ] {
+            assert_eq!(AsService::from_product(name), Some(service), "{}", name);
+        }
+        assert_eq!(AsService::from_award_column("ECRSSD Awarded"), Some(AsService::Ecrs));
+        assert_eq!(AsService::from_mcpc_column("NonSpin MCPC"), Some(AsService::NonSpin));
+        assert_eq!(AsService::from_award_column("Awarded Quantity"), None);
+        assert_eq!(AsService::from_product("DeliveryDate"), None);
+    }
+}
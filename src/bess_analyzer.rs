@@ -1,10 +1,103 @@
 use anyhow::Result;
+use log::warn;
+use chrono::NaiveDate;
 use glob::glob;
 use indicatif::{ProgressBar, ProgressStyle};
 use polars::prelude::*;
 use std::collections::HashMap;
 use std::path::PathBuf;
 
+use crate::bess_lifecycle::{resource_lifecycle, save_lifecycles};
+use crate::master_list::BessResource as MasterListResource;
+use crate::output_prefix::OutputPrefix;
+use crate::run_metadata;
+
+/// Battery duration assumed when the master list doesn't give one
+pub const DEFAULT_DURATION_HOURS: f64 = 2.0;
+
+/// Parse `--default-duration-hours <hours>` (or `--default-duration-hours=<hours>`),
+/// the duration assumed for resources without one in the master list
+pub fn default_duration_from_args(args: &[String]) -> Result<f64> {
+    let value = args.iter().enumerate().find_map(|(i, arg)| {
+        if arg == "--default-duration-hours" {
+            Some(args.get(i + 1).cloned().unwrap_or_default())
+        } else {
+            arg.strip_prefix("--default-duration-hours=").map(str::to_string)
+        }
+    });
+    match value {
+        Some(v) => v.parse::<f64>()
+            .ok()
+            .filter(|hours| *hours > 0.0)
+            .ok_or_else(|| anyhow::anyhow!("Invalid --default-duration-hours: {} (expected hours > 0)", v)),
+        None => Ok(DEFAULT_DURATION_HOURS),
+    }
+}
+
+/// Battery durations from the master list, with a configurable fallback for
+/// resources it has none for
+#[derive(Debug, Clone)]
+pub struct ResourceDurations {
+    durations: HashMap<String, f64>,
+    default_hours: f64,
+}
+
+impl Default for ResourceDurations {
+    fn default() -> Self {
+        Self { durations: HashMap::new(), default_hours: DEFAULT_DURATION_HOURS }
+    }
+}
+
+impl ResourceDurations {
+    pub fn from_resources(resources: &[MasterListResource]) -> Self {
+        let durations = resources.iter()
+            .filter_map(|r| r.duration_hours.map(|hours| (r.name.clone(), hours)))
+            .collect();
+        Self { durations, ..Self::default() }
+    }
+
+    pub fn with_default_hours(mut self, hours: f64) -> Self {
+        self.default_hours = hours;
+        self
+    }
+
+    pub fn default_hours(&self) -> f64 {
+        self.default_hours
+    }
+
+    pub fn insert(&mut self, resource_name: &str, hours: f64) {
+        self.durations.insert(resource_name.to_string(), hours);
+    }
+
+    /// Whether the master list gave this resource a duration
+    pub fn is_known(&self, resource_name: &str) -> bool {
+        self.durations.contains_key(resource_name)
+    }
+
+    /// The resource's duration, or the default
+    pub fn get(&self, resource_name: &str) -> f64 {
+        self.durations.get(resource_name).copied().unwrap_or(self.default_hours)
+    }
+
+    /// Warn how many of `resources` fall back to the default duration;
+    /// returns that count
+    pub fn warn_defaults<'a>(&self, resources: impl IntoIterator<Item = &'a String>) -> usize {
+        let mut total = 0;
+        let mut defaulted = 0;
+        for name in resources {
+            total += 1;
+            if !self.is_known(name) {
+                defaulted += 1;
+            }
+        }
+        if defaulted > 0 {
+            warn!("{} of {} resources have no duration in the master list; assuming {} hours (set with --default-duration-hours)",
+                  defaulted, total, self.default_hours);
+        }
+        defaulted
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct BessResource {
     pub name: String,
@@ -21,9 +114,9 @@ pub struct BessAnalyzer {
 }
 
 impl BessAnalyzer {
-    pub fn new() -> Result<Self> {
-        let disclosure_dir = PathBuf::from("disclosure_data");
-        let output_dir = PathBuf::from("bess_analysis");
+    pub fn new(output_prefix: &OutputPrefix) -> Result<Self> {
+        let disclosure_dir = output_prefix.input_path("disclosure_data");
+        let output_dir = output_prefix.output_dir("bess_analysis");
         std::fs::create_dir_all(&output_dir)?;
         
         Ok(Self {
@@ -43,6 +136,7 @@ impl BessAnalyzer {
             .collect();
         
         println!("Found {} Gen Resource Data files", resource_files.len());
+        run_metadata::record_inputs("DAM Gen Resource Data", resource_files.len());
         
         // Collect all unique BESS resources
         let mut all_bess_resources: HashMap<String, BessResource> = HashMap::new();
@@ -159,6 +253,28 @@ impl BessAnalyzer {
         // Analyze activity patterns
         self.analyze_bess_activity(&all_bess_resources, &bess_appearances)?;
         
+        // When each resource came online and how long it went missing
+        self.save_lifecycle_report(&bess_appearances)?;
+        
+        Ok(())
+    }
+
+    fn save_lifecycle_report(&self, appearances: &HashMap<String, Vec<(String, String)>>) -> Result<()> {
+        let mut lifecycles: Vec<_> = appearances.iter()
+            .filter_map(|(name, apps)| resource_lifecycle(
+                name,
+                apps.iter().filter_map(|(date, _)| NaiveDate::parse_from_str(date, "%m/%d/%Y").ok()),
+            ))
+            .collect();
+        lifecycles.sort_by(|a, b| a.first_active.cmp(&b.first_active).then(a.resource_name.cmp(&b.resource_name)));
+        
+        let with_gaps = lifecycles.iter().filter(|l| !l.gaps.is_empty()).count();
+        println!("\n📅 Resource Lifecycle: {} resources, {} with multi-day gaps", lifecycles.len(), with_gaps);
+        
+        let output_path = self.output_dir.join("bess_lifecycle.csv");
+        save_lifecycles(&lifecycles, &output_path)?;
+        println!("✅ Saved lifecycle report to: {}", output_path.display());
+        
         Ok(())
     }
 
@@ -236,8 +352,42 @@ impl BessAnalyzer {
     }
 }
 
-pub fn analyze_bess_resources() -> Result<()> {
-    let analyzer = BessAnalyzer::new()?;
+pub fn analyze_bess_resources(output_prefix: &OutputPrefix) -> Result<()> {
+    let analyzer = BessAnalyzer::new(output_prefix)?;
     analyzer.find_all_bess_resources()?;
     Ok(())
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::master_list::resources_from_master_list;
+
+    #[test]
+    fn test_resources_without_duration_use_configured_default() {
+        let df = df! {
+            "Resource_Name" => &["BESS_4H", "BESS_ENERGY", "BESS_UNKNOWN"],
+            "Settlement_Point" => &["SP_4H", "SP_ENERGY", "SP_UNKNOWN"],
+            "Max_Capacity_MW" => &[100.0, 50.0, 10.0],
+            "Duration_Hours" => &[Some(4.0), None, None],
+            "Max_Capacity_MWh" => &[None, Some(50.0), None],
+        }
+        .unwrap();
+        let args: Vec<String> = ["prog", "--bess-revenue", "--default-duration-hours", "3"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+
+        let durations = ResourceDurations::from_resources(&resources_from_master_list(&df).unwrap())
+            .with_default_hours(default_duration_from_args(&args).unwrap());
+        assert_eq!(durations.get("BESS_4H"), 4.0);
+        assert_eq!(durations.get("BESS_ENERGY"), 1.0);
+        assert_eq!(durations.get("BESS_UNKNOWN"), 3.0);
+
+        let names: Vec<String> = ["BESS_4H", "BESS_ENERGY", "BESS_UNKNOWN"].iter().map(|s| s.to_string()).collect();
+        assert_eq!(durations.warn_defaults(&names), 1);
+
+        assert_eq!(default_duration_from_args(&[]).unwrap(), DEFAULT_DURATION_HOURS);
+        assert!(default_duration_from_args(&["--default-duration-hours=0".to_string()]).is_err());
+    }
+}
@@ -0,0 +1,168 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+/// Benchmark revenue for one battery duration class (e.g. 1h, 2h, 4h)
+#[derive(Debug, Clone, Deserialize)]
+pub struct DurationBenchmark {
+    #[serde(rename = "Duration_Hours")]
+    pub duration_hours: f64,
+    #[serde(rename = "Revenue_Per_MW_Year")]
+    pub revenue_per_mw_year: f64,
+    #[serde(rename = "Source", default)]
+    pub source: String,
+}
+
+impl DurationBenchmark {
+    pub fn label(&self) -> String {
+        format!("{}h", self.duration_hours)
+    }
+}
+
+/// Peer benchmarks keyed by duration class, loaded from a CSV:
+///
+/// ```text
+/// Duration_Hours,Revenue_Per_MW_Year,Source
+/// 1,150000,Peer set 1h
+/// 2,196000,Modo Energy 2023
+/// 4,240000,Peer set 4h
+/// ```
+#[derive(Debug, Clone)]
+pub struct PeerBenchmarks {
+    classes: Vec<DurationBenchmark>,
+}
+
+impl PeerBenchmarks {
+    pub fn from_csv<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let mut reader = csv::Reader::from_path(path)
+            .with_context(|| format!("Failed to open benchmark file {}", path.display()))?;
+
+        let mut classes = Vec::new();
+        for record in reader.deserialize() {
+            let benchmark: DurationBenchmark = record
+                .with_context(|| format!("Failed to parse benchmark file {}", path.display()))?;
+            if !benchmark.duration_hours.is_finite() {
+                anyhow::bail!(
+                    "Benchmark file {} has a non-numeric duration class ({})",
+                    path.display(),
+                    benchmark.duration_hours
+                );
+            }
+            classes.push(benchmark);
+        }
+
+        if classes.is_empty() {
+            anyhow::bail!("Benchmark file {} has no duration classes", path.display());
+        }
+
+        classes.sort_by(|a, b| a.duration_hours.total_cmp(&b.duration_hours));
+        Ok(Self { classes })
+    }
+
+    /// Load from BESS_BENCHMARKS_FILE (default bess_analysis/bess_peer_benchmarks.csv),
+    /// falling back to the single Modo market average when no file exists
+    pub fn load() -> Result<Self> {
+        let path = std::env::var("BESS_BENCHMARKS_FILE")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| PathBuf::from("bess_analysis/bess_peer_benchmarks.csv"));
+
+        if path.exists() {
+            Self::from_csv(&path)
+        } else {
+            Ok(Self::default())
+        }
+    }
+
+    /// Benchmark for the duration class closest to the battery's duration
+    /// (the shortest class when the duration is NaN)
+    pub fn for_duration(&self, duration_hours: f64) -> &DurationBenchmark {
+        self.classes
+            .iter()
+            .min_by(|a, b| {
+                let da = (a.duration_hours - duration_hours).abs();
+                let db = (b.duration_hours - duration_hours).abs();
+                da.total_cmp(&db)
+            })
+            .expect("benchmarks always hold at least one class")
+    }
+
+    pub fn classes(&self) -> &[DurationBenchmark] {
+        &self.classes
+    }
+}
+
+impl Default for PeerBenchmarks {
+    fn default() -> Self {
+        Self {
+            classes: vec![DurationBenchmark {
+                duration_hours: 2.0,
+                revenue_per_mw_year: 196_000.0,
+                source: "Modo Energy 2023 average".to_string(),
+            }],
+        }
+    }
+}
+
+/// Relative over/under-performance against a benchmark, in percent
+pub fn performance_vs_benchmark(revenue_per_mw_year: f64, benchmark: &DurationBenchmark) -> f64 {
+    if benchmark.revenue_per_mw_year > 0.0 {
+        (revenue_per_mw_year / benchmark.revenue_per_mw_year - 1.0) * 100.0
+    } else {
+        0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_benchmarks() -> tempfile::NamedTempFile {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(
+            file.path(),
+            "Duration_Hours,Revenue_Per_MW_Year,Source\n1,150000,1h peers\n2,196000,2h peers\n4,240000,4h peers\n",
+        )
+        .unwrap();
+        file
+    }
+
+    #[test]
+    fn test_battery_compared_to_its_duration_class() {
+        let file = write_benchmarks();
+        let benchmarks = PeerBenchmarks::from_csv(file.path()).unwrap();
+
+        assert_eq!(benchmarks.classes().len(), 3);
+        assert_eq!(benchmarks.for_duration(4.0).revenue_per_mw_year, 240_000.0);
+        assert_eq!(benchmarks.for_duration(1.0).revenue_per_mw_year, 150_000.0);
+        // Non-standard durations use the nearest class
+        assert_eq!(benchmarks.for_duration(3.6).revenue_per_mw_year, 240_000.0);
+    }
+
+    #[test]
+    fn test_non_finite_durations() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(file.path(), "Duration_Hours,Revenue_Per_MW_Year,Source\nNaN,150000,bad row\n").unwrap();
+        assert!(PeerBenchmarks::from_csv(file.path()).is_err());
+
+        let file = write_benchmarks();
+        let benchmarks = PeerBenchmarks::from_csv(file.path()).unwrap();
+        assert_eq!(benchmarks.for_duration(f64::NAN).revenue_per_mw_year, 150_000.0);
+    }
+
+    #[test]
+    fn test_performance_vs_benchmark() {
+        let file = write_benchmarks();
+        let benchmarks = PeerBenchmarks::from_csv(file.path()).unwrap();
+
+        let four_hour = benchmarks.for_duration(4.0);
+        assert!((performance_vs_benchmark(264_000.0, four_hour) - 10.0).abs() < 1e-9);
+        assert!((performance_vs_benchmark(216_000.0, four_hour) + 10.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_default_is_modo_average() {
+        let benchmarks = PeerBenchmarks::default();
+        assert_eq!(benchmarks.for_duration(4.0).revenue_per_mw_year, 196_000.0);
+    }
+}
@@ -1,18 +1,17 @@
 use anyhow::{Result, Context};
-use chrono::{NaiveDate, NaiveDateTime, Datelike, Timelike};
+use chrono::{NaiveDate, Datelike, Timelike};
 use polars::prelude::*;
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use indicatif::{ProgressBar, ProgressStyle};
 use rayon::prelude::*;
 
-#[derive(Debug, Clone)]
-pub struct BessResource {
-    pub name: String,
-    pub settlement_point: String,
-    pub capacity_mw: f64,
-    pub qse: String,
-}
+use crate::as_services::{service_revenues, AsService};
+use crate::csv_headers::read_csv;
+use crate::intervals::{central_datetime_ms, delivery_interval_datetime_ms, is_repeated_hour, parse_sced_timestamp, RT_PRICE_INTERVAL, SCED_DISPATCH_INTERVAL};
+use crate::master_list::{load_master_list, BessResource};
+use crate::resource_filter::ResourceFilter;
+use crate::output_prefix::OutputPrefix;
 
 #[derive(Debug, Clone)]
 pub struct BessAnnualRevenue {
@@ -37,17 +36,17 @@ pub struct BessCompleteAnalyzer {
 }
 
 impl BessCompleteAnalyzer {
-    pub fn new() -> Result<Self> {
+    pub fn new(output_prefix: &OutputPrefix) -> Result<Self> {
         // Set up paths
         let dam_disclosure_dir = PathBuf::from("/Users/enrico/data/ERCOT_data/60-Day_DAM_Disclosure_Reports/csv");
         let sced_disclosure_dir = PathBuf::from("/Users/enrico/data/ERCOT_data/60-Day_SCED_Disclosure_Reports/csv");
-        let price_data_dir = PathBuf::from("annual_output");
-        let output_dir = PathBuf::from("bess_complete_analysis");
+        let price_data_dir = output_prefix.input_path("annual_output");
+        let output_dir = output_prefix.output_dir("bess_complete_analysis");
         
         std::fs::create_dir_all(&output_dir)?;
         
         // Load BESS resources
-        let bess_resources = Self::load_bess_resources()?;
+        let bess_resources = Self::load_bess_resources(output_prefix)?;
         println!("📋 Loaded {} BESS resources", bess_resources.len());
         
         Ok(Self {
@@ -59,36 +58,22 @@ impl BessCompleteAnalyzer {
         })
     }
     
-    fn load_bess_resources() -> Result<HashMap<String, BessResource>> {
-        let mut resources = HashMap::new();
-        
-        let master_list_path = PathBuf::from("bess_analysis/bess_resources_master_list.csv");
-        if master_list_path.exists() {
-            let file = std::fs::File::open(&master_list_path)?;
-            let df = CsvReader::new(file).has_header(true).finish()?;
-            
-            let names = df.column("Resource_Name")?.utf8()?;
-            let settlement_points = df.column("Settlement_Point")?.utf8()?;
-            let capacities = df.column("Max_Capacity_MW")?.f64()?;
-            let qses = df.column("QSE").ok().and_then(|c| c.utf8().ok());
-            
-            for i in 0..df.height() {
-                if let (Some(name), Some(sp), Some(capacity)) = 
-                    (names.get(i), settlement_points.get(i), capacities.get(i)) {
-                    
-                    let qse = qses.as_ref().and_then(|q| q.get(i)).unwrap_or("UNKNOWN");
-                    
-                    resources.insert(name.to_string(), BessResource {
-                        name: name.to_string(),
-                        settlement_point: sp.to_string(),
-                        capacity_mw: capacity,
-                        qse: qse.to_string(),
-                    });
-                }
-            }
+    /// Keep only resources matching `--resource-regex`
+    pub fn with_resource_filter(mut self, filter: &ResourceFilter) -> Self {
+        filter.retain(&mut self.bess_resources);
+        self
+    }
+    
+    fn load_bess_resources(output_prefix: &OutputPrefix) -> Result<HashMap<String, BessResource>> {
+        let master_list_path = output_prefix.input_path("bess_analysis/bess_resources_master_list.csv");
+        if !master_list_path.exists() {
+            return Ok(HashMap::new());
         }
         
-        Ok(resources)
+        Ok(load_master_list(&master_list_path)?
+            .into_iter()
+            .map(|r| (r.name.clone(), r))
+            .collect())
     }
     
     pub fn analyze_all_years(&self) -> Result<()> {
@@ -215,7 +200,7 @@ impl BessCompleteAnalyzer {
     }
     
     fn process_dam_file(&self, file: &Path, annual_revenues: &mut HashMap<String, BessAnnualRevenue>) -> Result<()> {
-        let df = CsvReader::new(std::fs::File::open(file)?).has_header(true).finish()?;
+        let df = read_csv(file)?;
         
         // Filter for BESS resources
         if let Ok(resource_types) = df.column("Resource Type") {
@@ -254,102 +239,17 @@ impl BessCompleteAnalyzer {
     fn process_dam_as_awards(&self, df: &DataFrame, annual_revenues: &mut HashMap<String, BessAnnualRevenue>) -> Result<()> {
         let resources = df.column("Resource Name")?.utf8()?;
         
-        // RegUp
-        if let (Ok(awards), Ok(prices)) = (
-            df.column("RegUp Awarded"),
-            df.column("RegUp MCPC")
-        ) {
-            let awards_f64 = Self::parse_numeric_column(awards)?;
-            let prices_f64 = Self::parse_numeric_column(prices)?;
-            
-            for i in 0..df.height() {
-                if let (Some(resource), Some(award), Some(price)) = 
-                    (resources.get(i), awards_f64.get(i), prices_f64.get(i)) {
-                    
-                    if let Some(revenue) = annual_revenues.get_mut(resource) {
-                        revenue.reg_up_revenue += award * price;
-                    }
-                }
-            }
-        }
-        
-        // RegDown
-        if let (Ok(awards), Ok(prices)) = (
-            df.column("RegDown Awarded"),
-            df.column("RegDown MCPC")
-        ) {
-            let awards_f64 = Self::parse_numeric_column(awards)?;
-            let prices_f64 = Self::parse_numeric_column(prices)?;
-            
-            for i in 0..df.height() {
-                if let (Some(resource), Some(award), Some(price)) = 
-                    (resources.get(i), awards_f64.get(i), prices_f64.get(i)) {
-                    
-                    if let Some(revenue) = annual_revenues.get_mut(resource) {
-                        revenue.reg_down_revenue += award * price;
-                    }
-                }
-            }
-        }
-        
-        // RRS (combines RRSPFR, RRSFFR, RRSUFR)
-        let mut rrs_total_awards = vec![0.0; df.height()];
-        for rrs_type in ["RRSPFR Awarded", "RRSFFR Awarded", "RRSUFR Awarded"] {
-            if let Ok(awards) = df.column(rrs_type) {
-                let awards_f64 = Self::parse_numeric_column(awards)?;
-                for i in 0..df.height() {
-                    if let Some(award) = awards_f64.get(i) {
-                        rrs_total_awards[i] += award;
-                    }
-                }
-            }
-        }
-        
-        if let Ok(prices) = df.column("RRS MCPC") {
-            let prices_f64 = Self::parse_numeric_column(prices)?;
-            
-            for i in 0..df.height() {
-                if let (Some(resource), Some(price)) = (resources.get(i), prices_f64.get(i)) {
-                    if let Some(revenue) = annual_revenues.get_mut(resource) {
-                        revenue.spin_revenue += rrs_total_awards[i] * price;
-                    }
-                }
-            }
-        }
-        
-        // ECRS
-        if let (Ok(awards), Ok(prices)) = (
-            df.column("ECRSSD Awarded"),
-            df.column("ECRS MCPC")
-        ) {
-            let awards_f64 = Self::parse_numeric_column(awards)?;
-            let prices_f64 = Self::parse_numeric_column(prices)?;
-            
-            for i in 0..df.height() {
-                if let (Some(resource), Some(award), Some(price)) = 
-                    (resources.get(i), awards_f64.get(i), prices_f64.get(i)) {
-                    
-                    if let Some(revenue) = annual_revenues.get_mut(resource) {
-                        revenue.ecrs_revenue += award * price;
-                    }
-                }
-            }
-        }
-        
-        // NonSpin
-        if let (Ok(awards), Ok(prices)) = (
-            df.column("NonSpin Awarded"),
-            df.column("NonSpin MCPC")
-        ) {
-            let awards_f64 = Self::parse_numeric_column(awards)?;
-            let prices_f64 = Self::parse_numeric_column(prices)?;
-            
-            for i in 0..df.height() {
-                if let (Some(resource), Some(award), Some(price)) = 
-                    (resources.get(i), awards_f64.get(i), prices_f64.get(i)) {
-                    
-                    if let Some(revenue) = annual_revenues.get_mut(resource) {
-                        revenue.non_spin_revenue += award * price;
+        // Combined "RRS Awarded" (older files) and the RRSPFR/RRSFFR/RRSUFR
+        // subtypes (newer) both land in spin_revenue
+        for (service, revenues) in service_revenues(df)? {
+            for (i, amount) in revenues.into_iter().enumerate() {
+                if let Some(revenue) = resources.get(i).and_then(|r| annual_revenues.get_mut(r)) {
+                    match service {
+                        AsService::RegUp => revenue.reg_up_revenue += amount,
+                        AsService::RegDown => revenue.reg_down_revenue += amount,
+                        AsService::Rrs => revenue.spin_revenue += amount,
+                        AsService::Ecrs => revenue.ecrs_revenue += amount,
+                        AsService::NonSpin => revenue.non_spin_revenue += amount,
                     }
                 }
             }
@@ -368,7 +268,8 @@ impl BessCompleteAnalyzer {
         
         // Load RT prices from Parquet files
         let rt_prices = self.load_rt_prices(year)?;
-        println!("    Loaded {} RT price points", rt_prices.len());
+        println!("    Loaded {} RT price points", rt_prices.height());
+        let settlement_points = self.settlement_point_frame()?;
         
         let pb = ProgressBar::new(sced_files.len() as u64);
         pb.set_style(ProgressStyle::default_bar()
@@ -377,98 +278,112 @@ impl BessCompleteAnalyzer {
         
         for file in sced_files {
             pb.inc(1);
-            self.process_sced_file(&file, &rt_prices, annual_revenues)?;
+            self.process_sced_file(&file, &settlement_points, &rt_prices, annual_revenues)?;
         }
         
         pb.finish();
         Ok(())
     }
     
-    fn load_rt_prices(&self, year: i32) -> Result<HashMap<(String, NaiveDateTime), f64>> {
-        let mut prices = HashMap::new();
-        
+    /// `Resource_Name` → `Settlement_Point` for every BESS resource
+    fn settlement_point_frame(&self) -> Result<DataFrame> {
+        let (names, points): (Vec<&str>, Vec<&str>) = self.bess_resources.iter()
+            .map(|(name, resource)| (name.as_str(), resource.settlement_point.as_str()))
+            .unzip();
+        Ok(DataFrame::new(vec![
+            Series::new("Resource_Name", names),
+            Series::new("Settlement_Point", points),
+        ])?)
+    }
+    
+    /// RT prices as `Settlement_Point`, `Interval_Start` (UTC ms, as the
+    /// `datetime` column is written) and `RT_Price`, one row per key
+    fn load_rt_prices(&self, year: i32) -> Result<DataFrame> {
         let file_path = self.price_data_dir
             .join("Settlement_Point_Prices_at_Resource_Nodes__Hubs_and_Load_Zones")
             .join(format!("Settlement_Point_Prices_at_Resource_Nodes__Hubs_and_Load_Zones_{}.parquet", year));
         
-        if !file_path.exists() {
-            return Ok(prices);
-        }
+        let mut points = Vec::new();
+        let mut starts = Vec::new();
+        let mut prices = Vec::new();
         
-        let file = std::fs::File::open(&file_path)?;
-        let df = ParquetReader::new(file).finish()?;
-        
-        if let (Ok(dates), Ok(hours), Ok(intervals), Ok(sps), Ok(prices_col)) = (
-            df.column("DeliveryDate"),
-            df.column("DeliveryHour"),
-            df.column("DeliveryInterval"),
-            df.column("SettlementPointName"),
-            df.column("SettlementPointPrice")
-        ) {
-            let dates_str = dates.utf8()?;
-            let hours_i64 = hours.i64()?;
-            let intervals_i64 = intervals.i64()?;
-            let sps_str = sps.utf8()?;
-            let prices_f64 = prices_col.f64()?;
+        if file_path.exists() {
+            let file = std::fs::File::open(&file_path)?;
+            let df = ParquetReader::new(file).finish()?;
             
-            for i in 0..df.height().min(50_000_000) {  // Limit for memory
-                if let (Some(date_str), Some(hour), Some(interval), Some(sp), Some(price)) = 
-                    (dates_str.get(i), hours_i64.get(i), intervals_i64.get(i), sps_str.get(i), prices_f64.get(i)) {
-                    
-                    if let Ok(date) = NaiveDate::parse_from_str(date_str, "%m/%d/%Y") {
-                        // Create timestamp for 15-minute interval
-                        // RT prices are 15-minute intervals, not 5-minute
-                        let minutes = (interval - 1) * 15;  // intervals are 1-4 within each hour
-                        if let Some(timestamp) = date.and_hms_opt(hour as u32, minutes as u32, 0) {
-                            prices.insert((sp.to_string(), timestamp), price);
+            if let (Ok(dates), Ok(hours), Ok(intervals), Ok(sps), Ok(prices_col)) = (
+                df.column("DeliveryDate"),
+                df.column("DeliveryHour"),
+                df.column("DeliveryInterval"),
+                df.column("SettlementPointName"),
+                df.column("SettlementPointPrice")
+            ) {
+                // The repeated fall-back hour is flagged so it doesn't collide with the first
+                let dst_flags = df.column("DSTFlag").ok().and_then(|c| c.utf8().ok());
+                let rows = dates.utf8()?.into_iter()
+                    .zip(hours.i64()?.into_iter())
+                    .zip(intervals.i64()?.into_iter())
+                    .zip(sps.utf8()?.into_iter())
+                    .zip(prices_col.f64()?.into_iter())
+                    .enumerate()
+                    .take(50_000_000);  // Limit for memory
+                
+                let mut last_date: Option<(&str, Option<NaiveDate>)> = None;
+                for (i, ((((date_str, hour), interval), sp), price)) in rows {
+                    let (Some(date_str), Some(hour), Some(interval), Some(sp), Some(price)) = (date_str, hour, interval, sp, price) else {
+                        continue;
+                    };
+                    let date = match last_date {
+                        Some((last, date)) if last == date_str => date,
+                        _ => {
+                            let date = NaiveDate::parse_from_str(date_str, "%m/%d/%Y").ok();
+                            last_date = Some((date_str, date));
+                            date
                         }
+                    };
+                    
+                    // Hour ending 1-24 and 15-minute intervals 1-4; HE1 starts at midnight
+                    let repeated_hour = is_repeated_hour(dst_flags.and_then(|f| f.get(i)));
+                    let start = date.and_then(|d| {
+                        delivery_interval_datetime_ms(d, u32::try_from(hour).ok()?, u32::try_from(interval).ok()?, repeated_hour)
+                    });
+                    if let Some(start) = start {
+                        points.push(sp.to_string());
+                        starts.push(start);
+                        prices.push(price);
                     }
                 }
             }
         }
         
+        // Later rows win, as they would overwriting a map entry
+        let prices = DataFrame::new(vec![
+            Series::new("Settlement_Point", points),
+            Series::new("Interval_Start", starts),
+            Series::new("RT_Price", prices),
+        ])?
+            .lazy()
+            .unique_stable(Some(vec!["Settlement_Point".to_string(), "Interval_Start".to_string()]), UniqueKeepStrategy::Last)
+            .collect()?;
         Ok(prices)
     }
     
-    fn process_sced_file(&self, file: &Path, rt_prices: &HashMap<(String, NaiveDateTime), f64>, 
+    fn process_sced_file(&self, file: &Path, settlement_points: &DataFrame, rt_prices: &DataFrame,
                          annual_revenues: &mut HashMap<String, BessAnnualRevenue>) -> Result<()> {
-        let df = CsvReader::new(std::fs::File::open(file)?).has_header(true).finish()?;
+        let df = read_csv(file)?;
         
         // Filter for BESS resources
         if let Ok(resource_types) = df.column("Resource Type") {
             let mask = resource_types.utf8()?.equal("PWRSTR");
             
             if let Ok(filtered) = df.filter(&mask) {
-                // Get base point (dispatch) data
-                if let (Ok(timestamps), Ok(resources), Ok(base_points)) = (
-                    filtered.column("SCED Time Stamp"),
-                    filtered.column("Resource Name"),
-                    filtered.column("Base Point")
-                ) {
-                    let timestamps_str = timestamps.utf8()?;
-                    let resources_str = resources.utf8()?;
-                    let base_points_f64 = Self::parse_numeric_column(base_points)?;
-                    
-                    for i in 0..filtered.height() {
-                        if let (Some(timestamp_str), Some(resource_name), Some(base_point)) = 
-                            (timestamps_str.get(i), resources_str.get(i), base_points_f64.get(i)) {
-                            
-                            // Parse timestamp
-                            if let Ok(timestamp) = NaiveDateTime::parse_from_str(timestamp_str, "%m/%d/%Y %H:%M:%S") {
-                                // Get price for this interval
-                                if let Some(resource) = self.bess_resources.get(resource_name) {
-                                    let price_key = (resource.settlement_point.clone(), timestamp);
-                                    
-                                    if let Some(&price) = rt_prices.get(&price_key) {
-                                        if let Some(revenue) = annual_revenues.get_mut(resource_name) {
-                                            // RT revenue = MW * $/MWh * hours
-                                            // SCED data is 5-minute, but RT prices are 15-minute
-                                            // Use 5-minute duration for SCED dispatch
-                                            revenue.rt_energy_revenue += base_point * price * (5.0 / 60.0);
-                                        }
-                                    }
-                                }
-                            }
+                let revenues = sced_rt_revenue(&filtered, settlement_points, rt_prices)?;
+                let names = revenues.column("Resource_Name")?.utf8()?;
+                let rt_revenues = revenues.column("RT_Revenue")?.f64()?;
+                for (name, rt_revenue) in names.into_iter().zip(rt_revenues.into_iter()) {
+                    if let (Some(name), Some(rt_revenue)) = (name, rt_revenue) {
+                        if let Some(revenue) = annual_revenues.get_mut(name) {
+                            revenue.rt_energy_revenue += rt_revenue;
                         }
                     }
                 }
@@ -597,8 +512,147 @@ impl BessCompleteAnalyzer {
     }
 }
 
-pub fn run_complete_bess_analysis() -> Result<()> {
-    let analyzer = BessCompleteAnalyzer::new()?;
+/// RT energy revenue per `Resource_Name` from SCED base points (`SCED Time Stamp`,
+/// `Resource Name`, `Base Point`), joined to the resource's settlement point and
+/// then to `rt_prices` on (settlement point, interval start). Each base point
+/// settles at the price of the 15-minute interval containing it, so SCED runs
+/// off the price grid (e.g. 00:20) count too. Dispatch without a matching
+/// price earns nothing.
+pub fn sced_rt_revenue(sced: &DataFrame, settlement_points: &DataFrame, rt_prices: &DataFrame) -> Result<DataFrame> {
+    let (Ok(timestamps), Ok(resources), Ok(base_points)) = (
+        sced.column("SCED Time Stamp"),
+        sced.column("Resource Name"),
+        sced.column("Base Point")
+    ) else {
+        return Ok(DataFrame::new(vec![
+            Series::new("Resource_Name", Vec::<String>::new()),
+            Series::new("RT_Revenue", Vec::<f64>::new()),
+        ])?);
+    };
+    
+    // SCED runs share time stamps across resources, so parse each run once.
+    // Time stamps are Central wall clock; the flag marks the repeated fall-back hour.
+    let repeated_hours = sced.column("Repeated Hour Flag").ok().and_then(|c| c.utf8().ok());
+    let mut last: Option<(&str, bool, Option<i64>)> = None;
+    let starts: Int64Chunked = timestamps.utf8()?.into_iter()
+        .enumerate()
+        .map(|(i, ts)| {
+            let ts = ts?;
+            let repeated_hour = is_repeated_hour(repeated_hours.and_then(|f| f.get(i)));
+            match last {
+                Some((prev, prev_repeated, start)) if prev == ts && prev_repeated == repeated_hour => start,
+                _ => {
+                    let start = parse_sced_timestamp(ts)
+                        .and_then(|t| central_datetime_ms(t, repeated_hour))
+                        .map(|ms| ms - ms.rem_euclid(RT_PRICE_INTERVAL.minutes() as i64 * 60_000));
+                    last = Some((ts, repeated_hour, start));
+                    start
+                }
+            }
+        })
+        .collect();
+    
+    let dispatch = DataFrame::new(vec![
+        resources.clone().with_name("Resource_Name"),
+        starts.into_series().with_name("Interval_Start"),
+        BessCompleteAnalyzer::parse_numeric_column(base_points)?.into_series().with_name("Base_Point"),
+    ])?;
+    
+    let revenues = dispatch.lazy()
+        .join(settlement_points.clone().lazy(), [col("Resource_Name")], [col("Resource_Name")], JoinArgs::new(JoinType::Inner))
+        .join(
+            rt_prices.clone().lazy(),
+            [col("Settlement_Point"), col("Interval_Start")],
+            [col("Settlement_Point"), col("Interval_Start")],
+            JoinArgs::new(JoinType::Inner),
+        )
+        // SCED data is 5-minute, but RT prices are 15-minute
+        .with_column((col("Base_Point") * col("RT_Price") * lit(SCED_DISPATCH_INTERVAL.hours())).alias("RT_Revenue"))
+        .group_by([col("Resource_Name")])
+        .agg([col("RT_Revenue").sum()])
+        .collect()?;
+    Ok(revenues)
+}
+
+pub fn run_complete_bess_analysis(resource_filter: &ResourceFilter, output_prefix: &OutputPrefix) -> Result<()> {
+    let analyzer = BessCompleteAnalyzer::new(output_prefix)?.with_resource_filter(resource_filter);
     analyzer.analyze_all_years()?;
     Ok(())
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDateTime;
+    use crate::intervals::interval_revenue;
+
+    #[test]
+    fn test_joined_revenue_matches_per_row_lookup() {
+        let settlement_points = df! {
+            "Resource_Name" => ["BATCAVE_BES1", "GAMBIT_BESS1"],
+            "Settlement_Point" => ["BATCAVE_RN", "HB_NORTH"],
+        }.unwrap();
+
+        // 15-minute prices for the first hour at both points, with a
+        // superseded duplicate row for BATCAVE_RN at 00:15
+        let start = |minute: u32| NaiveDate::from_ymd_opt(2024, 5, 1).unwrap()
+            .and_hms_opt(0, minute, 0).unwrap();
+        let mut price_rows: Vec<(&str, NaiveDateTime, f64)> = Vec::new();
+        for (i, minute) in [0, 15, 30, 45].into_iter().enumerate() {
+            price_rows.push(("BATCAVE_RN", start(minute), 20.0 + i as f64 * 10.0));
+            price_rows.push(("HB_NORTH", start(minute), -5.0 + i as f64));
+        }
+        price_rows.insert(2, ("BATCAVE_RN", start(15), 999.0));
+        let rt_prices = DataFrame::new(vec![
+            Series::new("Settlement_Point", price_rows.iter().map(|r| r.0).collect::<Vec<_>>()),
+            Series::new("Interval_Start", price_rows.iter().map(|r| central_datetime_ms(r.1, false).unwrap()).collect::<Vec<_>>()),
+            Series::new("RT_Price", price_rows.iter().map(|r| r.2).collect::<Vec<_>>()),
+        ]).unwrap()
+            .lazy()
+            .unique_stable(Some(vec!["Settlement_Point".to_string(), "Interval_Start".to_string()]), UniqueKeepStrategy::Last)
+            .collect()
+            .unwrap();
+
+        // Base points on and off the price grid, plus a non-BESS resource
+        let sced_rows = [
+            ("05/01/2024 00:00:00", "BATCAVE_BES1", "50"),
+            ("05/01/2024 00:15:00", "BATCAVE_BES1", "-25.5"),
+            ("05/01/2024 00:20:00", "BATCAVE_BES1", "40"),
+            ("05/01/2024 00:30:00", "GAMBIT_BESS1", "10"),
+            ("05/01/2024 00:45:00", "GAMBIT_BESS1", ""),
+            ("05/01/2024 00:45:00", "SOLAR_UNIT1", "80"),
+        ];
+        let sced = df! {
+            "SCED Time Stamp" => sced_rows.iter().map(|r| r.0).collect::<Vec<_>>(),
+            "Resource Name" => sced_rows.iter().map(|r| r.1).collect::<Vec<_>>(),
+            "Base Point" => sced_rows.iter().map(|r| r.2).collect::<Vec<_>>(),
+        }.unwrap();
+
+        let revenues = sced_rt_revenue(&sced, &settlement_points, &rt_prices).unwrap();
+        let joined: HashMap<&str, f64> = revenues.column("Resource_Name").unwrap().utf8().unwrap().into_iter()
+            .zip(revenues.column("RT_Revenue").unwrap().f64().unwrap().into_iter())
+            .map(|(name, revenue)| (name.unwrap(), revenue.unwrap()))
+            .collect();
+
+        // Per-row lookup in a (settlement point, interval start) map
+        let price_map: HashMap<(&str, NaiveDateTime), f64> = price_rows.iter().map(|r| ((r.0, r.1), r.2)).collect();
+        let sp = |name: &str| if name == "BATCAVE_BES1" { "BATCAVE_RN" } else { "HB_NORTH" };
+        let mut expected: HashMap<&str, f64> = HashMap::new();
+        for (ts, name, base_point) in sced_rows.iter().filter(|r| r.1 != "SOLAR_UNIT1") {
+            let timestamp = NaiveDateTime::parse_from_str(ts, "%m/%d/%Y %H:%M:%S").unwrap();
+            let timestamp = timestamp - chrono::Duration::minutes(timestamp.minute() as i64 % 15);
+            let base_point = if base_point.is_empty() { 0.0 } else { base_point.parse().unwrap() };
+            if let Some(price) = price_map.get(&(sp(name), timestamp)) {
+                *expected.entry(name).or_insert(0.0) += interval_revenue(base_point, *price, SCED_DISPATCH_INTERVAL);
+            }
+        }
+
+        assert_eq!(joined.len(), expected.len());
+        for (name, revenue) in &expected {
+            assert!((joined[name] - revenue).abs() < 1e-9, "{}: {} vs {}", name, joined[name], revenue);
+        }
+        // 50 MW at $20, then -25.5 MW and the off-grid 00:20 run's 40 MW both in
+        // the 00:15 interval at $30 (not the superseded $999), for 5 minutes each
+        assert!((joined["BATCAVE_BES1"] - (50.0 * 20.0 - 25.5 * 30.0 + 40.0 * 30.0) / 12.0).abs() < 1e-9);
+    }
+}
@@ -1,16 +1,15 @@
 use anyhow::Result;
-use chrono::{NaiveDate, NaiveDateTime, Timelike};
+use chrono::{NaiveDate, NaiveDateTime};
 use polars::prelude::*;
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
-#[derive(Debug, Clone)]
-pub struct BessResource {
-    pub name: String,
-    pub settlement_point: String,
-    pub capacity_mw: f64,
-    pub duration_hours: f64,  // Assumed 2 hours if not specified
-}
+use crate::bess_analyzer::ResourceDurations;
+use crate::master_list::{load_master_list, BessResource};
+use crate::intervals::{parse_sced_timestamp, IntervalLength};
+use crate::price_index::PriceIndex;
+use crate::resource_filter::ResourceFilter;
+use crate::output_prefix::OutputPrefix;
 
 #[derive(Debug, Clone)]
 pub struct DailyRevenue {
@@ -46,6 +45,7 @@ pub struct AnnualSummary {
 
 pub struct BessComprehensiveCalculator {
     bess_resources: HashMap<String, BessResource>,
+    resource_durations: ResourceDurations,
     annual_output_dir: PathBuf,
     disclosure_data_dir: PathBuf,
     output_dir: PathBuf,
@@ -56,55 +56,45 @@ impl BessComprehensiveCalculator {
         bess_master_list_path: &Path,
         annual_output_dir: PathBuf,
         disclosure_data_dir: PathBuf,
+        output_dir: PathBuf,
     ) -> Result<Self> {
         // Create output directory
-        let output_dir = PathBuf::from("bess_comprehensive_analysis");
         std::fs::create_dir_all(&output_dir)?;
 
         // Load BESS resources
-        let bess_resources = Self::load_bess_resources(bess_master_list_path)?;
+        let master_list = load_master_list(bess_master_list_path)?;
+        let resource_durations = ResourceDurations::from_resources(&master_list);
+        let bess_resources: HashMap<String, BessResource> = master_list.into_iter()
+            .map(|r| (r.name.clone(), r))
+            .collect();
         
         println!("✅ Loaded {} BESS resources", bess_resources.len());
         
         Ok(Self {
             bess_resources,
+            resource_durations,
             annual_output_dir,
             disclosure_data_dir,
             output_dir,
         })
     }
 
-    fn load_bess_resources(path: &Path) -> Result<HashMap<String, BessResource>> {
-        let file = std::fs::File::open(path)?;
-        let df = CsvReader::new(file)
-            .has_header(true)
-            .finish()?;
-        
-        let mut resources = HashMap::new();
-        
-        let names = df.column("Resource_Name")?.utf8()?;
-        let settlement_points = df.column("Settlement_Point")?.utf8()?;
-        let capacities = df.column("Max_Capacity_MW")?.f64()?;
-        
-        for i in 0..df.height() {
-            if let (Some(name), Some(sp), Some(capacity)) = 
-                (names.get(i), settlement_points.get(i), capacities.get(i)) {
-                
-                resources.insert(name.to_string(), BessResource {
-                    name: name.to_string(),
-                    settlement_point: sp.to_string(),
-                    capacity_mw: capacity,
-                    duration_hours: 2.0,  // Default assumption
-                });
-            }
-        }
-        
-        Ok(resources)
+    /// Duration assumed for resources the master list gives none for
+    pub fn with_default_duration_hours(mut self, hours: f64) -> Self {
+        self.resource_durations = self.resource_durations.with_default_hours(hours);
+        self
+    }
+
+    /// Keep only resources matching `--resource-regex`
+    pub fn with_resource_filter(mut self, filter: &ResourceFilter) -> Self {
+        filter.retain(&mut self.bess_resources);
+        self
     }
 
     pub fn calculate_all_revenues(&self) -> Result<()> {
         println!("\n💰 ERCOT BESS Comprehensive Revenue Analysis");
         println!("{}", "=".repeat(80));
+        self.resource_durations.warn_defaults(self.bess_resources.keys());
         
         // Get available years from parquet files
         let years = self.get_available_years()?;
@@ -194,8 +184,8 @@ impl BessComprehensiveCalculator {
         Ok(annual_summaries)
     }
 
-    fn load_rt_prices(&self, year: i32) -> Result<HashMap<(String, NaiveDate, u32), f64>> {
-        let mut prices = HashMap::new();
+    fn load_rt_prices(&self, year: i32) -> Result<PriceIndex> {
+        let mut prices = PriceIndex::new(IntervalLength::FiveMinute);
         
         let file_path = self.annual_output_dir
             .join("Settlement_Point_Prices_at_Resource_Nodes__Hubs_and_Load_Zones")
@@ -223,11 +213,8 @@ impl BessComprehensiveCalculator {
                     (timestamps_str.get(i), sps_str.get(i), prices_f64.get(i)) {
                     
                     // Parse timestamp
-                    if let Ok(timestamp) = NaiveDateTime::parse_from_str(timestamp_str, "%m/%d/%Y %H:%M:%S") {
-                        let date = timestamp.date();
-                        let interval = (timestamp.hour() * 60 + timestamp.minute()) / 5; // 5-minute intervals
-                        
-                        prices.insert((sp.to_string(), date, interval), price);
+                    if let Some(timestamp) = parse_sced_timestamp(timestamp_str) {
+                        prices.insert(sp, IntervalLength::FiveMinute.epoch_index(&timestamp.and_utc()), price); // 5-minute intervals
                     }
                 }
             }
@@ -236,8 +223,8 @@ impl BessComprehensiveCalculator {
         Ok(prices)
     }
 
-    fn load_dam_prices(&self, year: i32) -> Result<HashMap<(String, NaiveDate, u32), f64>> {
-        let mut prices = HashMap::new();
+    fn load_dam_prices(&self, year: i32) -> Result<PriceIndex> {
+        let mut prices = PriceIndex::new(IntervalLength::Hourly);
         
         // Try DAM LMP file
         let file_path = self.annual_output_dir
@@ -269,7 +256,7 @@ impl BessComprehensiveCalculator {
                     
                     // Parse date
                     if let Ok(date) = NaiveDate::parse_from_str(date_str, "%m/%d/%Y") {
-                        prices.insert((bus.to_string(), date, hour as u32), lmp);
+                        prices.insert(bus, dam_hour_index(date, hour as u32), lmp);
                     }
                 }
             }
@@ -309,8 +296,8 @@ impl BessComprehensiveCalculator {
         &self,
         resource: &BessResource,
         year: i32,
-        _rt_prices: &HashMap<(String, NaiveDate, u32), f64>,
-        dam_prices: &HashMap<(String, NaiveDate, u32), f64>,
+        _rt_prices: &PriceIndex,
+        dam_prices: &PriceIndex,
         _as_prices: &HashMap<(NaiveDate, u32, String), f64>,
         _dispatch_data: &[BessDispatch],
     ) -> Result<AnnualSummary> {
@@ -328,7 +315,7 @@ impl BessComprehensiveCalculator {
             // Get DAM prices for this day
             let mut daily_dam_prices = Vec::new();
             for hour in 1..=24 {
-                if let Some(&price) = dam_prices.get(&(resource.settlement_point.clone(), current_date, hour)) {
+                if let Some(price) = dam_prices.get(&resource.settlement_point, dam_hour_index(current_date, hour)) {
                     daily_dam_prices.push((hour, price));
                 }
             }
@@ -362,8 +349,9 @@ impl BessComprehensiveCalculator {
         
         let total_revenue = total_energy_revenue + total_as_revenue;
         let revenue_per_mw = if resource.capacity_mw > 0.0 { total_revenue / resource.capacity_mw } else { 0.0 };
-        let revenue_per_mwh = if resource.capacity_mw > 0.0 && resource.duration_hours > 0.0 {
-            total_revenue / (resource.capacity_mw * resource.duration_hours)
+        let duration_hours = self.resource_durations.get(&resource.name);
+        let revenue_per_mwh = if resource.capacity_mw > 0.0 && duration_hours > 0.0 {
+            total_revenue / (resource.capacity_mw * duration_hours)
         } else {
             0.0
         };
@@ -490,16 +478,29 @@ struct BessDispatch {
     service_type: String,
 }
 
-pub fn run_comprehensive_bess_analysis() -> Result<()> {
-    let master_list_path = PathBuf::from("bess_analysis/bess_resources_master_list.csv");
-    let annual_output_dir = PathBuf::from("annual_output");
-    let disclosure_data_dir = PathBuf::from("disclosure_data");
+/// Price key for DAM hour ending `hour_ending` (1-24) on `date`, by wall clock
+fn dam_hour_index(date: NaiveDate, hour_ending: u32) -> i64 {
+    let hour_start = date.and_hms_opt(0, 0, 0).unwrap() + chrono::Duration::hours(hour_ending as i64 - 1);
+    IntervalLength::Hourly.epoch_index(&hour_start.and_utc())
+}
+
+pub fn run_comprehensive_bess_analysis(
+    default_duration_hours: f64,
+    resource_filter: &ResourceFilter,
+    output_prefix: &OutputPrefix,
+) -> Result<()> {
+    let master_list_path = output_prefix.input_path("bess_analysis/bess_resources_master_list.csv");
+    let annual_output_dir = output_prefix.input_path("annual_output");
+    let disclosure_data_dir = output_prefix.input_path("disclosure_data");
     
     let calculator = BessComprehensiveCalculator::new(
         &master_list_path,
         annual_output_dir,
         disclosure_data_dir,
-    )?;
+        output_prefix.output_dir("bess_comprehensive_analysis"),
+    )?
+    .with_default_duration_hours(default_duration_hours)
+    .with_resource_filter(resource_filter);
     
     calculator.calculate_all_revenues()?;
     
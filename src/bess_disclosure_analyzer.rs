@@ -1,21 +1,23 @@
 use anyhow::{Result, Context};
-use chrono::{NaiveDate, NaiveDateTime, Datelike, Timelike};
+use chrono::{NaiveDate, Datelike};
 use polars::prelude::*;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::path::{Path, PathBuf};
 use indicatif::{ProgressBar, ProgressStyle};
 use ::zip::ZipArchive;
 use std::fs::File;
 use std::io::copy;
 
-#[derive(Debug, Clone)]
-pub struct BessResource {
-    pub name: String,
-    pub settlement_point: String,
-    pub capacity_mw: f64,
-    pub duration_hours: f64,
-    pub qse: String,
-}
+use crate::bess_analyzer::ResourceDurations;
+use crate::csv_headers::read_csv;
+use crate::date_range::DateRange;
+use crate::fiscal_year::FiscalCalendar;
+use crate::master_list::{load_master_list, BessResource};
+use crate::intervals::{is_repeated_hour, parse_sced_timestamp, RT_PRICE_INTERVAL, SCED_DISPATCH_INTERVAL};
+use crate::price_index::{MergeOverwrites, PriceIndex};
+use crate::price_reader::read_rt_prices;
+use crate::resource_filter::ResourceFilter;
+use crate::output_prefix::OutputPrefix;
 
 #[derive(Debug, Clone)]
 pub struct DailyRevenue {
@@ -54,6 +56,7 @@ pub struct MonthlyRevenue {
 #[derive(Debug, Clone)]
 pub struct AnnualRevenue {
     pub resource_name: String,
+    /// Fiscal year when rolled up with a fiscal calendar
     pub year: i32,
     pub capacity_mw: f64,
     pub rt_energy_revenue: f64,
@@ -74,7 +77,10 @@ pub struct BessDisclosureAnalyzer {
     price_data_dir: PathBuf,
     output_dir: PathBuf,
     bess_resources: HashMap<String, BessResource>,
-    rt_prices: HashMap<(String, NaiveDate, u32), f64>,
+    resource_durations: ResourceDurations,
+    fiscal_calendar: FiscalCalendar,
+    date_range: DateRange,
+    rt_prices: PriceIndex,
     dam_prices: HashMap<(String, NaiveDate, u32), f64>,
     as_clearing_prices: HashMap<(String, NaiveDate, u32), f64>, // service_type, date, hour
 }
@@ -84,12 +90,16 @@ impl BessDisclosureAnalyzer {
         disclosure_dir: PathBuf,
         price_data_dir: PathBuf,
         bess_master_list_path: &Path,
+        output_dir: PathBuf,
     ) -> Result<Self> {
-        let output_dir = PathBuf::from("bess_disclosure_analysis");
         std::fs::create_dir_all(&output_dir)?;
         
         // Load BESS resources
-        let bess_resources = Self::load_bess_resources(bess_master_list_path)?;
+        let master_list = load_master_list(bess_master_list_path)?;
+        let resource_durations = ResourceDurations::from_resources(&master_list);
+        let bess_resources: HashMap<String, BessResource> = master_list.into_iter()
+            .map(|r| (r.name.clone(), r))
+            .collect();
         println!("📋 Loaded {} BESS resources", bess_resources.len());
         
         Ok(Self {
@@ -97,47 +107,38 @@ impl BessDisclosureAnalyzer {
             price_data_dir,
             output_dir,
             bess_resources,
-            rt_prices: HashMap::new(),
+            resource_durations,
+            fiscal_calendar: FiscalCalendar::default(),
+            date_range: DateRange::default(),
+            rt_prices: PriceIndex::new(RT_PRICE_INTERVAL),
             dam_prices: HashMap::new(),
             as_clearing_prices: HashMap::new(),
         })
     }
     
-    fn load_bess_resources(path: &Path) -> Result<HashMap<String, BessResource>> {
-        let file = std::fs::File::open(path)?;
-        let df = CsvReader::new(file)
-            .has_header(true)
-            .finish()?;
-        
-        let mut resources = HashMap::new();
-        
-        let names = df.column("Resource_Name")?.utf8()?;
-        let settlement_points = df.column("Settlement_Point")?.utf8()?;
-        let capacities = df.column("Max_Capacity_MW")?.f64()?;
-        let qses = df.column("QSE").ok().and_then(|c| c.utf8().ok());
-        
-        for i in 0..df.height() {
-            if let (Some(name), Some(sp), Some(capacity)) = 
-                (names.get(i), settlement_points.get(i), capacities.get(i)) {
-                
-                let qse = qses.as_ref().and_then(|q| q.get(i)).unwrap_or("UNKNOWN");
-                
-                resources.insert(name.to_string(), BessResource {
-                    name: name.to_string(),
-                    settlement_point: sp.to_string(),
-                    capacity_mw: capacity,
-                    duration_hours: 2.0, // Default assumption
-                    qse: qse.to_string(),
-                });
-            }
-        }
-        
-        Ok(resources)
+    /// Duration assumed for resources the master list gives none for
+    pub fn with_default_duration_hours(mut self, hours: f64) -> Self {
+        self.resource_durations = self.resource_durations.with_default_hours(hours);
+        self
+    }
+    
+    /// Keep only resources matching `--resource-regex`
+    pub fn with_resource_filter(mut self, filter: &ResourceFilter) -> Self {
+        filter.retain(&mut self.bess_resources);
+        self
+    }
+    
+    /// Roll annual revenue up by fiscal rather than calendar year
+    pub fn with_fiscal_calendar(mut self, fiscal_calendar: FiscalCalendar) -> Self {
+        self.fiscal_calendar = fiscal_calendar;
+        self
     }
     
     pub fn analyze_all_revenues(&mut self) -> Result<()> {
         println!("\n💰 ERCOT BESS Revenue Analysis from 60-Day Disclosures");
         println!("{}", "=".repeat(80));
+        self.resource_durations.warn_defaults(self.bess_resources.keys());
+        self.date_range.print_summary();
         
         // Extract and prepare data
         self.prepare_disclosure_data()?;
@@ -151,7 +152,6 @@ impl BessDisclosureAnalyzer {
         
         let mut all_daily_revenues = Vec::new();
         let mut all_monthly_revenues = Vec::new();
-        let mut all_annual_revenues = Vec::new();
         
         for year in years {
             println!("\n📊 Processing year {}", year);
@@ -162,18 +162,18 @@ impl BessDisclosureAnalyzer {
             // Aggregate to monthly
             let monthly_revenues = self.aggregate_to_monthly(&daily_revenues);
             
-            // Aggregate to annual
-            let annual_revenues = self.aggregate_to_annual(&monthly_revenues);
-            
             all_daily_revenues.extend(daily_revenues);
             all_monthly_revenues.extend(monthly_revenues);
-            all_annual_revenues.extend(annual_revenues);
         }
         
+        // Aggregate to annual once all months are in, since a fiscal year
+        // spans two calendar years
+        let all_annual_revenues = self.aggregate_to_annual(&all_monthly_revenues);
+        
         // Generate reports
         self.generate_comprehensive_report(&all_annual_revenues)?;
         self.save_all_results(&all_daily_revenues, &all_monthly_revenues, &all_annual_revenues)?;
-        self.generate_cumulative_revenue_chart(&all_annual_revenues)?;
+        self.generate_cumulative_revenue_chart(&all_monthly_revenues)?;
         
         Ok(())
     }
@@ -256,34 +256,19 @@ impl BessDisclosureAnalyzer {
                 .filter_map(Result::ok)
                 .collect();
             
+            let mut overwrites = MergeOverwrites::default();
             for file in files {
-                let df = ParquetReader::new(std::fs::File::open(&file)?).finish()?;
-                
-                if let (Ok(dates), Ok(hours), Ok(intervals), Ok(sps), Ok(prices)) = (
-                    df.column("DeliveryDate"),
-                    df.column("DeliveryHour"),
-                    df.column("DeliveryInterval"),
-                    df.column("SettlementPointName"),
-                    df.column("SettlementPointPrice")
-                ) {
-                    let dates_str = dates.utf8()?;
-                    let hours_i64 = hours.i64()?;
-                    let intervals_i64 = intervals.i64()?;
-                    let sps_str = sps.utf8()?;
-                    let prices_f64 = prices.f64()?;
-                    
-                    for i in 0..df.height().min(10_000_000) {
-                        if let (Some(date_str), Some(hour), Some(interval), Some(sp), Some(price)) = 
-                            (dates_str.get(i), hours_i64.get(i), intervals_i64.get(i), sps_str.get(i), prices_f64.get(i)) {
-                            
-                            if let Ok(date) = NaiveDate::parse_from_str(date_str, "%m/%d/%Y") {
-                                let interval_idx = (hour as u32) * 12 + (interval as u32 - 1) * 3;
-                                self.rt_prices.insert((sp.to_string(), date, interval_idx), price);
-                            }
-                        }
+                // A malformed year is skipped rather than failing the whole analysis
+                let prices = match read_rt_prices(&file, 10_000_000) {
+                    Ok(prices) => prices,
+                    Err(e) => {
+                        log::warn!("Skipping RT prices in {}: {}", file.display(), e);
+                        continue;
                     }
-                }
+                };
+                overwrites.extend(&mut self.rt_prices, prices, &file);
             }
+            overwrites.warn("RT");
         }
         
         println!("  ✅ Loaded {} RT price points", self.rt_prices.len());
@@ -308,9 +293,7 @@ impl BessDisclosureAnalyzer {
         
         for file in files {
             // Process AS clearing price files
-            let df = CsvReader::new(std::fs::File::open(&file)?)
-                .has_header(true)
-                .finish()?;
+            let df = read_csv(&file)?;
             
             // Extract AS clearing prices by service type
             // This would parse MCPC (Market Clearing Price for Capacity) files
@@ -320,6 +303,13 @@ impl BessDisclosureAnalyzer {
         Ok(())
     }
     
+    /// Analyze only operating days in `date_range`, skipping disclosure
+    /// files dated outside it
+    pub fn with_date_range(mut self, date_range: DateRange) -> Self {
+        self.date_range = date_range;
+        self
+    }
+    
     fn get_available_years(&self) -> Result<Vec<i32>> {
         let csv_dir = self.disclosure_dir.join("csv");
         let mut years = std::collections::HashSet::new();
@@ -340,7 +330,7 @@ impl BessDisclosureAnalyzer {
             }
         }
         
-        let mut years_vec: Vec<i32> = years.into_iter().collect();
+        let mut years_vec: Vec<i32> = years.into_iter().filter(|y| self.date_range.includes_year(*y)).collect();
         years_vec.sort();
         Ok(years_vec)
     }
@@ -353,6 +343,7 @@ impl BessDisclosureAnalyzer {
         let sced_pattern = csv_dir.join(&format!("*SCED_Gen_Resource_Data*{}*.csv", year));
         let sced_files: Vec<PathBuf> = glob::glob(sced_pattern.to_str().unwrap())?
             .filter_map(Result::ok)
+            .filter(|path| self.date_range.includes_file(path))
             .collect();
         
         println!("  Processing {} SCED files for year {}", sced_files.len(), year);
@@ -366,6 +357,7 @@ impl BessDisclosureAnalyzer {
         let dam_pattern = csv_dir.join(&format!("*DAM_Gen_Resource_Data*{}*.csv", year));
         let dam_files: Vec<PathBuf> = glob::glob(dam_pattern.to_str().unwrap())?
             .filter_map(Result::ok)
+            .filter(|path| self.date_range.includes_file(path))
             .collect();
         
         println!("  Processing {} DAM files for year {}", dam_files.len(), year);
@@ -393,15 +385,14 @@ impl BessDisclosureAnalyzer {
             }
         }
         
+        daily_revenues.retain(|r| self.date_range.contains(r.date));
         Ok(daily_revenues)
     }
     
     fn process_sced_file(&self, file: &Path) -> Result<Vec<DailyRevenue>> {
         let mut revenues = Vec::new();
         
-        let df = CsvReader::new(std::fs::File::open(file)?)
-            .has_header(true)
-            .finish()?;
+        let df = read_csv(file)?;
         
         // Expected columns: Resource Name, SCED Timestamp, Base Point, Settlement Point Price
         if let (Ok(names), Ok(timestamps), Ok(base_points)) = (
@@ -412,6 +403,7 @@ impl BessDisclosureAnalyzer {
             let names_str = names.utf8()?;
             let timestamps_str = timestamps.utf8()?;
             let base_points_f64 = base_points.f64()?;
+            let repeated_flags = df.column("Repeated Hour Flag").ok().and_then(|c| c.utf8().ok());
             
             // Group by resource and date
             let mut daily_data: HashMap<(String, NaiveDate), Vec<(f64, f64)>> = HashMap::new();
@@ -426,14 +418,17 @@ impl BessDisclosureAnalyzer {
                     }
                     
                     // Parse timestamp
-                    if let Ok(timestamp) = NaiveDateTime::parse_from_str(timestamp_str, "%m/%d/%Y %H:%M:%S") {
+                    if let Some(timestamp) = parse_sced_timestamp(timestamp_str) {
                         let date = timestamp.date();
-                        let interval = (timestamp.hour() * 12 + timestamp.minute() / 5) as u32;
+                        let repeated = is_repeated_hour(repeated_flags.and_then(|f| f.get(i)));
+                        let Some(interval) = RT_PRICE_INTERVAL.index_of_local(timestamp, repeated) else {
+                            continue;
+                        };
                         
                         // Get RT price for this interval
                         let resource = &self.bess_resources[name];
-                        let price = self.rt_prices.get(&(resource.settlement_point.clone(), date, interval))
-                            .copied()
+                        let price = self.rt_prices
+                            .get(&resource.settlement_point, RT_PRICE_INTERVAL.epoch_index_of_day(date, interval))
                             .unwrap_or(0.0);
                         
                         daily_data.entry((name.to_string(), date))
@@ -450,7 +445,7 @@ impl BessDisclosureAnalyzer {
                 let mut rt_mwh_discharged = 0.0;
                 
                 for (base_point, price) in intervals {
-                    let mwh = base_point * (5.0 / 60.0); // 5-minute interval
+                    let mwh = SCED_DISPATCH_INTERVAL.energy_mwh(base_point);
                     let revenue = mwh * price;
                     
                     if base_point > 0.0 {
@@ -487,9 +482,7 @@ impl BessDisclosureAnalyzer {
     fn process_dam_file(&self, file: &Path) -> Result<Vec<DailyRevenue>> {
         let mut revenues = Vec::new();
         
-        let df = CsvReader::new(std::fs::File::open(file)?)
-            .has_header(true)
-            .finish()?;
+        let df = read_csv(file)?;
         
         // Process DAM energy and AS awards
         // Expected columns vary by file type, but typically include:
@@ -537,47 +530,7 @@ impl BessDisclosureAnalyzer {
     }
     
     fn aggregate_to_annual(&self, monthly_revenues: &[MonthlyRevenue]) -> Vec<AnnualRevenue> {
-        let mut annual_map: HashMap<(String, i32), AnnualRevenue> = HashMap::new();
-        
-        for monthly in monthly_revenues {
-            let key = (monthly.resource_name.clone(), monthly.year);
-            let resource = &self.bess_resources[&monthly.resource_name];
-            
-            let annual = annual_map.entry(key).or_insert(AnnualRevenue {
-                resource_name: monthly.resource_name.clone(),
-                year: monthly.year,
-                capacity_mw: resource.capacity_mw,
-                rt_energy_revenue: 0.0,
-                da_energy_revenue: 0.0,
-                reg_up_revenue: 0.0,
-                reg_down_revenue: 0.0,
-                spin_revenue: 0.0,
-                non_spin_revenue: 0.0,
-                ecrs_revenue: 0.0,
-                total_revenue: 0.0,
-                revenue_per_mw: 0.0,
-                revenue_per_mwh: 0.0,
-                months_active: 0,
-            });
-            
-            annual.rt_energy_revenue += monthly.rt_energy_revenue;
-            annual.da_energy_revenue += monthly.da_energy_revenue;
-            annual.reg_up_revenue += monthly.reg_up_revenue;
-            annual.reg_down_revenue += monthly.reg_down_revenue;
-            annual.spin_revenue += monthly.spin_revenue;
-            annual.non_spin_revenue += monthly.non_spin_revenue;
-            annual.ecrs_revenue += monthly.ecrs_revenue;
-            annual.total_revenue += monthly.total_revenue;
-            annual.months_active += 1;
-        }
-        
-        // Calculate per-MW and per-MWh metrics
-        for annual in annual_map.values_mut() {
-            annual.revenue_per_mw = annual.total_revenue / annual.capacity_mw;
-            annual.revenue_per_mwh = annual.total_revenue / (annual.capacity_mw * 2.0); // Assuming 2-hour duration
-        }
-        
-        annual_map.into_iter().map(|(_, v)| v).collect()
+        annual_revenues(monthly_revenues, &self.bess_resources, &self.resource_durations, self.fiscal_calendar)
     }
     
     fn generate_comprehensive_report(&self, annual_revenues: &[AnnualRevenue]) -> Result<()> {
@@ -591,7 +544,7 @@ impl BessDisclosureAnalyzer {
         }
         
         for (year, year_revenues) in by_year.iter() {
-            println!("\n🗓️  Year {} Summary:", year);
+            println!("\n🗓️  Year {} Summary:", self.fiscal_calendar.label(*year));
             
             let total_capacity: f64 = year_revenues.iter().map(|r| r.capacity_mw).sum();
             let total_revenue: f64 = year_revenues.iter().map(|r| r.total_revenue).sum();
@@ -698,57 +651,248 @@ impl BessDisclosureAnalyzer {
         Ok(())
     }
     
-    fn generate_cumulative_revenue_chart(&self, annual_revenues: &[AnnualRevenue]) -> Result<()> {
+    fn generate_cumulative_revenue_chart(&self, monthly_revenues: &[MonthlyRevenue]) -> Result<()> {
         println!("\n📈 Generating cumulative revenue charts...");
         
-        // Group by resource
-        let mut by_resource: HashMap<String, Vec<&AnnualRevenue>> = HashMap::new();
-        for rev in annual_revenues {
-            by_resource.entry(rev.resource_name.clone()).or_insert_with(Vec::new).push(rev);
-        }
+        let cumulative = cumulative_monthly_revenue(monthly_revenues);
         
-        // Create cumulative revenue data
-        for (resource_name, mut revenues) in by_resource {
-            revenues.sort_by_key(|r| r.year);
-            
-            let mut cumulative = 0.0;
-            let mut years = Vec::new();
-            let mut cumulative_values = Vec::new();
-            
-            for rev in revenues {
-                cumulative += rev.total_revenue;
-                years.push(rev.year);
-                cumulative_values.push(cumulative);
-            }
+        let mut all_names = Vec::new();
+        let mut all_months = Vec::new();
+        let mut all_values = Vec::new();
+        
+        for (resource_name, points) in &cumulative {
+            let months: Vec<String> = points.iter().map(|(month, _)| month.clone()).collect();
+            let values: Vec<f64> = points.iter().map(|(_, value)| *value).collect();
             
             // Save cumulative data for visualization
             let df = DataFrame::new(vec![
-                Series::new("Year", years),
-                Series::new("Cumulative_Revenue", cumulative_values),
+                Series::new("Year_Month", months.clone()),
+                Series::new("Cumulative_Revenue", values.clone()),
             ])?;
             
             let path = self.output_dir.join(format!("cumulative_{}.csv", resource_name.replace(" ", "_")));
             CsvWriter::new(std::fs::File::create(&path)?)
                 .finish(&mut df.clone())?;
+            
+            all_names.extend(std::iter::repeat(resource_name.clone()).take(months.len()));
+            all_months.extend(months);
+            all_values.extend(values);
         }
         
-        println!("  ✅ Generated cumulative revenue data");
+        let mut df = DataFrame::new(vec![
+            Series::new("Resource_Name", all_names),
+            Series::new("Year_Month", all_months),
+            Series::new("Cumulative_Revenue", all_values),
+        ])?;
+        let path = self.output_dir.join("bess_cumulative_monthly_revenues.csv");
+        CsvWriter::new(std::fs::File::create(&path)?)
+            .finish(&mut df)?;
+        
+        println!("  ✅ Generated cumulative revenue data: {}", path.display());
         Ok(())
     }
 }
 
-pub fn analyze_bess_disclosure_revenues() -> Result<()> {
+/// Sum monthly revenue into (resource, year) totals, by fiscal year under a
+/// fiscal calendar, with per-MW and per-MWh metrics from the resource's
+/// capacity and duration
+pub fn annual_revenues(
+    monthly_revenues: &[MonthlyRevenue],
+    bess_resources: &HashMap<String, BessResource>,
+    resource_durations: &ResourceDurations,
+    fiscal_calendar: FiscalCalendar,
+) -> Vec<AnnualRevenue> {
+    let mut annual_map: HashMap<(String, i32), AnnualRevenue> = HashMap::new();
+    
+    for monthly in monthly_revenues {
+        let year = fiscal_calendar.year_of(monthly.year, monthly.month);
+        let key = (monthly.resource_name.clone(), year);
+        let resource = &bess_resources[&monthly.resource_name];
+        
+        let annual = annual_map.entry(key).or_insert(AnnualRevenue {
+            resource_name: monthly.resource_name.clone(),
+            year,
+            capacity_mw: resource.capacity_mw,
+            rt_energy_revenue: 0.0,
+            da_energy_revenue: 0.0,
+            reg_up_revenue: 0.0,
+            reg_down_revenue: 0.0,
+            spin_revenue: 0.0,
+            non_spin_revenue: 0.0,
+            ecrs_revenue: 0.0,
+            total_revenue: 0.0,
+            revenue_per_mw: 0.0,
+            revenue_per_mwh: 0.0,
+            months_active: 0,
+        });
+        
+        annual.rt_energy_revenue += monthly.rt_energy_revenue;
+        annual.da_energy_revenue += monthly.da_energy_revenue;
+        annual.reg_up_revenue += monthly.reg_up_revenue;
+        annual.reg_down_revenue += monthly.reg_down_revenue;
+        annual.spin_revenue += monthly.spin_revenue;
+        annual.non_spin_revenue += monthly.non_spin_revenue;
+        annual.ecrs_revenue += monthly.ecrs_revenue;
+        annual.total_revenue += monthly.total_revenue;
+        annual.months_active += 1;
+    }
+    
+    // Calculate per-MW and per-MWh metrics
+    for annual in annual_map.values_mut() {
+        annual.revenue_per_mw = annual.total_revenue / annual.capacity_mw;
+        annual.revenue_per_mwh = annual.total_revenue / (annual.capacity_mw * resource_durations.get(&annual.resource_name));
+    }
+    
+    annual_map.into_iter().map(|(_, v)| v).collect()
+}
+
+/// Running revenue total per resource as (YYYY-MM, cumulative) points, one
+/// per month from the first to the last active month. Months without data
+/// carry the previous total forward so early-life ramps and outages show up
+/// as flat stretches rather than gaps.
+pub fn cumulative_monthly_revenue(monthly_revenues: &[MonthlyRevenue]) -> BTreeMap<String, Vec<(String, f64)>> {
+    let mut by_resource: BTreeMap<String, BTreeMap<(i32, u32), f64>> = BTreeMap::new();
+    for rev in monthly_revenues {
+        *by_resource
+            .entry(rev.resource_name.clone())
+            .or_default()
+            .entry((rev.year, rev.month))
+            .or_insert(0.0) += rev.total_revenue;
+    }
+    
+    let mut cumulative = BTreeMap::new();
+    for (resource_name, months) in by_resource {
+        let (Some(&first), Some(&last)) = (months.keys().next(), months.keys().next_back()) else { continue };
+        
+        let mut points = Vec::new();
+        let mut total = 0.0;
+        let (mut year, mut month) = first;
+        while (year, month) <= last {
+            total += months.get(&(year, month)).copied().unwrap_or(0.0);
+            points.push((format!("{:04}-{:02}", year, month), total));
+            if month == 12 {
+                year += 1;
+                month = 1;
+            } else {
+                month += 1;
+            }
+        }
+        cumulative.insert(resource_name, points);
+    }
+    
+    cumulative
+}
+
+pub fn analyze_bess_disclosure_revenues(
+    default_duration_hours: f64,
+    resource_filter: &ResourceFilter,
+    fiscal_calendar: FiscalCalendar,
+    date_range: DateRange,
+    output_prefix: &OutputPrefix,
+) -> Result<()> {
     let disclosure_dir = PathBuf::from("/Users/enrico/data/ERCOT_data/60-Day_COP_Adjustment_Period_Snapshot");
-    let price_data_dir = PathBuf::from("annual_output");
-    let master_list_path = PathBuf::from("bess_analysis/bess_resources_master_list.csv");
+    let price_data_dir = output_prefix.input_path("annual_output");
+    let master_list_path = output_prefix.input_path("bess_analysis/bess_resources_master_list.csv");
     
     let mut analyzer = BessDisclosureAnalyzer::new(
         disclosure_dir,
         price_data_dir,
         &master_list_path,
-    )?;
+        output_prefix.output_dir("bess_disclosure_analysis"),
+    )?
+    .with_default_duration_hours(default_duration_hours)
+    .with_resource_filter(resource_filter)
+    .with_fiscal_calendar(fiscal_calendar)
+    .with_date_range(date_range);
     
     analyzer.analyze_all_revenues()?;
     
     Ok(())
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn month(resource: &str, year: i32, month: u32, total: f64) -> MonthlyRevenue {
+        MonthlyRevenue {
+            resource_name: resource.to_string(),
+            year,
+            month,
+            rt_energy_revenue: 0.0,
+            da_energy_revenue: total,
+            reg_up_revenue: 0.0,
+            reg_down_revenue: 0.0,
+            spin_revenue: 0.0,
+            non_spin_revenue: 0.0,
+            ecrs_revenue: 0.0,
+            total_revenue: total,
+            days_active: 30,
+        }
+    }
+
+    #[test]
+    fn test_cumulative_monthly_revenue_has_a_point_per_month() {
+        let monthly = vec![
+            month("BESS_A", 2024, 1, 100.0),
+            month("BESS_A", 2023, 11, 10.0),
+            month("BESS_A", 2024, 2, 50.0),
+            month("BESS_B", 2024, 2, 5.0),
+        ];
+
+        let cumulative = cumulative_monthly_revenue(&monthly);
+        let bess_a = &cumulative["BESS_A"];
+        assert_eq!(
+            bess_a,
+            &vec![
+                ("2023-11".to_string(), 10.0),
+                ("2023-12".to_string(), 10.0),
+                ("2024-01".to_string(), 110.0),
+                ("2024-02".to_string(), 160.0),
+            ]
+        );
+        assert!(bess_a.windows(2).all(|w| w[1].1 >= w[0].1));
+        assert_eq!(cumulative["BESS_B"], vec![("2024-02".to_string(), 5.0)]);
+    }
+
+    #[test]
+    fn test_fiscal_year_rollup_spans_calendar_years() {
+        let resources: HashMap<String, BessResource> = [("BESS_A", 100.0)]
+            .iter()
+            .map(|(name, capacity)| (name.to_string(), BessResource {
+                name: name.to_string(),
+                settlement_point: "BESS_A_RN".to_string(),
+                capacity_mw: *capacity,
+                duration_hours: Some(2.0),
+                qse: "QSE".to_string(),
+                commissioning_date: None,
+            }))
+            .collect();
+        let durations = ResourceDurations::from_resources(&resources.values().cloned().collect::<Vec<_>>());
+        let monthly = vec![
+            month("BESS_A", 2022, 9, 1.0),
+            month("BESS_A", 2022, 10, 10.0),
+            month("BESS_A", 2022, 12, 20.0),
+            month("BESS_A", 2023, 1, 30.0),
+            month("BESS_A", 2023, 9, 40.0),
+            month("BESS_A", 2023, 10, 500.0),
+        ];
+
+        let totals = |fiscal_calendar| {
+            let mut annual: Vec<(i32, f64, u32)> = annual_revenues(&monthly, &resources, &durations, fiscal_calendar)
+                .iter()
+                .map(|a| (a.year, a.total_revenue, a.months_active))
+                .collect();
+            annual.sort_by_key(|(year, ..)| *year);
+            annual
+        };
+
+        let october = FiscalCalendar::new(10).unwrap();
+        assert_eq!(totals(october), vec![(2022, 1.0, 1), (2023, 100.0, 4), (2024, 500.0, 1)]);
+        assert_eq!(totals(FiscalCalendar::default()), vec![(2022, 31.0, 3), (2023, 570.0, 3)]);
+
+        let fy2023 = annual_revenues(&monthly, &resources, &durations, october).into_iter().find(|a| a.year == 2023).unwrap();
+        assert_eq!(fy2023.revenue_per_mw, 1.0);
+        assert_eq!(fy2023.revenue_per_mwh, 0.5);
+    }
+}
@@ -2,11 +2,16 @@ use anyhow::Result;
 use std::path::PathBuf;
 use indicatif::{ProgressBar, ProgressStyle};
 
+use crate::bess_revenue_calculator::RevenueRunOptions;
+use crate::stage_timer::StageTimer;
+
 /// Runs the comprehensive BESS revenue analysis using the complete 60-day disclosure dataset
-pub fn analyze_bess_with_full_disclosure() -> Result<()> {
+pub fn analyze_bess_with_full_disclosure(options: &RevenueRunOptions) -> Result<()> {
     println!("\n💰 ERCOT BESS Revenue Analysis - Complete 60-Day Disclosure Dataset");
     println!("{}", "=".repeat(80));
     
+    let timer = StageTimer::new();
+    
     // Set up paths
     let _master_list_path = PathBuf::from("bess_analysis/bess_resources_master_list.csv");
     
@@ -23,23 +28,27 @@ pub fn analyze_bess_with_full_disclosure() -> Result<()> {
     let csv_dir = disclosure_link.join("csv");
     if !csv_dir.exists() {
         println!("📂 Extracting disclosure ZIP files...");
-        extract_disclosure_zips(&actual_disclosure)?;
+        let extracted = timer.time("Extract", || extract_disclosure_zips(&actual_disclosure))?;
+        timer.add_rows("Extract", extracted);
     }
     
     // Now run the existing comprehensive revenue calculator
-    crate::bess_revenue_calculator::calculate_bess_revenues()?;
+    crate::bess_revenue_calculator::calculate_bess_revenues(options, &timer)?;
     
+    timer.print_summary();
     println!("\n✅ Analysis complete!");
     Ok(())
 }
 
-fn extract_disclosure_zips(disclosure_dir: &PathBuf) -> Result<()> {
+/// Returns the number of CSV files extracted
+fn extract_disclosure_zips(disclosure_dir: &PathBuf) -> Result<usize> {
     use ::zip::ZipArchive;
     use std::fs::File;
     use std::io::copy;
     
     let csv_dir = disclosure_dir.join("csv");
     std::fs::create_dir_all(&csv_dir)?;
+    let mut extracted = 0;
     
     // Find all ZIP files
     let pattern = disclosure_dir.join("*.zip");
@@ -74,6 +83,7 @@ fn extract_disclosure_zips(disclosure_dir: &PathBuf) -> Result<()> {
                 
                 let mut outfile = File::create(&outpath)?;
                 copy(&mut file, &mut outfile)?;
+                extracted += 1;
             }
         }
     }
@@ -112,7 +122,9 @@ fn extract_disclosure_zips(disclosure_dir: &PathBuf) -> Result<()> {
                                 }
                                 
                                 if let Ok(mut outfile) = File::create(&outpath) {
-                                    let _ = copy(&mut file, &mut outfile);
+                                    if copy(&mut file, &mut outfile).is_ok() {
+                                        extracted += 1;
+                                    }
                                 }
                             }
                         }
@@ -122,5 +134,5 @@ fn extract_disclosure_zips(disclosure_dir: &PathBuf) -> Result<()> {
         }
     }
     
-    Ok(())
+    Ok(extracted)
 }
\ No newline at end of file
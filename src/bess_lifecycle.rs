@@ -0,0 +1,117 @@
+use anyhow::Result;
+use chrono::NaiveDate;
+use polars::prelude::*;
+use std::collections::BTreeSet;
+use std::path::Path;
+
+/// Consecutive days a resource must be missing from disclosure data before
+/// the absence counts as a potential outage
+pub const MIN_GAP_DAYS: i64 = 2;
+
+/// A run of consecutive days with no disclosure records, inclusive
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ActivityGap {
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+}
+
+impl ActivityGap {
+    pub fn days(&self) -> i64 {
+        (self.end - self.start).num_days() + 1
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ResourceLifecycle {
+    pub resource_name: String,
+    pub first_active: NaiveDate,
+    pub last_active: NaiveDate,
+    pub active_days: usize,
+    pub gaps: Vec<ActivityGap>,
+}
+
+/// First/last appearance, distinct active days and multi-day gaps for one
+/// resource, from the delivery dates it appears on. `None` without any dates.
+pub fn resource_lifecycle(resource_name: &str, dates: impl IntoIterator<Item = NaiveDate>) -> Option<ResourceLifecycle> {
+    let days: BTreeSet<NaiveDate> = dates.into_iter().collect();
+    let first_active = *days.iter().next()?;
+    let last_active = *days.iter().next_back()?;
+
+    let gaps = days
+        .iter()
+        .zip(days.iter().skip(1))
+        .filter(|(prev, next)| {
+            let missing_days = (**next - **prev).num_days() - 1;
+            missing_days >= MIN_GAP_DAYS
+        })
+        .map(|(prev, next)| ActivityGap {
+            start: prev.succ_opt().unwrap(),
+            end: next.pred_opt().unwrap(),
+        })
+        .collect();
+
+    Some(ResourceLifecycle {
+        resource_name: resource_name.to_string(),
+        first_active,
+        last_active,
+        active_days: days.len(),
+        gaps,
+    })
+}
+
+pub fn save_lifecycles(lifecycles: &[ResourceLifecycle], output_path: &Path) -> Result<()> {
+    let mut df = DataFrame::new(vec![
+        Series::new("Resource_Name", lifecycles.iter().map(|l| l.resource_name.clone()).collect::<Vec<_>>()),
+        Series::new("First_Active_Date", lifecycles.iter().map(|l| l.first_active.to_string()).collect::<Vec<_>>()),
+        Series::new("Last_Active_Date", lifecycles.iter().map(|l| l.last_active.to_string()).collect::<Vec<_>>()),
+        Series::new("Active_Days", lifecycles.iter().map(|l| l.active_days as u32).collect::<Vec<_>>()),
+        Series::new("Gap_Count", lifecycles.iter().map(|l| l.gaps.len() as u32).collect::<Vec<_>>()),
+        Series::new("Longest_Gap_Days", lifecycles.iter()
+            .map(|l| l.gaps.iter().map(ActivityGap::days).max().unwrap_or(0))
+            .collect::<Vec<_>>()),
+        // e.g. "2024-04-01..2024-04-30;2024-07-04..2024-07-06"
+        Series::new("Gaps", lifecycles.iter()
+            .map(|l| l.gaps.iter().map(|g| format!("{}..{}", g.start, g.end)).collect::<Vec<_>>().join(";"))
+            .collect::<Vec<_>>()),
+    ])?;
+
+    CsvWriter::new(std::fs::File::create(output_path)?)
+        .finish(&mut df)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ymd(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn test_april_outage_shows_as_gap() {
+        // Active every day Jan-Mar, missing all of April, back in May
+        let mut dates: Vec<NaiveDate> = ymd(2024, 1, 1).iter_days().take_while(|d| *d <= ymd(2024, 3, 31)).collect();
+        dates.extend(ymd(2024, 5, 1).iter_days().take(10));
+        // Duplicate appearances (one per hour) count once
+        dates.push(ymd(2024, 2, 15));
+
+        let lifecycle = resource_lifecycle("BESS_A", dates).unwrap();
+
+        assert_eq!(lifecycle.first_active, ymd(2024, 1, 1));
+        assert_eq!(lifecycle.last_active, ymd(2024, 5, 10));
+        assert_eq!(lifecycle.active_days, 91 + 10);
+        assert_eq!(lifecycle.gaps, vec![ActivityGap { start: ymd(2024, 4, 1), end: ymd(2024, 4, 30) }]);
+        assert_eq!(lifecycle.gaps[0].days(), 30);
+    }
+
+    #[test]
+    fn test_single_missing_day_is_not_a_gap() {
+        let dates = vec![ymd(2024, 1, 1), ymd(2024, 1, 3), ymd(2024, 1, 6)];
+
+        let lifecycle = resource_lifecycle("BESS_A", dates).unwrap();
+
+        assert_eq!(lifecycle.gaps, vec![ActivityGap { start: ymd(2024, 1, 4), end: ymd(2024, 1, 5) }]);
+        assert!(resource_lifecycle("BESS_B", Vec::new()).is_none());
+    }
+}
@@ -4,6 +4,9 @@ use polars::prelude::*;
 use std::collections::HashMap;
 use std::path::PathBuf;
 
+use crate::master_list::load_master_list;
+use crate::output_prefix::OutputPrefix;
+
 /// Comprehensive BESS Market Analysis Report Generator
 pub struct BessMarketReport {
     output_dir: PathBuf,
@@ -68,24 +71,15 @@ impl BessMarketReport {
     }
     
     fn load_resource_info(&self) -> Result<HashMap<String, f64>> {
-        let path = self.output_dir.join("bess_resources_master_list.csv");
-        let df = CsvReader::new(std::fs::File::open(&path)?)
-            .has_header(true)
-            .finish()?;
-            
-        let mut capacities = HashMap::new();
-        if let (Ok(names), Ok(caps)) = (
-            df.column("Resource_Name")?.utf8(),
-            df.column("Max_Capacity_MW")?.f64()
-        ) {
-            for i in 0..df.height() {
-                if let (Some(name), Some(cap)) = (names.get(i), caps.get(i)) {
-                    capacities.insert(name.to_string(), cap);
-                }
-            }
+        // A prefixed run may share the master list of the default run
+        let mut path = self.output_dir.join("bess_resources_master_list.csv");
+        if !path.exists() {
+            path = PathBuf::from("bess_analysis/bess_resources_master_list.csv");
         }
-        
-        Ok(capacities)
+        Ok(load_master_list(&path)?
+            .into_iter()
+            .map(|r| (r.name, r.capacity_mw))
+            .collect())
     }
     
     fn calculate_market_metrics(&self, daily_revenues: &DataFrame, resource_info: &HashMap<String, f64>) 
@@ -434,8 +428,8 @@ impl BessMarketReport {
     }
 }
 
-pub fn generate_market_report() -> Result<()> {
-    let output_dir = PathBuf::from("bess_analysis");
+pub fn generate_market_report(output_prefix: &OutputPrefix) -> Result<()> {
+    let output_dir = output_prefix.output_dir("bess_analysis");
     let report_generator = BessMarketReport::new(output_dir);
     report_generator.generate_comprehensive_report()
 }
\ No newline at end of file
@@ -0,0 +1,789 @@
+use anyhow::Result;
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, Timelike};
+use polars::prelude::*;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::path::Path;
+
+use crate::as_services::service_awards;
+use crate::csv_headers::read_csv;
+use crate::intervals::{parse_sced_timestamp, IntervalLength, RT_PRICE_INTERVAL, SCED_DISPATCH_INTERVAL, SMNE_INTERVAL};
+
+/// Round-trip efficiency assumed when tracking SOC; all losses are taken on charge
+pub const DEFAULT_ROUND_TRIP_EFFICIENCY: f64 = 0.85;
+
+/// Allowed gap between charged MWh and discharged MWh / efficiency, in percent
+pub const DEFAULT_ENERGY_BALANCE_TOLERANCE_PCT: f64 = 20.0;
+
+/// Allowed gap between telemetered and metered MWh in a day, in percent of metered
+pub const DEFAULT_METER_TOLERANCE_PCT: f64 = 5.0;
+
+/// One telemetered SCED output for a resource: positive discharges, negative charges
+#[derive(Debug, Clone, Copy)]
+pub struct DispatchPoint {
+    pub timestamp: NaiveDateTime,
+    pub output_mw: f64,
+}
+
+/// Battery energy limits used to track state of charge through a day
+#[derive(Debug, Clone, Copy)]
+pub struct SocModel {
+    pub energy_capacity_mwh: f64,
+    /// State of charge at the start of each operating day
+    pub initial_soc_mwh: f64,
+    /// Share of charged energy that ends up stored
+    pub charge_efficiency: f64,
+}
+
+impl SocModel {
+    /// Battery starting each day half full
+    pub fn new(energy_capacity_mwh: f64, charge_efficiency: f64) -> Self {
+        Self {
+            energy_capacity_mwh,
+            initial_soc_mwh: energy_capacity_mwh / 2.0,
+            charge_efficiency,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SocViolationKind {
+    /// Discharged more energy than was stored
+    Overdraw,
+    /// Charged beyond the energy capacity
+    Overcharge,
+}
+
+#[derive(Debug, Clone)]
+pub struct SocViolation {
+    pub resource_name: String,
+    pub timestamp: NaiveDateTime,
+    pub kind: SocViolationKind,
+    /// Energy beyond the [0, capacity] bound, in MWh
+    pub magnitude_mwh: f64,
+}
+
+/// Integrate charge/discharge energy over each operating day and flag every
+/// interval where the state of charge would leave [0, energy capacity]. After
+/// a violation the SOC is pinned to the bound so later intervals are measured
+/// on their own. Each reading holds for at most one SCED interval, so
+/// telemetry that skips intervals counts the missing ones as zero output.
+pub fn detect_soc_violations(resource_name: &str, points: &[DispatchPoint], model: &SocModel) -> Vec<SocViolation> {
+    let mut by_day: BTreeMap<NaiveDate, Vec<DispatchPoint>> = BTreeMap::new();
+    for point in points {
+        by_day.entry(point.timestamp.date()).or_default().push(*point);
+    }
+
+    let mut violations = Vec::new();
+    for (_, mut day) in by_day {
+        day.sort_by_key(|p| p.timestamp);
+        let mut soc = model.initial_soc_mwh;
+
+        for (i, point) in day.iter().enumerate() {
+            let hours = day
+                .get(i + 1)
+                .map(|next| {
+                    let gap_hours = (next.timestamp - point.timestamp).num_seconds() as f64 / 3600.0;
+                    gap_hours.min(SCED_DISPATCH_INTERVAL.hours())
+                })
+                // The last interval of a day has no following timestamp
+                .unwrap_or(SCED_DISPATCH_INTERVAL.hours());
+            let energy_mwh = point.output_mw * hours;
+
+            if energy_mwh > 0.0 {
+                soc -= energy_mwh;
+            } else {
+                soc -= energy_mwh * model.charge_efficiency;
+            }
+
+            if soc < 0.0 {
+                violations.push(SocViolation {
+                    resource_name: resource_name.to_string(),
+                    timestamp: point.timestamp,
+                    kind: SocViolationKind::Overdraw,
+                    magnitude_mwh: -soc,
+                });
+                soc = 0.0;
+            } else if soc > model.energy_capacity_mwh {
+                violations.push(SocViolation {
+                    resource_name: resource_name.to_string(),
+                    timestamp: point.timestamp,
+                    kind: SocViolationKind::Overcharge,
+                    magnitude_mwh: soc - model.energy_capacity_mwh,
+                });
+                soc = model.energy_capacity_mwh;
+            }
+        }
+    }
+
+    violations
+}
+
+/// BESS (PWRSTR) dispatch from one 60-day SCED Gen Resource Data file, keyed
+/// by resource. Uses Telemetered Net Output, falling back to Output Schedule.
+pub fn load_sced_dispatch(path: &Path) -> Result<HashMap<String, Vec<DispatchPoint>>> {
+    let df = read_csv(path)?;
+
+    let mut dispatch: HashMap<String, Vec<DispatchPoint>> = HashMap::new();
+    let (Ok(resource_types), Ok(timestamps), Ok(resources)) = (
+        df.column("Resource Type"),
+        df.column("SCED Time Stamp"),
+        df.column("Resource Name"),
+    ) else {
+        return Ok(dispatch);
+    };
+    let outputs = match df.column("Telemetered Net Output").or_else(|_| df.column("Output Schedule")) {
+        Ok(outputs) => outputs.cast(&DataType::Float64)?,
+        Err(_) => return Ok(dispatch),
+    };
+
+    for (((resource_type, timestamp), resource), output_mw) in resource_types.utf8()?.into_iter()
+        .zip(timestamps.utf8()?)
+        .zip(resources.utf8()?)
+        .zip(outputs.f64()?)
+    {
+        if resource_type != Some("PWRSTR") {
+            continue;
+        }
+        if let (Some(timestamp), Some(resource), Some(output_mw)) = (timestamp, resource, output_mw) {
+            if let Some(timestamp) = parse_sced_timestamp(timestamp) {
+                dispatch
+                    .entry(resource.to_string())
+                    .or_default()
+                    .push(DispatchPoint { timestamp, output_mw });
+            }
+        }
+    }
+
+    Ok(dispatch)
+}
+
+/// Hourly DAM ancillary-service awards for one resource. Up services
+/// (RegUp, RRS, ECRS, Non-Spin) need room to discharge further; RegDown
+/// needs room to charge.
+#[derive(Debug, Clone, Copy)]
+pub struct AsAward {
+    /// Start of the operating hour (hour ending minus one)
+    pub hour_start: NaiveDateTime,
+    pub up_mw: f64,
+    pub down_mw: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AsDirection {
+    Up,
+    Down,
+}
+
+/// An interval where committed AS exceeded the headroom left by dispatch
+#[derive(Debug, Clone)]
+pub struct AsShortfall {
+    pub resource_name: String,
+    pub timestamp: NaiveDateTime,
+    pub direction: AsDirection,
+    pub committed_mw: f64,
+    pub output_mw: f64,
+    pub headroom_mw: f64,
+    pub shortfall_mw: f64,
+}
+
+/// Check each SCED interval against the award for its hour. Up headroom is
+/// capacity minus output; down headroom is output plus capacity, since a
+/// battery can swing to full charge.
+pub fn detect_as_shortfalls(
+    resource_name: &str,
+    awards: &[AsAward],
+    points: &[DispatchPoint],
+    capacity_mw: f64,
+) -> Vec<AsShortfall> {
+    let awards_by_hour: HashMap<NaiveDateTime, &AsAward> = awards.iter().map(|a| (a.hour_start, a)).collect();
+
+    let mut shortfalls = Vec::new();
+    for point in points {
+        let hour_start = point.timestamp.date().and_hms_opt(point.timestamp.hour(), 0, 0).unwrap();
+        let Some(award) = awards_by_hour.get(&hour_start) else {
+            continue;
+        };
+
+        for (direction, committed_mw, headroom_mw) in [
+            (AsDirection::Up, award.up_mw, capacity_mw - point.output_mw),
+            (AsDirection::Down, award.down_mw, capacity_mw + point.output_mw),
+        ] {
+            if committed_mw > 0.0 && committed_mw > headroom_mw {
+                shortfalls.push(AsShortfall {
+                    resource_name: resource_name.to_string(),
+                    timestamp: point.timestamp,
+                    direction,
+                    committed_mw,
+                    output_mw: point.output_mw,
+                    headroom_mw,
+                    shortfall_mw: committed_mw - headroom_mw.max(0.0),
+                });
+            }
+        }
+    }
+
+    shortfalls.sort_by_key(|s| s.timestamp);
+    shortfalls
+}
+
+/// BESS AS awards from one 60-day DAM Gen Resource Data file, keyed by resource
+pub fn load_dam_as_awards(path: &Path) -> Result<HashMap<String, Vec<AsAward>>> {
+    let df = read_csv(path)?;
+
+    let mut awards: HashMap<String, Vec<AsAward>> = HashMap::new();
+    let (Ok(resource_types), Ok(dates), Ok(hours), Ok(resources)) = (
+        df.column("Resource Type"),
+        df.column("Delivery Date"),
+        df.column("Hour Ending"),
+        df.column("Resource Name"),
+    ) else {
+        return Ok(awards);
+    };
+    let hours = hours.cast(&DataType::Int64)?;
+    let mut up = vec![0.0; df.height()];
+    let mut down = vec![0.0; df.height()];
+    for (service, service_awards) in service_awards(&df)? {
+        let totals = if service.is_up() { &mut up } else { &mut down };
+        for (total, award) in totals.iter_mut().zip(service_awards) {
+            *total += award;
+        }
+    }
+
+    for (i, (((resource_type, date), hour_ending), resource)) in resource_types.utf8()?.into_iter()
+        .zip(dates.utf8()?)
+        .zip(hours.i64()?)
+        .zip(resources.utf8()?)
+        .enumerate()
+    {
+        if resource_type != Some("PWRSTR") || (up[i] <= 0.0 && down[i] <= 0.0) {
+            continue;
+        }
+        if let (Some(date), Some(hour_ending), Some(resource)) = (date, hour_ending, resource) {
+            if let Ok(date) = NaiveDate::parse_from_str(date, "%m/%d/%Y") {
+                awards.entry(resource.to_string()).or_default().push(AsAward {
+                    hour_start: date.and_hms_opt(0, 0, 0).unwrap() + Duration::hours(hour_ending - 1),
+                    up_mw: up[i],
+                    down_mw: down[i],
+                });
+            }
+        }
+    }
+
+    Ok(awards)
+}
+
+pub fn save_as_shortfalls(shortfalls: &[AsShortfall], output_path: &Path) -> Result<()> {
+    let mut df = DataFrame::new(vec![
+        Series::new("Resource_Name", shortfalls.iter().map(|s| s.resource_name.clone()).collect::<Vec<_>>()),
+        Series::new("Date", shortfalls.iter().map(|s| s.timestamp.date().to_string()).collect::<Vec<_>>()),
+        Series::new("Interval", shortfalls.iter().map(|s| s.timestamp.format("%H:%M:%S").to_string()).collect::<Vec<_>>()),
+        Series::new("Direction", shortfalls.iter().map(|s| format!("{:?}", s.direction)).collect::<Vec<_>>()),
+        Series::new("Committed_MW", shortfalls.iter().map(|s| s.committed_mw).collect::<Vec<_>>()),
+        Series::new("Output_MW", shortfalls.iter().map(|s| s.output_mw).collect::<Vec<_>>()),
+        Series::new("Headroom_MW", shortfalls.iter().map(|s| s.headroom_mw).collect::<Vec<_>>()),
+        Series::new("Shortfall_MW", shortfalls.iter().map(|s| s.shortfall_mw).collect::<Vec<_>>()),
+    ])?;
+
+    CsvWriter::new(std::fs::File::create(output_path)?)
+        .finish(&mut df)?;
+    Ok(())
+}
+
+/// Charge vs discharge totals for one resource over the whole period
+#[derive(Debug, Clone)]
+pub struct EnergyBalance {
+    pub resource_name: String,
+    pub charged_mwh: f64,
+    pub discharged_mwh: f64,
+    /// Charge needed to supply the discharge at the round-trip efficiency
+    pub expected_charge_mwh: f64,
+    /// (charged - expected) / expected, in percent
+    pub pct_deviation: f64,
+    pub flagged: bool,
+}
+
+/// Charged MWh should roughly equal discharged MWh / round-trip efficiency;
+/// a large gap points at sign errors or missing intervals. Discharge without
+/// any charge is always flagged.
+pub fn check_energy_balance(
+    resource_name: &str,
+    points: &[DispatchPoint],
+    round_trip_efficiency: f64,
+    tolerance_pct: f64,
+) -> EnergyBalance {
+    let (mut charged_mwh, mut discharged_mwh) = (0.0, 0.0);
+    for point in points {
+        let energy_mwh = SCED_DISPATCH_INTERVAL.energy_mwh(point.output_mw);
+        if energy_mwh > 0.0 {
+            discharged_mwh += energy_mwh;
+        } else {
+            charged_mwh -= energy_mwh;
+        }
+    }
+
+    let expected_charge_mwh = discharged_mwh / round_trip_efficiency;
+    let pct_deviation = if expected_charge_mwh > 0.0 {
+        (charged_mwh - expected_charge_mwh) / expected_charge_mwh * 100.0
+    } else if charged_mwh == 0.0 {
+        0.0
+    } else {
+        f64::INFINITY
+    };
+
+    EnergyBalance {
+        resource_name: resource_name.to_string(),
+        charged_mwh,
+        discharged_mwh,
+        expected_charge_mwh,
+        pct_deviation,
+        flagged: pct_deviation.abs() > tolerance_pct,
+    }
+}
+
+pub fn save_energy_balance(balances: &[EnergyBalance], output_path: &Path) -> Result<()> {
+    let mut df = DataFrame::new(vec![
+        Series::new("Resource_Name", balances.iter().map(|b| b.resource_name.clone()).collect::<Vec<_>>()),
+        Series::new("Charged_MWh", balances.iter().map(|b| b.charged_mwh).collect::<Vec<_>>()),
+        Series::new("Discharged_MWh", balances.iter().map(|b| b.discharged_mwh).collect::<Vec<_>>()),
+        Series::new("Expected_Charge_MWh", balances.iter().map(|b| b.expected_charge_mwh).collect::<Vec<_>>()),
+        Series::new("Pct_Deviation", balances.iter().map(|b| b.pct_deviation).collect::<Vec<_>>()),
+    ])?;
+
+    CsvWriter::new(std::fs::File::create(output_path)?)
+        .finish(&mut df)?;
+    Ok(())
+}
+
+/// Share of a month's operating intervals in which a resource appeared in SCED
+#[derive(Debug, Clone)]
+pub struct MonthlyAvailability {
+    pub resource_name: String,
+    /// `YYYY-MM`
+    pub month: String,
+    /// 15-minute intervals with at least one SCED row for the resource
+    pub online_intervals: u32,
+    /// 15-minute intervals on the month's operating days
+    pub operating_intervals: u32,
+    pub availability: f64,
+}
+
+/// Days with SCED data for any resource, the operating days availability is
+/// measured over
+pub fn operating_days(dispatch: &HashMap<String, Vec<DispatchPoint>>) -> BTreeSet<NaiveDate> {
+    dispatch.values().flatten().map(|point| point.timestamp.date()).collect()
+}
+
+/// Monthly availability of one resource from its SCED presence, counted from
+/// its first SCED day so a battery isn't penalized before it was built. A
+/// resource missing from SCED for part of a month was offline, which tells
+/// low revenue from being down apart from low revenue from strategy.
+pub fn monthly_availability(
+    resource_name: &str,
+    points: &[DispatchPoint],
+    operating_days: &BTreeSet<NaiveDate>,
+) -> Vec<MonthlyAvailability> {
+    let online: HashSet<(NaiveDate, u32)> = points.iter()
+        .map(|point| (point.timestamp.date(), RT_PRICE_INTERVAL.index_of(point.timestamp)))
+        .collect();
+    let mut online_by_day: HashMap<NaiveDate, u32> = HashMap::new();
+    for (date, _) in &online {
+        *online_by_day.entry(*date).or_default() += 1;
+    }
+    let Some(first_day) = online_by_day.keys().min().copied() else {
+        return Vec::new();
+    };
+
+    let mut months: BTreeMap<(i32, u32), (u32, u32)> = BTreeMap::new();
+    for &date in operating_days.range(first_day..) {
+        let expected = RT_PRICE_INTERVAL.intervals_in_day(date);
+        // Wall-clock intervals can't tell the repeated fall-back hour apart
+        let seen = online_by_day.get(&date).copied().unwrap_or(0).min(expected);
+        let (online_intervals, operating_intervals) = months.entry((date.year(), date.month())).or_default();
+        *online_intervals += seen;
+        *operating_intervals += expected;
+    }
+
+    months.into_iter()
+        .map(|((year, month), (online_intervals, operating_intervals))| MonthlyAvailability {
+            resource_name: resource_name.to_string(),
+            month: format!("{:04}-{:02}", year, month),
+            online_intervals,
+            operating_intervals,
+            availability: online_intervals as f64 / operating_intervals as f64,
+        })
+        .collect()
+}
+
+pub fn save_availability(availability: &[MonthlyAvailability], output_path: &Path) -> Result<()> {
+    let mut df = DataFrame::new(vec![
+        Series::new("Resource_Name", availability.iter().map(|a| a.resource_name.clone()).collect::<Vec<_>>()),
+        Series::new("Month", availability.iter().map(|a| a.month.clone()).collect::<Vec<_>>()),
+        Series::new("Online_Intervals", availability.iter().map(|a| a.online_intervals).collect::<Vec<_>>()),
+        Series::new("Operating_Intervals", availability.iter().map(|a| a.operating_intervals).collect::<Vec<_>>()),
+        Series::new("Availability", availability.iter().map(|a| a.availability).collect::<Vec<_>>()),
+    ])?;
+
+    CsvWriter::new(std::fs::File::create(output_path)?)
+        .finish(&mut df)?;
+    Ok(())
+}
+
+/// Energy charged and discharged by one resource over a day
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct DailyEnergy {
+    pub charged_mwh: f64,
+    pub discharged_mwh: f64,
+}
+
+impl DailyEnergy {
+    pub fn record(&mut self, output_mw: f64, interval: IntervalLength) {
+        let energy_mwh = interval.energy_mwh(output_mw);
+        if energy_mwh > 0.0 {
+            self.discharged_mwh += energy_mwh;
+        } else {
+            self.charged_mwh -= energy_mwh;
+        }
+    }
+}
+
+/// Telemetered energy per operating day, from SCED dispatch
+pub fn telemetered_daily_energy(points: &[DispatchPoint]) -> BTreeMap<NaiveDate, DailyEnergy> {
+    let mut days: BTreeMap<NaiveDate, DailyEnergy> = BTreeMap::new();
+    for point in points {
+        days.entry(point.timestamp.date()).or_default().record(point.output_mw, SCED_DISPATCH_INTERVAL);
+    }
+    days
+}
+
+/// Metered energy per resource and operating day from one SCED SMNE file
+/// (`Interval Time`, `Resource Code`, `Interval Value`)
+pub fn load_smne_energy(path: &Path) -> Result<HashMap<String, BTreeMap<NaiveDate, DailyEnergy>>> {
+    let df = read_csv(path)?;
+
+    let mut metered: HashMap<String, BTreeMap<NaiveDate, DailyEnergy>> = HashMap::new();
+    let (Ok(timestamps), Ok(resources), Ok(values)) = (
+        df.column("Interval Time"),
+        df.column("Resource Code"),
+        df.column("Interval Value"),
+    ) else {
+        return Ok(metered);
+    };
+    let values = values.cast(&DataType::Float64)?;
+
+    for ((timestamp, resource), output_mw) in timestamps.utf8()?.into_iter()
+        .zip(resources.utf8()?)
+        .zip(values.f64()?)
+    {
+        if let (Some(timestamp), Some(resource), Some(output_mw)) = (timestamp, resource, output_mw) {
+            if let Some(timestamp) = parse_sced_timestamp(timestamp) {
+                metered
+                    .entry(resource.to_string())
+                    .or_default()
+                    .entry(timestamp.date())
+                    .or_default()
+                    .record(output_mw, SMNE_INTERVAL);
+            }
+        }
+    }
+
+    Ok(metered)
+}
+
+/// Telemetered vs metered energy for one resource-day
+#[derive(Debug, Clone)]
+pub struct MeterReconciliation {
+    pub resource_name: String,
+    pub date: NaiveDate,
+    pub telemetered: DailyEnergy,
+    pub metered: DailyEnergy,
+    /// Larger of the discharge and charge gaps, (telemetered - metered) / metered in percent
+    pub divergence_pct: f64,
+    pub flagged: bool,
+}
+
+fn divergence_pct(telemetered_mwh: f64, metered_mwh: f64) -> f64 {
+    if metered_mwh > 0.0 {
+        (telemetered_mwh - metered_mwh) / metered_mwh * 100.0
+    } else if telemetered_mwh == 0.0 {
+        0.0
+    } else {
+        f64::INFINITY
+    }
+}
+
+/// Compare SCED telemetry with SMNE meter data on each day both cover. A
+/// day off by more than `tolerance_pct` on discharge or charge points at a
+/// metering or telemetry problem.
+pub fn reconcile_metered_energy(
+    resource_name: &str,
+    telemetered: &BTreeMap<NaiveDate, DailyEnergy>,
+    metered: &BTreeMap<NaiveDate, DailyEnergy>,
+    tolerance_pct: f64,
+) -> Vec<MeterReconciliation> {
+    telemetered.iter()
+        .filter_map(|(date, telemetered)| {
+            let metered = metered.get(date)?;
+            let discharge = divergence_pct(telemetered.discharged_mwh, metered.discharged_mwh);
+            let charge = divergence_pct(telemetered.charged_mwh, metered.charged_mwh);
+            let divergence_pct = if discharge.abs() >= charge.abs() { discharge } else { charge };
+            Some(MeterReconciliation {
+                resource_name: resource_name.to_string(),
+                date: *date,
+                telemetered: *telemetered,
+                metered: *metered,
+                divergence_pct,
+                flagged: divergence_pct.abs() > tolerance_pct,
+            })
+        })
+        .collect()
+}
+
+pub fn save_meter_reconciliation(days: &[MeterReconciliation], output_path: &Path) -> Result<()> {
+    let mut df = DataFrame::new(vec![
+        Series::new("Resource_Name", days.iter().map(|d| d.resource_name.clone()).collect::<Vec<_>>()),
+        Series::new("Date", days.iter().map(|d| d.date.to_string()).collect::<Vec<_>>()),
+        Series::new("Telemetered_Discharge_MWh", days.iter().map(|d| d.telemetered.discharged_mwh).collect::<Vec<_>>()),
+        Series::new("Metered_Discharge_MWh", days.iter().map(|d| d.metered.discharged_mwh).collect::<Vec<_>>()),
+        Series::new("Telemetered_Charge_MWh", days.iter().map(|d| d.telemetered.charged_mwh).collect::<Vec<_>>()),
+        Series::new("Metered_Charge_MWh", days.iter().map(|d| d.metered.charged_mwh).collect::<Vec<_>>()),
+        Series::new("Divergence_Pct", days.iter().map(|d| d.divergence_pct).collect::<Vec<_>>()),
+        Series::new("Flagged", days.iter().map(|d| d.flagged).collect::<Vec<_>>()),
+    ])?;
+
+    CsvWriter::new(std::fs::File::create(output_path)?)
+        .finish(&mut df)?;
+    Ok(())
+}
+
+pub fn save_soc_violations(violations: &[SocViolation], output_path: &Path) -> Result<()> {
+    let mut df = DataFrame::new(vec![
+        Series::new("Resource_Name", violations.iter().map(|v| v.resource_name.clone()).collect::<Vec<_>>()),
+        Series::new("Date", violations.iter().map(|v| v.timestamp.date().to_string()).collect::<Vec<_>>()),
+        Series::new("Interval", violations.iter().map(|v| v.timestamp.format("%H:%M:%S").to_string()).collect::<Vec<_>>()),
+        Series::new("Violation", violations.iter().map(|v| format!("{:?}", v.kind)).collect::<Vec<_>>()),
+        Series::new("Magnitude_MWh", violations.iter().map(|v| v.magnitude_mwh).collect::<Vec<_>>()),
+    ])?;
+
+    CsvWriter::new(std::fs::File::create(output_path)?)
+        .finish(&mut df)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(hour: u32, minute: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2024, 8, 1).unwrap().and_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    /// `count` consecutive 5-minute SCED readings of `output_mw` from `start`,
+    /// then a zero reading
+    fn held(start: NaiveDateTime, count: i64, output_mw: f64) -> Vec<DispatchPoint> {
+        let mut points: Vec<DispatchPoint> = (0..count)
+            .map(|i| DispatchPoint { timestamp: start + Duration::minutes(5 * i), output_mw })
+            .collect();
+        points.push(DispatchPoint { timestamp: start + Duration::minutes(5 * count), output_mw: 0.0 });
+        points
+    }
+
+    #[test]
+    fn test_discharge_beyond_stored_energy_is_overdraw() {
+        // 100 MWh battery starting at 50 MWh: 90 MW (7.5 MWh per interval) for 35 minutes
+        let points = held(at(18, 0), 7, 90.0);
+        let model = SocModel::new(100.0, 1.0);
+
+        let violations = detect_soc_violations("BESS_A", &points, &model);
+
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].kind, SocViolationKind::Overdraw);
+        assert_eq!(violations[0].timestamp, at(18, 30));
+        assert!((violations[0].magnitude_mwh - 2.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_feasible_cycle_has_no_violations() {
+        // Charge 40 MWh then discharge 80 of the 90 stored
+        let mut points = held(at(2, 0), 12, -40.0);
+        points.extend(held(at(18, 0), 12, 80.0));
+        let model = SocModel::new(100.0, 1.0);
+
+        assert!(detect_soc_violations("BESS_A", &points, &model).is_empty());
+    }
+
+    #[test]
+    fn test_charging_past_capacity_is_overcharge() {
+        // 80 MW at 90% stores 6 MWh per interval: 50 + 9 x 6 = 104
+        let points = held(at(1, 0), 9, -80.0);
+        let model = SocModel::new(100.0, 0.9);
+
+        let violations = detect_soc_violations("BESS_A", &points, &model);
+
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].kind, SocViolationKind::Overcharge);
+        assert_eq!(violations[0].timestamp, at(1, 40));
+        assert!((violations[0].magnitude_mwh - 4.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_telemetry_gap_does_not_stretch_the_last_reading() {
+        // 40 MW at 18:00, then no telemetry until 20:00: one interval of
+        // discharge (3.3 MWh), not two hours of it (80 MWh from 50 stored)
+        let points = vec![
+            DispatchPoint { timestamp: at(18, 0), output_mw: 40.0 },
+            DispatchPoint { timestamp: at(20, 0), output_mw: 0.0 },
+        ];
+        let model = SocModel::new(100.0, 1.0);
+
+        assert!(detect_soc_violations("BESS_A", &points, &model).is_empty());
+    }
+
+    #[test]
+    fn test_regup_award_near_full_discharge_is_flagged() {
+        // 100 MW battery awarded 50 MW RegUp for HE19 (18:00-19:00)
+        let awards = vec![AsAward { hour_start: at(18, 0), up_mw: 50.0, down_mw: 0.0 }];
+        let points = vec![
+            DispatchPoint { timestamp: at(17, 55), output_mw: 98.0 },
+            DispatchPoint { timestamp: at(18, 0), output_mw: 40.0 },
+            DispatchPoint { timestamp: at(18, 5), output_mw: 95.0 },
+            DispatchPoint { timestamp: at(18, 10), output_mw: 98.0 },
+        ];
+
+        let shortfalls = detect_as_shortfalls("BESS_A", &awards, &points, 100.0);
+
+        assert_eq!(shortfalls.len(), 2);
+        assert!(shortfalls.iter().all(|s| s.direction == AsDirection::Up && s.committed_mw == 50.0));
+        assert_eq!(shortfalls[0].timestamp, at(18, 5));
+        assert!((shortfalls[0].headroom_mw - 5.0).abs() < 1e-9);
+        assert!((shortfalls[0].shortfall_mw - 45.0).abs() < 1e-9);
+        assert!((shortfalls[1].shortfall_mw - 48.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_regdown_award_while_charging_is_flagged() {
+        let awards = vec![AsAward { hour_start: at(2, 0), up_mw: 0.0, down_mw: 30.0 }];
+        let points = vec![
+            DispatchPoint { timestamp: at(2, 15), output_mw: -80.0 },
+            DispatchPoint { timestamp: at(2, 30), output_mw: 0.0 },
+        ];
+
+        let shortfalls = detect_as_shortfalls("BESS_A", &awards, &points, 100.0);
+
+        assert_eq!(shortfalls.len(), 1);
+        assert_eq!(shortfalls[0].direction, AsDirection::Down);
+        assert!((shortfalls[0].shortfall_mw - 10.0).abs() < 1e-9);
+    }
+
+    fn constant(from_hour: u32, to_hour: u32, output_mw: f64) -> Vec<DispatchPoint> {
+        (from_hour * 12..to_hour * 12)
+            .map(|i| DispatchPoint { timestamp: at(i / 12, (i % 12) * 5), output_mw })
+            .collect()
+    }
+
+    #[test]
+    fn test_discharge_far_above_charge_is_flagged() {
+        // 10 MWh charged, 40 MWh discharged: impossible for any efficiency
+        let mut points = constant(2, 3, -10.0);
+        points.extend(constant(18, 20, 20.0));
+
+        let balance = check_energy_balance("BESS_A", &points, 0.85, 20.0);
+
+        assert!((balance.charged_mwh - 10.0).abs() < 1e-9);
+        assert!((balance.discharged_mwh - 40.0).abs() < 1e-9);
+        assert!(balance.flagged);
+        assert!(balance.pct_deviation < -75.0);
+    }
+
+    #[test]
+    fn test_balanced_cycle_within_tolerance() {
+        // 50 MWh charged, 42 MWh discharged at ~85% round trip
+        let mut points = constant(1, 6, -10.0);
+        points.extend(constant(18, 20, 21.0));
+
+        let balance = check_energy_balance("BESS_A", &points, 0.85, 20.0);
+
+        assert!(!balance.flagged);
+        assert!(balance.pct_deviation.abs() < 2.0);
+    }
+
+    #[test]
+    fn test_two_weeks_offline_lowers_that_months_availability() {
+        // SCED every 5 minutes through June and July 2024; BESS_B is missing June 10-23
+        let every_interval = |from: NaiveDate, days: i64| -> Vec<DispatchPoint> {
+            (0..days * 288)
+                .map(|i| DispatchPoint {
+                    timestamp: from.and_hms_opt(0, 0, 0).unwrap() + Duration::minutes(5 * i),
+                    output_mw: 0.0,
+                })
+                .collect()
+        };
+        let june = |day| NaiveDate::from_ymd_opt(2024, 6, day).unwrap();
+        let mut dispatch = HashMap::new();
+        dispatch.insert("BESS_A".to_string(), every_interval(june(1), 61));
+        let mut offline = every_interval(june(1), 9);
+        offline.extend(every_interval(june(24), 38));
+        dispatch.insert("BESS_B".to_string(), offline);
+
+        let days = operating_days(&dispatch);
+        assert_eq!(days.len(), 61);
+
+        let always_on = monthly_availability("BESS_A", &dispatch["BESS_A"], &days);
+        assert!(always_on.iter().all(|m| m.availability == 1.0));
+
+        let availability = monthly_availability("BESS_B", &dispatch["BESS_B"], &days);
+        assert_eq!(availability.len(), 2);
+        assert_eq!(availability[0].month, "2024-06");
+        assert_eq!(availability[0].operating_intervals, 30 * 96);
+        assert_eq!(availability[0].online_intervals, 16 * 96);
+        assert!((availability[0].availability - 16.0 / 30.0).abs() < 1e-9);
+        assert_eq!(availability[1].month, "2024-07");
+        assert_eq!(availability[1].availability, 1.0);
+    }
+
+    #[test]
+    fn test_telemetry_ten_percent_above_meter_is_flagged() {
+        // Telemetry: 10 MW charge for 5 hours, 20 MW discharge for 2 hours
+        let mut points = constant(1, 6, -10.0);
+        points.extend(constant(18, 20, 20.0));
+        let telemetered = telemetered_daily_energy(&points);
+
+        // Meter agrees on charge but read 10% less discharge
+        let date = at(0, 0).date();
+        let mut day = DailyEnergy::default();
+        for _ in 0..20 {
+            day.record(-10.0, SMNE_INTERVAL);
+        }
+        for _ in 0..8 {
+            day.record(20.0 / 1.1, SMNE_INTERVAL);
+        }
+        let metered = BTreeMap::from([(date, day)]);
+
+        let days = reconcile_metered_energy("BESS_A", &telemetered, &metered, DEFAULT_METER_TOLERANCE_PCT);
+        assert_eq!(days.len(), 1);
+        assert!((days[0].telemetered.discharged_mwh - 40.0).abs() < 1e-9);
+        assert!((days[0].metered.charged_mwh - 50.0).abs() < 1e-9);
+        assert!((days[0].divergence_pct - 10.0).abs() < 1e-9);
+        assert!(days[0].flagged);
+
+        let within = reconcile_metered_energy("BESS_A", &telemetered, &metered, 15.0);
+        assert!(!within[0].flagged);
+    }
+
+    #[test]
+    fn test_sced_file_with_am_pm_timestamps_loads_every_row() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("60d_SCED_Gen_Resource_Data-01-AUG-24.csv");
+        std::fs::write(&path, "SCED Time Stamp,Repeated Hour Flag,Resource Name,Resource Type,Telemetered Net Output\n\
+                               08/01/2024 06:00:13 PM,N,BATCAVE_BES1,PWRSTR,50\n\
+                               08/01/2024 06:05:13 PM,N,BATCAVE_BES1,PWRSTR,50\n\
+                               08/01/2024 12:00:13 AM,N,BATCAVE_BES1,PWRSTR,-20\n").unwrap();
+
+        let dispatch = load_sced_dispatch(&path).unwrap();
+        let timestamps: Vec<NaiveDateTime> = dispatch["BATCAVE_BES1"].iter().map(|p| p.timestamp).collect();
+        assert_eq!(timestamps, vec![
+            at(18, 0) + Duration::seconds(13),
+            at(18, 5) + Duration::seconds(13),
+            at(0, 0) + Duration::seconds(13),
+        ]);
+    }
+}
@@ -1,9 +1,21 @@
 use anyhow::Result;
+use log::warn;
 use chrono::{NaiveDate, NaiveDateTime, Timelike, Datelike};
 use polars::prelude::*;
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use indicatif::{ProgressBar, ProgressStyle};
+use rayon::prelude::*;
+use std::sync::Arc;
+
+use crate::bess_analyzer::ResourceDurations;
+use crate::master_list::load_master_list;
+use crate::bess_operations::DEFAULT_ROUND_TRIP_EFFICIENCY;
+use crate::intervals::{is_repeated_hour, IntervalLength, RT_PRICE_INTERVAL};
+use crate::price_index::PriceIndex;
+use crate::price_reader::read_rt_prices;
+use crate::resource_filter::ResourceFilter;
+use crate::output_prefix::OutputPrefix;
 
 #[derive(Debug, Clone)]
 pub struct BessRevenue {
@@ -23,44 +35,58 @@ pub struct BessRevenue {
 
 pub struct BessParquetCalculator {
     bess_resources: HashMap<String, (String, f64)>, // name -> (settlement_point, capacity)
+    resource_durations: ResourceDurations,
+    round_trip_efficiency: f64,
     annual_output_dir: PathBuf,
     output_dir: PathBuf,
 }
 
 impl BessParquetCalculator {
-    pub fn new(bess_master_list_path: &Path) -> Result<Self> {
-        let annual_output_dir = PathBuf::from("annual_output");
-        let output_dir = PathBuf::from("bess_analysis");
+    pub fn new(bess_master_list_path: &Path, output_prefix: &OutputPrefix) -> Result<Self> {
+        let annual_output_dir = output_prefix.input_path("annual_output");
+        let output_dir = output_prefix.output_dir("bess_analysis");
         
         // Load BESS resources from master list
-        let master_df = CsvReader::new(std::fs::File::open(bess_master_list_path)?)
-            .has_header(true)
-            .finish()?;
-        
-        let mut bess_resources = HashMap::new();
-        let names = master_df.column("Resource_Name")?.utf8()?;
-        let settlement_points = master_df.column("Settlement_Point")?.utf8()?;
-        let capacities = master_df.column("Max_Capacity_MW")?.f64()?;
-        
-        for i in 0..master_df.height() {
-            if let (Some(name), Some(sp), Some(cap)) = 
-                (names.get(i), settlement_points.get(i), capacities.get(i)) {
-                bess_resources.insert(name.to_string(), (sp.to_string(), cap));
-            }
-        }
+        let master_list = load_master_list(bess_master_list_path)?;
+        let bess_resources: HashMap<String, (String, f64)> = master_list.iter()
+            .map(|r| (r.name.clone(), (r.settlement_point.clone(), r.capacity_mw)))
+            .collect();
         
         println!("Loaded {} BESS resources for revenue calculation", bess_resources.len());
         
+        let resource_durations = ResourceDurations::from_resources(&master_list);
+        
         Ok(Self {
             bess_resources,
+            resource_durations,
+            round_trip_efficiency: DEFAULT_ROUND_TRIP_EFFICIENCY,
             annual_output_dir,
             output_dir,
         })
     }
     
+    /// Round-trip efficiency applied to arbitrage, losses taken on charge
+    pub fn with_round_trip_efficiency(mut self, efficiency: f64) -> Self {
+        self.round_trip_efficiency = efficiency;
+        self
+    }
+    
+    /// Duration assumed for resources the master list gives none for
+    pub fn with_default_duration_hours(mut self, hours: f64) -> Self {
+        self.resource_durations = self.resource_durations.with_default_hours(hours);
+        self
+    }
+    
+    /// Keep only resources matching `--resource-regex`
+    pub fn with_resource_filter(mut self, filter: &ResourceFilter) -> Self {
+        filter.retain(&mut self.bess_resources);
+        self
+    }
+    
     pub fn calculate_all_revenues(&self) -> Result<()> {
         println!("\n💰 BESS Revenue Calculation Using Parquet Data");
         println!("{}", "=".repeat(80));
+        self.resource_durations.warn_defaults(self.bess_resources.keys());
         
         // Get available years from parquet files
         let years = self.get_available_years()?;
@@ -114,15 +140,24 @@ impl BessParquetCalculator {
         
         // Load RT prices from Parquet
         let rt_prices = self.load_rt_prices_parquet(year)?;
-        println!("    ✅ Loaded {} RT price records", rt_prices.len());
+        println!("    ✅ Loaded {} RT price records ({:.1} MB)", rt_prices.len(), rt_prices.approx_bytes() as f64 / 1e6);
         
         // Load DAM prices from Parquet
         let dam_prices = self.load_dam_prices_parquet(year)?;
-        println!("    ✅ Loaded {} DAM price records", dam_prices.len());
-        
-        // Calculate daily revenues for each BESS resource
-        let mut year_revenues = Vec::new();
+        println!("    ✅ Loaded {} DAM price records ({:.1} MB)", dam_prices.len(), dam_prices.approx_bytes() as f64 / 1e6);
         
+        self.calculate_resource_revenues(year, Arc::new(rt_prices), Arc::new(dam_prices))
+    }
+    
+    /// Daily revenues for every BESS resource over `year`, one resource per
+    /// rayon task against the shared read-only price maps. Sorted by resource
+    /// then date so output doesn't depend on scheduling.
+    fn calculate_resource_revenues(
+        &self,
+        year: i32,
+        rt_prices: Arc<PriceIndex>,
+        dam_prices: Arc<PriceIndex>,
+    ) -> Result<Vec<BessRevenue>> {
         // Get date range for the year
         let start_date = NaiveDate::from_ymd_opt(year, 1, 1).unwrap();
         let end_date = NaiveDate::from_ymd_opt(year, 12, 31).unwrap();
@@ -132,90 +167,59 @@ impl BessParquetCalculator {
             .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} {msg}")
             .unwrap());
         
-        for (resource_name, (settlement_point, capacity)) in &self.bess_resources {
-            pb.set_message(format!("Processing {}", resource_name));
-            pb.inc(1);
-            
-            let mut current_date = start_date;
-            while current_date <= end_date {
-                let revenue = self.calculate_daily_revenue(
-                    resource_name,
-                    settlement_point,
-                    *capacity,
-                    current_date,
-                    &rt_prices,
-                    &dam_prices,
-                )?;
-                
-                if revenue.total_revenue != 0.0 {
-                    year_revenues.push(revenue);
+        let resources: Vec<(&String, &(String, f64))> = self.bess_resources.iter().collect();
+        let per_resource: Vec<Vec<BessRevenue>> = resources
+            .par_iter()
+            .map(|(resource_name, (settlement_point, capacity))| {
+                let mut resource_revenues = Vec::new();
+                for current_date in start_date.iter_days().take_while(|d| *d <= end_date) {
+                    let revenue = self.calculate_daily_revenue(
+                        resource_name,
+                        settlement_point,
+                        *capacity,
+                        current_date,
+                        &rt_prices,
+                        &dam_prices,
+                    )?;
+                    
+                    if revenue.total_revenue != 0.0 {
+                        resource_revenues.push(revenue);
+                    }
                 }
                 
-                current_date = current_date.succ_opt().unwrap();
-            }
-        }
+                pb.set_message(format!("Processed {}", resource_name));
+                pb.inc(1);
+                Ok(resource_revenues)
+            })
+            .collect::<Result<_>>()?;
         
         pb.finish();
         
+        let mut year_revenues: Vec<BessRevenue> = per_resource.into_iter().flatten().collect();
+        year_revenues.sort_by(|a, b| {
+            a.resource_name.cmp(&b.resource_name)
+                .then(a.date.cmp(&b.date))
+        });
+        
         Ok(year_revenues)
     }
     
-    fn load_rt_prices_parquet(&self, year: i32) -> Result<HashMap<(String, NaiveDate, u32), f64>> {
-        let mut prices = HashMap::new();
-        
+    fn load_rt_prices_parquet(&self, year: i32) -> Result<PriceIndex> {
         let file_path = self.annual_output_dir
             .join("Settlement_Point_Prices_at_Resource_Nodes__Hubs_and_Load_Zones")
             .join(format!("Settlement_Point_Prices_at_Resource_Nodes__Hubs_and_Load_Zones_{}.parquet", year));
         
         if !file_path.exists() {
-            println!("    ⚠️  RT price file not found for {}", year);
-            return Ok(prices);
-        }
-        
-        let file = std::fs::File::open(&file_path)?;
-        let df = ParquetReader::new(file).finish()?;
-        
-        // Expected columns: DeliveryDate, DeliveryHour, DeliveryInterval, SettlementPointName, SettlementPointPrice
-        if let (Ok(dates), Ok(hours), Ok(intervals), Ok(sps), Ok(prices_col)) = (
-            df.column("DeliveryDate"),
-            df.column("DeliveryHour"),
-            df.column("DeliveryInterval"),
-            df.column("SettlementPointName"),
-            df.column("SettlementPointPrice")
-        ) {
-            let dates_str = dates.utf8()?;
-            let hours_i64 = hours.i64()?;
-            let intervals_i64 = intervals.i64()?;
-            let sps_str = sps.utf8()?;
-            let prices_f64 = prices_col.f64()?;
-            
-            for i in 0..df.height().min(5_000_000) { // Limit to first 5M rows per year
-                if let (Some(date_str), Some(hour), Some(interval), Some(sp), Some(price)) = 
-                    (dates_str.get(i), hours_i64.get(i), intervals_i64.get(i), sps_str.get(i), prices_f64.get(i)) {
-                    
-                    // Parse date
-                    let date = if let Ok(d) = NaiveDate::parse_from_str(date_str, "%m/%d/%Y") {
-                        d
-                    } else if let Ok(d) = NaiveDate::parse_from_str(date_str, "%Y-%m-%d") {
-                        d
-                    } else {
-                        continue;
-                    };
-                    
-                    // Calculate 5-minute interval index for the day (0-287)
-                    // DeliveryHour is 0-23, DeliveryInterval is 1-4 within each hour
-                    let interval_index = (hour as u32) * 12 + (interval as u32 - 1) * 3;
-                    
-                    prices.insert((sp.to_string(), date, interval_index), price);
-                }
-            }
+            warn!("RT price file not found for {}", year);
+            return Ok(PriceIndex::new(RT_PRICE_INTERVAL));
         }
         
-        Ok(prices)
+        // Limit to first 5M rows per year
+        read_rt_prices(&file_path, 5_000_000)
     }
     
-    fn load_dam_prices_parquet(&self, year: i32) -> Result<HashMap<(String, NaiveDate, u32), f64>> {
-        let mut prices = HashMap::new();
+    fn load_dam_prices_parquet(&self, year: i32) -> Result<PriceIndex> {
+        let mut prices = PriceIndex::new(IntervalLength::Hourly);
         
         let file_path = self.annual_output_dir
             .join("DAM_Hourly_LMPs_BusLevel")
@@ -223,7 +227,7 @@ impl BessParquetCalculator {
         
         if !file_path.exists() {
             // Try alternative location for DAM Settlement Point Prices
-            println!("    ⚠️  DAM LMP file not found for {}, checking for alternatives...", year);
+            warn!("DAM LMP file not found for {}, checking for alternatives...", year);
             return Ok(prices);
         }
         
@@ -257,6 +261,7 @@ impl BessParquetCalculator {
             };
             let buses_str = buses.utf8()?;
             let lmps_f64 = lmps.f64()?;
+            let dst_flags = df.column("DSTFlag").ok().and_then(|c| c.utf8().ok());
             
             for i in 0..df.height().min(1_000_000) { // Limit to first 1M rows per year
                 if let (Some(date_str), Some(hour), Some(bus), Some(lmp)) = 
@@ -271,7 +276,10 @@ impl BessParquetCalculator {
                         continue;
                     };
                     
-                    prices.insert((bus.to_string(), date, hour as u32), lmp);
+                    let repeated = is_repeated_hour(dst_flags.and_then(|f| f.get(i)));
+                    if let Some(hour_index) = IntervalLength::Hourly.index_from_hour_ending(date, hour as u32, 1, repeated) {
+                        prices.insert(bus, IntervalLength::Hourly.epoch_index_of_day(date, hour_index), lmp);
+                    }
                 }
             }
         }
@@ -285,8 +293,8 @@ impl BessParquetCalculator {
         settlement_point: &str,
         capacity_mw: f64,
         date: NaiveDate,
-        rt_prices: &HashMap<(String, NaiveDate, u32), f64>,
-        dam_prices: &HashMap<(String, NaiveDate, u32), f64>,
+        rt_prices: &PriceIndex,
+        dam_prices: &PriceIndex,
     ) -> Result<BessRevenue> {
         let mut dam_energy_revenue = 0.0;
         let mut rt_energy_revenue = 0.0;
@@ -294,36 +302,42 @@ impl BessParquetCalculator {
         // Simple energy arbitrage calculation
         // Get DAM prices for all hours of the day
         let mut hourly_dam_prices = Vec::new();
-        for hour in 1..=24 {
-            if let Some(&price) = dam_prices.get(&(settlement_point.to_string(), date, hour)) {
-                hourly_dam_prices.push((hour, price));
+        if let Some(point) = dam_prices.point_id(settlement_point) {
+            let midnight = IntervalLength::Hourly.epoch_index_of_day(date, 0);
+            for hour in 0..IntervalLength::Hourly.intervals_in_day(date) {
+                if let Some(price) = dam_prices.get_by_id(point, midnight + hour as i64) {
+                    hourly_dam_prices.push((hour, price));
+                }
             }
         }
         
+        // One full cycle: charge and discharge for as many hours as the battery's
+        // duration, a partial last hour (e.g. 1.5 h) weighted by its fraction
+        let duration_hours = self.resource_durations.get(resource_name);
+        let cycle_hours = duration_hours.ceil() as usize;
+        
         // If we have enough DAM prices, calculate arbitrage opportunity
-        if hourly_dam_prices.len() >= 4 {
+        if cycle_hours > 0 && hourly_dam_prices.len() >= 2 * cycle_hours {
             // Sort by price to find best charge/discharge hours
             hourly_dam_prices.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
             
-            // Charge during lowest price hours (2 hours for 2-hour duration BESS)
-            let charge_hours = &hourly_dam_prices[0..2.min(hourly_dam_prices.len())];
-            let avg_charge_price: f64 = charge_hours.iter().map(|(_, p)| p).sum::<f64>() / charge_hours.len() as f64;
-            
-            // Discharge during highest price hours
-            let discharge_start = hourly_dam_prices.len().saturating_sub(2);
-            let discharge_hours = &hourly_dam_prices[discharge_start..];
-            let avg_discharge_price: f64 = discharge_hours.iter().map(|(_, p)| p).sum::<f64>() / discharge_hours.len() as f64;
+            // Charge during lowest price hours, discharge during the highest
+            let charge_price_hours = price_hours(hourly_dam_prices.iter().map(|(_, p)| *p), duration_hours);
+            let discharge_price_hours = price_hours(hourly_dam_prices.iter().rev().map(|(_, p)| *p), duration_hours);
             
-            // Calculate DAM arbitrage revenue (assuming 90% round-trip efficiency)
-            dam_energy_revenue = capacity_mw * 2.0 * (avg_discharge_price * 0.95 - avg_charge_price / 0.95);
+            // Selling capacity × duration takes capacity × duration / efficiency of charging
+            dam_energy_revenue = capacity_mw * (discharge_price_hours - charge_price_hours / self.round_trip_efficiency);
         }
         
         // For RT revenue, calculate based on price volatility within the day
         // This is a simplified calculation - in reality would use actual dispatch data
         let mut rt_interval_prices = Vec::new();
-        for interval in 0..288 { // 288 5-minute intervals per day
-            if let Some(&price) = rt_prices.get(&(settlement_point.to_string(), date, interval)) {
-                rt_interval_prices.push(price);
+        if let Some(point) = rt_prices.point_id(settlement_point) {
+            let midnight = RT_PRICE_INTERVAL.epoch_index_of_day(date, 0);
+            for interval in 0..RT_PRICE_INTERVAL.intervals_in_day(date) { // 92/96/100 on DST days
+                if let Some(price) = rt_prices.get_by_id(point, midnight + interval as i64) {
+                    rt_interval_prices.push(price);
+                }
             }
         }
         
@@ -334,7 +348,7 @@ impl BessParquetCalculator {
             
             // Simple RT arbitrage calculation (1 cycle per day max)
             if max_price > min_price * 1.1 { // At least 10% spread
-                rt_energy_revenue = capacity_mw * 0.5 * (max_price - min_price) * 0.9; // Half capacity, 90% efficiency
+                rt_energy_revenue = capacity_mw * 0.5 * (max_price - min_price) * self.round_trip_efficiency; // Half capacity
             }
         }
         
@@ -467,9 +481,128 @@ impl BessParquetCalculator {
     }
 }
 
-pub fn calculate_bess_revenues_from_parquet() -> Result<()> {
-    let master_list_path = PathBuf::from("bess_analysis/bess_resources_master_list.csv");
-    let calculator = BessParquetCalculator::new(&master_list_path)?;
+pub fn calculate_bess_revenues_from_parquet(
+    round_trip_efficiency: f64,
+    default_duration_hours: f64,
+    resource_filter: &ResourceFilter,
+    output_prefix: &OutputPrefix,
+) -> Result<()> {
+    let master_list_path = output_prefix.input_path("bess_analysis/bess_resources_master_list.csv");
+    let calculator = BessParquetCalculator::new(&master_list_path, output_prefix)?
+        .with_round_trip_efficiency(round_trip_efficiency)
+        .with_default_duration_hours(default_duration_hours)
+        .with_resource_filter(resource_filter);
     calculator.calculate_all_revenues()?;
     Ok(())
-}
\ No newline at end of file
+}
+
+/// Sum of price × hours over the first `duration_hours` hours of `prices`,
+/// the last one weighted by the fraction of it the battery runs
+fn price_hours(prices: impl Iterator<Item = f64>, duration_hours: f64) -> f64 {
+    let mut remaining = duration_hours;
+    let mut total = 0.0;
+    for price in prices {
+        if remaining <= 0.0 {
+            break;
+        }
+        let hours = remaining.min(1.0);
+        total += price * hours;
+        remaining -= hours;
+    }
+    total
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn calculator(resources: &[(&str, &str, f64)]) -> BessParquetCalculator {
+        BessParquetCalculator {
+            bess_resources: resources.iter()
+                .map(|(name, sp, capacity)| (name.to_string(), (sp.to_string(), *capacity)))
+                .collect(),
+            resource_durations: ResourceDurations::default(),
+            round_trip_efficiency: DEFAULT_ROUND_TRIP_EFFICIENCY,
+            annual_output_dir: PathBuf::new(),
+            output_dir: PathBuf::new(),
+        }
+    }
+
+    #[test]
+    fn test_parallel_year_revenues_are_sorted_and_match_sequential() {
+        let calculator = calculator(&[("BESS_C", "SP_C", 50.0), ("BESS_A", "SP_A", 100.0), ("BESS_B", "SP_B", 10.0)]);
+
+        // Two weeks of DAM prices with a daily peak at each settlement point
+        let mut dam_prices = PriceIndex::new(IntervalLength::Hourly);
+        for date in NaiveDate::from_ymd_opt(2024, 3, 1).unwrap().iter_days().take(14) {
+            for (i, sp) in ["SP_A", "SP_B", "SP_C"].iter().enumerate() {
+                for hour in 0..24 {
+                    let price = if hour == 18 { 200.0 + i as f64 } else { 20.0 + hour as f64 };
+                    dam_prices.insert(sp, IntervalLength::Hourly.epoch_index_of_day(date, hour), price);
+                }
+            }
+        }
+        let rt_prices = PriceIndex::new(RT_PRICE_INTERVAL);
+
+        let revenues = calculator
+            .calculate_resource_revenues(2024, Arc::new(rt_prices.clone()), Arc::new(dam_prices.clone()))
+            .unwrap();
+
+        let keys: Vec<(&str, NaiveDate)> = revenues.iter().map(|r| (r.resource_name.as_str(), r.date)).collect();
+        let mut sorted = keys.clone();
+        sorted.sort();
+        assert_eq!(keys, sorted);
+        assert_eq!(keys.first().unwrap().0, "BESS_A");
+
+        // Same totals as computing each resource-day in turn
+        let mut expected = 0.0;
+        for (name, (sp, capacity)) in &calculator.bess_resources {
+            for date in NaiveDate::from_ymd_opt(2024, 1, 1).unwrap().iter_days().take_while(|d| d.year() == 2024) {
+                expected += calculator.calculate_daily_revenue(name, sp, *capacity, date, &rt_prices, &dam_prices)
+                    .unwrap()
+                    .total_revenue;
+            }
+        }
+        let actual: f64 = revenues.iter().map(|r| r.total_revenue).sum();
+        assert!((actual - expected).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_dam_arbitrage_cycles_over_battery_duration() {
+        let mut calculator = calculator(&[("BESS_4H", "SP", 10.0)]);
+        calculator.resource_durations.insert("BESS_4H", 4.0);
+        let calculator = calculator.with_round_trip_efficiency(0.8);
+
+        // Hours 0-5 cost 10, 6-17 cost 50, 18-23 pay 100
+        let date = NaiveDate::from_ymd_opt(2024, 3, 5).unwrap();
+        let mut dam_prices = PriceIndex::new(IntervalLength::Hourly);
+        for hour in 0..24 {
+            let price = match hour {
+                0..=5 => 10.0,
+                18..=23 => 100.0,
+                _ => 50.0,
+            };
+            dam_prices.insert("SP", IntervalLength::Hourly.epoch_index_of_day(date, hour), price);
+        }
+
+        let revenue = calculator
+            .calculate_daily_revenue("BESS_4H", "SP", 10.0, date, &PriceIndex::new(RT_PRICE_INTERVAL), &dam_prices)
+            .unwrap();
+        // 4 hours × 10 MW discharged at $100, 40 MWh / 0.8 charged at $10
+        assert!((revenue.dam_energy_revenue - (40.0 * 100.0 - 50.0 * 10.0)).abs() < 1e-9);
+
+        // Without a duration the default 2-hour cycle applies
+        let two_hour = calculator
+            .calculate_daily_revenue("OTHER", "SP", 10.0, date, &PriceIndex::new(RT_PRICE_INTERVAL), &dam_prices)
+            .unwrap();
+        assert!((two_hour.dam_energy_revenue - (20.0 * 100.0 - 25.0 * 10.0)).abs() < 1e-9);
+
+        // A 1.5-hour battery sells 15 MWh, not a rounded-up 20
+        let mut calculator = calculator;
+        calculator.resource_durations.insert("BESS_90M", 1.5);
+        let ninety_minute = calculator
+            .calculate_daily_revenue("BESS_90M", "SP", 10.0, date, &PriceIndex::new(RT_PRICE_INTERVAL), &dam_prices)
+            .unwrap();
+        assert!((ninety_minute.dam_energy_revenue - (15.0 * 100.0 - 15.0 / 0.8 * 10.0)).abs() < 1e-9);
+    }
+}
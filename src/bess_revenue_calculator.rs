@@ -1,9 +1,65 @@
 use anyhow::Result;
-use chrono::{NaiveDate, NaiveDateTime, Timelike, DateTime};
+use log::{debug, warn};
+use chrono::{NaiveDate, NaiveDateTime, Timelike};
 use polars::prelude::*;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap};
 use std::path::{Path, PathBuf};
 
+use crate::as_services::{service_awards, service_mcpcs, AsService, SettledAwardHours};
+use crate::bess_analyzer::{ResourceDurations, DEFAULT_DURATION_HOURS};
+use crate::master_list::load_master_list;
+use crate::bess_benchmarks::{performance_vs_benchmark, PeerBenchmarks};
+use crate::csv_headers::read_csv;
+use crate::date_range::DateRange;
+use crate::dataset_paths::{DatasetPaths, ANCILLARY_PRICES, DAM_PRICES, RT_LMPS, RT_PRICES};
+use crate::offer_curves;
+use crate::settlement_points::{SettlementPointAliases, SettlementPointList, SettlementPointMap};
+use crate::price_cache_export::{ancillary_price_frame, price_index_frame, write_parquet};
+#[cfg(feature = "sqlite")]
+use crate::sqlite_sink;
+use crate::price_index::{DuplicatePricePolicy, MergeOverwrites, PriceIndex, PriceIndexBuilder, RtPriceProduct};
+use crate::price_outliers::PriceOutlierFilter;
+use crate::resource_filter::ResourceFilter;
+use crate::rt_price_basis::{RtPriceBasis, RtPriceBasisConfig};
+use crate::scarcity::{capture_rate, rt_epoch_index, save_capture_rates, scarcity_intervals, CaptureRate, DEFAULT_SCARCITY_PRICE, SCARCITY_REFERENCE_POINT};
+use crate::unmatched_prices::{UnmatchedPrices, UnmatchedResolution};
+use crate::datetime_format::DatetimeFormat;
+use crate::price_store::{LazyPriceStore, PriceLoading, PriceStore, DEFAULT_LAZY_CACHE_DAYS};
+use crate::stage_timer::StageTimer;
+use crate::intervals::{interval_revenue, is_repeated_hour, parse_sced_timestamp, IntervalLength, DAM_AWARD_INTERVAL, RT_PRICE_INTERVAL, SCED_DISPATCH_INTERVAL, SMNE_INTERVAL};
+use crate::output_prefix::OutputPrefix;
+use crate::run_metadata;
+use crate::bess_operations::{
+    check_energy_balance, detect_as_shortfalls, detect_soc_violations, load_dam_as_awards, load_sced_dispatch,
+    load_smne_energy, monthly_availability, operating_days, reconcile_metered_energy, save_as_shortfalls,
+    save_availability, save_energy_balance, save_meter_reconciliation, save_soc_violations, telemetered_daily_energy,
+    AsAward, AsShortfall, DailyEnergy, DispatchPoint, EnergyBalance, MeterReconciliation, MonthlyAvailability,
+    SocModel, SocViolation, DEFAULT_ENERGY_BALANCE_TOLERANCE_PCT, DEFAULT_METER_TOLERANCE_PCT,
+    DEFAULT_ROUND_TRIP_EFFICIENCY,
+};
+
+/// Monthly availability below which a resource-month is listed in the operational checks
+const LOW_AVAILABILITY: f64 = 0.9;
+
+/// Highest- and lowest-revenue days listed per resource
+pub const DEFAULT_EXTREME_DAYS: usize = 5;
+
+/// Equal-width buckets per resource in the daily revenue histogram
+pub const DEFAULT_HISTOGRAM_BUCKETS: usize = 20;
+
+/// Fewest days of data a resource needs before its revenue is extrapolated to a year
+pub const DEFAULT_MIN_DAYS_FOR_ANNUALIZATION: u32 = 30;
+
+/// AS award and clearing price columns read from DAM Gen Resource Data.
+/// Older files predate some services (e.g. ECRS, added June 2023).
+pub const AS_AWARD_COLUMNS: [&str; 10] = [
+    "RegUp Awarded", "RegUp MCPC",
+    "RegDown Awarded", "RegDown MCPC",
+    "RRS Awarded", "RRS MCPC",
+    "NonSpin Awarded", "NonSpin MCPC",
+    "ECRSSD Awarded", "ECRS MCPC",
+];
+
 #[derive(Debug, Clone)]
 #[allow(dead_code)]
 pub struct BessRevenue {
@@ -21,6 +77,219 @@ pub struct BessRevenue {
     pub energy_cycles: f64,
     pub soc_violations: u32,
     pub as_failures: u32,
+    pub dam_energy: EnergySettlement,
+    pub rt_energy: EnergySettlement,
+}
+
+/// Energy settlement split into discharge revenue and charge cost; `net()`
+/// is what the energy revenue columns report by default
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct EnergySettlement {
+    /// MW × price for discharging intervals (positive unless prices go negative)
+    pub discharge_revenue: f64,
+    /// MW × price for charging intervals (negative unless prices go negative)
+    pub charge_cost: f64,
+    /// Energy discharged, MWh
+    pub discharged_mwh: f64,
+    /// Energy charged, MWh (positive)
+    pub charged_mwh: f64,
+}
+
+impl EnergySettlement {
+    /// Settle `output_mw` held for one `interval`, worth `amount`
+    pub fn record(&mut self, output_mw: f64, amount: f64, interval: IntervalLength) {
+        let energy_mwh = interval.energy_mwh(output_mw);
+        if output_mw > 0.0 {
+            self.discharge_revenue += amount;
+            self.discharged_mwh += energy_mwh;
+        } else {
+            self.charge_cost += amount;
+            self.charged_mwh -= energy_mwh;
+        }
+    }
+
+    pub fn net(&self) -> f64 {
+        self.discharge_revenue + self.charge_cost
+    }
+
+    /// Share of `charge_cost` paid for energy charged but not discharged:
+    /// (charged − discharged) MWh at the average charge price. Same sign as
+    /// `charge_cost`, so the rest of the charge cost is the cost of the
+    /// energy that came back out. Energy stored across the period's edges
+    /// nets out over a year; a period discharging more than it charged
+    /// shows no loss.
+    pub fn efficiency_loss(&self) -> f64 {
+        if self.charged_mwh <= 0.0 {
+            return 0.0;
+        }
+        let lost_mwh = (self.charged_mwh - self.discharged_mwh).max(0.0);
+        self.charge_cost * lost_mwh / self.charged_mwh
+    }
+}
+
+/// Sign convention of DAM `Awarded Quantity` for storage resources
+/// (`--award-sign gen-positive|load-positive`). ERCOT's Gen Resource Data
+/// reports energy sold as positive MW and energy bought to charge as
+/// negative; some re-published datasets flip this and report charging
+/// (Load) awards as positive.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum AwardSign {
+    #[default]
+    GenPositive,
+    LoadPositive,
+}
+
+impl AwardSign {
+    /// Parse `--award-sign gen-positive|load-positive` (or `--award-sign=...`); defaults to gen-positive
+    pub fn from_args(args: &[String]) -> Result<Self> {
+        let value = args.iter().enumerate().find_map(|(i, arg)| {
+            if arg == "--award-sign" {
+                args.get(i + 1).cloned()
+            } else {
+                arg.strip_prefix("--award-sign=").map(str::to_string)
+            }
+        });
+
+        match value.as_deref() {
+            None | Some("gen-positive") => Ok(AwardSign::GenPositive),
+            Some("load-positive") => Ok(AwardSign::LoadPositive),
+            Some(other) => anyhow::bail!("Invalid --award-sign: {} (expected gen-positive or load-positive)", other),
+        }
+    }
+
+    /// Award as output MW: positive discharges (sells), negative charges (buys)
+    pub fn output_mw(self, awarded_mw: f64) -> f64 {
+        match self {
+            AwardSign::GenPositive => awarded_mw,
+            AwardSign::LoadPositive => -awarded_mw,
+        }
+    }
+}
+
+/// Awarded MWh a resource charged and discharged over the period
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct AwardTotals {
+    pub charge_mwh: f64,
+    pub discharge_mwh: f64,
+}
+
+impl AwardTotals {
+    /// Add one award's energy: positive discharges, negative charges
+    pub fn record(&mut self, energy_mwh: f64) {
+        if energy_mwh > 0.0 {
+            self.discharge_mwh += energy_mwh;
+        } else {
+            self.charge_mwh -= energy_mwh;
+        }
+    }
+}
+
+/// Resources whose awards only ever went one way. A battery that cycles
+/// both charges and discharges over a period, so many one-way resources
+/// (especially charge-only) suggest the award sign convention is flipped.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AwardDirectionCheck {
+    pub only_charge: Vec<String>,
+    pub only_discharge: Vec<String>,
+    pub both: usize,
+}
+
+impl AwardDirectionCheck {
+    pub fn new(totals: &HashMap<String, AwardTotals>) -> Self {
+        let mut check = Self::default();
+        for (resource, t) in totals {
+            match (t.charge_mwh > 0.0, t.discharge_mwh > 0.0) {
+                (true, true) => check.both += 1,
+                (true, false) => check.only_charge.push(resource.clone()),
+                (false, true) => check.only_discharge.push(resource.clone()),
+                (false, false) => {}
+            }
+        }
+        check.only_charge.sort();
+        check.only_discharge.sort();
+        check
+    }
+
+    /// Most resources with awards were only ever charged
+    pub fn likely_flipped(&self) -> bool {
+        self.only_charge.len() > self.both + self.only_discharge.len()
+    }
+}
+
+/// How energy revenue is reported (`--revenue-basis net|gross`). Gross adds
+/// discharge revenue and charge cost columns alongside the net figures.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RevenueBasis {
+    #[default]
+    Net,
+    Gross,
+}
+
+impl RevenueBasis {
+    /// Parse `--revenue-basis net|gross` (or `--revenue-basis=...`); defaults to net
+    pub fn from_args(args: &[String]) -> Result<Self> {
+        let value = args.iter().enumerate().find_map(|(i, arg)| {
+            if arg == "--revenue-basis" {
+                args.get(i + 1).cloned()
+            } else {
+                arg.strip_prefix("--revenue-basis=").map(str::to_string)
+            }
+        });
+
+        match value.as_deref() {
+            None | Some("net") => Ok(RevenueBasis::Net),
+            Some("gross") => Ok(RevenueBasis::Gross),
+            Some(other) => anyhow::bail!("Invalid --revenue-basis: {} (expected net or gross)", other),
+        }
+    }
+}
+
+/// What the energy revenue figures count (`--revenue-view net|gross-discharge`).
+/// Net subtracts charging cost; GrossDischarge reports discharge revenue
+/// alone, for leaderboards that ignore what the energy cost to buy. Applies
+/// to every report built on the daily rollup.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RevenueView {
+    #[default]
+    Net,
+    GrossDischarge,
+}
+
+impl RevenueView {
+    /// Parse `--revenue-view net|gross-discharge` (or `--revenue-view=...`); defaults to net
+    pub fn from_args(args: &[String]) -> Result<Self> {
+        let value = args.iter().enumerate().find_map(|(i, arg)| {
+            if arg == "--revenue-view" {
+                args.get(i + 1).cloned()
+            } else {
+                arg.strip_prefix("--revenue-view=").map(str::to_string)
+            }
+        });
+
+        match value.as_deref() {
+            None | Some("net") => Ok(RevenueView::Net),
+            Some("gross-discharge") => Ok(RevenueView::GrossDischarge),
+            Some(other) => anyhow::bail!("Invalid --revenue-view: {} (expected net or gross-discharge)", other),
+        }
+    }
+
+    /// Energy revenue of one settlement under this view
+    pub fn energy_revenue(&self, settlement: &EnergySettlement) -> f64 {
+        match self {
+            RevenueView::Net => settlement.net(),
+            RevenueView::GrossDischarge => settlement.discharge_revenue,
+        }
+    }
+
+    /// Energy revenue column expression for `market` (`DAM` or `RT`) over
+    /// the rollup's `{market}_Discharge_Revenue` and `{market}_Charge_Cost`
+    fn energy_expr(&self, market: &str) -> Expr {
+        let discharge = col(&format!("{}_Discharge_Revenue", market));
+        match self {
+            RevenueView::Net => discharge + col(&format!("{}_Charge_Cost", market)),
+            RevenueView::GrossDischarge => discharge,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -35,20 +304,61 @@ pub struct AsDispatchEvent {
     pub compliance: bool,
 }
 
+#[derive(Debug, Clone)]
+pub struct LeaderboardEntry {
+    pub resource_name: String,
+    pub revenue_per_mw: f64,
+    pub annualized_revenue: f64,
+    pub capacity_mw: f64,
+    pub duration_hours: f64,
+    pub benchmark_per_mw: f64,
+    pub vs_benchmark_pct: f64,
+}
+
+/// Revenue and days of data for one resource over the analysis period
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResourceCoverage {
+    pub resource_name: String,
+    pub days: u32,
+    pub total_revenue: f64,
+}
+
+impl ResourceCoverage {
+    pub fn annualized_revenue(&self) -> f64 {
+        self.total_revenue / self.days as f64 * 365.0
+    }
+}
+
 pub struct BessRevenueCalculator {
     data_dir: PathBuf,
     output_dir: PathBuf,
     bess_resources: HashMap<String, (String, f64)>, // name -> (settlement_point, capacity)
-    settlement_point_map: HashMap<String, String>, // resource_name -> RT settlement point
-    rt_prices: HashMap<(String, NaiveDate, i64), f64>, // Cached RT prices
-    dam_prices: HashMap<(String, NaiveDate, i32), f64>, // Cached DAM prices
+    settlement_point_map: SettlementPointMap, // resource_name -> RT settlement point by date
+    settlement_point_aliases: SettlementPointAliases, // renamed point -> current name
+    settlement_point_list: SettlementPointList, // resource node -> load zone, from ERCOT's list
+    rt_price_basis: RtPriceBasisConfig, // resource_name -> SPP or LMP
+    resource_durations: ResourceDurations, // resource_name -> duration hours, with fallback
+    benchmarks: PeerBenchmarks,
+    dataset_paths: DatasetPaths, // glob patterns for the price datasets
+    rt_prices: PriceStore, // RT prices, in memory or read on demand
+    rt_lmps: PriceIndex, // 15-minute mean LMPs, kept only for resources on LMP basis
+    dam_prices: PriceIndex, // Cached DAM prices
+    duplicate_prices: DuplicatePricePolicy, // Repeated (point, interval) rows within a price file
     ancillary_prices: HashMap<(String, NaiveDate, i32), HashMap<String, f64>>, // Cached AS prices
+    revenue_basis: RevenueBasis,
+    revenue_view: RevenueView,
+    award_sign: AwardSign,
+    min_days_for_annualization: u32,
+    scarcity_price: f64, // HB_HUBAVG RT price above which an interval is scarcity
+    dump_unmatched: Option<PathBuf>, // where to write intervals with no RT price at their settlement point
+    date_range: DateRange, // operating days to settle
+    #[cfg(feature = "sqlite")]
+    sqlite_path: Option<PathBuf>, // SQLite database to also write the revenue tables to
+    timer: StageTimer,
 }
 
 impl BessRevenueCalculator {
-    fn load_settlement_point_mapping(output_dir: &Path) -> HashMap<String, String> {
-        let mut map = HashMap::new();
-        
+    fn load_settlement_point_mapping(output_dir: &Path) -> SettlementPointMap {
         // Try to load the updated mapping file first
         let updated_path = output_dir.join("settlement_point_mapping_updated.csv");
         let path = if updated_path.exists() {
@@ -57,59 +367,106 @@ impl BessRevenueCalculator {
             output_dir.join("settlement_point_mapping.csv")
         };
         
-        if let Ok(file) = std::fs::File::open(&path) {
-            if let Ok(df) = CsvReader::new(file)
-                .has_header(true)
-                .finish() {
-                
-                if let (Ok(resources), Ok(settlement_points)) = (
-                    df.column("Resource_Name"),
-                    df.column("Settlement_Point")
-                ) {
-                    let resources_utf8 = resources.utf8().unwrap();
-                    let sps_utf8 = settlement_points.utf8().unwrap();
-                    
-                    for i in 0..df.height() {
-                        if let (Some(resource), Some(sp)) = 
-                            (resources_utf8.get(i), sps_utf8.get(i)) {
-                            map.insert(resource.to_string(), sp.to_string());
-                        }
-                    }
-                    
-                    println!("    Loaded {} settlement point mappings from {}", 
-                             map.len(), path.file_name().unwrap().to_str().unwrap());
-                }
+        if !path.exists() {
+            return SettlementPointMap::default();
+        }
+        
+        match SettlementPointMap::load(&path) {
+            Ok(map) => {
+                println!("    Loaded {} settlement point mappings from {}", 
+                         map.len(), path.file_name().unwrap().to_str().unwrap());
+                map
+            }
+            Err(e) => {
+                warn!("Ignoring settlement point mapping {}: {}", path.display(), e);
+                SettlementPointMap::default()
             }
         }
+    }
+    
+    fn load_settlement_point_aliases(output_dir: &Path) -> SettlementPointAliases {
+        let path = output_dir.join("aliases.csv");
+        if !path.exists() {
+            return SettlementPointAliases::default();
+        }
         
-        map
+        match SettlementPointAliases::load(&path) {
+            Ok(aliases) => {
+                println!("    Loaded {} settlement point aliases from aliases.csv", aliases.len());
+                aliases
+            }
+            Err(e) => {
+                warn!("Ignoring settlement point aliases {}: {}", path.display(), e);
+                SettlementPointAliases::default()
+            }
+        }
     }
     
-    pub fn new(bess_master_list_path: &Path) -> Result<Self> {
-        let data_dir = PathBuf::from("disclosure_data");
-        let output_dir = PathBuf::from("bess_analysis");
+    fn load_settlement_point_list(output_dir: &Path) -> SettlementPointList {
+        let path = output_dir.join("settlement_points_list.csv");
+        if !path.exists() {
+            return SettlementPointList::default();
+        }
         
-        // Load BESS resources from master list
-        let master_df = CsvReader::new(std::fs::File::open(bess_master_list_path)?)
-            .has_header(true)
-            .finish()?;
-        
-        let mut bess_resources = HashMap::new();
-        let names = master_df.column("Resource_Name")?.utf8()?;
-        let settlement_points = master_df.column("Settlement_Point")?.utf8()?;
-        let capacities = master_df.column("Max_Capacity_MW")?.f64()?;
-        
-        for i in 0..master_df.height() {
-            if let (Some(name), Some(sp), Some(cap)) = 
-                (names.get(i), settlement_points.get(i), capacities.get(i)) {
-                bess_resources.insert(name.to_string(), (sp.to_string(), cap));
+        match SettlementPointList::load(&path) {
+            Ok(list) => {
+                println!("    Loaded load zones for {} resource nodes from settlement_points_list.csv", list.len());
+                list
+            }
+            Err(e) => {
+                warn!("Ignoring settlement point list {}: {}", path.display(), e);
+                SettlementPointList::default()
+            }
+        }
+    }
+    
+    fn load_rt_price_basis(output_dir: &Path) -> RtPriceBasisConfig {
+        let path = output_dir.join("rt_price_basis.csv");
+        if !path.exists() {
+            return RtPriceBasisConfig::default();
+        }
+        
+        match RtPriceBasisConfig::load(&path) {
+            Ok(config) => {
+                let lmp_resources = config.lmp_resources();
+                println!("    RT revenue priced at nodal LMP for {} resources (rt_price_basis.csv)", lmp_resources.len());
+                run_metadata::record_config("rt_price_basis:LMP", lmp_resources.join(","));
+                config
+            }
+            Err(e) => {
+                warn!("Ignoring RT price basis {}: {}", path.display(), e);
+                RtPriceBasisConfig::default()
             }
         }
+    }
+    
+    pub fn new(
+        bess_master_list_path: &Path,
+        price_loading: PriceLoading,
+        duplicate_prices: DuplicatePricePolicy,
+        timer: StageTimer,
+        output_prefix: &OutputPrefix,
+    ) -> Result<Self> {
+        let data_dir = output_prefix.input_path("disclosure_data");
+        let output_dir = output_prefix.output_dir("bess_analysis");
+        
+        // Load BESS resources from master list
+        let master_list = load_master_list(bess_master_list_path)?;
+        let bess_resources: HashMap<String, (String, f64)> = master_list.iter()
+            .map(|r| (r.name.clone(), (r.settlement_point.clone(), r.capacity_mw)))
+            .collect();
         
         println!("Loaded {} BESS resources for revenue calculation", bess_resources.len());
         
+        let resource_durations = ResourceDurations::from_resources(&master_list);
+        let benchmarks = PeerBenchmarks::load()?;
+        let dataset_paths = DatasetPaths::load()?;
+        
         // Load updated settlement point mapping if available
         let settlement_point_map = Self::load_settlement_point_mapping(&output_dir);
+        let settlement_point_aliases = Self::load_settlement_point_aliases(&output_dir);
+        let settlement_point_list = Self::load_settlement_point_list(&output_dir);
+        let rt_price_basis = Self::load_rt_price_basis(&output_dir);
         
         // Load all price data at initialization
         let mut calculator = Self {
@@ -117,25 +474,168 @@ impl BessRevenueCalculator {
             output_dir,
             bess_resources,
             settlement_point_map,
-            rt_prices: HashMap::new(),
-            dam_prices: HashMap::new(),
+            settlement_point_aliases,
+            settlement_point_list,
+            rt_price_basis,
+            resource_durations,
+            benchmarks,
+            dataset_paths,
+            rt_prices: PriceStore::Eager(PriceIndex::new(RT_PRICE_INTERVAL)),
+            rt_lmps: PriceIndex::new(RT_PRICE_INTERVAL),
+            dam_prices: PriceIndex::new(IntervalLength::Hourly),
+            duplicate_prices,
             ancillary_prices: HashMap::new(),
+            revenue_basis: RevenueBasis::default(),
+            revenue_view: RevenueView::default(),
+            award_sign: AwardSign::default(),
+            min_days_for_annualization: DEFAULT_MIN_DAYS_FOR_ANNUALIZATION,
+            scarcity_price: DEFAULT_SCARCITY_PRICE,
+            dump_unmatched: None,
+            date_range: DateRange::default(),
+            #[cfg(feature = "sqlite")]
+            sqlite_path: None,
+            timer,
         };
         
         // Load all available price data
-        calculator.load_all_price_data()?;
+        let timer = calculator.timer.clone();
+        timer.time("Load prices", || calculator.load_all_price_data(price_loading, output_prefix.input_path("annual_output")))?;
         
         Ok(calculator)
     }
     
-    fn load_all_price_data(&mut self) -> Result<()> {
+    pub fn with_revenue_basis(mut self, revenue_basis: RevenueBasis) -> Self {
+        self.revenue_basis = revenue_basis;
+        self
+    }
+    
+    pub fn with_revenue_view(mut self, revenue_view: RevenueView) -> Self {
+        self.revenue_view = revenue_view;
+        self
+    }
+    
+    pub fn with_award_sign(mut self, award_sign: AwardSign) -> Self {
+        self.award_sign = award_sign;
+        self
+    }
+    
+    /// Keep only resources matching `--resource-regex`
+    pub fn with_resource_filter(mut self, filter: &ResourceFilter) -> Self {
+        filter.retain(&mut self.bess_resources);
+        self
+    }
+    
+    /// Duration assumed for resources the master list gives none for
+    pub fn with_default_duration_hours(mut self, hours: f64) -> Self {
+        self.resource_durations = self.resource_durations.with_default_hours(hours);
+        self
+    }
+    
+    pub fn with_min_days_for_annualization(mut self, min_days: u32) -> Self {
+        self.min_days_for_annualization = min_days;
+        self
+    }
+    
+    /// RT price at HB_HUBAVG above which an interval counts toward scarcity capture rates
+    pub fn with_scarcity_price(mut self, price: f64) -> Self {
+        self.scarcity_price = price;
+        self
+    }
+    
+    /// Drop or cap anomalous RT and DAM prices before any revenue is computed
+    pub fn with_price_outlier_filter(mut self, filter: Option<PriceOutlierFilter>) -> Self {
+        let Some(filter) = filter else {
+            return self;
+        };
+        match &mut self.rt_prices {
+            PriceStore::Eager(rt_prices) => {
+                filter.apply_and_log(rt_prices, "RT prices");
+            }
+            PriceStore::Lazy(_) => warn!("Price outlier filter is not applied to lazily loaded RT prices"),
+        }
+        if !self.rt_lmps.is_empty() {
+            filter.apply_and_log(&mut self.rt_lmps, "RT LMPs");
+        }
+        filter.apply_and_log(&mut self.dam_prices, "DAM prices");
+        self
+    }
+    
+    /// Write every interval with no RT price at its settlement point to `path`
+    pub fn with_dump_unmatched(mut self, path: Option<PathBuf>) -> Self {
+        self.dump_unmatched = path;
+        self
+    }
+    
+    /// Settle only operating days in `date_range`, skipping disclosure files
+    /// dated outside it
+    pub fn with_date_range(mut self, date_range: DateRange) -> Self {
+        self.date_range = date_range;
+        self
+    }
+    
+    /// Also write the daily and annual revenue tables to the SQLite database
+    /// at `path`, as `daily_revenues` and `annual_revenues`
+    #[cfg(feature = "sqlite")]
+    pub fn with_sqlite(mut self, path: Option<PathBuf>) -> Self {
+        self.sqlite_path = path;
+        self
+    }
+    
+    /// Write the loaded RT, DAM and AS price caches to `rt_price_cache.parquet`,
+    /// `dam_price_cache.parquet` and `as_price_cache.parquet` in `dir`, as
+    /// keyed for revenue lookups. RT prices read under `--price-loading lazy`
+    /// are not cached up front and are skipped.
+    pub fn export_price_cache(&self, dir: &Path) -> Result<()> {
+        std::fs::create_dir_all(dir)?;
+        match &self.rt_prices {
+            PriceStore::Eager(rt_prices) => {
+                write_parquet(&mut price_index_frame(rt_prices)?, &dir.join("rt_price_cache.parquet"))?;
+            }
+            PriceStore::Lazy(_) => warn!("Not exporting RT prices: they are read lazily and not cached up front"),
+        }
+        write_parquet(&mut price_index_frame(&self.dam_prices)?, &dir.join("dam_price_cache.parquet"))?;
+        write_parquet(&mut ancillary_price_frame(&self.ancillary_prices)?, &dir.join("as_price_cache.parquet"))?;
+        println!("✅ Exported price caches to: {}", dir.display());
+        Ok(())
+    }
+    
+    fn load_all_price_data(&mut self, price_loading: PriceLoading, annual_output_dir: PathBuf) -> Result<()> {
         println!("📊 Loading all available price data...");
         
-        // Load RT prices
-        self.load_all_rt_prices()?;
+        match price_loading {
+            PriceLoading::Eager => {
+                // Load RT prices
+                let (rt_prices, rt_lmps) = self.load_all_rt_prices()?;
+                self.rt_prices = PriceStore::Eager(rt_prices);
+                if !self.rt_price_basis.lmp_resources().is_empty() {
+                    self.rt_lmps = rt_lmps;
+                }
+                
+                // Load DAM prices
+                self.load_all_dam_prices()?;
+
+                if let PriceStore::Eager(rt_prices) = &self.rt_prices {
+                    self.timer.add_rows("Load prices", rt_prices.len() + self.dam_prices.len());
+                }
+            }
+            PriceLoading::Lazy => {
+                // RT prices are read per settlement point-day as SCED data needs them
+                self.rt_prices = PriceStore::Lazy(
+                    LazyPriceStore::new(annual_output_dir, DEFAULT_LAZY_CACHE_DAYS)
+                        .with_duplicate_prices(self.duplicate_prices),
+                );
+                println!("    Lazy price loading: {}", self.rt_prices.describe());
+                
+                // DAM prices are hourly and small enough to load in full
+                self.load_all_dam_prices()?;
+            }
+        }
         
-        // Load DAM prices
-        self.load_all_dam_prices()?;
+        let lmp_resources = self.rt_price_basis.lmp_resources();
+        if !lmp_resources.is_empty() && self.rt_lmps.is_empty() {
+            warn!("{} resources are set to LMP basis but no RT LMPs are loaded (eager loading with [rt_lmps] files is needed); pricing them at SPP",
+                  lmp_resources.len());
+        }
         
         // Load Ancillary Service prices
         self.load_all_ancillary_prices()?;
@@ -144,78 +644,74 @@ impl BessRevenueCalculator {
         Ok(())
     }
     
-    fn load_all_rt_prices(&mut self) -> Result<()> {
-        // Check both unified_processed_data and annual_data directories
-        let patterns = vec![
-            "unified_processed_data/RT_Settlement_Point_Prices_*/RT_Settlement_Point_Prices_*.csv",
-            "unified_processed_data/RT_LMPs_*/RT_LMPs_*.csv",
-            "annual_data/RT_Settlement_Point_Prices_*.csv",
-            "annual_data/RT_LMPs_*.csv",
-        ];
-        
-        for pattern in patterns {
-            let files: Vec<PathBuf> = glob::glob(pattern)?
-                .filter_map(Result::ok)
-                .collect();
-            
-            for file in files {
-                println!("    Loading RT prices from: {}", file.display());
-                let prices = self.load_rt_prices(&file)?;
-                self.rt_prices.extend(prices);
+    /// Settlement point prices keyed at 15 minutes, and the LMPs averaged to
+    /// the same intervals. 5-minute LMPs load into their own index and only
+    /// stand in for a settlement point price where a point has no SPP for
+    /// that interval, unless a resource is priced at LMP.
+    fn load_all_rt_prices(&self) -> Result<(PriceIndex, PriceIndex)> {
+        let mut rt_prices = PriceIndex::new(RtPriceProduct::SettlementPointPrice.interval());
+        let mut lmps = PriceIndex::new(RtPriceProduct::Lmp.interval());
+        let mut spp_overwrites = MergeOverwrites::default();
+        let mut lmp_overwrites = MergeOverwrites::default();
+        for (dataset, expected) in [(RT_PRICES, RtPriceProduct::SettlementPointPrice), (RT_LMPS, RtPriceProduct::Lmp)] {
+            for file in self.dataset_paths.files(dataset)? {
+                let product = match RtPriceProduct::from_file_name(&file) {
+                    Some(named) if named != expected => {
+                        warn!("{} is listed under [{}] but its name marks it as {}; loading it as such",
+                              file.display(), dataset, named.label());
+                        named
+                    }
+                    _ => expected,
+                };
+                println!("    Loading {} from: {}", product.label(), file.display());
+                let prices = self.load_rt_prices(&file, product)?;
+                match product {
+                    RtPriceProduct::SettlementPointPrice => spp_overwrites.extend(&mut rt_prices, prices, &file),
+                    RtPriceProduct::Lmp => lmp_overwrites.extend(&mut lmps, prices, &file),
+                }
+            }
+        }
+        spp_overwrites.warn("RT settlement point");
+        lmp_overwrites.warn("RT LMP");
+
+        let lmps = lmps.resample_mean(RT_PRICE_INTERVAL);
+        if !lmps.is_empty() {
+            let filled = rt_prices.fill_missing(lmps.clone());
+            if filled > 0 {
+                warn!("{} 15-minute RT intervals had no settlement point price and are settled at the mean of their three 5-minute LMPs",
+                      filled);
             }
         }
         
-        println!("    Loaded {} total RT price points", self.rt_prices.len());
-        Ok(())
+        println!("    Loaded {} total RT price points at {} settlement points ({:.1} MB)",
+                 rt_prices.len(), rt_prices.point_count(), rt_prices.approx_bytes() as f64 / 1e6);
+        Ok((rt_prices, lmps))
     }
     
     fn load_all_dam_prices(&mut self) -> Result<()> {
-        // Load DAM prices from processed files
-        let patterns = vec![
-            "unified_processed_data/DAM_Settlement_Point_Prices_*/DAM_Settlement_Point_Prices_*.csv",
-            "unified_processed_data/DAM_Hourly_LMPs_*/DAM_Hourly_LMPs_*.csv",
-            "dam_annual_data/DAM_Settlement_Point_Prices_*.csv",
-            "dam_annual_data/DAM_Hourly_LMPs_*.csv",
-        ];
-        
-        for pattern in patterns {
-            let files: Vec<PathBuf> = glob::glob(pattern)?
-                .filter_map(Result::ok)
-                .collect();
-            
-            for file in files {
-                println!("    Loading DAM prices from: {}", file.display());
-                let prices = self.load_dam_prices(&file)?;
-                self.dam_prices.extend(prices);
-            }
+        let mut overwrites = MergeOverwrites::default();
+        for file in self.dataset_paths.files(DAM_PRICES)? {
+            println!("    Loading DAM prices from: {}", file.display());
+            let prices = self.load_dam_prices(&file)?;
+            overwrites.extend(&mut self.dam_prices, prices, &file);
         }
+        overwrites.warn("DAM");
         
-        println!("    Loaded {} total DAM price points", self.dam_prices.len());
+        println!("    Loaded {} total DAM price points at {} settlement points ({:.1} MB)",
+                 self.dam_prices.len(), self.dam_prices.point_count(), self.dam_prices.approx_bytes() as f64 / 1e6);
         Ok(())
     }
     
     fn load_all_ancillary_prices(&mut self) -> Result<()> {
-        // Load ancillary service clearing prices
-        let patterns = vec![
-            "unified_processed_data/DAM_Clearing_Prices_Capacity_*/DAM_Clearing_Prices_Capacity_*.csv",
-            "ancillary_annual_data/DAM_Clearing_Prices_Capacity_*.csv",
-        ];
-        
-        for pattern in patterns {
-            let files: Vec<PathBuf> = glob::glob(pattern)?
-                .filter_map(Result::ok)
-                .collect();
+        for file in self.dataset_paths.files(ANCILLARY_PRICES)? {
+            println!("    Loading AS prices from: {}", file.display());
+            let prices = self.load_ancillary_service_prices(&file)?;
             
-            for file in files {
-                println!("    Loading AS prices from: {}", file.display());
-                let prices = self.load_ancillary_service_prices(&file)?;
-                
-                // Merge AS prices into the map
-                for ((date, hour), service_prices) in prices {
-                    self.ancillary_prices.entry(("ERCOT".to_string(), date, hour))
-                        .or_insert_with(HashMap::new)
-                        .extend(service_prices);
-                }
+            // Merge AS prices into the map
+            for ((date, hour), service_prices) in prices {
+                self.ancillary_prices.entry(("ERCOT".to_string(), date, hour))
+                    .or_insert_with(HashMap::new)
+                    .extend(service_prices);
             }
         }
         
@@ -229,53 +725,70 @@ impl BessRevenueCalculator {
     pub fn calculate_all_revenues(&self) -> Result<()> {
         println!("💰 BESS Revenue Calculation");
         println!("{}", "=".repeat(80));
+        self.resource_durations.warn_defaults(self.bess_resources.keys());
+        self.date_range.print_summary();
         
         // Process energy revenues (now returns separate DAM and RT)
         let (dam_revenues, rt_revenues) = self.calculate_energy_revenues_split()?;
         
         // Process ancillary service revenues
-        let as_revenues = self.calculate_ancillary_revenues()?;
+        let as_revenues = self.timer.time("AS", || self.calculate_ancillary_revenues())?;
         
         // Combine and create daily rollups
-        let daily_revenues = self.create_daily_rollups_split(dam_revenues, rt_revenues, as_revenues)?;
+        let daily_revenues = self.timer.time("Rollup", || self.create_daily_rollups_split(dam_revenues, rt_revenues, as_revenues))?;
+        self.timer.add_rows("Rollup", daily_revenues.len());
         
         // Detect SOC violations and AS failures
-        self.detect_operational_issues(&daily_revenues)?;
-        
-        // Generate performance metrics
-        self.generate_performance_metrics(&daily_revenues)?;
+        self.timer.time("Operational checks", || self.detect_operational_issues())?;
         
-        // Generate detailed revenue breakdown
-        self.generate_detailed_revenue_breakdown(&daily_revenues)?;
+        self.timer.time("Save", || {
+            // Generate performance metrics
+            self.generate_performance_metrics(&daily_revenues)?;
+            
+            // Trailing 30/90-day revenue per resource
+            for window_days in [30, 90] {
+                self.save_rolling_revenue(&daily_revenues, window_days)?;
+            }
+            
+            // Spread of daily revenue per resource
+            self.save_revenue_histogram(&daily_revenues)?;
+            
+            // Days that drove each resource's revenue
+            self.save_best_worst_days(&daily_revenues)?;
+            
+            // Generate detailed revenue breakdown
+            self.generate_detailed_revenue_breakdown(&daily_revenues)
+        })?;
+        self.timer.add_rows("Save", daily_revenues.len());
         
         Ok(())
     }
 
-    fn calculate_energy_revenues_split(&self) -> Result<(HashMap<(String, NaiveDate), f64>, HashMap<(String, NaiveDate), f64>)> {
+    fn calculate_energy_revenues_split(&self) -> Result<(HashMap<(String, NaiveDate), EnergySettlement>, HashMap<(String, NaiveDate), EnergySettlement>)> {
         println!("\n📊 Calculating Energy Arbitrage Revenues...");
         
         let mut energy_revenues = HashMap::new();
         
         // First, calculate DAM costs (charging)
         println!("  📥 Calculating DAM energy costs (charging)...");
-        let dam_costs = self.calculate_dam_energy_costs()?;
+        let dam_costs = self.timer.time("DAM", || self.calculate_dam_energy_costs())?;
         
         // Then, calculate RT revenues (discharging)
         println!("  📤 Calculating RT energy revenues (discharging)...");
-        let rt_revenues = self.calculate_rt_energy_revenues()?;
+        let rt_revenues = self.timer.time("RT", || self.calculate_rt_energy_revenues())?;
         
         // Combine DAM costs and RT revenues
         for (key, dam_cost) in &dam_costs {
-            *energy_revenues.entry(key.clone()).or_insert(0.0) += dam_cost;
+            *energy_revenues.entry(key.clone()).or_insert(0.0) += dam_cost.net();
         }
         
         for (key, rt_revenue) in &rt_revenues {
-            *energy_revenues.entry(key.clone()).or_insert(0.0) += rt_revenue;
+            *energy_revenues.entry(key.clone()).or_insert(0.0) += rt_revenue.net();
         }
         
         // Calculate total
-        let total_dam: f64 = dam_costs.values().sum();
-        let total_rt: f64 = rt_revenues.values().sum();
+        let total_dam: f64 = dam_costs.values().map(EnergySettlement::net).sum();
+        let total_rt: f64 = rt_revenues.values().map(EnergySettlement::net).sum();
         let total_energy: f64 = energy_revenues.values().sum();
         
         println!("\n  Energy Revenue Summary:");
@@ -287,18 +800,21 @@ impl BessRevenueCalculator {
         Ok((dam_costs, rt_revenues))
     }
     
-    fn calculate_dam_energy_costs(&self) -> Result<HashMap<(String, NaiveDate), f64>> {
-        let mut dam_costs = HashMap::new();
-        let mut dam_revenues = HashMap::new();
-        let mut dam_net = HashMap::new();
+    fn calculate_dam_energy_costs(&self) -> Result<HashMap<(String, NaiveDate), EnergySettlement>> {
+        let mut dam_energy: HashMap<(String, NaiveDate), EnergySettlement> = HashMap::new();
+        let mut award_totals: HashMap<String, AwardTotals> = HashMap::new();
+        let mut settled_hours = SettledAwardHours::default();
+        let mut missed_awards = Vec::new();
         
         // Use DAM Gen Resource Data instead of Energy Bid Awards
         let dam_pattern = self.data_dir.join("DAM_extracted/60d_DAM_Gen_Resource_Data*.csv");
         let dam_files: Vec<PathBuf> = glob::glob(dam_pattern.to_str().unwrap())?
             .filter_map(Result::ok)
+            .filter(|path| self.date_range.includes_file(path))
             .collect();
         
         println!("    Processing {} DAM Gen Resource Data files (separating charging costs and discharging revenues)", dam_files.len());
+        run_metadata::record_inputs("DAM Gen Resource Data", dam_files.len());
         
         let pb = indicatif::ProgressBar::new(dam_files.len() as u64);
         pb.set_style(indicatif::ProgressStyle::default_bar()
@@ -308,108 +824,90 @@ impl BessRevenueCalculator {
         for file_path in dam_files {
             pb.inc(1);
             
-            if let Ok(df) = CsvReader::new(std::fs::File::open(&file_path)?)
-                .has_header(true)
-                .finish() {
+            if let Ok(df) = read_csv(&file_path) {
                 
                 // Filter for BESS resources (PWRSTR type)
                 if let Ok(resource_types) = df.column("Resource Type") {
                     let mask = resource_types.utf8()?.equal("PWRSTR");
                     
                     if let Ok(filtered) = df.filter(&mask) {
-                        // Process PWRSTR resources
-                        if let (Ok(dates), Ok(hours), Ok(resources), Ok(awards), Ok(prices)) = (
-                            filtered.column("Delivery Date"),
-                            filtered.column("Hour Ending"),
-                            filtered.column("Resource Name"),
-                            filtered.column("Awarded Quantity"),
-                            filtered.column("Energy Settlement Point Price")
-                        ) {
-                            let dates_utf8 = dates.utf8()?;
-                            let hours_i64 = hours.i64()?;
-                            let resources_utf8 = resources.utf8()?;
-                            
-                            // Handle awarded quantity - might be string or float
-                            let awards_f64 = if let Ok(f64_col) = awards.f64() {
-                                f64_col.clone()
-                            } else if let Ok(utf8_col) = awards.utf8() {
-                                // Convert string to float
-                                let values: Vec<Option<f64>> = utf8_col.into_iter()
-                                    .map(|v| v.and_then(|s| if s.is_empty() { Some(0.0) } else { s.parse().ok() }))
-                                    .collect();
-                                Float64Chunked::from_iter(values)
-                            } else {
-                                continue;
-                            };
-                            
-                            let prices_f64 = prices.f64()?;
-                            
-                            for i in 0..filtered.height() {
-                                if let (Some(date_str), Some(_hour), Some(resource), Some(award_mw), Some(price)) = 
-                                    (dates_utf8.get(i), hours_i64.get(i), resources_utf8.get(i), 
-                                     awards_f64.get(i), prices_f64.get(i)) {
-                                    
-                                    // Check if this is one of our BESS resources
-                                    if self.bess_resources.contains_key(resource) {
-                                        // Parse date
-                                        if let Ok(date) = NaiveDate::parse_from_str(date_str, "%m/%d/%Y") {
-                                            let key = (resource.to_string(), date);
-                                            
-                                            // Separate charging costs from discharging revenues
-                                            if award_mw < 0.0 {
-                                                // Charging (negative MW) = cost
-                                                let cost = award_mw * price; // Negative MW * $/MWh = negative $
-                                                *dam_costs.entry(key.clone()).or_insert(0.0) += cost;
-                                            } else if award_mw > 0.0 {
-                                                // Discharging (positive MW) = revenue
-                                                let revenue = award_mw * price; // Positive MW * $/MWh = positive $
-                                                *dam_revenues.entry(key.clone()).or_insert(0.0) += revenue;
-                                            }
-                                            
-                                            // Net revenue
-                                            let net = award_mw * price;
-                                            *dam_net.entry(key).or_insert(0.0) += net;
-                                        }
-                                    }
-                                }
-                            }
+                        self.timer.add_rows("DAM", filtered.height());
+                        // Offered below the DAM price but not cleared
+                        match offer_curves::missed_awards(&filtered) {
+                            Ok(missed) => missed_awards.extend(missed.into_iter()
+                                .filter(|m| self.bess_resources.contains_key(&m.resource_name))),
+                            Err(e) => warn!("Skipping offer curves in {}: {}", file_path.display(), e),
                         }
+                        
+                        // Process PWRSTR resources
+                        settle_dam_awards(&filtered, self.award_sign, |resource| self.bess_resources.contains_key(resource),
+                                          &mut dam_energy, &mut award_totals, &mut settled_hours)?;
                     }
                 }
             }
         }
         
         pb.finish();
+        if settled_hours.duplicates() > 0 {
+            warn!("Skipped {} DAM energy award rows repeating a resource-hour (sub-hourly or duplicated data); \
+                   awards are MW for a whole hour, so each resource-hour is settled once", settled_hours.duplicates());
+        }
+        dam_energy.retain(|(_, date), _| self.date_range.contains(*date));
         
         // Report DAM breakdown
-        let total_charging: f64 = dam_costs.values().sum();
-        let total_discharging: f64 = dam_revenues.values().sum();
-        let total_net: f64 = dam_net.values().sum();
+        let total_charging: f64 = dam_energy.values().map(|e| e.charge_cost).sum();
+        let total_discharging: f64 = dam_energy.values().map(|e| e.discharge_revenue).sum();
+        let total_net: f64 = dam_energy.values().map(EnergySettlement::net).sum();
         
         println!("      DAM Energy Breakdown:");
         println!("        Charging costs: ${:.2}", total_charging);
         println!("        Discharging revenues: ${:.2}", total_discharging);
         println!("        Net DAM energy: ${:.2}", total_net);
         
-        Ok(dam_net)
+        let directions = AwardDirectionCheck::new(&award_totals);
+        if !directions.only_charge.is_empty() || !directions.only_discharge.is_empty() {
+            warn!("DAM awards went only one way for {} charge-only and {} discharge-only resources ({} both)",
+                  directions.only_charge.len(), directions.only_discharge.len(), directions.both);
+            debug!("Charge-only: {:?}; discharge-only: {:?}", directions.only_charge, directions.only_discharge);
+        }
+        if directions.likely_flipped() {
+            warn!("Most batteries were only awarded to charge under the {:?} award sign convention; \
+                   if this dataset reports charging as positive MW, rerun with --award-sign {}",
+                  self.award_sign,
+                  if self.award_sign == AwardSign::GenPositive { "load-positive" } else { "gen-positive" });
+        }
+        
+        missed_awards.sort_by(|a, b| a.resource_name.cmp(&b.resource_name)
+            .then(a.delivery_date.cmp(&b.delivery_date))
+            .then(a.hour_ending.cmp(&b.hour_ending)));
+        let total_missed: f64 = missed_awards.iter().map(|m| m.missed_margin).sum();
+        println!("        Uncleared in-the-money offers: {} hours, ${:.2} missed margin",
+                 missed_awards.len(), total_missed);
+        let output_path = self.output_dir.join("opportunity_cost.csv");
+        offer_curves::save_opportunity_costs(&missed_awards, &output_path)?;
+        println!("        Saved opportunity cost to: {}", output_path.display());
+        
+        Ok(dam_energy)
     }
     
-    fn calculate_rt_energy_revenues(&self) -> Result<HashMap<(String, NaiveDate), f64>> {
+    fn calculate_rt_energy_revenues(&self) -> Result<HashMap<(String, NaiveDate), EnergySettlement>> {
         let mut rt_revenues = HashMap::new();
+        let mut unmatched = UnmatchedPrices::default();
         
         // Load RT SCED Gen Resource Data
         let sced_pattern = self.data_dir.join("SCED_extracted/60d_SCED_Gen_Resource_Data*.csv");
         let sced_files: Vec<PathBuf> = glob::glob(sced_pattern.to_str().unwrap())?
             .filter_map(Result::ok)
+            .filter(|path| self.date_range.includes_file(path))
             .collect();
         
         println!("    Processing {} SCED Gen Resource Data files (both charging and discharging)", sced_files.len());
+        run_metadata::record_inputs("SCED Gen Resource Data", sced_files.len());
         
         // Use cached RT prices
-        if self.rt_prices.is_empty() {
-            println!("    ⚠️  No RT prices loaded!");
-        } else {
-            println!("    Using {} cached RT price points", self.rt_prices.len());
+        match &self.rt_prices {
+            PriceStore::Eager(index) if index.is_empty() => warn!("No RT prices loaded!"),
+            store => println!("    Using {}", store.describe()),
         }
         
         let pb = indicatif::ProgressBar::new(sced_files.len() as u64);
@@ -420,16 +918,15 @@ impl BessRevenueCalculator {
         for file_path in sced_files {
             pb.inc(1);
             
-            if let Ok(df) = CsvReader::new(std::fs::File::open(&file_path)?)
-                .has_header(true)
-                .finish() {
+            if let Ok(df) = read_csv(&file_path) {
                 
                 // Filter for BESS resources (PWRSTR type)
                 if let Ok(resource_types) = df.column("Resource Type") {
                     let mask = resource_types.utf8()?.equal("PWRSTR");
                     
                     if let Ok(filtered) = df.filter(&mask) {
-                        self.process_rt_output(&filtered, &self.rt_prices, &mut rt_revenues)?;
+                        self.timer.add_rows("RT", filtered.height());
+                        self.process_rt_output(&filtered, &self.rt_prices, SCED_DISPATCH_INTERVAL, &mut rt_revenues, &mut unmatched)?;
                     }
                 }
             }
@@ -442,10 +939,12 @@ impl BessRevenueCalculator {
         let smne_pattern = self.data_dir.join("SCED_extracted/60d_SCED_SMNE_GEN_RES*.csv");
         let smne_files: Vec<PathBuf> = glob::glob(smne_pattern.to_str().unwrap())?
             .filter_map(Result::ok)
+            .filter(|path| self.date_range.includes_file(path))
             .collect();
             
         if !smne_files.is_empty() {
             println!("    Found {} SMNE files to process", smne_files.len());
+            run_metadata::record_inputs("SCED SMNE", smne_files.len());
             let pb2 = indicatif::ProgressBar::new(smne_files.len() as u64);
             pb2.set_style(indicatif::ProgressStyle::default_bar()
                 .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len}")
@@ -453,62 +952,63 @@ impl BessRevenueCalculator {
                 
             for file_path in smne_files {
                 pb2.inc(1);
-                self.process_smne_file(&file_path, &self.rt_prices, &mut rt_revenues)?;
+                self.process_smne_file(&file_path, &self.rt_prices, &mut rt_revenues, &mut unmatched)?;
             }
             pb2.finish();
         }
         
+        rt_revenues.retain(|(_, date): &(String, NaiveDate), _| self.date_range.contains(*date));
+        
+        unmatched.log_summary();
+        if let Some(path) = &self.dump_unmatched {
+            unmatched.write_csv(path)?;
+            println!("    Saved {} unmatched RT price intervals to: {}", unmatched.len(), path.display());
+        }
+        
         Ok(rt_revenues)
     }
     
-    fn load_rt_prices(&self, file_path: &Path) -> Result<HashMap<(String, NaiveDate, i64), f64>> {
-        let mut prices = HashMap::new();
+    fn load_rt_prices(&self, file_path: &Path, product: RtPriceProduct) -> Result<PriceIndex> {
+        let interval = product.interval();
+        let mut prices = PriceIndexBuilder::new(interval, self.duplicate_prices);
         
-        if let Ok(df) = CsvReader::new(std::fs::File::open(file_path)?)
-            .has_header(true)
-            .finish() {
+        if let Ok(df) = read_csv(file_path) {
             
+            // Processed LMP files carry the price as SettlementPointPrice; raw ones as LMP
             if let (Ok(datetimes), Ok(sps), Ok(prices_col)) = (
                 df.column("datetime"),
                 df.column("SettlementPoint"),
-                df.column("SettlementPointPrice")
+                df.column("SettlementPointPrice").or_else(|_| df.column("LMP"))
             ) {
                 let datetimes_i64 = datetimes.i64()?;
                 let sps_utf8 = sps.utf8()?;
                 let prices_f64 = prices_col.f64()?;
+                let datetime_format = DatetimeFormat::of_file_or_warn(file_path);
                 
-                println!("    Loading {} RT price records", df.height());
+                println!("    Loading {} {} records", df.height(), product.label());
                 
                 for i in 0..df.height() {
                     if let (Some(timestamp_ms), Some(sp), Some(price)) = 
                         (datetimes_i64.get(i), sps_utf8.get(i), prices_f64.get(i)) {
                         
-                        // Convert milliseconds to datetime
-                        let datetime = DateTime::from_timestamp_millis(timestamp_ms)
-                            .map(|dt| dt.naive_utc());
-                        if let Some(dt) = datetime {
-                            let date = dt.date();
-                            let interval = (dt.hour() * 60 + dt.minute()) / 15; // 15-min interval
-                            
-                            let key = (sp.to_string(), date, interval as i64);
-                            prices.insert(key, price);
+                        // Keyed by Central wall clock treated as UTC, matching the SCED timestamps
+                        if let Some(dt) = datetime_format.wall_clock(timestamp_ms) {
+                            prices.insert(sp, interval.epoch_index(&dt.and_utc()), price);
                         }
                     }
                 }
-                
-                println!("    Loaded {} unique RT price points", prices.len());
             }
         }
         
+        let prices = prices.finish(&file_path.display().to_string());
+        println!("    Loaded {} unique {} points", prices.len(), product.label());
         Ok(prices)
     }
     
-    fn load_dam_prices(&self, file_path: &Path) -> Result<HashMap<(String, NaiveDate, i32), f64>> {
-        let mut prices = HashMap::new();
+    fn load_dam_prices(&self, file_path: &Path) -> Result<PriceIndex> {
+        let mut prices = PriceIndexBuilder::new(IntervalLength::Hourly, self.duplicate_prices);
         
-        if let Ok(df) = CsvReader::new(std::fs::File::open(file_path)?)
-            .has_header(true)
-            .finish() {
+        if let Ok(df) = read_csv(file_path) {
             
             // Try different column names for DAM data
             let datetime_col = if df.get_column_names().contains(&"datetime") {
@@ -516,7 +1016,7 @@ impl BessRevenueCalculator {
             } else if df.get_column_names().contains(&"DeliveryDate") {
                 "DeliveryDate" 
             } else {
-                return Ok(prices);
+                return Ok(prices.finish(&file_path.display().to_string()));
             };
             
             let sp_col = if df.get_column_names().contains(&"SettlementPoint") {
@@ -524,7 +1024,7 @@ impl BessRevenueCalculator {
             } else if df.get_column_names().contains(&"BusName") {
                 "BusName"
             } else {
-                return Ok(prices);
+                return Ok(prices.finish(&file_path.display().to_string()));
             };
             
             let price_col = if df.get_column_names().contains(&"SettlementPointPrice") {
@@ -532,7 +1032,7 @@ impl BessRevenueCalculator {
             } else if df.get_column_names().contains(&"LMP") {
                 "LMP"
             } else {
-                return Ok(prices);
+                return Ok(prices.finish(&file_path.display().to_string()));
             };
             
             if datetime_col == "datetime" {
@@ -545,19 +1045,14 @@ impl BessRevenueCalculator {
                     let datetimes_i64 = datetimes.i64()?;
                     let sps_utf8 = sps.utf8()?;
                     let prices_f64 = prices_col.f64()?;
+                    let datetime_format = DatetimeFormat::of_file_or_warn(file_path);
                     
                     for i in 0..df.height() {
                         if let (Some(timestamp_ms), Some(sp), Some(price)) = 
                             (datetimes_i64.get(i), sps_utf8.get(i), prices_f64.get(i)) {
                             
-                            let datetime = DateTime::from_timestamp_millis(timestamp_ms)
-                            .map(|dt| dt.naive_utc());
-                            if let Some(dt) = datetime {
-                                let date = dt.date();
-                                let hour = dt.hour() as i32 + 1; // DAM uses hour ending (1-24)
-                                
-                                let key = (sp.to_string(), date, hour);
-                                prices.insert(key, price);
+                            if let Some(dt) = datetime_format.wall_clock(timestamp_ms) {
+                                prices.insert(sp, IntervalLength::Hourly.epoch_index(&dt.and_utc()), price);
                             }
                         }
                     }
@@ -580,27 +1075,36 @@ impl BessRevenueCalculator {
                         if let (Some(date_str), Some(hour), Some(sp), Some(price)) = 
                             (dates_utf8.get(i), hours_i32.get(i), sps_utf8.get(i), prices_f64.get(i)) {
                             
+                            // Hour ending (1-24) on the same wall-clock-as-UTC
+                            // convention as the `datetime` column
                             if let Ok(date) = NaiveDate::parse_from_str(date_str, "%m/%d/%Y") {
-                                let key = (sp.to_string(), date, hour);
-                                prices.insert(key, price);
+                                let hour_start = date.and_hms_opt(0, 0, 0).unwrap() + chrono::Duration::hours(hour as i64 - 1);
+                                prices.insert(sp, IntervalLength::Hourly.epoch_index(&hour_start.and_utc()), price);
                             }
                         }
                     }
                 }
             }
-            
-            println!("      Loaded {} DAM price points from {}", prices.len(), file_path.file_name().unwrap().to_str().unwrap());
         }
         
+        let prices = prices.finish(&file_path.display().to_string());
+        println!("      Loaded {} DAM price points from {}", prices.len(), file_path.file_name().unwrap().to_str().unwrap());
         Ok(prices)
     }
     
     fn load_ancillary_service_prices(&self, file_path: &Path) -> Result<HashMap<(NaiveDate, i32), HashMap<String, f64>>> {
         let mut prices = HashMap::new();
         
-        if let Ok(df) = CsvReader::new(std::fs::File::open(file_path)?)
-            .has_header(true)
-            .finish() {
+        if let Ok(df) = read_csv(file_path) {
+            // Every product column under its canonical service, whichever
+            // naming (RRS vs RRSPFR/RRSUFR/RRSFFR, ECRS variants) the file uses
+            let service_columns: Vec<(AsService, Float64Chunked)> = df.get_columns().iter()
+                .filter_map(|c| {
+                    let service = AsService::from_product(c.name())?;
+                    let prices = c.cast(&DataType::Float64).ok()?;
+                    Some((service, prices.f64().ok()?.clone()))
+                })
+                .collect();
             
             // Check if we have datetime column
             let has_datetime = df.get_column_names().contains(&"datetime");
@@ -608,30 +1112,15 @@ impl BessRevenueCalculator {
             if has_datetime {
                 if let Ok(datetimes) = df.column("datetime") {
                     let datetimes_i64 = datetimes.i64()?;
-                    
-                    // Get all AS service columns
-                    let service_columns = vec!["REGUP", "REGDN", "RRSPFR", "RRSUFR", "RRSFFR", "NSPIN", "ECRS", "ECRSM", "ECRSS"];
+                    let datetime_format = DatetimeFormat::of_file_or_warn(file_path);
                     
                     for i in 0..df.height() {
                         if let Some(timestamp_ms) = datetimes_i64.get(i) {
-                            let datetime = DateTime::from_timestamp_millis(timestamp_ms)
-                            .map(|dt| dt.naive_utc());
-                            if let Some(dt) = datetime {
+                            if let Some(dt) = datetime_format.wall_clock(timestamp_ms) {
                                 let date = dt.date();
                                 let hour = dt.hour() as i32 + 1; // Hour ending
                                 
-                                let mut service_prices = HashMap::new();
-                                
-                                // Extract price for each service
-                                for service in &service_columns {
-                                    if let Ok(price_col) = df.column(service) {
-                                        if let Ok(prices_f64) = price_col.f64() {
-                                            if let Some(price) = prices_f64.get(i) {
-                                                service_prices.insert(service.to_string(), price);
-                                            }
-                                        }
-                                    }
-                                }
+                                let service_prices = service_prices_at(&service_columns, i);
                                 
                                 if !service_prices.is_empty() {
                                     prices.insert((date, hour), service_prices);
@@ -650,24 +1139,10 @@ impl BessRevenueCalculator {
                     let hours_cast = hours.cast(&DataType::Int32)?;
                     let hours_i32 = hours_cast.i32()?;
                     
-                    // Get all AS service columns
-                    let service_columns = vec!["REGUP", "REGDN", "RRSPFR", "RRSUFR", "RRSFFR", "NSPIN", "ECRS", "ECRSM", "ECRSS"];
-                    
                     for i in 0..df.height() {
                         if let (Some(date_str), Some(hour)) = (dates_utf8.get(i), hours_i32.get(i)) {
                             if let Ok(date) = NaiveDate::parse_from_str(date_str, "%m/%d/%Y") {
-                                let mut service_prices = HashMap::new();
-                                
-                                // Extract price for each service
-                                for service in &service_columns {
-                                    if let Ok(price_col) = df.column(service) {
-                                        if let Ok(prices_f64) = price_col.f64() {
-                                            if let Some(price) = prices_f64.get(i) {
-                                                service_prices.insert(service.to_string(), price);
-                                            }
-                                        }
-                                    }
-                                }
+                                let service_prices = service_prices_at(&service_columns, i);
                                 
                                 if !service_prices.is_empty() {
                                     prices.insert((date, hour), service_prices);
@@ -684,8 +1159,10 @@ impl BessRevenueCalculator {
         Ok(prices)
     }
     
-    fn process_rt_output(&self, df: &DataFrame, rt_prices: &HashMap<(String, NaiveDate, i64), f64>,
-                        rt_revenues: &mut HashMap<(String, NaiveDate), f64>) -> Result<()> {
+    fn process_rt_output(&self, df: &DataFrame, rt_prices: &PriceStore,
+                        dispatch_interval: IntervalLength,
+                        rt_revenues: &mut HashMap<(String, NaiveDate), EnergySettlement>,
+                        unmatched: &mut UnmatchedPrices) -> Result<()> {
         // Debug: print columns once
         static mut PRINTED_SCED: bool = false;
         unsafe {
@@ -723,97 +1200,117 @@ impl BessRevenueCalculator {
                 return Ok(());
             };
             
+            let mut unparsed = 0;
             for i in 0..df.height() {
                 if let (Some(timestamp_str), Some(resource), Some(output_mw)) = 
                     (timestamps_utf8.get(i), resources_utf8.get(i), outputs_f64.get(i)) {
                     
-                    // Parse timestamp
-                    if let Ok(timestamp) = chrono::NaiveDateTime::parse_from_str(timestamp_str, "%m/%d/%Y %H:%M:%S") {
-                        let date = timestamp.date();
-                        let interval = (timestamp.hour() * 60 + timestamp.minute()) / 15; // 15-min interval
-                        
-                        // Both charging (negative) and discharging (positive)
-                        if output_mw != 0.0 {
-                            // Get settlement point for this resource
-                            if let Some((master_sp, _)) = self.bess_resources.get(resource) {
-                                // Use mapped settlement point if available, otherwise use master list SP
-                                let sp = self.settlement_point_map.get(resource)
-                                    .unwrap_or(master_sp);
-                                
-                                // Look up RT price
-                                let price_key = (sp.clone(), date, interval as i64);
-                                let price = if let Some(p) = rt_prices.get(&price_key) {
-                                    *p
+                    // Parse timestamp (24-hour, or 12-hour AM/PM in some files)
+                    let Some(timestamp) = parse_sced_timestamp(timestamp_str) else {
+                        unparsed += 1;
+                        continue;
+                    };
+                    let date = timestamp.date();
+                    let interval = RT_PRICE_INTERVAL.index_of(timestamp);
+                    // Wall clock treated as UTC, matching how RT prices are keyed in `load_rt_prices`
+                    let epoch_index = RT_PRICE_INTERVAL.epoch_index(&timestamp.and_utc());
+                    
+                    // Both charging (negative) and discharging (positive)
+                    if output_mw != 0.0 {
+                        // Get settlement point for this resource
+                        if let Some((master_sp, _)) = self.bess_resources.get(resource) {
+                            // Use the settlement point mapped for this date if available, otherwise use master list SP
+                            let sp = self.settlement_point_map.settlement_point_on(resource, date)
+                                .unwrap_or(master_sp);
+                            
+                            // Look up RT price
+                            let price = if let Some(p) = self.rt_price_at(resource, sp, rt_prices, epoch_index) {
+                                p
+                            } else {
+                                let (resolution, fallback) = self.fallback_rt_price(sp, rt_prices, epoch_index);
+                                unmatched.record(resource, sp, date, interval, "SCED", resolution);
+                                if let Some(p) = fallback {
+                                    debug!("Using {} fallback price for {} @ {} interval {}", resolution.label(), sp, date, interval);
+                                    p
                                 } else {
-                                    // Try Houston Hub as fallback
-                                    let houston_key = ("HB_HOUSTON".to_string(), date, interval as i64);
-                                    if let Some(p) = rt_prices.get(&houston_key) {
-                                        static mut DEBUG_HOUSTON: u32 = 0;
-                                        unsafe {
-                                            if DEBUG_HOUSTON < 3 {
-                                                println!("      Using Houston Hub price for {} @ {} interval {}", sp, date, interval);
-                                                DEBUG_HOUSTON += 1;
-                                            }
-                                        }
-                                        *p
-                                    } else {
-                                        // No price available - skip this interval
-                                        static mut DEBUG_NO_PRICE: u32 = 0;
-                                        unsafe {
-                                            if DEBUG_NO_PRICE < 3 {
-                                                println!("      No RT price found for {} @ {} interval {} - skipping", sp, date, interval);
-                                                DEBUG_NO_PRICE += 1;
-                                            }
-                                        }
-                                        continue; // Skip this interval entirely
-                                    }
-                                };
-                                
-                                let revenue = output_mw * price / 4.0; // MW * $/MWh / 4 = $ for 15-min interval
-                                
-                                // Debug first few RT revenues
-                                static mut DEBUG_COUNT: u32 = 0;
-                                unsafe {
-                                    if DEBUG_COUNT < 5 {
-                                        println!("      RT revenue: {} @ {} - {} MW × ${}/MWh = ${:.2}", 
-                                                 resource, timestamp_str, output_mw, price, revenue);
-                                        DEBUG_COUNT += 1;
-                                    }
+                                    // No price available - skip this interval
+                                    debug!("No RT price found for {} @ {} interval {} - skipping", sp, date, interval);
+                                    continue; // Skip this interval entirely
                                 }
-                                
-                                let key = (resource.to_string(), date);
-                                *rt_revenues.entry(key).or_insert(0.0) += revenue;
-                            } else {
-                                // Debug: resource not found in BESS list
-                                static mut DEBUG_NOT_FOUND: u32 = 0;
-                                unsafe {
-                                    if DEBUG_NOT_FOUND < 3 {
-                                        println!("      BESS resource not found: {}", resource);
-                                        DEBUG_NOT_FOUND += 1;
-                                    }
+                            };
+                            
+                            let revenue = interval_revenue(output_mw, price, dispatch_interval);
+                            
+                            // Debug first few RT revenues
+                            static mut DEBUG_COUNT: u32 = 0;
+                            unsafe {
+                                if DEBUG_COUNT < 5 {
+                                    println!("      RT revenue: {} @ {} - {} MW × ${}/MWh = ${:.2}", 
+                                             resource, timestamp_str, output_mw, price, revenue);
+                                    DEBUG_COUNT += 1;
                                 }
                             }
-                        }  // <-- This closes the if output_mw != 0.0 block
-                    }
+                            
+                            let key = (resource.to_string(), date);
+                            rt_revenues.entry(key).or_default().record(output_mw, revenue, dispatch_interval);
+                        } else {
+                            // Debug: resource not found in BESS list
+                            static mut DEBUG_NOT_FOUND: u32 = 0;
+                            unsafe {
+                                if DEBUG_NOT_FOUND < 3 {
+                                    warn!("BESS resource not found: {}", resource);
+                                    DEBUG_NOT_FOUND += 1;
+                                }
+                            }
+                        }
+                    }  // <-- This closes the if output_mw != 0.0 block
                 }
             }
+            if unparsed > 0 {
+                warn!("Skipped {} SCED rows whose SCED Time Stamp could not be parsed", unparsed);
+            }
         }
         
         Ok(())
     }
     
-    fn process_smne_file(&self, file_path: &Path, rt_prices: &HashMap<(String, NaiveDate, i64), f64>,
-                         rt_revenues: &mut HashMap<(String, NaiveDate), f64>) -> Result<()> {
-        if let Ok(df) = CsvReader::new(std::fs::File::open(file_path)?)
-            .has_header(true)
-            .finish() {
-            
-            // SMNE file format: "Interval Time","Interval Number","Resource Code","Interval Value"
-            if let (Ok(timestamps), Ok(resources), Ok(values)) = (
-                df.column("Interval Time"),
-                df.column("Resource Code"),
-                df.column("Interval Value")
-            ) {
+    /// RT price at `sp` on `resource`'s price basis (SPP, or nodal LMP when
+    /// configured and loaded), following renamed points
+    fn rt_price_at(&self, resource: &str, sp: &str, rt_prices: &PriceStore, epoch_index: i64) -> Option<f64> {
+        match self.rt_price_basis.basis(resource) {
+            RtPriceBasis::Lmp if !self.rt_lmps.is_empty() => {
+                self.settlement_point_aliases.lookup(sp, |sp| self.rt_lmps.get(sp, epoch_index))
+            }
+            _ => self.settlement_point_aliases.lookup(sp, |sp| rt_prices.get(sp, epoch_index)),
+        }
+    }
+    
+    /// Price for a settlement point with none of its own: its load zone's
+    /// per ERCOT's settlement point list, else HB_HOUSTON for points the list
+    /// doesn't cover (or whose zone has no price either)
+    fn fallback_rt_price(&self, sp: &str, rt_prices: &PriceStore, epoch_index: i64) -> (UnmatchedResolution, Option<f64>) {
+        let zone_price = self.settlement_point_list.load_zone(sp)
+            .and_then(|zone| rt_prices.get(zone, epoch_index));
+        if let Some(p) = zone_price {
+            return (UnmatchedResolution::LoadZoneFallback, Some(p));
+        }
+        match rt_prices.get("HB_HOUSTON", epoch_index) {
+            Some(p) => (UnmatchedResolution::HubFallback, Some(p)),
+            None => (UnmatchedResolution::Skipped, None),
+        }
+    }
+    
+    fn process_smne_file(&self, file_path: &Path, rt_prices: &PriceStore,
+                         rt_revenues: &mut HashMap<(String, NaiveDate), EnergySettlement>,
+                         unmatched: &mut UnmatchedPrices) -> Result<()> {
+        if let Ok(df) = read_csv(file_path) {
+            
+            // SMNE file format: "Interval Time","Interval Number","Resource Code","Interval Value"
+            if let (Ok(timestamps), Ok(resources), Ok(values)) = (
+                df.column("Interval Time"),
+                df.column("Resource Code"),
+                df.column("Interval Value")
+            ) {
                 let timestamps_utf8 = timestamps.utf8()?;
                 let resources_utf8 = resources.utf8()?;
                 
@@ -840,33 +1337,33 @@ impl BessRevenueCalculator {
                         }
                         
                         // Parse timestamp
-                        if let Ok(timestamp) = chrono::NaiveDateTime::parse_from_str(timestamp_str, "%m/%d/%Y %H:%M:%S") {
+                        if let Some(timestamp) = parse_sced_timestamp(timestamp_str) {
                             let date = timestamp.date();
-                            let interval = (timestamp.hour() * 60 + timestamp.minute()) / 15; // 15-min interval
+                            let interval = RT_PRICE_INTERVAL.index_of(timestamp);
+                            // Wall clock treated as UTC, matching how RT prices are keyed in `load_rt_prices`
+                            let epoch_index = RT_PRICE_INTERVAL.epoch_index(&timestamp.and_utc());
                             
                             // Both charging (negative) and discharging (positive)
                             if output_mw != 0.0 {
                                 // Get settlement point for this resource
                                 if let Some((master_sp, _)) = self.bess_resources.get(resource) {
-                                    // Use mapped settlement point if available, otherwise use master list SP
-                                    let sp = self.settlement_point_map.get(resource)
+                                    // Use the settlement point mapped for this date if available, otherwise use master list SP
+                                    let sp = self.settlement_point_map.settlement_point_on(resource, date)
                                         .unwrap_or(master_sp);
                                     
                                     // Look up RT price
-                                    let price_key = (sp.clone(), date, interval as i64);
-                                    let price = if let Some(p) = rt_prices.get(&price_key) {
-                                        *p
+                                    let price = if let Some(p) = self.rt_price_at(resource, sp, rt_prices, epoch_index) {
+                                        p
                                     } else {
-                                        // Try Houston Hub as fallback
-                                        let houston_key = ("HB_HOUSTON".to_string(), date, interval as i64);
-                                        if let Some(p) = rt_prices.get(&houston_key) {
-                                            *p
-                                        } else {
-                                            continue; // Skip this interval entirely
+                                        let (resolution, fallback) = self.fallback_rt_price(sp, rt_prices, epoch_index);
+                                        unmatched.record(resource, sp, date, interval, "SMNE", resolution);
+                                        match fallback {
+                                            Some(p) => p,
+                                            None => continue, // Skip this interval entirely
                                         }
                                     };
                                     
-                                    let revenue = output_mw * price / 4.0; // MW * $/MWh / 4 = $ for 15-min interval
+                                    let revenue = interval_revenue(output_mw, price, SMNE_INTERVAL);
                                     
                                     // Debug first few SMNE revenues
                                     static mut DEBUG_SMNE: u32 = 0;
@@ -879,7 +1376,7 @@ impl BessRevenueCalculator {
                                     }
                                     
                                     let key = (resource.to_string(), date);
-                                    *rt_revenues.entry(key).or_insert(0.0) += revenue;
+                                    rt_revenues.entry(key).or_default().record(output_mw, revenue, SMNE_INTERVAL);
                                 }
                             }
                         }
@@ -895,11 +1392,13 @@ impl BessRevenueCalculator {
         println!("\n⚡ Calculating Ancillary Service Revenues...");
         
         let mut as_revenues = HashMap::new();
+        let mut settled_hours = SettledAwardHours::default();
         
         // Load Gen Resource Data with AS awards
         let gen_pattern = self.data_dir.join("DAM_extracted/60d_DAM_Gen_Resource_Data*.csv");
         let gen_files: Vec<PathBuf> = glob::glob(gen_pattern.to_str().unwrap())?
             .filter_map(Result::ok)
+            .filter(|path| self.date_range.includes_file(path))
             .collect();
         
         println!("Processing {} Gen Resource Data files", gen_files.len());
@@ -912,29 +1411,34 @@ impl BessRevenueCalculator {
         for file_path in gen_files {
             pb.inc(1);
             
-            if let Ok(df) = CsvReader::new(std::fs::File::open(&file_path)?)
-                .has_header(true)
-                .finish() {
+            if let Ok(df) = read_csv(&file_path) {
                 
                 // Filter for BESS resources
                 if let Ok(resource_types) = df.column("Resource Type") {
                     let mask = resource_types.utf8()?.equal("PWRSTR");
                     
                     if let Ok(filtered) = df.filter(&mask) {
-                        self.process_as_awards(&filtered, &mut as_revenues)?;
+                        self.timer.add_rows("AS", filtered.height());
+                        self.process_as_awards(&filtered, &mut as_revenues, &mut settled_hours)?;
                     }
                 }
             }
         }
         
         pb.finish();
+        if settled_hours.duplicates() > 0 {
+            warn!("Skipped {} duplicate hourly AS award rows (same resource, operating day and hour ending); each hour is settled once",
+                  settled_hours.duplicates());
+        }
+        as_revenues.retain(|(_, date): &(String, NaiveDate), _| self.date_range.contains(*date));
         println!("Calculated AS revenues for {} resource-days", as_revenues.len());
         
         Ok(as_revenues)
     }
 
     fn process_as_awards(&self, df: &DataFrame, 
-                        as_revenues: &mut HashMap<(String, NaiveDate), HashMap<String, f64>>) -> Result<()> {
+                        as_revenues: &mut HashMap<(String, NaiveDate), HashMap<String, f64>>,
+                        settled_hours: &mut SettledAwardHours) -> Result<()> {
         // Debug: Print column names once
         static mut PRINTED: bool = false;
         unsafe {
@@ -946,194 +1450,38 @@ impl BessRevenueCalculator {
         
         // Extract relevant columns
         let dates = df.column("Delivery Date")?.utf8()?;
-        let _hours = df.column("Hour Ending")?.i64()?;
+        let hours = df.column("Hour Ending")?.i64()?;
+        let repeated_hours = df.column("Repeated Hour Flag").ok().and_then(|c| c.utf8().ok());
         let resources = df.column("Resource Name")?.utf8()?;
         
         // Try to get energy price column (may not exist in older formats)
         let _prices = df.column("Energy Settlement Point Price").ok().and_then(|c| c.f64().ok());
         
-        // AS awards and prices - handle both old and new formats
-        // Try to convert string columns to float, handling empty strings
-        let reg_up_awards = df.column("RegUp Awarded").ok()
-            .and_then(|c| {
-                if let Ok(utf8) = c.utf8() {
-                    // Convert empty strings to 0.0
-                    let values: Vec<Option<f64>> = utf8.into_iter()
-                        .map(|v| v.and_then(|s| if s.is_empty() { Some(0.0) } else { s.parse().ok() }))
-                        .collect();
-                    Some(Float64Chunked::from_iter(values))
-                } else {
-                    c.f64().ok().cloned()
-                }
-            });
-            
-        let reg_up_prices = df.column("RegUp MCPC").ok()
-            .and_then(|c| {
-                if let Ok(utf8) = c.utf8() {
-                    let values: Vec<Option<f64>> = utf8.into_iter()
-                        .map(|v| v.and_then(|s| if s.is_empty() { Some(0.0) } else { s.parse().ok() }))
-                        .collect();
-                    Some(Float64Chunked::from_iter(values))
-                } else {
-                    c.f64().ok().cloned()
-                }
-            });
-            
-        let reg_down_awards = df.column("RegDown Awarded").ok()
-            .and_then(|c| {
-                if let Ok(utf8) = c.utf8() {
-                    let values: Vec<Option<f64>> = utf8.into_iter()
-                        .map(|v| v.and_then(|s| if s.is_empty() { Some(0.0) } else { s.parse().ok() }))
-                        .collect();
-                    Some(Float64Chunked::from_iter(values))
-                } else {
-                    c.f64().ok().cloned()
-                }
-            });
-            
-        let reg_down_prices = df.column("RegDown MCPC").ok()
-            .and_then(|c| {
-                if let Ok(utf8) = c.utf8() {
-                    let values: Vec<Option<f64>> = utf8.into_iter()
-                        .map(|v| v.and_then(|s| if s.is_empty() { Some(0.0) } else { s.parse().ok() }))
-                        .collect();
-                    Some(Float64Chunked::from_iter(values))
-                } else {
-                    c.f64().ok().cloned()
-                }
-            });
-            
-        // For RRS, try both "RRS Awarded" and combined RRS types
-        let rrs_awards = df.column("RRS Awarded").ok()
-            .and_then(|c| {
-                if let Ok(utf8) = c.utf8() {
-                    let values: Vec<Option<f64>> = utf8.into_iter()
-                        .map(|v| v.and_then(|s| if s.is_empty() { Some(0.0) } else { s.parse().ok() }))
-                        .collect();
-                    Some(Float64Chunked::from_iter(values))
-                } else {
-                    c.f64().ok().cloned()
-                }
-            });
-            
-        let rrs_prices = df.column("RRS MCPC").ok()
-            .and_then(|c| {
-                if let Ok(utf8) = c.utf8() {
-                    let values: Vec<Option<f64>> = utf8.into_iter()
-                        .map(|v| v.and_then(|s| if s.is_empty() { Some(0.0) } else { s.parse().ok() }))
-                        .collect();
-                    Some(Float64Chunked::from_iter(values))
-                } else {
-                    c.f64().ok().cloned()
-                }
-            });
-            
-        let non_spin_awards = df.column("NonSpin Awarded").ok()
-            .and_then(|c| {
-                if let Ok(utf8) = c.utf8() {
-                    let values: Vec<Option<f64>> = utf8.into_iter()
-                        .map(|v| v.and_then(|s| if s.is_empty() { Some(0.0) } else { s.parse().ok() }))
-                        .collect();
-                    Some(Float64Chunked::from_iter(values))
-                } else {
-                    c.f64().ok().cloned()
-                }
-            });
-            
-        let non_spin_prices = df.column("NonSpin MCPC").ok()
-            .and_then(|c| {
-                if let Ok(utf8) = c.utf8() {
-                    let values: Vec<Option<f64>> = utf8.into_iter()
-                        .map(|v| v.and_then(|s| if s.is_empty() { Some(0.0) } else { s.parse().ok() }))
-                        .collect();
-                    Some(Float64Chunked::from_iter(values))
-                } else {
-                    c.f64().ok().cloned()
-                }
-            });
-        
-        // Try ECRS columns (newer format)
-        let ecrs_awards = df.column("ECRSSD Awarded").ok()
-            .and_then(|c| {
-                if let Ok(utf8) = c.utf8() {
-                    let values: Vec<Option<f64>> = utf8.into_iter()
-                        .map(|v| v.and_then(|s| if s.is_empty() { Some(0.0) } else { s.parse().ok() }))
-                        .collect();
-                    Some(Float64Chunked::from_iter(values))
-                } else {
-                    c.f64().ok().cloned()
-                }
-            });
-            
-        let ecrs_prices = df.column("ECRS MCPC").ok()
-            .and_then(|c| {
-                if let Ok(utf8) = c.utf8() {
-                    let values: Vec<Option<f64>> = utf8.into_iter()
-                        .map(|v| v.and_then(|s| if s.is_empty() { Some(0.0) } else { s.parse().ok() }))
-                        .collect();
-                    Some(Float64Chunked::from_iter(values))
-                } else {
-                    c.f64().ok().cloned()
-                }
-            });
-        
-        // Debug: Print if we found AS columns
-        if reg_up_awards.is_some() && reg_up_prices.is_some() {
-            println!("  Found RegUp columns in Gen Resource Data");
-        }
+        // Award and MCPC columns vary by year (combined vs split RRS,
+        // ECRS variants); fold them into canonical services
+        let awards = service_awards(df)?;
+        let mcpcs = service_mcpcs(df)?;
         
         for i in 0..df.height() {
             if let (Some(date_str), Some(resource)) = (dates.get(i), resources.get(i)) {
                 if self.bess_resources.contains_key(resource) {
                     if let Ok(date) = NaiveDate::parse_from_str(date_str, "%m/%d/%Y") {
-                        let key = (resource.to_string(), date);
-                        let revenues = as_revenues.entry(key).or_insert_with(HashMap::new);
-                        
-                        // Calculate revenues for each AS type
-                        if let (Some(awards), Some(prices)) = (reg_up_awards.as_ref(), reg_up_prices.as_ref()) {
-                            if let (Some(award), Some(price)) = (awards.get(i), prices.get(i)) {
-                                if award > 0.0 && price > 0.0 {
-                                    *revenues.entry("RegUp".to_string()).or_insert(0.0) += award * price;
-                                    // Debug first AS revenue calculation
-                                    static mut PRINTED_AS: bool = false;
-                                    unsafe {
-                                        if !PRINTED_AS && resource == "BLSUMMIT_BATTERY" {
-                                            println!("  BLSUMMIT_BATTERY RegUp: {} MW @ ${}/MW = ${}", award, price, award * price);
-                                            PRINTED_AS = true;
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                        
-                        if let (Some(awards), Some(prices)) = (reg_down_awards.as_ref(), reg_down_prices.as_ref()) {
-                            if let (Some(award), Some(price)) = (awards.get(i), prices.get(i)) {
-                                if award > 0.0 && price > 0.0 {
-                                    *revenues.entry("RegDown".to_string()).or_insert(0.0) += award * price;
-                                }
-                            }
-                        }
-                        
-                        if let (Some(awards), Some(prices)) = (rrs_awards.as_ref(), rrs_prices.as_ref()) {
-                            if let (Some(award), Some(price)) = (awards.get(i), prices.get(i)) {
-                                if award > 0.0 && price > 0.0 {
-                                    *revenues.entry("RRS".to_string()).or_insert(0.0) += award * price;
-                                }
+                        // One row per resource-hour; a repeat would double-count the hour
+                        if let Some(hour_ending) = hours.get(i) {
+                            let repeated_hour = is_repeated_hour(repeated_hours.and_then(|flags| flags.get(i)));
+                            if !settled_hours.insert(resource, date, hour_ending, repeated_hour) {
+                                continue;
                             }
                         }
                         
-                        if let (Some(awards), Some(prices)) = (ecrs_awards.as_ref(), ecrs_prices.as_ref()) {
-                            if let (Some(award), Some(price)) = (awards.get(i), prices.get(i)) {
-                                if award > 0.0 && price > 0.0 {
-                                    *revenues.entry("ECRS".to_string()).or_insert(0.0) += award * price;
-                                }
-                            }
-                        }
+                        let key = (resource.to_string(), date);
+                        let revenues = as_revenues.entry(key).or_insert_with(HashMap::new);
                         
-                        if let (Some(awards), Some(prices)) = (non_spin_awards.as_ref(), non_spin_prices.as_ref()) {
-                            if let (Some(award), Some(price)) = (awards.get(i), prices.get(i)) {
+                        for service in AsService::ALL {
+                            if let (Some(awards), Some(prices)) = (awards.get(&service), mcpcs.get(&service)) {
+                                let (award, price) = (awards[i], prices[i]);
                                 if award > 0.0 && price > 0.0 {
-                                    *revenues.entry("NonSpin".to_string()).or_insert(0.0) += award * price;
+                                    *revenues.entry(service.key().to_string()).or_insert(0.0) += award * price;
                                 }
                             }
                         }
@@ -1146,180 +1494,341 @@ impl BessRevenueCalculator {
     }
 
     fn create_daily_rollups_split(&self, 
-                           dam_revenues: HashMap<(String, NaiveDate), f64>,
-                           rt_revenues: HashMap<(String, NaiveDate), f64>,
+                           dam_revenues: HashMap<(String, NaiveDate), EnergySettlement>,
+                           rt_revenues: HashMap<(String, NaiveDate), EnergySettlement>,
                            as_revenues: HashMap<(String, NaiveDate), HashMap<String, f64>>) 
                            -> Result<Vec<BessRevenue>> {
         println!("\n📅 Creating Daily Revenue Rollups...");
         
-        let mut daily_revenues = Vec::new();
+        let rollup = daily_rollup_frame(&dam_revenues, &rt_revenues, &as_revenues, self.revenue_view)?;
+        let daily_revenues = daily_revenues_from_frame(&rollup)?;
         
-        // Get all unique resource-date combinations
-        let mut all_keys = HashSet::new();
-        for key in dam_revenues.keys() {
-            all_keys.insert(key.clone());
-        }
-        for key in rt_revenues.keys() {
-            all_keys.insert(key.clone());
+        println!("Created {} daily revenue records", daily_revenues.len());
+        
+        // Save daily rollups
+        self.save_daily_rollups(&rollup)?;
+        
+        Ok(daily_revenues)
+    }
+
+    fn detect_operational_issues(&self) -> Result<()> {
+        println!("\n🔍 Detecting Operational Issues...");
+        
+        let dispatch = self.load_bess_dispatch()?;
+        let soc_violations = self.detect_soc_violations(&dispatch);
+        let as_shortfalls = self.detect_as_shortfalls(&dispatch)?;
+        
+        let mut violations_by_resource: HashMap<&str, Vec<&SocViolation>> = HashMap::new();
+        for violation in &soc_violations {
+            violations_by_resource.entry(violation.resource_name.as_str())
+                .or_insert_with(Vec::new)
+                .push(violation);
         }
-        for key in as_revenues.keys() {
-            all_keys.insert(key.clone());
+        let mut shortfalls_by_resource: HashMap<&str, Vec<&AsShortfall>> = HashMap::new();
+        for shortfall in &as_shortfalls {
+            shortfalls_by_resource.entry(shortfall.resource_name.as_str())
+                .or_insert_with(Vec::new)
+                .push(shortfall);
         }
         
-        for (resource_name, date) in all_keys {
-            let dam_rev = dam_revenues.get(&(resource_name.clone(), date)).unwrap_or(&0.0);
-            let rt_rev = rt_revenues.get(&(resource_name.clone(), date)).unwrap_or(&0.0);
-            let energy_rev = dam_rev + rt_rev;
-            let as_rev = as_revenues.get(&(resource_name.clone(), date));
-            
-            let mut revenue = BessRevenue {
-                resource_name: resource_name.clone(),
-                date,
-                energy_revenue: energy_rev,
-                dam_energy_revenue: *dam_rev,
-                rt_energy_revenue: *rt_rev,
-                reg_up_revenue: 0.0,
-                reg_down_revenue: 0.0,
-                rrs_revenue: 0.0,
-                ecrs_revenue: 0.0,
-                non_spin_revenue: 0.0,
-                total_revenue: energy_rev,
-                energy_cycles: 0.0, // To be calculated
-                soc_violations: 0,
-                as_failures: 0,
-            };
-            
-            if let Some(as_revs) = as_rev {
-                revenue.reg_up_revenue = *as_revs.get("RegUp").unwrap_or(&0.0);
-                revenue.reg_down_revenue = *as_revs.get("RegDown").unwrap_or(&0.0);
-                revenue.rrs_revenue = *as_revs.get("RRS").unwrap_or(&0.0);
-                revenue.ecrs_revenue = *as_revs.get("ECRS").unwrap_or(&0.0);
-                revenue.non_spin_revenue = *as_revs.get("NonSpin").unwrap_or(&0.0);
-                
-                revenue.total_revenue += revenue.reg_up_revenue + revenue.reg_down_revenue + 
-                                       revenue.rrs_revenue + revenue.ecrs_revenue + revenue.non_spin_revenue;
+        let mut flagged: Vec<&str> = violations_by_resource.keys()
+            .chain(shortfalls_by_resource.keys())
+            .copied()
+            .collect();
+        flagged.sort();
+        flagged.dedup();
+        
+        for resource_name in flagged {
+            let violations = violations_by_resource.get(resource_name).map(Vec::as_slice).unwrap_or(&[]);
+            let shortfalls = shortfalls_by_resource.get(resource_name).map(Vec::as_slice).unwrap_or(&[]);
+            println!("  {} - SOC violations: {}, AS shortfall intervals: {}",
+                    resource_name, violations.len(), shortfalls.len());
+            if let Some(worst) = violations.iter().max_by(|a, b| a.magnitude_mwh.partial_cmp(&b.magnitude_mwh).unwrap()) {
+                println!("    worst SOC: {:?} {:.2} MWh at {}", worst.kind, worst.magnitude_mwh, worst.timestamp);
+            }
+            if let Some(worst) = shortfalls.iter().max_by(|a, b| a.shortfall_mw.partial_cmp(&b.shortfall_mw).unwrap()) {
+                println!("    worst AS: {:?} {:.1} MW committed, {:.1} MW headroom at {}",
+                        worst.direction, worst.committed_mw, worst.headroom_mw, worst.timestamp);
             }
-            
-            daily_revenues.push(revenue);
         }
         
-        // Sort by resource and date
-        daily_revenues.sort_by(|a, b| {
-            a.resource_name.cmp(&b.resource_name)
-                .then(a.date.cmp(&b.date))
-        });
+        println!("\nTotal operational issues detected:");
+        println!("  SOC violations: {}", soc_violations.len());
+        println!("  AS shortfall intervals: {}", as_shortfalls.len());
+        
+        let output_path = self.output_dir.join("bess_soc_violations.csv");
+        save_soc_violations(&soc_violations, &output_path)?;
+        println!("  Saved SOC violations to: {}", output_path.display());
+        
+        let output_path = self.output_dir.join("bess_as_shortfalls.csv");
+        save_as_shortfalls(&as_shortfalls, &output_path)?;
+        println!("  Saved AS shortfalls to: {}", output_path.display());
+        
+        let anomalies = self.check_energy_balance(&dispatch);
+        println!("\n⚖️  Energy balance: {} resources outside ±{:.0}% of discharge / {:.0}% RTE",
+                anomalies.len(), DEFAULT_ENERGY_BALANCE_TOLERANCE_PCT, DEFAULT_ROUND_TRIP_EFFICIENCY * 100.0);
+        for balance in &anomalies {
+            println!("  {} - charged {:.1} MWh, discharged {:.1} MWh, expected charge {:.1} MWh ({:+.1}%)",
+                    balance.resource_name, balance.charged_mwh, balance.discharged_mwh,
+                    balance.expected_charge_mwh, balance.pct_deviation);
+        }
+        let output_path = self.output_dir.join("energy_balance_anomalies.csv");
+        save_energy_balance(&anomalies, &output_path)?;
+        println!("  Saved energy balance anomalies to: {}", output_path.display());
         
-        println!("Created {} daily revenue records", daily_revenues.len());
+        let availability = self.monthly_availability(&dispatch);
+        let low: Vec<&MonthlyAvailability> = availability.iter()
+            .filter(|month| month.availability < LOW_AVAILABILITY)
+            .collect();
+        println!("\n🔌 Availability: {} of {} resource-months online in under {:.0}% of intervals",
+                low.len(), availability.len(), LOW_AVAILABILITY * 100.0);
+        for month in &low {
+            println!("  {} {} - {:.1}% ({} of {} intervals)",
+                    month.resource_name, month.month, month.availability * 100.0,
+                    month.online_intervals, month.operating_intervals);
+        }
+        let output_path = self.output_dir.join("bess_availability.csv");
+        save_availability(&availability, &output_path)?;
+        println!("  Saved availability to: {}", output_path.display());
+        
+        let reconciliation = self.reconcile_metered_energy(&dispatch)?;
+        let diverging: Vec<&MeterReconciliation> = reconciliation.iter().filter(|day| day.flagged).collect();
+        println!("\n📏 Telemetry vs meter: {} of {} resource-days off by more than {:.0}%",
+                diverging.len(), reconciliation.len(), DEFAULT_METER_TOLERANCE_PCT);
+        for day in &diverging {
+            println!("  {} {} - telemetered {:.1}/{:.1} MWh discharge/charge, metered {:.1}/{:.1} MWh ({:+.1}%)",
+                    day.resource_name, day.date, day.telemetered.discharged_mwh, day.telemetered.charged_mwh,
+                    day.metered.discharged_mwh, day.metered.charged_mwh, day.divergence_pct);
+        }
+        let output_path = self.output_dir.join("meter_reconciliation.csv");
+        save_meter_reconciliation(&reconciliation, &output_path)?;
+        println!("  Saved telemetry vs meter reconciliation to: {}", output_path.display());
+        
+        let capture_rates = self.scarcity_capture_rates(&dispatch);
+        println!("\n🔥 Scarcity capture (RT {} above ${:.0}/MWh): {} resources", SCARCITY_REFERENCE_POINT, self.scarcity_price, capture_rates.len());
+        let mut ranked: Vec<&CaptureRate> = capture_rates.iter().filter(|rate| rate.scarcity_intervals > 0).collect();
+        ranked.sort_by(|a, b| b.capture_rate.partial_cmp(&a.capture_rate).unwrap());
+        for rate in ranked.iter().take(10) {
+            println!("  {} - {:.1}% ({:.1} of {:.1} MWh over {} intervals)",
+                    rate.resource_name, rate.capture_rate * 100.0, rate.discharged_mwh, rate.scarcity_mwh, rate.scarcity_intervals);
+        }
+        let output_path = self.output_dir.join("bess_scarcity_capture.csv");
+        save_capture_rates(&capture_rates, &output_path)?;
+        println!("  Saved scarcity capture rates to: {}", output_path.display());
         
-        // Save daily rollups
-        self.save_daily_rollups(&daily_revenues)?;
+        Ok(())
+    }
+
+    /// Share of the scarcity each BESS was online for that it discharged
+    /// into, sorted by resource
+    fn scarcity_capture_rates(&self, dispatch: &HashMap<String, Vec<DispatchPoint>>) -> Vec<CaptureRate> {
+        let mut resources: Vec<&String> = dispatch.keys()
+            .filter(|resource_name| self.bess_resources.contains_key(*resource_name))
+            .collect();
+        resources.sort();
         
-        Ok(daily_revenues)
+        let epochs = resources.iter()
+            .flat_map(|resource_name| dispatch[*resource_name].iter())
+            .map(|point| rt_epoch_index(point.timestamp));
+        let (Some(first), Some(last)) = (epochs.clone().min(), epochs.max()) else {
+            return Vec::new();
+        };
+        let scarcity = scarcity_intervals(first, last, self.scarcity_price,
+                                          |epoch_index| self.rt_prices.get(SCARCITY_REFERENCE_POINT, epoch_index));
+        println!("    {} scarcity intervals ({:.1} hours)", scarcity.len(), scarcity.len() as f64 * RT_PRICE_INTERVAL.hours());
+        
+        resources.into_iter()
+            .map(|resource_name| {
+                let capacity_mw = self.bess_resources[resource_name].1;
+                capture_rate(resource_name, capacity_mw, &dispatch[resource_name], &scarcity)
+            })
+            .collect()
     }
 
-    fn detect_operational_issues(&self, daily_revenues: &[BessRevenue]) -> Result<()> {
-        println!("\n🔍 Detecting Operational Issues...");
+    /// SCED telemetry against SMNE meter data for each BESS-day both cover,
+    /// sorted by resource and date
+    fn reconcile_metered_energy(&self, dispatch: &HashMap<String, Vec<DispatchPoint>>) -> Result<Vec<MeterReconciliation>> {
+        let smne_pattern = self.data_dir.join("SCED_extracted/60d_SCED_SMNE_GEN_RES*.csv");
+        let smne_files: Vec<PathBuf> = glob::glob(smne_pattern.to_str().unwrap())?
+            .filter_map(Result::ok)
+            .filter(|path| self.date_range.includes_file(path))
+            .collect();
         
-        // Group by resource
-        let mut resources: HashMap<String, Vec<&BessRevenue>> = HashMap::new();
-        for revenue in daily_revenues {
-            resources.entry(revenue.resource_name.clone())
-                .or_insert_with(Vec::new)
-                .push(revenue);
+        let mut metered: HashMap<String, BTreeMap<NaiveDate, DailyEnergy>> = HashMap::new();
+        for file_path in &smne_files {
+            match load_smne_energy(file_path) {
+                Ok(file_energy) => {
+                    for (resource, days) in file_energy {
+                        if !self.bess_resources.contains_key(&resource) {
+                            continue;
+                        }
+                        let resource_days = metered.entry(resource).or_default();
+                        for (date, day) in days {
+                            let total = resource_days.entry(date).or_default();
+                            total.charged_mwh += day.charged_mwh;
+                            total.discharged_mwh += day.discharged_mwh;
+                        }
+                    }
+                }
+                Err(e) => warn!("Skipping {} for meter reconciliation: {}", file_path.display(), e),
+            }
         }
         
-        let mut total_violations = 0;
-        let mut total_failures = 0;
+        let mut resources: Vec<&String> = metered.keys().filter(|resource| dispatch.contains_key(*resource)).collect();
+        resources.sort();
+        Ok(resources.into_iter()
+            .flat_map(|resource| reconcile_metered_energy(
+                resource, &telemetered_daily_energy(&dispatch[resource]), &metered[resource], DEFAULT_METER_TOLERANCE_PCT))
+            .collect())
+    }
+
+    /// Monthly SCED presence of each BESS, sorted by resource and month
+    fn monthly_availability(&self, dispatch: &HashMap<String, Vec<DispatchPoint>>) -> Vec<MonthlyAvailability> {
+        let days = operating_days(dispatch);
+        let mut resources: Vec<&String> = dispatch.keys()
+            .filter(|resource_name| self.bess_resources.contains_key(*resource_name))
+            .collect();
+        resources.sort();
+        resources.into_iter()
+            .flat_map(|resource_name| monthly_availability(resource_name, &dispatch[resource_name], &days))
+            .collect()
+    }
+
+    /// Resources whose total charge is inconsistent with their total discharge
+    fn check_energy_balance(&self, dispatch: &HashMap<String, Vec<DispatchPoint>>) -> Vec<EnergyBalance> {
+        let mut anomalies: Vec<EnergyBalance> = dispatch.iter()
+            .filter(|(resource_name, _)| self.bess_resources.contains_key(*resource_name))
+            .map(|(resource_name, points)| check_energy_balance(
+                resource_name, points, DEFAULT_ROUND_TRIP_EFFICIENCY, DEFAULT_ENERGY_BALANCE_TOLERANCE_PCT))
+            .filter(|balance| balance.flagged)
+            .collect();
         
-        for (resource_name, revenues) in resources {
-            let mut violations = 0;
-            let mut failures = 0;
-            
-            // Simple heuristics for detecting issues
-            for window in revenues.windows(2) {
-                let (prev, curr) = (&window[0], &window[1]);
-                
-                // Check for potential SOC violations (simplified)
-                // If energy revenue swings are too large relative to capacity
-                if let Some((_, capacity)) = self.bess_resources.get(&resource_name) {
-                    let energy_swing = (curr.energy_revenue - prev.energy_revenue).abs();
-                    let max_daily_revenue = capacity * 24.0 * 100.0; // Assume $100/MWh max
-                    
-                    if energy_swing > max_daily_revenue * 2.0 {
-                        violations += 1;
+        anomalies.sort_by(|a, b| a.resource_name.cmp(&b.resource_name));
+        anomalies
+    }
+
+    /// SCED telemetry for every BESS, merged across all Gen Resource Data files
+    fn load_bess_dispatch(&self) -> Result<HashMap<String, Vec<DispatchPoint>>> {
+        let sced_pattern = self.data_dir.join("SCED_extracted/60d_SCED_Gen_Resource_Data*.csv");
+        let sced_files: Vec<PathBuf> = glob::glob(sced_pattern.to_str().unwrap())?
+            .filter_map(Result::ok)
+            .filter(|path| self.date_range.includes_file(path))
+            .collect();
+        
+        let mut dispatch: HashMap<String, Vec<DispatchPoint>> = HashMap::new();
+        for file_path in &sced_files {
+            match load_sced_dispatch(file_path) {
+                Ok(file_dispatch) => {
+                    for (resource, points) in file_dispatch {
+                        dispatch.entry(resource).or_default().extend(points);
                     }
                 }
-                
-                // Check for AS failures (no AS revenue when previously had AS obligations)
-                if (prev.reg_up_revenue > 0.0 || prev.reg_down_revenue > 0.0 || 
-                    prev.rrs_revenue > 0.0 || prev.ecrs_revenue > 0.0) &&
-                   (curr.reg_up_revenue == 0.0 && curr.reg_down_revenue == 0.0 && 
-                    curr.rrs_revenue == 0.0 && curr.ecrs_revenue == 0.0) &&
-                   curr.total_revenue < prev.total_revenue * 0.5 {
-                    failures += 1;
-                }
+                Err(e) => warn!("Skipping {} for operational checks: {}", file_path.display(), e),
             }
-            
-            if violations > 0 || failures > 0 {
-                println!("  {} - SOC violations: {}, AS failures: {}", 
-                        resource_name, violations, failures);
+        }
+        
+        Ok(dispatch)
+    }
+
+    /// Energy-balance SOC check over the SCED telemetry: each battery starts
+    /// every operating day half full and is flagged whenever integrated
+    /// charge/discharge would push it outside [0, energy capacity]
+    fn detect_soc_violations(&self, dispatch: &HashMap<String, Vec<DispatchPoint>>) -> Vec<SocViolation> {
+        let mut violations = Vec::new();
+        for (resource_name, points) in dispatch {
+            let Some((_, capacity_mw)) = self.bess_resources.get(resource_name) else {
+                continue;
+            };
+            let duration = self.resource_durations.get(resource_name);
+            let model = SocModel::new(capacity_mw * duration, DEFAULT_ROUND_TRIP_EFFICIENCY);
+            violations.extend(detect_soc_violations(resource_name, points, &model));
+        }
+        
+        violations.sort_by(|a, b| a.resource_name.cmp(&b.resource_name).then(a.timestamp.cmp(&b.timestamp)));
+        violations
+    }
+
+    /// Compare hourly DAM AS awards against the headroom SCED dispatch left
+    /// in each interval of the awarded hour
+    fn detect_as_shortfalls(&self, dispatch: &HashMap<String, Vec<DispatchPoint>>) -> Result<Vec<AsShortfall>> {
+        let gen_pattern = self.data_dir.join("DAM_extracted/60d_DAM_Gen_Resource_Data*.csv");
+        let gen_files: Vec<PathBuf> = glob::glob(gen_pattern.to_str().unwrap())?
+            .filter_map(Result::ok)
+            .filter(|path| self.date_range.includes_file(path))
+            .collect();
+        
+        let mut awards: HashMap<String, Vec<AsAward>> = HashMap::new();
+        for file_path in &gen_files {
+            match load_dam_as_awards(file_path) {
+                Ok(file_awards) => {
+                    for (resource, resource_awards) in file_awards {
+                        awards.entry(resource).or_default().extend(resource_awards);
+                    }
+                }
+                Err(e) => warn!("Skipping {} for AS headroom check: {}", file_path.display(), e),
             }
-            
-            total_violations += violations;
-            total_failures += failures;
         }
         
-        println!("\nTotal operational issues detected:");
-        println!("  SOC violations: {}", total_violations);
-        println!("  AS failures: {}", total_failures);
+        let mut shortfalls = Vec::new();
+        for (resource_name, resource_awards) in &awards {
+            if let (Some((_, capacity_mw)), Some(points)) = (self.bess_resources.get(resource_name), dispatch.get(resource_name)) {
+                shortfalls.extend(detect_as_shortfalls(resource_name, resource_awards, points, *capacity_mw));
+            }
+        }
         
-        Ok(())
+        shortfalls.sort_by(|a, b| a.resource_name.cmp(&b.resource_name).then(a.timestamp.cmp(&b.timestamp)));
+        Ok(shortfalls)
     }
 
     fn generate_performance_metrics(&self, daily_revenues: &[BessRevenue]) -> Result<()> {
         println!("\n📊 Generating Performance Metrics...");
         
-        // Calculate annual totals by resource
-        let mut annual_totals: HashMap<String, f64> = HashMap::new();
-        let mut resource_days: HashMap<String, u32> = HashMap::new();
+        let (annualizable, insufficient) = split_by_coverage(daily_revenues, self.min_days_for_annualization);
         
-        for revenue in daily_revenues {
-            *annual_totals.entry(revenue.resource_name.clone()).or_insert(0.0) += revenue.total_revenue;
-            *resource_days.entry(revenue.resource_name.clone()).or_insert(0) += 1;
-        }
-        
-        // Create leaderboard with $/MW metrics
+        // Create leaderboard with $/MW metrics, each resource compared to
+        // the peer benchmark for its duration class
         let mut leaderboard = Vec::new();
         
-        for (resource_name, total_revenue) in annual_totals {
-            if let Some((_, capacity)) = self.bess_resources.get(&resource_name) {
-                let days = resource_days.get(&resource_name).unwrap_or(&1);
-                let annualized_revenue = (total_revenue / *days as f64) * 365.0;
+        for coverage in annualizable {
+            if let Some((_, capacity)) = self.bess_resources.get(&coverage.resource_name) {
+                let annualized_revenue = coverage.annualized_revenue();
                 let revenue_per_mw = if *capacity > 0.0 { 
                     annualized_revenue / capacity 
                 } else { 
                     0.0 
                 };
+                let duration_hours = self.resource_durations.get(&coverage.resource_name);
+                let benchmark = self.benchmarks.for_duration(duration_hours);
                 
-                leaderboard.push((resource_name, revenue_per_mw, annualized_revenue, *capacity));
+                leaderboard.push(LeaderboardEntry {
+                    resource_name: coverage.resource_name,
+                    revenue_per_mw,
+                    annualized_revenue,
+                    capacity_mw: *capacity,
+                    duration_hours,
+                    benchmark_per_mw: benchmark.revenue_per_mw_year,
+                    vs_benchmark_pct: performance_vs_benchmark(revenue_per_mw, benchmark),
+                });
             }
         }
         
         // Sort by $/MW
-        leaderboard.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
+        leaderboard.sort_by(|a, b| b.revenue_per_mw.partial_cmp(&a.revenue_per_mw).unwrap());
         
         println!("\n🏆 BESS Performance Leaderboard (Top 20):");
-        println!("{:<40} {:>15} {:>20} {:>10}", "Resource Name", "$/MW/year", "Total $/year", "MW");
-        println!("{}", "-".repeat(95));
-        
-        for (i, (name, rev_per_mw, total_rev, capacity)) in leaderboard.iter().take(20).enumerate() {
-            println!("{:2}. {:<37} ${:>13.0} ${:>18.0} {:>9.1}", 
-                    i + 1, name, rev_per_mw, total_rev, capacity);
+        println!("{:<40} {:>15} {:>20} {:>10} {:>6} {:>15} {:>10}",
+                 "Resource Name", "$/MW/year", "Total $/year", "MW", "Hours", "Benchmark $/MW", "vs Bench");
+        println!("{}", "-".repeat(122));
+        
+        for (i, entry) in leaderboard.iter().take(20).enumerate() {
+            println!("{:2}. {:<37} ${:>13.0} ${:>18.0} {:>9.1} {:>6.1} ${:>13.0} {:>+9.1}%", 
+                    i + 1, entry.resource_name, entry.revenue_per_mw, entry.annualized_revenue,
+                    entry.capacity_mw, entry.duration_hours, entry.benchmark_per_mw, entry.vs_benchmark_pct);
         }
         
         // Calculate market statistics
-        let total_market_revenue: f64 = leaderboard.iter().map(|(_, _, rev, _)| rev).sum();
-        let total_market_capacity: f64 = leaderboard.iter().map(|(_, _, _, cap)| cap).sum();
+        let total_market_revenue: f64 = leaderboard.iter().map(|e| e.annualized_revenue).sum();
+        let total_market_capacity: f64 = leaderboard.iter().map(|e| e.capacity_mw).sum();
         let market_average = total_market_revenue / total_market_capacity;
         
         println!("\n📈 Market Statistics:");
@@ -1327,58 +1836,112 @@ impl BessRevenueCalculator {
         println!("  Total market revenue: ${:.0}/year", total_market_revenue);
         println!("  Market average: ${:.0}/MW/year", market_average);
         
-        // Compare to Modo benchmark
-        println!("\n📊 Benchmark Comparison:");
-        println!("  Modo Energy 2023 average: $196,000/MW/year");
-        println!("  This analysis average: ${:.0}/MW/year", market_average);
+        // Compare each duration class to its peer benchmark
+        println!("\n📊 Benchmark Comparison by Duration Class:");
+        for benchmark in self.benchmarks.classes() {
+            let peers: Vec<&LeaderboardEntry> = leaderboard.iter()
+                .filter(|e| self.benchmarks.for_duration(e.duration_hours).duration_hours == benchmark.duration_hours)
+                .collect();
+            let capacity: f64 = peers.iter().map(|e| e.capacity_mw).sum();
+            if peers.is_empty() || capacity <= 0.0 {
+                continue;
+            }
+            let class_average = peers.iter().map(|e| e.annualized_revenue).sum::<f64>() / capacity;
+            let outperforming = peers.iter().filter(|e| e.vs_benchmark_pct > 0.0).count();
+            
+            println!("  {} ({}): benchmark ${:.0}/MW/year, class average ${:.0}/MW/year ({:+.1}%), {}/{} resources above benchmark",
+                     benchmark.label(), benchmark.source, benchmark.revenue_per_mw_year, class_average,
+                     performance_vs_benchmark(class_average, benchmark), outperforming, peers.len());
+        }
         
         // Save leaderboard
         self.save_leaderboard(&leaderboard)?;
+        self.report_insufficient_data(&insufficient)?;
         
         Ok(())
     }
 
-    fn save_daily_rollups(&self, revenues: &[BessRevenue]) -> Result<()> {
-        let mut resource_names = Vec::new();
-        let mut dates = Vec::new();
-        let mut energy_revs = Vec::new();
-        let mut dam_energy_revs = Vec::new();
-        let mut rt_energy_revs = Vec::new();
-        let mut reg_up_revs = Vec::new();
-        let mut reg_down_revs = Vec::new();
-        let mut rrs_revs = Vec::new();
-        let mut ecrs_revs = Vec::new();
-        let mut non_spin_revs = Vec::new();
-        let mut total_revs = Vec::new();
+    /// Resources with fewer than `min_days_for_annualization` days are listed
+    /// with their unextrapolated totals instead of ranked
+    fn report_insufficient_data(&self, insufficient: &[ResourceCoverage]) -> Result<()> {
+        if insufficient.is_empty() {
+            return Ok(());
+        }
         
-        for rev in revenues {
-            resource_names.push(rev.resource_name.clone());
-            dates.push(rev.date.format("%Y-%m-%d").to_string());
-            energy_revs.push(rev.energy_revenue);
-            dam_energy_revs.push(rev.dam_energy_revenue);
-            rt_energy_revs.push(rev.rt_energy_revenue);
-            reg_up_revs.push(rev.reg_up_revenue);
-            reg_down_revs.push(rev.reg_down_revenue);
-            rrs_revs.push(rev.rrs_revenue);
-            ecrs_revs.push(rev.ecrs_revenue);
-            non_spin_revs.push(rev.non_spin_revenue);
-            total_revs.push(rev.total_revenue);
+        println!("\n⚠️  Insufficient data (< {} days, not annualized): {} resources",
+                 self.min_days_for_annualization, insufficient.len());
+        for coverage in insufficient {
+            println!("  {:<40} {:>4} days ${:>13.0}", coverage.resource_name, coverage.days, coverage.total_revenue);
         }
         
-        let df = DataFrame::new(vec![
-            Series::new("Resource_Name", resource_names),
-            Series::new("Date", dates),
-            Series::new("Energy_Revenue", energy_revs),
-            Series::new("DAM_Energy_Revenue", dam_energy_revs),
-            Series::new("RT_Energy_Revenue", rt_energy_revs),
-            Series::new("RegUp_Revenue", reg_up_revs),
-            Series::new("RegDown_Revenue", reg_down_revs),
-            Series::new("RRS_Revenue", rrs_revs),
-            Series::new("ECRS_Revenue", ecrs_revs),
-            Series::new("NonSpin_Revenue", non_spin_revs),
-            Series::new("Total_Revenue", total_revs),
+        let mut df = DataFrame::new(vec![
+            Series::new("Resource_Name", insufficient.iter().map(|c| c.resource_name.clone()).collect::<Vec<_>>()),
+            Series::new("Days", insufficient.iter().map(|c| c.days).collect::<Vec<_>>()),
+            Series::new("Total_Revenue", insufficient.iter().map(|c| c.total_revenue).collect::<Vec<_>>()),
         ])?;
         
+        let output_path = self.output_dir.join("bess_insufficient_data.csv");
+        CsvWriter::new(std::fs::File::create(&output_path)?)
+            .finish(&mut df)?;
+        
+        println!("✅ Saved insufficient-data resources to: {}", output_path.display());
+        
+        Ok(())
+    }
+
+    /// Trailing `window_days` revenue and $/MW per resource, from the daily rollup
+    pub fn rolling_revenue(&self, daily_revenues: &[BessRevenue], window_days: usize) -> Result<DataFrame> {
+        let capacities: HashMap<String, f64> = self.bess_resources.iter()
+            .map(|(name, (_, capacity))| (name.clone(), *capacity))
+            .collect();
+        rolling_revenue(daily_revenues, &capacities, window_days)
+    }
+
+    fn save_rolling_revenue(&self, daily_revenues: &[BessRevenue], window_days: usize) -> Result<()> {
+        let mut df = self.rolling_revenue(daily_revenues, window_days)?;
+        
+        let output_path = self.output_dir.join(format!("bess_rolling_{}d.parquet", window_days));
+        ParquetWriter::new(std::fs::File::create(&output_path)?)
+            .finish(&mut df)?;
+        
+        println!("✅ Saved trailing {}-day revenues to: {}", window_days, output_path.display());
+        
+        Ok(())
+    }
+
+    fn save_revenue_histogram(&self, daily_revenues: &[BessRevenue]) -> Result<()> {
+        let mut df = revenue_histogram(daily_revenues, DEFAULT_HISTOGRAM_BUCKETS)?;
+        
+        let output_path = self.output_dir.join("revenue_histogram.csv");
+        CsvWriter::new(std::fs::File::create(&output_path)?)
+            .finish(&mut df)?;
+        
+        println!("✅ Saved daily revenue histogram to: {}", output_path.display());
+        
+        Ok(())
+    }
+
+    fn save_best_worst_days(&self, daily_revenues: &[BessRevenue]) -> Result<()> {
+        let mut df = best_worst_days(daily_revenues, DEFAULT_EXTREME_DAYS)?;
+        
+        let output_path = self.output_dir.join("bess_best_worst_days.csv");
+        CsvWriter::new(std::fs::File::create(&output_path)?)
+            .finish(&mut df)?;
+        
+        println!("✅ Saved top and bottom {} revenue days per resource to: {}", DEFAULT_EXTREME_DAYS, output_path.display());
+        
+        Ok(())
+    }
+
+    fn save_daily_rollups(&self, rollup: &DataFrame) -> Result<()> {
+        let mut columns = vec!["Resource_Name", "Date", "Energy_Revenue", "DAM_Energy_Revenue", "RT_Energy_Revenue"];
+        if self.revenue_basis == RevenueBasis::Gross {
+            columns.extend(GROSS_ENERGY_COLUMNS);
+        }
+        columns.extend(AS_REVENUE_COLUMNS.iter().map(|(_, column)| *column));
+        columns.push("Total_Revenue");
+        let df = rollup.select(columns)?;
+        
         let output_path = self.output_dir.join("bess_daily_revenues.csv");
         CsvWriter::new(std::fs::File::create(&output_path)?)
             .finish(&mut df.clone())?;
@@ -1390,20 +1953,34 @@ impl BessRevenueCalculator {
         
         println!("\n✅ Saved daily revenue rollups to: {}", output_path.display());
         
+        #[cfg(feature = "sqlite")]
+        if let Some(sqlite_path) = &self.sqlite_path {
+            sqlite_sink::write_table(sqlite_path, "daily_revenues", &df, &["Resource_Name", "Date"])?;
+            println!("✅ Saved daily revenue rollups to table daily_revenues in: {}", sqlite_path.display());
+        }
+        
         Ok(())
     }
 
-    fn save_leaderboard(&self, leaderboard: &[(String, f64, f64, f64)]) -> Result<()> {
+    fn save_leaderboard(&self, leaderboard: &[LeaderboardEntry]) -> Result<()> {
         let mut names = Vec::new();
         let mut rev_per_mw = Vec::new();
         let mut total_revs = Vec::new();
         let mut capacities = Vec::new();
-        
-        for (name, rpm, total, cap) in leaderboard {
-            names.push(name.clone());
-            rev_per_mw.push(*rpm);
-            total_revs.push(*total);
-            capacities.push(*cap);
+        let mut durations = Vec::new();
+        let mut benchmarks = Vec::new();
+        let mut vs_benchmark = Vec::new();
+        let mut price_bases = Vec::new();
+        
+        for entry in leaderboard {
+            names.push(entry.resource_name.clone());
+            rev_per_mw.push(entry.revenue_per_mw);
+            total_revs.push(entry.annualized_revenue);
+            capacities.push(entry.capacity_mw);
+            durations.push(entry.duration_hours);
+            benchmarks.push(entry.benchmark_per_mw);
+            vs_benchmark.push(entry.vs_benchmark_pct);
+            price_bases.push(self.rt_price_basis.basis(&entry.resource_name).label());
         }
         
         let df = DataFrame::new(vec![
@@ -1411,6 +1988,10 @@ impl BessRevenueCalculator {
             Series::new("Revenue_Per_MW_Year", rev_per_mw),
             Series::new("Total_Revenue_Year", total_revs),
             Series::new("Capacity_MW", capacities),
+            Series::new("Duration_Hours", durations),
+            Series::new("Benchmark_Per_MW_Year", benchmarks),
+            Series::new("Vs_Benchmark_Pct", vs_benchmark),
+            Series::new("RT_Price_Basis", price_bases),
         ])?;
         
         let output_path = self.output_dir.join("bess_performance_leaderboard.csv");
@@ -1449,6 +2030,14 @@ impl BessRevenueCalculator {
             *totals.entry("DAM_Energy").or_insert(0.0) += revenue.dam_energy_revenue;
             *totals.entry("RT_Energy").or_insert(0.0) += revenue.rt_energy_revenue;
             *totals.entry("Total_Energy").or_insert(0.0) += revenue.energy_revenue;
+            *totals.entry("DAM_Discharge").or_insert(0.0) += revenue.dam_energy.discharge_revenue;
+            *totals.entry("DAM_Charge").or_insert(0.0) += revenue.dam_energy.charge_cost;
+            *totals.entry("RT_Discharge").or_insert(0.0) += revenue.rt_energy.discharge_revenue;
+            *totals.entry("RT_Charge").or_insert(0.0) += revenue.rt_energy.charge_cost;
+            *totals.entry("DAM_Discharged_MWh").or_insert(0.0) += revenue.dam_energy.discharged_mwh;
+            *totals.entry("DAM_Charged_MWh").or_insert(0.0) += revenue.dam_energy.charged_mwh;
+            *totals.entry("RT_Discharged_MWh").or_insert(0.0) += revenue.rt_energy.discharged_mwh;
+            *totals.entry("RT_Charged_MWh").or_insert(0.0) += revenue.rt_energy.charged_mwh;
             *totals.entry("RegUp").or_insert(0.0) += revenue.reg_up_revenue;
             *totals.entry("RegDown").or_insert(0.0) += revenue.reg_down_revenue;
             *totals.entry("RRS").or_insert(0.0) += revenue.rrs_revenue;
@@ -1465,6 +2054,7 @@ impl BessRevenueCalculator {
         let mut dam_energy_totals = Vec::new();
         let mut rt_energy_totals = Vec::new();
         let mut total_energy_totals = Vec::new();
+        let mut gross_energy_totals = Vec::new();
         let mut reg_up_totals = Vec::new();
         let mut reg_down_totals = Vec::new();
         let mut rrs_totals = Vec::new();
@@ -1482,9 +2072,14 @@ impl BessRevenueCalculator {
             total_b.partial_cmp(total_a).unwrap()
         });
         
+        let mut skipped = 0;
         for (resource_name, totals) in sorted_resources {
-            let days = *resource_days.get(resource_name).unwrap_or(&1) as f64;
-            let annualization_factor = 365.0 / days;
+            let days = *resource_days.get(resource_name).unwrap_or(&1);
+            if days < self.min_days_for_annualization {
+                skipped += 1;
+                continue;
+            }
+            let annualization_factor = 365.0 / days as f64;
             
             let capacity = self.bess_resources.get(resource_name)
                 .map(|(_, cap)| *cap)
@@ -1504,6 +2099,20 @@ impl BessRevenueCalculator {
             let non_spin_annual = totals.get("NonSpin").unwrap_or(&0.0) * annualization_factor;
             let total_annual = totals.get("Total").unwrap_or(&0.0) * annualization_factor;
             
+            gross_energy_totals.push((
+                EnergySettlement {
+                    discharge_revenue: totals.get("DAM_Discharge").unwrap_or(&0.0) * annualization_factor,
+                    charge_cost: totals.get("DAM_Charge").unwrap_or(&0.0) * annualization_factor,
+                    discharged_mwh: totals.get("DAM_Discharged_MWh").unwrap_or(&0.0) * annualization_factor,
+                    charged_mwh: totals.get("DAM_Charged_MWh").unwrap_or(&0.0) * annualization_factor,
+                },
+                EnergySettlement {
+                    discharge_revenue: totals.get("RT_Discharge").unwrap_or(&0.0) * annualization_factor,
+                    charge_cost: totals.get("RT_Charge").unwrap_or(&0.0) * annualization_factor,
+                    discharged_mwh: totals.get("RT_Discharged_MWh").unwrap_or(&0.0) * annualization_factor,
+                    charged_mwh: totals.get("RT_Charged_MWh").unwrap_or(&0.0) * annualization_factor,
+                },
+            ));
             dam_energy_totals.push(dam_annual);
             rt_energy_totals.push(rt_annual);
             total_energy_totals.push(total_energy_annual);
@@ -1521,19 +2130,48 @@ impl BessRevenueCalculator {
             revenue_per_mw_year.push(per_mw);
         }
         
+        if skipped > 0 {
+            println!("  Excluded {} resources with fewer than {} days of data (see bess_insufficient_data.csv)",
+                     skipped, self.min_days_for_annualization);
+        }
+        
         // Calculate summary statistics before moving vectors
         let total_dam: f64 = dam_energy_totals.iter().sum();
         let total_rt: f64 = rt_energy_totals.iter().sum();
         let total_energy: f64 = total_energy_totals.iter().sum();
         let total_as: f64 = total_as_revenues.iter().sum();
         let grand_total: f64 = grand_totals.iter().sum();
+        let total_discharge: f64 = gross_energy_totals.iter()
+            .map(|(dam, rt)| dam.discharge_revenue + rt.discharge_revenue)
+            .sum();
+        let total_charge: f64 = gross_energy_totals.iter()
+            .map(|(dam, rt)| dam.charge_cost + rt.charge_cost)
+            .sum();
         
-        let df = DataFrame::new(vec![
+        let (dam_settlements, rt_settlements): (Vec<_>, Vec<_>) = gross_energy_totals.into_iter().unzip();
+        let rt_discharge: f64 = rt_settlements.iter().map(|e| e.discharge_revenue).sum();
+        let rt_charge: f64 = rt_settlements.iter().map(|e| e.charge_cost).sum();
+        let rt_efficiency_loss: f64 = rt_settlements.iter()
+            .map(EnergySettlement::efficiency_loss)
+            .sum();
+        
+        let mut columns = vec![
             Series::new("Resource_Name", resource_names),
             Series::new("Capacity_MW", capacities),
             Series::new("DAM_Energy_Revenue_Annual", dam_energy_totals),
             Series::new("RT_Energy_Revenue_Annual", rt_energy_totals),
             Series::new("Total_Energy_Revenue_Annual", total_energy_totals),
+        ];
+        if self.revenue_basis == RevenueBasis::Gross {
+            columns.extend(settlement_columns("DAM", &dam_settlements, "_Annual"));
+        }
+        // RT is always broken out: discharge revenue, charge cost, and the
+        // part of the charge cost lost to round-trip inefficiency
+        columns.extend(settlement_columns("RT", &rt_settlements, "_Annual"));
+        columns.push(Series::new("RT_Efficiency_Loss_Annual", rt_settlements.iter()
+            .map(EnergySettlement::efficiency_loss)
+            .collect::<Vec<_>>()));
+        columns.extend([
             Series::new("RegUp_Revenue_Annual", reg_up_totals),
             Series::new("RegDown_Revenue_Annual", reg_down_totals),
             Series::new("RRS_Revenue_Annual", rrs_totals),
@@ -1542,7 +2180,8 @@ impl BessRevenueCalculator {
             Series::new("Total_AS_Revenue_Annual", total_as_revenues),
             Series::new("Total_Revenue_Annual", grand_totals),
             Series::new("Revenue_Per_MW_Year", revenue_per_mw_year),
-        ])?;
+        ]);
+        let df = DataFrame::new(columns)?;
         
         let output_path = self.output_dir.join("bess_revenue_breakdown_detailed.csv");
         CsvWriter::new(std::fs::File::create(&output_path)?)
@@ -1550,9 +2189,27 @@ impl BessRevenueCalculator {
         
         println!("✅ Saved detailed revenue breakdown to: {}", output_path.display());
         
+        #[cfg(feature = "sqlite")]
+        if let Some(sqlite_path) = &self.sqlite_path {
+            // Annualized figures have no date; one row per resource
+            sqlite_sink::write_table(sqlite_path, "annual_revenues", &df, &["Resource_Name"])?;
+            println!("✅ Saved detailed revenue breakdown to table annual_revenues in: {}", sqlite_path.display());
+        }
+        
         println!("\n📊 Portfolio Revenue Summary (Annualized):");
+        if self.revenue_view == RevenueView::GrossDischarge {
+            println!("  (energy revenue is gross discharge revenue; charge cost excluded)");
+        }
         println!("  DAM Energy Revenue: ${:.2}M", total_dam / 1_000_000.0);
         println!("  RT Energy Revenue: ${:.2}M", total_rt / 1_000_000.0);
+        println!("    RT Discharge Revenue: ${:.2}M", rt_discharge / 1_000_000.0);
+        println!("    RT Charge Cost: ${:.2}M", rt_charge / 1_000_000.0);
+        println!("      of which efficiency loss (charged but not discharged): ${:.2}M",
+                 rt_efficiency_loss / 1_000_000.0);
+        if self.revenue_basis == RevenueBasis::Gross {
+            println!("    Gross Discharge Revenue: ${:.2}M", total_discharge / 1_000_000.0);
+            println!("    Gross Charge Cost: ${:.2}M", total_charge / 1_000_000.0);
+        }
         println!("  Total Energy Revenue: ${:.2}M", total_energy / 1_000_000.0);
         println!("  Total AS Revenue: ${:.2}M", total_as / 1_000_000.0);
         println!("  Grand Total Revenue: ${:.2}M", grand_total / 1_000_000.0);
@@ -1568,9 +2225,824 @@ impl BessRevenueCalculator {
     }
 }
 
-pub fn calculate_bess_revenues() -> Result<()> {
-    let master_list_path = PathBuf::from("bess_analysis/bess_resources_master_list.csv");
-    let calculator = BessRevenueCalculator::new(&master_list_path)?;
+/// Settings for one `--bess-full-disclosure` revenue run, as parsed from the
+/// command line; `Default` gives the calculator's own defaults
+#[derive(Debug, Clone)]
+pub struct RevenueRunOptions {
+    pub revenue_basis: RevenueBasis,
+    pub revenue_view: RevenueView,
+    pub min_days_for_annualization: u32,
+    pub price_loading: PriceLoading,
+    pub duplicate_prices: DuplicatePricePolicy,
+    pub award_sign: AwardSign,
+    pub resource_filter: ResourceFilter,
+    pub default_duration_hours: f64,
+    /// CSV of RT intervals with no price, if wanted
+    pub dump_unmatched: Option<PathBuf>,
+    pub price_outliers: Option<PriceOutlierFilter>,
+    pub date_range: DateRange,
+    /// Directory to write the loaded price caches to before the run
+    pub export_price_cache: Option<PathBuf>,
+    /// SQLite database to also write the revenue tables to; needs the `sqlite` feature
+    pub sqlite_path: Option<PathBuf>,
+    pub scarcity_price: f64,
+    /// Run name from `--output-prefix`, applied to every directory the run reads and writes
+    pub output_prefix: OutputPrefix,
+}
+
+impl Default for RevenueRunOptions {
+    fn default() -> Self {
+        Self {
+            revenue_basis: RevenueBasis::default(),
+            revenue_view: RevenueView::default(),
+            min_days_for_annualization: DEFAULT_MIN_DAYS_FOR_ANNUALIZATION,
+            price_loading: PriceLoading::default(),
+            duplicate_prices: DuplicatePricePolicy::default(),
+            award_sign: AwardSign::default(),
+            resource_filter: ResourceFilter::default(),
+            default_duration_hours: DEFAULT_DURATION_HOURS,
+            dump_unmatched: None,
+            price_outliers: None,
+            date_range: DateRange::default(),
+            export_price_cache: None,
+            sqlite_path: None,
+            scarcity_price: DEFAULT_SCARCITY_PRICE,
+            output_prefix: OutputPrefix::default(),
+        }
+    }
+}
+
+pub fn calculate_bess_revenues(options: &RevenueRunOptions, timer: &StageTimer) -> Result<()> {
+    let master_list_path = options.output_prefix.input_path("bess_analysis/bess_resources_master_list.csv");
+    let calculator = BessRevenueCalculator::new(
+        &master_list_path,
+        options.price_loading,
+        options.duplicate_prices,
+        timer.clone(),
+        &options.output_prefix,
+    )?
+        .with_revenue_basis(options.revenue_basis)
+        .with_revenue_view(options.revenue_view)
+        .with_award_sign(options.award_sign)
+        .with_min_days_for_annualization(options.min_days_for_annualization)
+        .with_resource_filter(&options.resource_filter)
+        .with_default_duration_hours(options.default_duration_hours)
+        .with_dump_unmatched(options.dump_unmatched.clone())
+        .with_price_outlier_filter(options.price_outliers)
+        .with_date_range(options.date_range)
+        .with_scarcity_price(options.scarcity_price);
+    #[cfg(feature = "sqlite")]
+    let calculator = calculator.with_sqlite(options.sqlite_path.clone());
+    if let Some(dir) = &options.export_price_cache {
+        calculator.export_price_cache(dir)?;
+    }
     calculator.calculate_all_revenues()?;
     Ok(())
-}
\ No newline at end of file
+}
+
+/// Settle DAM energy awards from PWRSTR Gen Resource Data rows into
+/// `dam_energy` per resource-day, for resources `include` accepts. Awards
+/// are MW held for the whole hour ending (`DAM_AWARD_INTERVAL`), so each row
+/// settles award × 1 h × price. A row repeating a resource-hour already
+/// settled is sub-hourly or duplicated data that this can't account for; it
+/// is counted in `settled_hours` and skipped.
+fn settle_dam_awards(
+    df: &DataFrame,
+    award_sign: AwardSign,
+    include: impl Fn(&str) -> bool,
+    dam_energy: &mut HashMap<(String, NaiveDate), EnergySettlement>,
+    award_totals: &mut HashMap<String, AwardTotals>,
+    settled_hours: &mut SettledAwardHours,
+) -> Result<()> {
+    let (Ok(dates), Ok(hours), Ok(resources), Ok(awards), Ok(prices)) = (
+        df.column("Delivery Date"),
+        df.column("Hour Ending"),
+        df.column("Resource Name"),
+        df.column("Awarded Quantity"),
+        df.column("Energy Settlement Point Price")
+    ) else {
+        return Ok(());
+    };
+    let dates_utf8 = dates.utf8()?;
+    let hours_i64 = hours.i64()?;
+    let repeated_hours = df.column("Repeated Hour Flag").ok().and_then(|c| c.utf8().ok());
+    let resources_utf8 = resources.utf8()?;
+    
+    // Handle awarded quantity - might be string or float
+    let awards_f64 = if let Ok(f64_col) = awards.f64() {
+        f64_col.clone()
+    } else if let Ok(utf8_col) = awards.utf8() {
+        // Convert string to float
+        let values: Vec<Option<f64>> = utf8_col.into_iter()
+            .map(|v| v.and_then(|s| if s.is_empty() { Some(0.0) } else { s.parse().ok() }))
+            .collect();
+        Float64Chunked::from_iter(values)
+    } else {
+        return Ok(());
+    };
+    
+    let prices_f64 = prices.f64()?;
+    
+    for i in 0..df.height() {
+        if let (Some(date_str), Some(hour_ending), Some(resource), Some(award_mw), Some(price)) = 
+            (dates_utf8.get(i), hours_i64.get(i), resources_utf8.get(i), 
+             awards_f64.get(i), prices_f64.get(i)) {
+            
+            // Check if this is one of our BESS resources
+            if !include(resource) {
+                continue;
+            }
+            // Parse date
+            let Ok(date) = NaiveDate::parse_from_str(date_str, "%m/%d/%Y") else {
+                continue;
+            };
+            let repeated_hour = is_repeated_hour(repeated_hours.and_then(|flags| flags.get(i)));
+            if !settled_hours.insert(resource, date, hour_ending, repeated_hour) {
+                continue;
+            }
+            
+            // Charging (negative output) is a cost, discharging (positive output) revenue
+            let output_mw = award_sign.output_mw(award_mw);
+            dam_energy.entry((resource.to_string(), date)).or_default()
+                .record(output_mw, interval_revenue(output_mw, price, DAM_AWARD_INTERVAL), DAM_AWARD_INTERVAL);
+            award_totals.entry(resource.to_string()).or_default()
+                .record(DAM_AWARD_INTERVAL.energy_mwh(output_mw));
+        }
+    }
+    
+    Ok(())
+}
+
+/// Ancillary service keys in the AS revenue map and their daily rollup columns
+const AS_REVENUE_COLUMNS: [(&str, &str); 5] = [
+    ("RegUp", "RegUp_Revenue"),
+    ("RegDown", "RegDown_Revenue"),
+    ("RRS", "RRS_Revenue"),
+    ("ECRS", "ECRS_Revenue"),
+    ("NonSpin", "NonSpin_Revenue"),
+];
+
+const GROSS_ENERGY_COLUMNS: [&str; 4] = ["DAM_Discharge_Revenue", "DAM_Charge_Cost", "RT_Discharge_Revenue", "RT_Charge_Cost"];
+
+const ENERGY_MWH_COLUMNS: [&str; 4] = ["DAM_Discharged_MWh", "DAM_Charged_MWh", "RT_Discharged_MWh", "RT_Charged_MWh"];
+
+/// Per resource-day energy settlements as `Resource_Name, Date, {market}_Discharge_Revenue, {market}_Charge_Cost`,
+/// plus the `{market}_Discharged_MWh` and `{market}_Charged_MWh` behind them
+fn energy_frame(revenues: &HashMap<(String, NaiveDate), EnergySettlement>, market: &str) -> Result<DataFrame> {
+    Ok(DataFrame::new(vec![
+        Series::new("Resource_Name", revenues.keys().map(|(name, _)| name.clone()).collect::<Vec<_>>()),
+        Series::new("Date", revenues.keys().map(|(_, date)| date.format("%Y-%m-%d").to_string()).collect::<Vec<_>>()),
+        Series::new(&format!("{}_Discharge_Revenue", market), revenues.values().map(|e| e.discharge_revenue).collect::<Vec<_>>()),
+        Series::new(&format!("{}_Charge_Cost", market), revenues.values().map(|e| e.charge_cost).collect::<Vec<_>>()),
+        Series::new(&format!("{}_Discharged_MWh", market), revenues.values().map(|e| e.discharged_mwh).collect::<Vec<_>>()),
+        Series::new(&format!("{}_Charged_MWh", market), revenues.values().map(|e| e.charged_mwh).collect::<Vec<_>>()),
+    ])?)
+}
+
+/// AS clearing prices for row `i` keyed by canonical service; when a file has
+/// several variant columns for one service the first non-null one wins
+fn service_prices_at(service_columns: &[(AsService, Float64Chunked)], i: usize) -> HashMap<String, f64> {
+    let mut prices = HashMap::new();
+    for (service, column) in service_columns {
+        if let Some(price) = column.get(i) {
+            prices.entry(service.key().to_string()).or_insert(price);
+        }
+    }
+    prices
+}
+
+fn ancillary_frame(revenues: &HashMap<(String, NaiveDate), HashMap<String, f64>>) -> Result<DataFrame> {
+    let mut columns = vec![
+        Series::new("Resource_Name", revenues.keys().map(|(name, _)| name.clone()).collect::<Vec<_>>()),
+        Series::new("Date", revenues.keys().map(|(_, date)| date.format("%Y-%m-%d").to_string()).collect::<Vec<_>>()),
+    ];
+    for (service, column) in AS_REVENUE_COLUMNS {
+        columns.push(Series::new(column, revenues.values()
+            .map(|by_service| by_service.get(service).copied().unwrap_or(0.0))
+            .collect::<Vec<_>>()));
+    }
+    Ok(DataFrame::new(columns)?)
+}
+
+/// Daily revenue rollup: DAM, RT and AS results outer-joined on
+/// (Resource_Name, Date), missing streams filled with zero, sorted by
+/// resource then date. Carries the gross energy columns alongside the
+/// energy revenue columns, which count charge cost only under `RevenueView::Net`.
+pub fn daily_rollup_frame(
+    dam_revenues: &HashMap<(String, NaiveDate), EnergySettlement>,
+    rt_revenues: &HashMap<(String, NaiveDate), EnergySettlement>,
+    as_revenues: &HashMap<(String, NaiveDate), HashMap<String, f64>>,
+    view: RevenueView,
+) -> Result<DataFrame> {
+    let keys = [col("Resource_Name"), col("Date")];
+    let value_columns: Vec<&str> = GROSS_ENERGY_COLUMNS.iter().copied()
+        .chain(ENERGY_MWH_COLUMNS)
+        .chain(AS_REVENUE_COLUMNS.iter().map(|(_, column)| *column))
+        .collect();
+    let as_total = AS_REVENUE_COLUMNS.iter()
+        .map(|(_, column)| col(column))
+        .reduce(|sum, revenue| sum + revenue)
+        .unwrap();
+    
+    let rollup = energy_frame(dam_revenues, "DAM")?.lazy()
+        .join(energy_frame(rt_revenues, "RT")?.lazy(), &keys, &keys, JoinArgs::new(JoinType::Outer))
+        .join(ancillary_frame(as_revenues)?.lazy(), &keys, &keys, JoinArgs::new(JoinType::Outer))
+        .with_columns(value_columns.iter().map(|column| col(column).fill_null(lit(0.0))).collect::<Vec<_>>())
+        .with_columns([
+            view.energy_expr("DAM").alias("DAM_Energy_Revenue"),
+            view.energy_expr("RT").alias("RT_Energy_Revenue"),
+        ])
+        .with_column((col("DAM_Energy_Revenue") + col("RT_Energy_Revenue")).alias("Energy_Revenue"))
+        .with_column((col("Energy_Revenue") + as_total).alias("Total_Revenue"))
+        .sort_by_exprs(&keys, [false, false], false, false)
+        .collect()?;
+    
+    Ok(rollup)
+}
+
+/// `BessRevenue` records from a `daily_rollup_frame`, in frame order
+pub fn daily_revenues_from_frame(rollup: &DataFrame) -> Result<Vec<BessRevenue>> {
+    let names = rollup.column("Resource_Name")?.utf8()?;
+    let dates = rollup.column("Date")?.utf8()?;
+    let value = |column: &str, i: usize| -> Result<f64> {
+        Ok(rollup.column(column)?.f64()?.get(i).unwrap_or(0.0))
+    };
+    
+    let mut daily_revenues = Vec::with_capacity(rollup.height());
+    for i in 0..rollup.height() {
+        let (Some(name), Some(date)) = (names.get(i), dates.get(i)) else {
+            continue;
+        };
+        daily_revenues.push(BessRevenue {
+            resource_name: name.to_string(),
+            date: NaiveDate::parse_from_str(date, "%Y-%m-%d")?,
+            energy_revenue: value("Energy_Revenue", i)?,
+            dam_energy_revenue: value("DAM_Energy_Revenue", i)?,
+            rt_energy_revenue: value("RT_Energy_Revenue", i)?,
+            reg_up_revenue: value("RegUp_Revenue", i)?,
+            reg_down_revenue: value("RegDown_Revenue", i)?,
+            rrs_revenue: value("RRS_Revenue", i)?,
+            ecrs_revenue: value("ECRS_Revenue", i)?,
+            non_spin_revenue: value("NonSpin_Revenue", i)?,
+            total_revenue: value("Total_Revenue", i)?,
+            energy_cycles: 0.0, // To be calculated
+            soc_violations: 0,
+            as_failures: 0,
+            dam_energy: EnergySettlement {
+                discharge_revenue: value("DAM_Discharge_Revenue", i)?,
+                charge_cost: value("DAM_Charge_Cost", i)?,
+                discharged_mwh: value("DAM_Discharged_MWh", i)?,
+                charged_mwh: value("DAM_Charged_MWh", i)?,
+            },
+            rt_energy: EnergySettlement {
+                discharge_revenue: value("RT_Discharge_Revenue", i)?,
+                charge_cost: value("RT_Charge_Cost", i)?,
+                discharged_mwh: value("RT_Discharged_MWh", i)?,
+                charged_mwh: value("RT_Charged_MWh", i)?,
+            },
+        });
+    }
+    
+    Ok(daily_revenues)
+}
+
+/// Gross discharge revenue and charge cost columns for one market, e.g.
+/// `DAM_Discharge_Revenue{suffix}` and `DAM_Charge_Cost{suffix}`
+fn settlement_columns(market: &str, settlements: &[EnergySettlement], suffix: &str) -> Vec<Series> {
+    vec![
+        Series::new(&format!("{}_Discharge_Revenue{}", market, suffix), settlements.iter().map(|e| e.discharge_revenue).collect::<Vec<_>>()),
+        Series::new(&format!("{}_Charge_Cost{}", market, suffix), settlements.iter().map(|e| e.charge_cost).collect::<Vec<_>>()),
+    ]
+}
+
+/// Total revenue and days of data per resource, split into those with at
+/// least `min_days` days (safe to annualize) and those without. Both are
+/// sorted by resource name.
+pub fn split_by_coverage(daily_revenues: &[BessRevenue], min_days: u32) -> (Vec<ResourceCoverage>, Vec<ResourceCoverage>) {
+    let mut by_resource: HashMap<&str, (u32, f64)> = HashMap::new();
+    for revenue in daily_revenues {
+        let (days, total) = by_resource.entry(revenue.resource_name.as_str()).or_insert((0, 0.0));
+        *days += 1;
+        *total += revenue.total_revenue;
+    }
+    
+    let mut coverage: Vec<ResourceCoverage> = by_resource.into_iter()
+        .map(|(name, (days, total_revenue))| ResourceCoverage {
+            resource_name: name.to_string(),
+            days,
+            total_revenue,
+        })
+        .collect();
+    coverage.sort_by(|a, b| a.resource_name.cmp(&b.resource_name));
+    coverage.into_iter().partition(|c| c.days >= min_days)
+}
+
+/// Trailing-window revenue per resource. Every calendar day between a resource's
+/// first and last record gets a row; days without data count as zero revenue
+/// and are excluded from `Active_Days`.
+pub fn rolling_revenue(
+    daily_revenues: &[BessRevenue],
+    capacities: &HashMap<String, f64>,
+    window_days: usize,
+) -> Result<DataFrame> {
+    let mut by_resource: HashMap<&str, HashMap<NaiveDate, f64>> = HashMap::new();
+    for revenue in daily_revenues {
+        *by_resource.entry(revenue.resource_name.as_str())
+            .or_default()
+            .entry(revenue.date)
+            .or_insert(0.0) += revenue.total_revenue;
+    }
+    
+    let mut resources: Vec<&str> = by_resource.keys().copied().collect();
+    resources.sort();
+    
+    let mut names = Vec::new();
+    let mut dates = Vec::new();
+    let mut daily = Vec::new();
+    let mut trailing = Vec::new();
+    let mut trailing_per_mw = Vec::new();
+    let mut active_days = Vec::new();
+    
+    for resource in resources {
+        let revenues = &by_resource[resource];
+        let first = *revenues.keys().min().unwrap();
+        let last = *revenues.keys().max().unwrap();
+        let capacity = capacities.get(resource).copied().unwrap_or(0.0);
+        
+        let mut window: std::collections::VecDeque<Option<f64>> = std::collections::VecDeque::new();
+        let mut date = first;
+        while date <= last {
+            let value = revenues.get(&date).copied();
+            window.push_back(value);
+            if window.len() > window_days {
+                window.pop_front();
+            }
+            
+            let sum: f64 = window.iter().flatten().sum();
+            names.push(resource.to_string());
+            dates.push(date.format("%Y-%m-%d").to_string());
+            daily.push(value.unwrap_or(0.0));
+            trailing.push(sum);
+            trailing_per_mw.push(if capacity > 0.0 { sum / capacity } else { 0.0 });
+            active_days.push(window.iter().filter(|v| v.is_some()).count() as u32);
+            
+            date += chrono::Duration::days(1);
+        }
+    }
+    
+    Ok(DataFrame::new(vec![
+        Series::new("Resource_Name", names),
+        Series::new("Date", dates),
+        Series::new("Daily_Revenue", daily),
+        Series::new(&format!("Trailing_{}d_Revenue", window_days), trailing),
+        Series::new(&format!("Trailing_{}d_Revenue_Per_MW", window_days), trailing_per_mw),
+        Series::new("Active_Days", active_days),
+    ])?)
+}
+
+/// Each resource's daily total revenue binned into `buckets` equal-width
+/// buckets between its lowest and highest day, empty buckets included, so
+/// skew and tail days show. A resource's `Days` sum to its active days.
+pub fn revenue_histogram(daily_revenues: &[BessRevenue], buckets: usize) -> Result<DataFrame> {
+    let buckets = buckets.max(1);
+    let mut by_resource: HashMap<&str, HashMap<NaiveDate, f64>> = HashMap::new();
+    for revenue in daily_revenues {
+        *by_resource.entry(revenue.resource_name.as_str())
+            .or_default()
+            .entry(revenue.date)
+            .or_insert(0.0) += revenue.total_revenue;
+    }
+    
+    let mut resources: Vec<&str> = by_resource.keys().copied().collect();
+    resources.sort();
+    
+    let mut names = Vec::new();
+    let mut bucket_numbers = Vec::new();
+    let mut lows = Vec::new();
+    let mut highs = Vec::new();
+    let mut days = Vec::new();
+    
+    for resource in resources {
+        let revenues = &by_resource[resource];
+        let min = revenues.values().copied().fold(f64::INFINITY, f64::min);
+        let max = revenues.values().copied().fold(f64::NEG_INFINITY, f64::max);
+        // A resource that earned the same every day gets one bucket
+        let (count, width) = if max > min { (buckets, (max - min) / buckets as f64) } else { (1, 0.0) };
+        
+        let mut counts = vec![0u32; count];
+        for revenue in revenues.values() {
+            let bucket = if width > 0.0 { ((revenue - min) / width) as usize } else { 0 };
+            // The highest day lands on the last bucket's upper edge
+            counts[bucket.min(count - 1)] += 1;
+        }
+        
+        for (bucket, days_in_bucket) in counts.into_iter().enumerate() {
+            names.push(resource.to_string());
+            bucket_numbers.push(bucket as u32 + 1);
+            lows.push(min + bucket as f64 * width);
+            highs.push(if bucket + 1 == count { max } else { min + (bucket + 1) as f64 * width });
+            days.push(days_in_bucket);
+        }
+    }
+    
+    Ok(DataFrame::new(vec![
+        Series::new("Resource_Name", names),
+        Series::new("Bucket", bucket_numbers),
+        Series::new("Revenue_Low", lows),
+        Series::new("Revenue_High", highs),
+        Series::new("Days", days),
+    ])?)
+}
+
+/// Each resource's `n` highest and `n` lowest total-revenue days, with the
+/// revenue by stream, so the days that made or lost the year (scarcity
+/// events, outages) can be looked at directly. Rows run best first, then
+/// worst first, per resource.
+pub fn best_worst_days(daily_revenues: &[BessRevenue], n: usize) -> Result<DataFrame> {
+    let mut by_resource: HashMap<&str, Vec<&BessRevenue>> = HashMap::new();
+    for revenue in daily_revenues {
+        by_resource.entry(revenue.resource_name.as_str()).or_default().push(revenue);
+    }
+    
+    let mut resources: Vec<&str> = by_resource.keys().copied().collect();
+    resources.sort();
+    
+    let mut rows: Vec<(&str, u32, &BessRevenue)> = Vec::new();
+    for resource in resources {
+        let days = by_resource.get_mut(resource).unwrap();
+        days.sort_by(|a, b| b.total_revenue.partial_cmp(&a.total_revenue).unwrap().then(a.date.cmp(&b.date)));
+        for (rank, day) in days.iter().take(n).enumerate() {
+            rows.push(("Best", rank as u32 + 1, *day));
+        }
+        for (rank, day) in days.iter().rev().take(n).enumerate() {
+            rows.push(("Worst", rank as u32 + 1, *day));
+        }
+    }
+    
+    Ok(DataFrame::new(vec![
+        Series::new("Resource_Name", rows.iter().map(|(_, _, d)| d.resource_name.clone()).collect::<Vec<_>>()),
+        Series::new("Rank_Type", rows.iter().map(|(kind, _, _)| *kind).collect::<Vec<_>>()),
+        Series::new("Rank", rows.iter().map(|(_, rank, _)| *rank).collect::<Vec<_>>()),
+        Series::new("Date", rows.iter().map(|(_, _, d)| d.date.format("%Y-%m-%d").to_string()).collect::<Vec<_>>()),
+        Series::new("Total_Revenue", rows.iter().map(|(_, _, d)| d.total_revenue).collect::<Vec<_>>()),
+        Series::new("DAM_Energy_Revenue", rows.iter().map(|(_, _, d)| d.dam_energy_revenue).collect::<Vec<_>>()),
+        Series::new("RT_Energy_Revenue", rows.iter().map(|(_, _, d)| d.rt_energy_revenue).collect::<Vec<_>>()),
+        Series::new("RegUp_Revenue", rows.iter().map(|(_, _, d)| d.reg_up_revenue).collect::<Vec<_>>()),
+        Series::new("RegDown_Revenue", rows.iter().map(|(_, _, d)| d.reg_down_revenue).collect::<Vec<_>>()),
+        Series::new("RRS_Revenue", rows.iter().map(|(_, _, d)| d.rrs_revenue).collect::<Vec<_>>()),
+        Series::new("ECRS_Revenue", rows.iter().map(|(_, _, d)| d.ecrs_revenue).collect::<Vec<_>>()),
+        Series::new("NonSpin_Revenue", rows.iter().map(|(_, _, d)| d.non_spin_revenue).collect::<Vec<_>>()),
+    ])?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn daily(resource: &str, date: NaiveDate, total_revenue: f64) -> BessRevenue {
+        BessRevenue {
+            resource_name: resource.to_string(),
+            date,
+            energy_revenue: total_revenue,
+            dam_energy_revenue: total_revenue,
+            rt_energy_revenue: 0.0,
+            reg_up_revenue: 0.0,
+            reg_down_revenue: 0.0,
+            rrs_revenue: 0.0,
+            ecrs_revenue: 0.0,
+            non_spin_revenue: 0.0,
+            total_revenue,
+            energy_cycles: 0.0,
+            soc_violations: 0,
+            as_failures: 0,
+            dam_energy: EnergySettlement { discharge_revenue: total_revenue, ..Default::default() },
+            rt_energy: EnergySettlement::default(),
+        }
+    }
+
+    #[test]
+    fn test_trailing_30d_equals_sum_of_prior_30_days() {
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        // 60 days of data with a gap on days 10-14
+        let revenues: Vec<BessRevenue> = (0..60)
+            .filter(|d| !(10..15).contains(d))
+            .map(|d| daily("BESS_A", start + chrono::Duration::days(d), (d + 1) as f64 * 100.0))
+            .collect();
+        let capacities = HashMap::from([("BESS_A".to_string(), 50.0)]);
+
+        let df = rolling_revenue(&revenues, &capacities, 30).unwrap();
+        assert_eq!(df.height(), 60);
+
+        let daily_values: Vec<f64> = df.column("Daily_Revenue").unwrap().f64().unwrap().into_no_null_iter().collect();
+        let trailing: Vec<f64> = df.column("Trailing_30d_Revenue").unwrap().f64().unwrap().into_no_null_iter().collect();
+        let per_mw: Vec<f64> = df.column("Trailing_30d_Revenue_Per_MW").unwrap().f64().unwrap().into_no_null_iter().collect();
+        let active: Vec<u32> = df.column("Active_Days").unwrap().u32().unwrap().into_no_null_iter().collect();
+
+        for i in 0..60_usize {
+            let expected: f64 = daily_values[i.saturating_sub(29)..=i].iter().sum();
+            assert!((trailing[i] - expected).abs() < 1e-9);
+            assert!((per_mw[i] - expected / 50.0).abs() < 1e-9);
+        }
+        assert_eq!(daily_values[12], 0.0);
+        assert_eq!(active[29], 25);
+        assert_eq!(active[59], 30);
+    }
+
+    #[test]
+    fn test_histogram_buckets_sum_to_active_days() {
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        // 28 ordinary days and two scarcity days far out in the tail
+        let mut revenues: Vec<BessRevenue> = (0..28)
+            .map(|d| daily("BESS_A", start + chrono::Duration::days(d), 1000.0 + d as f64 * 10.0))
+            .collect();
+        revenues.push(daily("BESS_A", start + chrono::Duration::days(28), 9000.0));
+        revenues.push(daily("BESS_A", start + chrono::Duration::days(29), 10000.0));
+        revenues.push(daily("BESS_B", start, 500.0));
+
+        let df = revenue_histogram(&revenues, 10).unwrap();
+        let names: Vec<&str> = df.column("Resource_Name").unwrap().utf8().unwrap().into_no_null_iter().collect();
+        let days: Vec<u32> = df.column("Days").unwrap().u32().unwrap().into_no_null_iter().collect();
+        let lows: Vec<f64> = df.column("Revenue_Low").unwrap().f64().unwrap().into_no_null_iter().collect();
+        let highs: Vec<f64> = df.column("Revenue_High").unwrap().f64().unwrap().into_no_null_iter().collect();
+
+        assert_eq!(df.height(), 11);
+        assert_eq!(days[..10].iter().sum::<u32>(), 30);
+        // $1,000-$1,900 a day fills the first bucket; the tail sits in the last two
+        assert_eq!(days[0], 28);
+        assert_eq!(&days[8..10], &[1, 1]);
+        assert_eq!(lows[0], 1000.0);
+        assert_eq!(highs[9], 10000.0);
+        assert_eq!((names[10], days[10], lows[10], highs[10]), ("BESS_B", 1, 500.0, 500.0));
+    }
+
+    #[test]
+    fn test_best_days_show_scarcity_event_and_its_streams() {
+        let start = NaiveDate::from_ymd_opt(2024, 8, 1).unwrap();
+        let mut revenues: Vec<BessRevenue> = (0..20)
+            .map(|d| daily("BESS_A", start + chrono::Duration::days(d), 1000.0 + d as f64))
+            .collect();
+        // Scarcity day earned mostly in RT, plus an RRS award
+        revenues[7].rt_energy_revenue = 40000.0;
+        revenues[7].rrs_revenue = 5000.0;
+        revenues[7].total_revenue = 46007.0;
+        // A day spent charging into low prices
+        revenues[12].total_revenue = -800.0;
+
+        let df = best_worst_days(&revenues, 5).unwrap();
+        assert_eq!(df.height(), 10);
+        let kinds: Vec<&str> = df.column("Rank_Type").unwrap().utf8().unwrap().into_no_null_iter().collect();
+        let dates: Vec<&str> = df.column("Date").unwrap().utf8().unwrap().into_no_null_iter().collect();
+        let totals: Vec<f64> = df.column("Total_Revenue").unwrap().f64().unwrap().into_no_null_iter().collect();
+        let rt: Vec<f64> = df.column("RT_Energy_Revenue").unwrap().f64().unwrap().into_no_null_iter().collect();
+        let rrs: Vec<f64> = df.column("RRS_Revenue").unwrap().f64().unwrap().into_no_null_iter().collect();
+
+        assert_eq!((kinds[0], dates[0], totals[0]), ("Best", "2024-08-08", 46007.0));
+        assert_eq!((rt[0], rrs[0]), (40000.0, 5000.0));
+        assert_eq!(totals[1], 1019.0);
+        assert_eq!((kinds[5], dates[5], totals[5]), ("Worst", "2024-08-13", -800.0));
+        assert_eq!(totals[6], 1000.0);
+    }
+
+    #[test]
+    fn test_daily_rollup_joins_streams_in_resource_date_order() {
+        let day = |d: u32| NaiveDate::from_ymd_opt(2024, 8, d).unwrap();
+        let key = |name: &str, d: u32| (name.to_string(), day(d));
+        let energy = |discharge: f64, charge: f64| EnergySettlement { discharge_revenue: discharge, charge_cost: charge, ..Default::default() };
+
+        let dam = HashMap::from([
+            (key("BESS_B", 1), energy(4000.0, -1000.0)),
+            (key("BESS_A", 2), energy(2500.5, -300.25)),
+        ]);
+        // BESS_A day 1 only settled in RT
+        let rt = HashMap::from([
+            (key("BESS_A", 1), energy(120.0, -80.0)),
+            (key("BESS_B", 1), energy(10.0, -5.0)),
+        ]);
+        // BESS_C only carried AS
+        let ancillary = HashMap::from([
+            (key("BESS_B", 1), HashMap::from([("RegUp".to_string(), 50.0), ("RRS".to_string(), 25.0)])),
+            (key("BESS_C", 3), HashMap::from([("ECRS".to_string(), 12.5)])),
+        ]);
+
+        let rollup = daily_rollup_frame(&dam, &rt, &ancillary, RevenueView::Net).unwrap();
+        let revenues = daily_revenues_from_frame(&rollup).unwrap();
+
+        let order: Vec<(&str, NaiveDate)> = revenues.iter().map(|r| (r.resource_name.as_str(), r.date)).collect();
+        assert_eq!(order, vec![("BESS_A", day(1)), ("BESS_A", day(2)), ("BESS_B", day(1)), ("BESS_C", day(3))]);
+
+        // Same arithmetic as summing the per-key map entries
+        let expected = |dam: EnergySettlement, rt: EnergySettlement, as_total: f64| {
+            let energy = dam.net() + rt.net();
+            (dam.net(), rt.net(), energy, energy + as_total)
+        };
+        let actual: Vec<(f64, f64, f64, f64)> = revenues.iter()
+            .map(|r| (r.dam_energy_revenue, r.rt_energy_revenue, r.energy_revenue, r.total_revenue))
+            .collect();
+        assert_eq!(actual, vec![
+            expected(EnergySettlement::default(), energy(120.0, -80.0), 0.0),
+            expected(energy(2500.5, -300.25), EnergySettlement::default(), 0.0),
+            expected(energy(4000.0, -1000.0), energy(10.0, -5.0), 50.0 + 25.0),
+            expected(EnergySettlement::default(), EnergySettlement::default(), 12.5),
+        ]);
+        assert_eq!(revenues[2].reg_up_revenue, 50.0);
+        assert_eq!(revenues[2].dam_energy, energy(4000.0, -1000.0));
+        assert_eq!(revenues[3].ecrs_revenue, 12.5);
+    }
+
+    #[test]
+    fn test_short_history_resource_is_not_annualized() {
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let mut revenues: Vec<BessRevenue> = (0..60)
+            .map(|d| daily("BESS_A", start + chrono::Duration::days(d), 1000.0))
+            .collect();
+        // Commissioned late: 3 strong days would extrapolate to $3.65M/year
+        revenues.extend((57..60).map(|d| daily("BESS_NEW", start + chrono::Duration::days(d), 10_000.0)));
+
+        let (annualizable, insufficient) = split_by_coverage(&revenues, DEFAULT_MIN_DAYS_FOR_ANNUALIZATION);
+
+        assert_eq!(annualizable.len(), 1);
+        assert_eq!(annualizable[0].resource_name, "BESS_A");
+        assert!((annualizable[0].annualized_revenue() - 365_000.0).abs() < 1e-6);
+        assert_eq!(insufficient, vec![ResourceCoverage {
+            resource_name: "BESS_NEW".to_string(),
+            days: 3,
+            total_revenue: 30_000.0,
+        }]);
+    }
+
+    #[test]
+    fn test_gross_basis_splits_discharge_revenue_and_charge_cost() {
+        let mut dam = EnergySettlement::default();
+        dam.record(-50.0, -50.0 * 20.0, DAM_AWARD_INTERVAL);
+        dam.record(50.0, 50.0 * 80.0, DAM_AWARD_INTERVAL);
+        dam.record(0.0, 0.0, DAM_AWARD_INTERVAL);
+        let mut rt = EnergySettlement::default();
+        rt.record(10.0, 10.0 * 0.25 * 100.0, RT_PRICE_INTERVAL);
+
+        // Net preserves the single energy figure
+        assert_eq!(dam.net(), 3000.0);
+
+        let columns: Vec<Series> = settlement_columns("DAM", &[dam], "").into_iter()
+            .chain(settlement_columns("RT", &[rt], ""))
+            .collect();
+        let values: Vec<(&str, f64)> = columns.iter()
+            .map(|c| (c.name(), c.f64().unwrap().get(0).unwrap()))
+            .collect();
+        assert_eq!(values, vec![
+            ("DAM_Discharge_Revenue", 4000.0),
+            ("DAM_Charge_Cost", -1000.0),
+            ("RT_Discharge_Revenue", 250.0),
+            ("RT_Charge_Cost", 0.0),
+        ]);
+    }
+
+    #[test]
+    fn test_rt_efficiency_loss_reconciles_with_charge_cost() {
+        // 40 MWh charged at $25 and 34 MWh (85%) discharged at $60, as hourly blocks
+        let mut rt = EnergySettlement::default();
+        rt.record(-40.0, -40.0 * 25.0, IntervalLength::Hourly);
+        rt.record(34.0, 34.0 * 60.0, IntervalLength::Hourly);
+
+        let loss = rt.efficiency_loss();
+        // 6 MWh lost at the $25 charge price
+        assert!((loss - -150.0).abs() < 1e-9);
+        // Cost of the 34 MWh that came back out plus the loss is the charge cost
+        assert!((-34.0 * 25.0 + loss - rt.charge_cost).abs() < 1e-9);
+        assert!((rt.discharge_revenue + rt.charge_cost - rt.net()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_efficiency_loss_follows_metered_energy_across_days() {
+        // Day 1 charges 40 MWh at $25 but discharges only 10; the other 26
+        // come out on day 2, so the battery ran at 90% round trip, not 85%
+        let mut day1 = EnergySettlement::default();
+        day1.record(-40.0, -40.0 * 25.0, IntervalLength::Hourly);
+        day1.record(10.0, 10.0 * 60.0, IntervalLength::Hourly);
+        let mut day2 = EnergySettlement::default();
+        // 104 MW for 15 minutes is 26 MWh
+        day2.record(104.0, 26.0 * 70.0, RT_PRICE_INTERVAL);
+        assert_eq!(day2.discharged_mwh, 26.0);
+
+        // Day 2 discharged stored energy: no loss rather than a negative one
+        assert_eq!(day2.efficiency_loss(), 0.0);
+
+        // Over both days, 4 MWh lost at the $25 charge price
+        let key = |d: u32| ("BESS_A".to_string(), NaiveDate::from_ymd_opt(2024, 8, d).unwrap());
+        let rt = HashMap::from([(key(1), day1), (key(2), day2)]);
+        let revenues = daily_revenues_from_frame(
+            &daily_rollup_frame(&HashMap::new(), &rt, &HashMap::new(), RevenueView::Net).unwrap()).unwrap();
+        let year = EnergySettlement {
+            charge_cost: revenues.iter().map(|r| r.rt_energy.charge_cost).sum(),
+            charged_mwh: revenues.iter().map(|r| r.rt_energy.charged_mwh).sum(),
+            discharged_mwh: revenues.iter().map(|r| r.rt_energy.discharged_mwh).sum(),
+            ..Default::default()
+        };
+        assert!((year.efficiency_loss() - -100.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_gross_discharge_view_leaves_out_charge_cost() {
+        let key = ("BESS_A".to_string(), NaiveDate::from_ymd_opt(2024, 8, 1).unwrap());
+        let dam = HashMap::from([(key.clone(), EnergySettlement { discharge_revenue: 4000.0, charge_cost: -1000.0, ..Default::default() })]);
+        let rt = HashMap::from([(key.clone(), EnergySettlement { discharge_revenue: 120.0, charge_cost: -80.0, ..Default::default() })]);
+        let ancillary = HashMap::from([(key, HashMap::from([("RegUp".to_string(), 50.0)]))]);
+
+        let net = &daily_revenues_from_frame(&daily_rollup_frame(&dam, &rt, &ancillary, RevenueView::Net).unwrap()).unwrap()[0];
+        let gross = &daily_revenues_from_frame(&daily_rollup_frame(&dam, &rt, &ancillary, RevenueView::GrossDischarge).unwrap()).unwrap()[0];
+
+        assert_eq!((net.dam_energy_revenue, net.rt_energy_revenue, net.total_revenue), (3000.0, 40.0, 3090.0));
+        assert_eq!((gross.dam_energy_revenue, gross.rt_energy_revenue), (4000.0, 120.0));
+        assert_eq!(gross.energy_revenue, 4120.0);
+        assert_eq!(gross.total_revenue, 4170.0);
+        // The settlements themselves are the same under either view
+        assert_eq!(gross.dam_energy, net.dam_energy);
+
+        let args = |a: &[&str]| a.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert_eq!(RevenueView::from_args(&args(&["bin"])).unwrap(), RevenueView::Net);
+        assert_eq!(RevenueView::from_args(&args(&["bin", "--revenue-view", "gross-discharge"])).unwrap(), RevenueView::GrossDischarge);
+        assert!(RevenueView::from_args(&args(&["bin", "--revenue-view=gross"])).is_err());
+    }
+
+    #[test]
+    fn test_revenue_basis_from_args() {
+        let args = |a: &[&str]| a.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+
+        assert_eq!(RevenueBasis::from_args(&args(&["bin", "--bess-full-disclosure"])).unwrap(), RevenueBasis::Net);
+        assert_eq!(RevenueBasis::from_args(&args(&["bin", "--revenue-basis", "gross"])).unwrap(), RevenueBasis::Gross);
+        assert_eq!(RevenueBasis::from_args(&args(&["bin", "--revenue-basis=net"])).unwrap(), RevenueBasis::Net);
+        assert!(RevenueBasis::from_args(&args(&["bin", "--revenue-basis", "both"])).is_err());
+    }
+
+    #[test]
+    fn test_award_sign_conventions() {
+        // ERCOT convention: +10 MW sold at $50, -10 MW bought at $20
+        let mut settlement = EnergySettlement::default();
+        for (award_mw, price) in [(10.0, 50.0), (-10.0, 20.0)] {
+            let output_mw = AwardSign::GenPositive.output_mw(award_mw);
+            settlement.record(output_mw, output_mw * price, DAM_AWARD_INTERVAL);
+        }
+        assert_eq!(settlement, EnergySettlement {
+            discharge_revenue: 500.0, charge_cost: -200.0, discharged_mwh: 10.0, charged_mwh: 10.0,
+        });
+
+        // The same awards from a dataset that reports charging as positive
+        let mut flipped = EnergySettlement::default();
+        for (award_mw, price) in [(-10.0, 50.0), (10.0, 20.0)] {
+            let output_mw = AwardSign::LoadPositive.output_mw(award_mw);
+            flipped.record(output_mw, output_mw * price, DAM_AWARD_INTERVAL);
+        }
+        assert_eq!(flipped, settlement);
+
+        let args = |a: &[&str]| a.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert_eq!(AwardSign::from_args(&args(&["bin"])).unwrap(), AwardSign::GenPositive);
+        assert_eq!(AwardSign::from_args(&args(&["bin", "--award-sign", "load-positive"])).unwrap(), AwardSign::LoadPositive);
+        assert!(AwardSign::from_args(&args(&["bin", "--award-sign=load"])).is_err());
+    }
+
+    #[test]
+    fn test_dam_award_settles_one_hour_per_row_and_flags_sub_hourly_rows() {
+        // HE18 discharge and HE3 charge, then HE18 again as a 15-minute row
+        let df = df! {
+            "Delivery Date" => &["08/01/2024", "08/01/2024", "08/01/2024"],
+            "Hour Ending" => &[18i64, 3, 18],
+            "Resource Name" => &["BATCAVE_BES1", "BATCAVE_BES1", "BATCAVE_BES1"],
+            "Awarded Quantity" => &[10.0, -10.0, 10.0],
+            "Energy Settlement Point Price" => &[50.0, 20.0, 50.0],
+        }
+        .unwrap();
+
+        let mut dam_energy = HashMap::new();
+        let mut award_totals = HashMap::new();
+        let mut settled_hours = SettledAwardHours::default();
+        settle_dam_awards(&df, AwardSign::GenPositive, |_| true, &mut dam_energy, &mut award_totals, &mut settled_hours).unwrap();
+
+        // award × price × 1 hour
+        let date = NaiveDate::from_ymd_opt(2024, 8, 1).unwrap();
+        assert_eq!(dam_energy[&("BATCAVE_BES1".to_string(), date)],
+                   EnergySettlement {
+                       discharge_revenue: 10.0 * 50.0 * 1.0, charge_cost: -10.0 * 20.0 * 1.0,
+                       discharged_mwh: 10.0, charged_mwh: 10.0,
+                   });
+        assert_eq!(award_totals["BATCAVE_BES1"], AwardTotals { charge_mwh: 10.0, discharge_mwh: 10.0 });
+        assert_eq!(settled_hours.duplicates(), 1);
+    }
+
+    #[test]
+    fn test_award_direction_check_flags_flipped_convention() {
+        let totals = |entries: &[(&str, f64, f64)]| -> HashMap<String, AwardTotals> {
+            entries.iter()
+                .map(|(name, charge, discharge)| (name.to_string(), AwardTotals { charge_mwh: *charge, discharge_mwh: *discharge }))
+                .collect()
+        };
+
+        let normal = AwardDirectionCheck::new(&totals(&[("A", 100.0, 85.0), ("B", 50.0, 40.0), ("C", 0.0, 10.0)]));
+        assert_eq!(normal.both, 2);
+        assert_eq!(normal.only_discharge, vec!["C".to_string()]);
+        assert!(!normal.likely_flipped());
+
+        // Load-positive data read as gen-positive: charging looks like every award
+        let flipped = AwardDirectionCheck::new(&totals(&[("A", 185.0, 0.0), ("B", 90.0, 0.0), ("C", 60.0, 5.0)]));
+        assert_eq!(flipped.only_charge, vec!["A".to_string(), "B".to_string()]);
+        assert!(flipped.likely_flipped());
+    }
+}
@@ -2,8 +2,30 @@ use anyhow::Result;
 use chrono::NaiveDate;
 use plotters::prelude::*;
 use polars::prelude::*;
-use std::collections::HashMap;
-use std::path::PathBuf;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+use crate::error::require_column;
+use crate::output_prefix::OutputPrefix;
+
+/// Daily rollup columns that add up to `Total_Revenue`, stacked in this order
+const TIMESERIES_STREAMS: [&str; 7] = [
+    "DAM_Energy_Revenue",
+    "RT_Energy_Revenue",
+    "RegUp_Revenue",
+    "RegDown_Revenue",
+    "RRS_Revenue",
+    "ECRS_Revenue",
+    "NonSpin_Revenue",
+];
+
+/// One (date, stream, revenue) row of a resource's long-format time series
+#[derive(Debug, Clone, PartialEq)]
+pub struct StreamRevenue {
+    pub date: String,
+    pub stream: String,
+    pub revenue: f64,
+}
 
 pub struct BessVisualizer {
     data_dir: PathBuf,
@@ -11,9 +33,9 @@ pub struct BessVisualizer {
 }
 
 impl BessVisualizer {
-    pub fn new() -> Result<Self> {
-        let data_dir = PathBuf::from("bess_analysis");
-        let output_dir = PathBuf::from("bess_analysis/charts");
+    pub fn new(output_prefix: &OutputPrefix) -> Result<Self> {
+        let data_dir = output_prefix.output_dir("bess_analysis");
+        let output_dir = output_prefix.output_dir("bess_analysis/charts");
         std::fs::create_dir_all(&output_dir)?;
         
         Ok(Self {
@@ -454,8 +476,101 @@ impl BessVisualizer {
     }
 }
 
-pub fn generate_bess_visualizations() -> Result<()> {
-    let visualizer = BessVisualizer::new()?;
+/// Reshape the daily rollup into one long-format series per resource: a row
+/// for every date × revenue stream, sorted by date. Missing values count as zero.
+pub fn revenue_timeseries(daily: &DataFrame) -> Result<BTreeMap<String, Vec<StreamRevenue>>> {
+    let names = require_column(daily, "Resource_Name")?.cast(&DataType::Utf8)?;
+    let dates = require_column(daily, "Date")?.cast(&DataType::Utf8)?;
+    let streams = TIMESERIES_STREAMS
+        .iter()
+        .map(|column| Ok(require_column(daily, column)?.cast(&DataType::Float64)?))
+        .collect::<Result<Vec<Series>>>()?;
+
+    let names = names.utf8()?;
+    let dates = dates.utf8()?;
+    let mut series: BTreeMap<String, Vec<StreamRevenue>> = BTreeMap::new();
+    for i in 0..daily.height() {
+        let (Some(name), Some(date)) = (names.get(i), dates.get(i)) else { continue };
+        let rows = series.entry(name.to_string()).or_default();
+        for (column, values) in TIMESERIES_STREAMS.iter().zip(&streams) {
+            rows.push(StreamRevenue {
+                date: date.to_string(),
+                stream: column.trim_end_matches("_Revenue").to_string(),
+                revenue: values.f64()?.get(i).unwrap_or(0.0),
+            });
+        }
+    }
+
+    // Stable sort keeps the stream order within each date
+    for rows in series.values_mut() {
+        rows.sort_by(|a, b| a.date.cmp(&b.date));
+    }
+    Ok(series)
+}
+
+/// Write `{output_dir}/{resource}_revenue_timeseries.csv` (Date, Stream, Revenue)
+/// for every resource in the daily rollup, for stacked-area charts in any plotting tool
+pub fn export_revenue_timeseries(daily: &DataFrame, output_dir: &Path) -> Result<Vec<PathBuf>> {
+    std::fs::create_dir_all(output_dir)?;
+
+    let mut written = Vec::new();
+    for (resource, rows) in revenue_timeseries(daily)? {
+        let mut df = DataFrame::new(vec![
+            Series::new("Date", rows.iter().map(|r| r.date.clone()).collect::<Vec<_>>()),
+            Series::new("Stream", rows.iter().map(|r| r.stream.clone()).collect::<Vec<_>>()),
+            Series::new("Revenue", rows.iter().map(|r| r.revenue).collect::<Vec<_>>()),
+        ])?;
+
+        let output_path = output_dir.join(format!("{}_revenue_timeseries.csv", resource));
+        CsvWriter::new(std::fs::File::create(&output_path)?)
+            .finish(&mut df)?;
+        written.push(output_path);
+    }
+
+    println!("✅ Saved revenue time series for {} resources to: {}", written.len(), output_dir.display());
+    Ok(written)
+}
+
+pub fn generate_bess_visualizations(output_prefix: &OutputPrefix) -> Result<()> {
+    let visualizer = BessVisualizer::new(output_prefix)?;
     visualizer.generate_all_visualizations()?;
     Ok(())
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_revenue_timeseries_reconciles_to_daily_totals() {
+        let daily = df! {
+            "Resource_Name" => &["BESS_A", "BESS_B", "BESS_A"],
+            "Date" => &["2024-01-02", "2024-01-01", "2024-01-01"],
+            "DAM_Energy_Revenue" => &[100.0, 50.0, 80.0],
+            "RT_Energy_Revenue" => &[-20.0, 5.0, 10.0],
+            "RegUp_Revenue" => &[1.0, 0.0, 2.0],
+            "RegDown_Revenue" => &[0.0, 3.0, 0.0],
+            "RRS_Revenue" => &[4.0, 0.0, 0.0],
+            "ECRS_Revenue" => &[0.0, 0.0, 6.0],
+            "NonSpin_Revenue" => &[0.5, 0.0, 0.0],
+            "Total_Revenue" => &[85.5, 58.0, 98.0],
+        }
+        .unwrap();
+
+        let series = revenue_timeseries(&daily).unwrap();
+        let bess_a = &series["BESS_A"];
+        assert_eq!(bess_a.len(), 2 * TIMESERIES_STREAMS.len());
+        assert_eq!(bess_a[0], StreamRevenue { date: "2024-01-01".into(), stream: "DAM_Energy".into(), revenue: 80.0 });
+        assert_eq!(bess_a.last().unwrap().stream, "NonSpin");
+
+        for (resource, date, total) in [("BESS_A", "2024-01-01", 98.0), ("BESS_A", "2024-01-02", 85.5), ("BESS_B", "2024-01-01", 58.0)] {
+            let sum: f64 = series[resource].iter().filter(|r| r.date == date).map(|r| r.revenue).sum();
+            assert!((sum - total).abs() < 1e-9, "{} {}", resource, date);
+        }
+
+        let dir = tempfile::tempdir().unwrap();
+        let written = export_revenue_timeseries(&daily, dir.path()).unwrap();
+        assert_eq!(written.len(), 2);
+        assert!(dir.path().join("BESS_B_revenue_timeseries.csv").exists());
+    }
+}
@@ -4,8 +4,13 @@ use polars::prelude::*;
 use std::collections::HashMap;
 use std::path::PathBuf;
 
+use crate::deflator::Deflator;
+use crate::master_list::load_master_list;
+use crate::output_prefix::OutputPrefix;
+
 pub struct BessYearlyAnalysis {
     output_dir: PathBuf,
+    deflator: Option<Deflator>,
 }
 
 #[derive(Debug, Clone)]
@@ -28,7 +33,13 @@ pub struct YearlyRevenue {
 
 impl BessYearlyAnalysis {
     pub fn new(output_dir: PathBuf) -> Self {
-        Self { output_dir }
+        Self { output_dir, deflator: None }
+    }
+    
+    /// Also report revenues in constant base-year dollars
+    pub fn with_deflator(mut self, deflator: Deflator) -> Self {
+        self.deflator = Some(deflator);
+        self
     }
     
     pub fn generate_yearly_analysis(&self) -> Result<()> {
@@ -60,24 +71,15 @@ impl BessYearlyAnalysis {
     }
     
     fn load_resource_info(&self) -> Result<HashMap<String, f64>> {
-        let path = self.output_dir.join("bess_resources_master_list.csv");
-        let df = CsvReader::new(std::fs::File::open(&path)?)
-            .has_header(true)
-            .finish()?;
-            
-        let mut capacities = HashMap::new();
-        if let (Ok(names), Ok(caps)) = (
-            df.column("Resource_Name")?.utf8(),
-            df.column("Max_Capacity_MW")?.f64()
-        ) {
-            for i in 0..df.height() {
-                if let (Some(name), Some(cap)) = (names.get(i), caps.get(i)) {
-                    capacities.insert(name.to_string(), cap);
-                }
-            }
+        // A prefixed run may share the master list of the default run
+        let mut path = self.output_dir.join("bess_resources_master_list.csv");
+        if !path.exists() {
+            path = PathBuf::from("bess_analysis/bess_resources_master_list.csv");
         }
-        
-        Ok(capacities)
+        Ok(load_master_list(&path)?
+            .into_iter()
+            .map(|r| (r.name, r.capacity_mw))
+            .collect())
     }
     
     fn calculate_yearly_revenues(&self, daily_revenues: &DataFrame, resource_info: &HashMap<String, f64>) 
@@ -241,16 +243,31 @@ impl BessYearlyAnalysis {
         // Convert Vec<&i32> to Vec<i32>
         let years_owned: Vec<i32> = years.into_iter().map(|&y| y).collect();
         
-        let df = DataFrame::new(vec![
-            Series::new("Year", years_owned),
+        let mut columns = vec![
+            Series::new("Year", &years_owned),
             Series::new("Resource_Count", resource_count),
             Series::new("Total_Capacity_MW", total_capacity),
-            Series::new("Total_Revenue", total_revenues),
+            Series::new("Total_Revenue", &total_revenues),
+        ];
+        if let Some(deflator) = &self.deflator {
+            columns.push(deflator.real_column("Total_Revenue", &total_revenues, &years_owned));
+        }
+        columns.extend([
             Series::new("Energy_Revenue", energy_revenues),
             Series::new("AS_Revenue", as_revenues),
             Series::new("Energy_Revenue_Pct", energy_percentages),
             Series::new("Avg_Revenue_Per_MW", avg_revenue_per_mw),
-        ])?;
+        ]);
+        let df = DataFrame::new(columns)?;
+        
+        if let Some(deflator) = &self.deflator {
+            println!("\n💵 Total revenue in constant {} dollars:", deflator.base_year());
+            for (year, total) in years_owned.iter().zip(&total_revenues) {
+                if let Some(real) = deflator.to_real(*total, *year) {
+                    println!("{} | ${:>8.2}M", year, real / 1_000_000.0);
+                }
+            }
+        }
         
         CsvWriter::new(std::fs::File::create(&output_path)?)
             .finish(&mut df.clone())?;
@@ -317,9 +334,9 @@ impl BessYearlyAnalysis {
             days_ops.push(entry.12);
         }
         
-        let df = DataFrame::new(vec![
+        let mut columns = vec![
             Series::new("Resource_Name", resources),
-            Series::new("Year", years),
+            Series::new("Year", &years),
             Series::new("Capacity_MW", capacities),
             Series::new("Energy_Revenue", energy_revs),
             Series::new("DAM_Energy_Revenue", dam_revs),
@@ -329,9 +346,13 @@ impl BessYearlyAnalysis {
             Series::new("RRS_Revenue", rrs_revs),
             Series::new("ECRS_Revenue", ecrs_revs),
             Series::new("NonSpin_Revenue", nonspin_revs),
-            Series::new("Total_Revenue", total_revs),
-            Series::new("Days_Operating", days_ops),
-        ])?;
+            Series::new("Total_Revenue", &total_revs),
+        ];
+        if let Some(deflator) = &self.deflator {
+            columns.push(deflator.real_column("Total_Revenue", &total_revs, &years));
+        }
+        columns.push(Series::new("Days_Operating", days_ops));
+        let df = DataFrame::new(columns)?;
         
         CsvWriter::new(std::fs::File::create(&output_path)?)
             .finish(&mut df.clone())?;
@@ -418,8 +439,11 @@ impl BessYearlyAnalysis {
     }
 }
 
-pub fn generate_yearly_analysis() -> Result<()> {
-    let output_dir = PathBuf::from("bess_analysis");
-    let analyzer = BessYearlyAnalysis::new(output_dir);
+pub fn generate_yearly_analysis(deflator: Option<Deflator>, output_prefix: &OutputPrefix) -> Result<()> {
+    let output_dir = output_prefix.output_dir("bess_analysis");
+    let mut analyzer = BessYearlyAnalysis::new(output_dir);
+    if let Some(deflator) = deflator {
+        analyzer = analyzer.with_deflator(deflator);
+    }
     analyzer.generate_yearly_analysis()
 }
\ No newline at end of file
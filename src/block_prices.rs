@@ -0,0 +1,326 @@
+use anyhow::Result;
+use log::{info, warn};
+use chrono::{Datelike, NaiveDate, Weekday};
+use glob::glob;
+use polars::prelude::*;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::error::{require_column, PipelineError};
+use crate::holidays::is_nerc_holiday;
+use crate::output_prefix::OutputPrefix;
+
+/// Standard block prices for one settlement point in one month
+#[derive(Debug, Clone)]
+pub struct BlockPrice {
+    pub market: String,
+    pub settlement_point: String,
+    pub year: i32,
+    pub month: u32,
+    pub on_peak_avg: Option<f64>,   // 7x16 (ERCOT on-peak)
+    pub off_peak_avg: Option<f64>,
+    pub atc_avg: Option<f64>,       // 7x24 around-the-clock
+    pub on_peak_hours: usize,
+    pub off_peak_hours: usize,
+}
+
+#[derive(Default)]
+struct BlockAccumulator {
+    on_peak_sum: f64,
+    on_peak_count: usize,
+    off_peak_sum: f64,
+    off_peak_count: usize,
+}
+
+impl BlockAccumulator {
+    fn add(&mut self, price: f64, on_peak: bool) {
+        if on_peak {
+            self.on_peak_sum += price;
+            self.on_peak_count += 1;
+        } else {
+            self.off_peak_sum += price;
+            self.off_peak_count += 1;
+        }
+    }
+}
+
+pub struct BlockPriceCalculator {
+    annual_output_dir: PathBuf,
+    output_dir: PathBuf,
+}
+
+impl BlockPriceCalculator {
+    pub fn new(annual_output_dir: PathBuf, output_dir: PathBuf) -> Self {
+        Self { annual_output_dir, output_dir }
+    }
+
+    pub fn calculate_all_block_prices(&self) -> Result<()> {
+        info!("ERCOT Block Price Calculator (7x16 / off-peak / 7x24)");
+
+        std::fs::create_dir_all(&self.output_dir)?;
+
+        let mut block_prices = Vec::new();
+
+        let dam_dir = self.annual_output_dir.join("DAM_Settlement_Point_Prices");
+        for file in Self::find_parquet_files(&dam_dir)? {
+            info!("DAM: {}", file.display());
+            let df = ParquetReader::new(std::fs::File::open(&file)?).finish()?;
+            block_prices.extend(self.calculate_dam_block_prices(&df)?);
+        }
+
+        let rt_dir = self.annual_output_dir
+            .join("Settlement_Point_Prices_at_Resource_Nodes__Hubs_and_Load_Zones");
+        for file in Self::find_parquet_files(&rt_dir)? {
+            info!("RT: {}", file.display());
+            let df = ParquetReader::new(std::fs::File::open(&file)?).finish()?;
+            block_prices.extend(self.calculate_rt_block_prices(&df)?);
+        }
+
+        if block_prices.is_empty() {
+            warn!("No DAM or RT price files found in {}", self.annual_output_dir.display());
+            return Ok(());
+        }
+
+        block_prices.sort_by(|a, b| {
+            (&a.market, &a.settlement_point, a.year, a.month)
+                .cmp(&(&b.market, &b.settlement_point, b.year, b.month))
+        });
+
+        self.save_block_prices(&block_prices)?;
+
+        Ok(())
+    }
+
+    fn find_parquet_files(dir: &Path) -> Result<Vec<PathBuf>> {
+        if !dir.exists() {
+            warn!("Directory not found: {}", dir.display());
+            return Ok(Vec::new());
+        }
+
+        let pattern = dir.join("*.parquet");
+        let mut files: Vec<PathBuf> = glob(pattern.to_str().unwrap())?
+            .filter_map(Result::ok)
+            .collect();
+        files.sort();
+        Ok(files)
+    }
+
+    /// DAM prices are hourly: DeliveryDate, HourEnding ("01:00".."24:00"), SettlementPoint, SettlementPointPrice
+    fn calculate_dam_block_prices(&self, df: &DataFrame) -> Result<Vec<BlockPrice>> {
+        let dates = require_column(df, "DeliveryDate")?.cast(&DataType::Utf8)?;
+        let hours = require_column(df, "HourEnding")?.cast(&DataType::Utf8)?;
+        let sps = require_column(df, "SettlementPoint")?.cast(&DataType::Utf8)?;
+        let prices = require_column(df, "SettlementPointPrice")?.cast(&DataType::Float64)?;
+
+        let dates = dates.utf8()?;
+        let hours = hours.utf8()?;
+        let sps = sps.utf8()?;
+        let prices = prices.f64()?;
+
+        let mut accumulators: HashMap<(String, i32, u32), BlockAccumulator> = HashMap::new();
+
+        for i in 0..df.height() {
+            if let (Some(date_str), Some(hour_str), Some(sp), Some(price)) =
+                (dates.get(i), hours.get(i), sps.get(i), prices.get(i)) {
+
+                let date = match parse_delivery_date(date_str) {
+                    Ok(d) => d,
+                    Err(_) => continue,
+                };
+                let hour_ending = match hour_str.split(':').next().and_then(|h| h.trim().parse::<u32>().ok()) {
+                    Some(h) => h,
+                    None => continue,
+                };
+
+                accumulators
+                    .entry((sp.to_string(), date.year(), date.month()))
+                    .or_default()
+                    .add(price, is_on_peak(date, hour_ending));
+            }
+        }
+
+        Ok(Self::finalize("DAM", accumulators))
+    }
+
+    /// RT prices are 15-minute: DeliveryDate, DeliveryHour (hour ending 1-24), DeliveryInterval, SettlementPointName, SettlementPointPrice
+    fn calculate_rt_block_prices(&self, df: &DataFrame) -> Result<Vec<BlockPrice>> {
+        let dates = require_column(df, "DeliveryDate")?.cast(&DataType::Utf8)?;
+        let hours = require_column(df, "DeliveryHour")?.cast(&DataType::Int64)?;
+        let sps = require_column(df, "SettlementPointName")?.cast(&DataType::Utf8)?;
+        let prices = require_column(df, "SettlementPointPrice")?.cast(&DataType::Float64)?;
+
+        let dates = dates.utf8()?;
+        let hours = hours.i64()?;
+        let sps = sps.utf8()?;
+        let prices = prices.f64()?;
+
+        let mut accumulators: HashMap<(String, i32, u32), BlockAccumulator> = HashMap::new();
+
+        for i in 0..df.height() {
+            if let (Some(date_str), Some(hour_ending), Some(sp), Some(price)) =
+                (dates.get(i), hours.get(i), sps.get(i), prices.get(i)) {
+
+                let date = match parse_delivery_date(date_str) {
+                    Ok(d) => d,
+                    Err(_) => continue,
+                };
+
+                accumulators
+                    .entry((sp.to_string(), date.year(), date.month()))
+                    .or_default()
+                    .add(price, is_on_peak(date, hour_ending as u32));
+            }
+        }
+
+        Ok(Self::finalize("RT", accumulators))
+    }
+
+    fn finalize(market: &str, accumulators: HashMap<(String, i32, u32), BlockAccumulator>) -> Vec<BlockPrice> {
+        accumulators
+            .into_iter()
+            .map(|((settlement_point, year, month), acc)| {
+                let total_count = acc.on_peak_count + acc.off_peak_count;
+                BlockPrice {
+                    market: market.to_string(),
+                    settlement_point,
+                    year,
+                    month,
+                    on_peak_avg: average(acc.on_peak_sum, acc.on_peak_count),
+                    off_peak_avg: average(acc.off_peak_sum, acc.off_peak_count),
+                    atc_avg: average(acc.on_peak_sum + acc.off_peak_sum, total_count),
+                    on_peak_hours: acc.on_peak_count,
+                    off_peak_hours: acc.off_peak_count,
+                }
+            })
+            .collect()
+    }
+
+    fn save_block_prices(&self, block_prices: &[BlockPrice]) -> Result<()> {
+        let df = DataFrame::new(vec![
+            Series::new("Market", block_prices.iter().map(|b| b.market.clone()).collect::<Vec<_>>()),
+            Series::new("SettlementPoint", block_prices.iter().map(|b| b.settlement_point.clone()).collect::<Vec<_>>()),
+            Series::new("Year", block_prices.iter().map(|b| b.year).collect::<Vec<_>>()),
+            Series::new("Month", block_prices.iter().map(|b| b.month).collect::<Vec<_>>()),
+            Series::new("OnPeak_7x16", block_prices.iter().map(|b| b.on_peak_avg).collect::<Vec<_>>()),
+            Series::new("OffPeak", block_prices.iter().map(|b| b.off_peak_avg).collect::<Vec<_>>()),
+            Series::new("ATC_7x24", block_prices.iter().map(|b| b.atc_avg).collect::<Vec<_>>()),
+            Series::new("OnPeak_Intervals", block_prices.iter().map(|b| b.on_peak_hours as u64).collect::<Vec<_>>()),
+            Series::new("OffPeak_Intervals", block_prices.iter().map(|b| b.off_peak_hours as u64).collect::<Vec<_>>()),
+        ])?;
+
+        let csv_path = self.output_dir.join("block_prices_monthly.csv");
+        CsvWriter::new(std::fs::File::create(&csv_path)?)
+            .finish(&mut df.clone())?;
+
+        println!("\n✅ Saved {} monthly block prices to {}", block_prices.len(), csv_path.display());
+
+        Ok(())
+    }
+}
+
+fn average(sum: f64, count: usize) -> Option<f64> {
+    if count > 0 {
+        Some(sum / count as f64)
+    } else {
+        None
+    }
+}
+
+fn parse_delivery_date(date_str: &str) -> Result<NaiveDate, PipelineError> {
+    NaiveDate::parse_from_str(date_str, "%m/%d/%Y")
+        .or_else(|_| NaiveDate::parse_from_str(date_str, "%Y-%m-%d"))
+        .map_err(|_| PipelineError::UnparseableDate {
+            value: date_str.to_string(),
+        })
+}
+
+/// ERCOT on-peak: HE 7-22, Monday through Saturday, excluding NERC holidays
+pub fn is_on_peak(date: NaiveDate, hour_ending: u32) -> bool {
+    (7..=22).contains(&hour_ending)
+        && date.weekday() != Weekday::Sun
+        && !is_nerc_holiday(date)
+}
+
+pub fn calculate_block_prices(output_prefix: &OutputPrefix) -> Result<()> {
+    let annual_output_dir = output_prefix.input_path("annual_output");
+    let output_dir = output_prefix.output_dir("block_prices");
+
+    let calculator = BlockPriceCalculator::new(annual_output_dir, output_dir);
+    calculator.calculate_all_block_prices()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn calculator() -> BlockPriceCalculator {
+        BlockPriceCalculator::new(PathBuf::new(), PathBuf::new())
+    }
+
+    #[test]
+    fn test_on_peak_is_he7_through_he22_monday_to_saturday() {
+        let wednesday = NaiveDate::from_ymd_opt(2024, 7, 10).unwrap();
+        assert!(!is_on_peak(wednesday, 6));
+        assert!(is_on_peak(wednesday, 7));
+        assert!(is_on_peak(wednesday, 22));
+        assert!(!is_on_peak(wednesday, 23));
+        assert!(!is_on_peak(wednesday, 24));
+
+        assert!(is_on_peak(NaiveDate::from_ymd_opt(2024, 7, 13).unwrap(), 12));
+        assert!(!is_on_peak(NaiveDate::from_ymd_opt(2024, 7, 14).unwrap(), 12));
+        // Independence Day
+        assert!(!is_on_peak(NaiveDate::from_ymd_opt(2024, 7, 4).unwrap(), 12));
+    }
+
+    #[test]
+    fn test_dam_blocks_include_he24_and_split_at_peak_boundaries() {
+        let hours: Vec<String> = (1..=24).map(|he| format!("{:02}:00", he)).collect();
+        let prices: Vec<f64> = (1..=24).map(|he| if (7..=22).contains(&he) { 50.0 } else { 20.0 }).collect();
+        let df = df! {
+            "DeliveryDate" => vec!["07/10/2024"; 24],
+            "HourEnding" => hours,
+            "SettlementPoint" => vec!["HB_NORTH"; 24],
+            "SettlementPointPrice" => prices,
+        }
+        .unwrap();
+
+        let blocks = calculator().calculate_dam_block_prices(&df).unwrap();
+        assert_eq!(blocks.len(), 1);
+        let block = &blocks[0];
+        assert_eq!((block.on_peak_hours, block.off_peak_hours), (16, 8));
+        assert_eq!(block.on_peak_avg, Some(50.0));
+        assert_eq!(block.off_peak_avg, Some(20.0));
+        assert_eq!(block.atc_avg, Some((16.0 * 50.0 + 8.0 * 20.0) / 24.0));
+    }
+
+    #[test]
+    fn test_dst_days_count_every_published_hour() {
+        // Fall back: 25 hours with HE02 repeated; spring forward: 23 hours without HE03
+        let fall_back: Vec<String> = (1..=24)
+            .flat_map(|he| if he == 2 { vec![he, he] } else { vec![he] })
+            .map(|he| format!("{:02}:00", he))
+            .collect();
+        let spring_forward: Vec<String> = (1..=24)
+            .filter(|&he| he != 3)
+            .map(|he| format!("{:02}:00", he))
+            .collect();
+        let dates: Vec<&str> = std::iter::repeat("11/03/2024").take(25)
+            .chain(std::iter::repeat("03/10/2024").take(23))
+            .collect();
+        let df = df! {
+            "DeliveryDate" => dates,
+            "HourEnding" => fall_back.into_iter().chain(spring_forward).collect::<Vec<_>>(),
+            "SettlementPoint" => vec!["HB_NORTH"; 48],
+            "SettlementPointPrice" => vec![30.0; 48],
+        }
+        .unwrap();
+
+        let mut blocks = calculator().calculate_dam_block_prices(&df).unwrap();
+        blocks.sort_by_key(|b| b.month);
+        // Both are Sundays, so every hour is off-peak
+        assert_eq!(blocks.iter().map(|b| (b.month, b.on_peak_hours, b.off_peak_hours)).collect::<Vec<_>>(),
+                   vec![(3, 0, 23), (11, 0, 25)]);
+        assert_eq!(blocks[0].on_peak_avg, None);
+        assert_eq!(blocks[1].atc_avg, Some(30.0));
+    }
+}
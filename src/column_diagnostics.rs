@@ -0,0 +1,136 @@
+use anyhow::{Context, Result};
+use polars::prelude::*;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use crate::bess_revenue_calculator::AS_AWARD_COLUMNS;
+
+/// Which expected columns one file's header contains
+#[derive(Debug, Clone)]
+pub struct FileColumns {
+    pub file: String,
+    /// Expected column and whether it was present, in `AS_AWARD_COLUMNS` order
+    pub columns: Vec<(String, bool)>,
+}
+
+impl FileColumns {
+    pub fn missing(&self) -> Vec<&str> {
+        self.columns.iter().filter(|(_, present)| !present).map(|(c, _)| c.as_str()).collect()
+    }
+}
+
+/// Check a CSV header for each expected column
+pub fn check_header(path: &Path, expected: &[&str]) -> Result<FileColumns> {
+    let mut reader = csv::Reader::from_path(path)
+        .with_context(|| format!("Failed to open {}", path.display()))?;
+    let header: HashSet<String> = reader
+        .headers()
+        .with_context(|| format!("Failed to read header of {}", path.display()))?
+        .iter()
+        .map(|h| h.trim().to_string())
+        .collect();
+
+    Ok(FileColumns {
+        file: path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default(),
+        columns: expected.iter().map(|c| (c.to_string(), header.contains(*c))).collect(),
+    })
+}
+
+fn save_presence(results: &[FileColumns], output_path: &Path) -> Result<()> {
+    let mut files = Vec::new();
+    let mut columns = Vec::new();
+    let mut present = Vec::new();
+    for result in results {
+        for (column, is_present) in &result.columns {
+            files.push(result.file.clone());
+            columns.push(column.clone());
+            present.push(*is_present);
+        }
+    }
+
+    let mut df = DataFrame::new(vec![
+        Series::new("File", files),
+        Series::new("Column", columns),
+        Series::new("Present", present),
+    ])?;
+    CsvWriter::new(std::fs::File::create(output_path)?)
+        .finish(&mut df)?;
+    Ok(())
+}
+
+/// Report, for every DAM Gen Resource Data file under `data_dir`, which of the
+/// AS award columns it carries. Zero AS revenue from a file missing a column
+/// means a format gap, not an absence of awards.
+pub fn diagnose_columns(data_dir: &Path) -> Result<Vec<FileColumns>> {
+    println!("🔎 AS Award Column Presence");
+    println!("{}", "=".repeat(80));
+
+    let pattern = data_dir.join("DAM_extracted/60d_DAM_Gen_Resource_Data*.csv");
+    let mut files: Vec<PathBuf> = glob::glob(pattern.to_str().unwrap())?
+        .filter_map(Result::ok)
+        .collect();
+    files.sort();
+
+    if files.is_empty() {
+        println!("No Gen Resource Data files matching {}", pattern.display());
+        return Ok(Vec::new());
+    }
+
+    let mut results = Vec::new();
+    for file in &files {
+        match check_header(file, &AS_AWARD_COLUMNS) {
+            Ok(result) => results.push(result),
+            Err(e) => log::warn!("Skipping {}: {:#}", file.display(), e),
+        }
+    }
+
+    for result in &results {
+        let missing = result.missing();
+        if missing.is_empty() {
+            println!("  ✅ {}", result.file);
+        } else {
+            println!("  ⚠️  {} missing: {}", result.file, missing.join(", "));
+        }
+    }
+
+    println!("\n📊 {} files checked", results.len());
+    println!("{:<20} {:>8} {:>8}", "Column", "Present", "Absent");
+    println!("{}", "-".repeat(38));
+    for (i, column) in AS_AWARD_COLUMNS.iter().enumerate() {
+        let present = results.iter().filter(|r| r.columns[i].1).count();
+        println!("{:<20} {:>8} {:>8}", column, present, results.len() - present);
+    }
+
+    let output_path = data_dir.join("as_column_presence.csv");
+    save_presence(&results, &output_path)?;
+    println!("✅ Saved column presence to: {}", output_path.display());
+
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn test_pre_ecrs_file_reports_ecrs_columns_absent() {
+        let dir = tempfile::tempdir().unwrap();
+        let dam_dir = dir.path().join("DAM_extracted");
+        std::fs::create_dir_all(&dam_dir).unwrap();
+
+        let header_2022 = "Delivery Date,Hour Ending,Resource Name,RegUp Awarded,RegUp MCPC,RegDown Awarded,RegDown MCPC,RRS Awarded,RRS MCPC,NonSpin Awarded,NonSpin MCPC";
+        let header_2024 = format!("{},ECRSSD Awarded,ECRS MCPC", header_2022);
+        for (name, header) in [("60d_DAM_Gen_Resource_Data-01-JAN-22.csv", header_2022.to_string()),
+                               ("60d_DAM_Gen_Resource_Data-01-JAN-24.csv", header_2024)] {
+            let mut file = std::fs::File::create(dam_dir.join(name)).unwrap();
+            writeln!(file, "{}", header).unwrap();
+        }
+
+        let results = diagnose_columns(dir.path()).unwrap();
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].missing(), vec!["ECRSSD Awarded", "ECRS MCPC"]);
+        assert!(results[1].missing().is_empty());
+        assert!(dir.path().join("as_column_presence.csv").exists());
+    }
+}
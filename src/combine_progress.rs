@@ -0,0 +1,63 @@
+use indicatif::{ProgressBar, ProgressStyle};
+use polars::prelude::*;
+use std::time::Duration;
+
+/// Frames concatenated per step; small enough that the bar moves every few
+/// seconds on a large year, large enough that the extra concat is cheap
+const FRAMES_PER_STEP: usize = 50;
+
+/// Bar over `total_rows` showing rows combined, throughput and ETA. It keeps
+/// ticking after the rows are in so the dedup and sort stages, reported
+/// through `set_message`, still show elapsed time.
+pub fn row_progress_bar(total_rows: usize, label: &str) -> ProgressBar {
+    let pb = ProgressBar::new(total_rows as u64);
+    pb.set_style(ProgressStyle::default_bar()
+        .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {human_pos}/{human_len} rows ({per_sec}, ETA {eta}) {prefix} {msg}")
+        .unwrap());
+    pb.set_prefix(label.to_string());
+    pb.set_message("combining");
+    pb.enable_steady_tick(Duration::from_millis(250));
+    pb
+}
+
+/// Concatenate `dfs` a few frames at a time, advancing `pb` by the rows in
+/// each step, instead of one concat that gives no feedback until it ends
+pub fn concat_with_progress(dfs: Vec<DataFrame>, pb: &ProgressBar) -> PolarsResult<DataFrame> {
+    let mut steps = Vec::with_capacity(dfs.len() / FRAMES_PER_STEP + 1);
+    for chunk in dfs.chunks(FRAMES_PER_STEP) {
+        let lazy_dfs: Vec<LazyFrame> = chunk.iter().map(|df| df.clone().lazy()).collect();
+        let step = concat(lazy_dfs.as_slice(), UnionArgs::default())?.collect()?;
+        pb.inc(step.height() as u64);
+        steps.push(step.lazy());
+    }
+    drop(dfs);
+    if steps.len() == 1 {
+        return steps.pop().unwrap().collect();
+    }
+    concat(steps.as_slice(), UnionArgs::default())?.collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_concat_in_steps_keeps_every_row_and_counts_them() {
+        let dfs: Vec<DataFrame> = (0..120)
+            .map(|i| df! {
+                "SettlementPoint" => &["HB_NORTH", "HB_WEST"],
+                "DeliveryHour" => &[i, i],
+            }
+            .unwrap())
+            .collect();
+
+        let pb = ProgressBar::hidden();
+        let combined = concat_with_progress(dfs, &pb).unwrap();
+        assert_eq!(combined.height(), 240);
+        assert_eq!(pb.position(), 240);
+        // Steps are concatenated in order
+        let hours = combined.column("DeliveryHour").unwrap().i32().unwrap();
+        assert_eq!(hours.get(0), Some(0));
+        assert_eq!(hours.get(239), Some(119));
+    }
+}
@@ -0,0 +1,349 @@
+use anyhow::{Context, Result};
+use polars::prelude::*;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// Default absolute tolerance before two numeric cells count as different
+pub const DEFAULT_TOLERANCE: f64 = 1e-6;
+
+/// Differing cells listed per file in the console summary
+const CELLS_SHOWN_PER_FILE: usize = 10;
+
+/// One numeric cell that differs between the two runs
+#[derive(Debug, Clone, PartialEq)]
+pub struct CellDiff {
+    /// Key column values of the row, `|`-separated
+    pub key: String,
+    pub column: String,
+    pub a: Option<f64>,
+    pub b: Option<f64>,
+}
+
+/// Comparison of one Parquet file present in both runs
+#[derive(Debug, Clone, Default)]
+pub struct FileComparison {
+    /// Path relative to the run directory
+    pub file: String,
+    pub rows_a: usize,
+    pub rows_b: usize,
+    pub key_columns: Vec<String>,
+    /// Columns present in only one of the runs
+    pub unmatched_columns: Vec<String>,
+    pub only_in_a: Vec<String>,
+    pub only_in_b: Vec<String>,
+    pub cell_diffs: Vec<CellDiff>,
+}
+
+impl FileComparison {
+    /// Keys of rows with at least one differing cell, in row order
+    pub fn differing_rows(&self) -> Vec<&str> {
+        let mut keys: Vec<&str> = Vec::new();
+        for diff in &self.cell_diffs {
+            if keys.last() != Some(&diff.key.as_str()) {
+                keys.push(&diff.key);
+            }
+        }
+        keys
+    }
+
+    pub fn is_identical(&self) -> bool {
+        self.unmatched_columns.is_empty()
+            && self.only_in_a.is_empty()
+            && self.only_in_b.is_empty()
+            && self.cell_diffs.is_empty()
+    }
+}
+
+fn is_float(dtype: &DataType) -> bool {
+    matches!(dtype, DataType::Float32 | DataType::Float64)
+}
+
+/// `|`-separated key per row; repeated keys get a `#n` suffix so duplicate
+/// rows still pair up in order
+fn row_keys(df: &DataFrame, key_columns: &[String]) -> Result<Vec<String>> {
+    let columns = key_columns.iter()
+        .map(|name| df.column(name)?.cast(&DataType::Utf8))
+        .collect::<PolarsResult<Vec<Series>>>()?;
+
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    let mut keys = Vec::with_capacity(df.height());
+    for i in 0..df.height() {
+        let mut key = if columns.is_empty() {
+            format!("row {}", i)
+        } else {
+            columns.iter()
+                .map(|c| c.utf8().ok().and_then(|c| c.get(i)).unwrap_or("null").to_string())
+                .collect::<Vec<_>>()
+                .join("|")
+        };
+        let count = seen.entry(key.clone()).or_insert(0);
+        if *count > 0 {
+            key = format!("{}#{}", key, count);
+        }
+        *count += 1;
+        keys.push(key);
+    }
+    Ok(keys)
+}
+
+fn cells_differ(a: Option<f64>, b: Option<f64>, tolerance: f64) -> bool {
+    match (a, b) {
+        (Some(a), Some(b)) if a.is_nan() && b.is_nan() => false,
+        // One NaN against a number differs
+        (Some(a), Some(b)) if a.is_nan() || b.is_nan() => true,
+        (Some(a), Some(b)) => (a - b).abs() > tolerance,
+        (None, None) => false,
+        _ => true,
+    }
+}
+
+/// Pair rows of `a` and `b` on their shared non-float columns and compare
+/// every shared float column within `tolerance`. Frames without any
+/// non-float columns are paired by row number.
+pub fn compare_frames(file: &str, a: &DataFrame, b: &DataFrame, tolerance: f64) -> Result<FileComparison> {
+    let schema_b = b.schema();
+    let mut key_columns = Vec::new();
+    let mut value_columns = Vec::new();
+    let mut unmatched_columns = Vec::new();
+    for field in a.schema().iter_fields() {
+        match schema_b.get(field.name()) {
+            Some(dtype_b) if is_float(&field.dtype) && is_float(dtype_b) => value_columns.push(field.name().to_string()),
+            Some(_) => key_columns.push(field.name().to_string()),
+            None => unmatched_columns.push(field.name().to_string()),
+        }
+    }
+    for name in b.get_column_names() {
+        if !a.get_column_names().contains(&name) {
+            unmatched_columns.push(name.to_string());
+        }
+    }
+
+    let keys_a = row_keys(a, &key_columns)?;
+    let keys_b = row_keys(b, &key_columns)?;
+    let rows_b: HashMap<&str, usize> = keys_b.iter().enumerate().map(|(i, k)| (k.as_str(), i)).collect();
+
+    let values = |df: &DataFrame| -> Result<Vec<Float64Chunked>> {
+        value_columns.iter()
+            .map(|name| Ok(df.column(name)?.cast(&DataType::Float64)?.f64()?.clone()))
+            .collect()
+    };
+    let (values_a, values_b) = (values(a)?, values(b)?);
+
+    let mut comparison = FileComparison {
+        file: file.to_string(),
+        rows_a: a.height(),
+        rows_b: b.height(),
+        key_columns,
+        unmatched_columns,
+        ..Default::default()
+    };
+
+    for (i, key) in keys_a.iter().enumerate() {
+        let Some(&j) = rows_b.get(key.as_str()) else {
+            comparison.only_in_a.push(key.clone());
+            continue;
+        };
+        for (column, (col_a, col_b)) in value_columns.iter().zip(values_a.iter().zip(&values_b)) {
+            let (cell_a, cell_b) = (col_a.get(i), col_b.get(j));
+            if cells_differ(cell_a, cell_b, tolerance) {
+                comparison.cell_diffs.push(CellDiff {
+                    key: key.clone(),
+                    column: column.clone(),
+                    a: cell_a,
+                    b: cell_b,
+                });
+            }
+        }
+    }
+
+    let keys_a: HashSet<&str> = keys_a.iter().map(String::as_str).collect();
+    comparison.only_in_b = keys_b.iter().filter(|k| !keys_a.contains(k.as_str())).cloned().collect();
+
+    Ok(comparison)
+}
+
+/// Parquet files under `dir`, relative to it, in name order
+fn parquet_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = WalkDir::new(dir)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .map(|e| e.into_path())
+        .filter(|path| path.extension().map_or(false, |ext| ext == "parquet"))
+        .filter_map(|path| path.strip_prefix(dir).ok().map(Path::to_path_buf))
+        .collect();
+    files.sort();
+    files
+}
+
+fn read_parquet(path: &Path) -> Result<DataFrame> {
+    let file = std::fs::File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    Ok(ParquetReader::new(file).finish()?)
+}
+
+/// Run comparison: per-file results for files in both directories, plus
+/// files found in only one of them
+pub struct RunComparison {
+    pub files: Vec<FileComparison>,
+    pub only_in_a: Vec<PathBuf>,
+    pub only_in_b: Vec<PathBuf>,
+}
+
+impl RunComparison {
+    pub fn is_identical(&self) -> bool {
+        self.only_in_a.is_empty() && self.only_in_b.is_empty() && self.files.iter().all(FileComparison::is_identical)
+    }
+}
+
+pub fn compare_dirs(dir_a: &Path, dir_b: &Path, tolerance: f64) -> Result<RunComparison> {
+    let files_a = parquet_files(dir_a);
+    let files_b = parquet_files(dir_b);
+
+    let mut files = Vec::new();
+    for file in files_a.iter().filter(|f| files_b.contains(f)) {
+        let a = read_parquet(&dir_a.join(file))?;
+        let b = read_parquet(&dir_b.join(file))?;
+        files.push(compare_frames(&file.display().to_string(), &a, &b, tolerance)?);
+    }
+
+    Ok(RunComparison {
+        files,
+        only_in_a: files_a.iter().filter(|f| !files_b.contains(f)).cloned().collect(),
+        only_in_b: files_b.iter().filter(|f| !files_a.contains(f)).cloned().collect(),
+    })
+}
+
+fn format_cell(value: Option<f64>) -> String {
+    value.map_or("null".to_string(), |v| v.to_string())
+}
+
+/// Compare every Parquet file two runs have in common and print a per-file summary
+pub fn compare_runs(dir_a: &Path, dir_b: &Path, tolerance: f64) -> Result<RunComparison> {
+    println!("🔍 Comparing Runs");
+    println!("{}", "=".repeat(80));
+    println!("A: {}", dir_a.display());
+    println!("B: {}", dir_b.display());
+
+    let comparison = compare_dirs(dir_a, dir_b, tolerance)?;
+
+    println!("\n{:<50} {:>10} {:>10} {:>8} {:>8} {:>10}",
+             "File", "Rows A", "Rows B", "Only A", "Only B", "Diff Rows");
+    println!("{}", "-".repeat(101));
+    for file in &comparison.files {
+        println!("{:<50} {:>10} {:>10} {:>8} {:>8} {:>10}{}",
+                 file.file, file.rows_a, file.rows_b, file.only_in_a.len(), file.only_in_b.len(),
+                 file.differing_rows().len(),
+                 if file.is_identical() { "" } else { "  ⚠️" });
+    }
+
+    for file in comparison.files.iter().filter(|f| !f.is_identical()) {
+        println!("\n⚠️  {} (keys: {})", file.file, file.key_columns.join(", "));
+        if !file.unmatched_columns.is_empty() {
+            println!("  Columns in only one run: {}", file.unmatched_columns.join(", "));
+        }
+        for diff in file.cell_diffs.iter().take(CELLS_SHOWN_PER_FILE) {
+            println!("  {} {}: {} → {}", diff.key, diff.column, format_cell(diff.a), format_cell(diff.b));
+        }
+        if file.cell_diffs.len() > CELLS_SHOWN_PER_FILE {
+            println!("  ... {} more differing cells", file.cell_diffs.len() - CELLS_SHOWN_PER_FILE);
+        }
+        for key in file.only_in_a.iter().take(CELLS_SHOWN_PER_FILE) {
+            println!("  {} only in A", key);
+        }
+        for key in file.only_in_b.iter().take(CELLS_SHOWN_PER_FILE) {
+            println!("  {} only in B", key);
+        }
+    }
+
+    for file in &comparison.only_in_a {
+        println!("  {} only in A", file.display());
+    }
+    for file in &comparison.only_in_b {
+        println!("  {} only in B", file.display());
+    }
+
+    let differing: usize = comparison.files.iter().map(|f| f.differing_rows().len()).sum();
+    println!("\n📊 {} files compared, tolerance ±{}", comparison.files.len(), tolerance);
+    if comparison.is_identical() {
+        println!("✅ No differences");
+    } else {
+        println!("⚠️  {} differing rows across {} files", differing,
+                 comparison.files.iter().filter(|f| !f.is_identical()).count());
+    }
+
+    Ok(comparison)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn daily() -> DataFrame {
+        df! {
+            "Resource_Name" => &["BESS_A", "BESS_A", "BESS_B", "BESS_B"],
+            "Date" => &["2024-01-01", "2024-01-02", "2024-01-01", "2024-01-02"],
+            "RT_Revenue" => &[100.0, 200.0, 300.0, 400.0],
+            "Total_Revenue" => &[150.0, 250.0, 350.0, 450.0],
+        }
+        .unwrap()
+    }
+
+    fn write(dir: &Path, name: &str, df: &mut DataFrame) {
+        ParquetWriter::new(std::fs::File::create(dir.join(name)).unwrap()).finish(df).unwrap();
+    }
+
+    #[test]
+    fn test_identical_runs_have_no_differences() {
+        let (a, b) = (tempfile::tempdir().unwrap(), tempfile::tempdir().unwrap());
+        write(a.path(), "bess_daily_revenues.parquet", &mut daily());
+        // Same rows in a different order, with float noise inside tolerance
+        let mut shuffled = daily().reverse();
+        let noisy = shuffled.column("RT_Revenue").unwrap() + 1e-9;
+        shuffled.with_column(noisy).unwrap();
+        write(b.path(), "bess_daily_revenues.parquet", &mut shuffled);
+
+        let comparison = compare_dirs(a.path(), b.path(), DEFAULT_TOLERANCE).unwrap();
+        assert_eq!(comparison.files.len(), 1);
+        assert_eq!(comparison.files[0].key_columns, vec!["Resource_Name", "Date"]);
+        assert!(comparison.is_identical());
+    }
+
+    #[test]
+    fn test_known_change_surfaces_affected_rows() {
+        let (a, b) = (tempfile::tempdir().unwrap(), tempfile::tempdir().unwrap());
+        write(a.path(), "bess_daily_revenues.parquet", &mut daily());
+        write(a.path(), "only_a.parquet", &mut daily());
+
+        let mut changed = daily();
+        let total = Series::new("Total_Revenue", &[150.0, 260.0, 350.0, 450.0]);
+        changed.with_column(total).unwrap();
+        write(b.path(), "bess_daily_revenues.parquet", &mut changed);
+
+        let comparison = compare_dirs(a.path(), b.path(), DEFAULT_TOLERANCE).unwrap();
+        assert!(!comparison.is_identical());
+        assert_eq!(comparison.only_in_a, vec![PathBuf::from("only_a.parquet")]);
+
+        let file = &comparison.files[0];
+        assert_eq!(file.differing_rows(), vec!["BESS_A|2024-01-02"]);
+        assert_eq!(file.cell_diffs, vec![CellDiff {
+            key: "BESS_A|2024-01-02".to_string(),
+            column: "Total_Revenue".to_string(),
+            a: Some(250.0),
+            b: Some(260.0),
+        }]);
+    }
+
+    #[test]
+    fn test_missing_rows_and_nulls() {
+        let a = daily();
+        let b = daily().head(Some(3));
+        let comparison = compare_frames("x", &a, &b, DEFAULT_TOLERANCE).unwrap();
+        assert_eq!(comparison.only_in_a, vec!["BESS_B|2024-01-02"]);
+        assert!(comparison.only_in_b.is_empty());
+
+        assert!(cells_differ(Some(1.0), None, DEFAULT_TOLERANCE));
+        assert!(!cells_differ(None, None, DEFAULT_TOLERANCE));
+        assert!(!cells_differ(Some(f64::NAN), Some(f64::NAN), DEFAULT_TOLERANCE));
+        assert!(cells_differ(Some(1.0), Some(f64::NAN), DEFAULT_TOLERANCE));
+    }
+}
@@ -1,14 +1,15 @@
 use anyhow::Result;
+use log::info;
 use std::path::PathBuf;
 use crate::ercot_processor::ErcotProcessor;
+use crate::output_prefix::OutputPrefix;
 
-pub fn process_all_ercot_data() -> Result<()> {
-    println!("🚀 ERCOT Comprehensive Data Processor");
-    println!("Using {} CPU cores", num_cpus::get());
-    println!("{}", "=".repeat(80));
+pub fn process_all_ercot_data(output_prefix: &OutputPrefix) -> Result<()> {
+    info!("ERCOT Comprehensive Data Processor");
+    info!("Using {} CPU cores", num_cpus::get());
     
     let base_dir = PathBuf::from("/Users/enrico/data/ERCOT_data");
-    let output_dir = PathBuf::from("ercot_processed_data");
+    let output_dir = output_prefix.output_dir("ercot_processed_data");
     std::fs::create_dir_all(&output_dir)?;
     
     let processor = ErcotProcessor::new(output_dir.clone());
@@ -33,11 +34,11 @@ pub fn process_all_ercot_data() -> Result<()> {
     }
     
     // Process RT Settlement Point Prices (already done)
-    println!("\n✅ RT Settlement Point Prices already processed in annual_data/");
+    info!("RT Settlement Point Prices already processed in annual_data/");
     
     let duration = start.elapsed();
-    println!("\n🎉 All processing complete in {:?}!", duration);
-    println!("📁 Output directory: {}", output_dir.display());
+    info!("All processing complete in {:?}!", duration);
+    info!("Output directory: {}", output_dir.display());
     
     Ok(())
 }
\ No newline at end of file
@@ -0,0 +1,154 @@
+use anyhow::Result;
+use log::{debug, info};
+use polars::prelude::*;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+use crate::unified_processor::{align_columns, dedup_columns};
+
+/// Columns tried, in order, when sorting the consolidated output
+const DATETIME_COLUMNS: &[&str] = &["datetime", "SCEDTimestamp", "DeliveryDate", "OperatingDate"];
+
+/// Merges per-year `{prefix}_{year}.parquet` outputs into one `{prefix}_ALL.parquet`
+pub struct Consolidator {
+    data_dir: PathBuf,
+}
+
+impl Consolidator {
+    pub fn new(data_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            data_dir: data_dir.into(),
+        }
+    }
+
+    /// Per-year files for a dataset anywhere under the data directory, in name order
+    pub fn find_year_files(&self, prefix: &str) -> Vec<PathBuf> {
+        let mut files: Vec<PathBuf> = WalkDir::new(&self.data_dir)
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .map(|e| e.into_path())
+            .filter(|path| is_year_file(path, prefix))
+            .collect();
+        files.sort();
+        files
+    }
+
+    /// Combine every year of `prefix` into one deduplicated, datetime-sorted frame
+    pub fn consolidate(&self, prefix: &str) -> Result<DataFrame> {
+        let files = self.find_year_files(prefix);
+        if files.is_empty() {
+            anyhow::bail!("No {}_*.parquet files found under {}", prefix, self.data_dir.display());
+        }
+
+        info!("Consolidating {} files for {}", files.len(), prefix);
+        let mut dfs = Vec::with_capacity(files.len());
+        for file in &files {
+            let df = ParquetReader::new(std::fs::File::open(file)?).finish()?;
+            debug!("{}: {} rows", file.display(), df.height());
+            dfs.push(df);
+        }
+
+        consolidate_frames(dfs)
+    }
+
+    /// Consolidate and write `{prefix}_ALL.parquet` to the data directory
+    pub fn consolidate_to_file(&self, prefix: &str) -> Result<(PathBuf, usize)> {
+        let mut df = self.consolidate(prefix)?;
+        let output_path = self.data_dir.join(format!("{}_ALL.parquet", prefix));
+        ParquetWriter::new(std::fs::File::create(&output_path)?)
+            .finish(&mut df)?;
+        Ok((output_path, df.height()))
+    }
+}
+
+fn is_year_file(path: &Path, prefix: &str) -> bool {
+    let stem = match (path.extension().and_then(|e| e.to_str()), path.file_stem().and_then(|s| s.to_str())) {
+        (Some("parquet"), Some(stem)) => stem,
+        _ => return false,
+    };
+
+    stem.strip_prefix(prefix)
+        .and_then(|rest| rest.strip_prefix('_'))
+        .is_some_and(|year| year.len() == 4 && year.chars().all(|c| c.is_ascii_digit()))
+}
+
+/// Concatenate frames with aligned schemas, drop cross-year duplicates and sort by datetime
+pub fn consolidate_frames(dfs: Vec<DataFrame>) -> Result<DataFrame> {
+    let lazy_dfs: Vec<LazyFrame> = align_columns(dfs)
+        .into_iter()
+        .map(|df| df.lazy())
+        .collect();
+
+    let combined = concat(lazy_dfs.as_slice(), UnionArgs::default())?.collect()?;
+
+    let dedup_columns = dedup_columns(&combined);
+    let unique_df = combined.unique_stable(Some(&dedup_columns), UniqueKeepStrategy::Last, None)?;
+    debug!("Records before dedup: {}, after: {}", combined.height(), unique_df.height());
+
+    match DATETIME_COLUMNS.iter().find(|c| unique_df.get_column_names().contains(c)) {
+        Some(datetime_col) => Ok(unique_df.sort([*datetime_col], false, true)?),
+        None => Ok(unique_df),
+    }
+}
+
+pub fn consolidate_dataset(data_dir: &Path, prefix: &str) -> Result<()> {
+    let (output_path, rows) = Consolidator::new(data_dir).consolidate_to_file(prefix)?;
+    println!("✅ Consolidated {} rows into {}", rows, output_path.display());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_parquet(path: &Path, mut df: DataFrame) {
+        ParquetWriter::new(std::fs::File::create(path).unwrap())
+            .finish(&mut df)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_consolidate_drops_cross_year_duplicates() {
+        let dir = tempfile::tempdir().unwrap();
+        let prefix = "RT_Settlement_Point_Prices";
+
+        write_parquet(
+            &dir.path().join(format!("{}_2023.parquet", prefix)),
+            df! {
+                "datetime" => &[3_i64, 1, 2],
+                "SettlementPoint" => &["HB_NORTH", "HB_NORTH", "HB_NORTH"],
+                "SettlementPointPrice" => &[30.0, 10.0, 20.0],
+            }
+            .unwrap(),
+        );
+        // First interval of the new year also appears in the prior year's file
+        write_parquet(
+            &dir.path().join(format!("{}_2024.parquet", prefix)),
+            df! {
+                "datetime" => &[3_i64, 4],
+                "SettlementPoint" => &["HB_NORTH", "HB_NORTH"],
+                "SettlementPointPrice" => &[30.0, 40.0],
+            }
+            .unwrap(),
+        );
+        // Not a per-year file
+        write_parquet(
+            &dir.path().join("DAM_Settlement_Point_Prices_2024.parquet"),
+            df! { "datetime" => &[9_i64] }.unwrap(),
+        );
+
+        let consolidator = Consolidator::new(dir.path());
+        assert_eq!(consolidator.find_year_files(prefix).len(), 2);
+
+        let (output_path, rows) = consolidator.consolidate_to_file(prefix).unwrap();
+        assert_eq!(rows, 3 + 2 - 1);
+        assert!(output_path.ends_with("RT_Settlement_Point_Prices_ALL.parquet"));
+
+        let df = ParquetReader::new(std::fs::File::open(&output_path).unwrap()).finish().unwrap();
+        let datetimes: Vec<i64> = df.column("datetime").unwrap().i64().unwrap().into_no_null_iter().collect();
+        assert_eq!(datetimes, vec![1, 2, 3, 4]);
+
+        // Re-running ignores the _ALL output
+        assert_eq!(consolidator.find_year_files(prefix).len(), 2);
+    }
+}
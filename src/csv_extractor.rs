@@ -1,18 +1,25 @@
 use std::fs;
+use log::{error, info, warn};
 use std::path::{Path, PathBuf};
-use std::io::{self, BufRead, BufReader};
+use std::io::{self, BufRead, BufReader, Read};
 use rayon::prelude::*;
-use zip::ZipArchive;
+use flate2::read::GzDecoder;
+use xxhash_rust::xxh3::Xxh3;
 use anyhow::{Result, Context};
 use std::sync::atomic::{AtomicUsize, Ordering};
-use std::sync::Arc;
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
 use tempfile::TempDir;
 
+use crate::archive::{archive_reader_for, is_archive};
+
 pub struct CsvExtractor {
     input_dir: PathBuf,
     output_dir: PathBuf,
     processed_count: Arc<AtomicUsize>,
     csv_count: Arc<AtomicUsize>,
+    duplicate_count: Arc<AtomicUsize>,
+    seen_hashes: Arc<Mutex<HashSet<u64>>>,
 }
 
 impl CsvExtractor {
@@ -23,62 +30,95 @@ impl CsvExtractor {
             output_dir,
             processed_count: Arc::new(AtomicUsize::new(0)),
             csv_count: Arc::new(AtomicUsize::new(0)),
+            duplicate_count: Arc::new(AtomicUsize::new(0)),
+            seen_hashes: Arc::new(Mutex::new(HashSet::new())),
         }
     }
 
     pub fn extract_all(&self) -> Result<()> {
-        println!("Creating output directory: {:?}", self.output_dir);
+        info!("Creating output directory: {:?}", self.output_dir);
         fs::create_dir_all(&self.output_dir)?;
 
-        // Find all ZIP files in the input directory
-        let zip_files = self.find_zip_files(&self.input_dir)?;
-        println!("Found {} ZIP files to process", zip_files.len());
+        // Gzipped CSVs decompress straight into the output directory
+        let gz_files = self.find_gz_files(&self.input_dir)?;
+        if !gz_files.is_empty() {
+            info!("Found {} gzipped CSV files to process", gz_files.len());
+            gz_files.par_iter().for_each(|gz_path| {
+                let result = decompress_gz_file(gz_path, &self.output_dir).and_then(|csv_path| {
+                    if self.is_duplicate(&csv_path)? {
+                        fs::remove_file(&csv_path)?;
+                    } else {
+                        self.csv_count.fetch_add(1, Ordering::SeqCst);
+                    }
+                    Ok(())
+                });
+                if let Err(e) = result {
+                    error!("Error decompressing {:?}: {}", gz_path, e);
+                }
+            });
+        }
+
+        // Find all ZIP/TAR archives in the input directory
+        let archive_files = self.find_archive_files(&self.input_dir)?;
+        info!("Found {} archives to process", archive_files.len());
 
-        // Process each ZIP file in parallel
-        zip_files.par_iter().for_each(|zip_path| {
-            if let Err(e) = self.process_zip_file(zip_path) {
-                eprintln!("Error processing {:?}: {}", zip_path, e);
+        // Process each archive in parallel
+        archive_files.par_iter().for_each(|archive_path| {
+            if let Err(e) = self.process_archive_file(archive_path) {
+                error!("Error processing {:?}: {}", archive_path, e);
             }
             
             let count = self.processed_count.fetch_add(1, Ordering::SeqCst) + 1;
             if count % 100 == 0 {
-                println!("Processed {} ZIP files, found {} CSV files so far", 
+                info!("Processed {} archives, found {} CSV files so far", 
                     count, self.csv_count.load(Ordering::SeqCst));
             }
         });
 
         println!("\nExtraction complete!");
-        println!("Processed {} ZIP files", self.processed_count.load(Ordering::SeqCst));
+        println!("Processed {} archives", self.processed_count.load(Ordering::SeqCst));
         println!("Extracted {} CSV files to {:?}", self.csv_count.load(Ordering::SeqCst), self.output_dir);
+        println!("Skipped {} duplicate CSV files (identical content)", self.duplicate_count.load(Ordering::SeqCst));
 
         Ok(())
     }
 
-    fn find_zip_files(&self, dir: &Path) -> Result<Vec<PathBuf>> {
-        let mut zip_files = Vec::new();
+    fn find_archive_files(&self, dir: &Path) -> Result<Vec<PathBuf>> {
+        let mut archive_files = Vec::new();
         
         for entry in fs::read_dir(dir)? {
             let entry = entry?;
             let path = entry.path();
             
-            if path.is_file() && path.extension().and_then(|s| s.to_str()) == Some("zip") {
-                // Skip XML zip files - only process CSV zip files
-                let filename = path.file_name().and_then(|s| s.to_str()).unwrap_or("");
-                if !filename.contains("_xml.zip") && !filename.contains("_XML.zip") {
-                    zip_files.push(path);
-                }
+            // XML zip files are skipped by archive_reader_for - only CSV archives are processed
+            if path.is_file() && is_archive(&path) {
+                archive_files.push(path);
             }
         }
         
-        Ok(zip_files)
+        Ok(archive_files)
+    }
+
+    fn find_gz_files(&self, dir: &Path) -> Result<Vec<PathBuf>> {
+        let mut gz_files = Vec::new();
+
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
+            let filename = path.file_name().and_then(|s| s.to_str()).unwrap_or("");
+            if path.is_file() && is_gzipped_csv(filename) {
+                gz_files.push(path);
+            }
+        }
+
+        Ok(gz_files)
     }
 
-    fn process_zip_file(&self, zip_path: &Path) -> Result<()> {
+    fn process_archive_file(&self, archive_path: &Path) -> Result<()> {
         // Create a temporary directory for extraction
         let temp_dir = TempDir::new_in(&self.input_dir)?;
         
-        // Extract the ZIP file
-        self.extract_zip_recursive(zip_path, temp_dir.path())?;
+        // Extract the archive (ZIP, TAR or TAR.GZ)
+        self.extract_archive_recursive(archive_path, temp_dir.path())?;
         
         // Move all CSV files to the output directory
         self.collect_csv_files(temp_dir.path())?;
@@ -86,36 +126,23 @@ impl CsvExtractor {
         Ok(())
     }
 
-    fn extract_zip_recursive(&self, zip_path: &Path, extract_to: &Path) -> Result<()> {
-        let file = fs::File::open(zip_path)
-            .with_context(|| format!("Failed to open ZIP file: {:?}", zip_path))?;
-        
-        let mut archive = ZipArchive::new(file)
-            .with_context(|| format!("Failed to read ZIP archive: {:?}", zip_path))?;
-
-        // Extract all files
-        for i in 0..archive.len() {
-            let mut file = archive.by_index(i)?;
-            let outpath = extract_to.join(file.name());
-
-            if file.name().ends_with('/') {
-                fs::create_dir_all(&outpath)?;
-            } else {
-                if let Some(p) = outpath.parent() {
-                    fs::create_dir_all(p)?;
+    fn extract_archive_recursive(&self, archive_path: &Path, extract_to: &Path) -> Result<()> {
+        let reader = archive_reader_for(archive_path)
+            .ok_or_else(|| anyhow::anyhow!("Unsupported archive type: {:?}", archive_path))?;
+
+        for outpath in reader.extract(archive_path, extract_to)? {
+            let filename = outpath.file_name().and_then(|s| s.to_str()).unwrap_or("");
+
+            if is_archive(&outpath) {
+                // Nested archive - recursively extract it
+                if let Err(e) = self.extract_archive_recursive(&outpath, extract_to) {
+                    error!("Failed to extract nested archive {:?}: {}", outpath, e);
                 }
-                
-                let mut outfile = fs::File::create(&outpath)?;
-                io::copy(&mut file, &mut outfile)?;
-                
-                // If it's a ZIP file, recursively extract it (but skip XML zips)
-                if outpath.extension().and_then(|s| s.to_str()) == Some("zip") {
-                    let filename = outpath.file_name().and_then(|s| s.to_str()).unwrap_or("");
-                    if !filename.contains("_xml.zip") && !filename.contains("_XML.zip") {
-                        if let Err(e) = self.extract_zip_recursive(&outpath, extract_to) {
-                            eprintln!("Failed to extract nested ZIP {:?}: {}", outpath, e);
-                        }
-                    }
+            } else if is_gzipped_csv(filename) {
+                // Gzipped CSV inside an archive - decompress next to it
+                let parent = outpath.parent().unwrap_or(extract_to);
+                if let Err(e) = decompress_gz_file(&outpath, parent) {
+                    error!("Failed to decompress nested gzip {:?}: {}", outpath, e);
                 }
             }
         }
@@ -123,6 +150,16 @@ impl CsvExtractor {
         Ok(())
     }
 
+    /// Hash the file contents and record it; returns true if the same content was already seen
+    fn is_duplicate(&self, path: &Path) -> Result<bool> {
+        let hash = hash_file_contents(path)?;
+        let is_new = self.seen_hashes.lock().unwrap().insert(hash);
+        if !is_new {
+            self.duplicate_count.fetch_add(1, Ordering::SeqCst);
+        }
+        Ok(!is_new)
+    }
+
     fn collect_csv_files(&self, dir: &Path) -> Result<()> {
         // Walk through all directories recursively to find CSV files
         for entry in walkdir::WalkDir::new(dir)
@@ -132,6 +169,12 @@ impl CsvExtractor {
             let path = entry.path();
             
             if path.is_file() && path.extension().and_then(|s| s.to_str()) == Some("csv") {
+                // Byte-identical CSVs from overlapping archives are dropped
+                if self.is_duplicate(path)? {
+                    fs::remove_file(path)?;
+                    continue;
+                }
+
                 // Get the filename
                 let filename = path.file_name().unwrap();
                 let dest_path = self.output_dir.join(filename);
@@ -150,6 +193,64 @@ impl CsvExtractor {
     }
 }
 
+/// xxHash3 (64-bit) of a file's contents
+pub fn hash_file_contents(path: &Path) -> Result<u64> {
+    let mut file = fs::File::open(path)
+        .with_context(|| format!("Failed to open file for hashing: {:?}", path))?;
+    let mut hasher = Xxh3::new();
+    let mut buffer = vec![0u8; 1 << 16];
+
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+
+    Ok(hasher.digest())
+}
+
+/// True for gzip-compressed CSV files (`.csv.gz`)
+pub fn is_gzipped_csv(filename: &str) -> bool {
+    filename.to_lowercase().ends_with(".csv.gz")
+}
+
+/// `name.csv` for a gzipped `name.csv.gz`, whatever the case of the `.gz`
+pub fn gz_csv_name(filename: &str) -> &str {
+    match filename.len().checked_sub(3) {
+        Some(stem) if filename.is_char_boundary(stem) && filename[stem..].eq_ignore_ascii_case(".gz") => {
+            &filename[..stem]
+        }
+        _ => filename,
+    }
+}
+
+/// Decompress a gzip stream fully into memory
+pub fn read_gz_to_vec<R: Read>(reader: R) -> Result<Vec<u8>> {
+    let mut decoder = GzDecoder::new(reader);
+    let mut buffer = Vec::new();
+    decoder.read_to_end(&mut buffer)?;
+    Ok(buffer)
+}
+
+/// Decompress `name.csv.gz` to `output_dir/name.csv`, returning the CSV path
+pub fn decompress_gz_file(gz_path: &Path, output_dir: &Path) -> Result<PathBuf> {
+    let csv_name = gz_path
+        .file_stem()
+        .ok_or_else(|| anyhow::anyhow!("Invalid gzip file name: {:?}", gz_path))?;
+    let csv_path = output_dir.join(csv_name);
+
+    let file = fs::File::open(gz_path)
+        .with_context(|| format!("Failed to open gzip file: {:?}", gz_path))?;
+    let mut decoder = GzDecoder::new(file);
+    let mut outfile = fs::File::create(&csv_path)?;
+    io::copy(&mut decoder, &mut outfile)
+        .with_context(|| format!("Failed to decompress gzip file: {:?}", gz_path))?;
+
+    Ok(csv_path)
+}
+
 pub fn extract_csv_from_directory(input_dir: PathBuf) -> Result<()> {
     let extractor = CsvExtractor::new(input_dir);
     extractor.extract_all()
@@ -178,7 +279,7 @@ pub fn extract_all_ercot_directories(base_dir: PathBuf) -> Result<()> {
         directories.push(line.to_string());
     }
 
-    println!("Found {} directories to process from {}", directories.len(), csv_file);
+    info!("Found {} directories to process from {}", directories.len(), csv_file);
     
     // Process each directory
     let mut successful = 0;
@@ -188,19 +289,19 @@ pub fn extract_all_ercot_directories(base_dir: PathBuf) -> Result<()> {
         let full_path = base_dir.join(&dir_name);
         
         if !full_path.exists() {
-            println!("⚠️  Directory not found: {}", full_path.display());
+            warn!("Directory not found: {}", full_path.display());
             failed += 1;
             continue;
         }
         
-        println!("\n🚀 Processing: {}", dir_name);
+        info!("Processing: {}", dir_name);
         match extract_csv_from_directory(full_path) {
             Ok(()) => {
-                println!("✅ Completed: {}", dir_name);
+                info!("Completed: {}", dir_name);
                 successful += 1;
             }
             Err(e) => {
-                println!("❌ Failed: {} - Error: {}", dir_name, e);
+                error!("Failed: {} - Error: {}", dir_name, e);
                 failed += 1;
             }
         }
@@ -212,4 +313,88 @@ pub fn extract_all_ercot_directories(base_dir: PathBuf) -> Result<()> {
     println!("📁 Total: {}", successful + failed);
     
     Ok(())
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gz_csv_name_ignores_suffix_case() {
+        assert_eq!(gz_csv_name("prices_20240101.csv.gz"), "prices_20240101.csv");
+        assert_eq!(gz_csv_name("PRICES_20240101.CSV.GZ"), "PRICES_20240101.CSV");
+        assert_eq!(gz_csv_name("prices.csv"), "prices.csv");
+        assert!(is_gzipped_csv("PRICES_20240101.CSV.GZ"));
+    }
+
+    #[test]
+    fn test_directory_mixing_zip_and_tar_gz_archives() {
+        use flate2::{write::GzEncoder, Compression};
+        use std::io::Write;
+
+        let dir = tempfile::tempdir().unwrap();
+
+        let mut zip = ::zip::ZipWriter::new(fs::File::create(dir.path().join("prices_20240101.zip")).unwrap());
+        zip.start_file("prices_20240101.csv", ::zip::write::FileOptions::default()).unwrap();
+        zip.write_all(b"DeliveryDate,Price\n01/01/2024,25.0\n").unwrap();
+        zip.finish().unwrap();
+
+        let encoder = GzEncoder::new(fs::File::create(dir.path().join("prices_202401.tar.gz")).unwrap(), Compression::default());
+        let mut tar = tar::Builder::new(encoder);
+        for (name, contents) in [
+            ("reports/prices_20240102.csv", "DeliveryDate,Price\n01/02/2024,31.5\n"),
+            ("reports/prices_20240103.csv", "DeliveryDate,Price\n01/03/2024,28.0\n"),
+        ] {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(contents.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            tar.append_data(&mut header, name, contents.as_bytes()).unwrap();
+        }
+        tar.into_inner().unwrap().finish().unwrap();
+
+        let extractor = CsvExtractor::new(dir.path().to_path_buf());
+        extractor.extract_all().unwrap();
+
+        let mut extracted: Vec<String> = fs::read_dir(&extractor.output_dir).unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        extracted.sort();
+        assert_eq!(extracted, vec!["prices_20240101.csv", "prices_20240102.csv", "prices_20240103.csv"]);
+        assert_eq!(extractor.processed_count.load(Ordering::SeqCst), 2);
+        assert_eq!(extractor.csv_count.load(Ordering::SeqCst), 3);
+        assert_eq!(
+            fs::read_to_string(extractor.output_dir.join("prices_20240102.csv")).unwrap(),
+            "DeliveryDate,Price\n01/02/2024,31.5\n"
+        );
+    }
+
+    #[test]
+    fn test_identical_csvs_collapse_and_distinct_ones_survive() {
+        let dir = tempfile::tempdir().unwrap();
+        let extractor = CsvExtractor::new(dir.path().to_path_buf());
+        fs::create_dir_all(&extractor.output_dir).unwrap();
+
+        // The same report shipped in two overlapping archives, plus a different day
+        let staging = dir.path().join("staging");
+        for (sub, name, contents) in [
+            ("a", "prices_20240101.csv", "DeliveryDate,Price\n01/01/2024,25.0\n"),
+            ("b", "prices_20240101_copy.csv", "DeliveryDate,Price\n01/01/2024,25.0\n"),
+            ("c", "prices_20240102.csv", "DeliveryDate,Price\n01/02/2024,31.5\n"),
+        ] {
+            fs::create_dir_all(staging.join(sub)).unwrap();
+            fs::write(staging.join(sub).join(name), contents).unwrap();
+        }
+
+        extractor.collect_csv_files(&staging).unwrap();
+
+        let mut kept: Vec<String> = fs::read_dir(&extractor.output_dir).unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        kept.sort();
+        assert_eq!(kept.len(), 2);
+        assert!(kept.contains(&"prices_20240102.csv".to_string()));
+        assert_eq!(extractor.csv_count.load(Ordering::SeqCst), 2);
+        assert_eq!(extractor.duplicate_count.load(Ordering::SeqCst), 1);
+    }
+}
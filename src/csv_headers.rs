@@ -0,0 +1,91 @@
+use anyhow::{Context, Result};
+use log::{debug, info, warn};
+use polars::prelude::*;
+use std::collections::HashSet;
+use std::path::Path;
+use std::sync::{Mutex, OnceLock};
+
+/// Header with a leading byte-order mark and surrounding whitespace removed
+/// and internal runs of whitespace collapsed to one space, so
+/// `" Resource  Name "` becomes `"Resource Name"`
+pub fn normalize_header(name: &str) -> String {
+    name.trim_start_matches('\u{feff}')
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// (original, normalized) renames already logged, so a quirk repeated across
+/// thousands of daily files is reported once
+fn logged_renames() -> &'static Mutex<HashSet<(String, String)>> {
+    static LOGGED: OnceLock<Mutex<HashSet<(String, String)>>> = OnceLock::new();
+    LOGGED.get_or_init(|| Mutex::new(HashSet::new()))
+}
+
+/// Rename every column to its `normalize_header` form. A column whose
+/// normalized name another column already has is left as is, with a warning.
+/// Returns the (original, normalized) renames made.
+pub fn normalize_headers(df: &mut DataFrame, source: &str) -> Result<Vec<(String, String)>> {
+    let names: Vec<String> = df.get_column_names().iter().map(|n| n.to_string()).collect();
+    let mut taken: HashSet<String> = names.iter().cloned().collect();
+    let mut renames = Vec::new();
+
+    for original in names {
+        let normalized = normalize_header(&original);
+        if normalized == original {
+            continue;
+        }
+        if taken.contains(&normalized) {
+            warn!("{}: not renaming header {:?} to {:?}, which another column already has", source, original, normalized);
+            continue;
+        }
+        df.rename(&original, &normalized)?;
+        taken.remove(&original);
+        taken.insert(normalized.clone());
+        renames.push((original, normalized));
+    }
+
+    for (original, normalized) in &renames {
+        debug!("{}: header {:?} -> {:?}", source, original, normalized);
+        let mut logged = logged_renames().lock().unwrap_or_else(|e| e.into_inner());
+        if logged.insert((original.clone(), normalized.clone())) {
+            info!("Normalized CSV header {:?} -> {:?} (first seen in {})", original, normalized, source);
+        }
+    }
+    Ok(renames)
+}
+
+/// Read a CSV with a header row and normalize its column names
+pub fn read_csv(path: &Path) -> Result<DataFrame> {
+    let file = std::fs::File::open(path)
+        .with_context(|| format!("Failed to open {}", path.display()))?;
+    let mut df = CsvReader::new(file)
+        .has_header(true)
+        .finish()
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    normalize_headers(&mut df, &path.display().to_string())?;
+    Ok(df)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_double_spaced_header_found_by_normalized_name() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("60d_SCED_Gen_Resource_Data-01-AUG-24.csv");
+        std::fs::write(&path, "\u{feff}SCED Time Stamp,Resource  Name , Resource Type,Base Point\n\
+                               08/01/2024 18:00:13,BATCAVE_BES1,PWRSTR,10.5\n").unwrap();
+
+        let df = read_csv(&path).unwrap();
+        assert_eq!(df.get_column_names(), ["SCED Time Stamp", "Resource Name", "Resource Type", "Base Point"]);
+        assert_eq!(df.column("Resource Name").unwrap().utf8().unwrap().get(0), Some("BATCAVE_BES1"));
+
+        // A clash with an existing column keeps the original name
+        let mut df = df! { "Resource Name" => &["A"], "Resource  Name" => &["B"] }.unwrap();
+        let renames = normalize_headers(&mut df, "clash.csv").unwrap();
+        assert!(renames.is_empty());
+        assert_eq!(df.get_column_names(), ["Resource Name", "Resource  Name"]);
+    }
+}
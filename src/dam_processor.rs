@@ -1,4 +1,5 @@
 use anyhow::Result;
+use log::{info, warn};
 use glob::glob;
 use indicatif::{ProgressBar, ProgressStyle};
 use polars::prelude::*;
@@ -6,10 +7,29 @@ use rayon::prelude::*;
 use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
 
+use crate::output_formats::OutputFormats;
+use crate::csv_headers::read_csv;
+use crate::empty_year::{schema_from_existing, write_empty_parquet};
+use crate::output_prefix::OutputPrefix;
+use crate::run_metadata;
+
+/// Columns of an ERCOT DAM Settlement Point Prices file, used for an empty
+/// year when no other year's output is there to copy the schema from
+fn dam_settlement_price_schema() -> Schema {
+    Schema::from_iter([
+        Field::new("DeliveryDate", DataType::Utf8),
+        Field::new("HourEnding", DataType::Utf8),
+        Field::new("SettlementPoint", DataType::Utf8),
+        Field::new("SettlementPointPrice", DataType::Float64),
+        Field::new("DSTFlag", DataType::Utf8),
+    ])
+}
+
 pub struct DamProcessor {
     data_dir: PathBuf,
     output_dir: PathBuf,
     extracted_dir: PathBuf,
+    formats: OutputFormats,
 }
 
 impl DamProcessor {
@@ -22,12 +42,18 @@ impl DamProcessor {
             data_dir,
             output_dir,
             extracted_dir,
+            formats: OutputFormats::default(),
         }
     }
 
+    /// Which of CSV, Parquet and Arrow to write for each year
+    pub fn with_formats(mut self, formats: OutputFormats) -> Self {
+        self.formats = formats;
+        self
+    }
+
     pub fn process_dam_settlement_prices(&self) -> Result<()> {
-        println!("\n📅 Processing DAM Settlement Point Prices");
-        println!("{}", "=".repeat(60));
+        info!("Processing DAM Settlement Point Prices");
         
         // Step 1: Extract any unextracted zip files
         self.extract_new_zip_files()?;
@@ -39,7 +65,7 @@ impl DamProcessor {
     }
 
     fn extract_new_zip_files(&self) -> Result<()> {
-        println!("🔍 Checking for new ZIP files to extract...");
+        info!("Checking for new ZIP files to extract...");
         
         // Find all zip files
         let pattern = self.data_dir.join("*.zip");
@@ -93,7 +119,7 @@ impl DamProcessor {
     }
 
     fn create_annual_rollups(&self) -> Result<()> {
-        println!("\n📊 Creating annual rollups...");
+        info!("Creating annual rollups...");
         
         // Find all extracted CSV files
         let pattern = self.extracted_dir.join("*.csv");
@@ -101,7 +127,8 @@ impl DamProcessor {
             .filter_map(Result::ok)
             .collect();
         
-        println!("Found {} CSV files to process", csv_files.len());
+        info!("Found {} CSV files to process", csv_files.len());
+        run_metadata::record_inputs("DAM Settlement Point Prices CSV", csv_files.len());
         
         // Group files by year
         let mut files_by_year: HashMap<u16, Vec<PathBuf>> = HashMap::new();
@@ -128,7 +155,7 @@ impl DamProcessor {
     }
 
     fn process_year_dam_files(&self, year: u16, files: &[PathBuf]) -> Result<()> {
-        println!("\n📅 Processing DAM year {}: {} files", year, files.len());
+        info!("Processing DAM year {}: {} files", year, files.len());
         
         let pb = ProgressBar::new(files.len() as u64);
         pb.set_style(ProgressStyle::default_bar()
@@ -146,9 +173,7 @@ impl DamProcessor {
                     pb.inc(1);
                     
                     // Read CSV
-                    let df = CsvReader::new(std::fs::File::open(file).ok()?)
-                        .has_header(true)
-                        .finish()
+                    let df = read_csv(file)
                         .ok()?;
                     
                     // Basic validation
@@ -167,11 +192,15 @@ impl DamProcessor {
         pb.finish_with_message("Files loaded");
         
         if all_dfs.is_empty() {
-            println!("  ❌ No valid data for year {}", year);
+            let parquet_path = self.output_dir.join(format!("DAM_Settlement_Point_Prices_{}.parquet", year));
+            warn!("No valid data for year {}; writing schema-only {}", year, parquet_path.display());
+            let schema = schema_from_existing(&self.output_dir.join("DAM_Settlement_Point_Prices_*.parquet"))
+                .unwrap_or_else(dam_settlement_price_schema);
+            write_empty_parquet(&parquet_path, &schema)?;
             return Ok(());
         }
         
-        println!("  📊 Combining {} dataframes...", all_dfs.len());
+        info!("Combining {} dataframes...", all_dfs.len());
         
         // Concatenate all dataframes
         let combined = concat(
@@ -179,7 +208,7 @@ impl DamProcessor {
             UnionArgs::default(),
         )?.collect()?;
         
-        println!("  📊 Combined records: {}", combined.height());
+        info!("Combined records: {}", combined.height());
         
         // Sort by timestamp if available
         let sorted = if combined.get_column_names().contains(&"DeliveryDate") {
@@ -193,34 +222,18 @@ impl DamProcessor {
         // Save files
         let base_name = format!("DAM_Settlement_Point_Prices_{}", year);
         
-        // CSV
-        let csv_path = self.output_dir.join(format!("{}.csv", base_name));
-        println!("  💾 Saving CSV...");
-        CsvWriter::new(std::fs::File::create(&csv_path)?)
-            .finish(&mut sorted.clone())?;
-        
-        // Parquet
-        let parquet_path = self.output_dir.join(format!("{}.parquet", base_name));
-        println!("  📦 Saving Parquet...");
-        ParquetWriter::new(std::fs::File::create(&parquet_path)?)
-            .finish(&mut sorted.clone())?;
-        
-        // Arrow IPC
-        let arrow_path = self.output_dir.join(format!("{}.arrow", base_name));
-        println!("  🏹 Saving Arrow IPC...");
-        IpcWriter::new(std::fs::File::create(&arrow_path)?)
-            .finish(&mut sorted.clone())?;
-        
-        println!("  ✅ Completed DAM year {}", year);
+        self.formats.write(&sorted, &self.output_dir, &base_name)?;
+        
+        info!("Completed DAM year {}", year);
         Ok(())
     }
 }
 
-pub fn process_all_dam_data() -> Result<()> {
+pub fn process_all_dam_data(formats: OutputFormats, output_prefix: &OutputPrefix) -> Result<()> {
     let data_dir = PathBuf::from("/Users/enrico/data/ERCOT_data/DAM_Settlement_Point_Prices");
-    let output_dir = PathBuf::from("dam_annual_data");
+    let output_dir = output_prefix.output_dir("dam_annual_data");
     
-    let processor = DamProcessor::new(data_dir, output_dir);
+    let processor = DamProcessor::new(data_dir, output_dir).with_formats(formats);
     processor.process_dam_settlement_prices()?;
     
     Ok(())
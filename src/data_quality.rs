@@ -0,0 +1,269 @@
+use anyhow::{bail, Context, Result};
+use chrono::DateTime;
+use polars::prelude::*;
+
+use crate::intervals::IntervalLength;
+
+/// Expected cadence by dataset, matched against output file names in order
+/// so that e.g. RT LMPs are checked at 5 minutes before other RT files at 15
+pub const DATASET_CADENCES: [(&str, IntervalLength); 9] = [
+    ("RT_LMPs", IntervalLength::FiveMinute),
+    ("LMPs_by_Resource_Nodes", IntervalLength::FiveMinute),
+    ("SCED", IntervalLength::FiveMinute),
+    ("RT_Settlement_Point_Prices", IntervalLength::FifteenMinute),
+    ("Settlement_Point_Prices_at_Resource_Nodes", IntervalLength::FifteenMinute),
+    ("Historical_RTM", IntervalLength::FifteenMinute),
+    ("DAM", IntervalLength::Hourly),
+    ("Ancillary", IntervalLength::Hourly),
+    ("Clearing_Prices_for_Capacity", IntervalLength::Hourly),
+];
+
+/// Cadence of the dataset `file_name` belongs to, if known
+pub fn dataset_cadence(file_name: &str) -> Option<IntervalLength> {
+    DATASET_CADENCES.into_iter()
+        .find(|(dataset, _)| file_name.contains(dataset))
+        .map(|(_, cadence)| cadence)
+}
+
+/// Steps between consecutive distinct timestamps longer than `cadence`, with
+/// up to half a cadence of slack so jittered SCED timestamps (e.g. 5:07
+/// apart) aren't gaps; `None` when `timestamps` isn't a datetime column.
+/// A column without a time zone is Central wall clock, so the hour skipped
+/// by spring-forward isn't a gap either.
+pub fn count_gaps(timestamps: &Series, cadence: IntervalLength) -> Result<Option<usize>> {
+    let DataType::Datetime(unit, tz) = timestamps.dtype() else {
+        return Ok(None);
+    };
+    let per_minute: i64 = match unit {
+        TimeUnit::Milliseconds => 60_000,
+        TimeUnit::Microseconds => 60_000_000,
+        TimeUnit::Nanoseconds => 60_000_000_000,
+    };
+    let wall_clock = tz.is_none();
+    let mut values: Vec<i64> = timestamps.datetime()?.into_iter().flatten().collect();
+    values.sort_unstable();
+    values.dedup();
+
+    let step = cadence.minutes() as i64 * per_minute;
+    let tolerance = step + step / 2;
+    Ok(Some(values.windows(2).filter(|w| {
+        let mut elapsed = w[1] - w[0];
+        if wall_clock && spans_spring_forward(w[0], w[1], per_minute) {
+            elapsed -= 60 * per_minute;
+        }
+        elapsed > tolerance
+    }).count()))
+}
+
+/// Whether wall-clock values `from` and `to` straddle the 02:00-03:00 hour a
+/// spring-forward day skips
+fn spans_spring_forward(from: i64, to: i64, per_minute: i64) -> bool {
+    let to_wall_clock = |value: i64| {
+        DateTime::from_timestamp_millis(value / (per_minute / 60_000)).map(|t| t.naive_utc())
+    };
+    let (Some(from), Some(to)) = (to_wall_clock(from), to_wall_clock(to)) else {
+        return false;
+    };
+    let date = from.date();
+    IntervalLength::Hourly.intervals_in_day(date) == 23
+        && from < date.and_hms_opt(2, 0, 0).unwrap()
+        && to >= date.and_hms_opt(3, 0, 0).unwrap()
+}
+
+/// Strict mode for `--verify-results --fail-on-gaps`: a file with more
+/// duplicate (time, location) rows or gaps than allowed fails the run, so CI
+/// can stop on broken outputs
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct QualityGate {
+    pub max_duplicates: usize,
+    pub max_gaps: usize,
+}
+
+impl QualityGate {
+    /// `--fail-on-gaps` turns the gate on, with `--max-duplicates <n>` and
+    /// `--max-gaps <n>` per file (default 0); `None` without the flag
+    pub fn from_args(args: &[String]) -> Result<Option<Self>> {
+        if !args.iter().any(|arg| arg == "--fail-on-gaps") {
+            return Ok(None);
+        }
+        let limit = |flag: &str| -> Result<usize> {
+            match args.iter().position(|arg| arg == flag).map(|i| args.get(i + 1)) {
+                None => Ok(0),
+                Some(Some(value)) => value.parse()
+                    .with_context(|| format!("Invalid {}: {}", flag, value)),
+                Some(None) => bail!("{} needs a count, e.g. {} 5", flag, flag),
+            }
+        };
+        Ok(Some(Self { max_duplicates: limit("--max-duplicates")?, max_gaps: limit("--max-gaps")? }))
+    }
+
+    /// Why a file with these counts fails the gate, if it does
+    pub fn violation(&self, duplicates: usize, gaps: usize) -> Option<String> {
+        let mut reasons = Vec::new();
+        if duplicates > self.max_duplicates {
+            reasons.push(format!("{} duplicates (max {})", duplicates, self.max_duplicates));
+        }
+        if gaps > self.max_gaps {
+            reasons.push(format!("{} gaps (max {})", gaps, self.max_gaps));
+        }
+        (!reasons.is_empty()).then(|| reasons.join(", "))
+    }
+}
+
+/// Price columns checked by `verify_data_quality`, in order of preference
+pub const PRICE_COLUMNS: [&str; 5] = ["SettlementPointPrice", "LMP", "Price", "price", "MCPC"];
+
+/// Why a location's prices look broken
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PriceSuspicion {
+    /// Every price is null
+    AllNull,
+    /// Every price has this one value
+    Constant(f64),
+}
+
+/// Price sanity figures for one location
+#[derive(Debug, Clone, PartialEq)]
+pub struct LocationPriceSummary {
+    pub location: String,
+    pub rows: usize,
+    /// Non-null prices
+    pub prices: usize,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    pub mean: Option<f64>,
+}
+
+impl LocationPriceSummary {
+    pub fn suspicion(&self) -> Option<PriceSuspicion> {
+        match (self.min, self.max) {
+            _ if self.prices == 0 => Some(PriceSuspicion::AllNull),
+            (Some(min), Some(max)) if min == max && self.rows > 1 => Some(PriceSuspicion::Constant(min)),
+            _ => None,
+        }
+    }
+}
+
+/// First of `PRICE_COLUMNS` in `df`
+pub fn price_column(df: &DataFrame) -> Option<&'static str> {
+    let names = df.get_column_names();
+    PRICE_COLUMNS.into_iter().find(|name| names.contains(name))
+}
+
+/// Min, max and mean price per location, sorted by location
+pub fn location_price_summaries(df: &DataFrame, location_col: &str, price_col: &str) -> Result<Vec<LocationPriceSummary>> {
+    let summary = df.clone().lazy()
+        .with_column(col(price_col).cast(DataType::Float64))
+        .group_by([col(location_col).cast(DataType::Utf8)])
+        .agg([
+            col(price_col).count().alias("rows"),
+            col(price_col).is_not_null().sum().alias("prices"),
+            col(price_col).min().alias("min"),
+            col(price_col).max().alias("max"),
+            col(price_col).mean().alias("mean"),
+        ])
+        .sort(location_col, Default::default())
+        .collect()?;
+
+    let locations = summary.column(location_col)?.utf8()?;
+    let rows = summary.column("rows")?.cast(&DataType::UInt64)?;
+    let prices = summary.column("prices")?.cast(&DataType::UInt64)?;
+    let (mins, maxs, means) = (summary.column("min")?.f64()?, summary.column("max")?.f64()?, summary.column("mean")?.f64()?);
+
+    Ok((0..summary.height())
+        .map(|i| LocationPriceSummary {
+            location: locations.get(i).unwrap_or_default().to_string(),
+            rows: rows.u64().ok().and_then(|r| r.get(i)).unwrap_or(0) as usize,
+            prices: prices.u64().ok().and_then(|p| p.get(i)).unwrap_or(0) as usize,
+            min: mins.get(i),
+            max: maxs.get(i),
+            mean: means.get(i),
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gaps_judged_against_dataset_cadence() {
+        assert_eq!(dataset_cadence("RT_Settlement_Point_Prices_2023.parquet"), Some(IntervalLength::FifteenMinute));
+        assert_eq!(dataset_cadence("RT_LMPs_2023.parquet"), Some(IntervalLength::FiveMinute));
+        assert_eq!(dataset_cadence("DAM_Settlement_Point_Prices_2023.parquet"), Some(IntervalLength::Hourly));
+        assert_eq!(dataset_cadence("bess_daily_revenues.parquet"), None);
+
+        // 15-minute prices with one missing interval at 00:30
+        let minutes = [0_i64, 15, 45, 60];
+        let timestamps = Series::new("datetime", minutes.iter().map(|m| m * 60_000).collect::<Vec<_>>())
+            .cast(&DataType::Datetime(TimeUnit::Milliseconds, None))
+            .unwrap();
+        assert_eq!(count_gaps(&timestamps, IntervalLength::FifteenMinute).unwrap(), Some(1));
+        // Under the old 5-minute assumption every step looked like a gap
+        assert_eq!(count_gaps(&timestamps, IntervalLength::FiveMinute).unwrap(), Some(3));
+
+        let us = timestamps.cast(&DataType::Datetime(TimeUnit::Microseconds, None)).unwrap();
+        assert_eq!(count_gaps(&us, IntervalLength::FifteenMinute).unwrap(), Some(1));
+        assert_eq!(count_gaps(&Series::new("DeliveryDate", &["01/01/2023"]), IntervalLength::Hourly).unwrap(), None);
+    }
+
+    #[test]
+    fn test_jittered_sced_timestamps_and_spring_forward_are_not_gaps() {
+        let base = chrono::NaiveDate::from_ymd_opt(2024, 3, 10).unwrap().and_hms_opt(0, 0, 0).unwrap();
+        let at = |seconds: i64| (base + chrono::Duration::seconds(seconds)).and_utc().timestamp_millis();
+        let as_datetime = |ms: Vec<i64>| Series::new("SCEDTimestamp", ms)
+            .cast(&DataType::Datetime(TimeUnit::Milliseconds, None))
+            .unwrap();
+
+        // SCED runs 5:07, 4:55 and 5:25 apart, then a 14:20 hole
+        let sced = as_datetime(vec![at(13), at(320), at(615), at(940), at(1800)]);
+        assert_eq!(count_gaps(&sced, IntervalLength::FiveMinute).unwrap(), Some(1));
+
+        // 01:55 to 03:00 on the spring-forward day is five minutes on the clock
+        let spring_forward = as_datetime(vec![at(115 * 60), at(180 * 60), at(185 * 60)]);
+        assert_eq!(count_gaps(&spring_forward, IntervalLength::FiveMinute).unwrap(), Some(0));
+        // The same jump on an ordinary day is a gap
+        let ordinary = as_datetime(vec![at(115 * 60 + 86_400), at(180 * 60 + 86_400)]);
+        assert_eq!(count_gaps(&ordinary, IntervalLength::FiveMinute).unwrap(), Some(1));
+    }
+
+    #[test]
+    fn test_all_zero_settlement_point_is_suspect() {
+        let df = df! {
+            "SettlementPoint" => &["HB_NORTH", "HB_NORTH", "BROKEN_RN", "BROKEN_RN", "EMPTY_RN", "EMPTY_RN"],
+            "SettlementPointPrice" => &[Some(20.0), Some(40.0), Some(0.0), Some(0.0), None, None],
+        }
+        .unwrap();
+
+        let summaries = location_price_summaries(&df, "SettlementPoint", price_column(&df).unwrap()).unwrap();
+        let by_location = |name: &str| summaries.iter().find(|s| s.location == name).unwrap();
+
+        let north = by_location("HB_NORTH");
+        assert_eq!((north.min, north.max, north.mean), (Some(20.0), Some(40.0), Some(30.0)));
+        assert_eq!(north.suspicion(), None);
+        assert_eq!(by_location("BROKEN_RN").suspicion(), Some(PriceSuspicion::Constant(0.0)));
+        assert_eq!(by_location("EMPTY_RN").rows, 2);
+        assert_eq!(by_location("EMPTY_RN").suspicion(), Some(PriceSuspicion::AllNull));
+        assert_eq!(summaries.iter().map(|s| s.location.as_str()).collect::<Vec<_>>(), ["BROKEN_RN", "EMPTY_RN", "HB_NORTH"]);
+    }
+
+    #[test]
+    fn test_fail_on_gaps_fails_only_files_over_the_limits() {
+        let args = |list: &[&str]| list.iter().map(|a| a.to_string()).collect::<Vec<_>>();
+        assert_eq!(QualityGate::from_args(&args(&["prog", "--verify-results"])).unwrap(), None);
+
+        let strict = QualityGate::from_args(&args(&["prog", "--verify-results", "--fail-on-gaps"])).unwrap().unwrap();
+        assert_eq!(strict, QualityGate { max_duplicates: 0, max_gaps: 0 });
+        assert_eq!(strict.violation(0, 0), None);
+        assert_eq!(strict.violation(0, 1).as_deref(), Some("1 gaps (max 0)"));
+
+        let lenient = QualityGate::from_args(&args(&["prog", "--verify-results", "--fail-on-gaps", "--max-gaps", "3"]))
+            .unwrap()
+            .unwrap();
+        assert_eq!(lenient.violation(0, 3), None);
+        assert_eq!(lenient.violation(2, 4).as_deref(), Some("2 duplicates (max 0), 4 gaps (max 3)"));
+
+        assert!(QualityGate::from_args(&args(&["prog", "--fail-on-gaps", "--max-gaps", "many"])).is_err());
+        assert!(QualityGate::from_args(&args(&["prog", "--fail-on-gaps", "--max-gaps"])).is_err());
+    }
+}
@@ -0,0 +1,230 @@
+use anyhow::{Context, Result};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Processed 15-minute RT settlement point price CSVs
+pub const RT_PRICES: &str = "rt_prices";
+/// Processed 5-minute RT LMP CSVs
+pub const RT_LMPS: &str = "rt_lmps";
+/// Processed DAM settlement point price / hourly LMP CSVs
+pub const DAM_PRICES: &str = "dam_prices";
+/// DAM ancillary service clearing price CSVs
+pub const ANCILLARY_PRICES: &str = "ancillary_prices";
+
+/// Built-in glob patterns, relative to the working directory
+const DEFAULT_PATTERNS: [(&str, &[&str]); 4] = [
+    (RT_PRICES, &[
+        "unified_processed_data/RT_Settlement_Point_Prices_*/RT_Settlement_Point_Prices_*.csv",
+        "annual_data/RT_Settlement_Point_Prices_*.csv",
+    ]),
+    (RT_LMPS, &[
+        "unified_processed_data/RT_LMPs_*/RT_LMPs_*.csv",
+        "annual_data/RT_LMPs_*.csv",
+    ]),
+    (DAM_PRICES, &[
+        "unified_processed_data/DAM_Settlement_Point_Prices_*/DAM_Settlement_Point_Prices_*.csv",
+        "unified_processed_data/DAM_Hourly_LMPs_*/DAM_Hourly_LMPs_*.csv",
+        "dam_annual_data/DAM_Settlement_Point_Prices_*.csv",
+        "dam_annual_data/DAM_Hourly_LMPs_*.csv",
+    ]),
+    (ANCILLARY_PRICES, &[
+        "unified_processed_data/DAM_Clearing_Prices_Capacity_*/DAM_Clearing_Prices_Capacity_*.csv",
+        "ancillary_annual_data/DAM_Clearing_Prices_Capacity_*.csv",
+    ]),
+];
+
+/// Glob patterns per input dataset, read from a TOML file with one table per
+/// dataset. Datasets the file leaves out keep the built-in patterns:
+///
+/// ```text
+/// [rt_prices]
+/// patterns = [
+///     "/data/ercot/rt/*.csv",
+/// ]
+///
+/// [dam_prices]
+/// patterns = ["/data/ercot/dam/*.csv"]
+/// ```
+#[derive(Debug, Clone)]
+pub struct DatasetPaths {
+    patterns: BTreeMap<String, Vec<String>>,
+}
+
+impl Default for DatasetPaths {
+    fn default() -> Self {
+        let patterns = DEFAULT_PATTERNS
+            .iter()
+            .map(|(dataset, patterns)| (dataset.to_string(), patterns.iter().map(|p| p.to_string()).collect()))
+            .collect();
+        Self { patterns }
+    }
+}
+
+/// Strip a `#` comment that isn't inside a quoted string
+fn strip_comment(line: &str) -> &str {
+    let mut in_string = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            '"' => in_string = !in_string,
+            '#' if !in_string => return &line[..i],
+            _ => {}
+        }
+    }
+    line
+}
+
+/// Quoted strings in an array body like `"a", "b",`
+fn parse_strings(body: &str, line_no: usize) -> Result<Vec<String>> {
+    let mut values = Vec::new();
+    let mut rest = body.trim();
+    while !rest.is_empty() {
+        let Some(after_quote) = rest.strip_prefix('"') else {
+            anyhow::bail!("line {}: expected a quoted pattern, found `{}`", line_no, rest);
+        };
+        let end = after_quote
+            .find('"')
+            .with_context(|| format!("line {}: unterminated string", line_no))?;
+        values.push(after_quote[..end].to_string());
+        rest = after_quote[end + 1..].trim_start();
+        rest = rest.strip_prefix(',').unwrap_or(rest).trim_start();
+    }
+    Ok(values)
+}
+
+impl DatasetPaths {
+    /// Parse the `[dataset]` / `patterns = [...]` subset of TOML this config uses
+    pub fn parse(contents: &str) -> Result<Self> {
+        let mut paths = Self::default();
+        let mut dataset: Option<String> = None;
+        // Open multi-line array: (dataset, collected patterns)
+        let mut open_array: Option<(String, Vec<String>)> = None;
+
+        for (idx, raw) in contents.lines().enumerate() {
+            let line_no = idx + 1;
+            let line = strip_comment(raw).trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            if let Some((name, mut values)) = open_array.take() {
+                match line.strip_suffix(']') {
+                    Some(body) => {
+                        values.extend(parse_strings(body, line_no)?);
+                        paths.patterns.insert(name, values);
+                    }
+                    None => {
+                        values.extend(parse_strings(line, line_no)?);
+                        open_array = Some((name, values));
+                    }
+                }
+                continue;
+            }
+
+            if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                dataset = Some(name.trim().to_string());
+                continue;
+            }
+
+            let Some((key, value)) = line.split_once('=') else {
+                anyhow::bail!("line {}: expected `[dataset]` or `patterns = [...]`", line_no);
+            };
+            if key.trim() != "patterns" {
+                anyhow::bail!("line {}: unknown key `{}`", line_no, key.trim());
+            }
+            let name = dataset
+                .clone()
+                .with_context(|| format!("line {}: `patterns` outside a [dataset] table", line_no))?;
+            let Some(array) = value.trim().strip_prefix('[') else {
+                anyhow::bail!("line {}: `patterns` must be an array of strings", line_no);
+            };
+            match array.trim_end().strip_suffix(']') {
+                Some(body) => {
+                    paths.patterns.insert(name, parse_strings(body, line_no)?);
+                }
+                None => open_array = Some((name, parse_strings(array, line_no)?)),
+            }
+        }
+
+        if let Some((name, _)) = open_array {
+            anyhow::bail!("unterminated `patterns` array for [{}]", name);
+        }
+        Ok(paths)
+    }
+
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read dataset config {}", path.display()))?;
+        Self::parse(&contents).with_context(|| format!("Failed to parse dataset config {}", path.display()))
+    }
+
+    /// Load from BESS_DATASETS_FILE (default bess_analysis/datasets.toml),
+    /// falling back to the built-in patterns when no file exists
+    pub fn load() -> Result<Self> {
+        let path = std::env::var("BESS_DATASETS_FILE")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| PathBuf::from("bess_analysis/datasets.toml"));
+
+        if path.exists() {
+            Self::from_file(&path)
+        } else {
+            Ok(Self::default())
+        }
+    }
+
+    pub fn patterns(&self, dataset: &str) -> &[String] {
+        self.patterns.get(dataset).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// Files matching the dataset's patterns, in pattern order
+    pub fn files(&self, dataset: &str) -> Result<Vec<PathBuf>> {
+        let mut files = Vec::new();
+        for pattern in self.patterns(dataset) {
+            files.extend(glob::glob(pattern)
+                .with_context(|| format!("Invalid {} pattern {}", dataset, pattern))?
+                .filter_map(Result::ok));
+        }
+        Ok(files)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_overrides_only_listed_datasets() {
+        let paths = DatasetPaths::parse(r#"
+            # Prices live on the shared drive
+            [rt_prices]
+            patterns = [
+                "/mnt/ercot/rt/*.csv",   # SPPs
+                "/mnt/ercot/rt_lmp/*.csv",
+            ]
+
+            [dam_prices]
+            patterns = ["/mnt/ercot/dam/*.csv"]
+        "#).unwrap();
+
+        assert_eq!(paths.patterns(RT_PRICES), ["/mnt/ercot/rt/*.csv", "/mnt/ercot/rt_lmp/*.csv"]);
+        assert_eq!(paths.patterns(DAM_PRICES), ["/mnt/ercot/dam/*.csv"]);
+        assert_eq!(paths.patterns(ANCILLARY_PRICES), DatasetPaths::default().patterns(ANCILLARY_PRICES));
+
+        assert!(DatasetPaths::parse("[rt_prices]\npaths = [\"x\"]").is_err());
+        assert!(DatasetPaths::parse("[rt_prices]\npatterns = [\n\"x\",\n").is_err());
+    }
+
+    #[test]
+    fn test_files_follow_configured_layout() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("prices/rt")).unwrap();
+        for name in ["RT_2023.csv", "RT_2024.csv", "notes.txt"] {
+            std::fs::write(dir.path().join("prices/rt").join(name), "").unwrap();
+        }
+
+        let config = format!("[rt_prices]\npatterns = [\"{}/prices/rt/RT_*.csv\"]\n", dir.path().display());
+        let files = DatasetPaths::parse(&config).unwrap().files(RT_PRICES).unwrap();
+        let names: Vec<_> = files.iter().map(|f| f.file_name().unwrap().to_str().unwrap()).collect();
+        assert_eq!(names, ["RT_2023.csv", "RT_2024.csv"]);
+    }
+}
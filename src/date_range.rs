@@ -0,0 +1,113 @@
+use anyhow::Result;
+use chrono::{Datelike, NaiveDate};
+use log::info;
+use std::path::Path;
+
+use crate::processing_filter::date_from_ercot_filename;
+
+/// Inclusive operating-day range for the BESS revenue calculators
+/// (`--start-date 2023-07-01 --end-date 2023-07-31`); either end may be open
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DateRange {
+    start: Option<NaiveDate>,
+    end: Option<NaiveDate>,
+}
+
+impl DateRange {
+    pub fn new(start: Option<NaiveDate>, end: Option<NaiveDate>) -> Result<Self> {
+        if let (Some(start), Some(end)) = (start, end) {
+            if start > end {
+                anyhow::bail!("--start-date {} is after --end-date {}", start, end);
+            }
+        }
+        Ok(Self { start, end })
+    }
+
+    /// Parse `--start-date` / `--end-date` as YYYY-MM-DD (or the
+    /// `--flag=value` forms); without either every day is included
+    pub fn from_args(args: &[String]) -> Result<Self> {
+        let date_arg = |flag: &str| -> Result<Option<NaiveDate>> {
+            let value = args.iter().enumerate().find_map(|(i, arg)| {
+                if arg == flag {
+                    Some(args.get(i + 1).cloned().unwrap_or_default())
+                } else {
+                    arg.strip_prefix(flag).and_then(|rest| rest.strip_prefix('=')).map(str::to_string)
+                }
+            });
+            value
+                .map(|v| NaiveDate::parse_from_str(&v, "%Y-%m-%d")
+                    .map_err(|_| anyhow::anyhow!("Invalid {}: {} (expected YYYY-MM-DD)", flag, v)))
+                .transpose()
+        };
+        Self::new(date_arg("--start-date")?, date_arg("--end-date")?)
+    }
+
+    pub fn is_unbounded(&self) -> bool {
+        self.start.is_none() && self.end.is_none()
+    }
+
+    pub fn contains(&self, date: NaiveDate) -> bool {
+        self.start.is_none_or(|start| date >= start) && self.end.is_none_or(|end| date <= end)
+    }
+
+    /// Whether any day of `year` is in range
+    pub fn includes_year(&self, year: i32) -> bool {
+        self.start.is_none_or(|start| year >= start.year()) && self.end.is_none_or(|end| year <= end.year())
+    }
+
+    /// Whether a disclosure CSV may hold in-range days. Files whose name
+    /// carries no operating day are kept.
+    pub fn includes_file(&self, path: &Path) -> bool {
+        let filename = path.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+        operating_day_from_filename(filename).is_none_or(|date| self.contains(date))
+    }
+
+    pub fn print_summary(&self) {
+        if self.is_unbounded() {
+            return;
+        }
+        let show = |date: Option<NaiveDate>| date.map_or("open".to_string(), |d| d.to_string());
+        info!("Only operating days {} to {}", show(self.start), show(self.end));
+    }
+}
+
+/// Operating day from 60-day disclosure CSV names such as
+/// `60d_SCED_Gen_Resource_Data-01-AUG-24.csv`, or the `.YYYYMMDD.` form
+/// other ERCOT extracts use
+pub fn operating_day_from_filename(filename: &str) -> Option<NaiveDate> {
+    let re = regex::Regex::new(r"-(\d{2}-[A-Za-z]{3}-\d{2})\.csv$").ok()?;
+    match re.captures(filename).and_then(|c| c.get(1)) {
+        Some(date) => NaiveDate::parse_from_str(date.as_str(), "%d-%b-%y").ok(),
+        None => date_from_ercot_filename(filename),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_july_range_skips_other_months_files() {
+        let july = DateRange::from_args(&args(&["prog", "--bess-revenue", "--start-date", "2023-07-01", "--end-date=2023-07-31"])).unwrap();
+        let date = |m, d| NaiveDate::from_ymd_opt(2023, m, d).unwrap();
+        assert!(july.contains(date(7, 1)));
+        assert!(july.contains(date(7, 31)));
+        assert!(!july.contains(date(6, 30)));
+        assert!(!july.contains(date(8, 1)));
+        assert!(july.includes_year(2023));
+        assert!(!july.includes_year(2022));
+
+        assert!(july.includes_file(Path::new("SCED_extracted/60d_SCED_Gen_Resource_Data-15-JUL-23.csv")));
+        assert!(!july.includes_file(Path::new("SCED_extracted/60d_SCED_Gen_Resource_Data-01-AUG-23.csv")));
+        assert!(!july.includes_file(Path::new("DAM_extracted/60d_DAM_Gen_Resource_Data-30-Jun-23.csv")));
+        assert!(july.includes_file(Path::new("DAM_extracted/60d_DAM_Gen_Resource_Data.csv")));
+
+        assert!(DateRange::from_args(&args(&["prog"])).unwrap().is_unbounded());
+        assert!(DateRange::from_args(&args(&["prog", "--start-date", "07/01/2023"])).is_err());
+        assert!(DateRange::from_args(&args(&["prog", "--start-date", "2023-08-01", "--end-date", "2023-07-01"])).is_err());
+    }
+}
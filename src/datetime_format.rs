@@ -0,0 +1,106 @@
+use anyhow::{bail, Result};
+use chrono::{DateTime, NaiveDateTime};
+use log::warn;
+use std::path::{Path, PathBuf};
+
+use crate::intervals::central_wall_clock;
+
+/// Version of the `datetime` convention the processors write today
+pub const DATETIME_FORMAT_VERSION: u32 = 2;
+
+/// Extension of the marker written beside each processed file, e.g.
+/// `RT_Settlement_Point_Prices_2024.datetime_format` for its CSV, Parquet and Arrow
+pub const MARKER_EXTENSION: &str = "datetime_format";
+
+/// What a processed file's `datetime` column (ms since the epoch) means
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DatetimeFormat {
+    /// Files written before version 2, which carry no marker: the Central
+    /// wall clock at the interval start written as if it were UTC (hour
+    /// ending 24 as 00:00 the next day)
+    WallClockAsUtc,
+    /// Version 2: the UTC instant the interval starts, so the repeated
+    /// fall-back hour keeps its own datetimes
+    Utc,
+}
+
+impl DatetimeFormat {
+    /// Convention of the processed file `path`, from its marker
+    pub fn of_file(path: &Path) -> Result<Self> {
+        let marker = marker_path(path);
+        match std::fs::read_to_string(&marker) {
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(DatetimeFormat::WallClockAsUtc),
+            Err(e) => Err(e.into()),
+            Ok(contents) => match contents.trim().parse::<u32>() {
+                Ok(DATETIME_FORMAT_VERSION) => Ok(DatetimeFormat::Utc),
+                _ => bail!("{} names unknown datetime format {:?}", marker.display(), contents.trim()),
+            },
+        }
+    }
+
+    /// `of_file`, warning when the file predates version 2 so the shift is visible
+    pub fn of_file_or_warn(path: &Path) -> Self {
+        match Self::of_file(path) {
+            Ok(DatetimeFormat::WallClockAsUtc) => {
+                warn!("{} has no .{} marker; reading its datetime as Central wall clock. Reprocess it to write version {}",
+                      path.display(), MARKER_EXTENSION, DATETIME_FORMAT_VERSION);
+                DatetimeFormat::WallClockAsUtc
+            }
+            Ok(format) => format,
+            Err(e) => {
+                warn!("{}; reading {} as version {}", e, path.display(), DATETIME_FORMAT_VERSION);
+                DatetimeFormat::Utc
+            }
+        }
+    }
+
+    /// Central wall clock at the start of a `datetime` value's interval
+    pub fn wall_clock(self, datetime_ms: i64) -> Option<NaiveDateTime> {
+        match self {
+            DatetimeFormat::WallClockAsUtc => DateTime::from_timestamp_millis(datetime_ms).map(|dt| dt.naive_utc()),
+            DatetimeFormat::Utc => central_wall_clock(datetime_ms),
+        }
+    }
+}
+
+fn marker_path(path: &Path) -> PathBuf {
+    path.with_extension(MARKER_EXTENSION)
+}
+
+/// Mark `<dir>/<base_name>.*` as written in the current convention
+pub fn mark_current(dir: &Path, base_name: &str) -> Result<()> {
+    let marker = dir.join(format!("{}.{}", base_name, MARKER_EXTENSION));
+    std::fs::write(marker, format!("{}\n", DATETIME_FORMAT_VERSION))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intervals::hour_ending_datetime_ms;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn test_unmarked_files_keep_their_wall_clock_and_marked_files_convert() {
+        let dir = tempfile::tempdir().unwrap();
+        let legacy = dir.path().join("DAM_Settlement_Point_Prices_2023.csv");
+        let current = dir.path().join("DAM_Settlement_Point_Prices_2024.csv");
+        mark_current(dir.path(), "DAM_Settlement_Point_Prices_2024").unwrap();
+
+        assert_eq!(DatetimeFormat::of_file(&legacy).unwrap(), DatetimeFormat::WallClockAsUtc);
+        assert_eq!(DatetimeFormat::of_file(&current).unwrap(), DatetimeFormat::Utc);
+        assert_eq!(DatetimeFormat::of_file(&current.with_extension("parquet")).unwrap(), DatetimeFormat::Utc);
+
+        // HE19 on a summer day starts at 18:00 Central either way
+        let date = NaiveDate::from_ymd_opt(2024, 8, 1).unwrap();
+        let wall_clock = date.and_hms_opt(18, 0, 0).unwrap();
+        let legacy_ms = wall_clock.and_utc().timestamp_millis();
+        let current_ms = hour_ending_datetime_ms(date, 19, 0, false).unwrap();
+        assert_eq!(current_ms - legacy_ms, 5 * 3_600_000);
+        assert_eq!(DatetimeFormat::WallClockAsUtc.wall_clock(legacy_ms), Some(wall_clock));
+        assert_eq!(DatetimeFormat::Utc.wall_clock(current_ms), Some(wall_clock));
+
+        std::fs::write(dir.path().join("DAM_Settlement_Point_Prices_2023.datetime_format"), "7\n").unwrap();
+        assert!(DatetimeFormat::of_file(&legacy).is_err());
+    }
+}
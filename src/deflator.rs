@@ -0,0 +1,118 @@
+use anyhow::{bail, Result};
+use log::warn;
+use polars::prelude::*;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::error::require_column;
+
+/// Price index by year (e.g. annual-average CPI-U) used to restate nominal
+/// revenues in constant dollars of `base_year`
+#[derive(Debug, Clone)]
+pub struct Deflator {
+    index: BTreeMap<i32, f64>,
+    base_year: i32,
+}
+
+impl Deflator {
+    /// `base_year` defaults to the latest year in the index
+    pub fn new(index: BTreeMap<i32, f64>, base_year: Option<i32>) -> Result<Self> {
+        let Some((&latest, _)) = index.iter().next_back() else {
+            bail!("Deflator index is empty");
+        };
+        let base_year = base_year.unwrap_or(latest);
+        if !index.contains_key(&base_year) {
+            bail!("Deflator index has no value for base year {}", base_year);
+        }
+        if let Some((year, value)) = index.iter().find(|(_, value)| **value <= 0.0) {
+            bail!("Deflator index for {} must be positive, got {}", year, value);
+        }
+        Ok(Self { index, base_year })
+    }
+
+    /// Load a `Year,Index` CSV
+    pub fn load(path: &Path, base_year: Option<i32>) -> Result<Self> {
+        let df = CsvReader::new(std::fs::File::open(path)?)
+            .has_header(true)
+            .finish()?;
+
+        let years = require_column(&df, "Year")?.cast(&DataType::Int32)?;
+        let values = require_column(&df, "Index")?.cast(&DataType::Float64)?;
+        let index = years.i32()?.into_iter()
+            .zip(values.f64()?.into_iter())
+            .filter_map(|(year, value)| Some((year?, value?)))
+            .collect();
+
+        Self::new(index, base_year)
+    }
+
+    pub fn base_year(&self) -> i32 {
+        self.base_year
+    }
+
+    /// Multiplier taking `year` dollars to base-year dollars
+    pub fn factor(&self, year: i32) -> Option<f64> {
+        Some(self.index[&self.base_year] / self.index.get(&year)?)
+    }
+
+    pub fn to_real(&self, nominal: f64, year: i32) -> Option<f64> {
+        self.factor(year).map(|factor| nominal * factor)
+    }
+
+    /// `{name}_Real` column of nominal amounts restated in base-year dollars;
+    /// null for years missing from the index
+    pub fn real_column(&self, name: &str, nominal: &[f64], years: &[i32]) -> Series {
+        let mut missing = Vec::new();
+        let real: Vec<Option<f64>> = nominal.iter()
+            .zip(years)
+            .map(|(amount, &year)| {
+                let real = self.to_real(*amount, year);
+                if real.is_none() && !missing.contains(&year) {
+                    missing.push(year);
+                }
+                real
+            })
+            .collect();
+        if !missing.is_empty() {
+            warn!("No deflator index for {:?}; {}_Real left empty for those years", missing, name);
+        }
+        Series::new(&format!("{}_Real", name), real)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Annual-average CPI-U
+    fn cpi() -> BTreeMap<i32, f64> {
+        BTreeMap::from([(2021, 270.970), (2022, 292.655), (2023, 304.702), (2024, 313.689)])
+    }
+
+    #[test]
+    fn test_2021_revenue_in_base_year_dollars() {
+        let deflator = Deflator::new(cpi(), Some(2024)).unwrap();
+
+        let real = deflator.to_real(1_000_000.0, 2021).unwrap();
+        assert!((real - 1_000_000.0 * 313.689 / 270.970).abs() < 1e-6);
+        assert_eq!(deflator.to_real(1_000_000.0, 2024), Some(1_000_000.0));
+
+        // Older base year deflates later revenue
+        let deflator = Deflator::new(cpi(), Some(2021)).unwrap();
+        assert!(deflator.to_real(1_000_000.0, 2024).unwrap() < 1_000_000.0);
+    }
+
+    #[test]
+    fn test_real_column_and_base_year_defaults() {
+        let deflator = Deflator::new(cpi(), None).unwrap();
+        assert_eq!(deflator.base_year(), 2024);
+
+        let column = deflator.real_column("Total_Revenue", &[270.970, 100.0], &[2021, 2019]);
+        assert_eq!(column.name(), "Total_Revenue_Real");
+        let values: Vec<Option<f64>> = column.f64().unwrap().into_iter().collect();
+        assert!((values[0].unwrap() - 313.689).abs() < 1e-9);
+        assert_eq!(values[1], None);
+
+        assert!(Deflator::new(cpi(), Some(2015)).is_err());
+    }
+}
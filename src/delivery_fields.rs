@@ -0,0 +1,84 @@
+use log::warn;
+
+/// Hour endings a row may carry: 1-24, plus 25 for the repeated fall-back
+/// hour in files that number it that way
+const HOUR_ENDINGS: std::ops::RangeInclusive<i32> = 1..=25;
+/// 15-minute DeliveryInterval within the hour
+const DELIVERY_INTERVALS: std::ops::RangeInclusive<i32> = 1..=4;
+
+/// Tally of DeliveryHour / DeliveryInterval values outside ERCOT's ranges.
+/// Such rows are counted and left without a datetime, never guessed at, so
+/// they don't disappear silently. Pairs that would be valid the other way
+/// round are flagged as likely swapped columns.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DeliveryFieldCheck {
+    /// Invalid rows whose hour and interval would be valid swapped
+    pub swapped: usize,
+    /// Rows left without a datetime
+    pub invalid: usize,
+    /// Rows with an hour outside 1-24 (or 25)
+    pub bad_hour: usize,
+    /// Rows with an interval outside 1-4
+    pub bad_interval: usize,
+}
+
+impl DeliveryFieldCheck {
+    /// `(hour_ending, interval)` to build the datetime from, or `None` for
+    /// an invalid row
+    pub fn check(&mut self, hour: i32, interval: i32) -> Option<(u32, u32)> {
+        let hour_ok = HOUR_ENDINGS.contains(&hour);
+        let interval_ok = DELIVERY_INTERVALS.contains(&interval);
+        if hour_ok && interval_ok {
+            return Some((hour as u32, interval as u32));
+        }
+        if HOUR_ENDINGS.contains(&interval) && DELIVERY_INTERVALS.contains(&hour) {
+            self.swapped += 1;
+        }
+        self.invalid += 1;
+        if !hour_ok {
+            self.bad_hour += 1;
+        }
+        if !interval_ok {
+            self.bad_interval += 1;
+        }
+        None
+    }
+
+    pub fn is_clean(&self) -> bool {
+        self.invalid == 0
+    }
+
+    /// Warn about invalid rows for `label` (e.g. a dataset and year)
+    pub fn log(&self, label: &str) {
+        if self.invalid > 0 {
+            warn!(
+                "{}: {} rows have no datetime ({} with DeliveryHour outside 1-24, {} with DeliveryInterval outside 1-4)",
+                label, self.invalid, self.bad_hour, self.bad_interval
+            );
+        }
+        if self.swapped > 0 {
+            warn!("{}: {} of those rows look like DeliveryHour and DeliveryInterval swapped; check the source file",
+                  label, self.swapped);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bad_rows_counted_and_swapped_pairs_flagged_not_repaired() {
+        let mut check = DeliveryFieldCheck::default();
+        assert_eq!(check.check(18, 3), Some((18, 3)));
+        assert_eq!(check.check(25, 1), Some((25, 1)));
+        // Interval 18 in hour 3 only makes sense swapped, but isn't guessed at
+        assert_eq!(check.check(3, 18), None);
+        assert_eq!(check.check(30, 2), None);
+        assert_eq!(check.check(12, 7), None);
+        assert_eq!(check.check(0, 0), None);
+
+        assert_eq!(check, DeliveryFieldCheck { swapped: 1, invalid: 4, bad_hour: 2, bad_interval: 3 });
+        assert!(!check.is_clean());
+    }
+}
@@ -1,4 +1,5 @@
 use anyhow::Result;
+use log::{debug, info, warn};
 use glob::glob;
 use indicatif::{ProgressBar, ProgressStyle};
 use polars::prelude::*;
@@ -6,6 +7,10 @@ use rayon::prelude::*;
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
+use crate::csv_headers::read_csv;
+use crate::output_prefix::OutputPrefix;
+use crate::run_metadata;
+
 pub struct DisclosureFastProcessor {
     output_dir: PathBuf,
 }
@@ -17,8 +22,7 @@ impl DisclosureFastProcessor {
     }
 
     pub fn process_extracted_disclosures(&self) -> Result<()> {
-        println!("📊 Fast Processing 60-Day Disclosure Reports (from extracted CSVs)");
-        println!("{}", "=".repeat(80));
+        info!("Fast Processing 60-Day Disclosure Reports (from extracted CSVs)");
         
         let disclosure_dirs = vec![
             ("disclosure_data/SCED_extracted", "SCED"),
@@ -31,10 +35,10 @@ impl DisclosureFastProcessor {
         for (dir_path, report_type) in disclosure_dirs {
             let extracted_dir = PathBuf::from(dir_path);
             if extracted_dir.exists() {
-                println!("\n📁 Processing {} extracted files", report_type);
+                info!("Processing {} extracted files", report_type);
                 self.process_report_type(&extracted_dir, report_type)?;
             } else {
-                println!("⚠️  Directory not found: {}", dir_path);
+                warn!("Directory not found: {}", dir_path);
             }
         }
         
@@ -49,11 +53,12 @@ impl DisclosureFastProcessor {
             .collect();
         
         if csv_files.is_empty() {
-            println!("  ❌ No CSV files found");
+            warn!("No CSV files found");
             return Ok(());
         }
         
-        println!("  📊 Found {} CSV files to process", csv_files.len());
+        info!("Found {} CSV files to process", csv_files.len());
+        run_metadata::record_inputs("60-day disclosure CSV", csv_files.len());
         
         // Sample first file to understand structure
         if let Some(first_file) = csv_files.first() {
@@ -71,7 +76,7 @@ impl DisclosureFastProcessor {
         
         let mut years: Vec<u16> = files_by_year.keys().cloned().collect();
         years.sort();
-        println!("  Years found: {:?}", years);
+        info!("Years found: {:?}", years);
         
         // Process each year
         for (year, year_files) in files_by_year {
@@ -82,14 +87,12 @@ impl DisclosureFastProcessor {
     }
 
     fn analyze_file_structure(&self, file_path: &Path, report_type: &str) -> Result<()> {
-        println!("  🔍 Analyzing {} file structure...", report_type);
+        info!("Analyzing {} file structure...", report_type);
         
-        let df = CsvReader::new(std::fs::File::open(file_path)?)
-            .has_header(true)
-            .finish()?;
+        let df = read_csv(file_path)?;
         
-        println!("    Columns: {:?}", df.get_column_names());
-        println!("    Shape: {} rows x {} columns", df.height(), df.width());
+        debug!("Columns: {:?}", df.get_column_names());
+        debug!("Shape: {} rows x {} columns", df.height(), df.width());
         
         // Look for resource-related columns
         let columns = df.get_column_names();
@@ -101,7 +104,7 @@ impl DisclosureFastProcessor {
             .collect();
         
         if !resource_columns.is_empty() {
-            println!("    Resource columns found: {:?}", resource_columns);
+            debug!("Resource columns found: {:?}", resource_columns);
             
             // Check for BESS resources
             for col in &resource_columns {
@@ -116,7 +119,7 @@ impl DisclosureFastProcessor {
                             .collect::<Vec<_>>();
                         
                         if !bess_count.is_empty() {
-                            println!("    🔋 Found BESS resources in column '{}': {:?}", col, bess_count);
+                            debug!("Found BESS resources in column '{}': {:?}", col, bess_count);
                         }
                     }
                 }
@@ -146,7 +149,7 @@ impl DisclosureFastProcessor {
     }
 
     fn process_year_files(&self, year: u16, files: &[PathBuf], report_type: &str) -> Result<()> {
-        println!("    📅 Processing {} year {}: {} files", report_type, year, files.len());
+        debug!("Processing {} year {}: {} files", report_type, year, files.len());
         
         let pb = ProgressBar::new(files.len() as u64);
         pb.set_style(ProgressStyle::default_bar()
@@ -162,9 +165,7 @@ impl DisclosureFastProcessor {
                 .par_iter()
                 .filter_map(|file| {
                     pb.inc(1);
-                    match CsvReader::new(std::fs::File::open(file).ok()?)
-                        .has_header(true)
-                        .finish() {
+                    match read_csv(file) {
                         Ok(df) => {
                             let rows = df.height();
                             Some((df, rows))
@@ -183,16 +184,16 @@ impl DisclosureFastProcessor {
         pb.finish();
         
         if all_dfs.is_empty() {
-            println!("      ❌ No valid data");
+            warn!("No valid data");
             return Ok(());
         }
         
-        println!("      📊 Loaded {} dataframes with {} total rows", all_dfs.len(), total_rows);
+        debug!("Loaded {} dataframes with {} total rows", all_dfs.len(), total_rows);
         
         // For now, just save a sample of the data to understand structure
         if year == 2024 || year == 2025 {
             // Concatenate all dataframes
-            println!("      🔗 Concatenating dataframes...");
+            debug!("Concatenating dataframes...");
             let combined = concat(
                 all_dfs.iter().map(|df| df.clone().lazy()).collect::<Vec<_>>().as_slice(),
                 UnionArgs::default(),
@@ -206,7 +207,7 @@ impl DisclosureFastProcessor {
             ParquetWriter::new(std::fs::File::create(&parquet_path)?)
                 .finish(&mut combined.clone())?;
             
-            println!("      ✅ Saved {} records to {}", combined.height(), parquet_path.display());
+            debug!("Saved {} records to {}", combined.height(), parquet_path.display());
             
             // Analyze for BESS resources
             self.find_bess_resources(&combined, report_type, year)?;
@@ -237,13 +238,13 @@ impl DisclosureFastProcessor {
                             .collect();
                         
                         if !bess_resources.is_empty() {
-                            println!("      🔋 Found {} unique BESS resources in {} {} column '{}':", 
+                            debug!("Found {} unique BESS resources in {} {} column '{}':", 
                                     bess_resources.len(), report_type, year, col);
                             for (i, resource) in bess_resources.iter().take(10).enumerate() {
-                                println!("         {}: {}", i+1, resource);
+                                debug!("{}: {}", i+1, resource);
                             }
                             if bess_resources.len() > 10 {
-                                println!("         ... and {} more", bess_resources.len() - 10);
+                                debug!("... and {} more", bess_resources.len() - 10);
                             }
                         }
                     }
@@ -255,8 +256,8 @@ impl DisclosureFastProcessor {
     }
 }
 
-pub fn process_disclosure_fast() -> Result<()> {
-    let output_dir = PathBuf::from("disclosure_data");
+pub fn process_disclosure_fast(output_prefix: &OutputPrefix) -> Result<()> {
+    let output_dir = output_prefix.output_dir("disclosure_data");
     let processor = DisclosureFastProcessor::new(output_dir)?;
     processor.process_extracted_disclosures()?;
     Ok(())
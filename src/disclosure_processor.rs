@@ -1,4 +1,5 @@
 use anyhow::Result;
+use log::{debug, info, warn};
 use glob::glob;
 use indicatif::{ProgressBar, ProgressStyle};
 use polars::prelude::*;
@@ -6,20 +7,31 @@ use rayon::prelude::*;
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
+use crate::output_formats::OutputFormats;
+use crate::csv_headers::read_csv;
+use crate::output_prefix::OutputPrefix;
+use crate::run_metadata;
+
 pub struct DisclosureProcessor {
     base_dir: PathBuf,
     output_dir: PathBuf,
+    formats: OutputFormats,
 }
 
 impl DisclosureProcessor {
     pub fn new(base_dir: PathBuf, output_dir: PathBuf) -> Result<Self> {
         std::fs::create_dir_all(&output_dir)?;
-        Ok(Self { base_dir, output_dir })
+        Ok(Self { base_dir, output_dir, formats: OutputFormats::default() })
+    }
+
+    /// Which of CSV, Parquet and Arrow to write for each year
+    pub fn with_formats(mut self, formats: OutputFormats) -> Self {
+        self.formats = formats;
+        self
     }
 
     pub fn process_all_60_day_disclosures(&self) -> Result<()> {
-        println!("📊 Processing 60-Day Disclosure Reports");
-        println!("{}", "=".repeat(80));
+        info!("Processing 60-Day Disclosure Reports");
         
         let disclosure_folders = vec![
             ("60-Day_SCED_Disclosure_Reports", "SCED"),
@@ -32,10 +44,10 @@ impl DisclosureProcessor {
         for (folder_name, report_type) in disclosure_folders {
             let folder_path = self.base_dir.join(folder_name);
             if folder_path.exists() {
-                println!("\n📁 Processing {}", folder_name);
+                info!("Processing {}", folder_name);
                 self.process_disclosure_folder(&folder_path, report_type)?;
             } else {
-                println!("⚠️  Folder not found: {}", folder_name);
+                warn!("Folder not found: {}", folder_name);
             }
         }
         
@@ -61,13 +73,13 @@ impl DisclosureProcessor {
             .filter_map(Result::ok)
             .collect();
         
-        println!("  🗜️  Found {} ZIP files to extract", zip_files.len());
+        info!("Found {} ZIP files to extract", zip_files.len());
         
         // Check if already extracted
         let csv_pattern = extract_dir.join("*.csv");
         let existing_csv_count = glob(csv_pattern.to_str().unwrap())?.count();
         if existing_csv_count > 0 {
-            println!("  ✅ Already extracted {} CSV files, skipping extraction", existing_csv_count);
+            info!("Already extracted {} CSV files, skipping extraction", existing_csv_count);
             return Ok(());
         }
         
@@ -129,11 +141,12 @@ impl DisclosureProcessor {
             .collect();
         
         if csv_files.is_empty() {
-            println!("  ❌ No CSV files found");
+            warn!("No CSV files found");
             return Ok(());
         }
         
-        println!("  📊 Found {} CSV files to process", csv_files.len());
+        info!("Found {} CSV files to process", csv_files.len());
+        run_metadata::record_inputs("60-day disclosure CSV", csv_files.len());
         
         // Group by year
         let mut files_by_year: HashMap<u16, Vec<PathBuf>> = HashMap::new();
@@ -180,7 +193,7 @@ impl DisclosureProcessor {
     }
 
     fn process_year_files(&self, year: u16, files: &[PathBuf], report_type: &str) -> Result<()> {
-        println!("    📅 Processing {} year {}: {} files", report_type, year, files.len());
+        debug!("Processing {} year {}: {} files", report_type, year, files.len());
         
         let pb = ProgressBar::new(files.len() as u64);
         pb.set_style(ProgressStyle::default_bar()
@@ -195,9 +208,7 @@ impl DisclosureProcessor {
                 .par_iter()
                 .filter_map(|file| {
                     pb.inc(1);
-                    CsvReader::new(std::fs::File::open(file).ok()?)
-                        .has_header(true)
-                        .finish()
+                    read_csv(file)
                         .ok()
                 })
                 .collect();
@@ -208,7 +219,7 @@ impl DisclosureProcessor {
         pb.finish();
         
         if all_dfs.is_empty() {
-            println!("      ❌ No valid data");
+            warn!("No valid data");
             return Ok(());
         }
         
@@ -221,31 +232,18 @@ impl DisclosureProcessor {
         // Save output files
         let base_name = format!("60_Day_{}_Disclosure_{}", report_type, year);
         
-        // CSV
-        let csv_path = self.output_dir.join(format!("{}.csv", base_name));
-        CsvWriter::new(std::fs::File::create(&csv_path)?)
-            .finish(&mut combined.clone())?;
-        
-        // Parquet
-        let parquet_path = self.output_dir.join(format!("{}.parquet", base_name));
-        ParquetWriter::new(std::fs::File::create(&parquet_path)?)
-            .finish(&mut combined.clone())?;
-        
-        // Arrow
-        let arrow_path = self.output_dir.join(format!("{}.arrow", base_name));
-        IpcWriter::new(std::fs::File::create(&arrow_path)?)
-            .finish(&mut combined.clone())?;
+        self.formats.write(&combined, &self.output_dir, &base_name)?;
         
-        println!("      ✅ Saved {} records", combined.height());
+        debug!("Saved {} records", combined.height());
         Ok(())
     }
 }
 
-pub fn process_all_disclosures() -> Result<()> {
+pub fn process_all_disclosures(formats: OutputFormats, output_prefix: &OutputPrefix) -> Result<()> {
     let base_dir = PathBuf::from("/Users/enrico/data/ERCOT_data");
-    let output_dir = PathBuf::from("disclosure_data");
+    let output_dir = output_prefix.output_dir("disclosure_data");
     
-    let processor = DisclosureProcessor::new(base_dir, output_dir)?;
+    let processor = DisclosureProcessor::new(base_dir, output_dir)?.with_formats(formats);
     processor.process_all_60_day_disclosures()?;
     
     Ok(())
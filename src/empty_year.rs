@@ -0,0 +1,63 @@
+use anyhow::{Context, Result};
+use glob::glob;
+use log::warn;
+use polars::prelude::*;
+use std::path::Path;
+
+/// Schema of the first readable Parquet matching `pattern`, e.g. another
+/// year's output of the same dataset, for writing an empty year whose own
+/// files gave no rows to infer one from
+pub fn schema_from_existing(pattern: &Path) -> Option<Schema> {
+    let files = glob(pattern.to_str()?).ok()?.filter_map(Result::ok);
+    for file in files {
+        let df = std::fs::File::open(&file)
+            .map_err(PolarsError::from)
+            .and_then(|f| ParquetReader::new(f).with_n_rows(Some(1)).finish());
+        match df {
+            Ok(df) => return Some(df.schema()),
+            Err(e) => warn!("Skipping unreadable {} while looking for a schema: {}", file.display(), e),
+        }
+    }
+    None
+}
+
+/// Write a zero-row Parquet with `schema` to `path`, so loaders that expect
+/// one file per year read an empty year instead of failing on a missing file
+pub fn write_empty_parquet(path: &Path, schema: &Schema) -> Result<()> {
+    let mut df = DataFrame::from(schema);
+    let file = std::fs::File::create(path)
+        .with_context(|| format!("Failed to create {}", path.display()))?;
+    ParquetWriter::new(file).finish(&mut df)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_empty_year_reads_back_with_sibling_schema_and_no_rows() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut populated = df! {
+            "DeliveryDate" => &["01/01/2023"],
+            "HourEnding" => &["01:00"],
+            "SettlementPoint" => &["HB_NORTH"],
+            "SettlementPointPrice" => &[25.5],
+        }
+        .unwrap();
+        ParquetWriter::new(std::fs::File::create(dir.path().join("DAM_2023.parquet")).unwrap())
+            .finish(&mut populated)
+            .unwrap();
+
+        let schema = schema_from_existing(&dir.path().join("DAM_*.parquet")).unwrap();
+        let empty_path = dir.path().join("DAM_2024.parquet");
+        write_empty_parquet(&empty_path, &schema).unwrap();
+
+        let empty = ParquetReader::new(std::fs::File::open(&empty_path).unwrap()).finish().unwrap();
+        assert_eq!(empty.height(), 0);
+        assert_eq!(empty.schema(), populated.schema());
+        assert_eq!(empty.column("SettlementPointPrice").unwrap().f64().unwrap().len(), 0);
+
+        assert!(schema_from_existing(&dir.path().join("RT_*.parquet")).is_none());
+    }
+}
@@ -1,4 +1,5 @@
 use anyhow::Result;
+use log::info;
 use glob::glob;
 use indicatif::{ProgressBar, ProgressStyle};
 use polars::prelude::*;
@@ -26,15 +27,14 @@ impl ErcotProcessor {
     }
 
     pub fn process_historical_dam(&self, data_dir: &Path) -> Result<()> {
-        println!("\n🏛️  Processing Historical DAM Load Zone and Hub Prices");
-        println!("{}", "=".repeat(60));
+        info!("Processing Historical DAM Load Zone and Hub Prices");
         
         let pattern = data_dir.join("*.zip");
         let zip_files: Vec<PathBuf> = glob(pattern.to_str().unwrap())?
             .filter_map(Result::ok)
             .collect();
         
-        println!("Found {} historical DAM files", zip_files.len());
+        info!("Found {} historical DAM files", zip_files.len());
         
         let pb = ProgressBar::new(zip_files.len() as u64);
         pb.set_style(ProgressStyle::default_bar()
@@ -85,15 +85,14 @@ impl ErcotProcessor {
     }
 
     pub fn process_historical_rtm(&self, data_dir: &Path) -> Result<()> {
-        println!("\n🏛️  Processing Historical RTM Load Zone and Hub Prices");
-        println!("{}", "=".repeat(60));
+        info!("Processing Historical RTM Load Zone and Hub Prices");
         
         let pattern = data_dir.join("*.zip");
         let zip_files: Vec<PathBuf> = glob(pattern.to_str().unwrap())?
             .filter_map(Result::ok)
             .collect();
         
-        println!("Found {} historical RTM files", zip_files.len());
+        info!("Found {} historical RTM files", zip_files.len());
         
         let pb = ProgressBar::new(zip_files.len() as u64);
         pb.set_style(ProgressStyle::default_bar()
@@ -144,8 +143,7 @@ impl ErcotProcessor {
     }
 
     pub fn process_daily_dam(&self, data_dir: &Path) -> Result<()> {
-        println!("\n📅 Processing Daily DAM Settlement Point Prices");
-        println!("{}", "=".repeat(60));
+        info!("Processing Daily DAM Settlement Point Prices");
         
         // Find all CSV files (unzipped)
         let pattern = data_dir.join("*.csv");
@@ -153,7 +151,7 @@ impl ErcotProcessor {
             .filter_map(Result::ok)
             .collect();
         
-        println!("Found {} DAM CSV files", csv_files.len());
+        info!("Found {} DAM CSV files", csv_files.len());
         
         if csv_files.is_empty() {
             // Try processing zip files
@@ -162,7 +160,7 @@ impl ErcotProcessor {
                 .filter_map(Result::ok)
                 .collect();
             
-            println!("Found {} DAM ZIP files to process", zip_files.len());
+            info!("Found {} DAM ZIP files to process", zip_files.len());
             
             // Group by year
             let mut files_by_year: HashMap<u16, Vec<PathBuf>> = HashMap::new();
@@ -180,7 +178,7 @@ impl ErcotProcessor {
             }
             
             for (year, year_files) in files_by_year {
-                println!("\n📅 Processing DAM year {}: {} files", year, year_files.len());
+                info!("Processing DAM year {}: {} files", year, year_files.len());
                 
                 let pb = ProgressBar::new(year_files.len() as u64);
                 pb.set_style(ProgressStyle::default_bar()
@@ -217,7 +215,7 @@ impl ErcotProcessor {
                 pb.finish();
                 
                 if !all_dfs.is_empty() {
-                    println!("  📊 Combining {} DAM dataframes...", all_dfs.len());
+                    info!("Combining {} DAM dataframes...", all_dfs.len());
                     
                     // Concatenate all dataframes
                     let combined = concat(
@@ -230,7 +228,7 @@ impl ErcotProcessor {
                     ParquetWriter::new(std::fs::File::create(&output_path)?)
                         .finish(&mut combined.clone())?;
                     
-                    println!("  ✅ Saved DAM data for year {}", year);
+                    info!("Saved DAM data for year {}", year);
                 }
             }
         }
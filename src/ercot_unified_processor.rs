@@ -1,5 +1,6 @@
 use anyhow::Result;
-use chrono::{NaiveDate, NaiveDateTime, Datelike, Duration};
+use log::{debug, error, info, warn};
+use chrono::{NaiveDate, NaiveDateTime, Datelike};
 use indicatif::{ProgressBar, ProgressStyle, MultiProgress};
 use polars::prelude::*;
 use rayon::prelude::*;
@@ -7,11 +8,100 @@ use std::collections::HashMap;
 use std::fs;
 use std::io::Read;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
 
+use crate::combine_progress::row_progress_bar;
+use crate::output_formats::OutputFormats;
+use crate::processing_filter::ProcessingFilter;
+use crate::row_counts::RowCounts;
+use crate::delivery_fields::DeliveryFieldCheck;
+use crate::datetime_format;
+use crate::intervals::{delivery_interval_datetime_ms, hour_ending_datetime_ms, is_repeated_hour, operating_day_datetime_ms};
+use crate::csv_extractor::{is_gzipped_csv, read_gz_to_vec};
+use crate::csv_headers::normalize_headers;
+use crate::output_prefix::OutputPrefix;
+use crate::run_metadata;
+
 pub struct UnifiedProcessor {
     base_dir: PathBuf,
     output_dir: PathBuf,
+    parquet_options: ParquetOptions,
+    filter: ProcessingFilter,
+    formats: OutputFormats,
+}
+
+/// Parquet layout for annual files, configured via environment variables:
+/// - PARQUET_ROW_GROUP_SIZE: rows per row group (default: Polars default)
+/// - PARQUET_STATISTICS: write min/max column statistics (default: 1)
+/// - PARQUET_CLUSTER_COLUMN: sort the Parquet copy by this column first (e.g.
+///   SettlementPointName) so row-group statistics can prune on it
+#[derive(Debug, Clone)]
+pub struct ParquetOptions {
+    pub row_group_size: Option<usize>,
+    pub statistics: bool,
+    pub cluster_column: Option<String>,
+}
+
+impl ParquetOptions {
+    pub fn from_env() -> Self {
+        let row_group_size = std::env::var("PARQUET_ROW_GROUP_SIZE")
+            .ok()
+            .and_then(|v| v.parse::<usize>().ok())
+            .filter(|&size| size > 0);
+        let statistics = std::env::var("PARQUET_STATISTICS").unwrap_or_default() != "0";
+        let cluster_column = std::env::var("PARQUET_CLUSTER_COLUMN")
+            .ok()
+            .filter(|v| !v.is_empty());
+
+        Self { row_group_size, statistics, cluster_column }
+    }
+
+    /// Parquet writer with these row-group and statistics settings
+    pub fn writer<W: std::io::Write>(&self, writer: W) -> ParquetWriter<W> {
+        ParquetWriter::new(writer)
+            .with_statistics(self.statistics)
+            .with_row_group_size(self.row_group_size)
+    }
+}
+
+/// CSVs of one dataset that never reach its annual files, by reason, so
+/// files dropped while parsing are counted rather than silently skipped.
+/// Years left out by `--only-years` are not exclusions.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FileExclusions {
+    /// CSVs handed to the parser
+    pub files: usize,
+    pub unreadable: usize,
+    pub missing_date_column: usize,
+    /// Date column present, but its first value is empty or doesn't parse
+    pub unparseable_date: usize,
+    /// Dated outside the 2010-2025 sanity range
+    pub out_of_range_year: usize,
+}
+
+impl FileExclusions {
+    pub fn excluded(&self) -> usize {
+        self.unreadable + self.missing_date_column + self.unparseable_date + self.out_of_range_year
+    }
+
+    pub fn summary(&self, date_column: &str) -> String {
+        let pct = if self.files == 0 { 0.0 } else { self.excluded() as f64 / self.files as f64 * 100.0 };
+        format!(
+            "{} of {} files excluded ({:.1}%): {} missing the {} column, {} unreadable, {} with an unparseable date, {} dated outside 2010-2025",
+            self.excluded(), self.files, pct, self.missing_date_column, date_column,
+            self.unreadable, self.unparseable_date, self.out_of_range_year
+        )
+    }
+
+    /// Log the summary for `label`, as a warning if any file was excluded
+    pub fn log(&self, label: &str, date_column: &str) {
+        if self.excluded() > 0 {
+            warn!("{}: {}", label, self.summary(date_column));
+        } else {
+            info!("{}: {}", label, self.summary(date_column));
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -26,18 +116,42 @@ pub struct DatasetConfig {
 
 impl UnifiedProcessor {
     pub fn new(base_dir: PathBuf, output_dir: PathBuf) -> Self {
-        Self { base_dir, output_dir }
+        Self {
+            base_dir,
+            output_dir,
+            parquet_options: ParquetOptions::from_env(),
+            filter: ProcessingFilter::default(),
+            formats: OutputFormats::default(),
+        }
+    }
+    
+    /// Restrict the run to selected years/datasets
+    pub fn with_filter(mut self, filter: ProcessingFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+    
+    /// Which of CSV, Parquet and Arrow to write for each year
+    pub fn with_formats(mut self, formats: OutputFormats) -> Self {
+        self.formats = formats;
+        self
     }
     
     pub fn process_all_datasets(&self) -> Result<()> {
-        // Configure Rayon to use all available cores
+        // Configure Rayon to use all available cores (a no-op when the
+        // binary already configured the pool, e.g. from --max-threads)
         rayon::ThreadPoolBuilder::new()
             .num_threads(num_cpus::get())
             .build_global()
             .unwrap_or_else(|_| {});
             
-        println!("🚀 ERCOT Unified Data Processor");
-        println!("Using {} CPU cores", rayon::current_num_threads());
+        info!("ERCOT Unified Data Processor");
+        info!("Using {} threads", rayon::current_num_threads());
+        info!("Parquet: row groups {}, statistics {}, clustered by {}",
+            self.parquet_options.row_group_size.map(|s| s.to_string()).unwrap_or_else(|| "default".to_string()),
+            if self.parquet_options.statistics { "on" } else { "off" },
+            self.parquet_options.cluster_column.as_deref().unwrap_or("none"));
+        self.filter.print_summary();
         
         let datasets = vec![
             // RT Market SPPs and LMPs
@@ -55,7 +169,7 @@ impl UnifiedProcessor {
                 output_prefix: "RT_Settlement_Point_Prices",
                 date_column: "DeliveryDate",
                 datetime_format: "%m/%d/%Y",
-                key_columns: vec!["DeliveryDate", "DeliveryHour", "DeliveryInterval", "SettlementPointName"],
+                key_columns: vec!["DeliveryDate", "DeliveryHour", "DeliveryInterval", "DSTFlag", "SettlementPointName"],
             },
             // DAM Hourly SPPs and LMPs
             DatasetConfig {
@@ -64,7 +178,7 @@ impl UnifiedProcessor {
                 output_prefix: "DAM_Hourly_LMPs",
                 date_column: "DeliveryDate",
                 datetime_format: "%m/%d/%Y",
-                key_columns: vec!["DeliveryDate", "HourEnding", "BusName"],
+                key_columns: vec!["DeliveryDate", "HourEnding", "DSTFlag", "BusName"],
             },
             DatasetConfig {
                 name: "DAM Settlement Point Prices",
@@ -72,7 +186,7 @@ impl UnifiedProcessor {
                 output_prefix: "DAM_Settlement_Point_Prices",
                 date_column: "DeliveryDate",
                 datetime_format: "%m/%d/%Y",
-                key_columns: vec!["DeliveryDate", "HourEnding", "SettlementPoint"],
+                key_columns: vec!["DeliveryDate", "HourEnding", "DSTFlag", "SettlementPoint"],
             },
             // Ancillary Services
             DatasetConfig {
@@ -81,7 +195,7 @@ impl UnifiedProcessor {
                 output_prefix: "DAM_Clearing_Prices_Capacity",
                 date_column: "DeliveryDate",
                 datetime_format: "%m/%d/%Y",
-                key_columns: vec!["DeliveryDate", "HourEnding", "AncillaryType"],
+                key_columns: vec!["DeliveryDate", "HourEnding", "DSTFlag", "AncillaryType"],
             },
             // Shadow Prices
             DatasetConfig {
@@ -104,60 +218,99 @@ impl UnifiedProcessor {
         
         // Process datasets sequentially (but each dataset uses parallel processing internally)
         let multi_progress = Arc::new(MultiProgress::new());
+        let mut exclusions = Vec::new();
         
         for config in datasets.iter() {
-            println!("\n{}", "=".repeat(80));
-            println!("Processing: {}", config.name);
-            println!("{}", "=".repeat(80));
+            if !self.filter.includes_dataset(&[config.output_prefix, config.source_dir, config.name]) {
+                continue;
+            }
+            
+            info!("Processing: {}", config.name);
             
-            if let Err(e) = self.process_dataset(config, multi_progress.clone()) {
-                eprintln!("Error processing {}: {}", config.name, e);
+            match self.process_dataset(config, multi_progress.clone()) {
+                Ok(excluded) => exclusions.push((config, excluded)),
+                Err(e) => error!("Error processing {}: {}", config.name, e),
             }
         }
         
+        info!("Excluded files by dataset:");
+        for (config, excluded) in &exclusions {
+            excluded.log(config.output_prefix, config.date_column);
+        }
+        
         Ok(())
     }
     
-    fn process_dataset(&self, config: &DatasetConfig, multi_progress: Arc<MultiProgress>) -> Result<()> {
+    fn process_dataset(&self, config: &DatasetConfig, multi_progress: Arc<MultiProgress>) -> Result<FileExclusions> {
         let source_path = self.base_dir.join(config.source_dir);
         if !source_path.exists() {
-            println!("Source directory not found: {}", source_path.display());
-            return Ok(());
+            warn!("Source directory not found: {}", source_path.display());
+            return Ok(FileExclusions::default());
         }
         
         // Step 1: Extract all ZIP files recursively (in parallel)
-        println!("Step 1: Extracting ZIP files in parallel...");
+        info!("Step 1: Extracting ZIP files in parallel...");
         let csv_files = self.extract_all_zips_parallel(&source_path, multi_progress.clone())?;
-        println!("Found {} CSV files after extraction", csv_files.len());
+        info!("Found {} CSV files after extraction", csv_files.len());
+        run_metadata::record_inputs(config.output_prefix, csv_files.len());
         
         if csv_files.is_empty() {
-            println!("No CSV files found in {}", config.source_dir);
-            return Ok(());
+            warn!("No CSV files found in {}", config.source_dir);
+            return Ok(FileExclusions::default());
         }
         
         // Step 2: Process CSV files by year (in parallel)
-        println!("Step 2: Processing CSV files by year in parallel...");
-        let yearly_data = self.process_csv_files_by_year_parallel(&csv_files, config, multi_progress.clone())?;
+        info!("Step 2: Processing CSV files by year in parallel...");
+        let (yearly_data, exclusions) = self.process_csv_files_by_year_parallel(&csv_files, config, multi_progress.clone())?;
+        exclusions.log(config.output_prefix, config.date_column);
         
         // Step 3: Save annual files (in parallel)
-        println!("Step 3: Saving annual files in parallel...");
+        info!("Step 3: Saving annual files in parallel...");
         self.save_annual_files_parallel(&yearly_data, config)?;
         
-        Ok(())
+        Ok(exclusions)
     }
     
     fn extract_all_zips_parallel(&self, dir: &Path, multi_progress: Arc<MultiProgress>) -> Result<Vec<Vec<u8>>> {
-        // Find all initial ZIP files
+        // Find all initial ZIP and gzipped CSV files
         let mut zip_files = Vec::new();
+        let mut gz_files = Vec::new();
         for entry in fs::read_dir(dir)? {
             let entry = entry?;
             let path = entry.path();
+            let filename = path.file_name().and_then(|s| s.to_str()).unwrap_or("");
+            if !self.filter.includes_file(filename) {
+                continue;
+            }
             if path.extension().and_then(|s| s.to_str()) == Some("zip") {
                 zip_files.push(path);
+            } else if is_gzipped_csv(filename) {
+                gz_files.push(path);
             }
         }
         
-        println!("Found {} top-level ZIP files", zip_files.len());
+        info!("Found {} top-level ZIP files", zip_files.len());
+        
+        // Gzipped CSVs decompress straight into memory; unreadable ones are
+        // logged and skipped
+        let gz_contents: Vec<Vec<u8>> = gz_files
+            .par_iter()
+            .filter_map(|gz_path| {
+                let read = fs::File::open(gz_path)
+                    .map_err(anyhow::Error::from)
+                    .and_then(read_gz_to_vec);
+                match read {
+                    Ok(buffer) => Some(buffer),
+                    Err(e) => {
+                        warn!("Skipping unreadable gzip {}: {}", gz_path.display(), e);
+                        None
+                    }
+                }
+            })
+            .collect();
+        if !gz_files.is_empty() {
+            info!("Found {} gzipped CSV files, {} readable", gz_files.len(), gz_contents.len());
+        }
         
         let pb = multi_progress.add(ProgressBar::new(zip_files.len() as u64));
         pb.set_style(ProgressStyle::default_bar()
@@ -165,7 +318,7 @@ impl UnifiedProcessor {
             .unwrap());
         
         // Shared storage for CSV contents
-        let csv_contents = Arc::new(Mutex::new(Vec::new()));
+        let csv_contents = Arc::new(Mutex::new(gz_contents));
         
         // Process ZIP files in parallel with reasonable batch size
         let batch_size = 100; // Process 100 files at a time to avoid stack overflow
@@ -195,6 +348,11 @@ impl UnifiedProcessor {
                                     if file.read_to_end(&mut buffer).is_ok() {
                                         local_csvs.push(buffer);
                                     }
+                                } else if is_gzipped_csv(&name) {
+                                    match read_gz_to_vec(&mut file) {
+                                        Ok(buffer) => local_csvs.push(buffer),
+                                        Err(e) => warn!("Skipping unreadable gzip {} in {}: {}", name, zip_path.display(), e),
+                                    }
                                 }
                             }
                         }
@@ -210,6 +368,12 @@ impl UnifiedProcessor {
                                             if file.read_to_end(&mut buffer).is_ok() {
                                                 local_csvs.push(buffer);
                                             }
+                                        } else if is_gzipped_csv(file.name()) {
+                                            let name = file.name().to_string();
+                                            match read_gz_to_vec(&mut file) {
+                                                Ok(buffer) => local_csvs.push(buffer),
+                                                Err(e) => warn!("Skipping unreadable gzip {} nested in {}: {}", name, zip_path.display(), e),
+                                            }
                                         }
                                     }
                                 }
@@ -234,9 +398,13 @@ impl UnifiedProcessor {
     }
     
     fn process_csv_files_by_year_parallel(&self, csv_contents: &[Vec<u8>], config: &DatasetConfig, 
-                                         multi_progress: Arc<MultiProgress>) -> Result<HashMap<i32, Vec<DataFrame>>> {
+                                         multi_progress: Arc<MultiProgress>) -> Result<(HashMap<i32, Vec<DataFrame>>, FileExclusions)> {
         
         let yearly_dfs = Arc::new(Mutex::new(HashMap::new()));
+        let unreadable = AtomicUsize::new(0);
+        let missing_date_column = AtomicUsize::new(0);
+        let unparseable_date = AtomicUsize::new(0);
+        let out_of_range_year = AtomicUsize::new(0);
         
         let pb = multi_progress.add(ProgressBar::new(csv_contents.len() as u64));
         pb.set_style(ProgressStyle::default_bar()
@@ -251,47 +419,67 @@ impl UnifiedProcessor {
             
             // Parse CSV from memory
             let cursor = std::io::Cursor::new(csv_data);
-            if let Ok(df) = CsvReader::new(cursor)
+            let Ok(mut df) = CsvReader::new(cursor)
                 .has_header(true)
-                .finish() {
-                
-                // Check if date column exists
-                if df.column(config.date_column).is_err() {
-                    return;
+                .finish() else {
+                unreadable.fetch_add(1, Ordering::Relaxed);
+                return;
+            };
+            // Same header normalization as `csv_headers::read_csv`, for CSVs read from memory
+            if normalize_headers(&mut df, config.name).is_err() {
+                unreadable.fetch_add(1, Ordering::Relaxed);
+                return;
+            }
+            
+            // Check if date column exists
+            let Ok(dates) = df.column(config.date_column) else {
+                missing_date_column.fetch_add(1, Ordering::Relaxed);
+                return;
+            };
+            
+            // Extract year from the first date
+            let first_date = dates.utf8().ok().and_then(|date_str| date_str.get(0));
+            let year = first_date.and_then(|first_date| {
+                if config.datetime_format.contains("%H") {
+                    // DateTime format
+                    NaiveDateTime::parse_from_str(first_date, config.datetime_format)
+                        .ok()
+                        .map(|dt| dt.year())
+                } else {
+                    // Date only format
+                    NaiveDate::parse_from_str(first_date, config.datetime_format)
+                        .ok()
+                        .map(|d| d.year())
                 }
-                
-                // Extract year from date column
-                if let Ok(dates) = df.column(config.date_column) {
-                    if let Ok(date_str) = dates.utf8() {
-                        if let Some(first_date) = date_str.get(0) {
-                            let year = if config.datetime_format.contains("%H") {
-                                // DateTime format
-                                NaiveDateTime::parse_from_str(first_date, config.datetime_format)
-                                    .ok()
-                                    .map(|dt| dt.year())
-                            } else {
-                                // Date only format
-                                NaiveDate::parse_from_str(first_date, config.datetime_format)
-                                    .ok()
-                                    .map(|d| d.year())
-                            };
-                            
-                            if let Some(year) = year {
-                                if year >= 2010 && year <= 2025 { // Sanity check
-                                    yearly_dfs.lock().unwrap()
-                                        .entry(year)
-                                        .or_insert_with(Vec::new)
-                                        .push(df);
-                                }
-                            }
-                        }
-                    }
+            });
+            
+            match year {
+                None => {
+                    unparseable_date.fetch_add(1, Ordering::Relaxed);
+                }
+                // Sanity check
+                Some(year) if !(2010..=2025).contains(&year) => {
+                    out_of_range_year.fetch_add(1, Ordering::Relaxed);
                 }
+                Some(year) if self.filter.includes_year(year) => {
+                    yearly_dfs.lock().unwrap()
+                        .entry(year)
+                        .or_insert_with(Vec::new)
+                        .push(df);
+                }
+                Some(_) => {}
             }
             });
         }
         
         pb.finish_with_message("CSV processing complete");
+        let exclusions = FileExclusions {
+            files: csv_contents.len(),
+            unreadable: unreadable.into_inner(),
+            missing_date_column: missing_date_column.into_inner(),
+            unparseable_date: unparseable_date.into_inner(),
+            out_of_range_year: out_of_range_year.into_inner(),
+        };
         
         let yearly_data = Arc::try_unwrap(yearly_dfs)
             .map(|mutex| mutex.into_inner().unwrap())
@@ -300,10 +488,10 @@ impl UnifiedProcessor {
         // Report statistics
         for (year, dfs) in &yearly_data {
             let total_rows: usize = dfs.iter().map(|df| df.height()).sum();
-            println!("  Year {}: {} files, {} total rows", year, dfs.len(), total_rows);
+            info!("Year {}: {} files, {} total rows", year, dfs.len(), total_rows);
         }
         
-        Ok(yearly_data)
+        Ok((yearly_data, exclusions))
     }
     
     fn save_annual_files_parallel(&self, yearly_data: &HashMap<i32, Vec<DataFrame>>, config: &DatasetConfig) 
@@ -322,19 +510,19 @@ impl UnifiedProcessor {
                 continue;
             }
             
-            println!("  Processing year {} ({} files)...", year, dfs.len());
+            info!("Processing year {} ({} files)...", year, dfs.len());
             
             // For very large datasets, process in batches to avoid memory exhaustion
             let total_rows: usize = dfs.iter().map(|df| df.height()).sum();
             let estimated_memory_mb = (total_rows * 100) / 1_000_000; // More conservative estimate
-            println!("    Total rows: {} (estimated memory: {}MB)", total_rows, estimated_memory_mb);
+            debug!("Total rows: {} (estimated memory: {}MB)", total_rows, estimated_memory_mb);
             
             // Get available memory (rough estimate)
             let available_memory_gb = 8; // Conservative estimate for most systems
             let available_memory_mb = available_memory_gb * 1024;
             
             if estimated_memory_mb > available_memory_mb / 2 {
-                println!("    ⚠️  Large dataset detected, using aggressive batching");
+                warn!("Large dataset detected, using aggressive batching");
             }
             
             let batch_size = if estimated_memory_mb > available_memory_mb / 2 {
@@ -351,13 +539,15 @@ impl UnifiedProcessor {
                 dfs.len()
             };
             
-            println!("    Using batch size: {} files per batch", batch_size);
+            debug!("Using batch size: {} files per batch", batch_size);
             
             let mut all_processed_dfs = Vec::new();
+            let mut row_counts = RowCounts { input: total_rows, ..RowCounts::default() };
+            let pb = row_progress_bar(total_rows, &format!("{} {}", config.output_prefix, year));
             
             // Process in batches
             for (batch_idx, batch) in dfs.chunks(batch_size).enumerate() {
-                println!("    Processing batch {} of {} ({} files)...", 
+                debug!("Processing batch {} of {} ({} files)...", 
                          batch_idx + 1, 
                          (dfs.len() + batch_size - 1) / batch_size,
                          batch.len());
@@ -372,6 +562,8 @@ impl UnifiedProcessor {
                 ) {
                     // Collect the combined dataframe first
                     if let Ok(mut final_df) = combined.collect() {
+                    row_counts.concatenated += final_df.height();
+                    pb.inc(batch.iter().map(|df| df.height() as u64).sum());
                     // Create proper datetime column based on data type
                     let has_hour = final_df.get_column_names().contains(&"DeliveryHour") || 
                                    final_df.get_column_names().contains(&"HourEnding");
@@ -383,6 +575,8 @@ impl UnifiedProcessor {
                             let dates_str = dates.utf8()?;
                             
                             let mut datetimes = Vec::new();
+                            // The repeated fall-back hour is flagged so it doesn't collide with the first
+                            let dst_flags = final_df.column("DSTFlag").ok().and_then(|c| c.utf8().ok());
                             
                             if has_interval {
                                 // RT data with 5-minute intervals
@@ -393,20 +587,17 @@ impl UnifiedProcessor {
                                 let intervals_cast = intervals.cast(&DataType::Int32)?;
                                 let intervals_i32 = intervals_cast.i32()?;
                                 
+                                let mut field_check = DeliveryFieldCheck::default();
                                 for i in 0..final_df.height() {
                                     if let (Some(date_str), Some(hour), Some(interval)) = (
                                         dates_str.get(i),
                                         hours_i32.get(i),
                                         intervals_i32.get(i)
                                     ) {
-                                        if let Ok(date) = NaiveDate::parse_from_str(date_str, "%m/%d/%Y") {
-                                            let hour_adj = if hour == 24 { 0 } else { hour - 1 } as u32;
-                                            let minute = ((interval - 1) * 15) as u32;
-                                            let mut dt = date.and_hms_opt(hour_adj, minute, 0).unwrap();
-                                            if hour == 24 {
-                                                dt = dt + Duration::days(1);
-                                            }
-                                            datetimes.push(Some(dt.and_utc().timestamp_millis()));
+                                        let fields = field_check.check(hour, interval);
+                                        if let (Ok(date), Some((hour, interval))) = (NaiveDate::parse_from_str(date_str, "%m/%d/%Y"), fields) {
+                                            let repeated_hour = is_repeated_hour(dst_flags.and_then(|f| f.get(i)));
+                                            datetimes.push(delivery_interval_datetime_ms(date, hour, interval, repeated_hour));
                                         } else {
                                             datetimes.push(None);
                                         }
@@ -414,6 +605,7 @@ impl UnifiedProcessor {
                                         datetimes.push(None);
                                     }
                                 }
+                                field_check.log(&format!("{} {}", config.output_prefix, year));
                             } else if has_hour {
                                 // DAM data with hourly intervals
                                 let hour_col = if final_df.get_column_names().contains(&"HourEnding") {
@@ -431,12 +623,8 @@ impl UnifiedProcessor {
                                         hours_i32.get(i)
                                     ) {
                                         if let Ok(date) = NaiveDate::parse_from_str(date_str, "%m/%d/%Y") {
-                                            let hour_adj = if hour == 24 { 0 } else { hour - 1 } as u32;
-                                            let mut dt = date.and_hms_opt(hour_adj, 0, 0).unwrap();
-                                            if hour == 24 {
-                                                dt = dt + Duration::days(1);
-                                            }
-                                            datetimes.push(Some(dt.and_utc().timestamp_millis()));
+                                            let repeated_hour = is_repeated_hour(dst_flags.and_then(|f| f.get(i)));
+                                            datetimes.push(hour_ending_datetime_ms(date, hour as u32, 0, repeated_hour));
                                         } else {
                                             datetimes.push(None);
                                         }
@@ -449,8 +637,7 @@ impl UnifiedProcessor {
                                 for i in 0..final_df.height() {
                                     if let Some(date_str) = dates_str.get(i) {
                                         if let Ok(date) = NaiveDate::parse_from_str(date_str, "%m/%d/%Y") {
-                                            let dt = date.and_hms_opt(0, 0, 0).unwrap();
-                                            datetimes.push(Some(dt.and_utc().timestamp_millis()));
+                                            datetimes.push(Some(operating_day_datetime_ms(date)));
                                         } else {
                                             datetimes.push(None);
                                         }
@@ -486,7 +673,7 @@ impl UnifiedProcessor {
                         }
                         
                         if !unique_cols.is_empty() {
-                            println!("  🧹 Removing duplicates on columns: {:?}", unique_cols);
+                            info!("Removing duplicates on columns: {:?}", unique_cols);
                             if let Ok(unique_df) = final_df.unique(Some(&unique_cols), UniqueKeepStrategy::Last, None) {
                                 final_df = unique_df;
                             }
@@ -494,7 +681,7 @@ impl UnifiedProcessor {
                     }
                     
                     // Sort by datetime column
-                    println!("  🔄 Sorting by {}", datetime_col);
+                    info!("Sorting by {}", datetime_col);
                     let sorted_df = final_df.clone().lazy()
                         .sort(datetime_col, Default::default())
                         .collect();
@@ -510,7 +697,8 @@ impl UnifiedProcessor {
             
             // Now combine all batches and save the final result
             if !all_processed_dfs.is_empty() {
-                println!("    📦 Combining {} processed batches...", all_processed_dfs.len());
+                debug!("Combining {} processed batches...", all_processed_dfs.len());
+                pb.set_message("combining batches");
                 
                 let final_lazy_dfs: Vec<LazyFrame> = all_processed_dfs.iter()
                     .map(|df| df.clone().lazy())
@@ -531,13 +719,17 @@ impl UnifiedProcessor {
                             }
                             
                             if !unique_cols.is_empty() {
-                                println!("    🧹 Final deduplication on columns: {:?}", unique_cols);
+                                debug!("Final deduplication on columns: {:?}", unique_cols);
+                                pb.set_message("deduplicating");
                                 if let Ok(unique_df) = year_df.unique(Some(&unique_cols), UniqueKeepStrategy::Last, None) {
                                     year_df = unique_df;
                                 }
                             }
                         }
                         
+                        row_counts.deduplicated = year_df.height();
+                        row_counts.log(&format!("{} {}", config.output_prefix, year));
+                        
                         // Final sort
                         let datetime_col = if year_df.get_column_names().contains(&"datetime") {
                             "datetime"
@@ -545,13 +737,15 @@ impl UnifiedProcessor {
                             config.date_column
                         };
                         
-                        println!("    🔄 Final sorting by {}", datetime_col);
+                        debug!("Final sorting by {}", datetime_col);
+                        pb.set_message("sorting");
                         let sorted_df = year_df.clone().lazy()
                             .sort(datetime_col, Default::default())
                             .collect();
                         if let Ok(sorted) = sorted_df {
                             year_df = sorted;
                         }
+                        pb.finish_and_clear();
                         
                         let base_name = format!("{}_{}", config.output_prefix, year);
                         
@@ -560,38 +754,64 @@ impl UnifiedProcessor {
                         let parquet_path = dataset_output_dir.join(format!("{}.parquet", base_name));
                         let arrow_path = dataset_output_dir.join(format!("{}.arrow", base_name));
                         
-                        println!("    💾 Saving final files for year {}...", year);
+                        debug!("Saving final files for year {}...", year);
                         
+                        if datetime_col == "datetime" {
+                            if let Err(e) = datetime_format::mark_current(&dataset_output_dir, &base_name) {
+                                warn!("Could not write datetime format marker for {}: {}", base_name, e);
+                            }
+                        }
+                        
+                        let formats = self.formats;
                         rayon::scope(|s| {
-                            let df_csv = year_df.clone();
-                            s.spawn(move |_| {
-                                if let Ok(file) = fs::File::create(&csv_path) {
-                                    let mut df_mut = df_csv.clone();
-                                    if CsvWriter::new(file).finish(&mut df_mut).is_ok() {
-                                        println!("      ✓ Saved CSV: {}", csv_path.display());
+                            if formats.csv {
+                                let df_csv = year_df.clone();
+                                s.spawn(move |_| {
+                                    if let Ok(file) = fs::File::create(&csv_path) {
+                                        let mut df_mut = df_csv.clone();
+                                        if CsvWriter::new(file).finish(&mut df_mut).is_ok() {
+                                            debug!("Saved CSV: {}", csv_path.display());
+                                        }
                                     }
-                                }
-                            });
+                                });
+                            }
                             
-                            let df_parquet = year_df.clone();
-                            s.spawn(move |_| {
-                                if let Ok(file) = fs::File::create(&parquet_path) {
-                                    let mut df_mut = df_parquet.clone();
-                                    if ParquetWriter::new(file).finish(&mut df_mut).is_ok() {
-                                        println!("      ✓ Saved Parquet: {}", parquet_path.display());
+                            if formats.parquet {
+                                let df_parquet = year_df.clone();
+                                let parquet_options = &self.parquet_options;
+                                s.spawn(move |_| {
+                                    // Cluster by the configured column so row groups carry narrow min/max ranges
+                                    let mut df_mut = match &parquet_options.cluster_column {
+                                        Some(cluster_col) if df_parquet.column(cluster_col).is_ok() => {
+                                            let mut by = vec![cluster_col.as_str()];
+                                            if df_parquet.column(datetime_col).is_ok() && datetime_col != cluster_col {
+                                                by.push(datetime_col);
+                                            }
+                                            let descending = vec![false; by.len()];
+                                            df_parquet.sort(by, descending, false).unwrap_or(df_parquet)
+                                        }
+                                        _ => df_parquet,
+                                    };
+                                    
+                                    if let Ok(file) = fs::File::create(&parquet_path) {
+                                        if parquet_options.writer(file).finish(&mut df_mut).is_ok() {
+                                            debug!("Saved Parquet: {}", parquet_path.display());
+                                        }
                                     }
-                                }
-                            });
+                                });
+                            }
                             
-                            let df_arrow = year_df;
-                            s.spawn(move |_| {
-                                if let Ok(file) = fs::File::create(&arrow_path) {
-                                    let mut df_mut = df_arrow.clone();
-                                    if IpcWriter::new(file).finish(&mut df_mut).is_ok() {
-                                        println!("      ✓ Saved Arrow: {}", arrow_path.display());
+                            if formats.arrow {
+                                let df_arrow = year_df;
+                                s.spawn(move |_| {
+                                    if let Ok(file) = fs::File::create(&arrow_path) {
+                                        let mut df_mut = df_arrow.clone();
+                                        if IpcWriter::new(file).finish(&mut df_mut).is_ok() {
+                                            debug!("Saved Arrow: {}", arrow_path.display());
+                                        }
                                     }
-                                }
-                            });
+                                });
+                            }
                         });
                     }
                 }
@@ -602,10 +822,66 @@ impl UnifiedProcessor {
     }
 }
 
-pub fn process_all_ercot_data() -> Result<()> {
+pub fn process_all_ercot_data(filter: ProcessingFilter, formats: OutputFormats, output_prefix: &OutputPrefix) -> Result<()> {
     let base_dir = PathBuf::from("/Users/enrico/data/ERCOT_data");
-    let output_dir = PathBuf::from("processed_ercot_data");
+    let output_dir = output_prefix.output_dir("processed_ercot_data");
     
-    let processor = UnifiedProcessor::new(base_dir, output_dir);
+    let processor = UnifiedProcessor::new(base_dir, output_dir)
+        .with_filter(filter)
+        .with_formats(formats);
     processor.process_all_datasets()
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_files_missing_date_column_reported_as_exclusions() {
+        let exclusions = FileExclusions { files: 100, missing_date_column: 10, ..Default::default() };
+        assert_eq!(exclusions.excluded(), 10);
+        assert_eq!(
+            exclusions.summary("DeliveryDate"),
+            "10 of 100 files excluded (10.0%): 10 missing the DeliveryDate column, 0 unreadable, 0 with an unparseable date, 0 dated outside 2010-2025"
+        );
+        assert!(FileExclusions::default().summary("SCEDTimestamp").starts_with("0 of 0 files excluded (0.0%)"));
+    }
+
+    #[test]
+    fn test_parquet_written_in_row_groups_of_the_configured_size() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("prices.parquet");
+        let mut df = df!("SettlementPointPrice" => (0..12).map(|i| i as f64).collect::<Vec<_>>()).unwrap();
+
+        let options = ParquetOptions { row_group_size: Some(4), statistics: true, cluster_column: None };
+        options.writer(fs::File::create(&path).unwrap()).finish(&mut df).unwrap();
+
+        let mut written = fs::File::open(&path).unwrap();
+        let metadata = polars::export::arrow::io::parquet::read::read_metadata(&mut written).unwrap();
+        assert_eq!(metadata.num_rows, 12);
+        assert_eq!(metadata.row_groups.len(), 3);
+        assert!(metadata.row_groups.iter().all(|group| group.num_rows() == 4));
+    }
+
+    #[test]
+    fn test_gzipped_csvs_read_whatever_the_suffix_case_and_corrupt_ones_skipped() {
+        use flate2::{write::GzEncoder, Compression};
+        use std::io::Write;
+
+        let dir = tempfile::tempdir().unwrap();
+        let csv = "DeliveryDate,SettlementPointPrice\n01/01/2024,25.0\n";
+        for name in ["prices_20240101.csv.gz", "PRICES_20240102.CSV.GZ"] {
+            let mut encoder = GzEncoder::new(fs::File::create(dir.path().join(name)).unwrap(), Compression::default());
+            encoder.write_all(csv.as_bytes()).unwrap();
+            encoder.finish().unwrap();
+        }
+        fs::write(dir.path().join("truncated_20240103.csv.gz"), b"not gzip").unwrap();
+
+        let processor = UnifiedProcessor::new(dir.path().to_path_buf(), dir.path().join("out"));
+        let contents = processor
+            .extract_all_zips_parallel(dir.path(), Arc::new(MultiProgress::new()))
+            .unwrap();
+        assert_eq!(contents.len(), 2);
+        assert!(contents.iter().all(|c| c == csv.as_bytes()));
+    }
+}
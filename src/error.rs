@@ -0,0 +1,26 @@
+use polars::prelude::*;
+use thiserror::Error;
+
+/// Error kinds callers may want to match on. Entry points still return
+/// `anyhow::Result`; these convert at the boundary with `?`.
+#[derive(Debug, Error)]
+pub enum PipelineError {
+    #[error("Missing required column: {name}")]
+    MissingColumn { name: String },
+
+    #[error("Unparseable date: {value}")]
+    UnparseableDate { value: String },
+
+    #[error("No data for year {year}")]
+    NoDataForYear { year: i32 },
+
+    #[error("Schema mismatch: {details}")]
+    SchemaMismatch { details: String },
+}
+
+/// Look up a column, reporting a missing one as `PipelineError::MissingColumn`
+pub fn require_column<'a>(df: &'a DataFrame, name: &str) -> Result<&'a Series, PipelineError> {
+    df.column(name).map_err(|_| PipelineError::MissingColumn {
+        name: name.to_string(),
+    })
+}
@@ -0,0 +1,99 @@
+use anyhow::Result;
+use chrono::Month;
+
+/// Years used for annual rollups: twelve months from `start_month`, named for
+/// the calendar year they end in, so with an October start Oct 2022–Sep 2023
+/// is FY2023. A January start is the plain calendar year.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FiscalCalendar {
+    start_month: u32,
+}
+
+impl Default for FiscalCalendar {
+    fn default() -> Self {
+        Self { start_month: 1 }
+    }
+}
+
+impl FiscalCalendar {
+    pub fn new(start_month: u32) -> Result<Self> {
+        if !(1..=12).contains(&start_month) {
+            anyhow::bail!("Invalid fiscal year start month: {} (expected 1-12)", start_month);
+        }
+        Ok(Self { start_month })
+    }
+
+    /// Parse `--fiscal-year-start <month>` (or `--fiscal-year-start=<month>`)
+    /// as 1-12 or a month name like `oct`; defaults to the calendar year
+    pub fn from_args(args: &[String]) -> Result<Self> {
+        let value = args.iter().enumerate().find_map(|(i, arg)| {
+            if arg == "--fiscal-year-start" {
+                args.get(i + 1).cloned()
+            } else {
+                arg.strip_prefix("--fiscal-year-start=").map(str::to_string)
+            }
+        });
+
+        match value {
+            None => Ok(Self::default()),
+            Some(value) => {
+                let month = value
+                    .parse::<u32>()
+                    .ok()
+                    .or_else(|| value.parse::<Month>().ok().map(|m| m.number_from_month()))
+                    .ok_or_else(|| anyhow::anyhow!("Invalid --fiscal-year-start: {} (expected 1-12 or a month name)", value))?;
+                Self::new(month)
+            }
+        }
+    }
+
+    pub fn is_calendar_year(&self) -> bool {
+        self.start_month == 1
+    }
+
+    /// Fiscal year containing calendar `month` (1-12) of `year`
+    pub fn year_of(&self, year: i32, month: u32) -> i32 {
+        if !self.is_calendar_year() && month >= self.start_month {
+            year + 1
+        } else {
+            year
+        }
+    }
+
+    /// `2023` for calendar years, `FY2023` for fiscal ones
+    pub fn label(&self, year: i32) -> String {
+        if self.is_calendar_year() {
+            year.to_string()
+        } else {
+            format!("FY{}", year)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_october_start_names_year_by_its_end() {
+        let fiscal = FiscalCalendar::from_args(&args(&["prog", "--fiscal-year-start", "10"])).unwrap();
+        assert_eq!(fiscal.year_of(2022, 9), 2022);
+        assert_eq!(fiscal.year_of(2022, 10), 2023);
+        assert_eq!(fiscal.year_of(2023, 9), 2023);
+        assert_eq!(fiscal.label(2023), "FY2023");
+
+        assert_eq!(FiscalCalendar::from_args(&args(&["prog", "--fiscal-year-start=oct"])).unwrap(), fiscal);
+        assert_eq!(FiscalCalendar::from_args(&args(&["prog", "--fiscal-year-start", "October"])).unwrap(), fiscal);
+
+        let calendar = FiscalCalendar::from_args(&args(&["prog"])).unwrap();
+        assert_eq!(calendar.year_of(2022, 12), 2022);
+        assert_eq!(calendar.label(2022), "2022");
+
+        assert!(FiscalCalendar::from_args(&args(&["prog", "--fiscal-year-start", "13"])).is_err());
+        assert!(FiscalCalendar::from_args(&args(&["prog", "--fiscal-year-start", "fall"])).is_err());
+    }
+}
@@ -0,0 +1,107 @@
+use chrono::{Datelike, NaiveDate, Weekday};
+
+/// NERC holidays: New Year's Day, Memorial Day, Independence Day, Labor Day,
+/// Thanksgiving Day and Christmas Day.
+///
+/// Fixed-date holidays falling on a Sunday are observed the following Monday.
+/// Holidays falling on a Saturday stay on the Saturday (no Friday observance),
+/// which is the convention ERCOT uses for on-peak/off-peak definitions.
+pub fn is_nerc_holiday(date: NaiveDate) -> bool {
+    nerc_holidays(date.year()).contains(&date)
+}
+
+/// All NERC holiday dates (as observed) for a given year
+pub fn nerc_holidays(year: i32) -> Vec<NaiveDate> {
+    vec![
+        observed(NaiveDate::from_ymd_opt(year, 1, 1).unwrap()),   // New Year's Day
+        last_weekday_of_month(year, 5, Weekday::Mon),              // Memorial Day
+        observed(NaiveDate::from_ymd_opt(year, 7, 4).unwrap()),   // Independence Day
+        nth_weekday_of_month(year, 9, Weekday::Mon, 1),            // Labor Day
+        nth_weekday_of_month(year, 11, Weekday::Thu, 4),           // Thanksgiving Day
+        observed(NaiveDate::from_ymd_opt(year, 12, 25).unwrap()), // Christmas Day
+    ]
+}
+
+fn observed(holiday: NaiveDate) -> NaiveDate {
+    if holiday.weekday() == Weekday::Sun {
+        holiday.succ_opt().unwrap()
+    } else {
+        holiday
+    }
+}
+
+fn nth_weekday_of_month(year: i32, month: u32, weekday: Weekday, n: u8) -> NaiveDate {
+    NaiveDate::from_weekday_of_month_opt(year, month, weekday, n).unwrap()
+}
+
+fn last_weekday_of_month(year: i32, month: u32, weekday: Weekday) -> NaiveDate {
+    NaiveDate::from_weekday_of_month_opt(year, month, weekday, 5)
+        .unwrap_or_else(|| nth_weekday_of_month(year, month, weekday, 4))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn d(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn test_new_years_day() {
+        assert!(is_nerc_holiday(d(2024, 1, 1)));  // Monday
+        assert!(is_nerc_holiday(d(2022, 1, 1)));  // Saturday, not moved
+        assert!(!is_nerc_holiday(d(2021, 12, 31)));
+        assert!(is_nerc_holiday(d(2023, 1, 2)));  // Jan 1 2023 is a Sunday
+        assert!(!is_nerc_holiday(d(2023, 1, 1)));
+    }
+
+    #[test]
+    fn test_memorial_day() {
+        assert!(is_nerc_holiday(d(2023, 5, 29)));
+        assert!(is_nerc_holiday(d(2024, 5, 27)));
+        assert!(is_nerc_holiday(d(2026, 5, 25)));
+        assert!(!is_nerc_holiday(d(2024, 5, 20)));
+    }
+
+    #[test]
+    fn test_independence_day() {
+        assert!(is_nerc_holiday(d(2024, 7, 4)));  // Thursday
+        assert!(is_nerc_holiday(d(2026, 7, 4)));  // Saturday, not moved
+        assert!(!is_nerc_holiday(d(2026, 7, 3)));
+        assert!(is_nerc_holiday(d(2021, 7, 5)));  // July 4 2021 is a Sunday
+        assert!(!is_nerc_holiday(d(2021, 7, 4)));
+    }
+
+    #[test]
+    fn test_labor_day() {
+        assert!(is_nerc_holiday(d(2023, 9, 4)));
+        assert!(is_nerc_holiday(d(2024, 9, 2)));
+        assert!(is_nerc_holiday(d(2026, 9, 7)));
+        assert!(!is_nerc_holiday(d(2024, 9, 9)));
+    }
+
+    #[test]
+    fn test_thanksgiving() {
+        assert!(is_nerc_holiday(d(2023, 11, 23)));
+        assert!(is_nerc_holiday(d(2024, 11, 28)));
+        assert!(is_nerc_holiday(d(2026, 11, 26)));
+        assert!(!is_nerc_holiday(d(2024, 11, 29)));
+    }
+
+    #[test]
+    fn test_christmas() {
+        assert!(is_nerc_holiday(d(2024, 12, 25)));  // Wednesday
+        assert!(is_nerc_holiday(d(2021, 12, 25)));  // Saturday, not moved
+        assert!(!is_nerc_holiday(d(2021, 12, 24)));
+        assert!(is_nerc_holiday(d(2022, 12, 26)));  // Dec 25 2022 is a Sunday
+        assert!(!is_nerc_holiday(d(2022, 12, 25)));
+    }
+
+    #[test]
+    fn test_six_holidays_per_year() {
+        for year in 2010..=2030 {
+            assert_eq!(nerc_holidays(year).len(), 6);
+        }
+    }
+}
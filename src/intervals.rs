@@ -0,0 +1,363 @@
+use chrono::{DateTime, Duration, LocalResult, NaiveDate, NaiveDateTime, TimeZone, Timelike};
+use chrono_tz::{America::Chicago, Tz};
+
+/// Cadence of an ERCOT time series. Energy for a dispatch value is MW × the
+/// length of the dispatch interval, regardless of the cadence of the price it
+/// is settled against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IntervalLength {
+    FiveMinute,
+    FifteenMinute,
+    Hourly,
+}
+
+/// SCED Gen Resource Data (Base Point, Telemetered Net Output, Output Schedule)
+pub const SCED_DISPATCH_INTERVAL: IntervalLength = IntervalLength::FiveMinute;
+/// SCED SMNE (Settlement Metered Net Energy) interval values
+pub const SMNE_INTERVAL: IntervalLength = IntervalLength::FifteenMinute;
+/// Real-time settlement point prices
+pub const RT_PRICE_INTERVAL: IntervalLength = IntervalLength::FifteenMinute;
+/// DAM awards: MW held for the whole hour ending, one row per resource-hour
+pub const DAM_AWARD_INTERVAL: IntervalLength = IntervalLength::Hourly;
+
+impl IntervalLength {
+    pub fn minutes(self) -> u32 {
+        match self {
+            IntervalLength::FiveMinute => 5,
+            IntervalLength::FifteenMinute => 15,
+            IntervalLength::Hourly => 60,
+        }
+    }
+
+    pub fn hours(self) -> f64 {
+        self.minutes() as f64 / 60.0
+    }
+
+    pub fn intervals_per_hour(self) -> u32 {
+        60 / self.minutes()
+    }
+
+    /// Zero-based interval of the day containing a wall-clock `timestamp`,
+    /// ignoring DST; use `index_of_central` for data spanning transitions
+    pub fn index_of(self, timestamp: NaiveDateTime) -> u32 {
+        (timestamp.hour() * 60 + timestamp.minute()) / self.minutes()
+    }
+
+    /// Zero-based interval of the operating day by elapsed time since Central
+    /// midnight, so the repeated hour on the fall-back day gets its own
+    /// indices and the skipped spring-forward hour none
+    pub fn index_of_central(self, timestamp: DateTime<Tz>) -> u32 {
+        let midnight = central_midnight(timestamp.date_naive());
+        ((timestamp - midnight).num_minutes() / self.minutes() as i64) as u32
+    }
+
+    /// Zero-based interval of the day for a Central wall-clock timestamp such
+    /// as a SCED timestamp with its Repeated Hour Flag; `None` if the time was
+    /// skipped by spring-forward
+    pub fn index_of_local(self, local: NaiveDateTime, repeated_hour: bool) -> Option<u32> {
+        Some(self.index_of_central(resolve_central(local, repeated_hour)?))
+    }
+
+    /// Zero-based interval of the day from ERCOT's DeliveryHour (hour ending,
+    /// 1-24), DeliveryInterval (1-based within the hour) and DSTFlag
+    /// (`repeated_hour`, set on the second 1:00-2:00 of the fall-back day).
+    /// `None` for an hour outside 1-24 or an interval outside the hour.
+    pub fn index_from_hour_ending(self, date: NaiveDate, hour_ending: u32, interval: u32, repeated_hour: bool) -> Option<u32> {
+        if !(1..=24).contains(&hour_ending) || !(1..=self.intervals_per_hour()).contains(&interval) {
+            return None;
+        }
+        let start = central_timestamp(date, hour_ending, (interval - 1) * self.minutes(), repeated_hour)?;
+        Some(self.index_of_central(start))
+    }
+
+    /// Number of intervals in a Central operating day: 24 hours' worth
+    /// normally, 23 on the spring-forward day and 25 on the fall-back day
+    pub fn intervals_in_day(self, date: NaiveDate) -> u32 {
+        let day = central_midnight(date.succ_opt().unwrap()) - central_midnight(date);
+        (day.num_minutes() / self.minutes() as i64) as u32
+    }
+
+    /// Intervals elapsed since the Unix epoch at the start of the interval
+    /// containing `instant`; a compact, day-independent price key
+    pub fn epoch_index<T: TimeZone>(self, instant: &DateTime<T>) -> i64 {
+        instant.timestamp().div_euclid(self.minutes() as i64 * 60)
+    }
+
+    /// Epoch index of interval `index` of the Central operating day `date`,
+    /// where `index` counts from Central midnight as in `index_of_central`
+    pub fn epoch_index_of_day(self, date: NaiveDate, index: u32) -> i64 {
+        self.epoch_index(&central_midnight(date)) + index as i64
+    }
+
+    /// Energy delivered by a constant output over one interval
+    pub fn energy_mwh(self, output_mw: f64) -> f64 {
+        output_mw * self.hours()
+    }
+}
+
+/// Central-local instant `minute` minutes into ERCOT hour ending `hour_ending`
+/// on `date`. Ambiguous fall-back times resolve to the repeated (CST) hour when
+/// `repeated_hour` is set; times skipped by spring-forward return `None`.
+pub fn central_timestamp(date: NaiveDate, hour_ending: u32, minute: u32, repeated_hour: bool) -> Option<DateTime<Tz>> {
+    let local = date.and_hms_opt(0, 0, 0)? + Duration::hours(hour_ending as i64 - 1) + Duration::minutes(minute as i64);
+    resolve_central(local, repeated_hour)
+}
+
+/// `datetime` column value for an ERCOT hour-ending row: milliseconds since
+/// the epoch at the UTC instant the interval starts, so both occurrences of
+/// the repeated fall-back hour (DSTFlag=Y, or hour ending 25 in some files)
+/// get their own datetimes. Hour ending 24 starts at 23:00 on `date`; hours
+/// skipped by spring-forward return `None`.
+pub fn hour_ending_datetime_ms(date: NaiveDate, hour_ending: u32, minute: u32, repeated_hour: bool) -> Option<i64> {
+    let (hour_ending, repeated_hour) = if hour_ending == 25 { (2, true) } else { (hour_ending, repeated_hour) };
+    Some(central_timestamp(date, hour_ending, minute, repeated_hour)?.timestamp_millis())
+}
+
+/// `datetime` for an RT price row's DeliveryHour (hour ending, 1-24) and
+/// DeliveryInterval (1-4, 15 minutes each): interval 1 starts on the hour
+pub fn delivery_interval_datetime_ms(date: NaiveDate, hour_ending: u32, interval: u32, repeated_hour: bool) -> Option<i64> {
+    let minute = interval.checked_sub(1)?.checked_mul(RT_PRICE_INTERVAL.minutes())?;
+    hour_ending_datetime_ms(date, hour_ending, minute, repeated_hour)
+}
+
+/// `datetime` for a Central wall-clock timestamp such as a SCED time stamp
+/// with its Repeated Hour Flag, on the same UTC convention as
+/// `hour_ending_datetime_ms`; `None` for times skipped by spring-forward
+pub fn central_datetime_ms(local: NaiveDateTime, repeated_hour: bool) -> Option<i64> {
+    Some(resolve_central(local, repeated_hour)?.timestamp_millis())
+}
+
+/// `datetime` for a daily row: the UTC instant of Central midnight
+pub fn operating_day_datetime_ms(date: NaiveDate) -> i64 {
+    central_midnight(date).timestamp_millis()
+}
+
+/// Central wall clock of a `datetime` column value written by
+/// `hour_ending_datetime_ms`, for matching against wall-clock timestamps
+/// such as SCED's
+pub fn central_wall_clock(datetime_ms: i64) -> Option<NaiveDateTime> {
+    Some(DateTime::from_timestamp_millis(datetime_ms)?.with_timezone(&Chicago).naive_local())
+}
+
+fn resolve_central(local: NaiveDateTime, repeated_hour: bool) -> Option<DateTime<Tz>> {
+    match Chicago.from_local_datetime(&local) {
+        LocalResult::Single(t) => Some(t),
+        LocalResult::Ambiguous(first, second) => Some(if repeated_hour { second } else { first }),
+        LocalResult::None => None,
+    }
+}
+
+/// Central midnight is never ambiguous (US transitions happen at 2:00)
+fn central_midnight(date: NaiveDate) -> DateTime<Tz> {
+    Chicago
+        .from_local_datetime(&date.and_hms_opt(0, 0, 0).unwrap())
+        .earliest()
+        .expect("Central midnight always exists")
+}
+
+/// SCED or SMNE timestamp such as `08/01/2024 18:00:13`, or the 12-hour
+/// `08/01/2024 06:00:13 PM` form some SCED files use
+pub fn parse_sced_timestamp(value: &str) -> Option<NaiveDateTime> {
+    let value = value.trim();
+    NaiveDateTime::parse_from_str(value, "%m/%d/%Y %H:%M:%S")
+        .or_else(|_| NaiveDateTime::parse_from_str(value, "%m/%d/%Y %I:%M:%S %p"))
+        .ok()
+}
+
+/// ERCOT's DSTFlag / Repeated Hour Flag marks the repeated hour with "Y"
+pub fn is_repeated_hour(flag: Option<&str>) -> bool {
+    matches!(flag, Some("Y") | Some("y") | Some("true"))
+}
+
+/// Settlement for one dispatch interval: positive when discharging, a cost when charging
+pub fn interval_revenue(output_mw: f64, price: f64, dispatch_interval: IntervalLength) -> f64 {
+    dispatch_interval.energy_mwh(output_mw) * price
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use std::collections::HashMap;
+
+    /// An hour of 5-minute SCED dispatch at 10 MW settled against four 15-minute prices
+    fn hour_of_dispatch() -> (Vec<(NaiveDateTime, f64)>, [f64; 4]) {
+        let start = NaiveDate::from_ymd_opt(2024, 8, 1).unwrap().and_hms_opt(18, 0, 0).unwrap();
+        let points = (0..12)
+            .map(|i| (start + chrono::Duration::minutes(5 * i), 10.0))
+            .collect();
+        (points, [20.0, 40.0, 60.0, 80.0])
+    }
+
+    #[test]
+    fn test_revenue_calculator_and_disclosure_analyzer_agree() {
+        let (points, prices) = hour_of_dispatch();
+        // 10 MWh at an average of $50/MWh
+        let expected = 500.0;
+
+        // Revenue calculator: prices keyed from the RT datetime column
+        let by_datetime: HashMap<u32, f64> = prices
+            .iter()
+            .enumerate()
+            .map(|(i, p)| (RT_PRICE_INTERVAL.index_of(points[i * 3].0), *p))
+            .collect();
+        let calculator: f64 = points
+            .iter()
+            .map(|(t, mw)| interval_revenue(*mw, by_datetime[&RT_PRICE_INTERVAL.index_of(*t)], SCED_DISPATCH_INTERVAL))
+            .sum();
+
+        // Disclosure analyzer: prices keyed from DeliveryHour/DeliveryInterval (HE19)
+        let by_hour_ending: HashMap<u32, f64> = prices
+            .iter()
+            .enumerate()
+            .map(|(i, p)| {
+                let date = points[0].0.date();
+                (RT_PRICE_INTERVAL.index_from_hour_ending(date, 19, i as u32 + 1, false).unwrap(), *p)
+            })
+            .collect();
+        let analyzer: f64 = points
+            .iter()
+            .map(|(t, mw)| interval_revenue(*mw, by_hour_ending[&RT_PRICE_INTERVAL.index_of(*t)], SCED_DISPATCH_INTERVAL))
+            .sum();
+
+        assert!((calculator - expected).abs() < 1e-9);
+        assert!((analyzer - expected).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_price_cadence_must_not_set_energy_conversion() {
+        let (points, _) = hour_of_dispatch();
+
+        let energy: f64 = points.iter().map(|(_, mw)| SCED_DISPATCH_INTERVAL.energy_mwh(*mw)).sum();
+        assert!((energy - 10.0).abs() < 1e-9);
+
+        // Converting 5-minute dispatch at the 15-minute price cadence (MW / 4)
+        // triples the energy
+        let mismatched: f64 = points.iter().map(|(_, mw)| RT_PRICE_INTERVAL.energy_mwh(*mw)).sum();
+        assert!((mismatched - 30.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_interval_indices() {
+        let t = NaiveDate::from_ymd_opt(2024, 8, 1).unwrap().and_hms_opt(18, 40, 0).unwrap();
+        assert_eq!(IntervalLength::FiveMinute.index_of(t), 224);
+        assert_eq!(IntervalLength::FifteenMinute.index_of(t), 74);
+        let date = t.date();
+        assert_eq!(IntervalLength::FifteenMinute.index_from_hour_ending(date, 19, 3, false), Some(74));
+        assert_eq!(IntervalLength::FifteenMinute.index_from_hour_ending(date, 1, 1, false), Some(0));
+        assert_eq!(IntervalLength::FifteenMinute.index_from_hour_ending(date, 24, 4, false), Some(95));
+        assert_eq!(IntervalLength::FifteenMinute.index_from_hour_ending(date, 19, 0, false), None);
+        assert_eq!(IntervalLength::FifteenMinute.index_from_hour_ending(date, 19, 5, false), None);
+        assert_eq!(IntervalLength::FifteenMinute.index_from_hour_ending(date, 0, 1, false), None);
+        assert_eq!(IntervalLength::FifteenMinute.index_from_hour_ending(date, 25, 1, false), None);
+        assert_eq!(IntervalLength::Hourly.index_from_hour_ending(date, 19, 2, false), None);
+    }
+
+    #[test]
+    fn test_fall_back_day_indexes_all_25_hours_without_collisions() {
+        // 2024-11-03: 1:00-2:00 CDT, then 1:00-2:00 again as CST (DSTFlag=Y)
+        let date = NaiveDate::from_ymd_opt(2024, 11, 3).unwrap();
+        let interval = IntervalLength::FifteenMinute;
+        assert_eq!(interval.intervals_in_day(date), 100);
+
+        let mut indices = Vec::new();
+        for hour_ending in 1..=24 {
+            for i in 1..=4 {
+                indices.push(interval.index_from_hour_ending(date, hour_ending, i, false).unwrap());
+                if hour_ending == 2 {
+                    indices.push(interval.index_from_hour_ending(date, hour_ending, i, true).unwrap());
+                }
+            }
+        }
+        indices.sort();
+        indices.dedup();
+        assert_eq!(indices, (0..100).collect::<Vec<_>>());
+
+        // Evening peak sits an hour later in elapsed time than on a normal day
+        assert_eq!(interval.index_from_hour_ending(date, 19, 1, false), Some(76));
+        // HE2 repeated follows the first HE2
+        assert_eq!(interval.index_from_hour_ending(date, 2, 1, false), Some(4));
+        assert_eq!(interval.index_from_hour_ending(date, 2, 1, true), Some(8));
+    }
+
+    #[test]
+    fn test_repeated_hour_datetimes_are_distinct() {
+        let date = NaiveDate::from_ymd_opt(2024, 11, 3).unwrap();
+        let first = hour_ending_datetime_ms(date, 2, 15, false).unwrap();
+        let repeated = hour_ending_datetime_ms(date, 2, 15, true).unwrap();
+        assert_eq!(repeated - first, 3_600_000);
+        assert_eq!(hour_ending_datetime_ms(date, 25, 15, false), Some(repeated));
+
+        // Both map back to the same 01:15 wall clock
+        let wall_clock = date.and_hms_opt(1, 15, 0).unwrap();
+        assert_eq!(central_wall_clock(first), Some(wall_clock));
+        assert_eq!(central_wall_clock(repeated), Some(wall_clock));
+
+        // HE24 is the last hour of its own day, not the first of the next
+        let he24 = hour_ending_datetime_ms(date, 24, 0, false).unwrap();
+        assert_eq!(central_wall_clock(he24), Some(date.and_hms_opt(23, 0, 0).unwrap()));
+        // Spring-forward skips HE3
+        assert_eq!(hour_ending_datetime_ms(NaiveDate::from_ymd_opt(2024, 3, 10).unwrap(), 3, 0, false), None);
+
+        // SCED wall clock and daily rows use the same UTC convention
+        let sced = date.and_hms_opt(1, 15, 0).unwrap();
+        assert_eq!(central_datetime_ms(sced, false), Some(first));
+        assert_eq!(central_datetime_ms(sced, true), Some(repeated));
+        assert_eq!(Some(operating_day_datetime_ms(date)), hour_ending_datetime_ms(date, 1, 0, false));
+        assert_eq!(central_wall_clock(operating_day_datetime_ms(date)), date.and_hms_opt(0, 0, 0));
+    }
+
+    #[test]
+    fn test_delivery_interval_datetime_millis() {
+        let summer = NaiveDate::from_ymd_opt(2024, 8, 1).unwrap();
+        let winter = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+        for (date, hour_ending, interval, expected) in [
+            // Interval 1 is minute 0 of the hour: HE1 starts at 00:00 CDT (05:00 UTC)
+            (summer, 1, 1, 1_722_488_400_000),
+            (summer, 1, 4, 1_722_491_100_000),
+            // HE24 is 23:00 on the same operating day, which is the next UTC day
+            (summer, 24, 1, 1_722_571_200_000),
+            (summer, 24, 4, 1_722_573_900_000),
+            (winter, 24, 1, 1_705_381_200_000),
+            // 17:30 CST
+            (winter, 18, 3, 1_705_361_400_000),
+        ] {
+            assert_eq!(
+                delivery_interval_datetime_ms(date, hour_ending, interval, false),
+                Some(expected),
+                "{} HE{} interval {}", date, hour_ending, interval
+            );
+        }
+        assert_eq!(delivery_interval_datetime_ms(summer, 1, 0, false), None);
+    }
+
+    #[test]
+    fn test_sced_timestamps_parse_in_24_and_12_hour_forms() {
+        let evening = NaiveDate::from_ymd_opt(2024, 8, 1).unwrap().and_hms_opt(18, 0, 13).unwrap();
+        assert_eq!(parse_sced_timestamp("08/01/2024 18:00:13"), Some(evening));
+        assert_eq!(parse_sced_timestamp("08/01/2024 06:00:13 PM"), Some(evening));
+        assert_eq!(parse_sced_timestamp("8/1/2024 6:00:13 PM"), Some(evening));
+        assert_eq!(parse_sced_timestamp("08/01/2024 12:05:00 AM"), Some(evening.date().and_hms_opt(0, 5, 0).unwrap()));
+        assert_eq!(parse_sced_timestamp("2024-08-01T18:00:13"), None);
+    }
+
+    #[test]
+    fn test_epoch_indices_do_not_collide_across_dst_days() {
+        let interval = IntervalLength::FifteenMinute;
+        for date in [NaiveDate::from_ymd_opt(2024, 3, 10).unwrap(), NaiveDate::from_ymd_opt(2024, 11, 3).unwrap()] {
+            let next = date.succ_opt().unwrap();
+            let last = interval.epoch_index_of_day(date, interval.intervals_in_day(date) - 1);
+            assert_eq!(last + 1, interval.epoch_index_of_day(next, 0));
+        }
+    }
+
+    #[test]
+    fn test_spring_forward_day_has_23_hours() {
+        let date = NaiveDate::from_ymd_opt(2024, 3, 10).unwrap();
+        let interval = IntervalLength::FiveMinute;
+        assert_eq!(interval.intervals_in_day(date), 276);
+        // HE3 (2:00-3:00) doesn't exist
+        assert_eq!(interval.index_from_hour_ending(date, 3, 1, false), None);
+        assert_eq!(interval.index_from_hour_ending(date, 4, 1, false), Some(24));
+    }
+}
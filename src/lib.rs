@@ -0,0 +1,75 @@
+//! ERCOT data processing pipeline. `Pipeline` is the programmatic entry point;
+//! the `rt_rust_processor` binary wraps these modules behind command-line flags.
+
+pub mod ercot_processor;
+pub mod comprehensive_processor;
+pub mod process_historical;
+pub mod dam_processor;
+pub mod ancillary_processor;
+pub mod as_services;
+pub mod lmp_processor;
+pub mod lmp_fast_processor;
+pub mod lmp_full_processor;
+pub mod disclosure_processor;
+pub mod disclosure_fast_processor;
+pub mod bess_analyzer;
+pub mod bess_revenue_calculator;
+pub mod bess_visualization;
+pub mod bess_market_report;
+pub mod bess_yearly_analysis;
+pub mod bess_comprehensive_calculator;
+pub mod bess_parquet_calculator;
+pub mod bess_disclosure_analyzer;
+pub mod bess_full_disclosure_analyzer;
+pub mod bess_complete_analyzer;
+pub mod bess_benchmarks;
+pub mod bess_lifecycle;
+pub mod bess_operations;
+pub mod revenue_validation;
+pub mod scarcity;
+pub mod ercot_unified_processor;
+pub mod unified_processor;
+pub mod archive;
+pub mod csv_extractor;
+pub mod annual_processor;
+pub mod processing_filter;
+pub mod resource_filter;
+pub mod row_counts;
+pub mod run_metadata;
+pub mod unmatched_prices;
+pub mod block_prices;
+pub mod offer_curves;
+pub mod settlement_points;
+pub mod stage_timer;
+pub mod combine_progress;
+pub mod consolidate;
+pub mod compare_runs;
+pub mod analyzer_reconciliation;
+pub mod column_diagnostics;
+pub mod csv_headers;
+pub mod data_quality;
+pub mod dataset_paths;
+pub mod delivery_fields;
+pub mod date_range;
+pub mod datetime_format;
+pub mod empty_year;
+pub mod holidays;
+pub mod deflator;
+pub mod fiscal_year;
+pub mod intervals;
+pub mod master_list;
+pub mod output_formats;
+pub mod output_prefix;
+pub mod price_cache_export;
+pub mod price_index;
+pub mod price_outliers;
+pub mod price_reader;
+pub mod price_store;
+pub mod rt_price_basis;
+#[cfg(feature = "sqlite")]
+pub mod sqlite_sink;
+pub mod error;
+pub mod pipeline;
+
+pub use error::PipelineError;
+pub use pipeline::{rt_annual_schema, Pipeline, PipelineConfig};
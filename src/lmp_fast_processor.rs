@@ -1,4 +1,5 @@
 use anyhow::Result;
+use log::{info, warn};
 use glob::glob;
 use indicatif::{ProgressBar, ProgressStyle};
 use polars::prelude::*;
@@ -6,17 +7,23 @@ use rayon::prelude::*;
 use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
 
+use crate::output_formats::OutputFormats;
+use crate::csv_headers::read_csv;
+use crate::output_prefix::OutputPrefix;
+use crate::run_metadata;
+
 pub struct LmpFastProcessor {
     underscores_dir: PathBuf,
     csv_dir: PathBuf,
     output_dir: PathBuf,
+    formats: OutputFormats,
 }
 
 impl LmpFastProcessor {
-    pub fn new() -> Result<Self> {
+    pub fn new(output_prefix: &OutputPrefix) -> Result<Self> {
         let underscores_dir = PathBuf::from("/Users/enrico/data/ERCOT_data/LMPs_by_Resource_Nodes,_Load_Zones_and_Trading_Hubs");
         let csv_dir = underscores_dir.join("csv");
-        let output_dir = PathBuf::from("lmp_annual_data");
+        let output_dir = output_prefix.output_dir("lmp_annual_data");
         
         // Create directories
         std::fs::create_dir_all(&csv_dir)?;
@@ -26,12 +33,18 @@ impl LmpFastProcessor {
             underscores_dir,
             csv_dir,
             output_dir,
+            formats: OutputFormats::default(),
         })
     }
 
+    /// Which of CSV, Parquet and Arrow to write for each year
+    pub fn with_formats(mut self, formats: OutputFormats) -> Self {
+        self.formats = formats;
+        self
+    }
+
     pub fn process_existing_csv_files(&self) -> Result<()> {
-        println!("🚀 Fast LMP Processing - Using Existing CSV Files");
-        println!("{}", "=".repeat(60));
+        info!("Fast LMP Processing - Using Existing CSV Files");
         
         // Just process the CSV files that are already extracted
         self.create_annual_rollups()?;
@@ -40,8 +53,7 @@ impl LmpFastProcessor {
     }
 
     pub fn extract_sample_and_process(&self, sample_size: usize) -> Result<()> {
-        println!("🚀 LMP Sample Processing - {} files", sample_size);
-        println!("{}", "=".repeat(60));
+        info!("LMP Sample Processing - {} files", sample_size);
         
         // Process a sample of ZIP files first
         self.extract_sample_zip_files(sample_size)?;
@@ -53,7 +65,7 @@ impl LmpFastProcessor {
     }
 
     fn extract_sample_zip_files(&self, sample_size: usize) -> Result<()> {
-        println!("🗜️  Extracting sample of {} ZIP files...", sample_size);
+        info!("Extracting sample of {} ZIP files...", sample_size);
         
         // Find all ZIP files
         let pattern = self.underscores_dir.join("*.zip");
@@ -63,7 +75,7 @@ impl LmpFastProcessor {
         
         // Take a sample
         zip_files.truncate(sample_size);
-        println!("Processing {} ZIP files", zip_files.len());
+        info!("Processing {} ZIP files", zip_files.len());
         
         // Get existing CSV files to avoid re-extraction
         let csv_pattern = self.csv_dir.join("*.csv");
@@ -72,7 +84,7 @@ impl LmpFastProcessor {
             .filter_map(|p| p.file_stem().and_then(|s| s.to_str()).map(String::from))
             .collect();
         
-        println!("Found {} existing CSV files", existing_csvs.len());
+        info!("Found {} existing CSV files", existing_csvs.len());
         
         let pb = ProgressBar::new(zip_files.len() as u64);
         pb.set_style(ProgressStyle::default_bar()
@@ -122,7 +134,7 @@ impl LmpFastProcessor {
     }
 
     fn create_annual_rollups(&self) -> Result<()> {
-        println!("\n📊 Creating annual rollups from CSV files...");
+        info!("Creating annual rollups from CSV files...");
         
         // Find all CSV files
         let pattern = self.csv_dir.join("*.csv");
@@ -130,10 +142,11 @@ impl LmpFastProcessor {
             .filter_map(Result::ok)
             .collect();
         
-        println!("Found {} CSV files to process", csv_files.len());
+        info!("Found {} CSV files to process", csv_files.len());
+        run_metadata::record_inputs("LMP CSV", csv_files.len());
         
         if csv_files.is_empty() {
-            println!("❌ No CSV files found");
+            warn!("No CSV files found");
             return Ok(());
         }
         
@@ -151,7 +164,7 @@ impl LmpFastProcessor {
         
         let mut years: Vec<u16> = files_by_year.keys().cloned().collect();
         years.sort();
-        println!("Years found: {:?}", years);
+        info!("Years found: {:?}", years);
         
         // Process each year
         for (year, year_files) in files_by_year {
@@ -188,7 +201,7 @@ impl LmpFastProcessor {
     }
 
     fn process_year_lmp_files(&self, year: u16, files: &[PathBuf]) -> Result<()> {
-        println!("\n📅 Processing LMP year {}: {} files", year, files.len());
+        info!("Processing LMP year {}: {} files", year, files.len());
         
         let pb = ProgressBar::new(files.len() as u64);
         pb.set_style(ProgressStyle::default_bar()
@@ -206,9 +219,7 @@ impl LmpFastProcessor {
                     pb.inc(1);
                     
                     // Try to read CSV
-                    CsvReader::new(std::fs::File::open(file).ok()?)
-                        .has_header(true)
-                        .finish()
+                    read_csv(file)
                         .ok()
                 })
                 .collect();
@@ -219,11 +230,11 @@ impl LmpFastProcessor {
         pb.finish_with_message("Files loaded");
         
         if all_dfs.is_empty() {
-            println!("  ❌ No valid data for year {}", year);
+            warn!("No valid data for year {}", year);
             return Ok(());
         }
         
-        println!("  📊 Combining {} dataframes...", all_dfs.len());
+        info!("Combining {} dataframes...", all_dfs.len());
         
         // Concatenate all dataframes
         let combined = concat(
@@ -231,42 +242,26 @@ impl LmpFastProcessor {
             UnionArgs::default(),
         )?.collect()?;
         
-        println!("  📊 Combined records: {}", combined.height());
+        info!("Combined records: {}", combined.height());
         
         // Save files
         let base_name = format!("LMPs_by_Resource_Nodes_Load_Zones_Trading_Hubs_{}", year);
         
-        // CSV
-        let csv_path = self.output_dir.join(format!("{}.csv", base_name));
-        println!("  💾 Saving CSV...");
-        CsvWriter::new(std::fs::File::create(&csv_path)?)
-            .finish(&mut combined.clone())?;
-        
-        // Parquet
-        let parquet_path = self.output_dir.join(format!("{}.parquet", base_name));
-        println!("  📦 Saving Parquet...");
-        ParquetWriter::new(std::fs::File::create(&parquet_path)?)
-            .finish(&mut combined.clone())?;
-        
-        // Arrow IPC
-        let arrow_path = self.output_dir.join(format!("{}.arrow", base_name));
-        println!("  🏹 Saving Arrow IPC...");
-        IpcWriter::new(std::fs::File::create(&arrow_path)?)
-            .finish(&mut combined.clone())?;
-        
-        println!("  ✅ Completed LMP year {}", year);
+        self.formats.write(&combined, &self.output_dir, &base_name)?;
+        
+        info!("Completed LMP year {}", year);
         Ok(())
     }
 }
 
-pub fn process_existing_lmp_csv() -> Result<()> {
-    let processor = LmpFastProcessor::new()?;
+pub fn process_existing_lmp_csv(formats: OutputFormats, output_prefix: &OutputPrefix) -> Result<()> {
+    let processor = LmpFastProcessor::new(output_prefix)?.with_formats(formats);
     processor.process_existing_csv_files()?;
     Ok(())
 }
 
-pub fn process_lmp_sample(sample_size: usize) -> Result<()> {
-    let processor = LmpFastProcessor::new()?;
+pub fn process_lmp_sample(sample_size: usize, formats: OutputFormats, output_prefix: &OutputPrefix) -> Result<()> {
+    let processor = LmpFastProcessor::new(output_prefix)?.with_formats(formats);
     processor.extract_sample_and_process(sample_size)?;
     Ok(())
 }
\ No newline at end of file
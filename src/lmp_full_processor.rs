@@ -1,4 +1,5 @@
 use anyhow::Result;
+use log::{info, warn};
 use glob::glob;
 use indicatif::{ProgressBar, ProgressStyle};
 use polars::prelude::*;
@@ -7,17 +8,23 @@ use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
 
+use crate::output_formats::OutputFormats;
+use crate::csv_headers::read_csv;
+use crate::output_prefix::OutputPrefix;
+use crate::run_metadata;
+
 pub struct LmpFullProcessor {
     underscores_dir: PathBuf,
     csv_dir: PathBuf,
     output_dir: PathBuf,
+    formats: OutputFormats,
 }
 
 impl LmpFullProcessor {
-    pub fn new() -> Result<Self> {
+    pub fn new(output_prefix: &OutputPrefix) -> Result<Self> {
         let underscores_dir = PathBuf::from("/Users/enrico/data/ERCOT_data/LMPs_by_Resource_Nodes,_Load_Zones_and_Trading_Hubs");
         let csv_dir = underscores_dir.join("csv");
-        let output_dir = PathBuf::from("lmp_annual_data");
+        let output_dir = output_prefix.output_dir("lmp_annual_data");
         
         // Create directories
         std::fs::create_dir_all(&csv_dir)?;
@@ -27,12 +34,18 @@ impl LmpFullProcessor {
             underscores_dir,
             csv_dir,
             output_dir,
+            formats: OutputFormats::default(),
         })
     }
 
+    /// Which of CSV, Parquet and Arrow to write for each year
+    pub fn with_formats(mut self, formats: OutputFormats) -> Self {
+        self.formats = formats;
+        self
+    }
+
     pub fn extract_all_and_process(&self) -> Result<()> {
-        println!("🚀 LMP Full Historical Processing - ALL YEARS");
-        println!("{}", "=".repeat(60));
+        info!("LMP Full Historical Processing - ALL YEARS");
         
         // Step 1: Extract ALL remaining ZIP files
         self.extract_all_remaining_zips()?;
@@ -44,7 +57,7 @@ impl LmpFullProcessor {
     }
 
     fn extract_all_remaining_zips(&self) -> Result<()> {
-        println!("🗜️  Extracting ALL remaining ZIP files...");
+        info!("Extracting ALL remaining ZIP files...");
         
         // Find all ZIP files
         let pattern = self.underscores_dir.join("*.zip");
@@ -52,7 +65,7 @@ impl LmpFullProcessor {
             .filter_map(Result::ok)
             .collect();
         
-        println!("Found {} total ZIP files", zip_files.len());
+        info!("Found {} total ZIP files", zip_files.len());
         
         // Get existing CSV files to avoid re-extraction
         let csv_pattern = self.csv_dir.join("*.csv");
@@ -61,7 +74,7 @@ impl LmpFullProcessor {
             .filter_map(|p| p.file_stem().and_then(|s| s.to_str()).map(String::from))
             .collect();
         
-        println!("Found {} existing CSV files", existing_csvs.len());
+        info!("Found {} existing CSV files", existing_csvs.len());
         
         // Filter out already processed files
         let mut unprocessed_zips = Vec::new();
@@ -73,10 +86,10 @@ impl LmpFullProcessor {
             }
         }
         
-        println!("Need to extract {} ZIP files", unprocessed_zips.len());
+        info!("Need to extract {} ZIP files", unprocessed_zips.len());
         
         if unprocessed_zips.is_empty() {
-            println!("✅ All ZIP files already extracted");
+            info!("All ZIP files already extracted");
             return Ok(());
         }
         
@@ -136,7 +149,7 @@ impl LmpFullProcessor {
     }
 
     fn create_complete_annual_rollups(&self) -> Result<()> {
-        println!("\n📊 Creating complete annual rollups...");
+        info!("Creating complete annual rollups...");
         
         // Find all CSV files
         let pattern = self.csv_dir.join("*.csv");
@@ -144,10 +157,11 @@ impl LmpFullProcessor {
             .filter_map(Result::ok)
             .collect();
         
-        println!("Found {} total CSV files to process", csv_files.len());
+        info!("Found {} total CSV files to process", csv_files.len());
+        run_metadata::record_inputs("LMP CSV", csv_files.len());
         
         if csv_files.is_empty() {
-            println!("❌ No CSV files found");
+            warn!("No CSV files found");
             return Ok(());
         }
         
@@ -164,7 +178,7 @@ impl LmpFullProcessor {
         
         let mut years: Vec<u16> = files_by_year.keys().cloned().collect();
         years.sort();
-        println!("Complete years found: {:?}", years);
+        info!("Complete years found: {:?}", years);
         
         // Process each year
         for (year, year_files) in files_by_year {
@@ -174,7 +188,7 @@ impl LmpFullProcessor {
                 if let Ok(metadata) = std::fs::metadata(&existing_parquet) {
                     // If the parquet file is larger than 10MB, assume it's complete
                     if metadata.len() > 10_000_000 {
-                        println!("⏭️  Skipping year {} (already processed)", year);
+                        info!("Skipping year {} (already processed)", year);
                         continue;
                     }
                 }
@@ -226,7 +240,7 @@ impl LmpFullProcessor {
     }
 
     fn process_year_lmp_files(&self, year: u16, files: &[PathBuf]) -> Result<()> {
-        println!("\n📅 Processing LMP year {}: {} files", year, files.len());
+        info!("Processing LMP year {}: {} files", year, files.len());
         
         let pb = ProgressBar::new(files.len() as u64);
         pb.set_style(ProgressStyle::default_bar()
@@ -246,9 +260,7 @@ impl LmpFullProcessor {
                     pb.inc(1);
                     
                     // Read CSV with error handling
-                    match CsvReader::new(std::fs::File::open(file).ok()?)
-                        .has_header(true)
-                        .finish() {
+                    match read_csv(file) {
                         Ok(df) => {
                             // Basic validation - must have some data
                             if df.height() > 0 {
@@ -269,11 +281,11 @@ impl LmpFullProcessor {
         pb.finish_with_message(format!("Loaded {} dataframes", all_dfs.len()));
         
         if all_dfs.is_empty() {
-            println!("  ❌ No valid data for year {}", year);
+            warn!("No valid data for year {}", year);
             return Ok(());
         }
         
-        println!("  📊 Combining {} dataframes...", all_dfs.len());
+        info!("Combining {} dataframes...", all_dfs.len());
         
         // Concatenate all dataframes
         let combined = concat(
@@ -281,36 +293,20 @@ impl LmpFullProcessor {
             UnionArgs::default(),
         )?.collect()?;
         
-        println!("  📊 Combined records: {}", combined.height());
+        info!("Combined records: {}", combined.height());
         
         // Save files
         let base_name = format!("LMPs_by_Resource_Nodes_Load_Zones_Trading_Hubs_{}", year);
         
-        // CSV
-        let csv_path = self.output_dir.join(format!("{}.csv", base_name));
-        println!("  💾 Saving CSV...");
-        CsvWriter::new(std::fs::File::create(&csv_path)?)
-            .finish(&mut combined.clone())?;
-        
-        // Parquet
-        let parquet_path = self.output_dir.join(format!("{}.parquet", base_name));
-        println!("  📦 Saving Parquet...");
-        ParquetWriter::new(std::fs::File::create(&parquet_path)?)
-            .finish(&mut combined.clone())?;
-        
-        // Arrow IPC
-        let arrow_path = self.output_dir.join(format!("{}.arrow", base_name));
-        println!("  🏹 Saving Arrow IPC...");
-        IpcWriter::new(std::fs::File::create(&arrow_path)?)
-            .finish(&mut combined.clone())?;
-        
-        println!("  ✅ Completed LMP year {} - {} records", year, combined.height());
+        self.formats.write(&combined, &self.output_dir, &base_name)?;
+        
+        info!("Completed LMP year {} - {} records", year, combined.height());
         Ok(())
     }
 }
 
-pub fn process_all_lmp_historical() -> Result<()> {
-    let processor = LmpFullProcessor::new()?;
+pub fn process_all_lmp_historical(formats: OutputFormats, output_prefix: &OutputPrefix) -> Result<()> {
+    let processor = LmpFullProcessor::new(output_prefix)?.with_formats(formats);
     processor.extract_all_and_process()?;
     Ok(())
 }
\ No newline at end of file
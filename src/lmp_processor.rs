@@ -1,4 +1,5 @@
 use anyhow::Result;
+use log::{info, warn};
 use glob::glob;
 use indicatif::{ProgressBar, ProgressStyle};
 use polars::prelude::*;
@@ -6,19 +7,25 @@ use rayon::prelude::*;
 use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
 
+use crate::output_formats::OutputFormats;
+use crate::csv_headers::read_csv;
+use crate::output_prefix::OutputPrefix;
+use crate::run_metadata;
+
 pub struct LmpProcessor {
     spaces_dir: PathBuf,
     underscores_dir: PathBuf,
     csv_dir: PathBuf,
     output_dir: PathBuf,
+    formats: OutputFormats,
 }
 
 impl LmpProcessor {
-    pub fn new() -> Result<Self> {
+    pub fn new(output_prefix: &OutputPrefix) -> Result<Self> {
         let spaces_dir = PathBuf::from("/Users/enrico/data/ERCOT_data/LMPs by Resource Nodes, Load Zones and Trading Hubs");
         let underscores_dir = PathBuf::from("/Users/enrico/data/ERCOT_data/LMPs_by_Resource_Nodes,_Load_Zones_and_Trading_Hubs");
         let csv_dir = underscores_dir.join("csv");
-        let output_dir = PathBuf::from("lmp_annual_data");
+        let output_dir = output_prefix.output_dir("lmp_annual_data");
         
         // Create directories
         std::fs::create_dir_all(&underscores_dir)?;
@@ -30,12 +37,18 @@ impl LmpProcessor {
             underscores_dir,
             csv_dir,
             output_dir,
+            formats: OutputFormats::default(),
         })
     }
 
+    /// Which of CSV, Parquet and Arrow to write for each year
+    pub fn with_formats(mut self, formats: OutputFormats) -> Self {
+        self.formats = formats;
+        self
+    }
+
     pub fn process_all_lmp_data(&self) -> Result<()> {
-        println!("🏗️  LMP Data Processing Pipeline");
-        println!("{}", "=".repeat(60));
+        info!("LMP Data Processing Pipeline");
         
         // Step 1: Move files from spaces folder to underscores folder
         self.move_files_to_underscores_folder()?;
@@ -51,11 +64,11 @@ impl LmpProcessor {
 
     fn move_files_to_underscores_folder(&self) -> Result<()> {
         if !self.spaces_dir.exists() {
-            println!("📁 Spaces folder does not exist, skipping move step");
+            info!("Spaces folder does not exist, skipping move step");
             return Ok(());
         }
         
-        println!("📦 Moving files from spaces folder to underscores folder...");
+        info!("Moving files from spaces folder to underscores folder...");
         
         // Find all files in spaces folder
         let pattern = self.spaces_dir.join("*.zip");
@@ -64,7 +77,7 @@ impl LmpProcessor {
             .collect();
         
         if space_files.is_empty() {
-            println!("  ✅ No files to move");
+            info!("No files to move");
             return Ok(());
         }
         
@@ -91,7 +104,7 @@ impl LmpProcessor {
     }
 
     fn extract_and_organize_csv_files(&self) -> Result<()> {
-        println!("\n🗜️  Extracting nested ZIP files and organizing CSV files...");
+        info!("Extracting nested ZIP files and organizing CSV files...");
         
         // Find all ZIP files in underscores directory
         let pattern = self.underscores_dir.join("*.zip");
@@ -99,7 +112,7 @@ impl LmpProcessor {
             .filter_map(Result::ok)
             .collect();
         
-        println!("Found {} ZIP files to process", zip_files.len());
+        info!("Found {} ZIP files to process", zip_files.len());
         
         // Get existing CSV files to avoid re-extraction
         let csv_pattern = self.csv_dir.join("*.csv");
@@ -219,7 +232,7 @@ impl LmpProcessor {
     }
 
     fn create_annual_rollups(&self) -> Result<()> {
-        println!("\n📊 Creating annual rollups from CSV files...");
+        info!("Creating annual rollups from CSV files...");
         
         // Find all CSV files
         let pattern = self.csv_dir.join("*.csv");
@@ -227,10 +240,11 @@ impl LmpProcessor {
             .filter_map(Result::ok)
             .collect();
         
-        println!("Found {} CSV files to process", csv_files.len());
+        info!("Found {} CSV files to process", csv_files.len());
+        run_metadata::record_inputs("LMP CSV", csv_files.len());
         
         if csv_files.is_empty() {
-            println!("❌ No CSV files found");
+            warn!("No CSV files found");
             return Ok(());
         }
         
@@ -246,7 +260,7 @@ impl LmpProcessor {
             }
         }
         
-        println!("Years found: {:?}", {
+        info!("Years found: {:?}", {
             let mut years: Vec<u16> = files_by_year.keys().cloned().collect();
             years.sort();
             years
@@ -287,7 +301,7 @@ impl LmpProcessor {
     }
 
     fn process_year_lmp_files(&self, year: u16, files: &[PathBuf]) -> Result<()> {
-        println!("\n📅 Processing LMP year {}: {} files", year, files.len());
+        info!("Processing LMP year {}: {} files", year, files.len());
         
         let pb = ProgressBar::new(files.len() as u64);
         pb.set_style(ProgressStyle::default_bar()
@@ -305,9 +319,7 @@ impl LmpProcessor {
                     pb.inc(1);
                     
                     // Try to read CSV
-                    CsvReader::new(std::fs::File::open(file).ok()?)
-                        .has_header(true)
-                        .finish()
+                    read_csv(file)
                         .ok()
                 })
                 .collect();
@@ -318,11 +330,11 @@ impl LmpProcessor {
         pb.finish_with_message("Files loaded");
         
         if all_dfs.is_empty() {
-            println!("  ❌ No valid data for year {}", year);
+            warn!("No valid data for year {}", year);
             return Ok(());
         }
         
-        println!("  📊 Combining {} dataframes...", all_dfs.len());
+        info!("Combining {} dataframes...", all_dfs.len());
         
         // Concatenate all dataframes
         let combined = concat(
@@ -330,36 +342,20 @@ impl LmpProcessor {
             UnionArgs::default(),
         )?.collect()?;
         
-        println!("  📊 Combined records: {}", combined.height());
+        info!("Combined records: {}", combined.height());
         
         // Save files
         let base_name = format!("LMPs_by_Resource_Nodes_Load_Zones_Trading_Hubs_{}", year);
         
-        // CSV
-        let csv_path = self.output_dir.join(format!("{}.csv", base_name));
-        println!("  💾 Saving CSV...");
-        CsvWriter::new(std::fs::File::create(&csv_path)?)
-            .finish(&mut combined.clone())?;
-        
-        // Parquet
-        let parquet_path = self.output_dir.join(format!("{}.parquet", base_name));
-        println!("  📦 Saving Parquet...");
-        ParquetWriter::new(std::fs::File::create(&parquet_path)?)
-            .finish(&mut combined.clone())?;
-        
-        // Arrow IPC
-        let arrow_path = self.output_dir.join(format!("{}.arrow", base_name));
-        println!("  🏹 Saving Arrow IPC...");
-        IpcWriter::new(std::fs::File::create(&arrow_path)?)
-            .finish(&mut combined.clone())?;
-        
-        println!("  ✅ Completed LMP year {}", year);
+        self.formats.write(&combined, &self.output_dir, &base_name)?;
+        
+        info!("Completed LMP year {}", year);
         Ok(())
     }
 }
 
-pub fn process_all_lmp_data() -> Result<()> {
-    let processor = LmpProcessor::new()?;
+pub fn process_all_lmp_data(formats: OutputFormats, output_prefix: &OutputPrefix) -> Result<()> {
+    let processor = LmpProcessor::new(output_prefix)?.with_formats(formats);
     processor.process_all_lmp_data()?;
     Ok(())
 }
\ No newline at end of file
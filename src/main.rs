@@ -1,55 +1,78 @@
-use anyhow::Result;
-use chrono::{Duration, NaiveDate};
+use anyhow::{Context, Result};
+use log::{info, warn};
 use glob::glob;
-use indicatif::{ProgressBar, ProgressStyle};
 use polars::prelude::*;
-use rayon::prelude::*;
-use std::collections::HashMap;
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
 
-mod ercot_processor;
-mod comprehensive_processor;
-mod process_historical;
-mod dam_processor;
-mod ancillary_processor;
-mod lmp_processor;
-mod lmp_fast_processor;
-mod lmp_full_processor;
-mod disclosure_processor;
-mod disclosure_fast_processor;
-mod bess_analyzer;
-mod bess_revenue_calculator;
-mod bess_visualization;
-mod bess_market_report;
-mod bess_yearly_analysis;
-mod bess_comprehensive_calculator;
-mod bess_parquet_calculator;
-mod bess_disclosure_analyzer;
-mod bess_full_disclosure_analyzer;
-mod bess_complete_analyzer;
-mod ercot_unified_processor;
-mod unified_processor;
-mod csv_extractor;
-mod annual_processor;
+use rt_rust_processor::{
+    comprehensive_processor,
+    process_historical,
+    dam_processor,
+    ancillary_processor,
+    lmp_processor,
+    lmp_fast_processor,
+    lmp_full_processor,
+    disclosure_processor,
+    disclosure_fast_processor,
+    bess_analyzer,
+    bess_revenue_calculator,
+    bess_visualization,
+    bess_market_report,
+    bess_yearly_analysis,
+    deflator,
+    fiscal_year,
+    date_range,
+    bess_comprehensive_calculator,
+    bess_parquet_calculator,
+    bess_operations,
+    bess_disclosure_analyzer,
+    bess_full_disclosure_analyzer,
+    bess_complete_analyzer,
+    ercot_unified_processor,
+    unified_processor,
+    csv_extractor,
+    data_quality,
+    annual_processor,
+    processing_filter,
+    resource_filter,
+    column_diagnostics,
+    price_index,
+    price_outliers,
+    price_store,
+    block_prices,
+    consolidate,
+    compare_runs,
+    analyzer_reconciliation,
+    output_formats,
+    output_prefix::OutputPrefix,
+    revenue_validation,
+    scarcity,
+    run_metadata,
+};
+use rt_rust_processor::{rt_annual_schema, Pipeline, PipelineConfig, PipelineError};
 
-fn verify_data_quality(_dir: &Path) -> Result<()> {
+fn verify_data_quality(_dir: &Path, gate: Option<data_quality::QualityGate>, output_prefix: &OutputPrefix) -> Result<()> {
     println!("\n🔍 Data Quality Verification");
     println!("{}", "=".repeat(60));
     
-    // Find all processed files
+    // Find all processed files, in this run's directories under --output-prefix
     let patterns = vec![
-        "processed_ercot_data/**/*.parquet",
-        "annual_data/*.parquet",
-        "dam_annual_data/*.parquet",
-        "lmp_annual_data/*.parquet",
-        "ancillary_annual_data/*.parquet"
+        ("processed_ercot_data", "**/*.parquet"),
+        ("annual_data", "*.parquet"),
+        ("dam_annual_data", "*.parquet"),
+        ("lmp_annual_data", "*.parquet"),
+        ("ancillary_annual_data", "*.parquet")
     ];
     
     let mut total_issues = 0;
-    
-    for pattern in patterns {
-        let files: Vec<PathBuf> = glob(pattern)?
+    let mut failed_files = Vec::new();
+    // Files whose duplicate or gap checks could not run, with the reason
+    let mut unchecked_files = Vec::new();
+    
+    for (dir, files_pattern) in patterns {
+        let pattern = output_prefix.input_path(dir).join(files_pattern);
+        let pattern = pattern.to_string_lossy();
+        let files: Vec<PathBuf> = glob(&pattern)?
             .filter_map(Result::ok)
             .collect();
             
@@ -74,7 +97,8 @@ fn verify_data_quality(_dir: &Path) -> Result<()> {
             } else if df.get_column_names().contains(&"timestamp") {
                 "timestamp"
             } else {
-                println!("    ⚠️  No datetime column found");
+                println!("    ❌ No datetime column found; duplicates and gaps not checked");
+                unchecked_files.push(format!("{}: no datetime column", file.display()));
                 continue;
             };
             
@@ -86,7 +110,8 @@ fn verify_data_quality(_dir: &Path) -> Result<()> {
             } else if df.get_column_names().contains(&"location") {
                 "location"
             } else {
-                println!("    ⚠️  No location column found");
+                println!("    ❌ No location column found; duplicates and gaps not checked");
+                unchecked_files.push(format!("{}: no location column", file.display()));
                 continue;
             };
             
@@ -97,46 +122,37 @@ fn verify_data_quality(_dir: &Path) -> Result<()> {
                 .filter(col("count").gt(1))
                 .collect()?;
                 
-            if duplicate_check.height() > 0 {
-                println!("    ❌ Found {} duplicate entries", duplicate_check.height());
-                total_issues += duplicate_check.height();
+            let duplicates = duplicate_check.height();
+            if duplicates > 0 {
+                println!("    ❌ Found {} duplicate entries", duplicates);
+                total_issues += duplicates;
             } else {
                 println!("    ✅ No duplicates found");
             }
             
-            // Check for gaps (only for 5-minute interval data)
-            if file.to_str().unwrap().contains("RT_") {
-                // Sort by datetime and check intervals
-                let sorted_df = df.clone().lazy()
-                    .sort(datetime_col, Default::default())
-                    .collect()?;
-                    
-                // Get unique timestamps
-                let timestamps = sorted_df.column(datetime_col)?
-                    .unique()?;
-                    
-                let mut gaps_found = 0;
-                if let Ok(datetime_series) = timestamps.datetime() {
-                    let values: Vec<Option<i64>> = datetime_series.into_iter().collect();
-                    
-                    for i in 1..values.len() {
-                        if let (Some(prev), Some(curr)) = (values[i-1], values[i]) {
-                            let diff_minutes = (curr - prev) / (60 * 1000); // milliseconds to minutes
-                            
-                            // For RT data, expect 5-minute intervals
-                            if diff_minutes > 5 && diff_minutes < 60 {
-                                gaps_found += 1;
-                            }
-                        }
+            // Check for gaps against the dataset's own cadence
+            let file_name = file.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+            let mut gaps = 0;
+            if let Some(cadence) = data_quality::dataset_cadence(file_name) {
+                match data_quality::count_gaps(df.column(datetime_col)?, cadence)? {
+                    Some(0) => println!("    ✅ No gaps in {}-minute time series", cadence.minutes()),
+                    Some(gaps_found) => {
+                        println!("    ⚠️  Found {} gaps in {}-minute time series", gaps_found, cadence.minutes());
+                        total_issues += gaps_found;
+                        gaps = gaps_found;
+                    }
+                    None => {
+                        println!("    ❌ {} is not a datetime column; gaps not checked", datetime_col);
+                        unchecked_files.push(format!("{}: {} is not a datetime column", file.display(), datetime_col));
                     }
                 }
-                
-                if gaps_found > 0 {
-                    println!("    ⚠️  Found {} gaps in time series", gaps_found);
-                    total_issues += gaps_found;
-                } else {
-                    println!("    ✅ No gaps in time series");
-                }
+            } else {
+                println!("    ❌ Unknown interval length for {}; gaps not checked", file_name);
+                unchecked_files.push(format!("{}: unknown interval length, gaps not checked", file.display()));
+            }
+            
+            if let Some(reason) = gate.and_then(|gate| gate.violation(duplicates, gaps)) {
+                failed_files.push(format!("{}: {}", file.display(), reason));
             }
             
             // Check if data is sorted
@@ -161,6 +177,49 @@ fn verify_data_quality(_dir: &Path) -> Result<()> {
             if let Ok(unique_points) = df.column(location_col) {
                 println!("    📊 Unique locations: {}", unique_points.n_unique()?);
             }
+            
+            // Price sanity per location: all-null or constant prices are likely broken
+            if let Some(price_col) = data_quality::price_column(&df) {
+                let summaries = data_quality::location_price_summaries(&df, location_col, price_col)?;
+                let suspect: Vec<_> = summaries.iter()
+                    .filter_map(|s| s.suspicion().map(|suspicion| (s, suspicion)))
+                    .collect();
+                let low = summaries.iter().filter_map(|s| s.min).fold(f64::INFINITY, f64::min);
+                let high = summaries.iter().filter_map(|s| s.max).fold(f64::NEG_INFINITY, f64::max);
+                if low <= high {
+                    println!("    📊 {} range: ${:.2} to ${:.2} across {} locations", price_col, low, high, summaries.len());
+                }
+                
+                if suspect.is_empty() {
+                    println!("    ✅ No locations with all-null or constant prices");
+                } else {
+                    println!("    ❌ {} suspect locations:", suspect.len());
+                    for (summary, suspicion) in suspect.iter().take(10) {
+                        match suspicion {
+                            data_quality::PriceSuspicion::AllNull =>
+                                println!("       {}: all {} prices null", summary.location, summary.rows),
+                            data_quality::PriceSuspicion::Constant(price) =>
+                                println!("       {}: all {} prices ${:.2}", summary.location, summary.prices, price),
+                        }
+                    }
+                    if suspect.len() > 10 {
+                        println!("       ... and {} more", suspect.len() - 10);
+                    }
+                    total_issues += suspect.len();
+                }
+            }
+        }
+    }
+    
+    // A file that could not be checked is an issue, and fails the gate
+    total_issues += unchecked_files.len();
+    if !unchecked_files.is_empty() {
+        println!("\n⚠️  {} files could not be fully checked:", unchecked_files.len());
+        for file in &unchecked_files {
+            println!("   {}", file);
+        }
+        if gate.is_some() {
+            failed_files.extend(unchecked_files);
         }
     }
     
@@ -171,230 +230,85 @@ fn verify_data_quality(_dir: &Path) -> Result<()> {
         println!("⚠️  Data quality verification found {} issues", total_issues);
     }
     
-    Ok(())
-}
-
-fn extract_year_from_filename(filename: &str) -> Option<u16> {
-    // Look for pattern like .20240823. (YYYYMMDD) or _20240823_
-    // Try first pattern
-    if let Some(start) = filename.find(".20") {
-        if let Some(year_str) = filename.get(start + 1..start + 5) {
-            if let Ok(year) = year_str.parse::<u16>() {
-                if year >= 2000 && year <= 2100 {
-                    return Some(year);
-                }
-            }
+    if !failed_files.is_empty() {
+        println!("\n❌ {} files failed --fail-on-gaps:", failed_files.len());
+        for failure in &failed_files {
+            println!("   {}", failure);
         }
+        anyhow::bail!("Data quality gate failed for {} files", failed_files.len());
     }
     
-    // Try second pattern
-    if let Some(start) = filename.find("_20") {
-        if let Some(year_str) = filename.get(start + 1..start + 5) {
-            if let Ok(year) = year_str.parse::<u16>() {
-                if year >= 2000 && year <= 2100 {
-                    return Some(year);
-                }
-            }
-        }
-    }
-    
-    None
+    Ok(())
 }
 
-fn process_year_files(year: u16, files: &[PathBuf], output_dir: &Path) -> Result<()> {
-    println!("\n📅 Processing year {}: {} files", year, files.len());
-    
-    // Create progress bar
-    let pb = ProgressBar::new(files.len() as u64);
-    pb.set_style(ProgressStyle::default_bar()
-        .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} ({eta})")
-        .unwrap());
-    
-    // Process files in parallel batches
-    let batch_size = 100;
-    let mut all_dfs = Vec::new();
-    
-    for chunk in files.chunks(batch_size) {
-        let chunk_dfs: Vec<DataFrame> = chunk
-            .par_iter()
-            .filter_map(|file| {
-                pb.inc(1);
-                
-                // Read CSV with Polars, forcing price column to be float
-                let schema = Arc::new(Schema::from_iter([
-                    Field::new("SettlementPointPrice", DataType::Float64),
-                ]));
-                
-                let df = CsvReader::new(std::fs::File::open(file).ok()?)
-                    .has_header(true)
-                    .with_dtypes(Some(schema))
-                    .finish()
-                    .ok()?;
-                
-                // Check if it has required columns
-                let cols = df.get_column_names();
-                if !cols.contains(&"DeliveryDate") {
-                    return None;
-                }
-                
-                // Handle different column names for settlement point
-                let df = if cols.contains(&"SettlementPointName") && !cols.contains(&"SettlementPoint") {
-                    df.lazy()
-                        .with_column(col("SettlementPointName").alias("SettlementPoint"))
-                        .collect()
-                        .ok()?
-                } else if !cols.contains(&"SettlementPoint") {
-                    return None;
-                } else {
-                    df
-                };
-                
-                Some(df)
-            })
-            .collect();
-        
-        all_dfs.extend(chunk_dfs);
-    }
-    
-    pb.finish_with_message("Files loaded");
-    
-    if all_dfs.is_empty() {
-        println!("  ❌ No valid data for year {}", year);
-        return Ok(());
-    }
-    
-    println!("  📊 Combining {} dataframes...", all_dfs.len());
-    
-    // Concatenate all dataframes
-    let mut combined = concat(
-        all_dfs.iter().map(|df| df.clone().lazy()).collect::<Vec<_>>().as_slice(),
-        UnionArgs::default(),
-    )?
-    .collect()?;
-    
-    // Create datetime column
-    println!("  🕐 Creating datetime column...");
-    let delivery_dates = combined.column("DeliveryDate")?;
-    let delivery_hours = combined.column("DeliveryHour")?.cast(&DataType::Int32)?;
-    let delivery_intervals = combined.column("DeliveryInterval")?.cast(&DataType::Int32)?;
-    
-    // Calculate datetime components
-    let hours = delivery_hours.i32()?
-        .apply(|v| if v.unwrap_or(0) == 24 { Some(0) } else { v.map(|x| x - 1) });
+/// Value following `flag` on the command line
+fn arg_value(args: &[String], flag: &str) -> Option<String> {
+    args.iter()
+        .position(|arg| arg == flag)
+        .and_then(|i| args.get(i + 1))
+        .cloned()
+}
+
+fn main() -> Result<()> {
+    // Progress goes through `log`; RUST_LOG controls verbosity (default: info)
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info"))
+        .format_target(false)
+        .format_timestamp(None)
+        .init();
+
+    // Check command line arguments
+    let args: Vec<String> = std::env::args().collect();
     
-    let minutes = delivery_intervals.i32()?
-        .apply(|i| i.map(|v| (v - 1) * 15));
+    // Provenance for run_metadata.json, written to each output directory at the end
+    run_metadata::start(&args);
     
-    // Parse dates and create datetime
-    let mut datetimes = Vec::new();
-    for i in 0..combined.height() {
-        if let Some(date_str) = delivery_dates.utf8()?.get(i) {
-            if let Ok(date) = NaiveDate::parse_from_str(date_str, "%m/%d/%Y") {
-                let hour = hours.get(i).unwrap_or(0) as u32;
-                let minute = minutes.get(i).unwrap_or(0) as u32;
-                let mut datetime = date.and_hms_opt(hour, minute, 0).unwrap();
-                
-                // Handle hour 24
-                if delivery_hours.i32()?.get(i) == Some(24) {
-                    datetime = datetime + Duration::days(1);
-                }
-                
-                datetimes.push(Some(datetime.and_utc().timestamp_millis())); // milliseconds
-            } else {
-                datetimes.push(None);
-            }
-        } else {
-            datetimes.push(None);
-        }
+    // --output-prefix <name> writes this run's outputs to `<dir>_<name>/`
+    let output_prefix = OutputPrefix::from_args(&args)?;
+    if let Some(prefix) = output_prefix.name() {
+        info!("Writing outputs to directories suffixed _{}", prefix);
     }
     
-    let datetime_series = Series::new("datetime", datetimes);
-    combined.with_column(datetime_series)?;
-    
-    // Select and rename columns
-    println!("  📋 Selecting columns...");
-    let cols = combined.get_column_names();
-    let price_col = if cols.contains(&"SettlementPointPrice") {
-        col("SettlementPointPrice")
-    } else if cols.contains(&"LMP") {
-        col("LMP")
-    } else {
-        return Err(anyhow::anyhow!("No price column found"));
+    // Set Rayon to use all available cores, or --max-threads <n>: fewer
+    // threads hold fewer files in memory at once
+    let num_threads = match arg_value(&args, "--max-threads") {
+        Some(n) => n.parse::<usize>()
+            .ok()
+            .filter(|n| *n > 0)
+            .ok_or_else(|| anyhow::anyhow!("Invalid --max-threads: {} (expected a positive integer)", n))?,
+        None => num_cpus::get(),
     };
+    run_metadata::record_config("threads", num_threads);
     
-    let final_df = combined.lazy()
-        .select([
-            col("datetime"),
-            col("SettlementPoint"),
-            price_col.alias("SettlementPointPrice"),
-        ])
-        .collect()?;
-    
-    // Remove duplicates first (keeping the last occurrence)
-    println!("  🧹 Removing duplicates...");
-    let unique_df = final_df.unique(Some(&["datetime".to_string(), "SettlementPoint".to_string()]), UniqueKeepStrategy::Last, None)?;
-    
-    // Sort by datetime and settlement point
-    println!("  🔄 Sorting data...");
-    let sorted_df = unique_df.lazy()
-        .sort_by_exprs([col("datetime"), col("SettlementPoint")], [false, false], false, false)
-        .collect()?;
-    
-    println!("  📊 Final record count: {}", sorted_df.height());
-    
-    // Save files
-    let base_name = format!("RT_Settlement_Point_Prices_{}", year);
-    
-    // CSV
-    let csv_path = output_dir.join(format!("{}.csv", base_name));
-    println!("  💾 Saving CSV...");
-    CsvWriter::new(std::fs::File::create(&csv_path)?)
-        .finish(&mut sorted_df.clone())?;
-    
-    // Parquet
-    let parquet_path = output_dir.join(format!("{}.parquet", base_name));
-    println!("  📦 Saving Parquet...");
-    ParquetWriter::new(std::fs::File::create(&parquet_path)?)
-        .finish(&mut sorted_df.clone())?;
-    
-    // Arrow IPC (similar to .arrow)
-    let arrow_path = output_dir.join(format!("{}.arrow", base_name));
-    println!("  🏹 Saving Arrow IPC...");
-    IpcWriter::new(std::fs::File::create(&arrow_path)?)
-        .finish(&mut sorted_df.clone())?;
-    
-    println!("  ✅ Completed year {}", year);
-    Ok(())
-}
-
-fn main() -> Result<()> {
-    // Set Rayon to use all available cores
+    // --formats csv,parquet,arrow limits which annual files are written
+    let output_formats = output_formats::OutputFormats::from_args(&args)?;
+    if let Some(formats) = output_formats {
+        info!("Writing annual files as {}", formats.describe());
+        formats.warn_if_price_loaders_miss_csv();
+    }
+    let formats = output_formats.unwrap_or_default();
     rayon::ThreadPoolBuilder::new()
-        .num_threads(num_cpus::get())
+        .num_threads(num_threads)
         .build_global()
         .unwrap();
     
-    // Check command line arguments
-    let args: Vec<String> = std::env::args().collect();
-    
     if args.len() > 1 && args[1] == "--all" {
         // Process all ERCOT data types
-        comprehensive_processor::process_all_ercot_data()?;
+        comprehensive_processor::process_all_ercot_data(&output_prefix)?;
     } else if args.len() > 1 && args[1] == "--extract" {
         // Extract historical data
-        process_historical::extract_and_process_historical()?;
+        process_historical::extract_and_process_historical(&output_prefix)?;
     } else if args.len() > 1 && args[1] == "--dam" {
         // Process DAM settlement data
-        dam_processor::process_all_dam_data()?;
+        dam_processor::process_all_dam_data(formats, &output_prefix)?;
     } else if args.len() > 1 && args[1] == "--ancillary" {
         // Process ancillary services data
-        ancillary_processor::process_all_ancillary_data()?;
+        ancillary_processor::process_all_ancillary_data(formats, &output_prefix)?;
     } else if args.len() > 1 && args[1] == "--lmp" {
         // Process LMP data with nested extraction
-        lmp_processor::process_all_lmp_data()?;
+        lmp_processor::process_all_lmp_data(formats, &output_prefix)?;
     } else if args.len() > 1 && args[1] == "--lmp-fast" {
         // Process existing LMP CSV files only
-        lmp_fast_processor::process_existing_lmp_csv()?;
+        lmp_fast_processor::process_existing_lmp_csv(formats, &output_prefix)?;
     } else if args.len() > 1 && args[1] == "--lmp-sample" {
         // Process sample of LMP data
         let sample_size = if args.len() > 2 {
@@ -402,49 +316,152 @@ fn main() -> Result<()> {
         } else {
             1000
         };
-        lmp_fast_processor::process_lmp_sample(sample_size)?;
+        lmp_fast_processor::process_lmp_sample(sample_size, formats, &output_prefix)?;
     } else if args.len() > 1 && args[1] == "--lmp-all" {
         // Process ALL LMP historical data
-        lmp_full_processor::process_all_lmp_historical()?;
+        lmp_full_processor::process_all_lmp_historical(formats, &output_prefix)?;
     } else if args.len() > 1 && args[1] == "--disclosure" {
         // Process 60-Day disclosure reports
-        disclosure_processor::process_all_disclosures()?;
+        disclosure_processor::process_all_disclosures(formats, &output_prefix)?;
     } else if args.len() > 1 && args[1] == "--disclosure-fast" {
         // Process already extracted disclosure CSV files
-        disclosure_fast_processor::process_disclosure_fast()?;
+        disclosure_fast_processor::process_disclosure_fast(&output_prefix)?;
     } else if args.len() > 1 && args[1] == "--bess" {
         // Analyze BESS resources
-        bess_analyzer::analyze_bess_resources()?;
+        bess_analyzer::analyze_bess_resources(&output_prefix)?;
     } else if args.len() > 1 && args[1] == "--bess-revenue" {
         // Calculate BESS revenues using Parquet files
-        bess_parquet_calculator::calculate_bess_revenues_from_parquet()?;
+        // (honors --round-trip-efficiency <0-1>, --default-duration-hours <h>
+        // and --resource-regex <pattern>)
+        let round_trip_efficiency = match arg_value(&args, "--round-trip-efficiency") {
+            Some(e) => e.parse::<f64>()
+                .ok()
+                .filter(|e| *e > 0.0 && *e <= 1.0)
+                .ok_or_else(|| anyhow::anyhow!("Invalid --round-trip-efficiency: {} (expected 0-1)", e))?,
+            None => bess_operations::DEFAULT_ROUND_TRIP_EFFICIENCY,
+        };
+        let default_duration = bess_analyzer::default_duration_from_args(&args)?;
+        let resource_filter = resource_filter::ResourceFilter::from_args(&args)?;
+        bess_parquet_calculator::calculate_bess_revenues_from_parquet(round_trip_efficiency, default_duration, &resource_filter, &output_prefix)?;
     } else if args.len() > 1 && args[1] == "--bess-report" {
         // Generate comprehensive BESS market report
-        bess_market_report::generate_market_report()?;
+        bess_market_report::generate_market_report(&output_prefix)?;
     } else if args.len() > 1 && args[1] == "--bess-yearly" {
-        // Generate yearly BESS analysis
-        bess_yearly_analysis::generate_yearly_analysis()?;
+        // Generate yearly BESS analysis (honors --deflator <file> [--base-year <year>])
+        let deflator = match arg_value(&args, "--deflator") {
+            Some(path) => {
+                let base_year = match arg_value(&args, "--base-year") {
+                    Some(y) => Some(y.parse::<i32>()
+                        .map_err(|_| anyhow::anyhow!("Invalid --base-year: {}", y))?),
+                    None => None,
+                };
+                Some(deflator::Deflator::load(&PathBuf::from(path), base_year)?)
+            }
+            None => None,
+        };
+        bess_yearly_analysis::generate_yearly_analysis(deflator, &output_prefix)?;
     } else if args.len() > 1 && args[1] == "--bess-viz" {
         // Generate BESS visualizations
-        bess_visualization::generate_bess_visualizations()?;
+        bess_visualization::generate_bess_visualizations(&output_prefix)?;
+    } else if args.len() > 1 && args[1] == "--bess-timeseries" {
+        // Export per-resource long-format (date, stream, revenue) CSVs for charting
+        let daily = arg_value(&args, "--daily")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| output_prefix.input_path("bess_analysis/bess_daily_revenues.parquet"));
+        let output = arg_value(&args, "--output")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| output_prefix.output_dir("bess_analysis/timeseries"));
+        let daily_df = ParquetReader::new(std::fs::File::open(&daily)
+            .with_context(|| format!("Failed to open daily revenues {}", daily.display()))?)
+            .finish()?;
+        run_metadata::record_output_dir(&output);
+        bess_visualization::export_revenue_timeseries(&daily_df, &output)?;
     } else if args.len() > 1 && args[1] == "--bess-comprehensive" {
         // Run comprehensive BESS analysis using Parquet data
-        bess_comprehensive_calculator::run_comprehensive_bess_analysis()?;
+        // (honors --default-duration-hours and --resource-regex)
+        let default_duration = bess_analyzer::default_duration_from_args(&args)?;
+        let resource_filter = resource_filter::ResourceFilter::from_args(&args)?;
+        bess_comprehensive_calculator::run_comprehensive_bess_analysis(default_duration, &resource_filter, &output_prefix)?;
     } else if args.len() > 1 && args[1] == "--bess-disclosure" {
         // Analyze BESS revenues from 60-day disclosure data
-        bess_disclosure_analyzer::analyze_bess_disclosure_revenues()?;
+        // (honors --default-duration-hours, --resource-regex, --fiscal-year-start <month>
+        // and --start-date / --end-date YYYY-MM-DD)
+        let default_duration = bess_analyzer::default_duration_from_args(&args)?;
+        let resource_filter = resource_filter::ResourceFilter::from_args(&args)?;
+        let fiscal_calendar = fiscal_year::FiscalCalendar::from_args(&args)?;
+        let date_range = date_range::DateRange::from_args(&args)?;
+        bess_disclosure_analyzer::analyze_bess_disclosure_revenues(default_duration, &resource_filter, fiscal_calendar, date_range, &output_prefix)?;
     } else if args.len() > 1 && args[1] == "--bess-full-disclosure" {
         // Run complete BESS analysis with full 60-day disclosure dataset
-        bess_full_disclosure_analyzer::analyze_bess_with_full_disclosure()?;
+        // (honors --revenue-basis net|gross, --revenue-view net|gross-discharge, --min-days <n>, --price-loading eager|lazy
+        // --duplicate-prices latest|average, --award-sign gen-positive|load-positive,
+        // --resource-regex <pattern>, --default-duration-hours <h>,
+        // --dump-unmatched <csv>, --price-outliers abs:<$>|zscore:<sigmas>
+        // [--price-outlier-action drop|cap], --start-date / --end-date YYYY-MM-DD
+        // --export-price-cache <dir>, --sqlite <path> (sqlite feature) and
+        // --scarcity-price <$/MWh>)
+        let revenue_basis = bess_revenue_calculator::RevenueBasis::from_args(&args)?;
+        let revenue_view = bess_revenue_calculator::RevenueView::from_args(&args)?;
+        let price_loading = price_store::PriceLoading::from_args(&args)?;
+        let duplicate_prices = price_index::DuplicatePricePolicy::from_args(&args)?;
+        let award_sign = bess_revenue_calculator::AwardSign::from_args(&args)?;
+        let resource_filter = resource_filter::ResourceFilter::from_args(&args)?;
+        let default_duration = bess_analyzer::default_duration_from_args(&args)?;
+        let dump_unmatched = arg_value(&args, "--dump-unmatched").map(PathBuf::from);
+        let price_outliers = price_outliers::PriceOutlierFilter::from_args(&args)?;
+        let date_range = date_range::DateRange::from_args(&args)?;
+        let export_price_cache = arg_value(&args, "--export-price-cache").map(PathBuf::from);
+        let sqlite_path = arg_value(&args, "--sqlite").map(PathBuf::from);
+        #[cfg(not(feature = "sqlite"))]
+        if sqlite_path.is_some() {
+            anyhow::bail!("--sqlite needs a build with the sqlite feature (cargo build --release --features sqlite)");
+        }
+        let min_days = match arg_value(&args, "--min-days") {
+            Some(d) => d.parse::<u32>()
+                .map_err(|_| anyhow::anyhow!("Invalid --min-days: {}", d))?,
+            None => bess_revenue_calculator::DEFAULT_MIN_DAYS_FOR_ANNUALIZATION,
+        };
+        let scarcity_price = match arg_value(&args, "--scarcity-price") {
+            Some(p) => p.parse::<f64>()
+                .map_err(|_| anyhow::anyhow!("Invalid --scarcity-price: {}", p))?,
+            None => scarcity::DEFAULT_SCARCITY_PRICE,
+        };
+        let options = bess_revenue_calculator::RevenueRunOptions {
+            revenue_basis,
+            revenue_view,
+            min_days_for_annualization: min_days,
+            price_loading,
+            duplicate_prices,
+            award_sign,
+            resource_filter,
+            default_duration_hours: default_duration,
+            dump_unmatched,
+            price_outliers,
+            date_range,
+            export_price_cache,
+            sqlite_path,
+            scarcity_price,
+            output_prefix,
+        };
+        bess_full_disclosure_analyzer::analyze_bess_with_full_disclosure(&options)?;
     } else if args.len() > 1 && args[1] == "--bess-complete" {
-        // Run complete BESS revenue analysis with all data sources
-        bess_complete_analyzer::run_complete_bess_analysis()?;
+        // Run complete BESS revenue analysis with all data sources (honors --resource-regex)
+        let resource_filter = resource_filter::ResourceFilter::from_args(&args)?;
+        bess_complete_analyzer::run_complete_bess_analysis(&resource_filter, &output_prefix)?;
     } else if args.len() > 1 && args[1] == "--process-ercot" {
-        // Process all ERCOT data from source directories
-        ercot_unified_processor::process_all_ercot_data()?;
+        // Process all ERCOT data from source directories (honors --only-years / --only-datasets)
+        let filter = processing_filter::ProcessingFilter::from_args(&args)?;
+        ercot_unified_processor::process_all_ercot_data(filter, formats, &output_prefix)?;
     } else if args.len() > 1 && args[1] == "--unified" {
         // Process data with unified processor (recursive unzip, dedup, etc.)
-        unified_processor::process_unified_data()?;
+        // (honors --only-years / --only-datasets and --year-by-majority)
+        let filter = processing_filter::ProcessingFilter::from_args(&args)?;
+        let year_inference = if args.iter().any(|a| a == "--year-by-majority") {
+            unified_processor::YearInference::Majority
+        } else {
+            unified_processor::YearInference::FirstValidRow
+        };
+        unified_processor::process_unified_data(filter, year_inference, formats, &output_prefix)?;
     } else if args.len() > 1 && args[1] == "--extract-csv" {
         // Extract all CSV files from nested ZIPs into a single csv folder
         if args.len() > 2 {
@@ -465,16 +482,82 @@ fn main() -> Result<()> {
         }
     } else if args.len() > 1 && args[1] == "--process-annual" {
         // Process extracted CSV files into annual CSV, Parquet, and Arrow files
-        annual_processor::process_all_annual_data()?;
+        // (--streaming converts each year to Parquet/Arrow without holding it in memory)
+        annual_processor::process_all_annual_data(args.iter().any(|a| a == "--streaming"), output_formats, &output_prefix)?;
+    } else if args.len() > 1 && args[1] == "--block-prices" {
+        // Calculate monthly 7x16 on-peak, off-peak and 7x24 block prices from DAM/RT data
+        block_prices::calculate_block_prices(&output_prefix)?;
+    } else if args.len() > 1 && args[1] == "--consolidate" {
+        // Merge per-year {prefix}_{year}.parquet files into one {prefix}_ALL.parquet
+        match arg_value(&args, "--dataset") {
+            Some(dataset) => {
+                let data_dir = arg_value(&args, "--dir").unwrap_or_else(|| "annual_data".to_string());
+                consolidate::consolidate_dataset(&PathBuf::from(data_dir), &dataset)?;
+            }
+            None => {
+                println!("Usage: --consolidate --dataset <prefix> [--dir <directory>]");
+                println!("Example: --consolidate --dataset RT_Settlement_Point_Prices --dir annual_data");
+            }
+        }
+    } else if args.len() > 1 && args[1] == "--validate-revenue" {
+        // Reconcile computed monthly revenues against ERCOT settlement statement totals
+        match arg_value(&args, "--statement") {
+            Some(statement) => {
+                let daily = arg_value(&args, "--daily")
+                    .map(PathBuf::from)
+                    .unwrap_or_else(|| output_prefix.input_path("bess_analysis/bess_daily_revenues.parquet"));
+                let tolerance = match arg_value(&args, "--tolerance") {
+                    Some(t) => t.parse::<f64>()
+                        .map_err(|_| anyhow::anyhow!("Invalid --tolerance: {}", t))?,
+                    None => revenue_validation::DEFAULT_TOLERANCE_PCT,
+                };
+                revenue_validation::validate_revenue(&PathBuf::from(statement), &daily, tolerance)?;
+            }
+            None => {
+                println!("Usage: --validate-revenue --statement <file> [--daily <parquet>] [--tolerance <pct>]");
+                println!("Example: --validate-revenue --statement statements_2024.csv --tolerance 5");
+            }
+        }
+    } else if args.len() > 1 && args[1] == "--compare-runs" {
+        // Diff matching Parquet outputs of two runs, e.g. before and after a refactor
+        match (args.get(2).filter(|a| !a.starts_with("--")), args.get(3).filter(|a| !a.starts_with("--"))) {
+            (Some(dir_a), Some(dir_b)) => {
+                let tolerance = match arg_value(&args, "--tolerance") {
+                    Some(t) => t.parse::<f64>()
+                        .map_err(|_| anyhow::anyhow!("Invalid --tolerance: {}", t))?,
+                    None => compare_runs::DEFAULT_TOLERANCE,
+                };
+                compare_runs::compare_runs(&PathBuf::from(dir_a), &PathBuf::from(dir_b), tolerance)?;
+            }
+            _ => {
+                println!("Usage: --compare-runs <dirA> <dirB> [--tolerance <abs>]");
+                println!("Example: --compare-runs bess_analysis_before bess_analysis --tolerance 0.01");
+            }
+        }
+    } else if args.len() > 1 && args[1] == "--reconcile-analyzers" {
+        // Compare the revenue calculator, parquet calculator and complete analyzer
+        // outputs per resource-year (honors --dir <run dir> and --tolerance <pct>)
+        let base_dir = arg_value(&args, "--dir").unwrap_or_else(|| ".".to_string());
+        let tolerance = match arg_value(&args, "--tolerance") {
+            Some(t) => t.parse::<f64>()
+                .map_err(|_| anyhow::anyhow!("Invalid --tolerance: {}", t))?,
+            None => analyzer_reconciliation::DEFAULT_TOLERANCE_PCT,
+        };
+        analyzer_reconciliation::reconcile_analyzers(&PathBuf::from(base_dir), tolerance)?;
+    } else if args.len() > 1 && args[1] == "--diagnose-columns" {
+        // Report which AS award columns each DAM Gen Resource Data file carries
+        let data_dir = arg_value(&args, "--dir").unwrap_or_else(|| "disclosure_data".to_string());
+        column_diagnostics::diagnose_columns(&PathBuf::from(data_dir))?;
     } else if args.len() > 1 && args[1] == "--verify-results" {
-        // Verify data quality of processed files
-        verify_data_quality(&PathBuf::from("."))?;
+        // Verify data quality of processed files; honors --fail-on-gaps,
+        // --max-duplicates and --max-gaps
+        let gate = data_quality::QualityGate::from_args(&args)?;
+        verify_data_quality(&PathBuf::from("."), gate, &output_prefix)?;
     } else {
         // Process only RT Settlement Point Prices (original functionality)
-        println!("🚀 ERCOT RT Settlement Point Prices - Rust Processor");
-        println!("Using {} CPU cores", num_cpus::get());
-        println!("Rayon thread pool configured with {} threads", rayon::current_num_threads());
-        println!("{}", "=".repeat(60));
+        info!("ERCOT RT Settlement Point Prices - Rust Processor");
+        info!("Using {} CPU cores", num_cpus::get());
+        info!("Rayon thread pool configured with {} threads", rayon::current_num_threads());
         
         // Use test data directory for testing
         let data_dir = if std::env::args().any(|arg| arg == "--test") {
@@ -483,40 +566,44 @@ fn main() -> Result<()> {
             PathBuf::from("/Users/enrico/data/ERCOT_data/Settlement_Point_Prices_at_Resource_Nodes,_Hubs_and_Load_Zones/csv")
         };
         
-        let output_dir = PathBuf::from("annual_data");
-        std::fs::create_dir_all(&output_dir)?;
-    
-    // Find all CSV files
-    let pattern = data_dir.join("*.csv");
-    let csv_files: Vec<PathBuf> = glob(pattern.to_str().unwrap())?
-        .filter_map(Result::ok)
-        .collect();
-    
-    println!("Found {} RT CSV files", csv_files.len());
-    
-    // Group files by year
-    let mut files_by_year: HashMap<u16, Vec<PathBuf>> = HashMap::new();
-    for file in csv_files {
-        if let Some(year) = extract_year_from_filename(file.file_name().unwrap().to_str().unwrap()) {
-            files_by_year.entry(year).or_insert_with(Vec::new).push(file);
-        }
-    }
-    
-    let mut years: Vec<u16> = files_by_year.keys().cloned().collect();
-    years.sort();
-    println!("Years found: {:?}", years);
+        let config = PipelineConfig::new(data_dir)
+            .with_output_prefix(output_prefix)
+            .with_formats(formats)
+            .with_progress(true);
+        run_metadata::record_output_dir(&config.prefixed_output_dir());
+        let pipeline = Pipeline::new(config);
+    
+    // Find all CSV files, grouped by year
+    let files_by_year = pipeline.rt_files_by_year()?;
+    let rt_file_count = files_by_year.values().map(Vec::len).sum::<usize>();
+    info!("Found {} RT CSV files", rt_file_count);
+    run_metadata::record_inputs("RT Settlement Point Prices CSV", rt_file_count);
+    info!("Years found: {:?}", files_by_year.keys().collect::<Vec<_>>());
     
     // Process each year
     let start = std::time::Instant::now();
     
-    for year in years {
-        let year_files = &files_by_year[&year];
-        process_year_files(year, year_files, &output_dir)?;
+    for (year, year_files) in &files_by_year {
+        info!("Processing year {}: {} files", year, year_files.len());
+        let df = match pipeline.process_rt_settlement_prices_for_year(*year) {
+            Ok(df) => df,
+            Err(e) if matches!(e.downcast_ref(), Some(PipelineError::NoDataForYear { .. })) => {
+                warn!("No valid data for year {}; writing schema-only annual files", year);
+                pipeline.write_rt_annual_files(&DataFrame::from(&rt_annual_schema()), *year)?;
+                continue;
+            }
+            Err(e) => return Err(e),
+        };
+        info!("Final record count: {}", df.height());
+        pipeline.write_rt_annual_files(&df, *year)?;
+        info!("Completed year {}", year);
     }
     
         let duration = start.elapsed();
-        println!("\n✅ Processing complete in {:?}!", duration);
+        info!("Processing complete in {:?}!", duration);
     }
     
+    run_metadata::finish()?;
     Ok(())
-}
\ No newline at end of file
+}
+
@@ -0,0 +1,184 @@
+use anyhow::{Context, Result};
+use chrono::NaiveDate;
+use polars::prelude::*;
+use std::path::Path;
+
+use crate::error::require_column;
+
+/// Columns every master list must have; the rest are optional
+pub const REQUIRED_MASTER_LIST_COLUMNS: [&str; 3] = ["Resource_Name", "Settlement_Point", "Max_Capacity_MW"];
+
+/// One battery from the BESS master list written by `BessAnalyzer`
+#[derive(Debug, Clone, PartialEq)]
+pub struct BessResource {
+    pub name: String,
+    pub settlement_point: String,
+    pub capacity_mw: f64,
+    /// From `Duration_Hours`, or `Max_Capacity_MWh` / `Max_Capacity_MW`;
+    /// `None` leaves it to `ResourceDurations`' default
+    pub duration_hours: Option<f64>,
+    /// `UNKNOWN` when the list has no `QSE`
+    pub qse: String,
+    /// From the optional `Commissioning_Date` column
+    pub commissioning_date: Option<NaiveDate>,
+}
+
+/// Read the BESS master list as CSV or, for a `.parquet` path, Parquet
+pub fn read_master_list(path: &Path) -> Result<DataFrame> {
+    let file = std::fs::File::open(path)?;
+    let is_parquet = path
+        .extension()
+        .map_or(false, |ext| ext.eq_ignore_ascii_case("parquet"));
+
+    let df = if is_parquet {
+        ParquetReader::new(file).finish()?
+    } else {
+        CsvReader::new(file).has_header(true).finish()?
+    };
+    Ok(df)
+}
+
+/// Every master list row with a name, settlement point and capacity
+pub fn load_master_list(path: &Path) -> Result<Vec<BessResource>> {
+    resources_from_master_list(&read_master_list(path)?)
+        .with_context(|| format!("Invalid BESS master list {}", path.display()))
+}
+
+/// Fail naming every missing required column and listing the columns the
+/// list does have, so a misspelled header is obvious
+fn check_required_columns(df: &DataFrame) -> Result<()> {
+    let found = df.get_column_names();
+    let missing: Vec<&str> = REQUIRED_MASTER_LIST_COLUMNS
+        .iter()
+        .copied()
+        .filter(|name| !found.contains(name))
+        .collect();
+    if !missing.is_empty() {
+        anyhow::bail!(
+            "Master list is missing required column(s) {}; found columns: {}",
+            missing.join(", "),
+            found.join(", ")
+        );
+    }
+    Ok(())
+}
+
+pub fn resources_from_master_list(df: &DataFrame) -> Result<Vec<BessResource>> {
+    check_required_columns(df)?;
+    let utf8 = |name: &str| {
+        df.column(name).ok()
+            .and_then(|c| c.cast(&DataType::Utf8).ok())
+            .and_then(|c| c.utf8().ok().cloned())
+    };
+    let as_f64 = |name: &str| {
+        df.column(name).ok()
+            .and_then(|c| c.cast(&DataType::Float64).ok())
+            .and_then(|c| c.f64().ok().cloned())
+    };
+
+    let names = require_column(df, "Resource_Name")?.utf8()?.clone();
+    let settlement_points = require_column(df, "Settlement_Point")?.utf8()?.clone();
+    let capacities = require_column(df, "Max_Capacity_MW")?.cast(&DataType::Float64)?.f64()?.clone();
+    let hours = as_f64("Duration_Hours");
+    let energy = as_f64("Max_Capacity_MWh");
+    let qses = utf8("QSE");
+    let commissioning = utf8("Commissioning_Date");
+
+    let mut resources = Vec::new();
+    for i in 0..df.height() {
+        let (Some(name), Some(sp), Some(capacity)) = (names.get(i), settlement_points.get(i), capacities.get(i)) else {
+            continue;
+        };
+        let duration_hours = hours.as_ref().and_then(|h| h.get(i)).or_else(|| {
+            match energy.as_ref().and_then(|e| e.get(i)) {
+                Some(mwh) if capacity > 0.0 => Some(mwh / capacity),
+                _ => None,
+            }
+        });
+        let commissioning_date = commissioning.as_ref().and_then(|c| c.get(i)).and_then(|date| {
+            NaiveDate::parse_from_str(date, "%Y-%m-%d")
+                .or_else(|_| NaiveDate::parse_from_str(date, "%m/%d/%Y"))
+                .ok()
+        });
+
+        resources.push(BessResource {
+            name: name.to_string(),
+            settlement_point: sp.to_string(),
+            capacity_mw: capacity,
+            duration_hours: duration_hours.filter(|d| *d > 0.0),
+            qse: qses.as_ref().and_then(|q| q.get(i)).unwrap_or("UNKNOWN").to_string(),
+            commissioning_date,
+        });
+    }
+    Ok(resources)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_master_list_parquet_matches_csv() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut df = df! {
+            "Resource_Name" => &["BESS_A", "BESS_B"],
+            "Settlement_Point" => &["SP_A", "SP_B"],
+            "Max_Capacity_MW" => &[100.5, 9.9],
+        }
+        .unwrap();
+
+        let csv_path = dir.path().join("bess_resources_master_list.csv");
+        CsvWriter::new(std::fs::File::create(&csv_path).unwrap()).finish(&mut df).unwrap();
+        let parquet_path = dir.path().join("bess_resources_master_list.parquet");
+        ParquetWriter::new(std::fs::File::create(&parquet_path).unwrap()).finish(&mut df).unwrap();
+
+        let from_csv = read_master_list(&csv_path).unwrap();
+        let from_parquet = read_master_list(&parquet_path).unwrap();
+        assert!(from_csv.frame_equal(&from_parquet));
+        assert!(from_parquet.frame_equal(&df));
+        assert_eq!(load_master_list(&csv_path).unwrap(), load_master_list(&parquet_path).unwrap());
+    }
+
+    #[test]
+    fn test_load_master_list_reads_optional_columns() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("bess_resources_master_list.csv");
+        std::fs::write(&path, "Resource_Name,QSE,Settlement_Point,Max_Capacity_MW,Max_Capacity_MWh,Commissioning_Date\n\
+                               BATCAVE_BES1,QSE_A,BATCAVE_RN,100,200,2021-06-15\n\
+                               ALPHA_BESS,,ALPHA_RN,50,,\n\
+                               NO_SP_BESS,QSE_B,,10,20,\n").unwrap();
+
+        let resources = load_master_list(&path).unwrap();
+        assert_eq!(resources, vec![
+            BessResource {
+                name: "BATCAVE_BES1".to_string(),
+                settlement_point: "BATCAVE_RN".to_string(),
+                capacity_mw: 100.0,
+                duration_hours: Some(2.0),
+                qse: "QSE_A".to_string(),
+                commissioning_date: NaiveDate::from_ymd_opt(2021, 6, 15),
+            },
+            BessResource {
+                name: "ALPHA_BESS".to_string(),
+                settlement_point: "ALPHA_RN".to_string(),
+                capacity_mw: 50.0,
+                duration_hours: None,
+                qse: "UNKNOWN".to_string(),
+                commissioning_date: None,
+            },
+        ]);
+    }
+
+    #[test]
+    fn test_misspelled_required_column_names_it_and_lists_found_columns() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("bess_resources_master_list.csv");
+        std::fs::write(&path, "Resource_Name,Settlment_Point,Max_Capacity_MW\n\
+                               BATCAVE_BES1,BATCAVE_RN,100\n").unwrap();
+
+        let message = format!("{:#}", load_master_list(&path).unwrap_err());
+        assert!(message.contains(&path.display().to_string()), "{}", message);
+        assert!(message.contains("missing required column(s) Settlement_Point;"), "{}", message);
+        assert!(message.contains("found columns: Resource_Name, Settlment_Point, Max_Capacity_MW"), "{}", message);
+    }
+}
@@ -0,0 +1,253 @@
+use anyhow::Result;
+use chrono::NaiveDate;
+use polars::prelude::*;
+use std::path::Path;
+
+use crate::error::require_column;
+
+/// Number of price/quantity pairs in a 60-day DAM Gen Resource Data energy offer curve
+const CURVE_POINTS: usize = 10;
+
+/// One step of an energy offer curve: willing to sell up to `mw` at `price` or above
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OfferPoint {
+    pub mw: f64,
+    pub price: f64,
+}
+
+/// QSE-submitted energy offer curve for one resource-hour, in increasing MW order
+#[derive(Debug, Clone, Default)]
+pub struct OfferCurve {
+    points: Vec<OfferPoint>,
+}
+
+impl OfferCurve {
+    /// Curve from `points` in any order; points with a NaN or infinite MW
+    /// or price are dropped
+    pub fn new(mut points: Vec<OfferPoint>) -> Self {
+        points.retain(|p| p.mw.is_finite() && p.price.is_finite());
+        points.sort_by(|a, b| a.mw.total_cmp(&b.mw));
+        Self { points }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    /// Lowest price at which the resource was willing to discharge
+    pub fn marginal_price(&self) -> Option<f64> {
+        self.points.first().map(|p| p.price)
+    }
+
+    /// Quantity the resource offered at or below `price`
+    pub fn willing_mw_at(&self, price: f64) -> f64 {
+        self.points
+            .iter()
+            .filter(|p| p.price <= price)
+            .map(|p| p.mw)
+            .fold(0.0, f64::max)
+    }
+
+    /// Margin over the offer price for every in-the-money MW above `awarded_mw`
+    pub fn missed_margin(&self, price: f64, awarded_mw: f64) -> f64 {
+        let mut margin = 0.0;
+        let mut previous_mw: f64 = 0.0;
+        for point in &self.points {
+            if point.price > price {
+                break;
+            }
+            let uncleared = point.mw - previous_mw.max(awarded_mw);
+            if uncleared > 0.0 {
+                margin += uncleared * (price - point.price);
+            }
+            previous_mw = point.mw;
+        }
+        margin
+    }
+}
+
+/// An hour where the battery offered energy below the DAM price but was not
+/// (fully) awarded
+#[derive(Debug, Clone)]
+pub struct MissedAward {
+    pub resource_name: String,
+    pub delivery_date: NaiveDate,
+    pub hour_ending: i64,
+    pub settlement_price: f64,
+    pub marginal_offer_price: f64,
+    pub offered_mw: f64,
+    pub awarded_mw: f64,
+    pub missed_margin: f64,
+}
+
+impl MissedAward {
+    pub fn uncleared_mw(&self) -> f64 {
+        self.offered_mw - self.awarded_mw.max(0.0)
+    }
+}
+
+/// Compare an hour's offer curve with its award and settlement price
+pub fn missed_award(
+    resource_name: &str,
+    delivery_date: NaiveDate,
+    hour_ending: i64,
+    curve: &OfferCurve,
+    awarded_mw: f64,
+    settlement_price: f64,
+) -> Option<MissedAward> {
+    let marginal_offer_price = curve.marginal_price()?;
+    let offered_mw = curve.willing_mw_at(settlement_price);
+    let missed_margin = curve.missed_margin(settlement_price, awarded_mw.max(0.0));
+    if offered_mw <= awarded_mw || missed_margin <= 0.0 {
+        return None;
+    }
+
+    Some(MissedAward {
+        resource_name: resource_name.to_string(),
+        delivery_date,
+        hour_ending,
+        settlement_price,
+        marginal_offer_price,
+        offered_mw,
+        awarded_mw,
+        missed_margin,
+    })
+}
+
+/// Missed awards for every resource-hour in a (PWRSTR-filtered) DAM Gen
+/// Resource Data frame. Frames without offer curve columns yield nothing.
+pub fn missed_awards(df: &DataFrame) -> Result<Vec<MissedAward>> {
+    let curve_columns: Vec<(Series, Series)> = (1..=CURVE_POINTS)
+        .filter_map(|n| {
+            let mw = df.column(&format!("QSE submitted Curve-MW{}", n)).ok()?;
+            let price = df.column(&format!("QSE submitted Curve-Price{}", n)).ok()?;
+            Some((mw.cast(&DataType::Float64).ok()?, price.cast(&DataType::Float64).ok()?))
+        })
+        .collect();
+    if curve_columns.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let dates = require_column(df, "Delivery Date")?.utf8()?;
+    let hours = require_column(df, "Hour Ending")?.cast(&DataType::Int64)?;
+    let resources = require_column(df, "Resource Name")?.utf8()?;
+    let awards = require_column(df, "Awarded Quantity")?.cast(&DataType::Float64)?;
+    let prices = require_column(df, "Energy Settlement Point Price")?.cast(&DataType::Float64)?;
+    let (hours, awards, prices) = (hours.i64()?, awards.f64()?, prices.f64()?);
+
+    let mut missed = Vec::new();
+    for i in 0..df.height() {
+        let (Some(date), Some(hour_ending), Some(resource), Some(price)) =
+            (dates.get(i), hours.get(i), resources.get(i), prices.get(i))
+        else {
+            continue;
+        };
+        let Ok(date) = NaiveDate::parse_from_str(date, "%m/%d/%Y") else {
+            continue;
+        };
+
+        let mut points = Vec::new();
+        for (mw, offer_price) in &curve_columns {
+            if let (Some(mw), Some(offer_price)) = (mw.f64()?.get(i), offer_price.f64()?.get(i)) {
+                points.push(OfferPoint { mw, price: offer_price });
+            }
+        }
+        let curve = OfferCurve::new(points);
+        if curve.is_empty() {
+            continue;
+        }
+
+        let awarded_mw = awards.get(i).unwrap_or(0.0);
+        missed.extend(missed_award(resource, date, hour_ending, &curve, awarded_mw, price));
+    }
+
+    Ok(missed)
+}
+
+pub fn save_opportunity_costs(missed: &[MissedAward], output_path: &Path) -> Result<()> {
+    let mut df = DataFrame::new(vec![
+        Series::new("Resource_Name", missed.iter().map(|m| m.resource_name.clone()).collect::<Vec<_>>()),
+        Series::new("Delivery_Date", missed.iter().map(|m| m.delivery_date.to_string()).collect::<Vec<_>>()),
+        Series::new("Hour_Ending", missed.iter().map(|m| m.hour_ending).collect::<Vec<_>>()),
+        Series::new("Settlement_Price", missed.iter().map(|m| m.settlement_price).collect::<Vec<_>>()),
+        Series::new("Marginal_Offer_Price", missed.iter().map(|m| m.marginal_offer_price).collect::<Vec<_>>()),
+        Series::new("Offered_MW", missed.iter().map(|m| m.offered_mw).collect::<Vec<_>>()),
+        Series::new("Awarded_MW", missed.iter().map(|m| m.awarded_mw).collect::<Vec<_>>()),
+        Series::new("Uncleared_MW", missed.iter().map(MissedAward::uncleared_mw).collect::<Vec<_>>()),
+        Series::new("Missed_Margin", missed.iter().map(|m| m.missed_margin).collect::<Vec<_>>()),
+    ])?;
+
+    CsvWriter::new(std::fs::File::create(output_path)?)
+        .finish(&mut df)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn curve() -> OfferCurve {
+        OfferCurve::new(vec![
+            OfferPoint { mw: 50.0, price: 40.0 },
+            OfferPoint { mw: 10.0, price: 20.0 },
+            OfferPoint { mw: 100.0, price: 200.0 },
+        ])
+    }
+
+    #[test]
+    fn test_non_finite_points_are_dropped() {
+        let curve = OfferCurve::new(vec![
+            OfferPoint { mw: f64::NAN, price: 30.0 },
+            OfferPoint { mw: 50.0, price: 40.0 },
+            OfferPoint { mw: 80.0, price: f64::INFINITY },
+            OfferPoint { mw: 10.0, price: 20.0 },
+        ]);
+        assert_eq!(curve.marginal_price(), Some(20.0));
+        assert_eq!(curve.willing_mw_at(1000.0), 50.0);
+    }
+
+    #[test]
+    fn test_unawarded_hour_reports_missed_margin() {
+        let date = NaiveDate::from_ymd_opt(2024, 8, 1).unwrap();
+
+        let missed = missed_award("BESS_A", date, 19, &curve(), 0.0, 60.0).unwrap();
+        assert_eq!(missed.offered_mw, 50.0);
+        assert_eq!(missed.marginal_offer_price, 20.0);
+        // 10 MW at $40 over offer plus 40 MW at $20 over offer
+        assert!((missed.missed_margin - 1200.0).abs() < 1e-9);
+
+        // Partially cleared: only the 40 MW above the award is missed
+        let partial = missed_award("BESS_A", date, 19, &curve(), 10.0, 60.0).unwrap();
+        assert!((partial.missed_margin - 800.0).abs() < 1e-9);
+        assert_eq!(partial.uncleared_mw(), 40.0);
+    }
+
+    #[test]
+    fn test_no_missed_award_when_out_of_the_money_or_cleared() {
+        let date = NaiveDate::from_ymd_opt(2024, 8, 1).unwrap();
+
+        assert!(missed_award("BESS_A", date, 3, &curve(), 0.0, 15.0).is_none());
+        assert!(missed_award("BESS_A", date, 19, &curve(), 50.0, 60.0).is_none());
+    }
+
+    #[test]
+    fn test_missed_awards_from_gen_resource_frame() {
+        let df = df! {
+            "Delivery Date" => &["08/01/2024", "08/01/2024"],
+            "Hour Ending" => &[18_i64, 19],
+            "Resource Name" => &["BESS_A", "BESS_A"],
+            "Awarded Quantity" => &[50.0, 0.0],
+            "Energy Settlement Point Price" => &[80.0, 60.0],
+            "QSE submitted Curve-MW1" => &[10.0, 10.0],
+            "QSE submitted Curve-Price1" => &[20.0, 20.0],
+            "QSE submitted Curve-MW2" => &[Some(50.0), None],
+            "QSE submitted Curve-Price2" => &[Some(40.0), None],
+        }
+        .unwrap();
+
+        let missed = missed_awards(&df).unwrap();
+        assert_eq!(missed.len(), 1);
+        assert_eq!(missed[0].hour_ending, 19);
+        assert!((missed[0].missed_margin - 400.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,118 @@
+use anyhow::{bail, Result};
+use log::{debug, warn};
+use polars::prelude::*;
+use std::path::Path;
+
+use crate::dataset_paths::{ANCILLARY_PRICES, DAM_PRICES, RT_LMPS, RT_PRICES};
+
+/// Which files an annual output is written as (`--formats parquet,csv,arrow`).
+/// CSV is many times larger than Parquet and Arrow duplicates the Parquet,
+/// but the BESS revenue calculator's price loaders read the processed CSVs,
+/// so dropping CSV only suits runs whose prices are read some other way.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutputFormats {
+    pub csv: bool,
+    pub parquet: bool,
+    pub arrow: bool,
+}
+
+impl Default for OutputFormats {
+    /// CSV, Parquet and Arrow, as the processors have always written
+    fn default() -> Self {
+        Self { csv: true, parquet: true, arrow: true }
+    }
+}
+
+impl OutputFormats {
+    /// Parse a comma-separated list of `csv`, `parquet` and `arrow`
+    pub fn parse(list: &str) -> Result<Self> {
+        let mut formats = Self { csv: false, parquet: false, arrow: false };
+        for format in list.split(',').map(str::trim).filter(|f| !f.is_empty()) {
+            match format.to_ascii_lowercase().as_str() {
+                "csv" => formats.csv = true,
+                "parquet" => formats.parquet = true,
+                "arrow" | "ipc" => formats.arrow = true,
+                other => bail!("Invalid format in --formats: {} (expected csv, parquet or arrow)", other),
+            }
+        }
+        if !(formats.csv || formats.parquet || formats.arrow) {
+            bail!("--formats needs at least one of csv, parquet or arrow");
+        }
+        Ok(formats)
+    }
+
+    /// Parse `--formats <list>` (or `--formats=<list>`); no flag leaves each
+    /// processor's default
+    pub fn from_args(args: &[String]) -> Result<Option<Self>> {
+        let value = args.iter().enumerate().find_map(|(i, arg)| {
+            if arg == "--formats" {
+                args.get(i + 1).cloned()
+            } else {
+                arg.strip_prefix("--formats=").map(str::to_string)
+            }
+        });
+        value.map(|list| Self::parse(&list)).transpose()
+    }
+
+    /// Write `df` as `<base_name>.csv`, `.parquet` and/or `.arrow` in `dir`
+    pub fn write(&self, df: &DataFrame, dir: &Path, base_name: &str) -> Result<()> {
+        if self.csv {
+            let csv_path = dir.join(format!("{}.csv", base_name));
+            debug!("Saving CSV: {}", csv_path.display());
+            CsvWriter::new(std::fs::File::create(&csv_path)?)
+                .finish(&mut df.clone())?;
+        }
+        if self.parquet {
+            let parquet_path = dir.join(format!("{}.parquet", base_name));
+            debug!("Saving Parquet: {}", parquet_path.display());
+            ParquetWriter::new(std::fs::File::create(&parquet_path)?)
+                .finish(&mut df.clone())?;
+        }
+        if self.arrow {
+            let arrow_path = dir.join(format!("{}.arrow", base_name));
+            debug!("Saving Arrow IPC: {}", arrow_path.display());
+            IpcWriter::new(std::fs::File::create(&arrow_path)?)
+                .finish(&mut df.clone())?;
+        }
+        Ok(())
+    }
+
+    /// Warn when CSV is left out, since the revenue calculator's price
+    /// datasets (`dataset_paths`) are read from the processed CSVs
+    pub fn warn_if_price_loaders_miss_csv(&self) {
+        if !self.csv {
+            warn!("--formats {} writes no CSV, but the BESS revenue calculator reads {} from processed CSV files; \
+                   add csv to --formats if these outputs feed --bess-full-disclosure",
+                  self.describe(), [RT_PRICES, RT_LMPS, DAM_PRICES, ANCILLARY_PRICES].join(", "));
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        let names: Vec<&str> = [(self.csv, "CSV"), (self.parquet, "Parquet"), (self.arrow, "Arrow")]
+            .iter()
+            .filter(|(on, _)| *on)
+            .map(|(_, name)| *name)
+            .collect();
+        names.join(", ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_formats_list_selects_only_named_formats() {
+        let args: Vec<String> = vec!["--unified".into(), "--formats".into(), "parquet".into()];
+        let formats = OutputFormats::from_args(&args).unwrap().unwrap();
+        assert_eq!(formats, OutputFormats { csv: false, parquet: true, arrow: false });
+        assert_eq!(formats.describe(), "Parquet");
+
+        let formats = OutputFormats::parse("Parquet, csv").unwrap();
+        assert!(formats.csv && formats.parquet && !formats.arrow);
+
+        assert!(OutputFormats::parse("xlsx").is_err());
+        assert!(OutputFormats::parse(",").is_err());
+        assert!(OutputFormats::from_args(&["--unified".into()]).unwrap().is_none());
+    }
+}
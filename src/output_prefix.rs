@@ -0,0 +1,121 @@
+use anyhow::{bail, Result};
+use std::path::{Path, PathBuf};
+
+use crate::run_metadata;
+
+/// Run name from `--output-prefix`: every output directory of the run is
+/// written as `<dir>_<name>` (e.g. `bess_analysis_scenarioA/`), letting runs
+/// with different settings sit side by side. The default has no name and
+/// leaves directories unchanged.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OutputPrefix(Option<String>);
+
+impl OutputPrefix {
+    /// A run name; it becomes part of a directory name, so it can't be a path
+    pub fn new(name: &str) -> Result<Self> {
+        if name.is_empty() || name.starts_with("--") {
+            bail!("--output-prefix needs a name, e.g. --output-prefix scenarioA");
+        }
+        if name.contains(['/', '\\']) || name == "." || name == ".." {
+            bail!("Invalid --output-prefix: {} (expected a name, not a path)", name);
+        }
+        Ok(Self(Some(name.to_string())))
+    }
+
+    /// Parse `--output-prefix <name>` (or `--output-prefix=<name>`); no flag means no prefix
+    pub fn from_args(args: &[String]) -> Result<Self> {
+        let value = args.iter().enumerate().find_map(|(i, arg)| {
+            if arg == "--output-prefix" {
+                Some(args.get(i + 1).cloned().unwrap_or_default())
+            } else {
+                arg.strip_prefix("--output-prefix=").map(str::to_string)
+            }
+        });
+
+        match value {
+            Some(name) => Self::new(&name),
+            None => Ok(Self::default()),
+        }
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.0.as_deref()
+    }
+
+    /// `dir` with the run name appended to its first component, so nested
+    /// outputs like `bess_analysis/charts` stay inside the run's tree
+    fn apply(&self, dir: &str) -> PathBuf {
+        match self.name() {
+            None => PathBuf::from(dir),
+            Some(prefix) => match dir.split_once('/') {
+                Some((top, rest)) => PathBuf::from(format!("{}_{}", top, prefix)).join(rest),
+                None => PathBuf::from(format!("{}_{}", dir, prefix)),
+            },
+        }
+    }
+
+    /// `dir` with the run name appended to its last component, for output
+    /// directories given as full paths (e.g. `PipelineConfig::output_dir`)
+    pub fn apply_to_path(&self, dir: &Path) -> PathBuf {
+        match (self.name(), dir.file_name()) {
+            (Some(prefix), Some(last)) => dir.with_file_name(format!("{}_{}", last.to_string_lossy(), prefix)),
+            _ => dir.to_path_buf(),
+        }
+    }
+
+    /// Where this run writes the output directory (or file under it) `dir`;
+    /// the directory gets the run's `run_metadata.json` when it finishes
+    pub fn output_dir(&self, dir: &str) -> PathBuf {
+        let path = self.apply(dir);
+        run_metadata::record_output_dir(&path);
+        path
+    }
+
+    /// Where this run reads `path`, a file or directory some other command
+    /// writes: the run's own copy if it has one, otherwise the shared one, so a
+    /// sweep can reuse one master list and one set of annual prices
+    pub fn input_path(&self, path: &str) -> PathBuf {
+        let prefixed = self.apply(path);
+        if prefixed.exists() {
+            prefixed
+        } else {
+            PathBuf::from(path)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn test_prefixed_runs_write_separate_trees() {
+        assert_eq!(OutputPrefix::default().apply("bess_analysis"), PathBuf::from("bess_analysis"));
+        let a = OutputPrefix::new("scenarioA").unwrap().apply("bess_analysis/charts");
+        let b = OutputPrefix::new("scenarioB").unwrap().apply("bess_analysis/charts");
+        assert_eq!(a, PathBuf::from("bess_analysis_scenarioA/charts"));
+        assert_eq!(b, PathBuf::from("bess_analysis_scenarioB/charts"));
+
+        let prefix = OutputPrefix::new("scenarioA").unwrap();
+        assert_eq!(prefix.apply_to_path(Path::new("/tmp/run/annual_data")), PathBuf::from("/tmp/run/annual_data_scenarioA"));
+    }
+
+    #[test]
+    fn test_both_flag_forms_parse() {
+        let spaced = OutputPrefix::from_args(&args(&["prog", "--bess", "--output-prefix", "scenarioA"])).unwrap();
+        let joined = OutputPrefix::from_args(&args(&["prog", "--bess", "--output-prefix=scenarioA"])).unwrap();
+        assert_eq!(spaced.name(), Some("scenarioA"));
+        assert_eq!(joined, spaced);
+        assert_eq!(OutputPrefix::from_args(&args(&["prog", "--bess"])).unwrap(), OutputPrefix::default());
+
+        assert!(OutputPrefix::from_args(&args(&["prog", "--output-prefix", "runs/a"])).is_err());
+        assert!(OutputPrefix::from_args(&args(&["prog", "--output-prefix=.."])).is_err());
+        assert!(OutputPrefix::from_args(&args(&["prog", "--output-prefix"])).is_err());
+        assert!(OutputPrefix::from_args(&args(&["prog", "--output-prefix="])).is_err());
+        assert!(OutputPrefix::from_args(&args(&["prog", "--output-prefix", "--bess"])).is_err());
+    }
+}
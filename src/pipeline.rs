@@ -0,0 +1,446 @@
+use anyhow::Result;
+use chrono::NaiveDate;
+use glob::glob;
+use indicatif::{ProgressBar, ProgressStyle};
+use log::debug;
+use polars::prelude::*;
+use rayon::prelude::*;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::delivery_fields::DeliveryFieldCheck;
+use crate::datetime_format;
+use crate::error::PipelineError;
+use crate::intervals::{delivery_interval_datetime_ms, is_repeated_hour};
+use crate::output_formats::OutputFormats;
+use crate::output_prefix::OutputPrefix;
+use crate::processing_filter::ProcessingFilter;
+
+/// Paths and options for a programmatic pipeline run
+#[derive(Debug, Clone)]
+pub struct PipelineConfig {
+    pub rt_csv_dir: PathBuf,
+    pub output_dir: PathBuf,
+    pub filter: ProcessingFilter,
+    pub show_progress: bool,
+    pub formats: OutputFormats,
+    pub output_prefix: OutputPrefix,
+}
+
+impl PipelineConfig {
+    pub fn new(rt_csv_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            rt_csv_dir: rt_csv_dir.into(),
+            output_dir: PathBuf::from("annual_data"),
+            filter: ProcessingFilter::default(),
+            show_progress: false,
+            formats: OutputFormats::default(),
+            output_prefix: OutputPrefix::default(),
+        }
+    }
+
+    pub fn with_output_dir(mut self, output_dir: impl Into<PathBuf>) -> Self {
+        self.output_dir = output_dir.into();
+        self
+    }
+
+    pub fn with_filter(mut self, filter: ProcessingFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Draw a progress bar while loading files (off by default for library use)
+    pub fn with_progress(mut self, show_progress: bool) -> Self {
+        self.show_progress = show_progress;
+        self
+    }
+
+    /// Which of CSV, Parquet and Arrow `write_rt_annual_files` writes (all by default)
+    pub fn with_formats(mut self, formats: OutputFormats) -> Self {
+        self.formats = formats;
+        self
+    }
+
+    /// Run name from `--output-prefix`, appended to the output directory so
+    /// runs with different settings don't overwrite each other
+    pub fn with_output_prefix(mut self, output_prefix: OutputPrefix) -> Self {
+        self.output_prefix = output_prefix;
+        self
+    }
+
+    /// Where the annual files go: `output_dir` suffixed with the run name, if any
+    pub fn prefixed_output_dir(&self) -> PathBuf {
+        self.output_prefix.apply_to_path(&self.output_dir)
+    }
+}
+
+/// Library entry point: processes data and hands back DataFrames instead of
+/// writing files, so callers decide what to persist
+pub struct Pipeline {
+    config: PipelineConfig,
+}
+
+impl Pipeline {
+    pub fn new(config: PipelineConfig) -> Self {
+        Self { config }
+    }
+
+    pub fn config(&self) -> &PipelineConfig {
+        &self.config
+    }
+
+    /// RT settlement point price CSVs grouped by year, honoring the year filter
+    pub fn rt_files_by_year(&self) -> Result<BTreeMap<u16, Vec<PathBuf>>> {
+        let pattern = self.config.rt_csv_dir.join("*.csv");
+        let mut files_by_year: BTreeMap<u16, Vec<PathBuf>> = BTreeMap::new();
+
+        for file in glob(pattern.to_str().unwrap())?.filter_map(Result::ok) {
+            let filename = file.file_name().and_then(|s| s.to_str()).unwrap_or("");
+            if let Some(year) = extract_year_from_filename(filename) {
+                if self.config.filter.includes_year(year as i32) {
+                    files_by_year.entry(year).or_default().push(file);
+                }
+            }
+        }
+
+        Ok(files_by_year)
+    }
+
+    /// All selected years of RT settlement point prices as one DataFrame
+    /// (datetime, SettlementPoint, SettlementPointPrice), sorted and deduplicated
+    pub fn process_rt_settlement_prices(&self) -> Result<DataFrame> {
+        let mut combined: Option<DataFrame> = None;
+
+        for (year, files) in self.rt_files_by_year()? {
+            let df = match self.process_rt_files(year, &files) {
+                Ok(df) => df,
+                Err(e) if matches!(e.downcast_ref(), Some(PipelineError::NoDataForYear { .. })) => continue,
+                Err(e) => return Err(e),
+            };
+
+            combined = Some(match combined {
+                Some(mut all) => {
+                    all.vstack_mut(&df)?;
+                    all
+                }
+                None => df,
+            });
+        }
+
+        combined.ok_or_else(|| {
+            anyhow::anyhow!(
+                "No RT settlement price data found in {}",
+                self.config.rt_csv_dir.display()
+            )
+        })
+    }
+
+    /// One year of RT settlement point prices
+    pub fn process_rt_settlement_prices_for_year(&self, year: u16) -> Result<DataFrame> {
+        let files = self.rt_files_by_year()?.remove(&year).unwrap_or_default();
+        self.process_rt_files(year, &files)
+    }
+
+    fn process_rt_files(&self, year: u16, files: &[PathBuf]) -> Result<DataFrame> {
+        let pb = if self.config.show_progress {
+            let pb = ProgressBar::new(files.len() as u64);
+            pb.set_style(ProgressStyle::default_bar()
+                .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} ({eta})")
+                .unwrap());
+            pb
+        } else {
+            ProgressBar::hidden()
+        };
+
+        // Process files in parallel batches
+        let batch_size = 100;
+        let mut all_dfs = Vec::new();
+
+        for chunk in files.chunks(batch_size) {
+            let chunk_dfs: Vec<DataFrame> = chunk
+                .par_iter()
+                .filter_map(|file| {
+                    pb.inc(1);
+                    read_rt_csv(file)
+                })
+                .collect();
+
+            all_dfs.extend(chunk_dfs);
+        }
+
+        pb.finish_with_message("Files loaded");
+
+        if all_dfs.is_empty() {
+            return Err(PipelineError::NoDataForYear { year: year as i32 }.into());
+        }
+
+        debug!("Combining {} dataframes...", all_dfs.len());
+
+        // Concatenate all dataframes
+        let mut combined = concat(
+            all_dfs.iter().map(|df| df.clone().lazy()).collect::<Vec<_>>().as_slice(),
+            UnionArgs::default(),
+        )?
+        .collect()?;
+
+        // Create datetime column
+        debug!("Creating datetime column...");
+        let delivery_dates = combined.column("DeliveryDate")?.utf8()?;
+        let delivery_hours = combined.column("DeliveryHour")?.cast(&DataType::Int32)?;
+        let delivery_hours = delivery_hours.i32()?;
+        let delivery_intervals = combined.column("DeliveryInterval")?.cast(&DataType::Int32)?;
+        let delivery_intervals = delivery_intervals.i32()?;
+        // The repeated fall-back hour is flagged so it doesn't collide with the first
+        let dst_flags = combined.column("DSTFlag").ok().and_then(|c| c.utf8().ok());
+
+        let mut field_check = DeliveryFieldCheck::default();
+        let mut datetimes = Vec::with_capacity(combined.height());
+        for i in 0..combined.height() {
+            let datetime = match (delivery_dates.get(i), delivery_hours.get(i), delivery_intervals.get(i)) {
+                (Some(date_str), Some(hour), Some(interval)) => field_check.check(hour, interval)
+                    .zip(NaiveDate::parse_from_str(date_str, "%m/%d/%Y").ok())
+                    .and_then(|((hour, interval), date)| delivery_interval_datetime_ms(
+                        date,
+                        hour,
+                        interval,
+                        is_repeated_hour(dst_flags.and_then(|f| f.get(i))),
+                    )),
+                _ => None,
+            };
+            datetimes.push(datetime); // milliseconds
+        }
+        field_check.log(&format!("RT_Settlement_Point_Prices {}", year));
+
+        let datetime_series = Series::new("datetime", datetimes);
+        combined.with_column(datetime_series)?;
+
+        // Select and rename columns
+        debug!("Selecting columns...");
+        let price_col = price_column(&combined)?;
+
+        let final_df = combined.lazy()
+            .select([
+                col("datetime"),
+                col("SettlementPoint"),
+                price_col.alias("SettlementPointPrice"),
+            ])
+            .collect()?;
+
+        // Remove duplicates first (keeping the last occurrence)
+        debug!("Removing duplicates...");
+        let unique_df = final_df.unique(Some(&["datetime".to_string(), "SettlementPoint".to_string()]), UniqueKeepStrategy::Last, None)?;
+
+        // Sort by datetime and settlement point
+        debug!("Sorting data...");
+        let sorted_df = unique_df.lazy()
+            .sort_by_exprs([col("datetime"), col("SettlementPoint")], [false, false], false, false)
+            .collect()?;
+
+        Ok(sorted_df)
+    }
+
+    /// Write one year of RT prices under the output directory, as CSV,
+    /// Parquet and/or Arrow IPC per the configured formats
+    pub fn write_rt_annual_files(&self, df: &DataFrame, year: u16) -> Result<()> {
+        let output_dir = self.config.prefixed_output_dir();
+        std::fs::create_dir_all(&output_dir)?;
+        let base_name = format!("RT_Settlement_Point_Prices_{}", year);
+        self.config.formats.write(df, &output_dir, &base_name)?;
+        datetime_format::mark_current(&output_dir, &base_name)
+    }
+}
+
+/// Columns of the annual RT files `write_rt_annual_files` writes; a year with
+/// no valid rows is written as an empty frame with this schema
+pub fn rt_annual_schema() -> Schema {
+    Schema::from_iter([
+        Field::new("datetime", DataType::Int64),
+        Field::new("SettlementPoint", DataType::Utf8),
+        Field::new("SettlementPointPrice", DataType::Float64),
+    ])
+}
+
+/// Read one RT CSV, forcing the price column to float. Files without the
+/// required columns are skipped.
+fn read_rt_csv(file: &Path) -> Option<DataFrame> {
+    let schema = Arc::new(Schema::from_iter([
+        Field::new("SettlementPointPrice", DataType::Float64),
+    ]));
+
+    let df = CsvReader::new(std::fs::File::open(file).ok()?)
+        .has_header(true)
+        .with_dtypes(Some(schema))
+        .finish()
+        .ok()?;
+
+    // Check if it has required columns
+    let cols = df.get_column_names();
+    if !cols.contains(&"DeliveryDate") {
+        return None;
+    }
+
+    // Handle different column names for settlement point
+    if cols.contains(&"SettlementPointName") && !cols.contains(&"SettlementPoint") {
+        df.lazy()
+            .with_column(col("SettlementPointName").alias("SettlementPoint"))
+            .collect()
+            .ok()
+    } else if !cols.contains(&"SettlementPoint") {
+        None
+    } else {
+        Some(df)
+    }
+}
+
+pub fn extract_year_from_filename(filename: &str) -> Option<u16> {
+    // Look for pattern like .20240823. (YYYYMMDD) or _20240823_
+    // Try first pattern
+    if let Some(start) = filename.find(".20") {
+        if let Some(year_str) = filename.get(start + 1..start + 5) {
+            if let Ok(year) = year_str.parse::<u16>() {
+                if (2000..=2100).contains(&year) {
+                    return Some(year);
+                }
+            }
+        }
+    }
+
+    // Try second pattern
+    if let Some(start) = filename.find("_20") {
+        if let Some(year_str) = filename.get(start + 1..start + 5) {
+            if let Ok(year) = year_str.parse::<u16>() {
+                if (2000..=2100).contains(&year) {
+                    return Some(year);
+                }
+            }
+        }
+    }
+
+    None
+}
+
+/// RT files carry the price as `SettlementPointPrice`; some older ones use `LMP`
+fn price_column(df: &DataFrame) -> std::result::Result<Expr, PipelineError> {
+    let cols = df.get_column_names();
+    if cols.contains(&"SettlementPointPrice") {
+        Ok(col("SettlementPointPrice"))
+    } else if cols.contains(&"LMP") {
+        Ok(col("LMP"))
+    } else {
+        Err(PipelineError::MissingColumn {
+            name: "SettlementPointPrice".to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_missing_price_column_is_matchable() {
+        let df = df! {
+            "DeliveryDate" => &["01/01/2024"],
+            "SettlementPoint" => &["HB_NORTH"],
+        }
+        .unwrap();
+
+        match price_column(&df) {
+            Err(PipelineError::MissingColumn { name }) => assert_eq!(name, "SettlementPointPrice"),
+            other => panic!("expected MissingColumn, got {:?}", other.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn test_lmp_price_column_fallback() {
+        let df = df! { "LMP" => &[25.0] }.unwrap();
+        assert!(price_column(&df).is_ok());
+    }
+
+    #[test]
+    fn test_process_rt_settlement_prices_returns_dataframe() {
+        let dir = tempfile::tempdir().unwrap();
+        let header = "DeliveryDate,DeliveryHour,DeliveryInterval,SettlementPointName,SettlementPointType,SettlementPointPrice,DSTFlag\n";
+        std::fs::write(
+            dir.path().join("cdr.00012301.0000000000000000.20240101.000500.SPPHLZNP6905_20240101_0000.csv"),
+            format!("{}01/01/2024,1,1,HB_NORTH,HU,20.5,N\n01/01/2024,1,1,HB_WEST,HU,18.0,N\n", header),
+        )
+        .unwrap();
+        // Same interval posted twice: deduplicated to one row per point
+        std::fs::write(
+            dir.path().join("cdr.00012301.0000000000000000.20240101.001500.SPPHLZNP6905_20240101_0015.csv"),
+            format!("{}01/01/2024,1,1,HB_NORTH,HU,20.5,N\n01/01/2024,1,2,HB_NORTH,HU,22.0,N\n", header),
+        )
+        .unwrap();
+
+        let pipeline = Pipeline::new(PipelineConfig::new(dir.path()));
+        let df = pipeline.process_rt_settlement_prices().unwrap();
+
+        assert_eq!(df.get_column_names(), &["datetime", "SettlementPoint", "SettlementPointPrice"]);
+        assert_eq!(df.height(), 3);
+    }
+
+    #[test]
+    fn test_fall_back_repeated_hour_survives_dedup() {
+        let dir = tempfile::tempdir().unwrap();
+        let header = "DeliveryDate,DeliveryHour,DeliveryInterval,SettlementPointName,SettlementPointType,SettlementPointPrice,DSTFlag\n";
+        // 2024-11-03: HE2 (01:00-02:00) is published twice, the second time as CST with DSTFlag=Y
+        std::fs::write(
+            dir.path().join("cdr.00012301.0000000000000000.20241103.011500.SPPHLZNP6905_20241103_0115.csv"),
+            format!("{}11/03/2024,2,1,HB_NORTH,HU,20.5,N\n11/03/2024,2,1,HB_NORTH,HU,31.0,Y\n11/03/2024,24,4,HB_NORTH,HU,25.0,N\n", header),
+        )
+        .unwrap();
+
+        let pipeline = Pipeline::new(PipelineConfig::new(dir.path()));
+        let df = pipeline.process_rt_settlement_prices().unwrap();
+
+        assert_eq!(df.height(), 3);
+        let datetimes: Vec<i64> = df.column("datetime").unwrap().i64().unwrap().into_no_null_iter().collect();
+        let prices: Vec<f64> = df.column("SettlementPointPrice").unwrap().f64().unwrap().into_no_null_iter().collect();
+        assert_eq!(datetimes[1] - datetimes[0], 3_600_000);
+        assert_eq!(prices[..2], [20.5, 31.0]);
+
+        let wall_clock = |ms: i64| crate::intervals::central_wall_clock(ms).unwrap().to_string();
+        assert_eq!(wall_clock(datetimes[0]), "2024-11-03 01:00:00");
+        assert_eq!(wall_clock(datetimes[1]), "2024-11-03 01:00:00");
+        assert_eq!(wall_clock(datetimes[2]), "2024-11-03 23:45:00");
+    }
+
+    #[test]
+    fn test_parquet_only_formats_skip_csv_and_arrow() {
+        let dir = tempfile::tempdir().unwrap();
+        let pipeline = Pipeline::new(
+            PipelineConfig::new(dir.path())
+                .with_output_dir(dir.path().join("annual_data"))
+                .with_formats(OutputFormats::parse("parquet").unwrap()),
+        );
+        pipeline.write_rt_annual_files(&DataFrame::from(&rt_annual_schema()), 2024).unwrap();
+
+        let mut written: Vec<String> = std::fs::read_dir(dir.path().join("annual_data"))
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        written.sort();
+        assert_eq!(written, vec!["RT_Settlement_Point_Prices_2024.datetime_format", "RT_Settlement_Point_Prices_2024.parquet"]);
+    }
+
+    #[test]
+    fn test_output_prefix_keeps_runs_apart() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["scenarioA", "scenarioB"] {
+            let pipeline = Pipeline::new(
+                PipelineConfig::new(dir.path())
+                    .with_output_dir(dir.path().join("annual_data"))
+                    .with_formats(OutputFormats::parse("parquet").unwrap())
+                    .with_output_prefix(OutputPrefix::new(name).unwrap()),
+            );
+            pipeline.write_rt_annual_files(&DataFrame::from(&rt_annual_schema()), 2024).unwrap();
+        }
+
+        assert!(!dir.path().join("annual_data").exists());
+        for name in ["annual_data_scenarioA", "annual_data_scenarioB"] {
+            assert!(dir.path().join(name).join("RT_Settlement_Point_Prices_2024.parquet").exists());
+        }
+    }
+}
@@ -0,0 +1,111 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, NaiveDate};
+use polars::prelude::*;
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::price_index::PriceIndex;
+
+/// One row per cached price: `settlement_point, date, interval, price`,
+/// sorted by point, date and interval. `interval` is the zero-based
+/// interval of the day at the index's cadence (hour of the day for DAM),
+/// on the same wall-clock-as-UTC convention prices are keyed with.
+pub fn price_index_frame(index: &PriceIndex) -> Result<DataFrame> {
+    let interval = index.interval();
+    let mut rows: Vec<(&str, i64, f64)> = index.iter()
+        .map(|(id, epoch_index, price)| (index.point_name(id), epoch_index, price))
+        .collect();
+    rows.sort_by(|a, b| a.0.cmp(b.0).then(a.1.cmp(&b.1)));
+
+    let mut points = Vec::with_capacity(rows.len());
+    let mut dates = Vec::with_capacity(rows.len());
+    let mut intervals = Vec::with_capacity(rows.len());
+    let mut prices = Vec::with_capacity(rows.len());
+    for (point, epoch_index, price) in rows {
+        let start = DateTime::from_timestamp(epoch_index * interval.minutes() as i64 * 60, 0)
+            .with_context(|| format!("Epoch index {} out of range", epoch_index))?
+            .naive_utc();
+        points.push(point);
+        dates.push(start.date().format("%Y-%m-%d").to_string());
+        intervals.push(interval.index_of(start));
+        prices.push(price);
+    }
+
+    Ok(DataFrame::new(vec![
+        Series::new("settlement_point", points),
+        Series::new("date", dates),
+        Series::new("interval", intervals),
+        Series::new("price", prices),
+    ])?)
+}
+
+/// AS prices as `settlement_point, date, interval, service, price`, with
+/// `interval` the zero-based hour of the day (hour ending minus one)
+pub fn ancillary_price_frame(prices: &HashMap<(String, NaiveDate, i32), HashMap<String, f64>>) -> Result<DataFrame> {
+    let mut rows: Vec<(&str, NaiveDate, i32, &str, f64)> = prices.iter()
+        .flat_map(|((point, date, hour_ending), services)| {
+            services.iter().map(move |(service, price)| (point.as_str(), *date, *hour_ending, service.as_str(), *price))
+        })
+        .collect();
+    rows.sort_by(|a, b| (a.0, a.1, a.2, a.3).cmp(&(b.0, b.1, b.2, b.3)));
+
+    Ok(DataFrame::new(vec![
+        Series::new("settlement_point", rows.iter().map(|r| r.0).collect::<Vec<_>>()),
+        Series::new("date", rows.iter().map(|r| r.1.format("%Y-%m-%d").to_string()).collect::<Vec<_>>()),
+        Series::new("interval", rows.iter().map(|r| r.2 - 1).collect::<Vec<_>>()),
+        Series::new("service", rows.iter().map(|r| r.3).collect::<Vec<_>>()),
+        Series::new("price", rows.iter().map(|r| r.4).collect::<Vec<_>>()),
+    ])?)
+}
+
+pub fn write_parquet(df: &mut DataFrame, path: &Path) -> Result<()> {
+    let file = std::fs::File::create(path)
+        .with_context(|| format!("Failed to create {}", path.display()))?;
+    ParquetWriter::new(file).finish(df)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intervals::IntervalLength;
+
+    #[test]
+    fn test_price_index_frame_round_trips_keys() {
+        let interval = IntervalLength::FifteenMinute;
+        let date = NaiveDate::from_ymd_opt(2024, 8, 1).unwrap();
+        let at = |hour: u32, minute: u32| interval.epoch_index(&date.and_hms_opt(hour, minute, 0).unwrap().and_utc());
+
+        let mut index = PriceIndex::new(interval);
+        index.insert("HB_NORTH", at(18, 45), 75.0);
+        index.insert("BATCAVE_RN", at(0, 0), 20.5);
+        index.insert("BATCAVE_RN", at(23, 45), 31.0);
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("rt_price_cache.parquet");
+        write_parquet(&mut price_index_frame(&index).unwrap(), &path).unwrap();
+        let df = ParquetReader::new(std::fs::File::open(&path).unwrap()).finish().unwrap();
+
+        let expected = df! {
+            "settlement_point" => &["BATCAVE_RN", "BATCAVE_RN", "HB_NORTH"],
+            "date" => &["2024-08-01", "2024-08-01", "2024-08-01"],
+            "interval" => &[0u32, 95, 75],
+            "price" => &[20.5, 31.0, 75.0],
+        }
+        .unwrap();
+        assert!(df.frame_equal(&expected), "{}", df);
+    }
+
+    #[test]
+    fn test_ancillary_price_frame_uses_zero_based_hours() {
+        let date = NaiveDate::from_ymd_opt(2023, 7, 1).unwrap();
+        let prices = HashMap::from([(
+            ("ERCOT".to_string(), date, 24),
+            HashMap::from([("RRS".to_string(), 12.5), ("RegUp".to_string(), 8.0)]),
+        )]);
+
+        let df = ancillary_price_frame(&prices).unwrap();
+        assert_eq!(df.column("interval").unwrap().i32().unwrap().to_vec(), vec![Some(23), Some(23)]);
+        assert_eq!(df.column("service").unwrap().utf8().unwrap().into_iter().collect::<Vec<_>>(), vec![Some("RRS"), Some("RegUp")]);
+    }
+}
@@ -0,0 +1,490 @@
+use anyhow::Result;
+use log::warn;
+use polars::prelude::{Float64Chunked, Int64Chunked, Utf8Chunked};
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::intervals::{IntervalLength, RT_PRICE_INTERVAL, SCED_DISPATCH_INTERVAL};
+
+/// Interned settlement point name
+pub type PointId = u32;
+
+/// Prices keyed by (interned settlement point, epoch interval) instead of
+/// (String, NaiveDate, interval), so each point name is stored once rather
+/// than once per price
+#[derive(Debug, Clone)]
+pub struct PriceIndex {
+    interval: IntervalLength,
+    ids: HashMap<String, PointId>,
+    names: Vec<String>,
+    prices: HashMap<(PointId, i64), f64>,
+}
+
+impl PriceIndex {
+    pub fn new(interval: IntervalLength) -> Self {
+        Self {
+            interval,
+            ids: HashMap::new(),
+            names: Vec::new(),
+            prices: HashMap::new(),
+        }
+    }
+
+    /// Cadence of the prices; epoch indices count intervals of this length
+    pub fn interval(&self) -> IntervalLength {
+        self.interval
+    }
+
+    pub fn intern(&mut self, settlement_point: &str) -> PointId {
+        if let Some(&id) = self.ids.get(settlement_point) {
+            return id;
+        }
+        let id = self.names.len() as PointId;
+        self.names.push(settlement_point.to_string());
+        self.ids.insert(settlement_point.to_string(), id);
+        id
+    }
+
+    pub fn point_id(&self, settlement_point: &str) -> Option<PointId> {
+        self.ids.get(settlement_point).copied()
+    }
+
+    pub fn insert(&mut self, settlement_point: &str, epoch_index: i64, price: f64) {
+        let id = self.intern(settlement_point);
+        self.prices.insert((id, epoch_index), price);
+    }
+
+    /// Insert aligned point, epoch index and price columns, walking their
+    /// Arrow chunks together. Consecutive rows for the same point reuse its
+    /// id, so a file sorted by point hashes each name once per run. Rows
+    /// with a null in any column are skipped; returns the rows inserted.
+    pub fn insert_columns(&mut self, points: &Utf8Chunked, epoch_indices: &Int64Chunked, prices: &Float64Chunked) -> usize {
+        self.prices.reserve(prices.len());
+        let mut last: Option<(&str, PointId)> = None;
+        let mut inserted = 0;
+        for ((point, epoch_index), price) in points.into_iter().zip(epoch_indices).zip(prices) {
+            let (Some(point), Some(epoch_index), Some(price)) = (point, epoch_index, price) else { continue };
+            let id = match last {
+                Some((name, id)) if name == point => id,
+                _ => {
+                    let id = self.intern(point);
+                    last = Some((point, id));
+                    id
+                }
+            };
+            self.prices.insert((id, epoch_index), price);
+            inserted += 1;
+        }
+        inserted
+    }
+
+    pub fn get(&self, settlement_point: &str, epoch_index: i64) -> Option<f64> {
+        self.get_by_id(self.point_id(settlement_point)?, epoch_index)
+    }
+
+    /// Lookup for callers that resolve a point once and scan many intervals
+    pub fn get_by_id(&self, id: PointId, epoch_index: i64) -> Option<f64> {
+        self.prices.get(&(id, epoch_index)).copied()
+    }
+
+    /// Merge another index of the same cadence; its prices win on conflict.
+    /// Returns how many of this index's prices were overwritten.
+    pub fn extend(&mut self, other: PriceIndex) -> usize {
+        debug_assert_eq!(self.interval, other.interval);
+        self.prices.reserve(other.prices.len());
+        let mut overwritten = 0;
+        for ((id, epoch_index), price) in other.prices {
+            let id = self.intern(&other.names[id as usize]);
+            if self.prices.insert((id, epoch_index), price).is_some() {
+                overwritten += 1;
+            }
+        }
+        overwritten
+    }
+
+    /// Add `other`'s prices for (point, interval)s this index lacks, keeping
+    /// its own where both have one; returns the prices added
+    pub fn fill_missing(&mut self, other: PriceIndex) -> usize {
+        debug_assert_eq!(self.interval, other.interval);
+        let mut added = 0;
+        for ((id, epoch_index), price) in other.prices {
+            let id = self.intern(&other.names[id as usize]);
+            if let Entry::Vacant(entry) = self.prices.entry((id, epoch_index)) {
+                entry.insert(price);
+                added += 1;
+            }
+        }
+        added
+    }
+
+    /// Average prices into a coarser `interval`, e.g. three 5-minute LMPs into
+    /// one 15-minute settlement interval. Coarse intervals missing any of
+    /// their sub-intervals are left out rather than averaged over fewer.
+    pub fn resample_mean(&self, interval: IntervalLength) -> PriceIndex {
+        let per_interval = (interval.minutes() / self.interval.minutes()) as i64;
+        debug_assert!(per_interval >= 1 && interval.minutes().is_multiple_of(self.interval.minutes()));
+
+        let mut sums: HashMap<(PointId, i64), (f64, i64)> = HashMap::new();
+        for (&(id, epoch_index), &price) in &self.prices {
+            let sum = sums.entry((id, epoch_index.div_euclid(per_interval))).or_insert((0.0, 0));
+            sum.0 += price;
+            sum.1 += 1;
+        }
+
+        let mut resampled = PriceIndex::new(interval);
+        for ((id, epoch_index), (sum, count)) in sums {
+            if count == per_interval {
+                resampled.insert(&self.names[id as usize], epoch_index, sum / count as f64);
+            }
+        }
+        resampled
+    }
+
+    /// Every (point id, epoch index, price), in no particular order
+    pub fn iter(&self) -> impl Iterator<Item = (PointId, i64, f64)> + '_ {
+        self.prices.iter().map(|(&(id, epoch_index), &price)| (id, epoch_index, price))
+    }
+
+    pub fn point_name(&self, id: PointId) -> &str {
+        &self.names[id as usize]
+    }
+
+    /// Replace each price with `f(point, price)`, removing it where `f`
+    /// returns `None`
+    pub fn filter_map_prices(&mut self, mut f: impl FnMut(PointId, f64) -> Option<f64>) {
+        self.prices.retain(|&(id, _), price| match f(id, *price) {
+            Some(new_price) => {
+                *price = new_price;
+                true
+            }
+            None => false,
+        });
+    }
+
+    /// Number of prices
+    pub fn len(&self) -> usize {
+        self.prices.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.prices.is_empty()
+    }
+
+    /// Number of distinct settlement points
+    pub fn point_count(&self) -> usize {
+        self.names.len()
+    }
+
+    /// Approximate heap footprint: the price table plus one copy of each
+    /// name in each direction of the intern table
+    pub fn approx_bytes(&self) -> usize {
+        let entry = std::mem::size_of::<((PointId, i64), f64)>();
+        let names: usize = self.names.iter().map(|n| 2 * (n.capacity() + std::mem::size_of::<String>())).sum();
+        self.prices.capacity() * entry + names
+    }
+}
+
+/// Real-time price products. RT energy settles on 15-minute Settlement Point
+/// Prices; 5-minute LMPs are priced per SCED run and are kept in their own
+/// index rather than merged into the settlement prices.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RtPriceProduct {
+    SettlementPointPrice,
+    Lmp,
+}
+
+impl RtPriceProduct {
+    pub fn interval(self) -> IntervalLength {
+        match self {
+            RtPriceProduct::SettlementPointPrice => RT_PRICE_INTERVAL,
+            RtPriceProduct::Lmp => SCED_DISPATCH_INTERVAL,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            RtPriceProduct::SettlementPointPrice => "RT settlement point prices",
+            RtPriceProduct::Lmp => "RT LMPs",
+        }
+    }
+
+    /// Product named by an ERCOT file (`RT_LMPs_*` or
+    /// `RT_Settlement_Point_Prices_*`); processed files rename LMP to
+    /// SettlementPointPrice, so the columns can't tell them apart
+    pub fn from_file_name(path: &Path) -> Option<Self> {
+        let name = path.file_name()?.to_str()?;
+        if name.contains("RT_LMPs") {
+            Some(RtPriceProduct::Lmp)
+        } else if name.contains("RT_Settlement_Point_Prices") {
+            Some(RtPriceProduct::SettlementPointPrice)
+        } else {
+            None
+        }
+    }
+}
+
+/// How to resolve several prices for the same (settlement point, interval)
+/// in one price file, e.g. when ERCOT publishes preliminary and final values
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DuplicatePricePolicy {
+    /// Keep the last row in file order (the final/latest publication)
+    #[default]
+    Latest,
+    /// Average every price posted for the interval
+    Average,
+}
+
+impl DuplicatePricePolicy {
+    /// Parse `--duplicate-prices latest|average` (or `--duplicate-prices=...`); defaults to latest
+    pub fn from_args(args: &[String]) -> Result<Self> {
+        let value = args.iter().enumerate().find_map(|(i, arg)| {
+            if arg == "--duplicate-prices" {
+                args.get(i + 1).cloned()
+            } else {
+                arg.strip_prefix("--duplicate-prices=").map(str::to_string)
+            }
+        });
+
+        match value.as_deref() {
+            None | Some("latest") => Ok(DuplicatePricePolicy::Latest),
+            Some("average") => Ok(DuplicatePricePolicy::Average),
+            Some(other) => anyhow::bail!("Invalid --duplicate-prices: {} (expected latest or average)", other),
+        }
+    }
+}
+
+/// Prices overwritten while merging several sources into one index, per
+/// source, so collisions between e.g. annual and unified outputs are
+/// reported rather than silently resolved in favour of the last file
+#[derive(Debug, Default)]
+pub struct MergeOverwrites {
+    by_source: Vec<(String, usize)>,
+}
+
+impl MergeOverwrites {
+    /// Merge `prices` from `source` into `index`, counting what it overwrote
+    pub fn extend(&mut self, index: &mut PriceIndex, prices: PriceIndex, source: &Path) {
+        let overwritten = index.extend(prices);
+        if overwritten > 0 {
+            self.by_source.push((source.display().to_string(), overwritten));
+        }
+    }
+
+    pub fn total(&self) -> usize {
+        self.by_source.iter().map(|(_, count)| count).sum()
+    }
+
+    /// Warn with the total and the sources that overwrote earlier ones;
+    /// `label` names the prices
+    pub fn warn(&self, label: &str) {
+        if self.by_source.is_empty() {
+            return;
+        }
+        let sources: Vec<String> = self.by_source.iter()
+            .map(|(source, count)| format!("{} ({})", source, count))
+            .collect();
+        warn!("{} {} prices were loaded from more than one source; the later source overwrote: {}",
+              self.total(), label, sources.join(", "));
+    }
+}
+
+/// Builds a `PriceIndex` from rows that may repeat a (point, interval),
+/// resolving repeats by `DuplicatePricePolicy` and counting them
+pub struct PriceIndexBuilder {
+    index: PriceIndex,
+    policy: DuplicatePricePolicy,
+    /// Prices seen so far per key, for averaging
+    counts: HashMap<(PointId, i64), u32>,
+    collisions: usize,
+}
+
+impl PriceIndexBuilder {
+    pub fn new(interval: IntervalLength, policy: DuplicatePricePolicy) -> Self {
+        Self {
+            index: PriceIndex::new(interval),
+            policy,
+            counts: HashMap::new(),
+            collisions: 0,
+        }
+    }
+
+    pub fn insert(&mut self, settlement_point: &str, epoch_index: i64, price: f64) {
+        let id = self.index.intern(settlement_point);
+        let count = self.counts.entry((id, epoch_index)).or_insert(0);
+        *count += 1;
+
+        let resolved = match (self.index.prices.get(&(id, epoch_index)), self.policy) {
+            (None, _) => price,
+            (Some(_), DuplicatePricePolicy::Latest) => price,
+            // Running mean over the `count` prices seen
+            (Some(mean), DuplicatePricePolicy::Average) => mean + (price - mean) / *count as f64,
+        };
+        if *count > 1 {
+            self.collisions += 1;
+        }
+        self.index.prices.insert((id, epoch_index), resolved);
+    }
+
+    /// Rows that repeated an already-seen (point, interval)
+    pub fn collisions(&self) -> usize {
+        self.collisions
+    }
+
+    /// Finish the index, logging how many duplicate rows `source` had
+    pub fn finish(self, source: &str) -> PriceIndex {
+        if self.collisions > 0 {
+            warn!("{}: {} duplicate (settlement point, interval) price rows resolved by {:?}",
+                  source, self.collisions, self.policy);
+        }
+        self.index
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Datelike, NaiveDate};
+
+    /// A month of 15-minute prices at a handful of nodes, keyed the old way too
+    fn fixture() -> (PriceIndex, HashMap<(String, NaiveDate, u32), f64>) {
+        let interval = IntervalLength::FifteenMinute;
+        let mut index = PriceIndex::new(interval);
+        let mut by_string = HashMap::new();
+        let points = ["HB_HOUSTON", "HB_NORTH", "BATCAVE_RN", "NOTREES_BESS_RN"];
+        for date in NaiveDate::from_ymd_opt(2024, 10, 20).unwrap().iter_days().take(30) {
+            for (p, point) in points.iter().enumerate() {
+                for i in 0..interval.intervals_in_day(date) {
+                    let price = 20.0 + p as f64 + i as f64 / 10.0 + date.day0() as f64;
+                    index.insert(point, interval.epoch_index_of_day(date, i), price);
+                    by_string.insert((point.to_string(), date, i), price);
+                }
+            }
+        }
+        (index, by_string)
+    }
+
+    #[test]
+    fn test_lookups_match_string_keyed_map() {
+        let (index, by_string) = fixture();
+        assert_eq!(index.len(), by_string.len());
+        assert_eq!(index.point_count(), 4);
+
+        for ((point, date, i), price) in &by_string {
+            let epoch_index = index.interval().epoch_index_of_day(*date, *i);
+            assert_eq!(index.get(point, epoch_index), Some(*price));
+        }
+        let epoch_index = index.interval().epoch_index_of_day(NaiveDate::from_ymd_opt(2024, 10, 20).unwrap(), 0);
+        assert_eq!(index.get("HB_WEST", epoch_index), None);
+    }
+
+    #[test]
+    fn test_index_is_smaller_than_string_keyed_map() {
+        let (index, by_string) = fixture();
+
+        let string_entry = std::mem::size_of::<((String, NaiveDate, u32), f64)>();
+        let string_bytes: usize = by_string.capacity() * string_entry
+            + by_string.keys().map(|(point, _, _)| point.capacity()).sum::<usize>();
+
+        assert!(index.approx_bytes() * 2 < string_bytes,
+                "index {} bytes vs {} bytes", index.approx_bytes(), string_bytes);
+    }
+
+    #[test]
+    fn test_extend_reinterns_points() {
+        let interval = IntervalLength::Hourly;
+        let mut a = PriceIndex::new(interval);
+        a.insert("HB_NORTH", 10, 30.0);
+        let mut b = PriceIndex::new(interval);
+        b.insert("HB_WEST", 10, 45.0);
+        b.insert("HB_NORTH", 11, 31.0);
+
+        assert_eq!(a.extend(b), 0);
+        assert_eq!(a.point_count(), 2);
+        assert_eq!(a.get("HB_NORTH", 10), Some(30.0));
+        assert_eq!(a.get("HB_NORTH", 11), Some(31.0));
+        assert_eq!(a.get("HB_WEST", 10), Some(45.0));
+    }
+
+    #[test]
+    fn test_overlapping_sources_count_overwrites() {
+        let interval = IntervalLength::Hourly;
+        let mut annual = PriceIndex::new(interval);
+        let mut unified = PriceIndex::new(interval);
+        for hour in 0..24 {
+            annual.insert("HB_NORTH", hour, 30.0);
+            // The unified output repeats the last six hours
+            unified.insert("HB_NORTH", hour + 18, 40.0);
+        }
+
+        let mut index = PriceIndex::new(interval);
+        let mut overwrites = MergeOverwrites::default();
+        overwrites.extend(&mut index, annual, Path::new("annual_output/RT_2023.parquet"));
+        overwrites.extend(&mut index, unified, Path::new("unified_processed_data/RT_2023.parquet"));
+
+        assert_eq!(overwrites.total(), 6);
+        assert_eq!(overwrites.by_source, vec![("unified_processed_data/RT_2023.parquet".to_string(), 6)]);
+        assert_eq!(index.len(), 42);
+        assert_eq!(index.get("HB_NORTH", 20), Some(40.0));
+    }
+
+    #[test]
+    fn test_duplicate_prices_resolved_by_policy() {
+        let rows = [("HB_NORTH", 10, 30.0), ("HB_NORTH", 10, 34.0), ("HB_WEST", 10, 50.0), ("HB_NORTH", 10, 35.0)];
+
+        let mut latest = PriceIndexBuilder::new(IntervalLength::FifteenMinute, DuplicatePricePolicy::Latest);
+        let mut average = PriceIndexBuilder::new(IntervalLength::FifteenMinute, DuplicatePricePolicy::Average);
+        for (point, epoch_index, price) in rows {
+            latest.insert(point, epoch_index, price);
+            average.insert(point, epoch_index, price);
+        }
+        assert_eq!(latest.collisions(), 2);
+        assert_eq!(average.collisions(), 2);
+
+        let latest = latest.finish("test");
+        assert_eq!(latest.len(), 2);
+        assert_eq!(latest.get("HB_NORTH", 10), Some(35.0));
+        assert_eq!(latest.get("HB_WEST", 10), Some(50.0));
+
+        let average = average.finish("test");
+        assert!((average.get("HB_NORTH", 10).unwrap() - 33.0).abs() < 1e-9);
+        assert_eq!(average.get("HB_WEST", 10), Some(50.0));
+    }
+
+    #[test]
+    fn test_duplicate_price_policy_from_args() {
+        let args = |a: &[&str]| a.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert_eq!(DuplicatePricePolicy::from_args(&args(&["x"])).unwrap(), DuplicatePricePolicy::Latest);
+        assert_eq!(DuplicatePricePolicy::from_args(&args(&["x", "--duplicate-prices", "average"])).unwrap(),
+                   DuplicatePricePolicy::Average);
+        assert!(DuplicatePricePolicy::from_args(&args(&["x", "--duplicate-prices=first"])).is_err());
+    }
+
+    #[test]
+    fn test_lmps_fill_only_intervals_without_spp() {
+        let date = NaiveDate::from_ymd_opt(2024, 8, 1).unwrap();
+        let mut lmps = PriceIndex::new(RtPriceProduct::Lmp.interval());
+        // Two 15-minute intervals' worth of 5-minute LMPs, plus one orphan
+        for (i, price) in [30.0, 36.0, 42.0, 50.0, 60.0, 70.0, 99.0].iter().enumerate() {
+            lmps.insert("BATCAVE_RN", SCED_DISPATCH_INTERVAL.epoch_index_of_day(date, 216 + i as u32), *price);
+        }
+
+        let mut spp = PriceIndex::new(RtPriceProduct::SettlementPointPrice.interval());
+        let first = RT_PRICE_INTERVAL.epoch_index_of_day(date, 72);
+        spp.insert("BATCAVE_RN", first, 35.0);
+
+        let resampled = lmps.resample_mean(RT_PRICE_INTERVAL);
+        assert_eq!(resampled.len(), 2, "incomplete interval is not averaged");
+        assert_eq!(resampled.get("BATCAVE_RN", first), Some(36.0));
+
+        assert_eq!(spp.fill_missing(resampled), 1);
+        assert_eq!(spp.get("BATCAVE_RN", first), Some(35.0), "settlement price wins");
+        assert_eq!(spp.get("BATCAVE_RN", first + 1), Some(60.0));
+        assert_eq!(spp.get("BATCAVE_RN", first + 2), None);
+
+        assert_eq!(RtPriceProduct::from_file_name(Path::new("annual_data/RT_LMPs_2024.csv")), Some(RtPriceProduct::Lmp));
+        assert_eq!(RtPriceProduct::from_file_name(Path::new("RT_Settlement_Point_Prices_2024.csv")),
+                   Some(RtPriceProduct::SettlementPointPrice));
+        assert_eq!(RtPriceProduct::from_file_name(Path::new("/data/rt/2024.csv")), None);
+    }
+}
@@ -0,0 +1,205 @@
+use anyhow::Result;
+use log::{info, warn};
+use std::collections::HashMap;
+
+use crate::price_index::{PointId, PriceIndex};
+
+/// What makes a price an outlier
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OutlierRule {
+    /// |price| above this many $/MWh
+    Absolute(f64),
+    /// More than this many standard deviations from the settlement point's mean
+    ZScore(f64),
+}
+
+/// What to do with an outlier
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutlierAction {
+    #[default]
+    Drop,
+    /// Clamp to the rule's bound instead
+    Cap,
+}
+
+/// Prices the filter changed in one index
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OutlierReport {
+    pub filtered: usize,
+    /// Filtered prices per settlement point
+    pub by_point: HashMap<String, usize>,
+    /// Largest |price| filtered
+    pub max_abs_price: f64,
+}
+
+/// Optional per-settlement-point filter for bad price rows (parse artifacts,
+/// 999999 placeholders) applied before revenue is computed
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PriceOutlierFilter {
+    pub rule: OutlierRule,
+    pub action: OutlierAction,
+}
+
+impl PriceOutlierFilter {
+    /// Parse `--price-outliers abs:<$/MWh>|zscore:<sigmas>` and
+    /// `--price-outlier-action drop|cap` (or the `--flag=value` forms); no
+    /// `--price-outliers` leaves prices unfiltered
+    pub fn from_args(args: &[String]) -> Result<Option<Self>> {
+        let flag_value = |flag: &str| {
+            args.iter().enumerate().find_map(|(i, arg)| {
+                if arg == flag {
+                    args.get(i + 1).cloned()
+                } else {
+                    arg.strip_prefix(flag).and_then(|rest| rest.strip_prefix('=')).map(str::to_string)
+                }
+            })
+        };
+
+        let action = match flag_value("--price-outlier-action").as_deref() {
+            None | Some("drop") => OutlierAction::Drop,
+            Some("cap") => OutlierAction::Cap,
+            Some(other) => anyhow::bail!("Invalid --price-outlier-action: {} (expected drop or cap)", other),
+        };
+
+        let Some(spec) = flag_value("--price-outliers") else {
+            return Ok(None);
+        };
+        let invalid = || anyhow::anyhow!("Invalid --price-outliers: {} (expected abs:<$/MWh> or zscore:<sigmas>)", spec);
+        let (kind, value) = spec.split_once(':').ok_or_else(invalid)?;
+        let value: f64 = value.parse().ok().filter(|v: &f64| *v > 0.0).ok_or_else(invalid)?;
+        let rule = match kind {
+            "abs" => OutlierRule::Absolute(value),
+            "zscore" => OutlierRule::ZScore(value),
+            _ => return Err(invalid()),
+        };
+        Ok(Some(Self { rule, action }))
+    }
+
+    /// (low, high) bounds per settlement point
+    fn bounds(&self, index: &PriceIndex) -> HashMap<PointId, (f64, f64)> {
+        // Running count, mean and sum of squared deviations (Welford)
+        let mut stats: HashMap<PointId, (f64, f64, f64)> = HashMap::new();
+        for (id, _, price) in index.iter() {
+            let (count, mean, m2) = stats.entry(id).or_insert((0.0, 0.0, 0.0));
+            *count += 1.0;
+            let delta = price - *mean;
+            *mean += delta / *count;
+            *m2 += delta * (price - *mean);
+        }
+
+        stats.into_iter()
+            .map(|(id, (count, mean, m2))| {
+                let bounds = match self.rule {
+                    OutlierRule::Absolute(max_abs) => (-max_abs, max_abs),
+                    OutlierRule::ZScore(sigmas) => {
+                        let std_dev = (m2 / count).sqrt();
+                        (mean - sigmas * std_dev, mean + sigmas * std_dev)
+                    }
+                };
+                (id, bounds)
+            })
+            .collect()
+    }
+
+    /// Drop or cap the outliers in `index`
+    pub fn apply(&self, index: &mut PriceIndex) -> OutlierReport {
+        let bounds = self.bounds(index);
+        let mut by_id: HashMap<PointId, usize> = HashMap::new();
+        let mut report = OutlierReport::default();
+
+        index.filter_map_prices(|id, price| {
+            let (low, high) = bounds[&id];
+            if (low..=high).contains(&price) {
+                return Some(price);
+            }
+            *by_id.entry(id).or_insert(0) += 1;
+            report.filtered += 1;
+            report.max_abs_price = report.max_abs_price.max(price.abs());
+            match self.action {
+                OutlierAction::Drop => None,
+                OutlierAction::Cap => Some(price.clamp(low, high)),
+            }
+        });
+
+        report.by_point = by_id.into_iter()
+            .map(|(id, count)| (index.point_name(id).to_string(), count))
+            .collect();
+        report
+    }
+
+    /// Apply to `index` and log what was filtered; `label` names the prices
+    pub fn apply_and_log(&self, index: &mut PriceIndex, label: &str) -> OutlierReport {
+        let report = self.apply(index);
+        if report.filtered == 0 {
+            info!("{}: price outlier filter ({:?}) found no outliers", label, self.rule);
+            return report;
+        }
+
+        let mut worst: Vec<(&String, &usize)> = report.by_point.iter().collect();
+        worst.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+        let worst: Vec<String> = worst.iter().take(5).map(|(point, count)| format!("{} ({})", point, count)).collect();
+        warn!("{}: price outlier filter ({:?}) {} {} prices at {} settlement points, largest |${:.2}|/MWh; most at {}",
+              label, self.rule,
+              if self.action == OutlierAction::Drop { "dropped" } else { "capped" },
+              report.filtered, report.by_point.len(), report.max_abs_price, worst.join(", "));
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intervals::RT_PRICE_INTERVAL;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    /// Two days of ordinary 15-minute prices at two points, with one spike
+    fn prices_with_spike() -> PriceIndex {
+        let mut index = PriceIndex::new(RT_PRICE_INTERVAL);
+        for i in 0..192 {
+            index.insert("HB_NORTH", i, 25.0 + (i % 7) as f64);
+            index.insert("BATCAVE_RN", i, 30.0 + (i % 5) as f64);
+        }
+        index.insert("BATCAVE_RN", 100, 500_000.0);
+        index
+    }
+
+    #[test]
+    fn test_spike_removed_and_normal_prices_untouched() {
+        for spec in ["abs:10000", "zscore:6"] {
+            let filter = PriceOutlierFilter::from_args(&args(&["prog", "--price-outliers", spec])).unwrap().unwrap();
+            let mut index = prices_with_spike();
+            let report = filter.apply(&mut index);
+
+            assert_eq!(report.filtered, 1, "{}", spec);
+            assert_eq!(report.by_point, HashMap::from([("BATCAVE_RN".to_string(), 1)]));
+            assert_eq!(report.max_abs_price, 500_000.0);
+            assert_eq!(index.get("BATCAVE_RN", 100), None);
+            assert_eq!(index.len(), 2 * 192 - 1);
+            assert_eq!(index.get("BATCAVE_RN", 101), Some(31.0));
+            assert_eq!(index.get("HB_NORTH", 100), Some(27.0));
+        }
+    }
+
+    #[test]
+    fn test_cap_clamps_to_threshold() {
+        let filter = PriceOutlierFilter::from_args(&args(&["prog", "--price-outliers=abs:5000", "--price-outlier-action", "cap"]))
+            .unwrap()
+            .unwrap();
+        let mut index = prices_with_spike();
+        index.insert("HB_NORTH", 5, -999_999.0);
+        let report = filter.apply(&mut index);
+
+        assert_eq!(report.filtered, 2);
+        assert_eq!(index.get("BATCAVE_RN", 100), Some(5000.0));
+        assert_eq!(index.get("HB_NORTH", 5), Some(-5000.0));
+        assert_eq!(index.len(), 2 * 192);
+
+        assert_eq!(PriceOutlierFilter::from_args(&args(&["prog"])).unwrap(), None);
+        assert!(PriceOutlierFilter::from_args(&args(&["prog", "--price-outliers", "iqr:3"])).is_err());
+        assert!(PriceOutlierFilter::from_args(&args(&["prog", "--price-outliers", "abs:-1"])).is_err());
+        assert!(PriceOutlierFilter::from_args(&args(&["prog", "--price-outliers", "abs:1", "--price-outlier-action", "zero"])).is_err());
+    }
+}
@@ -0,0 +1,154 @@
+use anyhow::{Context, Result};
+use chrono::NaiveDate;
+use polars::prelude::*;
+use std::path::Path;
+
+use crate::error::require_column;
+use crate::intervals::{is_repeated_hour, IntervalLength, RT_PRICE_INTERVAL};
+use crate::price_index::PriceIndex;
+
+fn parse_delivery_date(value: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(value, "%m/%d/%Y")
+        .or_else(|_| NaiveDate::parse_from_str(value, "%Y-%m-%d"))
+        .ok()
+}
+
+/// Epoch interval index of every row from `DeliveryDate` (MM/DD/YYYY or
+/// YYYY-MM-DD), `DeliveryHour`, `DeliveryInterval` and, when present,
+/// `DSTFlag`; null where a row can't be placed. Walks the columns' Arrow
+/// chunks together and parses each run of equal dates once.
+pub fn rt_epoch_indices(df: &DataFrame, interval: IntervalLength) -> Result<Int64Chunked> {
+    let dates = require_column(df, "DeliveryDate")?.utf8()?;
+    let hours = require_column(df, "DeliveryHour")?.cast(&DataType::Int64)?;
+    let intervals = require_column(df, "DeliveryInterval")?.cast(&DataType::Int64)?;
+    let dst_flags: Box<dyn Iterator<Item = Option<&str>>> = match df.column("DSTFlag").ok().and_then(|c| c.utf8().ok()) {
+        Some(flags) => Box::new(flags.into_iter()),
+        None => Box::new(std::iter::repeat(None)),
+    };
+
+    let mut last_date: Option<(&str, Option<NaiveDate>)> = None;
+    let rows = dates.into_iter()
+        .zip(hours.i64()?.into_iter())
+        .zip(intervals.i64()?.into_iter())
+        .zip(dst_flags);
+
+    let mut epochs = Vec::with_capacity(df.height());
+    for (((date_str, hour), delivery_interval), dst_flag) in rows {
+        let (Some(date_str), Some(hour), Some(delivery_interval)) = (date_str, hour, delivery_interval) else {
+            epochs.push(None);
+            continue;
+        };
+        let date = match last_date {
+            Some((last, date)) if last == date_str => date,
+            _ => {
+                let date = parse_delivery_date(date_str);
+                last_date = Some((date_str, date));
+                date
+            }
+        };
+
+        epochs.push(date.filter(|_| hour >= 1 && delivery_interval >= 1).and_then(|date| {
+            interval
+                .index_from_hour_ending(date, hour as u32, delivery_interval as u32, is_repeated_hour(dst_flag))
+                .map(|index| interval.epoch_index_of_day(date, index))
+        }));
+    }
+
+    Ok(Int64Chunked::from_iter(epochs))
+}
+
+/// Read up to `row_limit` rows of an annual RT settlement point price Parquet
+/// (`DeliveryDate`, `DeliveryHour`, `DeliveryInterval`, `SettlementPointName`,
+/// `SettlementPointPrice`, optional `DSTFlag`) into a `PriceIndex` without a
+/// per-row String or row-by-row column lookups
+pub fn read_rt_prices(path: &Path, row_limit: usize) -> Result<PriceIndex> {
+    let file = std::fs::File::open(path)
+        .with_context(|| format!("Failed to open RT prices {}", path.display()))?;
+    let df = ParquetReader::new(file).finish()?;
+    let df = df.slice(0, row_limit.min(df.height()));
+
+    let epochs = rt_epoch_indices(&df, RT_PRICE_INTERVAL)?;
+    let points = require_column(&df, "SettlementPointName")?.utf8()?;
+    let prices = require_column(&df, "SettlementPointPrice")?.cast(&DataType::Float64)?;
+
+    let mut index = PriceIndex::new(RT_PRICE_INTERVAL);
+    index.insert_columns(points, &epochs, prices.f64()?);
+    Ok(index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_columnar_read_matches_row_by_row() {
+        // Two days at two nodes, the second day in ISO format, plus the
+        // repeated 01:00 hour on the 2024 fall-back day
+        let mut dates = Vec::new();
+        let mut hours = Vec::new();
+        let mut intervals = Vec::new();
+        let mut dst = Vec::new();
+        let mut points = Vec::new();
+        let mut prices = Vec::new();
+        for (day, date) in ["11/03/2024", "2024-11-04"].iter().enumerate() {
+            for point in ["HB_NORTH", "BATCAVE_RN"] {
+                for hour in 1..=24i64 {
+                    for interval in 1..=4i64 {
+                        dates.push(date.to_string());
+                        hours.push(hour);
+                        intervals.push(interval);
+                        dst.push("N".to_string());
+                        points.push(point.to_string());
+                        prices.push(20.0 + day as f64 + hour as f64 + interval as f64 / 10.0);
+                    }
+                }
+            }
+        }
+        for interval in 1..=4i64 {
+            dates.push("11/03/2024".to_string());
+            hours.push(2);
+            intervals.push(interval);
+            dst.push("Y".to_string());
+            points.push("HB_NORTH".to_string());
+            prices.push(99.0 + interval as f64);
+        }
+        let mut df = df! {
+            "DeliveryDate" => &dates,
+            "DeliveryHour" => &hours,
+            "DeliveryInterval" => &intervals,
+            "DSTFlag" => &dst,
+            "SettlementPointName" => &points,
+            "SettlementPointPrice" => &prices,
+        }
+        .unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("rt_2024.parquet");
+        ParquetWriter::new(std::fs::File::create(&path).unwrap()).finish(&mut df).unwrap();
+
+        let columnar = read_rt_prices(&path, usize::MAX).unwrap();
+
+        let mut row_by_row = PriceIndex::new(RT_PRICE_INTERVAL);
+        for i in 0..dates.len() {
+            let date = parse_delivery_date(&dates[i]).unwrap();
+            let index = RT_PRICE_INTERVAL
+                .index_from_hour_ending(date, hours[i] as u32, intervals[i] as u32, dst[i] == "Y")
+                .unwrap();
+            row_by_row.insert(&points[i], RT_PRICE_INTERVAL.epoch_index_of_day(date, index), prices[i]);
+        }
+
+        assert_eq!(columnar.len(), row_by_row.len());
+        assert_eq!(columnar.point_count(), 2);
+        for i in 0..dates.len() {
+            let date = parse_delivery_date(&dates[i]).unwrap();
+            let index = RT_PRICE_INTERVAL
+                .index_from_hour_ending(date, hours[i] as u32, intervals[i] as u32, dst[i] == "Y")
+                .unwrap();
+            let epoch = RT_PRICE_INTERVAL.epoch_index_of_day(date, index);
+            assert_eq!(columnar.get(&points[i], epoch), row_by_row.get(&points[i], epoch));
+        }
+
+        // Row limit applies before indexing
+        assert_eq!(read_rt_prices(&path, 96).unwrap().len(), 96);
+    }
+}
@@ -0,0 +1,349 @@
+use anyhow::Result;
+use chrono::{DateTime, Duration, NaiveDate};
+use polars::prelude::*;
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use crate::intervals::{is_repeated_hour, RT_PRICE_INTERVAL};
+use crate::price_index::{DuplicatePricePolicy, PriceIndex, PriceIndexBuilder};
+
+/// Point-days kept by the lazy store; a day of 15-minute prices is ~100
+/// entries, so the default bounds the cache to a few MB
+pub const DEFAULT_LAZY_CACHE_DAYS: usize = 1024;
+
+/// Annual RT settlement point price Parquet written by `--annual-process`
+const RT_PRICE_DATASET: &str = "Settlement_Point_Prices_at_Resource_Nodes__Hubs_and_Load_Zones";
+
+/// How the revenue calculator gets RT prices
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PriceLoading {
+    /// Load every price file into memory up front
+    #[default]
+    Eager,
+    /// Read one settlement point-day at a time from the annual Parquet files
+    Lazy,
+}
+
+impl PriceLoading {
+    /// Parse `--price-loading eager|lazy` (or `--price-loading=...`); defaults to eager
+    pub fn from_args(args: &[String]) -> Result<Self> {
+        let value = args.iter().enumerate().find_map(|(i, arg)| {
+            if arg == "--price-loading" {
+                args.get(i + 1).cloned()
+            } else {
+                arg.strip_prefix("--price-loading=").map(str::to_string)
+            }
+        });
+
+        match value.as_deref() {
+            None | Some("eager") => Ok(PriceLoading::Eager),
+            Some("lazy") => Ok(PriceLoading::Lazy),
+            Some(other) => anyhow::bail!("Invalid --price-loading: {} (expected eager or lazy)", other),
+        }
+    }
+}
+
+/// RT prices by settlement point and `RT_PRICE_INTERVAL` epoch index
+/// (wall-clock time treated as UTC)
+pub enum PriceStore {
+    Eager(PriceIndex),
+    Lazy(LazyPriceStore),
+}
+
+impl PriceStore {
+    pub fn get(&self, settlement_point: &str, epoch_index: i64) -> Option<f64> {
+        match self {
+            PriceStore::Eager(index) => index.get(settlement_point, epoch_index),
+            PriceStore::Lazy(store) => store.get(settlement_point, epoch_index),
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            PriceStore::Eager(index) => format!("{} cached RT price points", index.len()),
+            PriceStore::Lazy(store) => format!("lazy RT prices from {} (cache of {} point-days)",
+                                               store.rt_dir.display(), store.capacity),
+        }
+    }
+}
+
+/// Scans the annual RT price Parquet for one (settlement point, day) on first
+/// use and keeps the most recently used point-days in a small LRU cache, so
+/// memory stays bounded regardless of how many days are processed
+pub struct LazyPriceStore {
+    rt_dir: PathBuf,
+    capacity: usize,
+    duplicate_prices: DuplicatePricePolicy,
+    cache: Mutex<DayCache>,
+}
+
+type DayKey = (String, NaiveDate);
+
+#[derive(Default)]
+struct DayCache {
+    /// Each point-day with the tick of its last use
+    days: HashMap<DayKey, (Arc<HashMap<i64, f64>>, u64)>,
+    /// Point-days by last use, least recent first
+    lru: BTreeMap<u64, DayKey>,
+    tick: u64,
+    scans: usize,
+}
+
+impl DayCache {
+    fn touch(&mut self, key: &DayKey) -> Option<Arc<HashMap<i64, f64>>> {
+        self.tick += 1;
+        let tick = self.tick;
+        let (day, last_used) = self.days.get_mut(key)?;
+        let key = self.lru.remove(last_used).expect("cached day has an LRU entry");
+        *last_used = tick;
+        self.lru.insert(tick, key);
+        Some(Arc::clone(day))
+    }
+
+    fn insert(&mut self, key: DayKey, day: Arc<HashMap<i64, f64>>, capacity: usize) {
+        self.tick += 1;
+        self.lru.insert(self.tick, key.clone());
+        self.days.insert(key, (day, self.tick));
+        while self.days.len() > capacity {
+            let Some((_, oldest)) = self.lru.pop_first() else { break };
+            self.days.remove(&oldest);
+        }
+    }
+}
+
+impl LazyPriceStore {
+    /// `annual_output_dir` holds one directory per dataset, as written by the annual processor
+    pub fn new(annual_output_dir: PathBuf, capacity: usize) -> Self {
+        Self {
+            rt_dir: annual_output_dir.join(RT_PRICE_DATASET),
+            capacity: capacity.max(1),
+            duplicate_prices: DuplicatePricePolicy::default(),
+            cache: Mutex::new(DayCache::default()),
+        }
+    }
+
+    /// Resolve prices posted twice for an interval, including the repeated
+    /// fall-back hour, as the eager loader does
+    pub fn with_duplicate_prices(mut self, policy: DuplicatePricePolicy) -> Self {
+        self.duplicate_prices = policy;
+        self
+    }
+
+    pub fn get(&self, settlement_point: &str, epoch_index: i64) -> Option<f64> {
+        let start = DateTime::from_timestamp(epoch_index * RT_PRICE_INTERVAL.minutes() as i64 * 60, 0)?;
+        let day = self.day(settlement_point, start.date_naive());
+        day.get(&epoch_index).copied()
+    }
+
+    /// Number of Parquet scans so far (cache misses)
+    pub fn scans(&self) -> usize {
+        self.cache.lock().unwrap().scans
+    }
+
+    pub fn cached_days(&self) -> usize {
+        self.cache.lock().unwrap().days.len()
+    }
+
+    fn day(&self, settlement_point: &str, date: NaiveDate) -> Arc<HashMap<i64, f64>> {
+        let key = (settlement_point.to_string(), date);
+        if let Some(day) = self.cache.lock().unwrap().touch(&key) {
+            return day;
+        }
+
+        // Scanned without the lock so other threads' cached lookups don't
+        // wait on it. Unreadable or missing files cache as an empty day so
+        // they aren't rescanned.
+        let day = Arc::new(self.scan_day(settlement_point, date).unwrap_or_else(|e| {
+            log::warn!("RT price scan failed for {} on {}: {}", settlement_point, date, e);
+            HashMap::new()
+        }));
+        let mut cache = self.cache.lock().unwrap();
+        cache.scans += 1;
+        // Another thread may have scanned the same day meanwhile
+        if let Some(day) = cache.touch(&key) {
+            return day;
+        }
+        cache.insert(key, Arc::clone(&day), self.capacity);
+        day
+    }
+
+    fn scan_day(&self, settlement_point: &str, date: NaiveDate) -> Result<HashMap<i64, f64>> {
+        use chrono::Datelike;
+
+        let path = self.rt_dir.join(format!("{}_{}.parquet", RT_PRICE_DATASET, date.year()));
+        if !path.exists() {
+            return Ok(HashMap::new());
+        }
+
+        let frame = LazyFrame::scan_parquet(&path, Default::default())?;
+        let has_dst_flag = frame.schema()?.get("DSTFlag").is_some();
+        let mut columns = vec![
+            col("DeliveryHour").cast(DataType::Int64),
+            col("DeliveryInterval").cast(DataType::Int64),
+            col("SettlementPointPrice").cast(DataType::Float64),
+        ];
+        if has_dst_flag {
+            columns.push(col("DSTFlag").cast(DataType::Utf8));
+        }
+        let df = frame
+            .filter(col("SettlementPointName").eq(lit(settlement_point)).and(
+                col("DeliveryDate").eq(lit(date.format("%m/%d/%Y").to_string()))
+                    .or(col("DeliveryDate").eq(lit(date.format("%Y-%m-%d").to_string()))),
+            ))
+            .select(columns)
+            .collect()?;
+
+        let hours = df.column("DeliveryHour")?.i64()?;
+        let intervals = df.column("DeliveryInterval")?.i64()?;
+        let prices = df.column("SettlementPointPrice")?.f64()?;
+        let dst_flags = if has_dst_flag { Some(df.column("DSTFlag")?.utf8()?) } else { None };
+
+        // The repeated fall-back hour (DSTFlag=Y) shares its wall-clock keys
+        // with the first, so it goes in after it and the two resolve by
+        // `duplicate_prices`, as in the eager loader's time-ordered files
+        let mut rows: Vec<(bool, i64, f64)> = Vec::with_capacity(df.height());
+        let midnight = date.and_hms_opt(0, 0, 0).unwrap();
+        for i in 0..df.height() {
+            if let (Some(hour), Some(interval), Some(price)) = (hours.get(i), intervals.get(i), prices.get(i)) {
+                let start = midnight
+                    + Duration::hours(hour - 1)
+                    + Duration::minutes((interval - 1) * RT_PRICE_INTERVAL.minutes() as i64);
+                let repeated_hour = is_repeated_hour(dst_flags.and_then(|flags| flags.get(i)));
+                rows.push((repeated_hour, RT_PRICE_INTERVAL.epoch_index(&start.and_utc()), price));
+            }
+        }
+        rows.sort_by_key(|(repeated_hour, _, _)| *repeated_hour);
+
+        let mut builder = PriceIndexBuilder::new(RT_PRICE_INTERVAL, self.duplicate_prices);
+        for (_, epoch_index, price) in rows {
+            builder.insert(settlement_point, epoch_index, price);
+        }
+        let index = builder.finish(&format!("{} {} on {}", path.display(), settlement_point, date));
+        Ok(index.iter().map(|(_, epoch_index, price)| (epoch_index, price)).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const POINTS: [&str; 2] = ["HB_HOUSTON", "BATCAVE_RN"];
+
+    fn price(point: usize, day: u32, hour: i64, interval: i64) -> f64 {
+        10.0 * point as f64 + day as f64 + hour as f64 + interval as f64 / 10.0
+    }
+
+    /// Three days of 15-minute prices at two points, as the annual processor writes them
+    fn write_annual_prices(dir: &std::path::Path) {
+        let (mut dates, mut hours, mut intervals, mut points, mut prices) = (vec![], vec![], vec![], vec![], vec![]);
+        for day in 1..=3u32 {
+            for (p, point) in POINTS.iter().enumerate() {
+                for hour in 1..=24i64 {
+                    for interval in 1..=4i64 {
+                        dates.push(format!("01/{:02}/2024", day));
+                        hours.push(hour);
+                        intervals.push(interval);
+                        points.push(point.to_string());
+                        prices.push(price(p, day, hour, interval));
+                    }
+                }
+            }
+        }
+        let mut df = df! {
+            "DeliveryDate" => dates,
+            "DeliveryHour" => hours,
+            "DeliveryInterval" => intervals,
+            "SettlementPointName" => points,
+            "SettlementPointPrice" => prices,
+        }
+        .unwrap();
+
+        let dataset_dir = dir.join(RT_PRICE_DATASET);
+        std::fs::create_dir_all(&dataset_dir).unwrap();
+        let file = std::fs::File::create(dataset_dir.join(format!("{}_2024.parquet", RT_PRICE_DATASET))).unwrap();
+        ParquetWriter::new(file).finish(&mut df).unwrap();
+    }
+
+    fn epoch_index(day: u32, hour: i64, interval: i64) -> i64 {
+        let start = NaiveDate::from_ymd_opt(2024, 1, day).unwrap().and_hms_opt(0, 0, 0).unwrap()
+            + Duration::hours(hour - 1)
+            + Duration::minutes((interval - 1) * 15);
+        RT_PRICE_INTERVAL.epoch_index(&start.and_utc())
+    }
+
+    #[test]
+    fn test_lazy_store_matches_eager_and_stays_within_cache() {
+        let dir = tempfile::tempdir().unwrap();
+        write_annual_prices(dir.path());
+
+        let mut index = PriceIndex::new(RT_PRICE_INTERVAL);
+        for day in 1..=3 {
+            for (p, point) in POINTS.iter().enumerate() {
+                for hour in 1..=24 {
+                    for interval in 1..=4 {
+                        index.insert(point, epoch_index(day, hour, interval), price(p, day, hour, interval));
+                    }
+                }
+            }
+        }
+        let eager = PriceStore::Eager(index);
+        let lazy = LazyPriceStore::new(dir.path().to_path_buf(), 2);
+
+        for day in 1..=3 {
+            for point in POINTS {
+                for hour in [1, 12, 24] {
+                    let key = epoch_index(day, hour, 3);
+                    assert_eq!(lazy.get(point, key), eager.get(point, key));
+                    assert!(lazy.get(point, key).is_some());
+                }
+                assert!(lazy.cached_days() <= 2);
+            }
+        }
+        // One scan per point-day; repeat lookups within a day hit the cache
+        assert_eq!(lazy.scans(), 6);
+
+        assert_eq!(lazy.get("HB_WEST", epoch_index(1, 1, 1)), None);
+        assert_eq!(lazy.get("HB_HOUSTON", epoch_index(1, 1, 1) - 1_000_000), None);
+    }
+
+    #[test]
+    fn test_repeated_fall_back_hour_resolves_like_eager_loading() {
+        let dir = tempfile::tempdir().unwrap();
+        // The repeated HE2 is listed first; DSTFlag, not file order, makes it the later posting
+        let mut df = df! {
+            "DeliveryDate" => ["11/03/2024", "11/03/2024", "11/03/2024"],
+            "DeliveryHour" => [2i64, 2, 3],
+            "DeliveryInterval" => [1i64, 1, 1],
+            "DSTFlag" => ["Y", "N", "N"],
+            "SettlementPointName" => ["HB_HOUSTON"; 3],
+            "SettlementPointPrice" => [40.0, 20.0, 25.0],
+        }
+        .unwrap();
+        let dataset_dir = dir.path().join(RT_PRICE_DATASET);
+        std::fs::create_dir_all(&dataset_dir).unwrap();
+        let file = std::fs::File::create(dataset_dir.join(format!("{}_2024.parquet", RT_PRICE_DATASET))).unwrap();
+        ParquetWriter::new(file).finish(&mut df).unwrap();
+
+        let start = |hour: u32| NaiveDate::from_ymd_opt(2024, 11, 3).unwrap().and_hms_opt(hour, 0, 0).unwrap().and_utc();
+        let he2 = RT_PRICE_INTERVAL.epoch_index(&start(1));
+        let he3 = RT_PRICE_INTERVAL.epoch_index(&start(2));
+
+        let latest = LazyPriceStore::new(dir.path().to_path_buf(), 4);
+        assert_eq!(latest.get("HB_HOUSTON", he2), Some(40.0));
+        assert_eq!(latest.get("HB_HOUSTON", he3), Some(25.0));
+
+        let average = LazyPriceStore::new(dir.path().to_path_buf(), 4)
+            .with_duplicate_prices(DuplicatePricePolicy::Average);
+        assert_eq!(average.get("HB_HOUSTON", he2), Some(30.0));
+    }
+
+    #[test]
+    fn test_price_loading_from_args() {
+        let args = |a: &[&str]| a.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert_eq!(PriceLoading::from_args(&args(&["x"])).unwrap(), PriceLoading::Eager);
+        assert_eq!(PriceLoading::from_args(&args(&["x", "--price-loading", "lazy"])).unwrap(), PriceLoading::Lazy);
+        assert_eq!(PriceLoading::from_args(&args(&["x", "--price-loading=eager"])).unwrap(), PriceLoading::Eager);
+        assert!(PriceLoading::from_args(&args(&["x", "--price-loading", "mmap"])).is_err());
+    }
+}
@@ -1,16 +1,18 @@
 use anyhow::Result;
+use log::{debug, info};
 use std::path::PathBuf;
+use crate::output_prefix::OutputPrefix;
 
-pub fn extract_and_process_historical() -> Result<()> {
-    println!("📦 Extracting and processing historical ERCOT data...");
+pub fn extract_and_process_historical(output_prefix: &OutputPrefix) -> Result<()> {
+    info!("Extracting and processing historical ERCOT data...");
     
     let base_dir = PathBuf::from("/Users/enrico/data/ERCOT_data");
-    let output_dir = PathBuf::from("ercot_historical_extracted");
+    let output_dir = output_prefix.output_dir("ercot_historical_extracted");
     std::fs::create_dir_all(&output_dir)?;
     
     // Process Historical DAM
     let dam_dir = base_dir.join("Historical_DAM_Load_Zone_and_Hub_Prices");
-    println!("\n🏛️  Extracting Historical DAM files...");
+    info!("Extracting Historical DAM files...");
     
     for entry in std::fs::read_dir(&dam_dir)? {
         let entry = entry?;
@@ -22,7 +24,7 @@ pub fn extract_and_process_historical() -> Result<()> {
             // Extract year from filename
             if let Some(year_pos) = filename.rfind("_") {
                 if let Ok(year) = filename[year_pos+1..].parse::<u16>() {
-                    println!("  Extracting DAM year {}...", year);
+                    info!("Extracting DAM year {}...", year);
                     
                     // Extract CSV from zip
                     let file = std::fs::File::open(&path)?;
@@ -34,7 +36,7 @@ pub fn extract_and_process_historical() -> Result<()> {
                         
                         let mut outfile = std::fs::File::create(&outpath)?;
                         std::io::copy(&mut file, &mut outfile)?;
-                        println!("    ✅ Extracted to {}", outpath.display());
+                        debug!("Extracted to {}", outpath.display());
                     }
                 }
             }
@@ -43,7 +45,7 @@ pub fn extract_and_process_historical() -> Result<()> {
     
     // Process Historical RTM
     let rtm_dir = base_dir.join("Historical_RTM_Load_Zone_and_Hub_Prices");
-    println!("\n🏛️  Extracting Historical RTM files...");
+    info!("Extracting Historical RTM files...");
     
     for entry in std::fs::read_dir(&rtm_dir)? {
         let entry = entry?;
@@ -55,7 +57,7 @@ pub fn extract_and_process_historical() -> Result<()> {
             // Extract year from filename
             if let Some(year_pos) = filename.rfind("_") {
                 if let Ok(year) = filename[year_pos+1..].parse::<u16>() {
-                    println!("  Extracting RTM year {}...", year);
+                    info!("Extracting RTM year {}...", year);
                     
                     // Extract from zip
                     let file = std::fs::File::open(&path)?;
@@ -67,14 +69,14 @@ pub fn extract_and_process_historical() -> Result<()> {
                         
                         let mut outfile = std::fs::File::create(&outpath)?;
                         std::io::copy(&mut file, &mut outfile)?;
-                        println!("    ✅ Extracted to {}", outpath.display());
+                        debug!("Extracted to {}", outpath.display());
                     }
                 }
             }
         }
     }
     
-    println!("\n✅ Extraction complete! Check the {} directory", output_dir.display());
+    info!("Extraction complete! Check the {} directory", output_dir.display());
     
     // Now let's process the real-time settlement point prices with all historical data
     println!("\n🚀 Summary of available ERCOT data:");
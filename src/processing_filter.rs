@@ -0,0 +1,139 @@
+use anyhow::Result;
+use log::info;
+use chrono::{Datelike, NaiveDate};
+use regex::Regex;
+use std::collections::HashSet;
+use std::sync::OnceLock;
+
+/// Restricts a processing run to selected years and datasets
+/// (`--only-years 2022,2023`, `--only-datasets RT_Settlement_Point_Prices,...`)
+#[derive(Debug, Clone, Default)]
+pub struct ProcessingFilter {
+    years: Option<HashSet<i32>>,
+    datasets: Option<HashSet<String>>,
+}
+
+impl ProcessingFilter {
+    /// Parse `--only-years` / `--only-datasets` from the command line.
+    /// Both `--flag a,b` and `--flag=a,b` forms are accepted.
+    pub fn from_args(args: &[String]) -> Result<Self> {
+        let mut filter = Self::default();
+
+        if let Some(years) = list_arg(args, "--only-years") {
+            let mut parsed = HashSet::new();
+            for year in years {
+                let year = year
+                    .parse::<i32>()
+                    .map_err(|_| anyhow::anyhow!("Invalid year in --only-years: {}", year))?;
+                parsed.insert(year);
+            }
+            filter.years = Some(parsed);
+        }
+
+        if let Some(datasets) = list_arg(args, "--only-datasets") {
+            filter.datasets = Some(datasets.into_iter().collect());
+        }
+
+        Ok(filter)
+    }
+
+    pub fn includes_year(&self, year: i32) -> bool {
+        self.years.as_ref().map_or(true, |years| years.contains(&year))
+    }
+
+    /// Whether an archive/CSV posted on `posted` may hold data for a selected year.
+    /// Day-ahead files posted on Dec 31 carry the next year's first operating day.
+    /// Files whose date can't be determined are kept.
+    pub fn includes_posting_date(&self, posted: Option<NaiveDate>) -> bool {
+        match posted {
+            Some(date) => {
+                self.includes_year(date.year())
+                    || (date.month() == 12 && date.day() == 31 && self.includes_year(date.year() + 1))
+            }
+            None => true,
+        }
+    }
+
+    pub fn includes_file(&self, filename: &str) -> bool {
+        self.includes_posting_date(date_from_ercot_filename(filename))
+    }
+
+    /// A dataset matches if any of its names (output prefix, source dir, ...) was requested
+    pub fn includes_dataset(&self, names: &[&str]) -> bool {
+        self.datasets
+            .as_ref()
+            .map_or(true, |datasets| names.iter().any(|name| datasets.contains(*name)))
+    }
+
+    pub fn print_summary(&self) {
+        if let Some(years) = &self.years {
+            let mut years: Vec<_> = years.iter().collect();
+            years.sort();
+            info!("Only years: {:?}", years);
+        }
+        if let Some(datasets) = &self.datasets {
+            let mut datasets: Vec<_> = datasets.iter().collect();
+            datasets.sort();
+            info!("Only datasets: {:?}", datasets);
+        }
+    }
+}
+
+fn list_arg(args: &[String], flag: &str) -> Option<Vec<String>> {
+    let prefix = format!("{}=", flag);
+    let value = args.iter().enumerate().find_map(|(i, arg)| {
+        if arg == flag {
+            args.get(i + 1).cloned()
+        } else {
+            arg.strip_prefix(&prefix).map(|v| v.to_string())
+        }
+    })?;
+
+    Some(
+        value
+            .split(',')
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+            .collect(),
+    )
+}
+
+/// Posting date from ERCOT archive/CSV names, which embed it as `.YYYYMMDD.`
+pub fn date_from_ercot_filename(filename: &str) -> Option<NaiveDate> {
+    static POSTING_DATE: OnceLock<Regex> = OnceLock::new();
+    let re = POSTING_DATE.get_or_init(|| Regex::new(r"\.(20\d{6})\.").unwrap());
+    let date = re.captures(filename)?.get(1)?.as_str();
+    NaiveDate::parse_from_str(date, "%Y%m%d").ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_posting_date_read_from_ercot_filenames() {
+        assert_eq!(
+            date_from_ercot_filename("cdr.00012301.0000000000000000.20240315.021502.SPPHLZNP6905_20240315_0215.csv"),
+            NaiveDate::from_ymd_opt(2024, 3, 15)
+        );
+        assert_eq!(
+            date_from_ercot_filename("cdr.00012331.0000000000000000.20231231.123004.DAMSPNP4190.zip"),
+            NaiveDate::from_ymd_opt(2023, 12, 31)
+        );
+        // No dotted date, an impossible date, and a date outside 20xx
+        assert_eq!(date_from_ercot_filename("RT_Settlement_Point_Prices_2024.csv"), None);
+        assert_eq!(date_from_ercot_filename("cdr.00012301.20241345.021502.csv"), None);
+        assert_eq!(date_from_ercot_filename("cdr.00012301.19991231.021502.csv"), None);
+    }
+
+    #[test]
+    fn test_unreadable_names_are_kept_and_dec_31_counts_for_next_year() {
+        let args: Vec<String> = vec!["--unified".into(), "--only-years".into(), "2024".into()];
+        let filter = ProcessingFilter::from_args(&args).unwrap();
+
+        assert!(filter.includes_file("cdr.00012331.0000000000000000.20240601.123004.DAMSPNP4190.zip"));
+        assert!(filter.includes_file("cdr.00012331.0000000000000000.20231231.123004.DAMSPNP4190.zip"));
+        assert!(!filter.includes_file("cdr.00012331.0000000000000000.20230601.123004.DAMSPNP4190.zip"));
+        assert!(filter.includes_file("manual_export.csv"));
+    }
+}
@@ -0,0 +1,142 @@
+use anyhow::Result;
+use log::info;
+use regex::Regex;
+use std::collections::{HashMap, HashSet};
+
+/// Restricts an analysis to resources whose name matches `--resource-regex`,
+/// e.g. `'^GAMBIT'` for one developer's fleet or `'_BESS\d*$'`, and/or to the
+/// first `--limit-resources N` of them by name for quick smoke runs
+#[derive(Debug, Clone, Default)]
+pub struct ResourceFilter {
+    pattern: Option<Regex>,
+    limit: Option<usize>,
+}
+
+impl ResourceFilter {
+    pub fn new(pattern: &str) -> Result<Self> {
+        let pattern = Regex::new(pattern)
+            .map_err(|e| anyhow::anyhow!("Invalid --resource-regex '{}': {}", pattern, e))?;
+        Ok(Self { pattern: Some(pattern), limit: None })
+    }
+
+    /// Keep at most `limit` resources, the first by name
+    pub fn with_limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Parse `--resource-regex <pattern>` and `--limit-resources <N>` (or the
+    /// `--flag=value` forms); no flags keep every resource
+    pub fn from_args(args: &[String]) -> Result<Self> {
+        let mut filter = match flag_value(args, "--resource-regex")? {
+            Some(pattern) => Self::new(pattern)?,
+            None => Self::default(),
+        };
+        if let Some(limit) = flag_value(args, "--limit-resources")? {
+            let limit = limit
+                .parse()
+                .map_err(|_| anyhow::anyhow!("Invalid --limit-resources: {} (expected a count)", limit))?;
+            filter = filter.with_limit(limit);
+        }
+        Ok(filter)
+    }
+
+    pub fn matches(&self, resource_name: &str) -> bool {
+        self.pattern.as_ref().map_or(true, |p| p.is_match(resource_name))
+    }
+
+    /// Drop resources the filter excludes from a name-keyed resource map
+    pub fn retain<V>(&self, resources: &mut HashMap<String, V>) {
+        if let Some(pattern) = &self.pattern {
+            let before = resources.len();
+            resources.retain(|name, _| pattern.is_match(name));
+            info!("--resource-regex /{}/ kept {} of {} BESS resources", pattern, resources.len(), before);
+        }
+        if let Some(limit) = self.limit {
+            if resources.len() > limit {
+                let mut names: Vec<String> = resources.keys().cloned().collect();
+                names.sort();
+                let kept: HashSet<String> = names.into_iter().take(limit).collect();
+                let before = resources.len();
+                resources.retain(|name, _| kept.contains(name));
+                info!("--limit-resources {} kept {} of {} BESS resources", limit, resources.len(), before);
+            }
+        }
+    }
+}
+
+/// Value of `--flag <value>` or `--flag=<value>`
+fn flag_value<'a>(args: &'a [String], flag: &str) -> Result<Option<&'a str>> {
+    for (i, arg) in args.iter().enumerate() {
+        if let Some(value) = arg.strip_prefix(flag).and_then(|rest| rest.strip_prefix('=')) {
+            return Ok(Some(value));
+        }
+        if arg == flag {
+            let value = args
+                .get(i + 1)
+                .ok_or_else(|| anyhow::anyhow!("{} requires a value", flag))?;
+            return Ok(Some(value));
+        }
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_regex_keeps_only_matching_resources() {
+        let mut resources: HashMap<String, f64> = ["GAMBIT_BESS1", "GAMBIT_BESS2", "NOT_GAMBIT_BESS", "BATCAVE_BES1"]
+            .iter()
+            .map(|name| (name.to_string(), 100.0))
+            .collect();
+
+        let filter = ResourceFilter::from_args(&args(&["prog", "--bess-revenue", "--resource-regex", "^GAMBIT"])).unwrap();
+        filter.retain(&mut resources);
+        let mut names: Vec<&str> = resources.keys().map(String::as_str).collect();
+        names.sort();
+        assert_eq!(names, vec!["GAMBIT_BESS1", "GAMBIT_BESS2"]);
+
+        let filter = ResourceFilter::from_args(&args(&["prog", "--resource-regex=_BES\\d$"])).unwrap();
+        assert!(filter.matches("BATCAVE_BES1"));
+        assert!(!filter.matches("GAMBIT_BESS1"));
+
+        assert!(ResourceFilter::from_args(&args(&["prog"])).unwrap().matches("ANYTHING"));
+        assert!(ResourceFilter::from_args(&args(&["prog", "--resource-regex", "(unclosed"])).is_err());
+        assert!(ResourceFilter::from_args(&args(&["prog", "--resource-regex"])).is_err());
+    }
+
+    #[test]
+    fn test_limit_keeps_first_resources_by_name() {
+        let mut resources: HashMap<String, f64> = ["DELTA_BESS", "ALPHA_BESS", "CHARLIE_BESS", "BRAVO_BESS", "GAMBIT_BESS1"]
+            .iter()
+            .map(|name| (name.to_string(), 100.0))
+            .collect();
+
+        let filter = ResourceFilter::from_args(&args(&["prog", "--limit-resources", "2"])).unwrap();
+        filter.retain(&mut resources);
+        let mut names: Vec<&str> = resources.keys().map(String::as_str).collect();
+        names.sort();
+        assert_eq!(names, vec!["ALPHA_BESS", "BRAVO_BESS"]);
+
+        // The limit applies after the regex
+        let mut resources: HashMap<String, f64> = ["GAMBIT_BESS2", "ALPHA_BESS", "GAMBIT_BESS1"]
+            .iter()
+            .map(|name| (name.to_string(), 100.0))
+            .collect();
+        let filter = ResourceFilter::from_args(&args(&["prog", "--resource-regex=^GAMBIT", "--limit-resources=1"])).unwrap();
+        filter.retain(&mut resources);
+        assert_eq!(resources.keys().collect::<Vec<_>>(), vec!["GAMBIT_BESS1"]);
+
+        let filter = ResourceFilter::new(".").unwrap().with_limit(0);
+        filter.retain(&mut resources);
+        assert!(resources.is_empty());
+
+        assert!(ResourceFilter::from_args(&args(&["prog", "--limit-resources", "five"])).is_err());
+    }
+}
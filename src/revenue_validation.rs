@@ -0,0 +1,225 @@
+use anyhow::{Context, Result};
+use chrono::NaiveDate;
+use polars::prelude::*;
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+use crate::error::require_column;
+
+/// Default tolerance before a resource-month is flagged, in percent
+pub const DEFAULT_TOLERANCE_PCT: f64 = 5.0;
+
+/// One resource-month total from an ERCOT settlement statement:
+///
+/// ```text
+/// Resource_Name,Month,Total_Revenue
+/// BATCAVE_BES1,2024-01,412345.67
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+pub struct StatementTotal {
+    #[serde(rename = "Resource_Name")]
+    pub resource_name: String,
+    #[serde(rename = "Month")]
+    pub month: String,
+    #[serde(rename = "Total_Revenue")]
+    pub total_revenue: f64,
+}
+
+#[derive(Debug, Clone)]
+pub struct RevenueDiff {
+    pub resource_name: String,
+    pub month: String,
+    pub computed: f64,
+    pub statement: f64,
+    pub difference: f64,
+    pub pct_difference: f64,
+    pub flagged: bool,
+}
+
+pub fn load_statement(path: &Path) -> Result<Vec<StatementTotal>> {
+    let mut reader = csv::Reader::from_path(path)
+        .with_context(|| format!("Failed to open settlement statement {}", path.display()))?;
+
+    let mut totals = Vec::new();
+    for record in reader.deserialize() {
+        totals.push(record.with_context(|| format!("Failed to parse settlement statement {}", path.display()))?);
+    }
+    Ok(totals)
+}
+
+/// Computed revenue per (resource, YYYY-MM) from the daily rollup
+/// (Resource_Name, Date as YYYY-MM-DD, Total_Revenue)
+pub fn monthly_totals(daily: &DataFrame) -> Result<HashMap<(String, String), f64>> {
+    let names = require_column(daily, "Resource_Name")?.cast(&DataType::Utf8)?;
+    let dates = require_column(daily, "Date")?.cast(&DataType::Utf8)?;
+    let revenues = require_column(daily, "Total_Revenue")?.cast(&DataType::Float64)?;
+
+    let mut totals = HashMap::new();
+    for ((name, date), revenue) in names.utf8()?.into_iter()
+        .zip(dates.utf8()?.into_iter())
+        .zip(revenues.f64()?.into_iter())
+    {
+        if let (Some(name), Some(date), Some(revenue)) = (name, date, revenue) {
+            let month = match NaiveDate::parse_from_str(date, "%Y-%m-%d") {
+                Ok(date) => date.format("%Y-%m").to_string(),
+                Err(_) => continue,
+            };
+            *totals.entry((name.to_string(), month)).or_insert(0.0) += revenue;
+        }
+    }
+
+    Ok(totals)
+}
+
+/// Compare computed totals against every statement line. A resource-month
+/// missing from the computed totals counts as zero.
+pub fn reconcile(
+    computed: &HashMap<(String, String), f64>,
+    statement: &[StatementTotal],
+    tolerance_pct: f64,
+) -> Vec<RevenueDiff> {
+    let mut diffs: Vec<RevenueDiff> = statement
+        .iter()
+        .map(|line| {
+            let computed = computed
+                .get(&(line.resource_name.clone(), line.month.clone()))
+                .copied()
+                .unwrap_or(0.0);
+            let difference = computed - line.total_revenue;
+            let pct_difference = if line.total_revenue != 0.0 {
+                difference / line.total_revenue.abs() * 100.0
+            } else if difference == 0.0 {
+                0.0
+            } else {
+                f64::INFINITY
+            };
+
+            RevenueDiff {
+                resource_name: line.resource_name.clone(),
+                month: line.month.clone(),
+                computed,
+                statement: line.total_revenue,
+                difference,
+                pct_difference,
+                flagged: pct_difference.abs() > tolerance_pct,
+            }
+        })
+        .collect();
+
+    diffs.sort_by(|a, b| a.resource_name.cmp(&b.resource_name).then(a.month.cmp(&b.month)));
+    diffs
+}
+
+fn save_diffs(diffs: &[RevenueDiff], output_path: &Path) -> Result<()> {
+    let mut df = DataFrame::new(vec![
+        Series::new("Resource_Name", diffs.iter().map(|d| d.resource_name.clone()).collect::<Vec<_>>()),
+        Series::new("Month", diffs.iter().map(|d| d.month.clone()).collect::<Vec<_>>()),
+        Series::new("Computed_Revenue", diffs.iter().map(|d| d.computed).collect::<Vec<_>>()),
+        Series::new("Statement_Revenue", diffs.iter().map(|d| d.statement).collect::<Vec<_>>()),
+        Series::new("Difference", diffs.iter().map(|d| d.difference).collect::<Vec<_>>()),
+        Series::new("Pct_Difference", diffs.iter().map(|d| d.pct_difference).collect::<Vec<_>>()),
+        Series::new("Flagged", diffs.iter().map(|d| d.flagged).collect::<Vec<_>>()),
+    ])?;
+
+    CsvWriter::new(std::fs::File::create(output_path)?)
+        .finish(&mut df)?;
+    Ok(())
+}
+
+pub fn validate_revenue(statement_path: &Path, daily_path: &Path, tolerance_pct: f64) -> Result<Vec<RevenueDiff>> {
+    println!("🧾 Revenue Reconciliation vs ERCOT Settlement Statement");
+    println!("{}", "=".repeat(80));
+
+    let statement = load_statement(statement_path)?;
+    let daily = ParquetReader::new(
+        std::fs::File::open(daily_path)
+            .with_context(|| format!("Failed to open daily revenues {}", daily_path.display()))?,
+    )
+    .finish()?;
+    let diffs = reconcile(&monthly_totals(&daily)?, &statement, tolerance_pct);
+
+    println!("{:<30} {:>8} {:>16} {:>16} {:>14} {:>9}",
+             "Resource Name", "Month", "Computed $", "Statement $", "Diff $", "Diff %");
+    println!("{}", "-".repeat(98));
+    for diff in &diffs {
+        println!("{:<30} {:>8} {:>16.2} {:>16.2} {:>14.2} {:>8.1}%{}",
+                 diff.resource_name, diff.month, diff.computed, diff.statement,
+                 diff.difference, diff.pct_difference,
+                 if diff.flagged { "  ⚠️" } else { "" });
+    }
+
+    // Resources with at least one month outside tolerance
+    let mut flagged: BTreeMap<&str, usize> = BTreeMap::new();
+    for diff in diffs.iter().filter(|d| d.flagged) {
+        *flagged.entry(diff.resource_name.as_str()).or_insert(0) += 1;
+    }
+
+    println!("\n📊 {} resource-months compared, tolerance ±{:.1}%", diffs.len(), tolerance_pct);
+    if flagged.is_empty() {
+        println!("✅ All resources within tolerance");
+    } else {
+        println!("⚠️  {} resources outside tolerance:", flagged.len());
+        for (resource, months) in &flagged {
+            println!("  {} ({} months)", resource, months);
+        }
+    }
+
+    let output_path = daily_path
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("bess_revenue_validation.csv");
+    save_diffs(&diffs, &output_path)?;
+    println!("✅ Saved reconciliation to: {}", output_path.display());
+
+    Ok(diffs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn statement(resource: &str, month: &str, total: f64) -> StatementTotal {
+        StatementTotal {
+            resource_name: resource.to_string(),
+            month: month.to_string(),
+            total_revenue: total,
+        }
+    }
+
+    #[test]
+    fn test_reconcile_flags_resources_outside_tolerance() {
+        let daily = df! {
+            "Resource_Name" => &["BESS_A", "BESS_A", "BESS_A", "BESS_B"],
+            "Date" => &["2024-01-01", "2024-01-31", "2024-02-01", "2024-01-15"],
+            "Total_Revenue" => &[600.0, 400.0, 500.0, 880.0],
+        }
+        .unwrap();
+        let computed = monthly_totals(&daily).unwrap();
+        assert_eq!(computed[&("BESS_A".to_string(), "2024-01".to_string())], 1000.0);
+
+        let diffs = reconcile(
+            &computed,
+            &[
+                statement("BESS_A", "2024-01", 1020.0),
+                statement("BESS_A", "2024-02", 500.0),
+                statement("BESS_B", "2024-01", 1000.0),
+                statement("BESS_C", "2024-01", 300.0),
+            ],
+            5.0,
+        );
+
+        let flagged: Vec<(&str, &str)> = diffs
+            .iter()
+            .filter(|d| d.flagged)
+            .map(|d| (d.resource_name.as_str(), d.month.as_str()))
+            .collect();
+        assert_eq!(flagged, vec![("BESS_B", "2024-01"), ("BESS_C", "2024-01")]);
+
+        let a_jan = &diffs[0];
+        assert!((a_jan.pct_difference + 1.960_784).abs() < 1e-4);
+        assert!((diffs[2].pct_difference + 12.0).abs() < 1e-9);
+        assert_eq!(diffs[3].computed, 0.0);
+    }
+}
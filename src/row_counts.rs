@@ -0,0 +1,77 @@
+use log::{info, warn};
+
+/// Share of concatenated rows dedup may remove before the result is suspect.
+/// Republished files duplicate some rows, but losing over half usually means
+/// the key columns don't identify a row.
+pub const MAX_PLAUSIBLE_DEDUP_FRACTION: f64 = 0.5;
+
+/// Row totals for one annual output as it moves through concat and dedup
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RowCounts {
+    /// Rows read from the input files
+    pub input: usize,
+    /// Rows after concatenating the inputs
+    pub concatenated: usize,
+    /// Rows left after removing duplicates
+    pub deduplicated: usize,
+}
+
+impl RowCounts {
+    /// Fraction of concatenated rows dedup removed
+    pub fn dedup_fraction(&self) -> f64 {
+        if self.concatenated == 0 {
+            0.0
+        } else {
+            (self.concatenated - self.deduplicated.min(self.concatenated)) as f64 / self.concatenated as f64
+        }
+    }
+
+    pub fn is_implausible(&self) -> bool {
+        self.dedup_fraction() > MAX_PLAUSIBLE_DEDUP_FRACTION
+    }
+
+    pub fn summary(&self) -> String {
+        let kept = if self.concatenated == 0 { 0.0 } else { self.deduplicated as f64 / self.concatenated as f64 };
+        format!(
+            "{} input rows, {} after concat, {} after dedup ({:.1}% kept, {:.1}% removed as duplicates)",
+            self.input, self.concatenated, self.deduplicated, kept * 100.0, self.dedup_fraction() * 100.0
+        )
+    }
+
+    /// Log the totals for `label` (e.g. `RT_Settlement_Point_Prices 2023`),
+    /// warning when concat changed the row count or dedup removed an
+    /// implausibly large share
+    pub fn log(&self, label: &str) {
+        info!("{}: {}", label, self.summary());
+        if self.concatenated != self.input {
+            warn!("{}: concat produced {} rows from {} input rows", label, self.concatenated, self.input);
+        }
+        if self.is_implausible() {
+            warn!(
+                "{}: dedup removed more than {:.0}% of rows ({}); check the dedup key columns",
+                label, MAX_PLAUSIBLE_DEDUP_FRACTION * 100.0, self.summary()
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dedup_removing_most_rows_is_implausible() {
+        // A key missing DeliveryInterval collapses four 15-minute rows into one
+        let counts = RowCounts { input: 4000, concatenated: 4000, deduplicated: 1000 };
+        assert!(counts.is_implausible());
+        assert_eq!(
+            counts.summary(),
+            "4000 input rows, 4000 after concat, 1000 after dedup (25.0% kept, 75.0% removed as duplicates)"
+        );
+
+        // A republished day among a month of files is fine
+        let counts = RowCounts { input: 3100, concatenated: 3100, deduplicated: 3000 };
+        assert!(!counts.is_implausible());
+        assert!(!RowCounts::default().is_implausible());
+    }
+}
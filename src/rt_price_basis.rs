@@ -0,0 +1,97 @@
+use anyhow::{bail, Result};
+use polars::prelude::*;
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::error::require_column;
+
+/// RT price a resource's energy is valued at. Batteries settle at their
+/// settlement point price; nodal LMP is for analyses that price at the node.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RtPriceBasis {
+    #[default]
+    Spp,
+    Lmp,
+}
+
+impl RtPriceBasis {
+    pub fn parse(value: &str) -> Result<Self> {
+        match value.trim().to_ascii_uppercase().as_str() {
+            "SPP" => Ok(RtPriceBasis::Spp),
+            "LMP" => Ok(RtPriceBasis::Lmp),
+            other => bail!("Invalid RT price basis: {} (expected SPP or LMP)", other),
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            RtPriceBasis::Spp => "SPP",
+            RtPriceBasis::Lmp => "LMP",
+        }
+    }
+}
+
+/// RT price basis per resource; resources not listed use SPP
+#[derive(Debug, Clone, Default)]
+pub struct RtPriceBasisConfig {
+    by_resource: HashMap<String, RtPriceBasis>,
+}
+
+impl RtPriceBasisConfig {
+    pub fn insert(&mut self, resource_name: &str, basis: RtPriceBasis) {
+        self.by_resource.insert(resource_name.to_string(), basis);
+    }
+
+    pub fn basis(&self, resource_name: &str) -> RtPriceBasis {
+        self.by_resource.get(resource_name).copied().unwrap_or_default()
+    }
+
+    /// Resources priced at LMP, sorted
+    pub fn lmp_resources(&self) -> Vec<&str> {
+        let mut resources: Vec<&str> = self.by_resource.iter()
+            .filter(|(_, basis)| **basis == RtPriceBasis::Lmp)
+            .map(|(resource, _)| resource.as_str())
+            .collect();
+        resources.sort();
+        resources
+    }
+
+    /// Load a `Resource_Name,RT_Price_Basis` CSV (`SPP` or `LMP`)
+    pub fn load(path: &Path) -> Result<Self> {
+        let df = CsvReader::new(std::fs::File::open(path)?)
+            .has_header(true)
+            .finish()?;
+
+        let resources = require_column(&df, "Resource_Name")?.utf8()?;
+        let bases = require_column(&df, "RT_Price_Basis")?.utf8()?;
+
+        let mut config = Self::default();
+        for (resource, basis) in resources.into_iter().zip(bases) {
+            if let (Some(resource), Some(basis)) = (resource, basis) {
+                config.insert(resource.trim(), RtPriceBasis::parse(basis)?);
+            }
+        }
+        Ok(config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_listed_resources_switch_to_lmp_and_others_stay_on_spp() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("rt_price_basis.csv");
+        std::fs::write(&path, "Resource_Name,RT_Price_Basis\nBATCAVE_BES1,lmp\nNOTREES_BESS1,SPP\n").unwrap();
+        let config = RtPriceBasisConfig::load(&path).unwrap();
+
+        assert_eq!(config.basis("BATCAVE_BES1"), RtPriceBasis::Lmp);
+        assert_eq!(config.basis("NOTREES_BESS1"), RtPriceBasis::Spp);
+        assert_eq!(config.basis("ALPHA_BESS"), RtPriceBasis::Spp);
+        assert_eq!(config.lmp_resources(), vec!["BATCAVE_BES1"]);
+
+        std::fs::write(&path, "Resource_Name,RT_Price_Basis\nBATCAVE_BES1,DAM\n").unwrap();
+        assert!(RtPriceBasisConfig::load(&path).is_err());
+    }
+}
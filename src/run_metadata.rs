@@ -0,0 +1,193 @@
+use anyhow::{Context, Result};
+use chrono::Local;
+use log::{info, warn};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::SystemTime;
+use walkdir::WalkDir;
+
+use crate::csv_extractor::hash_file_contents;
+
+pub const FILE_NAME: &str = "run_metadata.json";
+
+/// Environment variables that change what a run reads
+const RECORDED_ENV: [&str; 2] = ["ERCOT_DATA_BASE_DIR", "BESS_DATASETS_FILE"];
+
+/// Provenance written to each output directory of a run: what built the
+/// outputs, with which parameters, from how many inputs, and when
+#[derive(Debug, Clone, Serialize)]
+pub struct RunMetadata {
+    pub crate_version: String,
+    /// Commit the binary was built from, if built inside a git checkout
+    pub git_commit: Option<String>,
+    pub command: Vec<String>,
+    /// Command-line flags (`true` for switches), plus settings resolved at startup
+    pub config: BTreeMap<String, String>,
+    /// Input files found, by kind
+    pub input_files: BTreeMap<String, usize>,
+    pub started_at: String,
+    pub finished_at: Option<String>,
+    /// Size and xxHash3 of each file the run wrote in this directory
+    pub outputs: BTreeMap<String, OutputFile>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OutputFile {
+    pub bytes: u64,
+    pub xxh3: String,
+}
+
+struct Run {
+    metadata: RunMetadata,
+    started: SystemTime,
+    output_dirs: BTreeSet<PathBuf>,
+}
+
+static RUN: OnceLock<Mutex<Run>> = OnceLock::new();
+
+/// Flags as `flag -> value`; a flag followed by another flag (or nothing) is a switch
+pub fn config_from_args(args: &[String]) -> BTreeMap<String, String> {
+    let mut config = BTreeMap::new();
+    for (i, arg) in args.iter().enumerate().skip(1) {
+        if let Some(flag) = arg.strip_prefix("--") {
+            let value = args.get(i + 1)
+                .filter(|next| !next.starts_with("--"))
+                .cloned()
+                .unwrap_or_else(|| "true".to_string());
+            config.insert(flag.to_string(), value);
+        }
+    }
+    config
+}
+
+fn git_commit() -> Option<String> {
+    option_env!("GIT_COMMIT").filter(|c| !c.is_empty()).map(str::to_string)
+}
+
+/// Start recording the run; until this is called the `record_*` functions
+/// do nothing, so library callers write no metadata
+pub fn start(args: &[String]) {
+    let mut config = config_from_args(args);
+    for var in RECORDED_ENV {
+        if let Ok(value) = std::env::var(var) {
+            config.insert(format!("env:{}", var), value);
+        }
+    }
+    let metadata = RunMetadata {
+        crate_version: env!("CARGO_PKG_VERSION").to_string(),
+        git_commit: git_commit(),
+        command: args.to_vec(),
+        config,
+        input_files: BTreeMap::new(),
+        started_at: Local::now().to_rfc3339(),
+        finished_at: None,
+        outputs: BTreeMap::new(),
+    };
+    let _ = RUN.set(Mutex::new(Run { metadata, started: SystemTime::now(), output_dirs: BTreeSet::new() }));
+}
+
+fn with_run(f: impl FnOnce(&mut Run)) {
+    if let Some(run) = RUN.get() {
+        f(&mut run.lock().unwrap());
+    }
+}
+
+/// Record a setting resolved at startup rather than given as a flag
+pub fn record_config(key: &str, value: impl ToString) {
+    with_run(|run| {
+        run.metadata.config.insert(key.to_string(), value.to_string());
+    });
+}
+
+/// Add `count` input files of `kind` (e.g. `DAM Gen Resource Data`)
+pub fn record_inputs(kind: &str, count: usize) {
+    with_run(|run| {
+        *run.metadata.input_files.entry(kind.to_string()).or_default() += count;
+    });
+}
+
+/// Mark the top-level directory of `path` as one of the run's outputs
+pub fn record_output_dir(path: &Path) {
+    let top: PathBuf = match path.components().next() {
+        Some(Component::Normal(top)) if path.is_relative() => PathBuf::from(top),
+        _ => path.to_path_buf(),
+    };
+    with_run(|run| {
+        run.output_dirs.insert(top);
+    });
+}
+
+/// Files under `dir` written since `since`, with their sizes and checksums
+fn outputs_since(dir: &Path, since: SystemTime) -> Result<BTreeMap<String, OutputFile>> {
+    let mut outputs = BTreeMap::new();
+    for entry in WalkDir::new(dir).into_iter().filter_map(|e| e.ok()) {
+        let path = entry.path();
+        if !entry.file_type().is_file() || path.file_name().is_some_and(|n| n == FILE_NAME) {
+            continue;
+        }
+        let meta = entry.metadata()?;
+        if !meta.modified().is_ok_and(|modified| modified >= since) {
+            continue;
+        }
+        let name = path.strip_prefix(dir).unwrap_or(path).to_string_lossy().into_owned();
+        outputs.insert(name, OutputFile { bytes: meta.len(), xxh3: format!("{:016x}", hash_file_contents(path)?) });
+    }
+    Ok(outputs)
+}
+
+/// Stamp the finish time and write `run_metadata.json` into every output
+/// directory the run created
+pub fn finish() -> Result<()> {
+    let Some(run) = RUN.get() else {
+        return Ok(());
+    };
+    let run = run.lock().unwrap();
+    let finished_at = Local::now().to_rfc3339();
+    for dir in run.output_dirs.iter().filter(|dir| dir.is_dir()) {
+        let mut metadata = run.metadata.clone();
+        metadata.finished_at = Some(finished_at.clone());
+        metadata.outputs = match outputs_since(dir, run.started) {
+            Ok(outputs) => outputs,
+            Err(e) => {
+                warn!("Could not checksum outputs in {}: {}", dir.display(), e);
+                BTreeMap::new()
+            }
+        };
+        let path = dir.join(FILE_NAME);
+        let file = std::fs::File::create(&path)
+            .with_context(|| format!("Failed to create {}", path.display()))?;
+        serde_json::to_writer_pretty(file, &metadata)?;
+        info!("Wrote {}", path.display());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flags_become_config_and_new_files_are_checksummed() {
+        let args: Vec<String> = ["rt_rust_processor", "--bess-full-disclosure", "--revenue-basis", "gross", "--min-days", "28"]
+            .iter()
+            .map(|a| a.to_string())
+            .collect();
+        let config = config_from_args(&args);
+        assert_eq!(config.get("bess-full-disclosure").map(String::as_str), Some("true"));
+        assert_eq!(config.get("revenue-basis").map(String::as_str), Some("gross"));
+        assert_eq!(config.get("min-days").map(String::as_str), Some("28"));
+
+        let dir = tempfile::tempdir().unwrap();
+        let before = SystemTime::now() - std::time::Duration::from_secs(1);
+        std::fs::create_dir(dir.path().join("charts")).unwrap();
+        std::fs::write(dir.path().join("charts/revenue.csv"), "a,b\n1,2\n").unwrap();
+        std::fs::write(dir.path().join(FILE_NAME), "{}").unwrap();
+
+        let outputs = outputs_since(dir.path(), before).unwrap();
+        assert_eq!(outputs.keys().collect::<Vec<_>>(), vec!["charts/revenue.csv"]);
+        assert_eq!(outputs["charts/revenue.csv"].bytes, 8);
+        assert!(outputs_since(dir.path(), SystemTime::now() + std::time::Duration::from_secs(60)).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,121 @@
+use anyhow::Result;
+use chrono::NaiveDateTime;
+use polars::prelude::*;
+use std::collections::BTreeSet;
+use std::path::Path;
+
+use crate::bess_operations::DispatchPoint;
+use crate::intervals::{RT_PRICE_INTERVAL, SCED_DISPATCH_INTERVAL};
+
+/// Market-wide price the scarcity threshold is applied to
+pub const SCARCITY_REFERENCE_POINT: &str = "HB_HUBAVG";
+
+/// RT price ($/MWh) above which an interval counts as scarcity
+pub const DEFAULT_SCARCITY_PRICE: f64 = 1000.0;
+
+/// RT price interval a SCED timestamp falls in, with the wall clock treated
+/// as UTC as when SCED dispatch is settled
+pub fn rt_epoch_index(timestamp: NaiveDateTime) -> i64 {
+    RT_PRICE_INTERVAL.epoch_index(&timestamp.and_utc())
+}
+
+/// RT intervals from `first` to `last` (epoch indices, inclusive) whose
+/// reference price from `price_at` is above `threshold`
+pub fn scarcity_intervals(first: i64, last: i64, threshold: f64, price_at: impl Fn(i64) -> Option<f64>) -> BTreeSet<i64> {
+    (first..=last)
+        .filter(|&epoch_index| price_at(epoch_index).is_some_and(|price| price > threshold))
+        .collect()
+}
+
+/// How much of the scarcity a battery was around for it discharged into
+#[derive(Debug, Clone)]
+pub struct CaptureRate {
+    pub resource_name: String,
+    pub capacity_mw: f64,
+    /// Scarcity intervals between the resource's first and last SCED row
+    pub scarcity_intervals: usize,
+    /// Capacity × the length of those intervals
+    pub scarcity_mwh: f64,
+    pub discharged_mwh: f64,
+    pub capture_rate: f64,
+}
+
+/// Discharged MWh during scarcity over what the battery could have
+/// discharged at full capacity. Charging during scarcity counts as zero.
+pub fn capture_rate(resource_name: &str, capacity_mw: f64, points: &[DispatchPoint], scarcity: &BTreeSet<i64>) -> CaptureRate {
+    let span = points.iter().map(|point| rt_epoch_index(point.timestamp));
+    let in_span = match (span.clone().min(), span.max()) {
+        (Some(first), Some(last)) => scarcity.range(first..=last).count(),
+        _ => 0,
+    };
+    let discharged_mwh: f64 = points.iter()
+        .filter(|point| point.output_mw > 0.0 && scarcity.contains(&rt_epoch_index(point.timestamp)))
+        .map(|point| SCED_DISPATCH_INTERVAL.energy_mwh(point.output_mw))
+        .sum();
+    let scarcity_mwh = capacity_mw * RT_PRICE_INTERVAL.hours() * in_span as f64;
+
+    CaptureRate {
+        resource_name: resource_name.to_string(),
+        capacity_mw,
+        scarcity_intervals: in_span,
+        scarcity_mwh,
+        discharged_mwh,
+        capture_rate: if scarcity_mwh > 0.0 { discharged_mwh / scarcity_mwh } else { 0.0 },
+    }
+}
+
+pub fn save_capture_rates(rates: &[CaptureRate], output_path: &Path) -> Result<()> {
+    let mut df = DataFrame::new(vec![
+        Series::new("Resource_Name", rates.iter().map(|r| r.resource_name.clone()).collect::<Vec<_>>()),
+        Series::new("Capacity_MW", rates.iter().map(|r| r.capacity_mw).collect::<Vec<_>>()),
+        Series::new("Scarcity_Intervals", rates.iter().map(|r| r.scarcity_intervals as u32).collect::<Vec<_>>()),
+        Series::new("Scarcity_MWh", rates.iter().map(|r| r.scarcity_mwh).collect::<Vec<_>>()),
+        Series::new("Discharged_MWh", rates.iter().map(|r| r.discharged_mwh).collect::<Vec<_>>()),
+        Series::new("Capture_Rate", rates.iter().map(|r| r.capture_rate).collect::<Vec<_>>()),
+    ])?;
+
+    CsvWriter::new(std::fs::File::create(output_path)?)
+        .finish(&mut df)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, NaiveDate};
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_battery_discharging_through_scarcity_captures_it() {
+        let start = NaiveDate::from_ymd_opt(2024, 8, 20).unwrap().and_hms_opt(0, 0, 0).unwrap();
+        let every_5_min = |output_mw: &dyn Fn(NaiveDateTime) -> f64| -> Vec<DispatchPoint> {
+            (0..288)
+                .map(|i| start + Duration::minutes(5 * i))
+                .map(|timestamp| DispatchPoint { timestamp, output_mw: output_mw(timestamp) })
+                .collect()
+        };
+        // $5,000 from 19:00 to 20:00, $40 otherwise
+        let event = start + Duration::hours(19)..start + Duration::hours(20);
+        let prices: HashMap<i64, f64> = (0..96)
+            .map(|i| start + Duration::minutes(15 * i))
+            .map(|t| (rt_epoch_index(t), if event.contains(&t) { 5000.0 } else { 40.0 }))
+            .collect();
+
+        let scarcity = scarcity_intervals(rt_epoch_index(start), rt_epoch_index(start) + 95, DEFAULT_SCARCITY_PRICE,
+                                          |epoch_index| prices.get(&epoch_index).copied());
+        assert_eq!(scarcity.len(), 4);
+
+        // Full 100 MW through the event vs charging through it
+        let discharged = every_5_min(&|t| if event.contains(&t) { 100.0 } else { -20.0 });
+        let charged = every_5_min(&|t| if event.contains(&t) { -50.0 } else { 10.0 });
+
+        let high = capture_rate("BESS_A", 100.0, &discharged, &scarcity);
+        assert_eq!(high.scarcity_intervals, 4);
+        assert!((high.scarcity_mwh - 100.0).abs() < 1e-9);
+        assert!((high.capture_rate - 1.0).abs() < 1e-9);
+
+        let low = capture_rate("BESS_B", 100.0, &charged, &scarcity);
+        assert_eq!(low.discharged_mwh, 0.0);
+        assert_eq!(low.capture_rate, 0.0);
+    }
+}
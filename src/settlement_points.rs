@@ -0,0 +1,343 @@
+use anyhow::Result;
+use chrono::NaiveDate;
+use log::info;
+use polars::prelude::*;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::Mutex;
+
+use crate::error::{require_column, PipelineError};
+
+/// A resource's settlement point over an inclusive date range; an open end
+/// means the assignment has no start or is still in force
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SettlementPointAssignment {
+    pub resource_name: String,
+    pub settlement_point: String,
+    pub effective_from: Option<NaiveDate>,
+    pub effective_to: Option<NaiveDate>,
+}
+
+impl SettlementPointAssignment {
+    pub fn covers(&self, date: NaiveDate) -> bool {
+        self.effective_from.map_or(true, |from| from <= date) && self.effective_to.map_or(true, |to| date <= to)
+    }
+}
+
+/// Effective-dated resource → settlement point mapping. Resources that were
+/// re-registered carry one assignment per settlement point.
+#[derive(Debug, Clone, Default)]
+pub struct SettlementPointMap {
+    by_resource: HashMap<String, Vec<SettlementPointAssignment>>,
+}
+
+impl SettlementPointMap {
+    pub fn insert(&mut self, assignment: SettlementPointAssignment) {
+        let assignments = self.by_resource.entry(assignment.resource_name.clone()).or_default();
+        assignments.push(assignment);
+        assignments.sort_by_key(|a| a.effective_from);
+    }
+
+    /// Number of resources with at least one assignment
+    pub fn len(&self) -> usize {
+        self.by_resource.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.by_resource.is_empty()
+    }
+
+    /// Settlement point in force for `resource` on `date`. Where assignments
+    /// overlap, the one that took effect most recently wins.
+    pub fn settlement_point_on(&self, resource: &str, date: NaiveDate) -> Option<&str> {
+        self.by_resource
+            .get(resource)?
+            .iter()
+            .rev()
+            .find(|a| a.covers(date))
+            .map(|a| a.settlement_point.as_str())
+    }
+
+    /// Load `Resource_Name,Settlement_Point[,Effective_From,Effective_To]`
+    /// (dates as YYYY-MM-DD, blank for open-ended). Files without the date
+    /// columns map each resource to one point for all dates.
+    pub fn load(path: &Path) -> Result<Self> {
+        let df = CsvReader::new(std::fs::File::open(path)?)
+            .has_header(true)
+            .finish()?;
+
+        let resources = require_column(&df, "Resource_Name")?.utf8()?;
+        let settlement_points = require_column(&df, "Settlement_Point")?.utf8()?;
+        let date_column = |name: &str| -> Result<Option<Series>> {
+            Ok(match df.column(name) {
+                Ok(column) => Some(column.cast(&DataType::Utf8)?),
+                Err(_) => None,
+            })
+        };
+        let effective_from = date_column("Effective_From")?;
+        let effective_to = date_column("Effective_To")?;
+
+        let mut map = Self::default();
+        for i in 0..df.height() {
+            let (Some(resource), Some(settlement_point)) = (resources.get(i), settlement_points.get(i)) else {
+                continue;
+            };
+            map.insert(SettlementPointAssignment {
+                resource_name: resource.to_string(),
+                settlement_point: settlement_point.to_string(),
+                effective_from: parse_effective_date(effective_from.as_ref(), i)?,
+                effective_to: parse_effective_date(effective_to.as_ref(), i)?,
+            });
+        }
+
+        Ok(map)
+    }
+}
+
+/// Old → new settlement point names for points ERCOT has renamed (e.g. hub
+/// name changes), used when a mapped point has no price under its own name
+#[derive(Debug, Default)]
+pub struct SettlementPointAliases {
+    renamed: HashMap<String, String>,
+    /// Aliases already logged, so each rename is reported once per run
+    logged: Mutex<HashSet<String>>,
+}
+
+impl SettlementPointAliases {
+    pub fn insert(&mut self, old_name: &str, new_name: &str) {
+        self.renamed.insert(old_name.to_string(), new_name.to_string());
+    }
+
+    pub fn len(&self) -> usize {
+        self.renamed.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.renamed.is_empty()
+    }
+
+    /// Current name of `settlement_point`, following chains of renames;
+    /// `None` if it was never renamed
+    pub fn resolve(&self, settlement_point: &str) -> Option<&str> {
+        let mut current = self.renamed.get(settlement_point)?;
+        let mut seen = HashSet::from([settlement_point]);
+        while let Some(next) = self.renamed.get(current) {
+            if !seen.insert(current.as_str()) {
+                break;
+            }
+            current = next;
+        }
+        Some(current.as_str())
+    }
+
+    /// Price for `settlement_point` from `get`, falling back to its renamed
+    /// point when the old name has no price. The first match through each
+    /// alias is logged.
+    pub fn lookup(&self, settlement_point: &str, get: impl Fn(&str) -> Option<f64>) -> Option<f64> {
+        if let Some(price) = get(settlement_point) {
+            return Some(price);
+        }
+        let renamed = self.resolve(settlement_point)?;
+        let price = get(renamed)?;
+        if self.logged.lock().unwrap().insert(settlement_point.to_string()) {
+            info!("Settlement point {} priced as {} via alias table", settlement_point, renamed);
+        }
+        Some(price)
+    }
+
+    /// Load an `Old_Name,New_Name` CSV
+    pub fn load(path: &Path) -> Result<Self> {
+        let df = CsvReader::new(std::fs::File::open(path)?)
+            .has_header(true)
+            .finish()?;
+
+        let old_names = require_column(&df, "Old_Name")?.utf8()?;
+        let new_names = require_column(&df, "New_Name")?.utf8()?;
+
+        let mut aliases = Self::default();
+        for (old_name, new_name) in old_names.into_iter().zip(new_names.into_iter()) {
+            if let (Some(old_name), Some(new_name)) = (old_name, new_name) {
+                aliases.insert(old_name.trim(), new_name.trim());
+            }
+        }
+        Ok(aliases)
+    }
+}
+
+/// ERCOT's Settlement Points List (the `Settlement_Points_*.csv` in its
+/// settlement point and electrical bus mapping), giving the load zone and hub
+/// each resource node sits in
+#[derive(Debug, Clone, Default)]
+pub struct SettlementPointList {
+    load_zones: HashMap<String, String>,
+    hubs: HashMap<String, String>,
+}
+
+impl SettlementPointList {
+    pub fn insert(&mut self, resource_node: &str, load_zone: &str, hub: Option<&str>) {
+        self.load_zones.insert(resource_node.to_string(), load_zone.to_string());
+        if let Some(hub) = hub {
+            self.hubs.insert(resource_node.to_string(), hub.to_string());
+        }
+    }
+
+    /// Number of resource nodes with a load zone
+    pub fn len(&self) -> usize {
+        self.load_zones.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.load_zones.is_empty()
+    }
+
+    /// Settlement load zone (e.g. `LZ_HOUSTON`) containing `resource_node`
+    pub fn load_zone(&self, resource_node: &str) -> Option<&str> {
+        self.load_zones.get(resource_node).map(String::as_str)
+    }
+
+    /// Hub (e.g. `HB_HOUSTON`) containing `resource_node`, if the list has one
+    pub fn hub(&self, resource_node: &str) -> Option<&str> {
+        self.hubs.get(resource_node).map(String::as_str)
+    }
+
+    /// Load the list's `RESOURCE_NODE`, `SETTLEMENT_LOAD_ZONE` and optional
+    /// `HUB` columns. The list has a row per electrical bus, so buses that are
+    /// not resource nodes are skipped and a node's first row wins.
+    pub fn load(path: &Path) -> Result<Self> {
+        let df = CsvReader::new(std::fs::File::open(path)?)
+            .has_header(true)
+            .finish()?;
+
+        let nodes = require_column(&df, "RESOURCE_NODE")?.utf8()?;
+        let load_zones = require_column(&df, "SETTLEMENT_LOAD_ZONE")?.utf8()?;
+        let hubs = df.column("HUB").ok().and_then(|c| c.utf8().ok());
+
+        let mut list = Self::default();
+        for i in 0..df.height() {
+            let (Some(node), Some(load_zone)) = (non_empty(nodes.get(i)), non_empty(load_zones.get(i))) else {
+                continue;
+            };
+            if list.load_zones.contains_key(node) {
+                continue;
+            }
+            list.insert(node, load_zone, hubs.and_then(|h| non_empty(h.get(i))));
+        }
+        Ok(list)
+    }
+}
+
+fn non_empty(value: Option<&str>) -> Option<&str> {
+    value.map(str::trim).filter(|v| !v.is_empty())
+}
+
+fn parse_effective_date(column: Option<&Series>, row: usize) -> Result<Option<NaiveDate>> {
+    let Some(value) = column.and_then(|c| c.utf8().ok()?.get(row)) else {
+        return Ok(None);
+    };
+    let value = value.trim();
+    if value.is_empty() {
+        return Ok(None);
+    }
+    let date = NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map_err(|_| PipelineError::UnparseableDate { value: value.to_string() })?;
+    Ok(Some(date))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ymd(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    #[test]
+    fn test_july_reregistration_switches_settlement_point() {
+        let mut map = SettlementPointMap::default();
+        map.insert(SettlementPointAssignment {
+            resource_name: "BESS_A".to_string(),
+            settlement_point: "BESS_A_NEW".to_string(),
+            effective_from: Some(ymd(2024, 7, 1)),
+            effective_to: None,
+        });
+        map.insert(SettlementPointAssignment {
+            resource_name: "BESS_A".to_string(),
+            settlement_point: "BESS_A_OLD".to_string(),
+            effective_from: None,
+            effective_to: Some(ymd(2024, 6, 30)),
+        });
+
+        assert_eq!(map.settlement_point_on("BESS_A", ymd(2024, 3, 15)), Some("BESS_A_OLD"));
+        assert_eq!(map.settlement_point_on("BESS_A", ymd(2024, 6, 30)), Some("BESS_A_OLD"));
+        assert_eq!(map.settlement_point_on("BESS_A", ymd(2024, 7, 1)), Some("BESS_A_NEW"));
+        assert_eq!(map.settlement_point_on("BESS_A", ymd(2024, 11, 1)), Some("BESS_A_NEW"));
+        assert_eq!(map.settlement_point_on("BESS_B", ymd(2024, 7, 1)), None);
+    }
+
+    #[test]
+    fn test_load_with_and_without_effective_dates() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let dated = dir.path().join("dated.csv");
+        std::fs::write(&dated, "Resource_Name,Settlement_Point,Effective_From,Effective_To\n\
+            BESS_A,BESS_A_OLD,,2024-06-30\n\
+            BESS_A,BESS_A_NEW,2024-07-01,\n").unwrap();
+        let map = SettlementPointMap::load(&dated).unwrap();
+        assert_eq!(map.len(), 1);
+        assert_eq!(map.settlement_point_on("BESS_A", ymd(2024, 6, 1)), Some("BESS_A_OLD"));
+        assert_eq!(map.settlement_point_on("BESS_A", ymd(2024, 8, 1)), Some("BESS_A_NEW"));
+
+        let current = dir.path().join("current.csv");
+        std::fs::write(&current, "Resource_Name,Settlement_Point\nBESS_B,BESS_B_RN\n").unwrap();
+        let map = SettlementPointMap::load(&current).unwrap();
+        assert_eq!(map.settlement_point_on("BESS_B", ymd(2020, 1, 1)), Some("BESS_B_RN"));
+    }
+
+    #[test]
+    fn test_old_hub_name_prices_against_renamed_hub() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("aliases.csv");
+        std::fs::write(&path, "Old_Name,New_Name\nHB_OLDHUB,HB_RENAMED\nHB_OLDER,HB_OLDHUB\n").unwrap();
+        let aliases = SettlementPointAliases::load(&path).unwrap();
+        assert_eq!(aliases.len(), 2);
+
+        let prices = HashMap::from([("HB_RENAMED", 42.0), ("HB_NORTH", 30.0)]);
+        let get = |sp: &str| prices.get(sp).copied();
+
+        assert_eq!(aliases.resolve("HB_OLDHUB"), Some("HB_RENAMED"));
+        assert_eq!(aliases.resolve("HB_OLDER"), Some("HB_RENAMED"));
+        assert_eq!(aliases.lookup("HB_OLDHUB", get), Some(42.0));
+        assert_eq!(aliases.lookup("HB_OLDER", get), Some(42.0));
+        // Points with their own prices and unknown points are unaffected
+        assert_eq!(aliases.lookup("HB_NORTH", get), Some(30.0));
+        assert_eq!(aliases.lookup("HB_WEST", get), None);
+        assert_eq!(aliases.logged.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_settlement_point_list_maps_resource_nodes_to_load_zones() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("Settlement_Points_07152024.csv");
+        std::fs::write(&path, "ELECTRICAL_BUS,NODE_NAME,PSSE_BUS_NAME,VOLTAGE_LEVEL,SUBSTATION,SETTLEMENT_LOAD_ZONE,RESOURCE_NODE,HUB_BUS_NAME,HUB,PSSE_BUS_NUMBER\n\
+            BATCAVE_1,BC1,BATCAVE1,138,BATCAVE,LZ_SOUTH,BATCAVE_RN,,HB_SOUTH,1001\n\
+            BATCAVE_2,BC2,BATCAVE2,138,BATCAVE,LZ_SOUTH,BATCAVE_RN,,HB_SOUTH,1002\n\
+            NOTREES_1,NT1,NOTREES1,345,NOTREES,LZ_WEST,NOTREES_BESS_RN,,HB_WEST,2001\n\
+            DOWNTOWN_1,DT1,DOWNTOWN1,138,DOWNTOWN,LZ_HOUSTON,,,HB_HOUSTON,3001\n").unwrap();
+        let list = SettlementPointList::load(&path).unwrap();
+
+        assert_eq!(list.len(), 2);
+        assert_eq!(list.load_zone("BATCAVE_RN"), Some("LZ_SOUTH"));
+        assert_eq!(list.load_zone("NOTREES_BESS_RN"), Some("LZ_WEST"));
+        assert_eq!(list.hub("NOTREES_BESS_RN"), Some("HB_WEST"));
+        assert_eq!(list.load_zone("UNKNOWN_RN"), None);
+    }
+
+    #[test]
+    fn test_alias_cycles_terminate() {
+        let mut aliases = SettlementPointAliases::default();
+        aliases.insert("A", "B");
+        aliases.insert("B", "A");
+        assert!(aliases.resolve("A").is_some());
+        assert_eq!(aliases.lookup("A", |_| None), None);
+    }
+}
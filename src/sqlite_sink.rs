@@ -0,0 +1,124 @@
+use anyhow::{Context, Result};
+use polars::prelude::*;
+use rusqlite::types::Value;
+use rusqlite::{params_from_iter, Connection};
+use std::path::Path;
+
+/// SQLite column type for a frame column: floats are REAL, integers and
+/// booleans INTEGER, anything else (names, `YYYY-MM-DD` dates) TEXT
+fn sql_type(dtype: &DataType) -> &'static str {
+    match dtype {
+        DataType::Float32 | DataType::Float64 => "REAL",
+        DataType::Boolean
+        | DataType::Int8
+        | DataType::Int16
+        | DataType::Int32
+        | DataType::Int64
+        | DataType::UInt8
+        | DataType::UInt16
+        | DataType::UInt32
+        | DataType::UInt64 => "INTEGER",
+        _ => "TEXT",
+    }
+}
+
+fn sql_value(value: AnyValue) -> Value {
+    match value {
+        AnyValue::Null => Value::Null,
+        AnyValue::Boolean(v) => Value::Integer(v as i64),
+        AnyValue::Int8(v) => Value::Integer(v as i64),
+        AnyValue::Int16(v) => Value::Integer(v as i64),
+        AnyValue::Int32(v) => Value::Integer(v as i64),
+        AnyValue::Int64(v) => Value::Integer(v),
+        AnyValue::UInt8(v) => Value::Integer(v as i64),
+        AnyValue::UInt16(v) => Value::Integer(v as i64),
+        AnyValue::UInt32(v) => Value::Integer(v as i64),
+        AnyValue::UInt64(v) => Value::Integer(v as i64),
+        AnyValue::Float32(v) => Value::Real(v as f64),
+        AnyValue::Float64(v) => Value::Real(v),
+        AnyValue::Utf8(v) => Value::Text(v.to_string()),
+        other => Value::Text(other.to_string()),
+    }
+}
+
+/// Replace `table` in the SQLite database at `path` (created if missing) with
+/// the rows of `df`, typed per column, and index it on `index_columns`
+pub fn write_table(path: &Path, table: &str, df: &DataFrame, index_columns: &[&str]) -> Result<()> {
+    let mut conn = Connection::open(path)
+        .with_context(|| format!("Failed to open SQLite database {}", path.display()))?;
+    let tx = conn.transaction()?;
+
+    let columns = df.get_columns();
+    let definitions: Vec<String> = columns.iter()
+        .map(|column| format!("\"{}\" {}", column.name(), sql_type(column.dtype())))
+        .collect();
+    tx.execute(&format!("DROP TABLE IF EXISTS \"{}\"", table), [])?;
+    tx.execute(&format!("CREATE TABLE \"{}\" ({})", table, definitions.join(", ")), [])?;
+
+    {
+        let placeholders = vec!["?"; columns.len()].join(", ");
+        let mut insert = tx.prepare(&format!("INSERT INTO \"{}\" VALUES ({})", table, placeholders))?;
+        for i in 0..df.height() {
+            let row = columns.iter()
+                .map(|column| column.get(i).map(sql_value))
+                .collect::<PolarsResult<Vec<_>>>()?;
+            insert.execute(params_from_iter(row))?;
+        }
+    }
+
+    if !index_columns.is_empty() {
+        let quoted: Vec<String> = index_columns.iter().map(|column| format!("\"{}\"", column)).collect();
+        tx.execute(
+            &format!("CREATE INDEX \"idx_{}_{}\" ON \"{}\" ({})", table, index_columns.join("_"), table, quoted.join(", ")),
+            [],
+        )?;
+    }
+
+    tx.commit()
+        .with_context(|| format!("Failed to write table {} to {}", table, path.display()))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_daily_table_keeps_numeric_types_and_index() {
+        let daily = df! {
+            "Resource_Name" => &["BATCAVE_BES1", "BATCAVE_BES1"],
+            "Date" => &["2024-08-01", "2024-08-02"],
+            "Days" => &[1u32, 1],
+            "Total_Revenue" => &[1250.5, -80.0],
+        }
+        .unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("bess_revenues.sqlite");
+        write_table(&path, "daily_revenues", &daily, &["Resource_Name", "Date"]).unwrap();
+        // Re-running replaces the table rather than appending to it
+        write_table(&path, "daily_revenues", &daily, &["Resource_Name", "Date"]).unwrap();
+
+        let conn = Connection::open(&path).unwrap();
+        let (count, total, types): (i64, f64, String) = conn
+            .query_row(
+                "SELECT COUNT(*), SUM(Total_Revenue), typeof(Total_Revenue) || ',' || typeof(Days) || ',' || typeof(Date)
+                 FROM daily_revenues WHERE Resource_Name = 'BATCAVE_BES1'",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .unwrap();
+        assert_eq!(count, 2);
+        assert!((total - 1170.5).abs() < 1e-9);
+        assert_eq!(types, "real,integer,text");
+
+        let indexed: Vec<String> = conn
+            .prepare("SELECT name FROM pragma_index_info('idx_daily_revenues_Resource_Name_Date')")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap();
+        assert_eq!(indexed, vec!["Resource_Name", "Date"]);
+    }
+}
@@ -0,0 +1,121 @@
+use anyhow::Result;
+use std::fmt::Write as _;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Wall-clock time and rows processed for one pipeline stage
+#[derive(Debug, Clone, PartialEq)]
+pub struct StageTiming {
+    pub name: String,
+    pub elapsed: Duration,
+    pub rows: u64,
+}
+
+impl StageTiming {
+    pub fn rows_per_sec(&self) -> Option<f64> {
+        let secs = self.elapsed.as_secs_f64();
+        (self.rows > 0 && secs > 0.0).then(|| self.rows as f64 / secs)
+    }
+}
+
+/// Accumulates elapsed time and row counts per named stage, in the order
+/// stages first appear. Clones share the same timings, so one timer can be
+/// handed to each component of a run.
+#[derive(Debug, Clone, Default)]
+pub struct StageTimer {
+    stages: Arc<Mutex<Vec<StageTiming>>>,
+}
+
+impl StageTimer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Run `f`, adding its wall-clock time to `stage`
+    pub fn time<T>(&self, stage: &str, f: impl FnOnce() -> Result<T>) -> Result<T> {
+        let start = Instant::now();
+        let result = f();
+        self.record(stage, start.elapsed(), 0);
+        result
+    }
+
+    /// Count rows processed by `stage` towards its throughput
+    pub fn add_rows(&self, stage: &str, rows: usize) {
+        self.record(stage, Duration::ZERO, rows as u64);
+    }
+
+    pub fn record(&self, stage: &str, elapsed: Duration, rows: u64) {
+        let mut stages = self.stages.lock().unwrap();
+        match stages.iter_mut().find(|s| s.name == stage) {
+            Some(timing) => {
+                timing.elapsed += elapsed;
+                timing.rows += rows;
+            }
+            None => stages.push(StageTiming { name: stage.to_string(), elapsed, rows }),
+        }
+    }
+
+    pub fn stages(&self) -> Vec<StageTiming> {
+        self.stages.lock().unwrap().clone()
+    }
+
+    pub fn total(&self) -> Duration {
+        self.stages.lock().unwrap().iter().map(|s| s.elapsed).sum()
+    }
+
+    /// Per-stage seconds, share of the run, rows and rows/sec
+    pub fn summary(&self) -> String {
+        let stages = self.stages();
+        let total = self.total().as_secs_f64();
+
+        let mut out = String::new();
+        writeln!(out, "{:<20} {:>10} {:>7} {:>14} {:>14}", "Stage", "Seconds", "%", "Rows", "Rows/sec").unwrap();
+        writeln!(out, "{}", "-".repeat(69)).unwrap();
+        for stage in &stages {
+            let secs = stage.elapsed.as_secs_f64();
+            let pct = if total > 0.0 { 100.0 * secs / total } else { 0.0 };
+            let rate = stage.rows_per_sec().map_or("-".to_string(), |r| format!("{:.0}", r));
+            let rows = if stage.rows > 0 { stage.rows.to_string() } else { "-".to_string() };
+            writeln!(out, "{:<20} {:>10.2} {:>6.1}% {:>14} {:>14}", stage.name, secs, pct, rows, rate).unwrap();
+        }
+        writeln!(out, "{}", "-".repeat(69)).unwrap();
+        write!(out, "{:<20} {:>10.2}", "Total", total).unwrap();
+        out
+    }
+
+    pub fn print_summary(&self) {
+        println!("\n⏱️  Stage Timing Summary:");
+        println!("{}", self.summary());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stages_accumulate_time_and_rows() {
+        let timer = StageTimer::new();
+        let shared = timer.clone();
+
+        shared.record("Load prices", Duration::from_millis(1500), 3_000_000);
+        timer.record("RT", Duration::from_secs(2), 0);
+        timer.add_rows("RT", 500_000);
+        timer.record("RT", Duration::from_secs(2), 500_000);
+        let rows = timer.time("Rollup", || Ok(42)).unwrap();
+        assert_eq!(rows, 42);
+
+        let stages = timer.stages();
+        let names: Vec<&str> = stages.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["Load prices", "RT", "Rollup"]);
+        assert_eq!(stages[1].elapsed, Duration::from_secs(4));
+        assert_eq!(stages[1].rows, 1_000_000);
+        assert_eq!(stages[1].rows_per_sec(), Some(250_000.0));
+        assert_eq!(stages[0].rows_per_sec(), Some(2_000_000.0));
+        assert_eq!(stages[2].rows_per_sec(), None);
+
+        let summary = timer.summary();
+        assert!(summary.lines().any(|l| l.starts_with("RT") && l.contains("4.00") && l.contains("250000")));
+        assert!(summary.lines().last().unwrap().starts_with("Total"));
+    }
+}
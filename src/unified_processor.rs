@@ -1,20 +1,93 @@
 use anyhow::Result;
-use chrono::{NaiveDate, NaiveDateTime, Datelike, Duration};
+use log::{debug, info, warn};
+use chrono::{NaiveDate, NaiveDateTime, Datelike};
 use glob::glob;
 use indicatif::{ProgressBar, ProgressStyle};
 use polars::prelude::*;
 use rayon::prelude::*;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs;
 use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
+use std::time::Instant;
 use ::zip::ZipArchive;
 
+use crate::combine_progress::{concat_with_progress, row_progress_bar};
+use crate::csv_headers::read_csv;
+use crate::delivery_fields::DeliveryFieldCheck;
+use crate::empty_year::{schema_from_existing, write_empty_parquet};
+use crate::error::PipelineError;
+use crate::datetime_format;
+use crate::intervals::{central_datetime_ms, delivery_interval_datetime_ms, hour_ending_datetime_ms, is_repeated_hour, operating_day_datetime_ms, parse_sced_timestamp};
+use crate::output_formats::OutputFormats;
+use crate::processing_filter::ProcessingFilter;
+use crate::row_counts::RowCounts;
+use crate::csv_extractor::{decompress_gz_file, gz_csv_name, is_gzipped_csv, read_gz_to_vec};
+use crate::output_prefix::OutputPrefix;
+use crate::run_metadata;
+
+/// (source directory, output prefix) for each dataset
+const DATASETS: [(&str, &str); 7] = [
+    ("Settlement_Point_Prices_at_Resource_Nodes,_Hubs_and_Load_Zones", "RT_Settlement_Point_Prices"),
+    ("LMPs by Resource Nodes, Load Zones and Trading Hubs", "RT_LMPs"),
+    ("DAM_Settlement_Point_Prices", "DAM_Settlement_Point_Prices"),
+    ("DAM_Hourly_LMPs", "DAM_Hourly_LMPs"),
+    ("DAM_Clearing_Prices_for_Capacity", "DAM_Ancillary_Services"),
+    ("SCED_Shadow_Prices_and_Binding_Transmission_Constraints", "SCED_Shadow_Prices"),
+    ("DAM_Shadow_Prices", "DAM_Shadow_Prices"),
+];
+
+/// Columns a file's year is read from when its name has none
+const DATE_COLUMNS: [&str; 7] = [
+    "DeliveryDate",
+    "SCEDTimestamp",
+    "Date",
+    "OperatingDate",
+    "TradeDate",
+    "Interval",
+    "SCED_TIMESTAMP",
+];
+
+/// Rows read to find a file's year from its content
+const YEAR_SAMPLE_ROWS: usize = 10;
+/// Rows read under `YearInference::Majority`
+const MAJORITY_SAMPLE_ROWS: usize = 1000;
+
+/// How a file's year is read from its rows when its name has none
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum YearInference {
+    /// Year of the first row with a parseable date
+    #[default]
+    FirstValidRow,
+    /// Most common year among the sampled rows' dates (`--year-by-majority`)
+    Majority,
+}
+
+/// Annual output a file goes to; files whose year can't be determined are
+/// kept together in an `unknown` partition rather than dropped
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum YearPartition {
+    Year(i32),
+    Unknown,
+}
+
+impl std::fmt::Display for YearPartition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            YearPartition::Year(year) => write!(f, "{}", year),
+            YearPartition::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
 pub struct UnifiedDataProcessor {
     base_dir: PathBuf,
     output_dir: PathBuf,
     column_history: Arc<Mutex<HashMap<String, HashSet<String>>>>,
+    filter: ProcessingFilter,
+    year_inference: YearInference,
+    formats: OutputFormats,
 }
 
 impl UnifiedDataProcessor {
@@ -23,24 +96,45 @@ impl UnifiedDataProcessor {
             base_dir, 
             output_dir,
             column_history: Arc::new(Mutex::new(HashMap::new())),
+            filter: ProcessingFilter::default(),
+            year_inference: YearInference::default(),
+            formats: OutputFormats::default(),
         }
     }
     
+    /// Restrict the run to selected years/datasets
+    pub fn with_filter(mut self, filter: ProcessingFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+    
+    /// How to read a year from file contents when the name has none
+    pub fn with_year_inference(mut self, year_inference: YearInference) -> Self {
+        self.year_inference = year_inference;
+        self
+    }
+    
+    /// Which of CSV, Parquet and Arrow to write for each year
+    pub fn with_formats(mut self, formats: OutputFormats) -> Self {
+        self.formats = formats;
+        self
+    }
+    
     pub fn process_all_data(&self) -> Result<()> {
-        println!("🚀 ERCOT Unified Data Processor");
-        println!("Using {} CPU cores", rayon::current_num_threads());
-        println!("{}", "=".repeat(80));
+        info!("ERCOT Unified Data Processor");
+        info!("Using {} CPU cores", rayon::current_num_threads());
+        self.filter.print_summary();
         
         // Step 1: Recursively unzip all files
-        println!("\n📦 Step 1: Extracting all ZIP files recursively...");
+        info!("Step 1: Extracting all ZIP files recursively...");
         self.recursive_unzip_all()?;
         
         // Step 2: Process CSV files by year
-        println!("\n📅 Step 2: Processing CSV files by year...");
+        info!("Step 2: Processing CSV files by year...");
         self.process_csv_by_year()?;
         
         // Step 3: Report column changes over time
-        println!("\n📊 Step 3: Column evolution report...");
+        info!("Step 3: Column evolution report...");
         self.report_column_changes();
         
         Ok(())
@@ -48,27 +142,21 @@ impl UnifiedDataProcessor {
     
     fn recursive_unzip_all(&self) -> Result<()> {
         // Find all directories to process
-        let dirs_to_process = vec![
-            "Settlement_Point_Prices_at_Resource_Nodes,_Hubs_and_Load_Zones",
-            "LMPs by Resource Nodes, Load Zones and Trading Hubs",
-            "DAM_Settlement_Point_Prices",
-            "DAM_Hourly_LMPs",
-            "DAM_Clearing_Prices_for_Capacity",
-            "SCED_Shadow_Prices_and_Binding_Transmission_Constraints",
-            "DAM_Shadow_Prices",
-        ];
-        
-        for dir_name in dirs_to_process {
+        for (dir_name, output_prefix) in DATASETS {
+            if !self.filter.includes_dataset(&[dir_name, output_prefix]) {
+                continue;
+            }
+            
             let source_dir = self.base_dir.join(dir_name);
             if !source_dir.exists() {
-                println!("  ⚠️  Directory not found: {}", dir_name);
+                warn!("Directory not found: {}", dir_name);
                 continue;
             }
             
             let unzipped_dir = source_dir.join("unzipped");
             fs::create_dir_all(&unzipped_dir)?;
             
-            println!("\n  📁 Processing: {}", dir_name);
+            info!("Processing: {}", dir_name);
             self.recursive_unzip(&source_dir, &unzipped_dir)?;
         }
         
@@ -76,18 +164,35 @@ impl UnifiedDataProcessor {
     }
     
     fn recursive_unzip(&self, source_dir: &Path, unzipped_dir: &Path) -> Result<()> {
+        // Gzipped CSVs only need decompressing into the unzipped directory
+        let gz_pattern = source_dir.join("*.csv.gz");
+        let gz_files: Vec<PathBuf> = glob(gz_pattern.to_str().unwrap())?
+            .filter_map(Result::ok)
+            .filter(|p| self.filter.includes_file(&p.file_name().unwrap_or_default().to_string_lossy()))
+            .collect();
+        
+        if !gz_files.is_empty() {
+            debug!("Found {} gzipped CSV files", gz_files.len());
+            gz_files.par_iter().for_each(|gz_path| {
+                if let Err(e) = decompress_gz_file(gz_path, unzipped_dir) {
+                    warn!("Failed to decompress {}: {}", gz_path.display(), e);
+                }
+            });
+        }
+        
         // Find all ZIP files in the source directory
         let pattern = source_dir.join("*.zip");
         let zip_files: Vec<PathBuf> = glob(pattern.to_str().unwrap())?
             .filter_map(Result::ok)
+            .filter(|p| self.filter.includes_file(&p.file_name().unwrap_or_default().to_string_lossy()))
             .collect();
         
         if zip_files.is_empty() {
-            println!("    No ZIP files found in {}", source_dir.display());
+            warn!("No ZIP files found in {}", source_dir.display());
             return Ok(());
         }
         
-        println!("    Found {} ZIP files", zip_files.len());
+        debug!("Found {} ZIP files", zip_files.len());
         
         let pb = ProgressBar::new(zip_files.len() as u64);
         pb.set_style(ProgressStyle::default_bar()
@@ -133,6 +238,19 @@ impl UnifiedDataProcessor {
                                         let _ = out_file.write_all(&buffer);
                                     }
                                 }
+                            } else if is_gzipped_csv(&file_name) {
+                                // Gzipped CSV - decompress directly
+                                let out_path = unzipped_dir.join(gz_csv_name(&file_name));
+                                if let Some(parent) = out_path.parent() {
+                                    let _ = fs::create_dir_all(parent);
+                                }
+                                
+                                match read_gz_to_vec(&mut file) {
+                                    Ok(buffer) => {
+                                        let _ = fs::write(&out_path, buffer);
+                                    }
+                                    Err(e) => warn!("Skipping unreadable gzip {} in {}: {}", file_name, zip_path.display(), e),
+                                }
                             }
                         }
                     }
@@ -149,7 +267,7 @@ impl UnifiedDataProcessor {
         };
         
         if !nested.is_empty() {
-            println!("    Found {} nested ZIP files, extracting...", nested.len());
+            debug!("Found {} nested ZIP files, extracting...", nested.len());
             
             let pb_nested = ProgressBar::new(nested.len() as u64);
             pb_nested.set_style(ProgressStyle::default_bar()
@@ -175,6 +293,19 @@ impl UnifiedDataProcessor {
                                             let _ = out_file.write_all(&buffer);
                                         }
                                     }
+                                } else if is_gzipped_csv(file.name()) {
+                                    let csv_name = gz_csv_name(file.name()).to_string();
+                                    let out_path = zip_path.with_extension("").join(&csv_name);
+                                    if let Some(parent) = out_path.parent() {
+                                        let _ = fs::create_dir_all(parent);
+                                    }
+                                    
+                                    match read_gz_to_vec(&mut file) {
+                                        Ok(buffer) => {
+                                            let _ = fs::write(&out_path, buffer);
+                                        }
+                                        Err(e) => warn!("Skipping unreadable gzip {}.gz in {}: {}", csv_name, zip_path.display(), e),
+                                    }
                                 }
                             }
                         }
@@ -192,23 +323,17 @@ impl UnifiedDataProcessor {
     
     fn process_csv_by_year(&self) -> Result<()> {
         // Process each data type
-        let datasets = vec![
-            ("Settlement_Point_Prices_at_Resource_Nodes,_Hubs_and_Load_Zones", "RT_Settlement_Point_Prices"),
-            ("LMPs by Resource Nodes, Load Zones and Trading Hubs", "RT_LMPs"),
-            ("DAM_Settlement_Point_Prices", "DAM_Settlement_Point_Prices"),
-            ("DAM_Hourly_LMPs", "DAM_Hourly_LMPs"),
-            ("DAM_Clearing_Prices_for_Capacity", "DAM_Ancillary_Services"),
-            ("SCED_Shadow_Prices_and_Binding_Transmission_Constraints", "SCED_Shadow_Prices"),
-            ("DAM_Shadow_Prices", "DAM_Shadow_Prices"),
-        ];
-        
-        for (dir_name, output_prefix) in datasets {
+        for (dir_name, output_prefix) in DATASETS {
+            if !self.filter.includes_dataset(&[dir_name, output_prefix]) {
+                continue;
+            }
+            
             let unzipped_dir = self.base_dir.join(dir_name).join("unzipped");
             if !unzipped_dir.exists() {
                 continue;
             }
             
-            println!("\n📊 Processing dataset: {}", output_prefix);
+            info!("Processing dataset: {}", output_prefix);
             
             // Find all CSV files
             let pattern = unzipped_dir.join("**/*.csv");
@@ -217,31 +342,39 @@ impl UnifiedDataProcessor {
                 .collect();
             
             if csv_files.is_empty() {
-                println!("  No CSV files found");
+                warn!("No CSV files found");
                 continue;
             }
             
-            println!("  Found {} CSV files", csv_files.len());
+            info!("Found {} CSV files", csv_files.len());
+            run_metadata::record_inputs(output_prefix, csv_files.len());
             
             // Group files by year
             let files_by_year = self.group_files_by_year(&csv_files)?;
             
             // Process each year
-            for (year, files) in files_by_year {
+            for (partition, files) in files_by_year {
                 if files.is_empty() {
                     continue;
                 }
-                
-                println!("\n  📅 Processing year {}: {} files", year, files.len());
-                self.process_year_data(year, &files, output_prefix)?;
+                match partition {
+                    YearPartition::Year(year) if !self.filter.includes_year(year) => continue,
+                    YearPartition::Year(year) => info!("Processing year {}: {} files", year, files.len()),
+                    YearPartition::Unknown => {
+                        let examples: Vec<String> = files.iter().take(5).map(|f| f.display().to_string()).collect();
+                        warn!("{} {} files have no year in their name or dates; writing them to {}_unknown (e.g. {})",
+                              files.len(), output_prefix, output_prefix, examples.join(", "));
+                    }
+                }
+                self.process_year_data(partition, &files, output_prefix)?;
             }
         }
         
         Ok(())
     }
     
-    fn group_files_by_year(&self, files: &[PathBuf]) -> Result<HashMap<i32, Vec<PathBuf>>> {
-        let mut files_by_year: HashMap<i32, Vec<PathBuf>> = HashMap::new();
+    fn group_files_by_year(&self, files: &[PathBuf]) -> Result<BTreeMap<YearPartition, Vec<PathBuf>>> {
+        let mut files_by_year: BTreeMap<YearPartition, Vec<PathBuf>> = BTreeMap::new();
         
         for file_path in files {
             // Extract year from filename
@@ -253,11 +386,11 @@ impl UnifiedDataProcessor {
             let year = self.extract_year_from_filename(filename)
                 .or_else(|| self.extract_year_from_csv_content(file_path).ok().flatten());
             
-            if let Some(y) = year {
-                if y >= 2010 && y <= 2030 { // Sanity check
-                    files_by_year.entry(y).or_insert_with(Vec::new).push(file_path.clone());
-                }
-            }
+            let partition = match year {
+                Some(y) if (2010..=2030).contains(&y) => YearPartition::Year(y), // Sanity check
+                _ => YearPartition::Unknown,
+            };
+            files_by_year.entry(partition).or_insert_with(Vec::new).push(file_path.clone());
         }
         
         Ok(files_by_year)
@@ -288,47 +421,20 @@ impl UnifiedDataProcessor {
     }
     
     fn extract_year_from_csv_content(&self, file_path: &Path) -> Result<Option<i32>> {
-        // Read first few lines to determine year
+        // Read the first rows to determine year
+        let sample_rows = match self.year_inference {
+            YearInference::FirstValidRow => YEAR_SAMPLE_ROWS,
+            YearInference::Majority => MAJORITY_SAMPLE_ROWS,
+        };
         let df = CsvReader::new(fs::File::open(file_path)?)
             .has_header(true)
-            .with_n_rows(Some(10))
+            .with_n_rows(Some(sample_rows))
             .finish()?;
         
-        // Try to find date columns - expanded list for different data types
-        let date_columns = vec![
-            "DeliveryDate", 
-            "SCEDTimestamp", 
-            "Date", 
-            "OperatingDate",
-            "TradeDate",
-            "Interval",
-            "SCED_TIMESTAMP"
-        ];
-        
-        for col_name in date_columns {
-            if let Ok(col) = df.column(col_name) {
-                if let Ok(str_col) = col.utf8() {
-                    if let Some(first_val) = str_col.get(0) {
-                        // Try parsing different date formats
-                        if let Ok(date) = NaiveDate::parse_from_str(first_val, "%m/%d/%Y") {
-                            return Ok(Some(date.year()));
-                        }
-                        if let Ok(datetime) = NaiveDateTime::parse_from_str(first_val, "%m/%d/%Y %H:%M:%S") {
-                            return Ok(Some(datetime.year()));
-                        }
-                        // Try SCED timestamp format with AM/PM
-                        if let Ok(datetime) = NaiveDateTime::parse_from_str(first_val, "%m/%d/%Y %I:%M:%S %p") {
-                            return Ok(Some(datetime.year()));
-                        }
-                    }
-                }
-            }
-        }
-        
-        Ok(None)
+        Ok(content_year(&df, self.year_inference))
     }
     
-    fn process_year_data(&self, year: i32, files: &[PathBuf], output_prefix: &str) -> Result<()> {
+    fn process_year_data(&self, year: YearPartition, files: &[PathBuf], output_prefix: &str) -> Result<()> {
         let output_dir = self.output_dir.join(format!("{}_{}", output_prefix, year));
         fs::create_dir_all(&output_dir)?;
         
@@ -336,29 +442,49 @@ impl UnifiedDataProcessor {
         let batch_size = 100; // Process 100 files at a time for better memory management
         let total_batches = (files.len() + batch_size - 1) / batch_size;
         
-        println!("    Total files: {}, Batch size: {}, Total batches: {}", 
+        debug!("Total files: {}, Batch size: {}, Total batches: {}", 
                  files.len(), batch_size, total_batches);
         
         let mut all_batch_results = Vec::new();
+        let mut rows_so_far = 0usize;
+        let started = Instant::now();
         
         for (batch_idx, batch) in files.chunks(batch_size).enumerate() {
-            println!("    Processing batch {}/{} ({} files)...", 
+            debug!("Processing batch {}/{} ({} files)...", 
                      batch_idx + 1, total_batches, batch.len());
             
             let batch_df = self.process_batch(batch, year)?;
             if let Some(df) = batch_df {
+                rows_so_far += df.height();
                 all_batch_results.push(df);
             }
+            
+            // Each batch draws its own file bar, so report the year's overall pace here
+            let elapsed = started.elapsed();
+            let remaining = elapsed.mul_f64((total_batches - batch_idx - 1) as f64 / (batch_idx + 1) as f64);
+            info!("{} {}: batch {}/{} done, {} rows so far ({:.0} rows/s, ETA {}s)",
+                  output_prefix, year, batch_idx + 1, total_batches, rows_so_far,
+                  rows_so_far as f64 / elapsed.as_secs_f64().max(1e-3), remaining.as_secs());
         }
         
         if all_batch_results.is_empty() {
-            println!("    ⚠️  No valid data found for year {}", year);
+            // Leave a schema-only Parquet, copied from another year of the
+            // dataset, so per-year loaders read zero rows instead of failing
+            let pattern = self.output_dir.join(format!("{}_*", output_prefix)).join(format!("{}_*.parquet", output_prefix));
+            match schema_from_existing(&pattern) {
+                Some(schema) => {
+                    let parquet_path = output_dir.join(format!("{}_{}.parquet", output_prefix, year));
+                    warn!("No valid data found for year {}; writing schema-only {}", year, parquet_path.display());
+                    write_empty_parquet(&parquet_path, &schema)?;
+                }
+                None => warn!("No valid data found for year {} and no other {} year to take a schema from", year, output_prefix),
+            }
             return Ok(());
         }
         
         // Combine all batches
-        println!("    📦 Combining {} batches...", all_batch_results.len());
-        let combined_df = self.combine_and_deduplicate(all_batch_results)?;
+        debug!("Combining {} batches...", all_batch_results.len());
+        let combined_df = self.combine_and_deduplicate(all_batch_results, year, output_prefix)?;
         
         // Save annual files
         self.save_annual_files(&combined_df, &output_dir, output_prefix, year)?;
@@ -366,7 +492,7 @@ impl UnifiedDataProcessor {
         Ok(())
     }
     
-    fn process_batch(&self, files: &[PathBuf], year: i32) -> Result<Option<DataFrame>> {
+    fn process_batch(&self, files: &[PathBuf], year: YearPartition) -> Result<Option<DataFrame>> {
         let pb = ProgressBar::new(files.len() as u64);
         pb.set_style(ProgressStyle::default_bar()
             .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len}")
@@ -378,9 +504,7 @@ impl UnifiedDataProcessor {
                 pb.inc(1);
                 
                 // Read CSV file
-                let mut df = CsvReader::new(fs::File::open(file_path).ok()?)
-                    .has_header(true)
-                    .finish()
+                let mut df = read_csv(file_path)
                     .ok()?;
                 
                 // Standardize column names for consistency across different datasets
@@ -481,7 +605,7 @@ impl UnifiedDataProcessor {
                 }
                 
                 // Filter by year if we can verify it
-                if let Some(extracted_year) = self.verify_year(&df) {
+                if let (YearPartition::Year(year), Some(extracted_year)) = (year, content_year(&df, self.year_inference)) {
                     if extracted_year != year {
                         return None;
                     }
@@ -545,168 +669,70 @@ impl UnifiedDataProcessor {
         ) {
             Ok(lf) => lf,
             Err(e) => {
-                println!("      ⚠️  Error concatenating dataframes: {}", e);
-                println!("      Attempting to diagnose schema issues...");
+                warn!("Error concatenating dataframes: {}", e);
+                debug!("Attempting to diagnose schema issues...");
                 
                 // Print column info for first few dataframes
                 for (i, df) in aligned_dfs.iter().take(3).enumerate() {
-                    println!("      DataFrame {}: {} columns", i, df.width());
+                    debug!("DataFrame {}: {} columns", i, df.width());
                     for col in df.get_column_names() {
-                        println!("        - {}", col);
+                        debug!("- {}", col);
                     }
                 }
                 
-                return Err(anyhow::anyhow!("Failed to concatenate: {}", e));
+                return Err(PipelineError::SchemaMismatch {
+                    details: format!("failed to concatenate: {}", e),
+                }
+                .into());
             }
         };
         
         Ok(Some(combined.collect()?))
     }
     
-    fn verify_year(&self, df: &DataFrame) -> Option<i32> {
-        // Try to extract year from the dataframe content
-        let date_columns = vec![
-            "DeliveryDate", 
-            "SCEDTimestamp", 
-            "Date", 
-            "OperatingDate",
-            "TradeDate",
-            "Interval",
-            "SCED_TIMESTAMP"
-        ];
-        
-        for col_name in date_columns {
-            if let Ok(col) = df.column(col_name) {
-                if let Ok(str_col) = col.utf8() {
-                    if let Some(first_val) = str_col.get(0) {
-                        if let Ok(date) = NaiveDate::parse_from_str(first_val, "%m/%d/%Y") {
-                            return Some(date.year());
-                        }
-                        if let Ok(datetime) = NaiveDateTime::parse_from_str(first_val, "%m/%d/%Y %H:%M:%S") {
-                            return Some(datetime.year());
-                        }
-                        // Try SCED timestamp format with AM/PM
-                        if let Ok(datetime) = NaiveDateTime::parse_from_str(first_val, "%m/%d/%Y %I:%M:%S %p") {
-                            return Some(datetime.year());
-                        }
-                    }
-                }
-            }
-        }
-        
-        None
-    }
-    
-    fn combine_and_deduplicate(&self, dfs: Vec<DataFrame>) -> Result<DataFrame> {
-        println!("      🔄 Combining dataframes...");
+    fn combine_and_deduplicate(&self, dfs: Vec<DataFrame>, year: YearPartition, output_prefix: &str) -> Result<DataFrame> {
+        debug!("Combining dataframes...");
         
         if dfs.is_empty() {
-            return Err(anyhow::anyhow!("No dataframes to combine"));
+            return match year {
+                YearPartition::Year(year) => Err(PipelineError::NoDataForYear { year }.into()),
+                YearPartition::Unknown => Err(anyhow::anyhow!("No data in files of unknown year")),
+            };
         }
         
-        // First, find all unique columns across all dataframes
-        let mut all_columns = HashSet::new();
-        for df in &dfs {
-            for col in df.get_column_names() {
-                all_columns.insert(col.to_string());
-            }
-        }
-        
-        // Ensure all price columns are float64 before concatenation
-        let price_columns = vec!["SettlementPointPrice", "LMP", "Price", "ShadowPrice",
-                                "MCPCValue", "EnergyPrice", "CongestionPrice", "LossPrice",
-                                "Energy", "Congestion", "Loss"];
-        
-        let aligned_dfs: Vec<DataFrame> = dfs.into_iter()
-            .map(|mut df| {
-                // First add missing columns
-                for col in &all_columns {
-                    if !df.get_column_names().contains(&col.as_str()) {
-                        // Add null column of appropriate type based on column name
-                        let null_series = if col == "DSTFlag" {
-                            Series::new(col, vec![None::<&str>; df.height()])
-                        } else if col.contains("Price") || col.contains("LMP") || col.contains("MCPC") {
-                            Series::new(col, vec![None::<f64>; df.height()])
-                        } else if col.contains("Hour") || col.contains("Interval") {
-                            Series::new(col, vec![None::<i32>; df.height()])
-                        } else {
-                            Series::new(col, vec![None::<&str>; df.height()])
-                        };
-                        let _ = df.with_column(null_series);
-                    }
-                }
-                
-                // Cast price columns to float64
-                for price_col in &price_columns {
-                    if df.get_column_names().contains(price_col) {
-                        if let Ok(col) = df.column(price_col) {
-                            if col.dtype() != &DataType::Float64 {
-                                if let Ok(cast_col) = col.cast(&DataType::Float64) {
-                                    let _ = df.with_column(cast_col);
-                                }
-                            }
-                        }
-                    }
-                }
-                
-                // Also ensure DSTFlag is string
-                if df.get_column_names().contains(&"DSTFlag") {
-                    if let Ok(col) = df.column("DSTFlag") {
-                        if col.dtype() != &DataType::Utf8 {
-                            if let Ok(cast_col) = col.cast(&DataType::Utf8) {
-                                let _ = df.with_column(cast_col);
-                            }
-                        }
-                    }
-                }
-                
-                df
-            })
-            .collect();
-        
-        // Combine all dataframes with relaxed concat
-        let lazy_dfs: Vec<LazyFrame> = aligned_dfs.into_iter()
-            .map(|df| df.lazy())
-            .collect();
-        
-        let mut union_args = UnionArgs::default();
-        union_args.parallel = true;
+        let input_rows: usize = dfs.iter().map(|df| df.height()).sum();
+        let label = format!("{} {}", output_prefix, year);
+        let pb = row_progress_bar(input_rows, &label);
+        let aligned_dfs = align_columns(dfs);
         
-        let mut combined = concat(
-            lazy_dfs.iter().map(|lf| lf.clone()).collect::<Vec<_>>().as_slice(),
-            union_args,
-        )?.collect()?;
+        let mut combined = concat_with_progress(aligned_dfs, &pb)?;
         
         // Create datetime column if possible
+        pb.set_message("building datetime");
         if let Ok(datetime_df) = self.create_datetime_column(&combined) {
             combined = datetime_df;
         }
         
-        // Identify price columns (columns to exclude from deduplication)
-        let price_columns: HashSet<&str> = vec![
-            "SettlementPointPrice", "LMP", "Price", "ShadowPrice",
-            "MCPCValue", "EnergyPrice", "CongestionPrice", "LossPrice",
-            "Energy", "Congestion", "Loss"
-        ].into_iter().collect();
-        
         // Get all columns except price columns for deduplication
-        let all_columns = combined.get_column_names();
-        let dedup_columns: Vec<String> = all_columns.iter()
-            .filter(|col| !price_columns.contains(*col))
-            .map(|s| s.to_string())
-            .collect();
+        let dedup_columns = dedup_columns(&combined);
         
-        println!("      🧹 Deduplicating on {} columns (excluding price fields)...", dedup_columns.len());
+        debug!("Deduplicating on {} columns (excluding price fields)...", dedup_columns.len());
+        pb.set_message("deduplicating");
         
         // Remove duplicates
         let unique_df = combined.unique(Some(&dedup_columns), UniqueKeepStrategy::Last, None)?;
         
-        println!("      📊 Records before dedup: {}, after: {}", 
-                 combined.height(), unique_df.height());
+        RowCounts {
+            input: input_rows,
+            concatenated: combined.height(),
+            deduplicated: unique_df.height(),
+        }
+        .log(&label);
+        pb.set_message("sorting");
         
         // Sort by datetime if available
         let sorted_df = if unique_df.get_column_names().contains(&"datetime") {
-            println!("      🔄 Sorting by datetime...");
+            debug!("Sorting by datetime...");
             unique_df.lazy()
                 .sort("datetime", Default::default())
                 .collect()?
@@ -720,13 +746,14 @@ impl UnifiedDataProcessor {
                 sort_cols.push(col("DeliveryInterval"));
             }
             
-            println!("      🔄 Sorting by date fields...");
+            debug!("Sorting by date fields...");
             unique_df.lazy()
                 .sort_by_exprs(&sort_cols, vec![false; sort_cols.len()], false, false)
                 .collect()?
         } else {
             unique_df
         };
+        pb.finish_and_clear();
         
         Ok(sorted_df)
     }
@@ -742,21 +769,17 @@ impl UnifiedDataProcessor {
             let timestamp_col = if cols.contains(&"SCEDTimestamp") { "SCEDTimestamp" } else { "SCED_TIMESTAMP" };
             let timestamps = df.column(timestamp_col)?;
             let timestamps_str = timestamps.utf8()?;
+            // SCED time stamps are Central wall clock; the flag marks the repeated fall-back hour
+            let repeated_hours = ["RepeatedHourFlag", "Repeated Hour Flag"].iter()
+                .find_map(|name| df.column(name).ok())
+                .and_then(|c| c.utf8().ok());
             
             let mut datetimes = Vec::new();
             for i in 0..df.height() {
-                if let Some(ts_str) = timestamps_str.get(i) {
-                    // Try different timestamp formats
-                    if let Ok(dt) = NaiveDateTime::parse_from_str(ts_str, "%m/%d/%Y %H:%M:%S") {
-                        datetimes.push(Some(dt.and_utc().timestamp_millis()));
-                    } else if let Ok(dt) = NaiveDateTime::parse_from_str(ts_str, "%m/%d/%Y %I:%M:%S %p") {
-                        datetimes.push(Some(dt.and_utc().timestamp_millis()));
-                    } else {
-                        datetimes.push(None);
-                    }
-                } else {
-                    datetimes.push(None);
-                }
+                let datetime = timestamps_str.get(i)
+                    .and_then(parse_sced_timestamp)
+                    .and_then(|dt| central_datetime_ms(dt, is_repeated_hour(repeated_hours.and_then(|f| f.get(i)))));
+                datetimes.push(datetime);
             }
             
             let datetime_series = Series::new("datetime", datetimes);
@@ -769,6 +792,8 @@ impl UnifiedDataProcessor {
             let has_interval = cols.contains(&"DeliveryInterval");
             
             let mut datetimes = Vec::new();
+            // The repeated fall-back hour is flagged so it doesn't collide with the first
+            let dst_flags = df.column("DSTFlag").ok().and_then(|c| c.utf8().ok());
             
             if has_interval {
                 // RT data with 5-minute intervals
@@ -779,20 +804,17 @@ impl UnifiedDataProcessor {
                 let intervals_cast = intervals.cast(&DataType::Int32)?;
                 let intervals_i32 = intervals_cast.i32()?;
                 
+                let mut field_check = DeliveryFieldCheck::default();
                 for i in 0..df.height() {
                     if let (Some(date_str), Some(hour), Some(interval)) = (
                         dates_str.get(i),
                         hours_i32.get(i),
                         intervals_i32.get(i)
                     ) {
-                        if let Ok(date) = NaiveDate::parse_from_str(date_str, "%m/%d/%Y") {
-                            let hour_adj = if hour == 24 { 0 } else { hour - 1 } as u32;
-                            let minute = ((interval - 1) * 15) as u32;
-                            let mut dt = date.and_hms_opt(hour_adj, minute, 0).unwrap();
-                            if hour == 24 {
-                                dt = dt + Duration::days(1);
-                            }
-                            datetimes.push(Some(dt.and_utc().timestamp_millis()));
+                        let fields = field_check.check(hour, interval);
+                        if let (Ok(date), Some((hour, interval))) = (NaiveDate::parse_from_str(date_str, "%m/%d/%Y"), fields) {
+                            let repeated_hour = is_repeated_hour(dst_flags.and_then(|f| f.get(i)));
+                            datetimes.push(delivery_interval_datetime_ms(date, hour, interval, repeated_hour));
                         } else {
                             datetimes.push(None);
                         }
@@ -800,6 +822,7 @@ impl UnifiedDataProcessor {
                         datetimes.push(None);
                     }
                 }
+                field_check.log("DeliveryHour/DeliveryInterval");
             } else if has_hour {
                 // DAM data with hourly intervals
                 let hour_col = if cols.contains(&"HourEnding") { "HourEnding" } else { "DeliveryHour" };
@@ -813,12 +836,8 @@ impl UnifiedDataProcessor {
                         hours_i32.get(i)
                     ) {
                         if let Ok(date) = NaiveDate::parse_from_str(date_str, "%m/%d/%Y") {
-                            let hour_adj = if hour == 24 { 0 } else { hour - 1 } as u32;
-                            let mut dt = date.and_hms_opt(hour_adj, 0, 0).unwrap();
-                            if hour == 24 {
-                                dt = dt + Duration::days(1);
-                            }
-                            datetimes.push(Some(dt.and_utc().timestamp_millis()));
+                            let repeated_hour = is_repeated_hour(dst_flags.and_then(|f| f.get(i)));
+                            datetimes.push(hour_ending_datetime_ms(date, hour as u32, 0, repeated_hour));
                         } else {
                             datetimes.push(None);
                         }
@@ -831,8 +850,7 @@ impl UnifiedDataProcessor {
                 for i in 0..df.height() {
                     if let Some(date_str) = dates_str.get(i) {
                         if let Ok(date) = NaiveDate::parse_from_str(date_str, "%m/%d/%Y") {
-                            let dt = date.and_hms_opt(0, 0, 0).unwrap();
-                            datetimes.push(Some(dt.and_utc().timestamp_millis()));
+                            datetimes.push(Some(operating_day_datetime_ms(date)));
                         } else {
                             datetimes.push(None);
                         }
@@ -849,51 +867,61 @@ impl UnifiedDataProcessor {
         Ok(result_df)
     }
     
-    fn save_annual_files(&self, df: &DataFrame, output_dir: &Path, prefix: &str, year: i32) -> Result<()> {
+    fn save_annual_files(&self, df: &DataFrame, output_dir: &Path, prefix: &str, year: YearPartition) -> Result<()> {
         let base_name = format!("{}_{}", prefix, year);
         
-        println!("    💾 Saving annual files...");
-        println!("      Total records: {}", df.height());
+        debug!("Saving annual files...");
+        debug!("Total records: {}", df.height());
         
         // Save in parallel
         rayon::scope(|s| {
             // CSV
-            let csv_path = output_dir.join(format!("{}.csv", base_name));
-            let df_csv = df.clone();
-            s.spawn(move |_| {
-                if let Ok(file) = fs::File::create(&csv_path) {
-                    let mut df_mut = df_csv.clone();
-                    if CsvWriter::new(file).finish(&mut df_mut).is_ok() {
-                        println!("      ✓ Saved CSV: {}", csv_path.display());
+            if self.formats.csv {
+                let csv_path = output_dir.join(format!("{}.csv", base_name));
+                let df_csv = df.clone();
+                s.spawn(move |_| {
+                    if let Ok(file) = fs::File::create(&csv_path) {
+                        let mut df_mut = df_csv.clone();
+                        if CsvWriter::new(file).finish(&mut df_mut).is_ok() {
+                            debug!("Saved CSV: {}", csv_path.display());
+                        }
                     }
-                }
-            });
+                });
+            }
             
             // Parquet
-            let parquet_path = output_dir.join(format!("{}.parquet", base_name));
-            let df_parquet = df.clone();
-            s.spawn(move |_| {
-                if let Ok(file) = fs::File::create(&parquet_path) {
-                    let mut df_mut = df_parquet.clone();
-                    if ParquetWriter::new(file).finish(&mut df_mut).is_ok() {
-                        println!("      ✓ Saved Parquet: {}", parquet_path.display());
+            if self.formats.parquet {
+                let parquet_path = output_dir.join(format!("{}.parquet", base_name));
+                let df_parquet = df.clone();
+                s.spawn(move |_| {
+                    if let Ok(file) = fs::File::create(&parquet_path) {
+                        let mut df_mut = df_parquet.clone();
+                        if ParquetWriter::new(file).finish(&mut df_mut).is_ok() {
+                            debug!("Saved Parquet: {}", parquet_path.display());
+                        }
                     }
-                }
-            });
+                });
+            }
             
             // Arrow
-            let arrow_path = output_dir.join(format!("{}.arrow", base_name));
-            let df_arrow = df.clone();
-            s.spawn(move |_| {
-                if let Ok(file) = fs::File::create(&arrow_path) {
-                    let mut df_mut = df_arrow.clone();
-                    if IpcWriter::new(file).finish(&mut df_mut).is_ok() {
-                        println!("      ✓ Saved Arrow: {}", arrow_path.display());
+            if self.formats.arrow {
+                let arrow_path = output_dir.join(format!("{}.arrow", base_name));
+                let df_arrow = df.clone();
+                s.spawn(move |_| {
+                    if let Ok(file) = fs::File::create(&arrow_path) {
+                        let mut df_mut = df_arrow.clone();
+                        if IpcWriter::new(file).finish(&mut df_mut).is_ok() {
+                            debug!("Saved Arrow: {}", arrow_path.display());
+                        }
                     }
-                }
-            });
+                });
+            }
         });
         
+        if df.get_column_names().contains(&"datetime") {
+            datetime_format::mark_current(output_dir, &base_name)?;
+        }
+        
         Ok(())
     }
     
@@ -940,16 +968,318 @@ impl UnifiedDataProcessor {
     }
 }
 
-pub fn process_unified_data() -> Result<()> {
+pub fn process_unified_data(filter: ProcessingFilter, year_inference: YearInference, formats: OutputFormats, output_prefix: &OutputPrefix) -> Result<()> {
     // Check for environment variable override
     let base_dir = if let Ok(custom_dir) = std::env::var("ERCOT_DATA_BASE_DIR") {
-        println!("Using custom data directory: {}", custom_dir);
+        info!("Using custom data directory: {}", custom_dir);
         PathBuf::from(custom_dir)
     } else {
         PathBuf::from("/Users/enrico/data/ERCOT_data")
     };
-    let output_dir = PathBuf::from("unified_processed_data");
+    let output_dir = output_prefix.output_dir("unified_processed_data");
     
-    let processor = UnifiedDataProcessor::new(base_dir, output_dir);
+    let processor = UnifiedDataProcessor::new(base_dir, output_dir)
+        .with_filter(filter)
+        .with_year_inference(year_inference)
+        .with_formats(formats);
     processor.process_all_data()
-}
\ No newline at end of file
+}
+
+/// Year of a date or timestamp in any of ERCOT's formats
+fn parse_year(value: &str) -> Option<i32> {
+    NaiveDate::parse_from_str(value, "%m/%d/%Y").map(|d| d.year())
+        .or_else(|_| NaiveDateTime::parse_from_str(value, "%m/%d/%Y %H:%M:%S").map(|dt| dt.year()))
+        // SCED timestamp format with AM/PM
+        .or_else(|_| NaiveDateTime::parse_from_str(value, "%m/%d/%Y %I:%M:%S %p").map(|dt| dt.year()))
+        .ok()
+}
+
+/// Year of the rows in `df`, from the first of `DATE_COLUMNS` holding a
+/// parseable date; rows that don't parse are skipped rather than deciding
+pub fn content_year(df: &DataFrame, inference: YearInference) -> Option<i32> {
+    for col_name in DATE_COLUMNS {
+        let Some(values) = df.column(col_name).ok().and_then(|c| c.utf8().ok()) else {
+            continue;
+        };
+        let mut years = values.into_iter().flatten().filter_map(parse_year);
+        let year = match inference {
+            YearInference::FirstValidRow => years.next(),
+            YearInference::Majority => {
+                let mut counts: BTreeMap<i32, usize> = BTreeMap::new();
+                for year in years {
+                    *counts.entry(year).or_insert(0) += 1;
+                }
+                // Ties go to the later year
+                counts.into_iter().max_by_key(|(year, count)| (*count, *year)).map(|(year, _)| year)
+            }
+        };
+        if year.is_some() {
+            return year;
+        }
+    }
+    None
+}
+
+/// Price columns: cast to Float64 before concatenation and excluded from dedup keys
+pub const PRICE_COLUMNS: &[&str] = &[
+    "SettlementPointPrice", "LMP", "Price", "ShadowPrice",
+    "MCPCValue", "EnergyPrice", "CongestionPrice", "LossPrice",
+    "Energy", "Congestion", "Loss",
+];
+
+/// Give every dataframe the same set of columns so they can be concatenated:
+/// columns whose dtype changed between files are reconciled, missing columns
+/// are added as typed nulls, price columns are cast to Float64 and DSTFlag to Utf8
+pub fn align_columns(dfs: Vec<DataFrame>) -> Vec<DataFrame> {
+    // First, find all unique columns across all dataframes
+    let mut all_columns = HashSet::new();
+    for df in &dfs {
+        for col in df.get_column_names() {
+            all_columns.insert(col.to_string());
+        }
+    }
+    
+    let (dfs, column_types) = reconcile_column_types(dfs);
+    
+    dfs.into_iter()
+        .map(|mut df| {
+            // First add missing columns
+            for col in &all_columns {
+                if !df.get_column_names().contains(&col.as_str()) {
+                    // Add null column matching the other files' type, or guess from the name
+                    let null_series = if let Some(dtype) = column_types.get(col) {
+                        Series::full_null(col, df.height(), dtype)
+                    } else if col == "DSTFlag" {
+                        Series::new(col, vec![None::<&str>; df.height()])
+                    } else if col.contains("Price") || col.contains("LMP") || col.contains("MCPC") {
+                        Series::new(col, vec![None::<f64>; df.height()])
+                    } else if col.contains("Hour") || col.contains("Interval") {
+                        Series::new(col, vec![None::<i32>; df.height()])
+                    } else {
+                        Series::new(col, vec![None::<&str>; df.height()])
+                    };
+                    let _ = df.with_column(null_series);
+                }
+            }
+            
+            // Cast price columns to float64
+            for price_col in PRICE_COLUMNS {
+                if df.get_column_names().contains(price_col) {
+                    if let Ok(col) = df.column(price_col) {
+                        if col.dtype() != &DataType::Float64 {
+                            if let Ok(cast_col) = col.cast(&DataType::Float64) {
+                                let _ = df.with_column(cast_col);
+                            }
+                        }
+                    }
+                }
+            }
+            
+            // Also ensure DSTFlag is string
+            if df.get_column_names().contains(&"DSTFlag") {
+                if let Ok(col) = df.column("DSTFlag") {
+                    if col.dtype() != &DataType::Utf8 {
+                        if let Ok(cast_col) = col.cast(&DataType::Utf8) {
+                            let _ = df.with_column(cast_col);
+                        }
+                    }
+                }
+            }
+            
+            df
+        })
+        .collect()
+}
+
+/// Cast columns that appear with different dtypes across files (e.g. HourEnding
+/// as an integer one year and "HH:00" the next) to a common type. Returns the
+/// frames along with the resolved dtype of every column.
+fn reconcile_column_types(dfs: Vec<DataFrame>) -> (Vec<DataFrame>, HashMap<String, DataType>) {
+    let mut dtypes: HashMap<String, Vec<DataType>> = HashMap::new();
+    for df in &dfs {
+        for series in df.get_columns() {
+            if series.dtype() == &DataType::Null {
+                continue;
+            }
+            let seen = dtypes.entry(series.name().to_string()).or_default();
+            if !seen.contains(series.dtype()) {
+                seen.push(series.dtype().clone());
+            }
+        }
+    }
+    
+    let mut column_types = HashMap::new();
+    let mut targets = HashMap::new();
+    for (col, seen) in dtypes {
+        if seen.len() == 1 {
+            column_types.insert(col, seen[0].clone());
+            continue;
+        }
+        
+        let target = common_supertype(&seen);
+        info!("Reconciling column {} from {:?} to {}", col, seen, target);
+        column_types.insert(col.clone(), target.clone());
+        targets.insert(col, target);
+    }
+    
+    if targets.is_empty() {
+        return (dfs, column_types);
+    }
+    
+    let dfs = dfs.into_iter()
+        .map(|mut df| {
+            for (col, target) in &targets {
+                let converted = match df.column(col) {
+                    Ok(series) if series.dtype() != target => cast_reconciled(series, target),
+                    _ => continue,
+                };
+                match converted {
+                    Ok(series) => {
+                        let _ = df.with_column(series);
+                    }
+                    Err(e) => warn!("Failed to reconcile column {} to {}: {}", col, target, e),
+                }
+            }
+            df
+        })
+        .collect();
+    
+    (dfs, column_types)
+}
+
+/// Widest numeric type when every dtype is numeric, Utf8 otherwise
+fn common_supertype(dtypes: &[DataType]) -> DataType {
+    if dtypes.iter().all(|d| d.is_numeric()) {
+        if dtypes.iter().any(|d| d.is_float()) {
+            DataType::Float64
+        } else {
+            DataType::Int64
+        }
+    } else {
+        DataType::Utf8
+    }
+}
+
+/// Hour columns ERCOT publishes as "HH:00" strings
+const HOUR_ENDING_COLUMNS: [&str; 2] = ["HourEnding", "Hour Ending"];
+
+fn cast_reconciled(series: &Series, target: &DataType) -> PolarsResult<Series> {
+    // Integer HourEnding becomes ERCOT's "HH:00" when the other files use
+    // strings; other hour columns (DeliveryHour) stay plain numbers so they
+    // still cast back to integers
+    if target == &DataType::Utf8 && series.dtype().is_integer() && HOUR_ENDING_COLUMNS.contains(&series.name()) {
+        let hours = series.cast(&DataType::Int64)?;
+        let formatted: Utf8Chunked = hours.i64()?
+            .into_iter()
+            .map(|h| h.map(|h| format!("{:02}:00", h)))
+            .collect();
+        return Ok(formatted.into_series().with_name(series.name()));
+    }
+    
+    series.cast(target)
+}
+
+/// Deduplication key: every column except the price fields
+pub fn dedup_columns(df: &DataFrame) -> Vec<String> {
+    df.get_column_names().iter()
+        .filter(|col| !PRICE_COLUMNS.contains(*col))
+        .map(|s| s.to_string())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn concat_aligned(dfs: Vec<DataFrame>) -> DataFrame {
+        let lazy_dfs: Vec<LazyFrame> = align_columns(dfs).into_iter().map(|df| df.lazy()).collect();
+        concat(lazy_dfs.as_slice(), UnionArgs::default()).unwrap().collect().unwrap()
+    }
+
+    #[test]
+    fn test_bad_first_row_still_assigned_year_and_unknown_files_kept() {
+        let dir = tempfile::tempdir().unwrap();
+        let bad_first_row = dir.path().join("cdr.00012301.SPPHLZNP6905.csv");
+        std::fs::write(&bad_first_row, "DeliveryDate,DeliveryHour,SettlementPointPrice\n\
+                                        N/A,1,20.5\n\
+                                        03/01/2023,2,21.0\n").unwrap();
+        let no_dates = dir.path().join("cdr.00012301.SPPHLZNP6906.csv");
+        std::fs::write(&no_dates, "SettlementPoint,SettlementPointPrice\nHB_NORTH,20.5\n").unwrap();
+
+        let processor = UnifiedDataProcessor::new(dir.path().to_path_buf(), dir.path().join("out"));
+        let groups = processor.group_files_by_year(&[bad_first_row.clone(), no_dates.clone()]).unwrap();
+        assert_eq!(groups.get(&YearPartition::Year(2023)), Some(&vec![bad_first_row]));
+        assert_eq!(groups.get(&YearPartition::Unknown), Some(&vec![no_dates]));
+        assert_eq!(YearPartition::Unknown.to_string(), "unknown");
+
+        // A stray row from the previous year only wins under first-row inference
+        let df = df! { "DeliveryDate" => &["12/31/2022", "01/01/2023", "01/02/2023"] }.unwrap();
+        assert_eq!(content_year(&df, YearInference::FirstValidRow), Some(2022));
+        assert_eq!(content_year(&df, YearInference::Majority), Some(2023));
+    }
+
+    #[test]
+    fn test_hour_ending_int_and_string_years_concatenate() {
+        let int_year = df! {
+            "DeliveryDate" => &["12/31/2015", "12/31/2015"],
+            "HourEnding" => &[1_i64, 14],
+            "SettlementPointPrice" => &[20.0, 30.0],
+        }
+        .unwrap();
+        let string_year = df! {
+            "DeliveryDate" => &["01/01/2016"],
+            "HourEnding" => &["03:00"],
+            "SettlementPointPrice" => &[25.0],
+        }
+        .unwrap();
+
+        let combined = concat_aligned(vec![int_year, string_year]);
+
+        let hours: Vec<&str> = combined.column("HourEnding").unwrap().utf8().unwrap().into_no_null_iter().collect();
+        assert_eq!(hours, vec!["01:00", "14:00", "03:00"]);
+    }
+
+    #[test]
+    fn test_delivery_hour_int_and_string_years_keep_numeric_hours() {
+        let int_year = df! {
+            "DeliveryDate" => &["12/31/2015", "12/31/2015"],
+            "DeliveryHour" => &[1_i64, 14],
+            "SettlementPointPrice" => &[20.0, 30.0],
+        }
+        .unwrap();
+        let string_year = df! {
+            "DeliveryDate" => &["01/01/2016"],
+            "DeliveryHour" => &["3"],
+            "SettlementPointPrice" => &[25.0],
+        }
+        .unwrap();
+
+        let combined = concat_aligned(vec![int_year, string_year]);
+
+        let hours = combined.column("DeliveryHour").unwrap().cast(&DataType::Int64).unwrap();
+        let hours: Vec<Option<i64>> = hours.i64().unwrap().into_iter().collect();
+        assert_eq!(hours, vec![Some(1), Some(14), Some(3)]);
+    }
+
+    #[test]
+    fn test_numeric_and_missing_columns_reconcile() {
+        let a = df! {
+            "Count" => &[1_i32, 2],
+            "Code" => &[7_i64, 8],
+        }
+        .unwrap();
+        let b = df! {
+            "Count" => &[2.5],
+            "Code" => &["X"],
+            "Extra" => &[true],
+        }
+        .unwrap();
+
+        let combined = concat_aligned(vec![a, b]);
+
+        assert_eq!(combined.column("Count").unwrap().dtype(), &DataType::Float64);
+        assert_eq!(combined.column("Code").unwrap().dtype(), &DataType::Utf8);
+        assert_eq!(combined.column("Extra").unwrap().dtype(), &DataType::Boolean);
+        assert_eq!(combined.column("Extra").unwrap().null_count(), 2);
+    }
+}
@@ -0,0 +1,123 @@
+use anyhow::{Context, Result};
+use chrono::NaiveDate;
+use log::warn;
+use polars::prelude::*;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// How an interval with no RT price at its settlement point was settled
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnmatchedResolution {
+    /// Settled at the price of the point's load zone, per ERCOT's settlement point list
+    LoadZoneFallback,
+    /// Settled at the HB_HOUSTON price instead
+    HubFallback,
+    /// No hub price either; the interval earned nothing
+    Skipped,
+}
+
+impl UnmatchedResolution {
+    pub fn label(self) -> &'static str {
+        match self {
+            UnmatchedResolution::LoadZoneFallback => "load_zone",
+            UnmatchedResolution::HubFallback => "HB_HOUSTON",
+            UnmatchedResolution::Skipped => "skipped",
+        }
+    }
+}
+
+/// (resource, settlement point, date, 15-minute interval of the day, data source)
+type UnmatchedKey = (String, String, NaiveDate, u32, &'static str);
+
+/// Every (resource, settlement point, date, interval) whose dispatch found no
+/// RT price at its settlement point, for `--dump-unmatched`. Several
+/// 5-minute dispatch rows in one price interval are recorded once.
+#[derive(Debug, Default)]
+pub struct UnmatchedPrices {
+    intervals: BTreeMap<UnmatchedKey, UnmatchedResolution>,
+}
+
+impl UnmatchedPrices {
+    pub fn record(&mut self, resource: &str, settlement_point: &str, date: NaiveDate, interval: u32,
+                  source: &'static str, resolution: UnmatchedResolution) {
+        self.intervals
+            .entry((resource.to_string(), settlement_point.to_string(), date, interval, source))
+            .or_insert(resolution);
+    }
+
+    pub fn len(&self) -> usize {
+        self.intervals.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    pub fn count(&self, resolution: UnmatchedResolution) -> usize {
+        self.intervals.values().filter(|r| **r == resolution).count()
+    }
+
+    /// Warn with the totals when any interval went unmatched
+    pub fn log_summary(&self) {
+        if self.is_empty() {
+            return;
+        }
+        let points: std::collections::HashSet<&str> = self.intervals.keys().map(|(_, sp, ..)| sp.as_str()).collect();
+        warn!("{} RT intervals at {} settlement points had no price at their settlement point ({} settled at their load zone, {} at HB_HOUSTON, {} skipped)",
+              self.len(), points.len(), self.count(UnmatchedResolution::LoadZoneFallback),
+              self.count(UnmatchedResolution::HubFallback), self.count(UnmatchedResolution::Skipped));
+    }
+
+    /// Write `Resource_Name, Settlement_Point, Date, Interval, Source,
+    /// Resolution`, sorted by resource, point, date and interval. `Interval`
+    /// is the zero-based 15-minute interval of the day.
+    pub fn write_csv(&self, output_path: &Path) -> Result<()> {
+        let keys = self.intervals.keys();
+        let mut df = DataFrame::new(vec![
+            Series::new("Resource_Name", keys.clone().map(|(resource, ..)| resource.clone()).collect::<Vec<_>>()),
+            Series::new("Settlement_Point", keys.clone().map(|(_, sp, ..)| sp.clone()).collect::<Vec<_>>()),
+            Series::new("Date", keys.clone().map(|(_, _, date, ..)| date.format("%Y-%m-%d").to_string()).collect::<Vec<_>>()),
+            Series::new("Interval", keys.clone().map(|(_, _, _, interval, _)| *interval).collect::<Vec<_>>()),
+            Series::new("Source", keys.map(|(.., source)| *source).collect::<Vec<_>>()),
+            Series::new("Resolution", self.intervals.values().map(|r| r.label()).collect::<Vec<_>>()),
+        ])?;
+
+        CsvWriter::new(std::fs::File::create(output_path)
+            .with_context(|| format!("Failed to create {}", output_path.display()))?)
+            .finish(&mut df)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_unmatched_interval_is_written_once() {
+        let date = NaiveDate::from_ymd_opt(2024, 8, 1).unwrap();
+        let mut unmatched = UnmatchedPrices::default();
+        // Three 5-minute SCED rows in the same 15-minute interval
+        for _ in 0..3 {
+            unmatched.record("BATCAVE_BES1", "BATCAVE_RN", date, 72, "SCED", UnmatchedResolution::HubFallback);
+        }
+        unmatched.record("BATCAVE_BES1", "BATCAVE_RN", date, 73, "SCED", UnmatchedResolution::Skipped);
+        unmatched.record("ALPHA_BESS", "ALPHA_RN", date, 10, "SMNE", UnmatchedResolution::Skipped);
+
+        assert_eq!(unmatched.len(), 3);
+        assert_eq!(unmatched.count(UnmatchedResolution::Skipped), 2);
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("unmatched.csv");
+        unmatched.write_csv(&path).unwrap();
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = contents.lines().collect();
+        assert_eq!(lines, vec![
+            "Resource_Name,Settlement_Point,Date,Interval,Source,Resolution",
+            "ALPHA_BESS,ALPHA_RN,2024-08-01,10,SMNE,skipped",
+            "BATCAVE_BES1,BATCAVE_RN,2024-08-01,72,SCED,HB_HOUSTON",
+            "BATCAVE_BES1,BATCAVE_RN,2024-08-01,73,SCED,skipped",
+        ]);
+    }
+}
@@ -0,0 +1,221 @@
+use crate::models::{ArbitrageWindow, TbxResult};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Strategies compared by the backtest, named as in `TbxResult::best_strategy`
+pub const STRATEGIES: [&str; 3] = ["DayAhead", "RealTime", "Blended"];
+
+/// One strategy's results for one resource over the backtest period
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BacktestRow {
+    pub resource_name: String,
+    pub strategy: String,
+    pub days: usize,
+    pub total_revenue: f64,
+    pub avg_daily_revenue: f64,
+    /// Full cycles over the period: discharged MWh / battery capacity
+    pub cycles: f64,
+    /// Energy-weighted discharge minus charge price ($/MWh)
+    pub avg_spread: f64,
+    /// Days on which this strategy earned the most of the three
+    pub days_best: usize,
+    /// Highest total revenue of the resource's strategies
+    pub is_best: bool,
+}
+
+#[derive(Default)]
+struct Totals {
+    days: usize,
+    revenue: f64,
+    cycles: f64,
+    spread_x_energy: f64,
+    energy: f64,
+    days_best: usize,
+}
+
+fn strategy_windows<'a>(result: &'a TbxResult, strategy: &str) -> (&'a [ArbitrageWindow], f64) {
+    match strategy {
+        "DayAhead" => (&result.da_windows, result.revenue_da),
+        "RealTime" => (&result.rt_windows, result.revenue_rt),
+        _ => (&result.blended_windows, result.revenue_blended),
+    }
+}
+
+/// Compare DA-only, RT-only and blended revenue from daily results that
+/// carry all three. Returns one row per (resource, strategy), resources in
+/// name order and strategies in `STRATEGIES` order.
+pub fn backtest(results: &[TbxResult]) -> Vec<BacktestRow> {
+    let mut by_resource: BTreeMap<&str, [Totals; 3]> = BTreeMap::new();
+
+    for result in results {
+        let totals = by_resource.entry(result.resource_name.as_str()).or_default();
+        let best = result.best_strategy();
+
+        for (strategy, t) in STRATEGIES.iter().zip(totals.iter_mut()) {
+            let (windows, revenue) = strategy_windows(result, strategy);
+            let energy: f64 = windows.iter().map(|w| w.energy_mwh).sum();
+
+            t.days += 1;
+            t.revenue += revenue;
+            if result.config.battery_capacity_mwh > 0.0 {
+                t.cycles += energy / result.config.battery_capacity_mwh;
+            }
+            t.spread_x_energy += windows
+                .iter()
+                .map(|w| (w.discharge_price - w.charge_price) * w.energy_mwh)
+                .sum::<f64>();
+            t.energy += energy;
+            if best == *strategy {
+                t.days_best += 1;
+            }
+        }
+    }
+
+    let mut rows = Vec::new();
+    for (resource_name, totals) in by_resource {
+        let mut best_idx = 0;
+        for (idx, t) in totals.iter().enumerate() {
+            if t.revenue > totals[best_idx].revenue {
+                best_idx = idx;
+            }
+        }
+
+        for (idx, (strategy, t)) in STRATEGIES.iter().zip(totals.iter()).enumerate() {
+            rows.push(BacktestRow {
+                resource_name: resource_name.to_string(),
+                strategy: strategy.to_string(),
+                days: t.days,
+                total_revenue: t.revenue,
+                avg_daily_revenue: if t.days > 0 { t.revenue / t.days as f64 } else { 0.0 },
+                cycles: t.cycles,
+                avg_spread: if t.energy > 0.0 { t.spread_x_energy / t.energy } else { 0.0 },
+                days_best: t.days_best,
+                is_best: idx == best_idx,
+            });
+        }
+    }
+
+    rows
+}
+
+/// DA-only, RT-only and blended revenue for one resource side by side
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StrategyComparison {
+    pub resource_name: String,
+    pub revenue_da: f64,
+    pub revenue_rt: f64,
+    pub revenue_blended: f64,
+}
+
+impl StrategyComparison {
+    /// Blended revenue above the better of the single-market strategies
+    pub fn blending_uplift(&self) -> f64 {
+        self.revenue_blended - self.revenue_da.max(self.revenue_rt)
+    }
+
+    /// `blending_uplift` as a percentage of the better single-market revenue,
+    /// `None` when that revenue isn't positive
+    pub fn blending_uplift_pct(&self) -> Option<f64> {
+        let single_market = self.revenue_da.max(self.revenue_rt);
+        (single_market > 0.0).then(|| self.blending_uplift() / single_market * 100.0)
+    }
+}
+
+/// Sum each strategy's revenue per resource, resources in name order
+pub fn compare_strategies(results: &[TbxResult]) -> Vec<StrategyComparison> {
+    let mut by_resource: BTreeMap<&str, StrategyComparison> = BTreeMap::new();
+    for result in results {
+        let comparison = by_resource
+            .entry(result.resource_name.as_str())
+            .or_insert_with(|| StrategyComparison {
+                resource_name: result.resource_name.clone(),
+                revenue_da: 0.0,
+                revenue_rt: 0.0,
+                revenue_blended: 0.0,
+            });
+        comparison.revenue_da += result.revenue_da;
+        comparison.revenue_rt += result.revenue_rt;
+        comparison.revenue_blended += result.revenue_blended;
+    }
+    by_resource.into_values().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::TbxConfig;
+    use chrono::{NaiveDate, TimeZone, Utc};
+
+    fn window(charge_price: f64, discharge_price: f64, energy_mwh: f64) -> ArbitrageWindow {
+        let t = |hour| Utc.with_ymd_and_hms(2024, 1, 1, hour, 0, 0).unwrap();
+        ArbitrageWindow {
+            charge_start: t(2),
+            charge_end: t(4),
+            charge_price,
+            discharge_start: t(18),
+            discharge_end: t(20),
+            discharge_price,
+            energy_mwh,
+            revenue: (discharge_price - charge_price) * energy_mwh * 0.85,
+        }
+    }
+
+    fn day(day: u32, da: ArbitrageWindow, rt: ArbitrageWindow, blended: ArbitrageWindow) -> TbxResult {
+        let date = NaiveDate::from_ymd_opt(2024, 1, day).unwrap();
+        let mut result = TbxResult::new("BESS".into(), "NODE".into(), date, TbxConfig::new_tb2(100.0));
+        result.revenue_da = da.revenue;
+        result.revenue_rt = rt.revenue;
+        result.revenue_blended = blended.revenue;
+        result.da_windows = vec![da];
+        result.rt_windows = vec![rt];
+        result.blended_windows = vec![blended];
+        result
+    }
+
+    #[test]
+    fn test_backtest_has_a_row_per_strategy_with_one_best() {
+        let results = vec![
+            // DA wins day 1, RT wins day 2 by more
+            day(1, window(20.0, 100.0, 200.0), window(20.0, 90.0, 200.0), window(20.0, 95.0, 200.0)),
+            day(2, window(20.0, 60.0, 200.0), window(10.0, 300.0, 200.0), window(20.0, 200.0, 100.0)),
+        ];
+
+        let rows = backtest(&results);
+        assert_eq!(rows.len(), 3);
+        let strategies: Vec<&str> = rows.iter().map(|r| r.strategy.as_str()).collect();
+        assert_eq!(strategies, STRATEGIES.to_vec());
+
+        let best: Vec<&BacktestRow> = rows.iter().filter(|r| r.is_best).collect();
+        assert_eq!(best.len(), 1);
+        assert_eq!(best[0].strategy, "RealTime");
+
+        let da = &rows[0];
+        assert_eq!(da.days, 2);
+        assert_eq!(da.days_best, 1);
+        assert!((da.cycles - 2.0).abs() < 1e-9); // 200 MWh of a 200 MWh battery, twice
+        assert!((da.avg_spread - 60.0).abs() < 1e-9);
+        assert!((da.total_revenue - (80.0 + 40.0) * 200.0 * 0.85).abs() < 1e-6);
+
+        let blended = &rows[2];
+        assert!((blended.cycles - 1.5).abs() < 1e-9);
+        assert_eq!(blended.days_best, 0);
+    }
+
+    #[test]
+    fn test_compare_strategies_reports_blending_uplift() {
+        let results = vec![
+            day(1, window(20.0, 100.0, 200.0), window(20.0, 90.0, 200.0), window(20.0, 110.0, 200.0)),
+            day(2, window(20.0, 60.0, 200.0), window(20.0, 80.0, 200.0), window(20.0, 90.0, 200.0)),
+        ];
+
+        let comparisons = compare_strategies(&results);
+        assert_eq!(comparisons.len(), 1);
+        let c = &comparisons[0];
+        assert!((c.revenue_da - 120.0 * 170.0).abs() < 1e-6);
+        assert!((c.revenue_rt - 130.0 * 170.0).abs() < 1e-6);
+        assert!((c.revenue_blended - 160.0 * 170.0).abs() < 1e-6);
+        // Blended beats the better single market (RT) by 30 $/MWh of spread
+        assert!((c.blending_uplift() - 30.0 * 170.0).abs() < 1e-6);
+        assert!((c.blending_uplift_pct().unwrap() - 30.0 / 130.0 * 100.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,207 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::models::TbxConfig;
+
+/// Per-resource battery parameters. Any field left out falls back to the
+/// CLI/global configuration.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BatteryParams {
+    pub power_mw: Option<f64>,
+    /// Charge limit when it differs from `power_mw`
+    pub max_charge_mw: Option<f64>,
+    /// Discharge limit when it differs from `power_mw`
+    pub max_discharge_mw: Option<f64>,
+    pub energy_mwh: Option<f64>,
+    pub charge_eff: Option<f64>,
+    pub discharge_eff: Option<f64>,
+    pub duration: Option<u8>,
+}
+
+impl BatteryParams {
+    /// Apply these overrides on top of a base configuration
+    pub fn apply(&self, base: &TbxConfig) -> TbxConfig {
+        let mut config = base.clone();
+
+        if let Some(power_mw) = self.power_mw {
+            config.set_power_mw(power_mw);
+        }
+        if let Some(max_charge_mw) = self.max_charge_mw {
+            config.max_charge_mw = max_charge_mw;
+        }
+        if let Some(max_discharge_mw) = self.max_discharge_mw {
+            config.max_discharge_mw = max_discharge_mw;
+        }
+
+        // Duration drives how many hours are bought/sold; energy follows
+        // power x duration unless given explicitly
+        match (self.duration, self.energy_mwh) {
+            (Some(duration), Some(energy_mwh)) => {
+                config.duration_hours = duration;
+                config.battery_capacity_mwh = energy_mwh;
+            }
+            (Some(duration), None) => {
+                config.duration_hours = duration;
+                config.battery_capacity_mwh = config.battery_power_mw * duration as f64;
+            }
+            (None, Some(energy_mwh)) => {
+                config.battery_capacity_mwh = energy_mwh;
+                if config.battery_power_mw > 0.0 {
+                    config.duration_hours = (energy_mwh / config.battery_power_mw).round().max(1.0) as u8;
+                }
+            }
+            (None, None) => {
+                config.battery_capacity_mwh = config.battery_power_mw * config.duration_hours as f64;
+            }
+        }
+
+        // Round trip efficiency is the product of the one-way efficiencies
+        let one_way = base.one_way_efficiency();
+        if self.charge_eff.is_some() || self.discharge_eff.is_some() {
+            config.round_trip_efficiency =
+                self.charge_eff.unwrap_or(one_way) * self.discharge_eff.unwrap_or(one_way);
+        }
+
+        config
+    }
+}
+
+/// Battery constraints keyed by resource name, loaded from a JSON file:
+///
+/// ```json
+/// {
+///   "BATCAVE_BES1": { "power_mw": 100.0, "duration": 2 },
+///   "ANGLETON_UNIT1": { "power_mw": 40.0, "energy_mwh": 160.0, "charge_eff": 0.95, "discharge_eff": 0.92 },
+///   "GAMBIT_BESS1": { "power_mw": 100.0, "max_charge_mw": 50.0 }
+/// }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct BatteryConfigSet {
+    resources: HashMap<String, BatteryParams>,
+    /// `--max-charge-mw` / `--max-discharge-mw`, which win over the file
+    cli_max_charge_mw: Option<f64>,
+    cli_max_discharge_mw: Option<f64>,
+}
+
+impl BatteryConfigSet {
+    pub fn from_json_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read battery config {}", path.display()))?;
+        Self::from_json_str(&contents)
+            .with_context(|| format!("Failed to parse battery config {}", path.display()))
+    }
+
+    pub fn from_json_str(contents: &str) -> Result<Self> {
+        let resources: HashMap<String, BatteryParams> = serde_json::from_str(contents)?;
+        Ok(Self {
+            resources,
+            ..Self::default()
+        })
+    }
+
+    /// Keep limits set on the command line over each resource's `power_mw`,
+    /// `max_charge_mw` and `max_discharge_mw`
+    pub fn with_cli_limits(mut self, max_charge_mw: Option<f64>, max_discharge_mw: Option<f64>) -> Self {
+        self.cli_max_charge_mw = max_charge_mw;
+        self.cli_max_discharge_mw = max_discharge_mw;
+        self
+    }
+
+    pub fn get(&self, resource_name: &str) -> Option<&BatteryParams> {
+        self.resources.get(resource_name)
+    }
+
+    /// Configuration for a resource, falling back to the base config when the
+    /// resource is not listed
+    pub fn config_for(&self, resource_name: &str, base: &TbxConfig) -> TbxConfig {
+        let Some(params) = self.resources.get(resource_name) else {
+            return base.clone();
+        };
+        let mut config = params.apply(base);
+        if let Some(max_charge_mw) = self.cli_max_charge_mw {
+            config.max_charge_mw = max_charge_mw;
+        }
+        if let Some(max_discharge_mw) = self.cli_max_discharge_mw {
+            config.max_discharge_mw = max_discharge_mw;
+        }
+        config
+    }
+
+    pub fn len(&self) -> usize {
+        self.resources.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.resources.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_per_resource_overrides() {
+        let set = BatteryConfigSet::from_json_str(
+            r#"{
+                "BESS_A": { "power_mw": 50.0, "duration": 4 },
+                "BESS_B": { "energy_mwh": 300.0, "charge_eff": 0.95, "discharge_eff": 0.9 }
+            }"#,
+        )
+        .unwrap();
+        let base = TbxConfig::new_tb2(100.0);
+
+        let a = set.config_for("BESS_A", &base);
+        assert_eq!(a.battery_power_mw, 50.0);
+        assert_eq!(a.duration_hours, 4);
+        assert_eq!(a.battery_capacity_mwh, 200.0);
+        assert_eq!(a.round_trip_efficiency, base.round_trip_efficiency);
+
+        let b = set.config_for("BESS_B", &base);
+        assert_eq!(b.battery_power_mw, 100.0);
+        assert_eq!(b.battery_capacity_mwh, 300.0);
+        assert_eq!(b.duration_hours, 3);
+        assert!((b.round_trip_efficiency - 0.855).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_unlisted_resource_uses_defaults() {
+        let set = BatteryConfigSet::from_json_str(r#"{ "BESS_A": { "power_mw": 50.0 } }"#).unwrap();
+        let base = TbxConfig::new_tb1(25.0);
+
+        let config = set.config_for("OTHER", &base);
+        assert_eq!(config.battery_power_mw, 25.0);
+        assert_eq!(config.duration_hours, 1);
+    }
+
+    #[test]
+    fn test_charge_limit_overrides_power() {
+        let set = BatteryConfigSet::from_json_str(r#"{ "BESS_A": { "power_mw": 100.0, "max_charge_mw": 50.0 } }"#).unwrap();
+
+        let config = set.config_for("BESS_A", &TbxConfig::new_tb2(20.0));
+        assert_eq!(config.max_charge_mw, 50.0);
+        assert_eq!(config.max_discharge_mw, 100.0);
+        assert!(config.check_power_limits().is_ok());
+    }
+
+    #[test]
+    fn test_cli_limits_survive_file_power() {
+        let set = BatteryConfigSet::from_json_str(r#"{ "BESS_A": { "power_mw": 100.0, "max_charge_mw": 50.0 } }"#)
+            .unwrap()
+            .with_cli_limits(None, Some(30.0));
+        let mut base = TbxConfig::new_tb2(20.0);
+        base.max_discharge_mw = 30.0;
+
+        let config = set.config_for("BESS_A", &base);
+        assert_eq!(config.battery_power_mw, 100.0);
+        assert_eq!(config.max_charge_mw, 50.0);
+        assert_eq!(config.max_discharge_mw, 30.0);
+
+        let zero = BatteryConfigSet::from_json_str(r#"{ "BESS_A": { "max_charge_mw": 0.0 } }"#).unwrap();
+        let err = zero.config_for("BESS_A", &base).check_power_limits().unwrap_err();
+        assert!(err.to_string().contains("must be positive"));
+    }
+}
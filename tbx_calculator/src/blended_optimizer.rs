@@ -21,11 +21,21 @@ struct BatteryState {
 
 pub struct BlendedOptimizer {
     config: TbxConfig,
+    max_ramp_mw_per_interval: Option<f64>,
 }
 
 impl BlendedOptimizer {
     pub fn new(config: TbxConfig) -> Self {
-        Self { config }
+        Self {
+            config,
+            max_ramp_mw_per_interval: None,
+        }
+    }
+
+    /// Limit how far the power setpoint can move between consecutive intervals
+    pub fn with_ramp_limit(mut self, max_ramp_mw_per_interval: f64) -> Self {
+        self.max_ramp_mw_per_interval = Some(max_ramp_mw_per_interval);
+        self
     }
 
     /// Optimize battery dispatch across DA and RT markets
@@ -47,6 +57,21 @@ impl BlendedOptimizer {
         self.create_arbitrage_windows(dispatch_plan)
     }
 
+    /// Power setpoint for every interval (positive = discharge, negative = charge)
+    pub fn dispatch_schedule(
+        &self,
+        da_prices: &[PriceData],
+        rt_prices: &[PriceData],
+    ) -> Vec<(DateTime<Utc>, f64)> {
+        let mut intervals = self.create_intervals(da_prices, rt_prices);
+        intervals.sort_by_key(|i| i.start);
+
+        self.optimize_dispatch(&intervals)
+            .into_iter()
+            .map(|(interval, power)| (interval.start, power))
+            .collect()
+    }
+
     /// Create unified interval representation from DA and RT prices
     fn create_intervals(&self, da_prices: &[PriceData], rt_prices: &[PriceData]) -> Vec<Interval> {
         let mut intervals = Vec::new();
@@ -95,7 +120,7 @@ impl BlendedOptimizer {
                         i.market == MarketType::DayAhead
                             && i.start == hour
                     }) {
-                        da_interval.available_mw -= self.config.battery_power_mw / 4.0;
+                        da_interval.available_mw -= self.config.max_discharge_mw / 4.0;
                     }
 
                     intervals.push(Interval {
@@ -116,6 +141,7 @@ impl BlendedOptimizer {
     fn optimize_dispatch(&self, intervals: &[Interval]) -> Vec<(Interval, f64)> {
         let mut dispatch_plan = Vec::new();
         let mut battery_soc = self.config.battery_capacity_mwh * 0.5; // Start at 50% SOC
+        let mut previous_power = 0.0;
         
         // Find daily price patterns
         let daily_stats = self.calculate_daily_stats(intervals);
@@ -125,12 +151,29 @@ impl BlendedOptimizer {
             let current_stats = &daily_stats[&interval.start.date_naive()];
             
             // Decide whether to charge, discharge, or hold
-            let action = self.decide_action(
+            let mut action = self.decide_action(
                 interval,
                 battery_soc,
                 current_stats,
                 hours_remaining,
             );
+
+            // Respect ramp limit between consecutive intervals
+            if let Some(max_ramp) = self.max_ramp_mw_per_interval {
+                action = action.clamp(previous_power - max_ramp, previous_power + max_ramp);
+
+                // Ramping out of a move can't push SOC past its limits
+                let duration = self.interval_duration_hours(interval);
+                if action > 0.0 {
+                    let max_discharge = battery_soc * self.config.one_way_efficiency() / duration;
+                    action = action.min(max_discharge);
+                } else if action < 0.0 {
+                    let headroom = self.config.battery_capacity_mwh - battery_soc;
+                    let max_charge = headroom / self.config.one_way_efficiency() / duration;
+                    action = action.max(-max_charge);
+                }
+            }
+            previous_power = action;
             
             if action != 0.0 {
                 // Update SOC
@@ -145,9 +188,10 @@ impl BlendedOptimizer {
                 
                 // Ensure SOC stays within bounds
                 battery_soc = battery_soc.clamp(0.0, self.config.battery_capacity_mwh);
-                
-                dispatch_plan.push((interval.clone(), action));
             }
+
+            // Holds are kept so the schedule covers every interval
+            dispatch_plan.push((interval.clone(), action));
         }
         
         dispatch_plan
@@ -192,19 +236,19 @@ impl BlendedOptimizer {
         
         // High price and sufficient SOC -> discharge
         if interval.price > daily_stats.p90_price && soc_percent > 0.2 {
-            return interval.available_mw.min(self.config.battery_power_mw);
+            return interval.available_mw.min(self.config.max_discharge_mw);
         }
         
         // Low price and room to charge -> charge
         if interval.price < daily_stats.p10_price && soc_percent < 0.8 {
-            return -interval.available_mw.min(self.config.battery_power_mw);
+            return -interval.available_mw.min(self.config.max_charge_mw);
         }
         
         // RT spike -> prioritize discharge
         if interval.market == MarketType::RealTime15Min 
             && interval.price > daily_stats.avg_price * 1.5 
             && soc_percent > 0.1 {
-            return interval.available_mw.min(self.config.battery_power_mw);
+            return interval.available_mw.min(self.config.max_discharge_mw);
         }
         
         0.0 // Hold
@@ -311,4 +355,77 @@ struct DischargeWindow {
     end: DateTime<Utc>,
     total_energy: f64,
     total_revenue: f64,
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn daily_da_prices() -> Vec<PriceData> {
+        let hourly = [
+            20.0, 15.0, 10.0, 10.0, 12.0, 18.0, 30.0, 40.0, 35.0, 30.0, 28.0, 30.0,
+            35.0, 40.0, 50.0, 70.0, 90.0, 120.0, 150.0, 110.0, 80.0, 50.0, 35.0, 25.0,
+        ];
+        hourly
+            .iter()
+            .enumerate()
+            .map(|(hour, price)| PriceData {
+                timestamp: Utc.with_ymd_and_hms(2024, 7, 15, hour as u32, 0, 0).unwrap(),
+                settlement_point: "HB_HOUSTON".to_string(),
+                price: *price,
+                market: MarketType::DayAhead,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_ramp_limit_respected() {
+        let prices = daily_da_prices();
+        let optimizer = BlendedOptimizer::new(TbxConfig::new_tb2(100.0)).with_ramp_limit(25.0);
+
+        let schedule = optimizer.dispatch_schedule(&prices, &[]);
+        assert_eq!(schedule.len(), prices.len());
+
+        let mut previous = 0.0;
+        for (_, power) in &schedule {
+            assert!((power - previous).abs() <= 25.0 + 1e-9);
+            previous = *power;
+        }
+    }
+
+    #[test]
+    fn test_ramp_limit_does_not_increase_revenue() {
+        let prices = daily_da_prices();
+        let config = TbxConfig::new_tb2(100.0);
+
+        let unconstrained: f64 = BlendedOptimizer::new(config.clone())
+            .optimize_blended(&prices, &[])
+            .iter()
+            .map(|w| w.revenue)
+            .sum();
+        let ramped: f64 = BlendedOptimizer::new(config)
+            .with_ramp_limit(25.0)
+            .optimize_blended(&prices, &[])
+            .iter()
+            .map(|w| w.revenue)
+            .sum();
+
+        assert!(unconstrained > 0.0);
+        assert!(ramped <= unconstrained);
+    }
+
+    #[test]
+    fn test_asymmetric_power_limits_respected() {
+        let prices = daily_da_prices();
+        let mut config = TbxConfig::new_tb2(100.0);
+        config.max_charge_mw = 50.0;
+
+        let schedule = BlendedOptimizer::new(config).dispatch_schedule(&prices, &[]);
+        let deepest_charge = schedule.iter().map(|(_, power)| *power).fold(0.0, f64::min);
+        let peak_discharge = schedule.iter().map(|(_, power)| *power).fold(0.0, f64::max);
+
+        assert_eq!(deepest_charge, -50.0);
+        assert_eq!(peak_discharge, 100.0);
+    }
+}
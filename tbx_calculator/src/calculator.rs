@@ -1,7 +1,24 @@
-use crate::models::{ArbitrageWindow, MarketType, PriceData, TbxConfig, TbxResult};
+use crate::models::{
+    ArbitrageWindow, MarketType, PriceData, ScheduleAction, ScheduleInterval, TbxConfig, TbxResult,
+};
+use anyhow::Result;
 use chrono::{DateTime, Datelike, Duration, NaiveDate, Timelike, Utc};
 use std::collections::HashMap;
 
+/// One interval's start time and price
+type PricePoint = (DateTime<Utc>, f64);
+
+/// Energy left over below this counts as a finished leg
+const ENERGY_TOLERANCE_MWH: f64 = 1e-9;
+
+/// One interval of a charge or discharge leg and the power held in it
+#[derive(Debug, Clone, Copy)]
+struct LegInterval {
+    timestamp: DateTime<Utc>,
+    price: f64,
+    power_mw: f64,
+}
+
 pub struct TbxCalculator {
     config: TbxConfig,
 }
@@ -11,6 +28,12 @@ impl TbxCalculator {
         Self { config }
     }
 
+    /// Like `new`, but fails when the charge or discharge limit isn't positive
+    pub fn try_new(config: TbxConfig) -> Result<Self> {
+        config.check_power_limits()?;
+        Ok(Self::new(config))
+    }
+
     /// Calculate arbitrage opportunities for a single day
     pub fn calculate_daily_arbitrage(
         &self,
@@ -73,46 +96,25 @@ impl TbxCalculator {
         let daily_groups = self.group_by_day(&interval_prices);
 
         for (_date, day_prices) in daily_groups {
-            if day_prices.len() < 2 {
+            let Some((charge_intervals, discharge_intervals)) = self.select_tbx_intervals(&day_prices, market_type) else {
                 continue;
-            }
-
-            // Sort prices to find cheapest and most expensive periods
-            let mut sorted_prices = day_prices.clone();
-            sorted_prices.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
-
-            let num_intervals = self.config.duration_hours as usize * self.intervals_per_hour(market_type);
-            
-            if sorted_prices.len() < num_intervals * 2 {
-                continue; // Not enough intervals for a full cycle
-            }
-
-            // Get bottom X intervals (for charging)
-            let charge_intervals = &sorted_prices[..num_intervals];
-            let avg_charge_price: f64 = charge_intervals.iter().map(|(_, p)| p).sum::<f64>() / num_intervals as f64;
-
-            // Get top X intervals (for discharging)
-            let discharge_intervals = &sorted_prices[sorted_prices.len() - num_intervals..];
-            let avg_discharge_price: f64 = discharge_intervals.iter().map(|(_, p)| p).sum::<f64>() / num_intervals as f64;
-
-            // Check if spread meets threshold
+            };
+            let avg_charge_price = average_price(&charge_intervals);
+            let avg_discharge_price = average_price(&discharge_intervals);
             let spread = avg_discharge_price - avg_charge_price;
-            if spread < self.config.min_spread_threshold {
-                continue;
-            }
 
             // Calculate revenue considering efficiency
             let one_way_efficiency = self.config.one_way_efficiency();
-            let energy_per_interval = self.config.battery_power_mw / self.intervals_per_hour(market_type) as f64;
-            let total_energy = energy_per_interval * num_intervals as f64;
+            let total_energy = self.config.cycle_energy_mwh();
             
             let revenue = total_energy * spread * self.config.round_trip_efficiency;
 
             // Create arbitrage window
-            let charge_start = charge_intervals[0].0;
-            let charge_end = self.add_duration(charge_intervals.last().unwrap().0, market_type);
-            let discharge_start = discharge_intervals[0].0;
-            let discharge_end = self.add_duration(discharge_intervals.last().unwrap().0, market_type);
+            // Intervals are sorted by price, so take the time bounds explicitly
+            let charge_start = charge_intervals.iter().map(|i| i.timestamp).min().unwrap();
+            let charge_end = self.add_duration(charge_intervals.iter().map(|i| i.timestamp).max().unwrap(), market_type);
+            let discharge_start = discharge_intervals.iter().map(|i| i.timestamp).min().unwrap();
+            let discharge_end = self.add_duration(discharge_intervals.iter().map(|i| i.timestamp).max().unwrap(), market_type);
 
             windows.push(ArbitrageWindow {
                 charge_start,
@@ -129,6 +131,84 @@ impl TbxCalculator {
         windows
     }
 
+    /// Cheapest intervals (charge) and priciest intervals (discharge) of one
+    /// day's prices that move the cycle energy, each leg at its own power
+    /// limit, or `None` if the day is too short for a full cycle or the
+    /// spread between them is below the threshold. With both limits at the
+    /// rated power these are the bottom X and top X intervals.
+    fn select_tbx_intervals(
+        &self,
+        day_prices: &[PricePoint],
+        market_type: MarketType,
+    ) -> Option<(Vec<LegInterval>, Vec<LegInterval>)> {
+        if day_prices.len() < 2 {
+            return None;
+        }
+
+        // Sort prices to find cheapest and most expensive periods
+        let mut sorted_prices = day_prices.to_vec();
+        sorted_prices.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
+
+        let energy_mwh = self.config.cycle_energy_mwh();
+        let intervals_per_hour = self.intervals_per_hour(market_type);
+        let charge_intervals = leg(sorted_prices.iter(), energy_mwh, self.config.max_charge_mw, intervals_per_hour)?;
+        let discharge_intervals =
+            leg(sorted_prices.iter().rev(), energy_mwh, self.config.max_discharge_mw, intervals_per_hour)?;
+        if charge_intervals.len() + discharge_intervals.len() > sorted_prices.len() {
+            return None; // Not enough intervals for a full cycle
+        }
+
+        // Check if spread meets threshold
+        let spread = average_price(&discharge_intervals) - average_price(&charge_intervals);
+        if spread < self.config.min_spread_threshold {
+            return None;
+        }
+
+        Some((charge_intervals, discharge_intervals))
+    }
+
+    /// Per-interval TBX dispatch for one day in `market_type` (DA hourly or
+    /// RT): the cheapest intervals charge at the charge limit and the
+    /// priciest discharge at the discharge limit when the spread clears the
+    /// threshold; every other interval idles
+    pub fn daily_schedule(&self, prices: &[PriceData], market_type: MarketType) -> Vec<ScheduleInterval> {
+        let mut interval_prices: Vec<PricePoint> = prices
+            .iter()
+            .filter(|p| match market_type {
+                MarketType::DayAhead => p.market == MarketType::DayAhead,
+                MarketType::RealTime5Min | MarketType::RealTime15Min => {
+                    matches!(p.market, MarketType::RealTime5Min | MarketType::RealTime15Min)
+                }
+            })
+            .map(|p| (p.timestamp, p.price))
+            .collect();
+        interval_prices.sort_by_key(|(t, _)| *t);
+
+        let (charge, discharge) = self
+            .select_tbx_intervals(&interval_prices, market_type)
+            .unwrap_or_default();
+
+        interval_prices
+            .into_iter()
+            .map(|(timestamp, price)| {
+                let (action, power_mw) = if let Some(i) = charge.iter().find(|i| i.timestamp == timestamp) {
+                    (ScheduleAction::Charge, -i.power_mw)
+                } else if let Some(i) = discharge.iter().find(|i| i.timestamp == timestamp) {
+                    (ScheduleAction::Discharge, i.power_mw)
+                } else {
+                    (ScheduleAction::Idle, 0.0)
+                };
+                ScheduleInterval {
+                    timestamp,
+                    market: market_type,
+                    action,
+                    power_mw,
+                    price,
+                }
+            })
+            .collect()
+    }
+
     /// Group prices by appropriate interval based on market type
     fn group_prices_by_interval(
         &self,
@@ -207,7 +287,7 @@ impl TbxCalculator {
             .chain(result.da_windows.iter())
             .chain(result.rt_windows.iter())
             .map(|w| w.energy_mwh)
-            .fold(0.0, |a, b| a.max(b));
+            .fold(0.0_f64, |a, b| a.max(b));
 
         if max_daily_energy > 0.0 {
             actual_energy / max_daily_energy
@@ -217,6 +297,37 @@ impl TbxCalculator {
     }
 }
 
+/// Take intervals from `prices` in order, each at `limit_mw` (the last one
+/// only as long as needed), until `energy_mwh` has moved; `None` if the
+/// prices run out first or there is nothing to move
+fn leg<'a>(
+    prices: impl Iterator<Item = &'a PricePoint>,
+    energy_mwh: f64,
+    limit_mw: f64,
+    intervals_per_hour: usize,
+) -> Option<Vec<LegInterval>> {
+    if limit_mw.is_nan() || limit_mw <= 0.0 {
+        return None;
+    }
+    let mut remaining_mwh = energy_mwh;
+    let mut intervals = Vec::new();
+    for &(timestamp, price) in prices {
+        if remaining_mwh <= ENERGY_TOLERANCE_MWH {
+            break;
+        }
+        let power_mw = limit_mw.min(remaining_mwh * intervals_per_hour as f64);
+        remaining_mwh -= power_mw / intervals_per_hour as f64;
+        intervals.push(LegInterval { timestamp, price, power_mw });
+    }
+    (remaining_mwh <= ENERGY_TOLERANCE_MWH && !intervals.is_empty()).then_some(intervals)
+}
+
+/// Energy-weighted average price of a leg
+fn average_price(intervals: &[LegInterval]) -> f64 {
+    let power_mw: f64 = intervals.iter().map(|i| i.power_mw).sum();
+    intervals.iter().map(|i| i.price * i.power_mw).sum::<f64>() / power_mw
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -260,5 +371,149 @@ mod tests {
         assert!(result.revenue_da > 0.0);
         assert!(!result.da_windows.is_empty());
         assert!(result.avg_spread_da > 50.0); // Should find the 100-20 spread
+        result.validate().unwrap();
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_calculator_windows_satisfy_invariants() {
+        let base_time = DateTime::parse_from_rfc3339("2024-03-01T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+
+        // Two days of DA hourly and RT 15-minute prices with a daily shape
+        let mut prices = vec![];
+        for hour in 0..48 {
+            let hour_of_day = hour % 24;
+            let da_price = 25.0 + 60.0 * ((hour_of_day as f64 - 6.0) / 24.0 * std::f64::consts::PI).sin().max(0.0);
+            prices.push(PriceData {
+                timestamp: base_time + Duration::hours(hour),
+                settlement_point: "TEST_NODE".to_string(),
+                price: da_price,
+                market: MarketType::DayAhead,
+            });
+            for quarter in 0..4 {
+                prices.push(PriceData {
+                    timestamp: base_time + Duration::hours(hour) + Duration::minutes(15 * quarter),
+                    settlement_point: "TEST_NODE".to_string(),
+                    price: da_price + (quarter as f64 - 1.5) * 4.0,
+                    market: MarketType::RealTime15Min,
+                });
+            }
+        }
+
+        for config in [TbxConfig::new_tb1(50.0), TbxConfig::new_tb2(100.0), TbxConfig::new_tb4(25.0)] {
+            let calculator = TbxCalculator::new(config);
+            for day in 0..2 {
+                let date = (base_time + Duration::days(day)).date_naive();
+                let day_prices: Vec<_> = prices
+                    .iter()
+                    .filter(|p| p.timestamp.date_naive() == date)
+                    .cloned()
+                    .collect();
+
+                let result = calculator.calculate_daily_arbitrage(&day_prices, "TEST_BATTERY", "TEST_NODE", date);
+                assert!(!result.da_windows.is_empty());
+                assert!(!result.rt_windows.is_empty());
+                result.validate().unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn test_daily_schedule_has_an_action_and_price_per_interval() {
+        let base_time = DateTime::parse_from_rfc3339("2024-01-01T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+
+        let mut prices = vec![];
+        for hour in 0..24 {
+            let da_price = if hour < 2 { 10.0 } else if (18..20).contains(&hour) { 120.0 } else { 40.0 };
+            prices.push(PriceData {
+                timestamp: base_time + Duration::hours(hour),
+                settlement_point: "TEST_NODE".to_string(),
+                price: da_price,
+                market: MarketType::DayAhead,
+            });
+            for quarter in 0..4 {
+                prices.push(PriceData {
+                    timestamp: base_time + Duration::hours(hour) + Duration::minutes(15 * quarter),
+                    settlement_point: "TEST_NODE".to_string(),
+                    price: da_price + quarter as f64,
+                    market: MarketType::RealTime15Min,
+                });
+            }
+        }
+
+        let calculator = TbxCalculator::new(TbxConfig::new_tb2(100.0));
+
+        let da = calculator.daily_schedule(&prices, MarketType::DayAhead);
+        assert_eq!(da.len(), 24);
+        let hours_doing = |schedule: &[ScheduleInterval], action| -> Vec<u32> {
+            schedule.iter().filter(|s| s.action == action).map(|s| s.timestamp.hour()).collect()
+        };
+        assert_eq!(hours_doing(&da, ScheduleAction::Charge), vec![0, 1]);
+        assert_eq!(hours_doing(&da, ScheduleAction::Discharge), vec![18, 19]);
+        assert_eq!(da[0].power_mw, -100.0);
+        assert_eq!(da[18].power_mw, 100.0);
+        assert_eq!(da[18].price, 120.0);
+        assert!(da.iter().filter(|s| s.action == ScheduleAction::Idle).all(|s| s.power_mw == 0.0));
+
+        let rt = calculator.daily_schedule(&prices, MarketType::RealTime15Min);
+        assert_eq!(rt.len(), 96);
+        assert_eq!(rt.iter().filter(|s| s.action == ScheduleAction::Charge).count(), 8);
+        assert_eq!(rt.iter().filter(|s| s.action == ScheduleAction::Discharge).count(), 8);
+
+        // A flat day has no spread to capture
+        let flat: Vec<_> = prices.iter().cloned().map(|p| PriceData { price: 40.0, ..p }).collect();
+        assert!(calculator
+            .daily_schedule(&flat, MarketType::DayAhead)
+            .iter()
+            .all(|s| s.action == ScheduleAction::Idle));
+    }
+
+    #[test]
+    fn test_each_leg_runs_at_its_own_limit() {
+        let base_time = DateTime::parse_from_rfc3339("2024-01-01T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let prices: Vec<PriceData> = (0..24)
+            .map(|hour| PriceData {
+                timestamp: base_time + Duration::hours(hour),
+                settlement_point: "TEST_NODE".to_string(),
+                price: if hour < 4 { 10.0 + hour as f64 } else if hour >= 20 { 100.0 } else { 40.0 },
+                market: MarketType::DayAhead,
+            })
+            .collect();
+
+        // 100 MW TB2 that can only charge at 50 MW and discharge at 80 MW:
+        // 200 MWh takes four charge hours and two and a half discharge hours
+        let mut config = TbxConfig::new_tb2(100.0);
+        config.max_charge_mw = 50.0;
+        config.max_discharge_mw = 80.0;
+        let calculator = TbxCalculator::new(config);
+
+        let schedule = calculator.daily_schedule(&prices, MarketType::DayAhead);
+        let charged: Vec<f64> = schedule.iter().filter(|s| s.action == ScheduleAction::Charge).map(|s| s.power_mw).collect();
+        let discharged: Vec<f64> =
+            schedule.iter().filter(|s| s.action == ScheduleAction::Discharge).map(|s| s.power_mw).collect();
+        assert_eq!(charged, vec![-50.0; 4]);
+        assert_eq!(discharged.iter().filter(|&&mw| mw == 80.0).count(), 2);
+        assert!(discharged.contains(&40.0));
+        assert_eq!(discharged.len(), 3);
+
+        let result = calculator.calculate_daily_arbitrage(&prices, "TEST_BATTERY", "TEST_NODE", base_time.date_naive());
+        let window = &result.da_windows[0];
+        assert_eq!(window.energy_mwh, 200.0);
+        assert_eq!(window.charge_price, 11.5);
+        assert_eq!(window.discharge_price, 100.0);
+        result.validate().unwrap();
+
+        let mut stalled = TbxConfig::new_tb2(100.0);
+        stalled.max_charge_mw = 0.0;
+        assert!(TbxCalculator::try_new(stalled.clone()).is_err());
+        assert!(TbxCalculator::new(stalled)
+            .calculate_daily_arbitrage(&prices, "TEST_BATTERY", "TEST_NODE", base_time.date_naive())
+            .da_windows
+            .is_empty());
+    }
+}
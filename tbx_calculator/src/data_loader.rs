@@ -1,10 +1,8 @@
 use crate::models::{MarketType, PriceData};
 use anyhow::Result;
-use arrow::array::{Float64Array, StringArray, TimestampMicrosecondArray};
-use arrow::datatypes::TimeUnit;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Timelike, Utc};
+use polars::export::arrow::array::{PrimitiveArray, Utf8Array};
 use polars::prelude::*;
-use std::sync::Arc;
 
 pub struct DataLoader {
     use_arrow: bool,
@@ -36,7 +34,7 @@ impl DataLoader {
     /// Load DA prices using Polars
     fn load_da_prices_polars(&self, file_path: &str, settlement_points: &[String]) -> Result<Vec<PriceData>> {
         let df = LazyFrame::scan_parquet(file_path, Default::default())?
-            .filter(col("SettlementPoint").is_in(lit(Series::from_iter(settlement_points))))
+            .filter(col("SettlementPoint").is_in(lit(Series::new("points".into(), settlement_points))))
             .collect()?;
 
         let mut prices = Vec::new();
@@ -75,7 +73,7 @@ impl DataLoader {
     /// Load RT prices using Polars
     fn load_rt_prices_polars(&self, file_path: &str, settlement_points: &[String]) -> Result<Vec<PriceData>> {
         let df = LazyFrame::scan_parquet(file_path, Default::default())?
-            .filter(col("SettlementPointName").is_in(lit(Series::from_iter(settlement_points))))
+            .filter(col("SettlementPointName").is_in(lit(Series::new("points".into(), settlement_points))))
             .collect()?;
 
         let mut prices = Vec::new();
@@ -105,58 +103,66 @@ impl DataLoader {
         Ok(prices)
     }
 
-    /// Load DA prices using Arrow (for performance comparison)
+    /// Load DA prices by walking the Arrow record batches of the file
+    /// directly (for performance comparison)
     fn load_da_prices_arrow(&self, file_path: &str, settlement_points: &[String]) -> Result<Vec<PriceData>> {
-        use arrow::record_batch::RecordBatchReader;
-        use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
-        use std::fs::File;
-
-        let file = File::open(file_path)?;
-        let builder = ParquetRecordBatchReaderBuilder::try_new(file)?;
-        let mut reader = builder.build()?;
+        let file = std::fs::File::open(file_path)?;
+        let df = ParquetReader::new(file).finish()?;
 
         let mut prices = Vec::new();
-        let settlement_set: std::collections::HashSet<_> = settlement_points.iter().collect();
+        let settlement_set: std::collections::HashSet<&str> =
+            settlement_points.iter().map(String::as_str).collect();
+
+        let column_index = |name: &str| {
+            df.get_column_index(name)
+                .ok_or_else(|| anyhow::anyhow!("{} column not found", name))
+        };
+        let points_idx = column_index("SettlementPoint")?;
+        let dates_idx = column_index("DeliveryDate")?;
+        let hours_idx = column_index("DeliveryHour")?;
+        let values_idx = column_index("SettlementPointPrice")?;
+
+        for batch in df.iter_chunks(CompatLevel::oldest(), false) {
+            let columns = batch.columns();
 
-        for batch in reader {
-            let batch = batch?;
-            
             // Get column arrays
-            let points = batch
-                .column_by_name("SettlementPoint")
-                .and_then(|c| c.as_any().downcast_ref::<StringArray>())
-                .ok_or_else(|| anyhow::anyhow!("SettlementPoint column not found"))?;
-            
-            let dates = batch
-                .column_by_name("DeliveryDate")
-                .and_then(|c| c.as_any().downcast_ref::<TimestampMicrosecondArray>())
-                .ok_or_else(|| anyhow::anyhow!("DeliveryDate column not found"))?;
-            
-            let hours = batch
-                .column_by_name("DeliveryHour")
-                .and_then(|c| c.as_any().downcast_ref::<arrow::array::Int32Array>())
-                .ok_or_else(|| anyhow::anyhow!("DeliveryHour column not found"))?;
-            
-            let values = batch
-                .column_by_name("SettlementPointPrice")
-                .and_then(|c| c.as_any().downcast_ref::<Float64Array>())
-                .ok_or_else(|| anyhow::anyhow!("SettlementPointPrice column not found"))?;
-
-            for row in 0..batch.num_rows() {
-                if let Some(point) = points.value(row) {
-                    if settlement_set.contains(&point.to_string()) {
+            let points = columns[points_idx]
+                .as_any()
+                .downcast_ref::<Utf8Array<i64>>()
+                .ok_or_else(|| anyhow::anyhow!("SettlementPoint is not a string column"))?;
+
+            let dates = columns[dates_idx]
+                .as_any()
+                .downcast_ref::<PrimitiveArray<i64>>()
+                .ok_or_else(|| anyhow::anyhow!("DeliveryDate is not a timestamp column"))?;
+
+            let hours = columns[hours_idx]
+                .as_any()
+                .downcast_ref::<PrimitiveArray<i32>>()
+                .ok_or_else(|| anyhow::anyhow!("DeliveryHour is not an Int32 column"))?;
+
+            let values = columns[values_idx]
+                .as_any()
+                .downcast_ref::<PrimitiveArray<f64>>()
+                .ok_or_else(|| anyhow::anyhow!("SettlementPointPrice is not a Float64 column"))?;
+
+            for row in 0..batch.len() {
+                if let (Some(point), Some(date), Some(hour), Some(price)) =
+                    (points.get(row), dates.get(row), hours.get(row), values.get(row))
+                {
+                    if settlement_set.contains(point) {
                         let timestamp = DateTime::<Utc>::from_timestamp(
-                            dates.value(row) / 1_000_000, // Convert microseconds to seconds
+                            date / 1_000_000, // Convert microseconds to seconds
                             0,
                         )
                         .unwrap()
-                        .with_hour(hours.value(row) as u32)
+                        .with_hour(hour as u32)
                         .unwrap();
 
                         prices.push(PriceData {
                             timestamp,
                             settlement_point: point.to_string(),
-                            price: values.value(row),
+                            price,
                             market: MarketType::DayAhead,
                         });
                     }
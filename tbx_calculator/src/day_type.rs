@@ -0,0 +1,110 @@
+use crate::holidays::is_nerc_holiday;
+use crate::models::TbxResult;
+use chrono::{Datelike, NaiveDate, Weekday};
+use serde::{Deserialize, Serialize};
+
+/// Calendar bucket for a day's arbitrage value. Every date falls in exactly
+/// one: NERC holidays count as holidays even on a weekend.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum DayType {
+    Weekday,
+    Weekend,
+    Holiday,
+}
+
+impl DayType {
+    pub const ALL: [DayType; 3] = [DayType::Weekday, DayType::Weekend, DayType::Holiday];
+
+    pub fn of(date: NaiveDate) -> Self {
+        if is_nerc_holiday(date) {
+            DayType::Holiday
+        } else if matches!(date.weekday(), Weekday::Sat | Weekday::Sun) {
+            DayType::Weekend
+        } else {
+            DayType::Weekday
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            DayType::Weekday => "Weekday",
+            DayType::Weekend => "Weekend",
+            DayType::Holiday => "Holiday",
+        }
+    }
+}
+
+/// Best-strategy revenue of the resource-days in one day type
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DayTypeRow {
+    pub day_type: DayType,
+    /// Distinct dates in the bucket
+    pub days: usize,
+    /// Resource-days in the bucket
+    pub resource_days: usize,
+    pub total_revenue: f64,
+    /// Mean of each resource-day's revenue per MW of battery power
+    pub avg_revenue_per_mw_day: f64,
+}
+
+/// Split results into weekday, weekend and holiday rows, in that order
+pub fn day_type_breakdown(results: &[TbxResult]) -> Vec<DayTypeRow> {
+    DayType::ALL
+        .iter()
+        .map(|&day_type| {
+            let bucket: Vec<&TbxResult> = results.iter().filter(|r| DayType::of(r.date) == day_type).collect();
+            let mut dates: Vec<NaiveDate> = bucket.iter().map(|r| r.date).collect();
+            dates.sort();
+            dates.dedup();
+
+            let per_mw: Vec<f64> = bucket
+                .iter()
+                .filter(|r| r.config.battery_power_mw > 0.0)
+                .map(|r| r.best_revenue() / r.config.battery_power_mw)
+                .collect();
+            let avg_revenue_per_mw_day = if per_mw.is_empty() {
+                0.0
+            } else {
+                per_mw.iter().sum::<f64>() / per_mw.len() as f64
+            };
+
+            DayTypeRow {
+                day_type,
+                days: dates.len(),
+                resource_days: bucket.len(),
+                total_revenue: bucket.iter().map(|r| r.best_revenue()).sum(),
+                avg_revenue_per_mw_day,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::TbxConfig;
+
+    #[test]
+    fn test_day_types_partition_the_days() {
+        // Mon 2024-07-01 .. Sun 2024-07-07; Thursday the 4th is Independence Day
+        let results: Vec<TbxResult> = (1..=7)
+            .map(|day| {
+                let date = NaiveDate::from_ymd_opt(2024, 7, day).unwrap();
+                let mut result = TbxResult::new("BESS".into(), "NODE".into(), date, TbxConfig::new_tb2(100.0));
+                result.revenue_da = 1000.0 * day as f64;
+                result
+            })
+            .collect();
+
+        let rows = day_type_breakdown(&results);
+        let days: Vec<(DayType, usize)> = rows.iter().map(|r| (r.day_type, r.days)).collect();
+        assert_eq!(days, vec![(DayType::Weekday, 4), (DayType::Weekend, 2), (DayType::Holiday, 1)]);
+        assert_eq!(rows.iter().map(|r| r.resource_days).sum::<usize>(), results.len());
+
+        let holiday = &rows[2];
+        assert_eq!(holiday.total_revenue, 4000.0);
+        assert!((holiday.avg_revenue_per_mw_day - 40.0).abs() < 1e-9);
+        // Weekend: (6000 + 7000) / 2 / 100 MW
+        assert!((rows[1].avg_revenue_per_mw_day - 65.0).abs() < 1e-9);
+    }
+}
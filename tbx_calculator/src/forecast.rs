@@ -0,0 +1,304 @@
+use anyhow::Result;
+use chrono::{Datelike, Duration, NaiveDate, TimeZone, Timelike, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::calculator::TbxCalculator;
+use crate::models::{MarketType, PriceData, TbxConfig};
+
+/// Historical hourly price distributions by hour-of-day (and month, when available)
+#[derive(Debug, Clone, Default)]
+pub struct HourlyPriceProfile {
+    by_month_hour: HashMap<(u32, u32), Vec<f64>>,
+    by_hour: HashMap<u32, Vec<f64>>,
+}
+
+impl HourlyPriceProfile {
+    /// Build the profile from historical prices. Sub-hourly prices are averaged
+    /// to hourly values first so every observation is one hour.
+    pub fn from_prices(prices: &[PriceData]) -> Self {
+        let mut hourly: HashMap<(NaiveDate, u32), (f64, usize)> = HashMap::new();
+        for price in prices {
+            let key = (price.timestamp.date_naive(), price.timestamp.hour());
+            let entry = hourly.entry(key).or_insert((0.0, 0));
+            entry.0 += price.price;
+            entry.1 += 1;
+        }
+
+        let mut profile = Self::default();
+        for ((date, hour), (sum, count)) in hourly {
+            let avg = sum / count as f64;
+            profile.by_month_hour.entry((date.month(), hour)).or_default().push(avg);
+            profile.by_hour.entry(hour).or_default().push(avg);
+        }
+
+        profile
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.by_hour.is_empty()
+    }
+
+    /// Draw a price for the given month and hour, preferring same-month history
+    fn sample(&self, month: u32, hour: u32, rng: &mut SimpleRng) -> Option<f64> {
+        let observations = self
+            .by_month_hour
+            .get(&(month, hour))
+            .filter(|v| !v.is_empty())
+            .or_else(|| self.by_hour.get(&hour).filter(|v| !v.is_empty()))?;
+
+        Some(observations[rng.next_index(observations.len())])
+    }
+}
+
+/// Distribution of total TBX revenue over a forecast period
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RevenueForecast {
+    pub resource_name: String,
+    pub settlement_point: String,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    pub config: TbxConfig,
+    pub num_samples: usize,
+    pub mean: f64,
+    pub p10: f64,
+    pub p50: f64,
+    pub p90: f64,
+    pub samples: Vec<f64>,
+}
+
+pub struct RevenueForecaster {
+    config: TbxConfig,
+    num_samples: usize,
+    seed: u64,
+}
+
+impl RevenueForecaster {
+    pub fn new(config: TbxConfig) -> Self {
+        Self {
+            config,
+            num_samples: 500,
+            seed: 42,
+        }
+    }
+
+    pub fn with_samples(mut self, num_samples: usize) -> Self {
+        self.num_samples = num_samples.max(1);
+        self
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Monte Carlo forecast: each sample builds synthetic hourly price days for
+    /// the target period from the profile and sums the DA TBX revenue
+    pub fn forecast(
+        &self,
+        profile: &HourlyPriceProfile,
+        resource_name: &str,
+        settlement_point: &str,
+        start_date: NaiveDate,
+        end_date: NaiveDate,
+    ) -> Result<RevenueForecast> {
+        if profile.is_empty() {
+            anyhow::bail!("No historical prices available to forecast {}", resource_name);
+        }
+        if end_date < start_date {
+            anyhow::bail!("Forecast end date {} is before start date {}", end_date, start_date);
+        }
+
+        let calculator = TbxCalculator::try_new(self.config.clone())?;
+        let mut rng = SimpleRng::new(self.seed);
+        let mut samples = Vec::with_capacity(self.num_samples);
+
+        for _ in 0..self.num_samples {
+            let mut total_revenue = 0.0;
+            let mut current_date = start_date;
+
+            while current_date <= end_date {
+                let day_prices = self.sample_day(profile, settlement_point, current_date, &mut rng);
+                let result = calculator.calculate_daily_arbitrage(
+                    &day_prices,
+                    resource_name,
+                    settlement_point,
+                    current_date,
+                );
+                total_revenue += result.revenue_da;
+
+                current_date += Duration::days(1);
+            }
+
+            samples.push(total_revenue);
+        }
+
+        let mut sorted = samples.clone();
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+        Ok(RevenueForecast {
+            resource_name: resource_name.to_string(),
+            settlement_point: settlement_point.to_string(),
+            start_date,
+            end_date,
+            config: self.config.clone(),
+            num_samples: samples.len(),
+            mean: samples.iter().sum::<f64>() / samples.len() as f64,
+            p10: percentile(&sorted, 0.10),
+            p50: percentile(&sorted, 0.50),
+            p90: percentile(&sorted, 0.90),
+            samples,
+        })
+    }
+
+    fn sample_day(
+        &self,
+        profile: &HourlyPriceProfile,
+        settlement_point: &str,
+        date: NaiveDate,
+        rng: &mut SimpleRng,
+    ) -> Vec<PriceData> {
+        (0..24)
+            .filter_map(|hour| {
+                let price = profile.sample(date.month(), hour, rng)?;
+                let timestamp = Utc.from_utc_datetime(&date.and_hms_opt(hour, 0, 0).unwrap());
+                Some(PriceData {
+                    timestamp,
+                    settlement_point: settlement_point.to_string(),
+                    price,
+                    market: MarketType::DayAhead,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Nearest-rank percentile of an ascending-sorted slice
+fn percentile(sorted: &[f64], p: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let idx = ((sorted.len() - 1) as f64 * p).round() as usize;
+    sorted[idx.min(sorted.len() - 1)]
+}
+
+/// Small deterministic xorshift generator so forecasts are reproducible
+struct SimpleRng(u64);
+
+impl SimpleRng {
+    fn new(seed: u64) -> Self {
+        Self(seed.max(1))
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut x = self.0;
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.0 = x;
+        x
+    }
+
+    fn next_index(&mut self, len: usize) -> usize {
+        (self.next_u64() % len as u64) as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Synthetic DA history: evening peak shape with deterministic day-to-day noise
+    fn synthetic_history(start: NaiveDate, days: i64) -> Vec<PriceData> {
+        let shape = [
+            22.0, 20.0, 18.0, 17.0, 18.0, 22.0, 28.0, 32.0, 30.0, 29.0, 30.0, 32.0,
+            35.0, 40.0, 48.0, 60.0, 80.0, 110.0, 130.0, 95.0, 65.0, 45.0, 32.0, 26.0,
+        ];
+        let mut rng = SimpleRng::new(7);
+        let mut prices = Vec::new();
+
+        for day in 0..days {
+            let date = start + Duration::days(day);
+            for (hour, base) in shape.iter().enumerate() {
+                let noise = (rng.next_u64() % 2000) as f64 / 100.0 - 10.0;
+                prices.push(PriceData {
+                    timestamp: Utc.from_utc_datetime(&date.and_hms_opt(hour as u32, 0, 0).unwrap()),
+                    settlement_point: "HB_WEST".to_string(),
+                    price: base + noise,
+                    market: MarketType::DayAhead,
+                });
+            }
+        }
+
+        prices
+    }
+
+    #[test]
+    fn test_forecast_bands_are_ordered() {
+        let start = NaiveDate::from_ymd_opt(2024, 6, 1).unwrap();
+        let history = synthetic_history(start, 30);
+        let profile = HourlyPriceProfile::from_prices(&history);
+
+        let forecast = RevenueForecaster::new(TbxConfig::new_tb2(100.0))
+            .with_samples(200)
+            .forecast(&profile, "TEST_BESS", "HB_WEST", start + Duration::days(30), start + Duration::days(36))
+            .unwrap();
+
+        assert_eq!(forecast.samples.len(), 200);
+        assert!(forecast.p10 <= forecast.p50);
+        assert!(forecast.p50 <= forecast.p90);
+        assert!(forecast.p10 > 0.0);
+    }
+
+    #[test]
+    fn test_forecast_matches_held_out_backtest() {
+        let start = NaiveDate::from_ymd_opt(2024, 6, 1).unwrap();
+        let history = synthetic_history(start, 60);
+
+        // Train on the first 46 days, hold out the last 14
+        let holdout_start = start + Duration::days(46);
+        let holdout_end = start + Duration::days(59);
+        let (train, holdout): (Vec<_>, Vec<_>) = history
+            .into_iter()
+            .partition(|p| p.timestamp.date_naive() < holdout_start);
+
+        let config = TbxConfig::new_tb2(100.0);
+        let calculator = TbxCalculator::new(config.clone());
+        let mut actual = 0.0;
+        let mut date = holdout_start;
+        while date <= holdout_end {
+            let day: Vec<_> = holdout
+                .iter()
+                .filter(|p| p.timestamp.date_naive() == date)
+                .cloned()
+                .collect();
+            actual += calculator
+                .calculate_daily_arbitrage(&day, "TEST_BESS", "HB_WEST", date)
+                .revenue_da;
+            date += Duration::days(1);
+        }
+
+        let profile = HourlyPriceProfile::from_prices(&train);
+        let forecast = RevenueForecaster::new(config)
+            .with_samples(300)
+            .forecast(&profile, "TEST_BESS", "HB_WEST", holdout_start, holdout_end)
+            .unwrap();
+
+        // Sampling hours independently widens the daily spread slightly, so
+        // allow some slack around the backtested revenue
+        assert!((forecast.p50 - actual).abs() / actual < 0.15);
+    }
+
+    #[test]
+    fn test_empty_profile_errors() {
+        let date = NaiveDate::from_ymd_opt(2024, 6, 1).unwrap();
+        let result = RevenueForecaster::new(TbxConfig::new_tb1(10.0)).forecast(
+            &HourlyPriceProfile::default(),
+            "TEST_BESS",
+            "HB_WEST",
+            date,
+            date,
+        );
+        assert!(result.is_err());
+    }
+}
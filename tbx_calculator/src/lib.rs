@@ -3,9 +3,23 @@ pub mod models;
 pub mod data_loader;
 pub mod settlement_mapper;
 pub mod blended_optimizer;
+pub mod battery_config;
+pub mod scenario;
+pub mod forecast;
+pub mod backtest;
+pub mod day_type;
+/// NERC holiday calendar, shared with the pipeline crate so both agree on
+/// which days are holidays
+#[path = "../../src/holidays.rs"]
+pub mod holidays;
 
 pub use calculator::TbxCalculator;
-pub use models::{TbxConfig, TbxResult, ArbitrageWindow, PriceData};
+pub use models::{TbxConfig, TbxResult, ArbitrageWindow, PriceData, ScheduleAction, ScheduleInterval};
 pub use data_loader::DataLoader;
 pub use settlement_mapper::SettlementMapper;
-pub use blended_optimizer::BlendedOptimizer;
\ No newline at end of file
+pub use blended_optimizer::BlendedOptimizer;
+pub use battery_config::{BatteryConfigSet, BatteryParams};
+pub use scenario::PriceScenario;
+pub use forecast::{HourlyPriceProfile, RevenueForecast, RevenueForecaster};
+pub use backtest::{backtest, compare_strategies, BacktestRow, StrategyComparison};
+pub use day_type::{day_type_breakdown, DayType, DayTypeRow};
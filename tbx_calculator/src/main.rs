@@ -1,11 +1,14 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use chrono::NaiveDate;
 use clap::{Parser, ValueEnum};
 use env_logger;
 use log::info;
+use std::io::Write;
 use std::path::Path;
+use tbx_calculator::models::MarketType;
 use tbx_calculator::{
-    BlendedOptimizer, DataLoader, SettlementMapper, TbxCalculator, TbxConfig,
+    backtest, compare_strategies, day_type_breakdown, BacktestRow, BatteryConfigSet, BlendedOptimizer, DataLoader, HourlyPriceProfile, PriceScenario,
+    RevenueForecast, RevenueForecaster, ScheduleInterval, SettlementMapper, TbxCalculator, TbxConfig,
 };
 
 #[derive(Parser)]
@@ -20,6 +23,14 @@ struct Args {
     #[arg(short, long, default_value = "100.0")]
     power_mw: f64,
 
+    /// Charge power limit in MW, when lower or higher than --power-mw
+    #[arg(long)]
+    max_charge_mw: Option<f64>,
+
+    /// Discharge power limit in MW, when lower or higher than --power-mw
+    #[arg(long)]
+    max_discharge_mw: Option<f64>,
+
     /// Round-trip efficiency (0-1)
     #[arg(short, long, default_value = "0.85")]
     efficiency: f64,
@@ -36,6 +47,11 @@ struct Args {
     #[arg(long)]
     mapping_file: String,
 
+    /// Load the mapping through a Parquet cache beside the CSV
+    /// (`<mapping_file>.cache.parquet`), rebuilt when the CSV changes
+    #[arg(long)]
+    mapping_cache: bool,
+
     /// DA price data path pattern (use {date} for date substitution)
     #[arg(long)]
     da_path_pattern: String,
@@ -48,6 +64,21 @@ struct Args {
     #[arg(short, long, default_value = "ALL")]
     resource: String,
 
+    /// Only analyze resources mapped to these settlement points
+    /// (comma-separated, e.g. `HB_NORTH,LZ_WEST`); combines with --resource
+    #[arg(long, value_delimiter = ',')]
+    settlement_points: Vec<String>,
+
+    /// Only analyze resources whose name matches this regex
+    /// (e.g. `'^GAMBIT'`); combines with --resource and --settlement-points
+    #[arg(long)]
+    resource_regex: Option<String>,
+
+    /// With `--resource ALL`, skip resources the mapping's STATUS column marks
+    /// decommissioned or not yet online
+    #[arg(long)]
+    active_only: bool,
+
     /// Output format
     #[arg(short, long, value_enum, default_value = "json")]
     output: OutputFormat,
@@ -59,6 +90,55 @@ struct Args {
     /// Calculate blended DA+RT optimization
     #[arg(long)]
     blended: bool,
+
+    /// JSON file with per-resource battery parameters
+    /// (power_mw, energy_mwh, charge_eff, discharge_eff, duration)
+    #[arg(long)]
+    battery_config: Option<String>,
+
+    /// What-if price scenario, run alongside the base case. Either
+    /// `name:multiplier[:shift]` (e.g. `stress:1.2`) or a JSON scenario file.
+    /// Can be repeated.
+    #[arg(long)]
+    scenario: Vec<String>,
+
+    /// Forecast mode: start of the target period (YYYY-MM-DD). Prices between
+    /// --start-date and --end-date are used as history.
+    #[arg(long)]
+    forecast_start: Option<String>,
+
+    /// Forecast mode: end of the target period (YYYY-MM-DD)
+    #[arg(long)]
+    forecast_end: Option<String>,
+
+    /// Number of Monte Carlo samples for the forecast
+    #[arg(long, default_value = "500")]
+    forecast_samples: usize,
+
+    /// Maximum change in MW between consecutive intervals for blended dispatch
+    #[arg(long)]
+    max_ramp_mw: Option<f64>,
+
+    /// Write each resource-day's per-interval charge/discharge schedule and
+    /// prices (for the better of the DA and RT strategies) to this CSV file
+    #[arg(long)]
+    export_schedule: Option<String>,
+
+    /// Backtest DA-only, RT-only and blended strategies over the date range
+    /// and print one comparison row per (resource, strategy) instead of
+    /// daily results (implies --blended)
+    #[arg(long)]
+    backtest: bool,
+
+    /// In the summary, show DA-only, RT-only and blended revenue side by
+    /// side per resource with the blending uplift (implies --blended)
+    #[arg(long)]
+    compare_strategies: bool,
+
+    /// In the summary, split revenue into weekday, weekend and NERC holiday
+    /// rows with the average $/MW-day of each
+    #[arg(long)]
+    by_day_type: bool,
 }
 
 #[derive(Clone, ValueEnum)]
@@ -91,18 +171,59 @@ fn main() -> Result<()> {
     // Override efficiency if specified
     let mut config = config;
     config.round_trip_efficiency = args.efficiency;
+    if let Some(max_charge_mw) = args.max_charge_mw {
+        config.max_charge_mw = max_charge_mw;
+    }
+    if let Some(max_discharge_mw) = args.max_discharge_mw {
+        config.max_discharge_mw = max_discharge_mw;
+    }
+
+    // Load per-resource battery overrides
+    let battery_configs = match &args.battery_config {
+        Some(path) => {
+            let configs = BatteryConfigSet::from_json_file(path)?
+                .with_cli_limits(args.max_charge_mw, args.max_discharge_mw);
+            info!("Loaded battery parameters for {} resources", configs.len());
+            configs
+        }
+        None => BatteryConfigSet::default(),
+    };
+
+    // Base case plus any what-if scenarios
+    let mut scenarios = vec![PriceScenario::base()];
+    for spec in &args.scenario {
+        let scenario = PriceScenario::parse(spec)?;
+        info!("Adding price scenario '{}'", scenario.name);
+        scenarios.push(scenario);
+    }
 
     // Parse dates
     let start_date = NaiveDate::parse_from_str(&args.start_date, "%Y-%m-%d")?;
     let end_date = NaiveDate::parse_from_str(&args.end_date, "%Y-%m-%d")?;
 
+    // Forecast target period, if running in forecast mode
+    let forecast_period = match (&args.forecast_start, &args.forecast_end) {
+        (Some(start), Some(end)) => Some((
+            NaiveDate::parse_from_str(start, "%Y-%m-%d")?,
+            NaiveDate::parse_from_str(end, "%Y-%m-%d")?,
+        )),
+        (None, None) => None,
+        _ => anyhow::bail!("--forecast-start and --forecast-end must be used together"),
+    };
+
     // Load settlement mappings
     info!("Loading settlement point mappings");
-    let mapper = SettlementMapper::from_ercot_files(&args.mapping_file)?;
+    let mapping_start = std::time::Instant::now();
+    let mapper = if args.mapping_cache {
+        SettlementMapper::from_ercot_files_cached(&args.mapping_file)?
+    } else {
+        SettlementMapper::from_ercot_files(&args.mapping_file)?
+    };
+    info!("Loaded {} settlement point mapping keys in {:.2?}", mapper.len(), mapping_start.elapsed());
 
     // Determine resources to analyze
-    let resources: Vec<_> = if args.resource == "ALL" {
-        mapper.get_all_bess().into_iter().map(|m| m.clone()).collect()
+    let mut resources: Vec<_> = if args.resource == "ALL" {
+        mapper.get_all_bess(args.active_only).into_iter().cloned().collect()
     } else {
         mapper
             .get_mapping(&args.resource)
@@ -110,8 +231,25 @@ fn main() -> Result<()> {
             .unwrap_or_default()
     };
 
+    if !args.settlement_points.is_empty() {
+        resources = SettlementMapper::filter_by_settlement_points(resources, &args.settlement_points);
+    }
+
+    if let Some(pattern) = &args.resource_regex {
+        let regex = regex::Regex::new(pattern)
+            .map_err(|e| anyhow::anyhow!("Invalid --resource-regex '{}': {}", pattern, e))?;
+        resources = SettlementMapper::filter_by_resource_regex(resources, &regex);
+    }
+
     if resources.is_empty() {
-        anyhow::bail!("No resources found matching '{}'", args.resource);
+        let mut selection = format!("'{}'", args.resource);
+        if !args.settlement_points.is_empty() {
+            selection.push_str(&format!(" at settlement points {}", args.settlement_points.join(",")));
+        }
+        if let Some(pattern) = &args.resource_regex {
+            selection.push_str(&format!(" with names matching /{}/", pattern));
+        }
+        anyhow::bail!("No resources found matching {}", selection);
     }
 
     info!("Analyzing {} resources", resources.len());
@@ -121,10 +259,14 @@ fn main() -> Result<()> {
 
     // Process each resource
     let mut all_results = Vec::new();
+    let mut forecasts = Vec::new();
+    let mut schedules = Vec::new();
 
     for resource in resources {
         info!("Processing {}", resource.resource_name);
 
+        let config = battery_configs.config_for(&resource.resource_name, &config);
+
         // Get settlement points
         let settlement_points = vec![resource.settlement_point.clone()];
 
@@ -139,72 +281,130 @@ fn main() -> Result<()> {
 
         info!("Loaded {} price points", prices.len());
 
-        // Calculate TBX for each day
-        let calculator = TbxCalculator::new(config.clone());
-        let mut current_date = start_date;
-
-        while current_date <= end_date {
-            // Filter prices for this day
-            let day_prices: Vec<_> = prices
-                .iter()
-                .filter(|p| p.timestamp.date_naive() == current_date)
-                .cloned()
-                .collect();
-
-            if !day_prices.is_empty() {
-                let mut result = calculator.calculate_daily_arbitrage(
-                    &day_prices,
+        if let Some((forecast_start, forecast_end)) = forecast_period {
+            let profile = HourlyPriceProfile::from_prices(&prices);
+            let forecast = RevenueForecaster::new(config.clone())
+                .with_samples(args.forecast_samples)
+                .forecast(
+                    &profile,
                     &resource.resource_name,
                     &resource.settlement_point,
-                    current_date,
-                );
+                    forecast_start,
+                    forecast_end,
+                )?;
+            forecasts.push(forecast);
+            continue;
+        }
 
-                // Calculate blended if requested
-                if args.blended {
-                    let da_prices: Vec<_> = day_prices
-                        .iter()
-                        .filter(|p| p.market == tbx_calculator::models::MarketType::DayAhead)
-                        .cloned()
-                        .collect();
+        // Calculate TBX for each scenario and day
+        for scenario in &scenarios {
+            let scenario_prices = scenario.apply(&prices);
+
+            let calculator = TbxCalculator::try_new(config.clone())
+                .with_context(|| format!("Invalid battery limits for {}", resource.resource_name))?;
+            let mut current_date = start_date;
+
+            while current_date <= end_date {
+                // Filter prices for this day
+                let day_prices: Vec<_> = scenario_prices
+                    .iter()
+                    .filter(|p| p.timestamp.date_naive() == current_date)
+                    .cloned()
+                    .collect();
+
+                if !day_prices.is_empty() {
+                    let mut result = calculator.calculate_daily_arbitrage(
+                        &day_prices,
+                        &resource.resource_name,
+                        &resource.settlement_point,
+                        current_date,
+                    );
+
+                    // Calculate blended if requested
+                    if args.blended || args.backtest || args.compare_strategies {
+                        let da_prices: Vec<_> = day_prices
+                            .iter()
+                            .filter(|p| p.market == tbx_calculator::models::MarketType::DayAhead)
+                            .cloned()
+                            .collect();
                     
-                    let rt_prices: Vec<_> = day_prices
-                        .iter()
-                        .filter(|p| {
-                            matches!(
-                                p.market,
-                                tbx_calculator::models::MarketType::RealTime5Min
-                                    | tbx_calculator::models::MarketType::RealTime15Min
-                            )
-                        })
-                        .cloned()
-                        .collect();
-
-                    if !da_prices.is_empty() && !rt_prices.is_empty() {
-                        let optimizer = BlendedOptimizer::new(config.clone());
-                        let blended_windows = optimizer.optimize_blended(&da_prices, &rt_prices);
+                        let rt_prices: Vec<_> = day_prices
+                            .iter()
+                            .filter(|p| {
+                                matches!(
+                                    p.market,
+                                    tbx_calculator::models::MarketType::RealTime5Min
+                                        | tbx_calculator::models::MarketType::RealTime15Min
+                                )
+                            })
+                            .cloned()
+                            .collect();
+
+                        if !da_prices.is_empty() && !rt_prices.is_empty() {
+                            let mut optimizer = BlendedOptimizer::new(config.clone());
+                            if let Some(max_ramp_mw) = args.max_ramp_mw {
+                                optimizer = optimizer.with_ramp_limit(max_ramp_mw);
+                            }
+                            let blended_windows = optimizer.optimize_blended(&da_prices, &rt_prices);
                         
-                        result.blended_windows = blended_windows.clone();
-                        result.revenue_blended = blended_windows.iter().map(|w| w.revenue).sum();
-                        result.avg_spread_blended = if !blended_windows.is_empty() {
-                            let total_spread: f64 = blended_windows
-                                .iter()
-                                .map(|w| (w.discharge_price - w.charge_price) * w.energy_mwh)
-                                .sum();
-                            let total_energy: f64 = blended_windows.iter().map(|w| w.energy_mwh).sum();
-                            total_spread / total_energy
+                            result.blended_windows = blended_windows.clone();
+                            result.revenue_blended = blended_windows.iter().map(|w| w.revenue).sum();
+                            result.avg_spread_blended = if !blended_windows.is_empty() {
+                                let total_spread: f64 = blended_windows
+                                    .iter()
+                                    .map(|w| (w.discharge_price - w.charge_price) * w.energy_mwh)
+                                    .sum();
+                                let total_energy: f64 = blended_windows.iter().map(|w| w.energy_mwh).sum();
+                                total_spread / total_energy
+                            } else {
+                                0.0
+                            };
+                        }
+                    }
+
+                    result.scenario = scenario.name.clone();
+
+                    if args.export_schedule.is_some() {
+                        let market = if result.revenue_rt > result.revenue_da {
+                            MarketType::RealTime15Min
                         } else {
-                            0.0
+                            MarketType::DayAhead
                         };
+                        for interval in calculator.daily_schedule(&day_prices, market) {
+                            schedules.push((
+                                resource.resource_name.clone(),
+                                current_date,
+                                scenario.name.clone(),
+                                interval,
+                            ));
+                        }
                     }
+
+                    all_results.push(result);
                 }
 
-                all_results.push(result);
+                current_date += chrono::Duration::days(1);
             }
-
-            current_date += chrono::Duration::days(1);
         }
     }
 
+    if forecast_period.is_some() {
+        return output_forecasts(&forecasts, &args.output);
+    }
+
+    if args.backtest {
+        let base_results: Vec<_> = all_results
+            .into_iter()
+            .filter(|r| r.scenario == tbx_calculator::scenario::BASE_SCENARIO)
+            .collect();
+        return output_backtest(&backtest(&base_results), &args.output, start_date, end_date);
+    }
+
+    if let Some(path) = &args.export_schedule {
+        write_schedule(path, &schedules)?;
+        info!("Wrote {} schedule intervals to {}", schedules.len(), path);
+    }
+
     // Output results
     match args.output {
         OutputFormat::Json => {
@@ -212,12 +412,17 @@ fn main() -> Result<()> {
             println!("{}", json);
         }
         OutputFormat::Csv => {
-            println!("Resource,Date,Strategy,Revenue,AvgSpread,Utilization");
+            println!("Resource,Date,Scenario,PowerMW,CapacityMWh,DurationHours,Efficiency,Strategy,Revenue,AvgSpread,Utilization");
             for result in &all_results {
                 println!(
-                    "{},{},{},{:.2},{:.2},{:.2}",
+                    "{},{},{},{:.1},{:.1},{},{:.3},{},{:.2},{:.2},{:.2}",
                     result.resource_name,
                     result.date,
+                    result.scenario,
+                    result.config.battery_power_mw,
+                    result.config.battery_capacity_mwh,
+                    result.config.duration_hours,
+                    result.config.round_trip_efficiency,
                     result.best_strategy(),
                     result.best_revenue(),
                     result.avg_spread_da.max(result.avg_spread_rt).max(result.avg_spread_blended),
@@ -226,11 +431,23 @@ fn main() -> Result<()> {
             }
         }
         OutputFormat::Summary => {
-            // Group by resource
+            // Group by resource (base case) and by scenario
             let mut resource_totals: std::collections::HashMap<String, f64> = std::collections::HashMap::new();
+            let mut resource_configs: std::collections::HashMap<String, TbxConfig> = std::collections::HashMap::new();
+            let mut scenario_totals: std::collections::HashMap<String, (f64, f64, usize)> = std::collections::HashMap::new();
             
             for result in &all_results {
+                let spread = result.avg_spread_da.max(result.avg_spread_rt).max(result.avg_spread_blended);
+                let entry = scenario_totals.entry(result.scenario.clone()).or_insert((0.0, 0.0, 0));
+                entry.0 += result.best_revenue();
+                entry.1 += spread;
+                entry.2 += 1;
+
+                if result.scenario != tbx_calculator::scenario::BASE_SCENARIO {
+                    continue;
+                }
                 *resource_totals.entry(result.resource_name.clone()).or_insert(0.0) += result.best_revenue();
+                resource_configs.entry(result.resource_name.clone()).or_insert_with(|| result.config.clone());
             }
 
             println!("TBX Analysis Summary");
@@ -251,9 +468,210 @@ fn main() -> Result<()> {
                     "  {}: ${:.2} total (${:.2}/day)",
                     resource, total_revenue, daily_avg
                 );
+                if let Some(resource_config) = resource_configs.get(&resource) {
+                    println!(
+                        "    {} MW / {} MWh, {:.1}% efficiency",
+                        resource_config.battery_power_mw,
+                        resource_config.battery_capacity_mwh,
+                        resource_config.round_trip_efficiency * 100.0
+                    );
+                }
+            }
+
+            if args.compare_strategies {
+                let base_results: Vec<_> = all_results
+                    .iter()
+                    .filter(|r| r.scenario == tbx_calculator::scenario::BASE_SCENARIO)
+                    .cloned()
+                    .collect();
+
+                println!();
+                println!("Revenue by Strategy:");
+                println!(
+                    "  {:<24} {:>14} {:>14} {:>14} {:>14} {:>8}",
+                    "Resource", "DA-only $", "RT-only $", "Blended $", "Uplift $", "Uplift"
+                );
+                for comparison in compare_strategies(&base_results) {
+                    let uplift_pct = comparison
+                        .blending_uplift_pct()
+                        .map(|pct| format!("{:+.1}%", pct))
+                        .unwrap_or_else(|| "n/a".to_string());
+                    println!(
+                        "  {:<24} {:>14.2} {:>14.2} {:>14.2} {:>14.2} {:>8}",
+                        comparison.resource_name,
+                        comparison.revenue_da,
+                        comparison.revenue_rt,
+                        comparison.revenue_blended,
+                        comparison.blending_uplift(),
+                        uplift_pct
+                    );
+                }
+            }
+
+            if args.by_day_type {
+                let base_results: Vec<_> = all_results
+                    .iter()
+                    .filter(|r| r.scenario == tbx_calculator::scenario::BASE_SCENARIO)
+                    .cloned()
+                    .collect();
+
+                println!();
+                println!("Revenue by Day Type:");
+                println!("  {:<10} {:>6} {:>16} {:>14}", "Day Type", "Days", "Total $", "Avg $/MW-day");
+                for row in day_type_breakdown(&base_results) {
+                    println!(
+                        "  {:<10} {:>6} {:>16.2} {:>14.2}",
+                        row.day_type.label(),
+                        row.days,
+                        row.total_revenue,
+                        row.avg_revenue_per_mw_day
+                    );
+                }
+            }
+
+            if scenarios.len() > 1 {
+                let base_total = scenario_totals
+                    .get(tbx_calculator::scenario::BASE_SCENARIO)
+                    .map(|t| t.0)
+                    .unwrap_or(0.0);
+
+                println!();
+                println!("Revenue by Scenario:");
+                for scenario in &scenarios {
+                    if let Some((total_revenue, total_spread, count)) = scenario_totals.get(&scenario.name) {
+                        let change = if base_total != 0.0 {
+                            (total_revenue / base_total - 1.0) * 100.0
+                        } else {
+                            0.0
+                        };
+                        println!(
+                            "  {} (x{:.2}, {:+.2} $/MWh): ${:.2} total, avg spread ${:.2}/MWh, {:+.1}% vs base",
+                            scenario.name,
+                            scenario.multiplier,
+                            scenario.shift,
+                            total_revenue,
+                            total_spread / *count as f64,
+                            change
+                        );
+                    }
+                }
             }
         }
     }
 
     Ok(())
-}
\ No newline at end of file
+}
+
+/// One row per interval: resource, day, scenario, interval start, market, action, MW and price
+fn write_schedule(path: &str, schedules: &[(String, NaiveDate, String, ScheduleInterval)]) -> Result<()> {
+    let mut file = std::io::BufWriter::new(std::fs::File::create(path)?);
+    writeln!(file, "Resource,Date,Scenario,Timestamp,Market,Action,PowerMW,Price")?;
+    for (resource, date, scenario, interval) in schedules {
+        writeln!(
+            file,
+            "{},{},{},{},{:?},{:?},{:.1},{:.2}",
+            resource,
+            date,
+            scenario,
+            interval.timestamp.to_rfc3339(),
+            interval.market,
+            interval.action,
+            interval.power_mw,
+            interval.price
+        )?;
+    }
+    file.flush()?;
+    Ok(())
+}
+
+fn output_backtest(rows: &[BacktestRow], output: &OutputFormat, start_date: NaiveDate, end_date: NaiveDate) -> Result<()> {
+    match output {
+        OutputFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(rows)?);
+        }
+        OutputFormat::Csv => {
+            println!("Resource,Strategy,Days,TotalRevenue,AvgDailyRevenue,Cycles,AvgSpread,DaysBest,Best");
+            for row in rows {
+                println!(
+                    "{},{},{},{:.2},{:.2},{:.2},{:.2},{},{}",
+                    row.resource_name,
+                    row.strategy,
+                    row.days,
+                    row.total_revenue,
+                    row.avg_daily_revenue,
+                    row.cycles,
+                    row.avg_spread,
+                    row.days_best,
+                    row.is_best
+                );
+            }
+        }
+        OutputFormat::Summary => {
+            println!("TBX Strategy Backtest");
+            println!("=====================");
+            println!("Period: {} to {}", start_date, end_date);
+            println!();
+            println!(
+                "{:<24} {:<10} {:>14} {:>12} {:>8} {:>10} {:>9}",
+                "Resource", "Strategy", "Revenue $", "$/day", "Cycles", "Spread $", "Days Best"
+            );
+            for row in rows {
+                println!(
+                    "{:<24} {:<10} {:>14.2} {:>12.2} {:>8.1} {:>10.2} {:>9}{}",
+                    row.resource_name,
+                    row.strategy,
+                    row.total_revenue,
+                    row.avg_daily_revenue,
+                    row.cycles,
+                    row.avg_spread,
+                    row.days_best,
+                    if row.is_best { "  <- best" } else { "" }
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn output_forecasts(forecasts: &[RevenueForecast], output: &OutputFormat) -> Result<()> {
+    match output {
+        OutputFormat::Json => {
+            println!("{}", serde_json::to_string_pretty(forecasts)?);
+        }
+        OutputFormat::Csv => {
+            println!("Resource,StartDate,EndDate,Samples,Mean,P10,P50,P90");
+            for forecast in forecasts {
+                println!(
+                    "{},{},{},{},{:.2},{:.2},{:.2},{:.2}",
+                    forecast.resource_name,
+                    forecast.start_date,
+                    forecast.end_date,
+                    forecast.num_samples,
+                    forecast.mean,
+                    forecast.p10,
+                    forecast.p50,
+                    forecast.p90
+                );
+            }
+        }
+        OutputFormat::Summary => {
+            println!("TBX Revenue Forecast");
+            println!("====================");
+            for forecast in forecasts {
+                println!(
+                    "  {} ({} to {}): P10 ${:.2} / P50 ${:.2} / P90 ${:.2} ({} samples)",
+                    forecast.resource_name,
+                    forecast.start_date,
+                    forecast.end_date,
+                    forecast.p10,
+                    forecast.p50,
+                    forecast.p90,
+                    forecast.num_samples
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -1,10 +1,16 @@
+use anyhow::{bail, Result};
 use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(from = "StoredTbxConfig")]
 pub struct TbxConfig {
     pub duration_hours: u8, // 1, 2, or 4
     pub battery_power_mw: f64,
+    /// Most the inverter can draw while charging
+    pub max_charge_mw: f64,
+    /// Most the inverter can deliver while discharging
+    pub max_discharge_mw: f64,
     pub battery_capacity_mwh: f64,
     pub round_trip_efficiency: f64,
     pub min_spread_threshold: f64, // Minimum $/MWh spread to arbitrage
@@ -15,6 +21,8 @@ impl TbxConfig {
         Self {
             duration_hours: 1,
             battery_power_mw: power_mw,
+            max_charge_mw: power_mw,
+            max_discharge_mw: power_mw,
             battery_capacity_mwh: power_mw * 1.0,
             round_trip_efficiency: 0.85,
             min_spread_threshold: 5.0,
@@ -25,6 +33,8 @@ impl TbxConfig {
         Self {
             duration_hours: 2,
             battery_power_mw: power_mw,
+            max_charge_mw: power_mw,
+            max_discharge_mw: power_mw,
             battery_capacity_mwh: power_mw * 2.0,
             round_trip_efficiency: 0.85,
             min_spread_threshold: 5.0,
@@ -35,17 +45,73 @@ impl TbxConfig {
         Self {
             duration_hours: 4,
             battery_power_mw: power_mw,
+            max_charge_mw: power_mw,
+            max_discharge_mw: power_mw,
             battery_capacity_mwh: power_mw * 4.0,
             round_trip_efficiency: 0.85,
             min_spread_threshold: 5.0,
         }
     }
 
+    /// Set the rated power and both the charge and discharge limits
+    pub fn set_power_mw(&mut self, power_mw: f64) {
+        self.battery_power_mw = power_mw;
+        self.max_charge_mw = power_mw;
+        self.max_discharge_mw = power_mw;
+    }
+
+    /// Energy one TBX cycle buys and sells: rated power x duration
+    pub fn cycle_energy_mwh(&self) -> f64 {
+        self.battery_power_mw * self.duration_hours as f64
+    }
+
+    /// Check both the charge and discharge limits are positive
+    pub fn check_power_limits(&self) -> Result<()> {
+        let positive = |mw: f64| !mw.is_nan() && mw > 0.0;
+        if !positive(self.max_charge_mw) || !positive(self.max_discharge_mw) {
+            bail!(
+                "Battery charge and discharge limits must be positive MW (max charge {}, max discharge {})",
+                self.max_charge_mw,
+                self.max_discharge_mw
+            );
+        }
+        Ok(())
+    }
+
     pub fn one_way_efficiency(&self) -> f64 {
         self.round_trip_efficiency.sqrt()
     }
 }
 
+/// `TbxConfig` as serialized; configs saved before the separate charge and
+/// discharge limits leave them out, and both fall back to the rated power
+#[derive(Deserialize)]
+struct StoredTbxConfig {
+    duration_hours: u8,
+    battery_power_mw: f64,
+    #[serde(default)]
+    max_charge_mw: Option<f64>,
+    #[serde(default)]
+    max_discharge_mw: Option<f64>,
+    battery_capacity_mwh: f64,
+    round_trip_efficiency: f64,
+    min_spread_threshold: f64,
+}
+
+impl From<StoredTbxConfig> for TbxConfig {
+    fn from(stored: StoredTbxConfig) -> Self {
+        Self {
+            duration_hours: stored.duration_hours,
+            battery_power_mw: stored.battery_power_mw,
+            max_charge_mw: stored.max_charge_mw.unwrap_or(stored.battery_power_mw),
+            max_discharge_mw: stored.max_discharge_mw.unwrap_or(stored.battery_power_mw),
+            battery_capacity_mwh: stored.battery_capacity_mwh,
+            round_trip_efficiency: stored.round_trip_efficiency,
+            min_spread_threshold: stored.min_spread_threshold,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PriceData {
     pub timestamp: DateTime<Utc>,
@@ -61,6 +127,24 @@ pub enum MarketType {
     RealTime15Min,
 }
 
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ScheduleAction {
+    Charge,
+    Discharge,
+    Idle,
+}
+
+/// One interval of a day's TBX dispatch: what the battery does and the price it sees
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduleInterval {
+    pub timestamp: DateTime<Utc>,
+    pub market: MarketType,
+    pub action: ScheduleAction,
+    /// Positive = discharge, negative = charge
+    pub power_mw: f64,
+    pub price: f64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ArbitrageWindow {
     pub charge_start: DateTime<Utc>,
@@ -73,12 +157,58 @@ pub struct ArbitrageWindow {
     pub revenue: f64,
 }
 
+/// Absolute tolerance ($) when checking window revenue against its prices
+pub const REVENUE_TOLERANCE: f64 = 0.01;
+
+impl ArbitrageWindow {
+    /// Revenue implied by the window's prices and energy
+    pub fn expected_revenue(&self, round_trip_efficiency: f64) -> f64 {
+        (self.discharge_price - self.charge_price) * self.energy_mwh * round_trip_efficiency
+    }
+
+    /// Check the window is internally consistent
+    pub fn validate(&self, round_trip_efficiency: f64) -> anyhow::Result<()> {
+        if self.energy_mwh.is_nan() || self.energy_mwh < 0.0 {
+            anyhow::bail!("energy_mwh must be non-negative, got {}", self.energy_mwh);
+        }
+        if self.charge_end < self.charge_start {
+            anyhow::bail!("charge window ends ({}) before it starts ({})", self.charge_end, self.charge_start);
+        }
+        if self.discharge_end < self.discharge_start {
+            anyhow::bail!(
+                "discharge window ends ({}) before it starts ({})",
+                self.discharge_end,
+                self.discharge_start
+            );
+        }
+
+        let expected = self.expected_revenue(round_trip_efficiency);
+        let tolerance = REVENUE_TOLERANCE.max(expected.abs() * 1e-9);
+        let error = (self.revenue - expected).abs();
+        if error.is_nan() || error > tolerance {
+            anyhow::bail!(
+                "revenue {:.4} does not match (discharge {:.4} - charge {:.4}) x {:.4} MWh x {:.4} = {:.4}",
+                self.revenue,
+                self.discharge_price,
+                self.charge_price,
+                self.energy_mwh,
+                round_trip_efficiency,
+                expected
+            );
+        }
+
+        Ok(())
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TbxResult {
     pub resource_name: String,
     pub settlement_point: String,
     pub date: NaiveDate,
     pub config: TbxConfig,
+    #[serde(default = "default_scenario")]
+    pub scenario: String,
     
     // Revenue by market
     pub revenue_da: f64,
@@ -98,6 +228,10 @@ pub struct TbxResult {
     pub cycles_per_day: f64,
 }
 
+fn default_scenario() -> String {
+    crate::scenario::BASE_SCENARIO.to_string()
+}
+
 impl TbxResult {
     pub fn new(resource_name: String, settlement_point: String, date: NaiveDate, config: TbxConfig) -> Self {
         Self {
@@ -105,6 +239,7 @@ impl TbxResult {
             settlement_point,
             date,
             config,
+            scenario: default_scenario(),
             revenue_da: 0.0,
             revenue_rt: 0.0,
             revenue_blended: 0.0,
@@ -123,6 +258,37 @@ impl TbxResult {
         self.revenue_da.max(self.revenue_rt).max(self.revenue_blended)
     }
 
+    /// Check every window and that market revenues equal the sum of their windows
+    pub fn validate(&self) -> anyhow::Result<()> {
+        let markets = [
+            ("DA", &self.da_windows, self.revenue_da),
+            ("RT", &self.rt_windows, self.revenue_rt),
+            ("Blended", &self.blended_windows, self.revenue_blended),
+        ];
+
+        for (market, windows, revenue) in markets {
+            for (idx, window) in windows.iter().enumerate() {
+                window.validate(self.config.round_trip_efficiency).map_err(|e| {
+                    anyhow::anyhow!("{} {} {} window {}: {}", self.resource_name, self.date, market, idx, e)
+                })?;
+            }
+
+            let window_total: f64 = windows.iter().map(|w| w.revenue).sum();
+            if (window_total - revenue).abs() > REVENUE_TOLERANCE.max(revenue.abs() * 1e-9) {
+                anyhow::bail!(
+                    "{} {} {} revenue {:.4} does not equal window total {:.4}",
+                    self.resource_name,
+                    self.date,
+                    market,
+                    revenue,
+                    window_total
+                );
+            }
+        }
+
+        Ok(())
+    }
+
     pub fn best_strategy(&self) -> &str {
         if self.revenue_blended >= self.revenue_da && self.revenue_blended >= self.revenue_rt {
             "Blended"
@@ -132,4 +298,75 @@ impl TbxResult {
             "DayAhead"
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn window(charge_price: f64, discharge_price: f64, energy_mwh: f64, revenue: f64) -> ArbitrageWindow {
+        let t = |hour| Utc.with_ymd_and_hms(2024, 1, 1, hour, 0, 0).unwrap();
+        ArbitrageWindow {
+            charge_start: t(2),
+            charge_end: t(4),
+            charge_price,
+            discharge_start: t(18),
+            discharge_end: t(20),
+            discharge_price,
+            energy_mwh,
+            revenue,
+        }
+    }
+
+    #[test]
+    fn test_consistent_window_validates() {
+        let w = window(20.0, 100.0, 200.0, 80.0 * 200.0 * 0.85);
+        assert!(w.validate(0.85).is_ok());
+    }
+
+    #[test]
+    fn test_sign_error_fails_validation() {
+        // Revenue computed as (charge - discharge) instead of (discharge - charge)
+        let w = window(20.0, 100.0, 200.0, -80.0 * 200.0 * 0.85);
+        assert!(w.validate(0.85).is_err());
+    }
+
+    #[test]
+    fn test_inconsistent_result_fails_validation() {
+        let date = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let mut result = TbxResult::new("BESS".into(), "NODE".into(), date, TbxConfig::new_tb2(100.0));
+        result.da_windows = vec![window(20.0, 100.0, 200.0, 80.0 * 200.0 * 0.85)];
+        result.revenue_da = 80.0 * 200.0 * 0.85;
+        assert!(result.validate().is_ok());
+
+        // Market total disagrees with its windows
+        result.revenue_da += 100.0;
+        assert!(result.validate().is_err());
+
+        // Efficiency not applied
+        result.da_windows[0].revenue = 80.0 * 200.0;
+        result.revenue_da = 80.0 * 200.0;
+        assert!(result.validate().is_err());
+
+        // Negative energy
+        result.da_windows[0] = window(20.0, 100.0, -200.0, -80.0 * 200.0 * 0.85);
+        result.revenue_da = result.da_windows[0].revenue;
+        assert!(result.validate().is_err());
+    }
+
+    #[test]
+    fn test_config_without_power_limits_falls_back_to_rated_power() {
+        let old = r#"{"duration_hours":2,"battery_power_mw":100.0,"battery_capacity_mwh":200.0,
+            "round_trip_efficiency":0.85,"min_spread_threshold":5.0}"#;
+        let config: TbxConfig = serde_json::from_str(old).unwrap();
+        assert_eq!(config.max_charge_mw, 100.0);
+        assert_eq!(config.max_discharge_mw, 100.0);
+
+        let mut limited = TbxConfig::new_tb2(100.0);
+        limited.max_charge_mw = 50.0;
+        let round_trip: TbxConfig = serde_json::from_str(&serde_json::to_string(&limited).unwrap()).unwrap();
+        assert_eq!(round_trip.max_charge_mw, 50.0);
+        assert_eq!(round_trip.max_discharge_mw, 100.0);
+    }
+}
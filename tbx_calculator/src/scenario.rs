@@ -0,0 +1,161 @@
+use anyhow::{Context, Result};
+use chrono::Timelike;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::models::PriceData;
+
+pub const BASE_SCENARIO: &str = "base";
+
+/// What-if price adjustment applied to loaded prices before optimization:
+/// `price * multiplier * hourly_multipliers[hour] + shift`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PriceScenario {
+    pub name: String,
+    #[serde(default = "default_multiplier")]
+    pub multiplier: f64,
+    #[serde(default)]
+    pub shift: f64,
+    /// Optional per-hour (0-23) multipliers
+    #[serde(default)]
+    pub hourly_multipliers: Option<Vec<f64>>,
+}
+
+fn default_multiplier() -> f64 {
+    1.0
+}
+
+impl PriceScenario {
+    pub fn base() -> Self {
+        Self {
+            name: BASE_SCENARIO.to_string(),
+            multiplier: 1.0,
+            shift: 0.0,
+            hourly_multipliers: None,
+        }
+    }
+
+    /// Parse a scenario spec: either `name:multiplier[:shift]` (e.g. `stress:1.2`,
+    /// `floor:1.0:-10`) or the path to a JSON scenario file
+    pub fn parse(spec: &str) -> Result<Self> {
+        if spec.ends_with(".json") || Path::new(spec).is_file() {
+            return Self::from_json_file(spec);
+        }
+
+        let parts: Vec<&str> = spec.split(':').collect();
+        if parts.len() < 2 || parts.len() > 3 || parts[0].is_empty() {
+            anyhow::bail!(
+                "Invalid scenario '{}': expected name:multiplier[:shift] or a JSON file",
+                spec
+            );
+        }
+
+        let multiplier = parts[1]
+            .parse::<f64>()
+            .with_context(|| format!("Invalid multiplier in scenario '{}'", spec))?;
+        let shift = match parts.get(2) {
+            Some(shift) => shift
+                .parse::<f64>()
+                .with_context(|| format!("Invalid shift in scenario '{}'", spec))?,
+            None => 0.0,
+        };
+
+        Ok(Self {
+            name: parts[0].to_string(),
+            multiplier,
+            shift,
+            hourly_multipliers: None,
+        })
+    }
+
+    pub fn from_json_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read scenario file {}", path.display()))?;
+        let scenario: Self = serde_json::from_str(&contents)
+            .with_context(|| format!("Failed to parse scenario file {}", path.display()))?;
+
+        if let Some(hourly) = &scenario.hourly_multipliers {
+            if hourly.len() != 24 {
+                anyhow::bail!(
+                    "Scenario '{}' has {} hourly multipliers, expected 24",
+                    scenario.name,
+                    hourly.len()
+                );
+            }
+        }
+
+        Ok(scenario)
+    }
+
+    pub fn is_base(&self) -> bool {
+        self.multiplier == 1.0 && self.shift == 0.0 && self.hourly_multipliers.is_none()
+    }
+
+    pub fn adjust_price(&self, price: &PriceData) -> f64 {
+        let hourly = self
+            .hourly_multipliers
+            .as_ref()
+            .and_then(|h| h.get(price.timestamp.hour() as usize))
+            .copied()
+            .unwrap_or(1.0);
+
+        price.price * self.multiplier * hourly + self.shift
+    }
+
+    pub fn apply(&self, prices: &[PriceData]) -> Vec<PriceData> {
+        prices
+            .iter()
+            .map(|p| PriceData {
+                price: self.adjust_price(p),
+                ..p.clone()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::MarketType;
+    use chrono::{TimeZone, Utc};
+
+    fn price_at(hour: u32, price: f64) -> PriceData {
+        PriceData {
+            timestamp: Utc.with_ymd_and_hms(2024, 8, 1, hour, 0, 0).unwrap(),
+            settlement_point: "HB_NORTH".to_string(),
+            price,
+            market: MarketType::DayAhead,
+        }
+    }
+
+    #[test]
+    fn test_parse_multiplier_and_shift() {
+        let scenario = PriceScenario::parse("stress:1.2:5").unwrap();
+        assert_eq!(scenario.name, "stress");
+        assert_eq!(scenario.multiplier, 1.2);
+        assert_eq!(scenario.shift, 5.0);
+
+        let adjusted = scenario.apply(&[price_at(0, 50.0)]);
+        assert!((adjusted[0].price - 65.0).abs() < 1e-9);
+
+        assert!(PriceScenario::parse("bad").is_err());
+        assert!(PriceScenario::base().is_base());
+    }
+
+    #[test]
+    fn test_hourly_multipliers() {
+        let mut hourly = vec![1.0; 24];
+        hourly[18] = 2.0;
+        let scenario = PriceScenario {
+            name: "evening_peak".to_string(),
+            multiplier: 1.0,
+            shift: 0.0,
+            hourly_multipliers: Some(hourly),
+        };
+
+        let adjusted = scenario.apply(&[price_at(3, 20.0), price_at(18, 100.0)]);
+        assert_eq!(adjusted[0].price, 20.0);
+        assert_eq!(adjusted[1].price, 200.0);
+    }
+}
@@ -1,6 +1,14 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
+use log::{info, warn};
 use polars::prelude::*;
+use regex::Regex;
 use std::collections::HashMap;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+/// Mapping CSV columns kept in the cache; STATUS is optional
+const MAPPING_COLUMNS: [&str; 4] = ["RESOURCE_NODE", "UNIT_NAME", "UNIT_SUBSTATION", "STATUS"];
 
 #[derive(Debug, Clone)]
 pub struct ResourceMapping {
@@ -9,6 +17,29 @@ pub struct ResourceMapping {
     pub settlement_point: String,
     pub capacity_mw: Option<f64>,
     pub duration_hours: Option<f64>,
+    /// From the mapping's optional STATUS column
+    pub status: Option<String>,
+}
+
+/// Statuses marking a resource decommissioned or not yet online
+const INACTIVE_STATUSES: [&str; 6] = [
+    "INACTIVE",
+    "DECOMMISSIONED",
+    "RETIRED",
+    "PLANNED",
+    "NOT_YET_ONLINE",
+    "UNDER_CONSTRUCTION",
+];
+
+impl ResourceMapping {
+    /// Whether the resource is operating; resources without a status count
+    /// as active
+    pub fn is_active(&self) -> bool {
+        self.status.as_deref().is_none_or(|status| {
+            let status = status.trim().to_ascii_uppercase().replace([' ', '-'], "_");
+            !INACTIVE_STATUSES.contains(&status.as_str())
+        })
+    }
 }
 
 pub struct SettlementMapper {
@@ -18,16 +49,64 @@ pub struct SettlementMapper {
 impl SettlementMapper {
     /// Load settlement point mappings from ERCOT CSV files
     pub fn from_ercot_files(resource_node_path: &str) -> Result<Self> {
-        let df = CsvReader::from_path(resource_node_path)?
-            .has_header(true)
-            .finish()?;
+        let df = read_csv(Path::new(resource_node_path))?;
+        Self::from_frame(&df)
+    }
+
+    /// Cache file for a mapping CSV: `<file>.cache.parquet` beside it
+    pub fn cache_path(resource_node_path: &str) -> PathBuf {
+        PathBuf::from(format!("{}.cache.parquet", resource_node_path))
+    }
+
+    /// Load mappings like `from_ercot_files`, but through a Parquet copy of
+    /// the mapping columns at `cache_path`. The cache is used while the CSV
+    /// has the size and modification time it was built from, and rewritten
+    /// from the CSV otherwise.
+    pub fn from_ercot_files_cached(resource_node_path: &str) -> Result<Self> {
+        let cache_path = Self::cache_path(resource_node_path);
+        if cache_is_fresh(Path::new(resource_node_path), &cache_path) {
+            match File::open(&cache_path).map_err(PolarsError::from).and_then(|f| ParquetReader::new(f).finish()) {
+                Ok(df) => {
+                    info!("Loaded settlement point mappings from cache {}", cache_path.display());
+                    return Self::from_frame(&df);
+                }
+                Err(e) => warn!("Ignoring unreadable mapping cache {}: {}", cache_path.display(), e),
+            }
+        }
+
+        let df = read_csv(Path::new(resource_node_path))?;
+        let columns: Vec<&str> = MAPPING_COLUMNS
+            .into_iter()
+            .filter(|column| df.column(column).is_ok())
+            .collect();
+        let mut cached = df
+            .select(columns)
+            .with_context(|| format!("Mapping file {} is missing required columns", resource_node_path))?;
+        match File::create(&cache_path) {
+            Ok(file) => match ParquetWriter::new(file).finish(&mut cached) {
+                Ok(_) => {
+                    let stamp = source_stamp(Path::new(resource_node_path)).unwrap_or_default();
+                    if let Err(e) = std::fs::write(stamp_path(&cache_path), stamp) {
+                        warn!("Failed to write mapping cache stamp for {}: {}", cache_path.display(), e);
+                    }
+                }
+                Err(e) => warn!("Failed to write mapping cache {}: {}", cache_path.display(), e),
+            },
+            Err(e) => warn!("Failed to create mapping cache {}: {}", cache_path.display(), e),
+        }
+        Self::from_frame(&cached)
+    }
 
+    /// Mappings from a frame with RESOURCE_NODE, UNIT_NAME, UNIT_SUBSTATION
+    /// and optionally STATUS columns
+    fn from_frame(df: &DataFrame) -> Result<Self> {
         let mut mappings = HashMap::new();
 
         // Extract columns
         let resource_nodes = df.column("RESOURCE_NODE")?.str()?;
         let unit_names = df.column("UNIT_NAME")?.str()?;
         let unit_substations = df.column("UNIT_SUBSTATION")?.str()?;
+        let statuses = df.column("STATUS").ok().and_then(|c| c.str().ok());
 
         for idx in 0..df.height() {
             if let (Some(resource_node), Some(unit_name), Some(unit_substation)) = (
@@ -42,6 +121,7 @@ impl SettlementMapper {
                     settlement_point: resource_node.to_string(),
                     capacity_mw: None, // Would need to load from separate file
                     duration_hours: None,
+                    status: statuses.and_then(|s| s.get(idx)).map(str::to_string),
                 };
 
                 mappings.insert(unit_name.to_string(), mapping.clone());
@@ -65,14 +145,56 @@ impl SettlementMapper {
         self.mappings.get(resource_name)
     }
 
-    /// Get all BESS resources
-    pub fn get_all_bess(&self) -> Vec<&ResourceMapping> {
+    /// Get all BESS resources, once each; with `active_only`, leave out
+    /// those whose status marks them decommissioned or not yet online
+    pub fn get_all_bess(&self, active_only: bool) -> Vec<&ResourceMapping> {
+        // Each mapping is indexed under its resource and unit name; only
+        // the resource-name entry is returned
         self.mappings
-            .values()
+            .iter()
+            .filter(|(key, m)| **key == m.resource_name)
+            .map(|(_, m)| m)
             .filter(|m| m.unit_name.contains("BESS") || m.unit_name.contains("ESS"))
+            .filter(|m| !active_only || m.is_active())
             .collect()
     }
 
+    /// Keep only resources mapped to one of `settlement_points`, once each
+    /// and sorted by name
+    pub fn filter_by_settlement_points(
+        resources: Vec<ResourceMapping>,
+        settlement_points: &[String],
+    ) -> Vec<ResourceMapping> {
+        let mut resources: Vec<ResourceMapping> = resources
+            .into_iter()
+            .filter(|m| settlement_points.contains(&m.settlement_point))
+            .collect();
+        resources.sort_by(|a, b| a.resource_name.cmp(&b.resource_name));
+        resources.dedup_by(|a, b| a.resource_name == b.resource_name);
+        resources
+    }
+
+    /// Keep only resources whose name matches `pattern`, once each and
+    /// sorted by name
+    pub fn filter_by_resource_regex(resources: Vec<ResourceMapping>, pattern: &Regex) -> Vec<ResourceMapping> {
+        let mut resources: Vec<ResourceMapping> = resources
+            .into_iter()
+            .filter(|m| pattern.is_match(&m.resource_name))
+            .collect();
+        resources.sort_by(|a, b| a.resource_name.cmp(&b.resource_name));
+        resources.dedup_by(|a, b| a.resource_name == b.resource_name);
+        resources
+    }
+
+    /// Number of keys mappings are indexed under
+    pub fn len(&self) -> usize {
+        self.mappings.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.mappings.is_empty()
+    }
+
     /// Add or update a mapping
     pub fn add_mapping(&mut self, mapping: ResourceMapping) {
         self.mappings
@@ -82,9 +204,7 @@ impl SettlementMapper {
 
     /// Load additional battery specifications from a separate file
     pub fn load_battery_specs(&mut self, specs_path: &str) -> Result<()> {
-        let df = CsvReader::from_path(specs_path)?
-            .has_header(true)
-            .finish()?;
+        let df = read_csv(Path::new(specs_path))?;
 
         let resource_names = df.column("resource_name")?.str()?;
         let capacities = df.column("capacity_mw")?.f64()?;
@@ -107,6 +227,39 @@ impl SettlementMapper {
     }
 }
 
+/// Read a CSV with a header row
+fn read_csv(path: &Path) -> Result<DataFrame> {
+    Ok(CsvReadOptions::default()
+        .with_has_header(true)
+        .try_into_reader_with_file_path(Some(path.to_path_buf()))?
+        .finish()?)
+}
+
+/// Size and modification time (ns since the epoch) of `source`, e.g. `"5120 1718000000123456789"`
+fn source_stamp(source: &Path) -> Option<String> {
+    let metadata = std::fs::metadata(source).ok()?;
+    let modified = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+    Some(format!("{} {}", metadata.len(), modified.as_nanos()))
+}
+
+/// Stamp of the CSV a cache was built from: `<cache>.source` beside it
+fn stamp_path(cache: &Path) -> PathBuf {
+    PathBuf::from(format!("{}.source", cache.display()))
+}
+
+/// Whether `cache` was built from `source` as it is now. Comparing the
+/// stamp rather than mtimes catches rewrites within the filesystem's
+/// timestamp granularity and sources replaced by older files.
+fn cache_is_fresh(source: &Path, cache: &Path) -> bool {
+    if !cache.exists() {
+        return false;
+    }
+    match (source_stamp(source), std::fs::read_to_string(stamp_path(cache))) {
+        (Some(current), Ok(stored)) => stored.trim() == current,
+        _ => false,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -123,6 +276,7 @@ mod tests {
             settlement_point: "TEST_NODE_RN".to_string(),
             capacity_mw: Some(100.0),
             duration_hours: Some(2.0),
+            status: None,
         };
 
         mapper.add_mapping(mapping);
@@ -133,7 +287,167 @@ mod tests {
         );
         assert_eq!(mapper.get_settlement_point("BESS1"), Some("TEST_NODE_RN"));
 
-        let bess_list = mapper.get_all_bess();
+        let bess_list = mapper.get_all_bess(false);
         assert_eq!(bess_list.len(), 1);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_filter_by_settlement_points() {
+        let mut mapper = SettlementMapper {
+            mappings: HashMap::new(),
+        };
+        for (name, settlement_point) in [
+            ("NORTH_BESS1", "HB_NORTH"),
+            ("NORTH_BESS2", "HB_NORTH"),
+            ("WEST_BESS1", "LZ_WEST"),
+            ("HOUSTON_BESS1", "HB_HOUSTON"),
+        ] {
+            mapper.add_mapping(ResourceMapping {
+                resource_name: name.to_string(),
+                unit_name: name.to_string(),
+                settlement_point: settlement_point.to_string(),
+                capacity_mw: None,
+                duration_hours: None,
+                status: None,
+            });
+        }
+        let all: Vec<ResourceMapping> = mapper.get_all_bess(false).into_iter().cloned().collect();
+        let names = |resources: Vec<ResourceMapping>| -> Vec<String> {
+            resources.into_iter().map(|m| m.resource_name).collect()
+        };
+
+        let points = vec!["HB_NORTH".to_string(), "LZ_WEST".to_string()];
+        assert_eq!(
+            names(SettlementMapper::filter_by_settlement_points(all.clone(), &points)),
+            vec!["NORTH_BESS1", "NORTH_BESS2", "WEST_BESS1"]
+        );
+
+        // Intersection with a single-resource selection
+        let west = vec![mapper.get_mapping("WEST_BESS1").unwrap().clone()];
+        assert!(SettlementMapper::filter_by_settlement_points(west, &["HB_NORTH".to_string()]).is_empty());
+    }
+
+    #[test]
+    fn test_inactive_resources_excluded_when_filtering() {
+        let mut mapper = SettlementMapper {
+            mappings: HashMap::new(),
+        };
+        for (name, status) in [
+            ("LIVE_BESS", Some("Active")),
+            ("OLD_BESS", Some("Decommissioned")),
+            ("NEW_BESS", Some("not yet online")),
+            ("UNLISTED_BESS", None),
+        ] {
+            mapper.add_mapping(ResourceMapping {
+                resource_name: name.to_string(),
+                unit_name: name.to_string(),
+                settlement_point: "HB_NORTH".to_string(),
+                capacity_mw: None,
+                duration_hours: None,
+                status: status.map(str::to_string),
+            });
+        }
+        let names = |active_only: bool| -> Vec<String> {
+            let mut names: Vec<String> = mapper
+                .get_all_bess(active_only)
+                .into_iter()
+                .map(|m| m.resource_name.clone())
+                .collect();
+            names.sort();
+            names
+        };
+
+        assert_eq!(names(true), vec!["LIVE_BESS", "UNLISTED_BESS"]);
+        assert_eq!(names(false).len(), 4);
+    }
+
+    #[test]
+    fn test_filter_by_resource_regex() {
+        let mut mapper = SettlementMapper {
+            mappings: HashMap::new(),
+        };
+        for name in ["GAMBIT_BESS1", "GAMBIT_BESS2", "NOT_GAMBIT_BESS", "BATCAVE_BESS1"] {
+            mapper.add_mapping(ResourceMapping {
+                resource_name: name.to_string(),
+                unit_name: format!("{}_UNIT", name),
+                settlement_point: "HB_NORTH".to_string(),
+                capacity_mw: None,
+                duration_hours: None,
+                status: None,
+            });
+        }
+        let all: Vec<ResourceMapping> = mapper.get_all_bess(false).into_iter().cloned().collect();
+
+        let gambit = SettlementMapper::filter_by_resource_regex(all, &Regex::new("^GAMBIT").unwrap());
+        let names: Vec<&str> = gambit.iter().map(|m| m.resource_name.as_str()).collect();
+        assert_eq!(names, vec!["GAMBIT_BESS1", "GAMBIT_BESS2"]);
+    }
+
+    #[test]
+    fn test_cache_freshness_follows_source_size_and_mtime() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = dir.path().join("mapping.csv");
+        let cache = dir.path().join("mapping.csv.cache.parquet");
+        std::fs::write(&source, "RESOURCE_NODE\nA_RN\n").unwrap();
+        std::fs::write(&cache, "").unwrap();
+        assert!(!cache_is_fresh(&source, &cache));
+
+        std::fs::write(stamp_path(&cache), source_stamp(&source).unwrap()).unwrap();
+        assert!(cache_is_fresh(&source, &cache));
+
+        // Same size, different mtime
+        let modified = std::fs::metadata(&source).unwrap().modified().unwrap();
+        File::options()
+            .write(true)
+            .open(&source)
+            .unwrap()
+            .set_modified(modified + std::time::Duration::from_secs(1))
+            .unwrap();
+        assert!(!cache_is_fresh(&source, &cache));
+    }
+
+    #[test]
+    fn test_mapping_cache_is_used_until_csv_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let csv_path = dir.path().join("mapping.csv");
+        let csv = csv_path.to_str().unwrap();
+        std::fs::write(&csv_path, "RESOURCE_NODE,UNIT_NAME,UNIT_SUBSTATION,STATUS\nBATCAVE_RN,BATCAVE_BES1,BATCAVE,ACTIVE\n").unwrap();
+        let written = std::fs::metadata(&csv_path).unwrap().modified().unwrap();
+
+        let first = SettlementMapper::from_ercot_files_cached(csv).unwrap();
+        assert_eq!(first.get_settlement_point("BATCAVE_BES1"), Some("BATCAVE_RN"));
+        assert_eq!(first.get_mapping("BATCAVE_BES1").unwrap().status.as_deref(), Some("ACTIVE"));
+        assert!(SettlementMapper::cache_path(csv).exists());
+
+        // While the CSV is unchanged the cache is read instead of the CSV
+        let mut cached = df! {
+            "RESOURCE_NODE" => &["CACHED_RN"],
+            "UNIT_NAME" => &["BATCAVE_BES1"],
+            "UNIT_SUBSTATION" => &["BATCAVE"],
+        }
+        .unwrap();
+        ParquetWriter::new(File::create(SettlementMapper::cache_path(csv)).unwrap())
+            .finish(&mut cached)
+            .unwrap();
+        let second = SettlementMapper::from_ercot_files_cached(csv).unwrap();
+        assert_eq!(second.get_settlement_point("BATCAVE_BES1"), Some("CACHED_RN"));
+
+        // Rewritten within the same mtime tick: the size gives it away
+        let rewrite = |contents: &str, modified: std::time::SystemTime| {
+            std::fs::write(&csv_path, contents).unwrap();
+            File::options().write(true).open(&csv_path).unwrap().set_modified(modified).unwrap();
+        };
+        rewrite("RESOURCE_NODE,UNIT_NAME,UNIT_SUBSTATION\nNEW_RN,BATCAVE_BES1,BATCAVE\n", written);
+        let refreshed = SettlementMapper::from_ercot_files_cached(csv).unwrap();
+        assert_eq!(refreshed.get_settlement_point("BATCAVE_BES1"), Some("NEW_RN"));
+        assert_eq!(refreshed.get_mapping("BATCAVE_BES1").unwrap().status, None);
+
+        // Same size, replaced by a file older than the cache
+        rewrite(
+            "RESOURCE_NODE,UNIT_NAME,UNIT_SUBSTATION\nOLD_RN,BATCAVE_BES1,BATCAVE\n",
+            written - std::time::Duration::from_secs(60),
+        );
+        let replaced = SettlementMapper::from_ercot_files_cached(csv).unwrap();
+        assert_eq!(replaced.get_settlement_point("BATCAVE_BES1"), Some("OLD_RN"));
+    }
+}